@@ -3,6 +3,7 @@
   "program"
   "module"
   "use"
+  "mod"
   "fn"
   "pub"
   "sec"
@@ -51,6 +52,24 @@
   function: (module_path
     (identifier) @function .))
 
+(generate_expression
+  "generate" @function.macro
+  "!" @function.macro
+  function: (module_path
+    (identifier) @function .))
+
+(generate_expression
+  "generate" @function.macro
+  "!" @function.macro
+  function: (module_path
+    (identifier) @function .))
+
+(generate_expression
+  "generate" @function.macro
+  "!" @function.macro
+  function: (module_path
+    (identifier) @function .))
+
 ; Struct and event definitions
 (struct_definition
   name: (identifier) @type)
@@ -98,6 +117,10 @@
 (use_declaration
   (module_path (identifier) @module))
 
+; Program parts
+(mod_declaration
+  name: (identifier) @module)
+
 ; Field access (dotted paths used as expressions)
 ; Field init names
 (field_init
@@ -134,6 +157,8 @@
   "&"
   "^"
   "/%"
+  "&&"
+  "||"
   "="
   "->"
   ".."
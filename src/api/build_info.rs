@@ -0,0 +1,108 @@
+use crate::cost::model::has_cost_model;
+
+/// Identity of the compiler build: version, commit, enabled cargo
+/// features, and the targets it can lower to.
+///
+/// Embedded in `manifest.json` and cost JSON files so artifacts and bug
+/// reports trace back to the exact compiler that produced them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    /// Short commit hash, or `"unknown"` when built outside a git checkout.
+    pub git_hash: &'static str,
+    /// Cargo profile of the compiler itself (`"debug"` or `"release"`).
+    pub profile: &'static str,
+    /// Enabled cargo features, e.g. `["capi"]`.
+    pub features: Vec<&'static str>,
+    pub targets: Vec<TargetSupport>,
+}
+
+/// A code generation target and how much of TIR it lowers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TargetSupport {
+    pub name: &'static str,
+    /// Highest TIR tier the backend lowers (0 structure … 3 recursion).
+    pub max_tier: u8,
+    /// Whether `--costs` uses a dedicated model for this target.
+    pub cost_model: bool,
+}
+
+/// Backends with a stack lowering, and the highest tier each supports.
+const BACKENDS: &[(&str, u8)] = &[("triton", 3)];
+
+/// Describe this compiler build.
+pub fn build_info() -> BuildInfo {
+    let mut features = Vec::new();
+    if cfg!(feature = "capi") {
+        features.push("capi");
+    }
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_hash: option_env!("TRIDENT_GIT_HASH").unwrap_or("unknown"),
+        profile: if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        },
+        features,
+        targets: BACKENDS
+            .iter()
+            .map(|&(name, max_tier)| TargetSupport {
+                name,
+                max_tier,
+                cost_model: has_cost_model(name),
+            })
+            .collect(),
+    }
+}
+
+impl BuildInfo {
+    /// Multi-line report for `trident --version --verbose`.
+    pub fn to_text(&self) -> String {
+        let features = if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(", ")
+        };
+        let mut out = format!("trident {}\n", self.version);
+        out.push_str(&format!("commit:   {}\n", self.git_hash));
+        out.push_str(&format!("profile:  {}\n", self.profile));
+        out.push_str(&format!("features: {}\n", features));
+        out.push_str("targets:\n");
+        for t in &self.targets {
+            out.push_str(&format!(
+                "  {:<10} tiers 0-{}{}\n",
+                t.name,
+                t.max_tier,
+                if t.cost_model { ", cost model" } else { "" }
+            ));
+        }
+        out
+    }
+
+    /// Single-line JSON object, for embedding in other JSON files.
+    pub fn to_json(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s);
+        let features: Vec<String> = self.features.iter().map(|f| quote(f)).collect();
+        let targets: Vec<String> = self
+            .targets
+            .iter()
+            .map(|t| {
+                format!(
+                    "{{ \"name\": {}, \"max_tier\": {}, \"cost_model\": {} }}",
+                    quote(t.name),
+                    t.max_tier,
+                    t.cost_model
+                )
+            })
+            .collect();
+        format!(
+            "{{ \"version\": {}, \"git_hash\": {}, \"profile\": {}, \"features\": [{}], \"targets\": [{}] }}",
+            quote(self.version),
+            quote(self.git_hash),
+            quote(self.profile),
+            features.join(", "),
+            targets.join(", ")
+        )
+    }
+}
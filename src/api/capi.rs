@@ -0,0 +1,205 @@
+//! C ABI for embedding the compiler (`--features capi`).
+//!
+//! Every entry point takes NUL-terminated UTF-8 strings and returns a
+//! JSON document that the caller releases with `trident_string_free`:
+//!
+//! ```text
+//! { "ok": true, "tasm": "..." }                 trident_compile
+//! { "ok": true }                                trident_check
+//! { "ok": true, "cost": { ... } }               trident_analyze_costs
+//! { "ok": false, "diagnostics": [ ... ] }       any failure
+//! ```
+//!
+//! Each diagnostic carries `severity`, `message`, byte `start`/`end`,
+//! 1-based `line`/`column`, `notes`, `help`, and `rendered` (the plain
+//! text the CLI would print). Compile and cost errors are also
+//! rendered to stderr, as in the CLI. Build the shared library with
+//! `cargo rustc --lib --release --features capi --crate-type cdylib`.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+use super::*;
+use crate::diagnostic::{line_column, Severity};
+use crate::runtime::artifact::json_string;
+
+/// Compile `source` to TASM. `target` may be null for the default VM.
+///
+/// # Safety
+///
+/// `source` and `filename` must be valid NUL-terminated strings;
+/// `target` must be one or null.
+#[no_mangle]
+pub unsafe extern "C" fn trident_compile(
+    source: *const c_char,
+    filename: *const c_char,
+    target: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let (source, filename) = (read_str(source, "source")?, read_str(filename, "filename")?);
+        let mut options = CompileOptions::default();
+        if !target.is_null() {
+            options.target_config = TerrainConfig::resolve(read_str(target, "target")?)
+                .map_err(|e| diagnostics_json(&[e], filename, source))?;
+        }
+        compile_with_options(source, filename, &options)
+            .map(|tasm| format!("\"tasm\": {}", json_string(&tasm)))
+            .map_err(|errors| diagnostics_json(&errors, filename, source))
+    })
+}
+
+/// Type-check `source` without generating code.
+///
+/// # Safety
+///
+/// `source` and `filename` must be valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn trident_check(
+    source: *const c_char,
+    filename: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let (source, filename) = (read_str(source, "source")?, read_str(filename, "filename")?);
+        check_silent(source, filename)
+            .map(|()| String::new())
+            .map_err(|errors| diagnostics_json(&errors, filename, source))
+    })
+}
+
+/// Static cost analysis, in the `trident build --costs --json` layout.
+///
+/// # Safety
+///
+/// `source` and `filename` must be valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn trident_analyze_costs(
+    source: *const c_char,
+    filename: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let (source, filename) = (read_str(source, "source")?, read_str(filename, "filename")?);
+        analyze_costs(source, filename)
+            .map(|cost| format!("\"cost\": {}", cost.to_json().trim_end()))
+            .map_err(|errors| diagnostics_json(&errors, filename, source))
+    })
+}
+
+/// Release a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `s` must come from a `trident_*` call and not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn trident_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Library version as a static string; do not free it.
+#[no_mangle]
+pub extern "C" fn trident_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Wrap the body's JSON members (or its diagnostics array) in an
+/// envelope. Panics become an `ok: false` answer instead of unwinding
+/// into C.
+fn respond(body: impl FnOnce() -> Result<String, String>) -> *mut c_char {
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(body))
+        .unwrap_or_else(|_| Err(message_json("internal compiler error")));
+    let json = match result {
+        Ok(members) if members.is_empty() => "{ \"ok\": true }".to_string(),
+        Ok(members) => format!("{{ \"ok\": true, {} }}", members),
+        Err(diagnostics) => format!("{{ \"ok\": false, \"diagnostics\": {} }}", diagnostics),
+    };
+    // JSON escapes control characters, so there is no interior NUL.
+    CString::new(json).unwrap_or_default().into_raw()
+}
+
+unsafe fn read_str<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(message_json(&format!("{} is null", what)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| message_json(&format!("{} is not valid UTF-8", what)))
+}
+
+/// A diagnostics array holding one error without a location.
+fn message_json(message: &str) -> String {
+    diagnostics_json(
+        &[Diagnostic::error(message.to_string(), span::Span::dummy())],
+        "",
+        "",
+    )
+}
+
+fn diagnostics_json(diagnostics: &[Diagnostic], filename: &str, source: &str) -> String {
+    let items: Vec<String> = diagnostics
+        .iter()
+        .map(|d| {
+            let (line, column) = line_column(source, d.span.start as usize);
+            let notes: Vec<String> = d.notes.iter().map(|n| json_string(n)).collect();
+            format!(
+                "{{ \"severity\": {}, \"message\": {}, \"start\": {}, \"end\": {}, \"line\": {}, \"column\": {}, \"notes\": [{}], \"help\": {}, \"rendered\": {} }}",
+                json_string(match d.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                }),
+                json_string(&d.message),
+                d.span.start,
+                d.span.end,
+                line,
+                column,
+                notes.join(", "),
+                d.help.as_deref().map_or("null".to_string(), json_string),
+                json_string(&d.render_to_string(filename, source))
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Entry = unsafe extern "C" fn(*const c_char, *const c_char) -> *mut c_char;
+
+    fn call(f: Entry, source: &str) -> String {
+        let source = CString::new(source).unwrap();
+        let filename = CString::new("main.tri").unwrap();
+        unsafe {
+            let out = f(source.as_ptr(), filename.as_ptr());
+            let json = CStr::from_ptr(out).to_str().unwrap().to_string();
+            trident_string_free(out);
+            json
+        }
+    }
+
+    #[test]
+    fn test_check_reports_diagnostics_as_json() {
+        let valid = "program test\nfn main() {\n    pub_write(pub_read())\n}";
+        assert_eq!(call(trident_check, valid), "{ \"ok\": true }");
+
+        let json = call(
+            trident_check,
+            "program test\nfn main() {\n    pub_write(undefined_var)\n}",
+        );
+        assert!(json.starts_with("{ \"ok\": false, \"diagnostics\": [{ \"severity\": \"error\""));
+        assert!(json.contains("\"line\": 3, \"column\": 15"));
+    }
+
+    #[test]
+    fn test_null_input_is_an_error_not_a_crash() {
+        let filename = CString::new("main.tri").unwrap();
+        let json = unsafe {
+            let out = trident_compile(std::ptr::null(), filename.as_ptr(), std::ptr::null());
+            let json = CStr::from_ptr(out).to_str().unwrap().to_string();
+            trident_string_free(out);
+            json
+        };
+        assert!(json.contains("source is null"));
+    }
+}
@@ -0,0 +1,288 @@
+use std::cell::OnceCell;
+use std::path::PathBuf;
+
+use super::*;
+use crate::pipeline::PreparedProject;
+
+/// A project held in memory for repeated queries.
+///
+/// The free functions (`compile_project_with_options`,
+/// `analyze_costs_project`, ...) each resolve, parse, and type-check the
+/// project from scratch. A `Compiler` does that once, on first use, and
+/// answers every later call from the same modules and exports. Linked
+/// TASM and the cost analysis are cached as well.
+pub struct Compiler {
+    entry_path: PathBuf,
+    options: CompileOptions,
+    project: OnceCell<PreparedProject>,
+    tasm: OnceCell<String>,
+    costs: OnceCell<cost::ProgramCost>,
+}
+
+impl Compiler {
+    /// A compiler for the project rooted at `entry_path`, with default
+    /// options (Triton target, debug profile).
+    pub fn new(entry_path: impl Into<PathBuf>) -> Self {
+        Self {
+            entry_path: entry_path.into(),
+            options: CompileOptions::default(),
+            project: OnceCell::new(),
+            tasm: OnceCell::new(),
+            costs: OnceCell::new(),
+        }
+    }
+
+    /// Replace the compile options. Clears anything already cached.
+    pub fn with_options(mut self, options: CompileOptions) -> Self {
+        self.options = options;
+        self.project = OnceCell::new();
+        self.tasm = OnceCell::new();
+        self.costs = OnceCell::new();
+        self
+    }
+
+    /// Select a target VM, keeping the other options.
+    pub fn with_target(self, target_config: TerrainConfig) -> Self {
+        let options = CompileOptions {
+            target_config,
+            ..self.options.clone()
+        };
+        self.with_options(options)
+    }
+
+    pub fn entry_path(&self) -> &Path {
+        &self.entry_path
+    }
+
+    pub fn options(&self) -> &CompileOptions {
+        &self.options
+    }
+
+    /// Resolve, parse, and type-check the project, once.
+    pub(crate) fn project(&self) -> Result<&PreparedProject, CompileError> {
+        if let Some(project) = self.project.get() {
+            return Ok(project);
+        }
+        let project = PreparedProject::build(&self.entry_path, &self.options)?;
+        Ok(self.project.get_or_init(|| project))
+    }
+
+    /// Names of the project's modules in dependency order, the entry last.
+    pub fn modules(&self) -> Result<Vec<String>, CompileError> {
+        Ok(self
+            .project()?
+            .modules
+            .iter()
+            .map(|pm| pm.file.name.node.clone())
+            .collect())
+    }
+
+    /// Type-check the project (see `check_project`).
+    pub fn check(&self) -> Result<(), CompileError> {
+        self.project().map(|_| ())
+    }
+
+    /// Linked TASM of the project (see `compile_project_with_options`).
+    pub fn compile(&self) -> Result<&str, CompileError> {
+        if let Some(tasm) = self.tasm.get() {
+            return Ok(tasm);
+        }
+        let project = self.project()?;
+        let linked = link_project(project, &self.options)?;
+        if let Some(diag) = mono_budget_error(project, &linked.mono, &self.options.mono_limits) {
+            let errors = vec![diag.0];
+            self.options
+                .sink
+                .report(&errors, &diag.1.file_path.to_string_lossy(), &diag.1.source);
+            return Err(CompileError::Link(errors));
+        }
+        Ok(self.tasm.get_or_init(|| linked.tasm))
+    }
+
+    /// Cost analysis of the entry module (see `analyze_costs_project`).
+    pub fn costs(&self) -> Result<&cost::ProgramCost, CompileError> {
+        if let Some(costs) = self.costs.get() {
+            return Ok(costs);
+        }
+        let project = self.project()?;
+        let Some(file) = project.last_file() else {
+            return Err(CompileError::Io(vec![Diagnostic::error(
+                "no program file found".to_string(),
+                span::Span::dummy(),
+            )]));
+        };
+        let costs = cost::CostAnalyzer::for_config(&self.options.target_config)
+            .with_pure_fns(project.pure_functions())
+            .analyze_file(file);
+        Ok(self.costs.get_or_init(|| costs))
+    }
+
+    /// Cost roll-up of every module, with the functions that exceed the
+    /// target's proving limit on their own (see `module_costs_project`).
+    pub fn module_costs(&self) -> Result<cost::ProjectCost, CompileError> {
+        let project = self.project()?;
+        let config = &self.options.target_config;
+        let mut report = cost::ProjectCost {
+            target: config.name.clone(),
+            max_padded_height: 0,
+            modules: Vec::new(),
+            oversized: Vec::new(),
+        };
+        for pm in &project.modules {
+            let mut analyzer =
+                cost::CostAnalyzer::for_config(config).with_pure_fns(project.pure_functions());
+            let costs = analyzer.analyze_file(&pm.file);
+            let limit = analyzer.max_padded_height();
+            report.max_padded_height = limit;
+            let mut module = cost::ModuleCost {
+                module: pm.file.name.node.clone(),
+                functions: 0,
+                total: cost::TableCost::ZERO,
+                heaviest: None,
+            };
+            let flags = self.options.flags_for(&pm.file.name.node);
+            for item in &pm.file.items {
+                let ast::Item::Fn(func) = &item.node else {
+                    continue;
+                };
+                let active = func
+                    .cfg
+                    .as_ref()
+                    .is_none_or(|flag| flags.contains(&flag.node));
+                if func.is_test || func.body.is_none() || !active {
+                    continue;
+                }
+                let Some(fc) = costs.functions.iter().find(|f| f.name == func.name.node) else {
+                    continue;
+                };
+                module.functions += 1;
+                module.total = module.total.add(&fc.cost);
+                let padded = cost::next_power_of_two(fc.cost.max_height());
+                if module.heaviest.as_ref().is_none_or(|(_, h)| padded > *h) {
+                    module.heaviest = Some((fc.name.clone(), padded));
+                }
+                if padded > limit {
+                    let split_points = analyzer
+                        .split_points(func, limit)
+                        .into_iter()
+                        .map(|(span, rows)| {
+                            let line =
+                                crate::diagnostic::line_column(&pm.source, span.start as usize).0;
+                            (line, rows)
+                        })
+                        .collect();
+                    report.oversized.push(cost::OversizedFunction {
+                        module: module.module.clone(),
+                        function: fc.name.clone(),
+                        padded_height: padded,
+                        split_points,
+                    });
+                }
+            }
+            report.modules.push(module);
+        }
+        Ok(report)
+    }
+
+    /// Markdown documentation (see `generate_docs`).
+    pub fn docs(&self) -> Result<String, CompileError> {
+        Ok(doc::project_docs(self.project()?, &self.options))
+    }
+
+    /// Symbolic verification of every function in every module
+    /// (see `verify_project`).
+    pub fn verify(&self) -> Result<solve::VerificationReport, CompileError> {
+        let mut combined = sym::ConstraintSystem::new();
+        for pm in &self.project()?.modules {
+            for (_, system) in sym::analyze_all(&pm.file) {
+                combined.extend(system);
+            }
+        }
+        Ok(solve::verify(&combined))
+    }
+
+    /// ABI of the entry module (see `abi_project`).
+    pub fn abi(&self) -> Result<crate::runtime::ProgramAbi, CompileError> {
+        let project = self.project()?;
+        let entry = project
+            .program_module()
+            .or_else(|| project.modules.last())
+            .expect("a prepared project has at least one module");
+        let mut abi = crate::runtime::ProgramAbi::from_file(&entry.file);
+        abi.event_channel = create_stack_lowering(&self.options.target_config.name).event_channel();
+        Ok(abi)
+    }
+
+    /// RAM regions of the project (see `ram_layout_project`).
+    pub fn ram_layout(&self) -> Result<&RamLayout, CompileError> {
+        Ok(&self.project()?.ram)
+    }
+
+    /// Function-level source map (see `source_map_project`).
+    pub fn source_map(&self) -> Result<crate::runtime::debug::SourceMap, CompileError> {
+        Ok(crate::runtime::debug::SourceMap::from_modules(
+            &self.project()?.modules,
+            self.options.label_scheme,
+        ))
+    }
+
+    /// Runtime checks by error id (see `assertion_table_project`).
+    pub fn assertion_table(&self) -> Result<crate::runtime::debug::AssertionTable, CompileError> {
+        Ok(crate::runtime::debug::AssertionTable::from_project(
+            self.project()?,
+            &self.options,
+        ))
+    }
+
+    /// The function, struct, constant, event, or generic instance that
+    /// `query` names (`trident view`): `name`, `module.name`, `first<3>`,
+    /// or a function's content hash prefix.
+    pub fn view(&self, query: &str) -> Result<Option<ViewedItem>, CompileError> {
+        Ok(view::view_item(self.project()?, &self.options, query))
+    }
+
+    /// Every item of the project as `module.name`, with the content hash
+    /// of each function.
+    pub fn items(&self) -> Result<Vec<(String, Option<crate::hash::ContentHash>)>, CompileError> {
+        Ok(view::item_index(self.project()?))
+    }
+
+    /// Structural matches of `pattern` in every module (see `grep_project`).
+    pub fn grep(&self, pattern: &ast::search::Pattern) -> Result<Vec<GrepMatch>, CompileError> {
+        let text = |source: &str, span: span::Span| {
+            source
+                .get(span.start as usize..span.end as usize)
+                .unwrap_or_default()
+                .to_string()
+        };
+        let mut matches = Vec::new();
+        for pm in &self.project()?.modules {
+            for m in pattern.find_in_file(&pm.file) {
+                let (line, column) =
+                    crate::diagnostic::line_column(&pm.source, m.span.start as usize);
+                matches.push(GrepMatch {
+                    file: pm.file_path.clone(),
+                    module: pm.file.name.node.clone(),
+                    function: m.function,
+                    line,
+                    column,
+                    text: text(&pm.source, m.span),
+                    bindings: m
+                        .bindings
+                        .into_iter()
+                        .map(|(name, span)| (name, text(&pm.source, span)))
+                        .collect(),
+                });
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Symbol table of every module (see `symbol_table_project`).
+    pub fn symbol_table(&self) -> Result<crate::runtime::debug::SymbolTable, CompileError> {
+        Ok(crate::runtime::debug::SymbolTable::from_modules(
+            &self.project()?.modules,
+            self.options.label_scheme,
+        ))
+    }
+}
@@ -2,17 +2,25 @@
 //!
 //! Produces markdown documentation listing all public functions, structs,
 //! constants, and events with their type signatures and cost annotations.
+//! Each function also gets a verification subsection: the solver's verdict,
+//! cached by the function's content hash, and its declared and synthesized
+//! specifications. When the entry belongs to a trident.toml project, its
+//! description, license, authors and repository head the document.
 
 use std::path::Path;
 
 use crate::ast;
 use crate::ast::display::{format_ast_type, format_const_value, format_fn_signature};
 use crate::ast::FileKind;
+use crate::cache::{self, CachedVerification};
 use crate::cost;
-use crate::diagnostic::Diagnostic;
+use crate::hash::ContentHash;
 use crate::pipeline::PreparedProject;
+use crate::project::{Project, ProjectMetadata};
+use crate::synthesize::{synthesize_specs, SpecKind, SynthesizedSpec};
 use crate::target::TerrainConfig;
-use crate::CompileOptions;
+use crate::{solve, sym};
+use crate::{CompileError, CompileOptions};
 
 /// Generate markdown documentation for a Trident project.
 ///
@@ -22,13 +30,47 @@ use crate::CompileOptions;
 pub(crate) fn generate_docs(
     entry_path: &Path,
     options: &CompileOptions,
-) -> Result<String, Vec<Diagnostic>> {
+) -> Result<String, CompileError> {
     let project = PreparedProject::build(entry_path, options)?;
+    Ok(project_docs(&project, options))
+}
+
+/// Distribution metadata of the trident.toml governing the entry module.
+fn project_metadata(project: &PreparedProject) -> Option<ProjectMetadata> {
+    let entry = project.modules.last()?;
+    let toml = Project::find(entry.file_path.parent()?)?;
+    let metadata = Project::load(&toml).ok()?.metadata;
+    (!metadata.is_empty()).then_some(metadata)
+}
 
+fn metadata_section(metadata: &ProjectMetadata) -> String {
+    let mut out = String::new();
+    if let Some(ref description) = metadata.description {
+        out.push_str(&format!("\n{}\n", description));
+    }
+    let mut fields = Vec::new();
+    if let Some(ref license) = metadata.license {
+        fields.push(format!("- **License:** {}\n", license));
+    }
+    if !metadata.authors.is_empty() {
+        fields.push(format!("- **Authors:** {}\n", metadata.authors.join(", ")));
+    }
+    if let Some(ref repository) = metadata.repository {
+        fields.push(format!("- **Repository:** <{}>\n", repository));
+    }
+    if !fields.is_empty() {
+        out.push('\n');
+        out.push_str(&fields.concat());
+    }
+    out
+}
+
+/// Markdown documentation for an already prepared project.
+pub(crate) fn project_docs(project: &PreparedProject, options: &CompileOptions) -> String {
     // Compute cost analysis per module
     let mut module_costs: Vec<Option<cost::ProgramCost>> = Vec::new();
     for pm in &project.modules {
-        let pc = cost::CostAnalyzer::for_target(&options.target_config.name).analyze_file(&pm.file);
+        let pc = cost::CostAnalyzer::for_config(&options.target_config).analyze_file(&pm.file);
         module_costs.push(Some(pc));
     }
 
@@ -40,12 +82,18 @@ pub(crate) fn generate_docs(
 
     let mut doc = String::new();
     doc.push_str(&format!("# {}\n", program_name));
+    if let Some(metadata) = project_metadata(project) {
+        doc.push_str(&metadata_section(&metadata));
+    }
 
     // --- Functions ---
     let mut fn_entries: Vec<String> = Vec::new();
     for (i, pm) in project.modules.iter().enumerate() {
         let module_name = &pm.file.name.node;
         let costs = module_costs[i].as_ref();
+        let flags = options.flags_for(module_name);
+        let fn_hashes = crate::hash::hash_file(&pm.file);
+        let specs = synthesize_specs(&pm.file);
         for item in &pm.file.items {
             if let ast::Item::Fn(func) = &item.node {
                 // Skip test functions, intrinsic-only, and non-pub functions in modules
@@ -57,7 +105,7 @@ pub(crate) fn generate_docs(
                 }
                 // Skip cfg-excluded items
                 if let Some(ref cfg) = func.cfg {
-                    if !options.cfg_flags.contains(&cfg.node) {
+                    if !flags.contains(&cfg.node) {
                         continue;
                     }
                 }
@@ -81,6 +129,12 @@ pub(crate) fn generate_docs(
                     ));
                 }
                 entry.push_str(&format!("**Module:** {}\n", module_name));
+                if func.body.is_some() && func.intrinsic.is_none() {
+                    let status = fn_hashes
+                        .get(&func.name.node)
+                        .map(|hash| verification(&pm.file, &func.name.node, hash));
+                    entry.push_str(&verification_section(func, status.as_ref(), &specs));
+                }
                 fn_entries.push(entry);
             }
         }
@@ -97,13 +151,14 @@ pub(crate) fn generate_docs(
     // --- Structs ---
     let mut struct_entries: Vec<String> = Vec::new();
     for pm in project.modules.iter() {
+        let flags = options.flags_for(&pm.file.name.node);
         for item in &pm.file.items {
             if let ast::Item::Struct(sdef) = &item.node {
                 if pm.file.kind == FileKind::Module && !sdef.is_pub {
                     continue;
                 }
                 if let Some(ref cfg) = sdef.cfg {
-                    if !options.cfg_flags.contains(&cfg.node) {
+                    if !flags.contains(&cfg.node) {
                         continue;
                     }
                 }
@@ -138,13 +193,14 @@ pub(crate) fn generate_docs(
     // --- Constants ---
     let mut const_entries: Vec<(String, String, String)> = Vec::new(); // (name, type, value)
     for pm in project.modules.iter() {
+        let flags = options.flags_for(&pm.file.name.node);
         for item in &pm.file.items {
             if let ast::Item::Const(cdef) = &item.node {
                 if pm.file.kind == FileKind::Module && !cdef.is_pub {
                     continue;
                 }
                 if let Some(ref cfg) = cdef.cfg {
-                    if !options.cfg_flags.contains(&cfg.node) {
+                    if !flags.contains(&cfg.node) {
                         continue;
                     }
                 }
@@ -168,10 +224,11 @@ pub(crate) fn generate_docs(
     // --- Events ---
     let mut event_entries: Vec<String> = Vec::new();
     for pm in project.modules.iter() {
+        let flags = options.flags_for(&pm.file.name.node);
         for item in &pm.file.items {
             if let ast::Item::Event(edef) = &item.node {
                 if let Some(ref cfg) = edef.cfg {
-                    if !options.cfg_flags.contains(&cfg.node) {
+                    if !flags.contains(&cfg.node) {
                         continue;
                     }
                 }
@@ -232,10 +289,95 @@ pub(crate) fn generate_docs(
     doc.push_str(&format!("| U32 | {} |\n", total_cost.get(2)));
     doc.push_str(&format!("| Padded | {} |\n", padded_height));
 
-    Ok(doc)
+    doc
 }
 
 /// Compute the width in field elements for an AST type (best-effort).
+/// The verdict for one function: from the cache when its content hash
+/// was verified before, otherwise computed and cached.
+fn verification(file: &ast::File, name: &str, hash: &ContentHash) -> CachedVerification {
+    if let Some(cached) = cache::lookup_verification(hash) {
+        return cached;
+    }
+    let system = sym::analyze_function(file, name);
+    let report = solve::verify(&system);
+    let verdict = if !report.is_safe() {
+        "unsafe".to_string()
+    } else if system.constraints.is_empty() {
+        "safe (no assertions)".to_string()
+    } else if !report.loop_limits.is_empty() {
+        format!(
+            "unknown ({} loop(s) not fully unrolled)",
+            report.loop_limits.len()
+        )
+    } else if report.witness_required > 0 {
+        format!(
+            "unknown ({} constraint(s) depend on hash outputs)",
+            report.witness_required
+        )
+    } else {
+        "safe".to_string()
+    };
+    let result = CachedVerification {
+        is_safe: report.is_safe(),
+        constraints: system.constraints.len(),
+        variables: system.variables.len() as u32,
+        verdict,
+        timestamp: cache::timestamp(),
+    };
+    // A read-only cache only costs the next run a re-verification.
+    let _ = cache::store_verification(hash, &result);
+    result
+}
+
+/// The "Verification" subsection of a function entry.
+fn verification_section(
+    func: &ast::FnDef,
+    status: Option<&CachedVerification>,
+    specs: &[SynthesizedSpec],
+) -> String {
+    let mut out = String::from("#### Verification\n");
+    if let Some(status) = status {
+        out.push_str(&format!(
+            "- **Status:** {} ({} constraints)\n",
+            status.verdict, status.constraints
+        ));
+    }
+    let declared = |items: &[crate::span::Spanned<String>]| -> Vec<String> {
+        items
+            .iter()
+            .map(|p| format!("`{}` (declared)", p.node))
+            .collect()
+    };
+    let mut preconditions = declared(&func.requires);
+    let mut postconditions = declared(&func.ensures);
+    let mut invariants = Vec::new();
+    for spec in specs.iter().filter(|s| s.function == func.name.node) {
+        let (list, scope) = match &spec.kind {
+            SpecKind::Precondition => (&mut preconditions, String::new()),
+            SpecKind::Postcondition => (&mut postconditions, String::new()),
+            SpecKind::LoopInvariant { loop_var } => {
+                (&mut invariants, format!("loop over {}, ", loop_var))
+            }
+            SpecKind::Assertion => continue,
+        };
+        list.push(format!(
+            "`{}` ({}inferred, {}% confidence)",
+            spec.expression, scope, spec.confidence
+        ));
+    }
+    for (label, list) in [
+        ("Preconditions", preconditions),
+        ("Postconditions", postconditions),
+        ("Invariants", invariants),
+    ] {
+        if !list.is_empty() {
+            out.push_str(&format!("- **{}:** {}\n", label, list.join("; ")));
+        }
+    }
+    out
+}
+
 pub(crate) fn ast_type_width(ty: &ast::Type, config: &TerrainConfig) -> u32 {
     match ty {
         ast::Type::Field | ast::Type::Bool | ast::Type::U32 => 1,
@@ -247,6 +389,12 @@ pub(crate) fn ast_type_width(ty: &ast::Type, config: &TerrainConfig) -> u32 {
             inner_w * n
         }
         ast::Type::Tuple(elems) => elems.iter().map(|e| ast_type_width(e, config)).sum(),
+        ast::Type::NamedTuple(components) => components
+            .iter()
+            .map(|(_, e)| ast_type_width(e, config))
+            .sum(),
+        ast::Type::Option(inner) => 1 + ast_type_width(inner, config),
+        ast::Type::Bytes(size) => size.as_literal().unwrap_or(1) as u32,
         ast::Type::Named(_) => 1, // unknown, default to 1
     }
 }
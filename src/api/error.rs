@@ -0,0 +1,120 @@
+//! Typed errors of the library API.
+//!
+//! Every entry point fails with a `CompileError`: the diagnostics, tagged
+//! with the stage that produced them, so embedders can tell a missing file
+//! from a syntax error or a type error without reading messages. For code
+//! written against the earlier `Vec<Diagnostic>` errors, a `CompileError`
+//! derefs to its diagnostics, iterates over them, and converts into a
+//! `Vec<Diagnostic>` (so `?` still works in functions returning one).
+
+use std::fmt;
+use std::ops::Deref;
+
+use crate::diagnostic::Diagnostic;
+
+/// Why compilation stopped, with the diagnostics explaining it.
+#[derive(Clone, Debug)]
+pub enum CompileError {
+    /// A source file or module could not be read, found, or imported.
+    Io(Vec<Diagnostic>),
+    /// Lexing, parsing, or derive expansion failed.
+    Parse(Vec<Diagnostic>),
+    /// Type checking, or a later semantic check such as `sec ram`
+    /// placement, failed.
+    Type(Vec<Diagnostic>),
+    /// Building IR or lowering to assembly failed (stack invariant
+    /// violations under `--validate-codegen`).
+    Emit(Vec<Diagnostic>),
+    /// Linking modules failed, or the linked program is over its budget.
+    Link(Vec<Diagnostic>),
+    /// The verifier could not analyze the program. Findings (an unsafe
+    /// verdict) are part of a `VerificationReport`, not errors.
+    Verify(Vec<Diagnostic>),
+}
+
+impl CompileError {
+    /// The stage, as a lowercase word (`io`, `parse`, `type`, ...).
+    pub fn stage(&self) -> &'static str {
+        match self {
+            CompileError::Io(_) => "io",
+            CompileError::Parse(_) => "parse",
+            CompileError::Type(_) => "type",
+            CompileError::Emit(_) => "emit",
+            CompileError::Link(_) => "link",
+            CompileError::Verify(_) => "verify",
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            CompileError::Io(d)
+            | CompileError::Parse(d)
+            | CompileError::Type(d)
+            | CompileError::Emit(d)
+            | CompileError::Link(d)
+            | CompileError::Verify(d) => d,
+        }
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        match self {
+            CompileError::Io(d)
+            | CompileError::Parse(d)
+            | CompileError::Type(d)
+            | CompileError::Emit(d)
+            | CompileError::Link(d)
+            | CompileError::Verify(d) => d,
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let diagnostics = self.diagnostics();
+        match diagnostics.first() {
+            Some(first) if diagnostics.len() > 1 => write!(
+                f,
+                "{} error: {} (and {} more)",
+                self.stage(),
+                first.message,
+                diagnostics.len() - 1
+            ),
+            Some(first) => write!(f, "{} error: {}", self.stage(), first.message),
+            None => write!(f, "{} error", self.stage()),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl Deref for CompileError {
+    type Target = [Diagnostic];
+
+    fn deref(&self) -> &[Diagnostic] {
+        self.diagnostics()
+    }
+}
+
+impl From<CompileError> for Vec<Diagnostic> {
+    fn from(error: CompileError) -> Self {
+        error.into_diagnostics()
+    }
+}
+
+impl IntoIterator for CompileError {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_diagnostics().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a CompileError {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics().iter()
+    }
+}
@@ -0,0 +1,196 @@
+//! Fix engine behind `trident fix`: gathers the machine-applicable
+//! suggestions attached to diagnostics and splices them into source.
+
+use std::ops::Range;
+
+use super::*;
+use crate::diagnostic::Suggestion;
+
+/// Unchanged lines shown around each hunk of a `fix_diff`.
+const DIFF_CONTEXT: usize = 3;
+
+/// Diagnostics in one file that carry suggestions: unused imports, loop
+/// bounds (H0004), and `#[cfg(...)]` attributes not written the way
+/// `trident fmt` writes them. Ordered by position; nothing is rendered.
+/// Parse errors are returned as `Err`.
+pub fn collect_fixes(source: &str, filename: &str) -> Result<Vec<Diagnostic>, CompileError> {
+    let file = crate::parse_source_silent(source, filename).map_err(CompileError::Parse)?;
+    let checked = match TypeChecker::new().check_file(&file) {
+        Ok(exports) => exports.warnings,
+        // Unresolved imports fail a single-file check; their
+        // suggestions are still sound.
+        Err(diagnostics) => diagnostics,
+    };
+    let mut fixes: Vec<Diagnostic> = checked
+        .into_iter()
+        .filter(|d| !d.suggestions().is_empty())
+        .collect();
+    fixes.extend(
+        cost::CostAnalyzer::default()
+            .analyze_file(&file)
+            .loop_bound_fixes(source),
+    );
+    fixes.extend(cfg_attr_fixes(&file, source));
+    fixes.sort_by_key(|d| d.span.start);
+    Ok(fixes)
+}
+
+/// `#[cfg( debug )]` and similar: rewrite to `#[cfg(debug)]`.
+fn cfg_attr_fixes(file: &ast::File, source: &str) -> Vec<Diagnostic> {
+    file.items
+        .iter()
+        .filter_map(|item| {
+            let cfg = match &item.node {
+                ast::Item::Const(c) => c.cfg.as_ref(),
+                ast::Item::Struct(s) => s.cfg.as_ref(),
+                ast::Item::Event(e) => e.cfg.as_ref(),
+                ast::Item::Fn(f) => f.cfg.as_ref(),
+            }?;
+            let written = source.get(cfg.span.start as usize..cfg.span.end as usize)?;
+            let canonical = format!("#[cfg({})]", cfg.node);
+            (written != canonical).then(|| {
+                Diagnostic::warning(
+                    format!("cfg attribute `{}` is not formatted", written),
+                    cfg.span,
+                )
+                .with_suggestion(Suggestion {
+                    message: format!("write `{}`", canonical),
+                    span: cfg.span,
+                    replacement: canonical,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Prepare `suggestions` for `apply_fixes` and `fix_diff`: sort them by
+/// position, drop any that overlap an earlier one, and widen deletions
+/// that would leave a blank line to the whole line.
+pub fn plan_fixes(source: &str, mut suggestions: Vec<Suggestion>) -> Vec<Suggestion> {
+    for s in &mut suggestions {
+        if s.replacement.is_empty() {
+            let (start, end) = (s.span.start as usize, s.span.end as usize);
+            let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = source[end..]
+                .find('\n')
+                .map_or(source.len(), |i| end + i + 1);
+            if source[line_start..start].trim().is_empty()
+                && source[end..line_end].trim().is_empty()
+            {
+                s.span.start = line_start as u32;
+                s.span.end = line_end as u32;
+            }
+        }
+    }
+    suggestions.sort_by_key(|s| (s.span.start, s.span.end));
+    let mut planned: Vec<Suggestion> = Vec::new();
+    for s in suggestions {
+        if planned
+            .last()
+            .is_none_or(|prev| s.span.start >= prev.span.end)
+        {
+            planned.push(s);
+        }
+    }
+    planned
+}
+
+/// Apply suggestions prepared by `plan_fixes`.
+pub fn apply_fixes(source: &str, fixes: &[Suggestion]) -> String {
+    let all: Vec<&Suggestion> = fixes.iter().collect();
+    splice(source, 0..source.len(), &all)
+}
+
+/// Unified diff of what `apply_fixes` would change, for `--dry-run`.
+pub fn fix_diff(filename: &str, source: &str, fixes: &[Suggestion]) -> String {
+    let starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .filter(|&i| i < source.len())
+        .collect();
+    let line_count = starts.len();
+    let line_of = |offset: usize| starts.partition_point(|&s| s <= offset).saturating_sub(1);
+    let line_start = |line: usize| starts.get(line).copied().unwrap_or(source.len());
+    let old_lines = |lines: Range<usize>| -> Vec<&str> {
+        source[line_start(lines.start)..line_start(lines.end)]
+            .lines()
+            .collect()
+    };
+
+    // Changed regions: old line range and the text that replaces it.
+    let mut regions: Vec<(Range<usize>, Vec<&Suggestion>)> = Vec::new();
+    for fix in fixes {
+        let (start, end) = (fix.span.start as usize, fix.span.end as usize);
+        let first = line_of(start);
+        let last = if end > start { line_of(end - 1) } else { first };
+        match regions.last_mut() {
+            Some((lines, group)) if first < lines.end => {
+                lines.end = lines.end.max(last + 1);
+                group.push(fix);
+            }
+            _ => regions.push((first..last + 1, vec![fix])),
+        }
+    }
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", filename, filename);
+    let mut delta: isize = 0;
+    let mut i = 0;
+    while i < regions.len() {
+        // Regions whose context overlaps share a hunk.
+        let mut j = i + 1;
+        while j < regions.len() && regions[j].0.start <= regions[j - 1].0.end + 2 * DIFF_CONTEXT {
+            j += 1;
+        }
+        let hunk_start = regions[i].0.start.saturating_sub(DIFF_CONTEXT);
+        let hunk_end = (regions[j - 1].0.end + DIFF_CONTEXT).min(line_count);
+
+        let mut body = String::new();
+        let mut new_count = 0;
+        let mut line = hunk_start;
+        for (lines, group) in &regions[i..j] {
+            for context in old_lines(line..lines.start) {
+                body.push_str(&format!(" {}\n", context));
+                new_count += 1;
+            }
+            for old in old_lines(lines.clone()) {
+                body.push_str(&format!("-{}\n", old));
+            }
+            let range = line_start(lines.start)..line_start(lines.end);
+            for new in splice(source, range, group).lines() {
+                body.push_str(&format!("+{}\n", new));
+                new_count += 1;
+            }
+            line = lines.end;
+        }
+        for context in old_lines(line..hunk_end) {
+            body.push_str(&format!(" {}\n", context));
+            new_count += 1;
+        }
+
+        let old_count = hunk_end - hunk_start;
+        let new_start = (hunk_start as isize + delta) as usize;
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            hunk_start + usize::from(old_count > 0),
+            old_count,
+            new_start + usize::from(new_count > 0),
+            new_count
+        ));
+        out.push_str(&body);
+        delta += new_count as isize - old_count as isize;
+        i = j;
+    }
+    out
+}
+
+/// `source[range]` with `fixes` (sorted, disjoint, inside `range`) applied.
+fn splice(source: &str, range: Range<usize>, fixes: &[&Suggestion]) -> String {
+    let mut out = String::with_capacity(range.len());
+    let mut pos = range.start;
+    for fix in fixes {
+        out.push_str(&source[pos..fix.span.start as usize]);
+        out.push_str(&fix.replacement);
+        pos = fix.span.end as usize;
+    }
+    out.push_str(&source[pos..range.end]);
+    out
+}
@@ -1,16 +1,21 @@
 pub(crate) use std::collections::{BTreeMap, BTreeSet};
 pub(crate) use std::path::Path;
+pub(crate) use std::sync::Arc;
 
 pub(crate) use crate::ast::{self, FileKind};
 pub(crate) use crate::cost;
-pub(crate) use crate::diagnostic::{render_diagnostics, Diagnostic};
+pub(crate) use crate::diagnostic::{render_diagnostics, Diagnostic, DiagnosticSink};
+pub(crate) use crate::project::MonoLimits;
 pub(crate) use crate::resolve::resolve_modules;
 pub(crate) use crate::span;
 pub(crate) use crate::target::TerrainConfig;
 pub(crate) use crate::tir::builder::TIRBuilder;
-pub(crate) use crate::tir::linker::{link, ModuleTasm};
+pub(crate) use crate::tir::linker::{link, link_reachable, LinkedProgram, ModuleTasm};
 pub(crate) use crate::tir::lower::create_stack_lowering;
+pub(crate) use crate::tir::optimize::constants::ConstPool;
 pub(crate) use crate::tir::optimize::optimize as optimize_tir;
+pub(crate) use crate::tir::ram::{data_segments, RamLayout};
+pub(crate) use crate::tir::{LabelScheme, MonoReport};
 pub(crate) use crate::typecheck::{ModuleExports, TypeChecker};
 pub(crate) use crate::{format, lexer, parser, project, solve, sym};
 
@@ -28,6 +33,21 @@ pub struct CompileOptions {
     pub target_config: TerrainConfig,
     /// Additional module search directories (from locked dependencies).
     pub dep_dirs: Vec<std::path::PathBuf>,
+    /// Modules behind a disabled feature: module → feature that enables it.
+    pub gated_modules: BTreeMap<String, String>,
+    /// Extra cfg flags per module or namespace (`[cfg.*]` in trident.toml).
+    pub module_cfg: BTreeMap<String, BTreeSet<String>>,
+    /// Monomorphization budget checked when a project is linked.
+    pub mono_limits: MonoLimits,
+    /// How linked labels encode module paths (`labels` in trident.toml).
+    pub label_scheme: LabelScheme,
+    /// Fail on stack invariant violations found while building IR
+    /// (`--validate-codegen`). Debug builds of the compiler check the
+    /// invariants regardless and report violations as warnings.
+    pub validate_codegen: bool,
+    /// Where diagnostics are reported while compiling; stderr by default.
+    /// Errors are also returned either way.
+    pub sink: Arc<dyn DiagnosticSink>,
 }
 
 impl Default for CompileOptions {
@@ -37,6 +57,12 @@ impl Default for CompileOptions {
             cfg_flags: BTreeSet::from(["debug".to_string()]),
             target_config: TerrainConfig::triton(),
             dep_dirs: Vec::new(),
+            gated_modules: BTreeMap::new(),
+            module_cfg: BTreeMap::new(),
+            mono_limits: MonoLimits::default(),
+            label_scheme: LabelScheme::default(),
+            validate_codegen: false,
+            sink: Arc::new(crate::diagnostic::StderrSink::default()),
         }
     }
 }
@@ -49,6 +75,12 @@ impl CompileOptions {
             cfg_flags: BTreeSet::from([profile.to_string()]),
             target_config: TerrainConfig::triton(),
             dep_dirs: Vec::new(),
+            gated_modules: BTreeMap::new(),
+            module_cfg: BTreeMap::new(),
+            mono_limits: MonoLimits::default(),
+            label_scheme: LabelScheme::default(),
+            validate_codegen: false,
+            sink: Arc::new(crate::diagnostic::StderrSink::default()),
         }
     }
 
@@ -56,10 +88,37 @@ impl CompileOptions {
     pub fn for_target(target: &str) -> Self {
         Self::for_profile(target)
     }
+
+    /// Report diagnostics to `sink` instead of stderr.
+    pub fn with_sink(mut self, sink: Arc<dyn DiagnosticSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    /// Report nothing; diagnostics come back only in the returned errors.
+    pub fn silent(self) -> Self {
+        self.with_sink(Arc::new(crate::diagnostic::SilentSink))
+    }
+
+    /// Cfg flags active in `module`: the profile's flags plus those
+    /// `module_cfg` gives the module and every namespace above it
+    /// (`[cfg.std]` covers `std.crypto.hash`).
+    pub fn flags_for(&self, module: &str) -> BTreeSet<String> {
+        let mut flags = self.cfg_flags.clone();
+        for (name, extra) in &self.module_cfg {
+            let covers = module
+                .strip_prefix(name.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'));
+            if covers {
+                flags.extend(extra.iter().cloned());
+            }
+        }
+        flags
+    }
 }
 
 /// Compile a single Trident source string to TASM.
-pub fn compile(source: &str, filename: &str) -> Result<String, Vec<Diagnostic>> {
+pub fn compile(source: &str, filename: &str) -> Result<String, CompileError> {
     compile_with_options(source, filename, &CompileOptions::default())
 }
 
@@ -68,95 +127,315 @@ pub fn compile_with_options(
     source: &str,
     filename: &str,
     options: &CompileOptions,
-) -> Result<String, Vec<Diagnostic>> {
-    let file = crate::parse_source(source, filename)?;
+) -> Result<String, CompileError> {
+    compile_source(source, filename, options)
+}
+
+/// Compile a source string.
+fn compile_source(
+    source: &str,
+    filename: &str,
+    options: &CompileOptions,
+) -> Result<String, CompileError> {
+    let file = crate::parse_source_with(source, filename, options.sink.as_ref())
+        .map_err(CompileError::Parse)?;
 
     // Type check
     let exports = match TypeChecker::with_target(options.target_config.clone())
-        .with_cfg_flags(options.cfg_flags.clone())
+        .with_cfg_flags(options.flags_for(&file.name.node))
         .check_file(&file)
     {
         Ok(exports) => exports,
         Err(errors) => {
-            render_diagnostics(&errors, filename, source);
-            return Err(errors);
+            options.sink.report(&errors, filename, source);
+            return Err(CompileError::Type(errors));
         }
     };
+    let mut ram = RamLayout::new(&options.target_config);
+    ram.reserve_data(&data_segments(
+        exports.pure_fns.tables(),
+        &options.target_config,
+    ));
+    let mut errors = ram.reserve_sec_ram(&file, &options.target_config);
+    if errors.is_empty() {
+        errors =
+            ram.check_static_accesses(&file, &BTreeMap::new(), &options.flags_for(&file.name.node));
+    }
+    if !errors.is_empty() {
+        options.sink.report(&errors, filename, source);
+        return Err(CompileError::Type(errors));
+    }
 
     // Build IR, optimize, and lower to target assembly
-    let ir = TIRBuilder::new(options.target_config.clone())
-        .with_cfg_flags(options.cfg_flags.clone())
+    let (ir, violations) = TIRBuilder::new(options.target_config.clone())
+        .with_cfg_flags(options.flags_for(&file.name.node))
         .with_mono_instances(exports.mono_instances)
         .with_call_resolutions(exports.call_resolutions)
-        .build_file(&file);
-    let ir = optimize_tir(ir);
+        .with_validation(validates_codegen(options))
+        .build_file_checked(&file)
+        .map_err(|errors| {
+            options.sink.report(&errors, filename, source);
+            CompileError::Emit(errors)
+        })?;
+    check_codegen(violations, options, filename, source)?;
+    let ir = optimize_tir(ConstPool::new(&options.target_config).apply(ir));
     let lowering = create_stack_lowering(&options.target_config.name);
     let tasm = lowering.lower(&ir).join("\n");
     Ok(tasm)
 }
 
 /// Compile a multi-module project from an entry point path.
-pub fn compile_project(entry_path: &Path) -> Result<String, Vec<Diagnostic>> {
+pub fn compile_project(entry_path: &Path) -> Result<String, CompileError> {
     compile_project_with_options(entry_path, &CompileOptions::default())
 }
 
 /// Compile a multi-module project with options.
+///
+/// Fails when the generic instances exceed `options.mono_limits`.
 pub fn compile_project_with_options(
     entry_path: &Path,
     options: &CompileOptions,
-) -> Result<String, Vec<Diagnostic>> {
-    use crate::pipeline::PreparedProject;
+) -> Result<String, CompileError> {
+    let compiler = Compiler::new(entry_path).with_options(options.clone());
+    compiler.compile().map(str::to_string)
+}
 
-    let project = PreparedProject::build(entry_path, options)?;
+/// List the generic instances of a linked project (`trident build --mono-report`).
+/// Does not enforce the monomorphization budget.
+pub fn mono_report_project(
+    entry_path: &Path,
+    options: &CompileOptions,
+) -> Result<MonoReport, CompileError> {
+    let project = crate::pipeline::PreparedProject::build(entry_path, options)?;
+    Ok(link_project(&project, options)?.mono)
+}
 
+/// Check a linked program against the budget. The error points at the
+/// generic function with the most instances.
+fn mono_budget_error<'p>(
+    project: &'p crate::pipeline::PreparedProject,
+    report: &MonoReport,
+    limits: &MonoLimits,
+) -> Option<(Diagnostic, &'p crate::pipeline::ParsedModule)> {
+    let (count, instructions) = (report.instance_count(), report.instruction_count());
+    let message = if count > limits.mono_instances {
+        format!(
+            "program has {} generic instances, over the limit of {}",
+            count, limits.mono_instances
+        )
+    } else if instructions > limits.mono_instructions {
+        format!(
+            "generic instances emit {} instructions, over the limit of {}",
+            instructions, limits.mono_instructions
+        )
+    } else {
+        return None;
+    };
+
+    let mut per_fn: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for e in report.kept() {
+        let name = e
+            .label
+            .rsplit_once("__N")
+            .map_or(e.label.as_str(), |(n, _)| n);
+        *per_fn.entry((e.module.as_str(), name)).or_default() += 1;
+    }
+    let ((module, name), n) = per_fn.into_iter().max_by_key(|(_, n)| *n)?;
+    let pm = project
+        .modules
+        .iter()
+        .find(|pm| pm.file.name.node == module)
+        .or_else(|| project.modules.last())?;
+    let span = pm
+        .file
+        .items
+        .iter()
+        .find_map(|item| match &item.node {
+            ast::Item::Fn(f) if f.name.node == name => Some(f.name.span),
+            _ => None,
+        })
+        .unwrap_or(pm.file.name.span);
+    let mut diag = Diagnostic::error(message, span)
+        .with_note(format!("'{}' alone has {} instances", name, n))
+        .with_help(
+            "raise `mono_instances` / `mono_instructions` under [limits] in trident.toml, \
+             or list the instances with `trident build --mono-report`"
+                .to_string(),
+        );
+    let first_site = report
+        .kept()
+        .filter(|e| e.module == module && e.label.rsplit_once("__N").map(|(n, _)| n) == Some(name))
+        .find_map(|e| e.instantiated_at.clone());
+    if let Some(site) = first_site {
+        diag = diag.with_instantiation(site);
+    }
+    Some((diag, pm))
+}
+
+/// Whether to check the stack invariants while building IR.
+fn validates_codegen(options: &CompileOptions) -> bool {
+    options.validate_codegen || cfg!(debug_assertions)
+}
+
+/// Report stack invariant violations: errors under `--validate-codegen`,
+/// warnings otherwise.
+fn check_codegen(
+    mut violations: Vec<Diagnostic>,
+    options: &CompileOptions,
+    filename: &str,
+    source: &str,
+) -> Result<(), CompileError> {
+    if violations.is_empty() {
+        return Ok(());
+    }
+    if !options.validate_codegen {
+        for diag in &mut violations {
+            diag.severity = crate::diagnostic::Severity::Warning;
+        }
+    }
+    options.sink.report(&violations, filename, source);
+    if options.validate_codegen {
+        return Err(CompileError::Emit(violations));
+    }
+    Ok(())
+}
+
+/// Emit TASM for every module of a prepared project and link it.
+///
+/// Fails on stack invariant violations under `--validate-codegen`, and
+/// when a `#[no_spill]` function would spill.
+fn link_project(
+    project: &crate::pipeline::PreparedProject,
+    options: &CompileOptions,
+) -> Result<LinkedProgram, CompileError> {
+    let modules = emit_modules(project, options, false)?;
+    let mut linked = link(modules, options.label_scheme);
+    for entry in &mut linked.mono.instances {
+        entry.instantiated_at = project.instantiation_site(&entry.module, &entry.label);
+    }
+    Ok(linked)
+}
+
+/// Emit TASM for every module of a prepared project, unlinked. With
+/// `tests`, the `#[test]` functions are emitted too and programs get no
+/// entry (see `TIRBuilder::with_tests`).
+fn emit_modules(
+    project: &crate::pipeline::PreparedProject,
+    options: &CompileOptions,
+    tests: bool,
+) -> Result<Vec<ModuleTasm>, CompileError> {
+    let mut emit = module_emitter(project, options, tests);
+    (0..project.modules.len()).map(&mut emit).collect()
+}
+
+/// A function that emits the TASM of the `i`th module of `project`.
+/// Modules must be emitted in order: pooled constants share one
+/// project-wide pool, filled as each module is emitted.
+fn module_emitter<'a>(
+    project: &'a crate::pipeline::PreparedProject,
+    options: &'a CompileOptions,
+    tests: bool,
+) -> impl FnMut(usize) -> Result<ModuleTasm, CompileError> + 'a {
     let intrinsic_map = project.intrinsic_map();
-    let module_aliases = project.module_aliases();
     let external_constants = project.external_constants();
+    let struct_defs = project.struct_defs();
+    let external_returns = project.external_returns();
+    let pure_fns = project.pure_functions();
+    let assertions = crate::runtime::debug::AssertionTable::from_project(project, options);
 
-    // Emit TASM for each module
-    let mut tasm_modules = Vec::new();
-    for (i, pm) in project.modules.iter().enumerate() {
+    let mut pool = ConstPool::new(&options.target_config);
+    move |i| {
+        let pm = &project.modules[i];
         let is_program = pm.file.kind == FileKind::Program;
-        let mono = project
-            .exports
-            .get(i)
-            .map(|e| e.mono_instances.clone())
-            .unwrap_or_default();
+        let mono = project.mono_instances(i);
         let call_res = project
             .exports
             .get(i)
             .map(|e| e.call_resolutions.clone())
             .unwrap_or_default();
-        let ir = TIRBuilder::new(options.target_config.clone())
-            .with_cfg_flags(options.cfg_flags.clone())
+        let mut builder = TIRBuilder::new(options.target_config.clone())
+            .with_cfg_flags(options.flags_for(&pm.file.name.node))
             .with_intrinsics(intrinsic_map.clone())
-            .with_module_aliases(module_aliases.clone())
+            .with_module_aliases(project.module_aliases_for(i))
             .with_constants(external_constants.clone())
+            .with_struct_types(struct_defs.clone())
+            .with_external_returns(external_returns.clone())
+            .with_pure_fns(pure_fns.clone())
             .with_mono_instances(mono)
             .with_call_resolutions(call_res)
-            .build_file(&pm.file);
-        let ir = optimize_tir(ir);
+            .with_assertion_ids(assertions.ids_in(&pm.file_path));
+        if tests {
+            builder = builder.with_tests();
+        }
+        let filename = pm.file_path.to_string_lossy();
+        let (ir, violations) = builder
+            .with_validation(validates_codegen(options))
+            .build_file_checked(&pm.file)
+            .map_err(|errors| {
+                options.sink.report(&errors, &filename, &pm.source);
+                CompileError::Emit(errors)
+            })?;
+        check_codegen(violations, options, &filename, &pm.source)?;
+        let ir = optimize_tir(pool.apply(ir));
+        let fn_labels: Vec<String> = ir
+            .iter()
+            .filter_map(|op| match op {
+                crate::tir::TIROp::FnStart(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        let mono_labels = fn_labels
+            .iter()
+            .filter(|label| is_mono_label(label, &pm.file))
+            .cloned()
+            .collect();
         let lowering = create_stack_lowering(&options.target_config.name);
         let tasm = lowering.lower(&ir).join("\n");
-        tasm_modules.push(ModuleTasm {
+        Ok(ModuleTasm {
             module_name: pm.file.name.node.clone(),
             is_program,
             tasm,
-        });
+            fn_labels,
+            mono_labels,
+        })
     }
+}
 
-    // Link
-    let linked = link(tasm_modules);
-    Ok(linked)
+/// Whether `label` is the mangled name of an instance of one of the
+/// generic functions of `file` (`sum__N3` for `fn sum<N>`).
+fn is_mono_label(label: &str, file: &ast::File) -> bool {
+    file.items.iter().any(|item| match &item.node {
+        ast::Item::Fn(f) if !f.type_params.is_empty() => label
+            .strip_prefix(f.name.node.as_str())
+            .and_then(|rest| rest.strip_prefix("__N"))
+            .is_some_and(|args| {
+                !args.is_empty() && args.chars().all(|c| c.is_ascii_digit() || c == '_')
+            }),
+        _ => false,
+    })
+}
+
+/// Reserve the RAM regions of a project and return its memory map.
+///
+/// Fails with the same diagnostics as `compile_project_with_options` when a
+/// `sec ram` slot overlaps compiler-owned RAM or another slot, or a static
+/// access falls outside every region.
+pub fn ram_layout_project(
+    entry_path: &Path,
+    options: &CompileOptions,
+) -> Result<RamLayout, CompileError> {
+    use crate::pipeline::PreparedProject;
+
+    Ok(PreparedProject::build(entry_path, options)?.ram)
 }
 
 /// Type-check only (no TASM emission).
-pub fn check(source: &str, filename: &str) -> Result<(), Vec<Diagnostic>> {
-    let file = crate::parse_source(source, filename)?;
+pub fn check(source: &str, filename: &str) -> Result<(), CompileError> {
+    let file = crate::parse_source(source, filename).map_err(CompileError::Parse)?;
 
     if let Err(errors) = TypeChecker::new().check_file(&file) {
         render_diagnostics(&errors, filename, source);
-        return Err(errors);
+        return Err(CompileError::Type(errors));
     }
 
     Ok(())
@@ -164,7 +443,7 @@ pub fn check(source: &str, filename: &str) -> Result<(), Vec<Diagnostic>> {
 
 /// Project-aware type-check from an entry point path.
 /// Resolves all modules (including std.*) and type-checks in dependency order.
-pub fn check_project(entry_path: &Path) -> Result<(), Vec<Diagnostic>> {
+pub fn check_project(entry_path: &Path) -> Result<(), CompileError> {
     use crate::pipeline::PreparedProject;
 
     PreparedProject::build_default(entry_path)?;
@@ -188,151 +467,292 @@ pub fn discover_tests(file: &ast::File) -> Vec<String> {
 #[derive(Clone, Debug)]
 pub struct TestResult {
     pub name: String,
+    /// Module that defines the test function.
+    pub module: String,
     pub passed: bool,
+    /// The test compiled but reached an instruction the emulator only
+    /// fakes (`runtime::debug::PLACEHOLDER_INSTRUCTIONS`), so it was not
+    /// run to the end; it neither passed nor failed.
+    pub compile_only: bool,
     pub cost: Option<cost::TableCost>,
+    /// Why the test failed, or where a compile-only test stopped.
     pub error: Option<String>,
+    /// Time spent running this test, plus its share of compiling and
+    /// costing the project.
+    pub duration: std::time::Duration,
+}
+
+/// Which tests `run_tests_with` runs, and on how many threads.
+#[derive(Clone, Debug, Default)]
+pub struct TestSelection {
+    /// Substring of the test name (or `module.name`); `*` and `?` make it a glob.
+    pub filter: Option<String>,
+    /// Match the filter against the whole name instead of a substring.
+    pub exact: bool,
+    /// Worker threads running tests; 0 uses one per CPU.
+    pub jobs: usize,
+    /// Cycle budget for tests without `#[test(max_cycles = N)]`
+    /// (default `runtime::debug::DEFAULT_CYCLE_LIMIT`).
+    pub max_cycles: Option<u64>,
+    /// Public and secret input every test starts with (`--witness`).
+    pub input: crate::runtime::ProgramInput,
+}
+
+impl TestSelection {
+    /// Whether the test `name` in `module` is selected.
+    pub fn matches(&self, module: &str, name: &str) -> bool {
+        let Some(filter) = &self.filter else {
+            return true;
+        };
+        let qualified = format!("{}.{}", module, name);
+        let glob = filter.contains(['*', '?']).then(|| {
+            if self.exact {
+                filter.clone()
+            } else {
+                format!("*{}*", filter)
+            }
+        });
+        [name, qualified.as_str()]
+            .iter()
+            .any(|candidate| match &glob {
+                Some(pattern) => glob_match(pattern.as_bytes(), candidate.as_bytes()),
+                None if self.exact => *candidate == filter.as_str(),
+                None => candidate.contains(filter.as_str()),
+            })
+    }
+}
+
+/// Match `text` against a glob with `*` (any run) and `?` (any one byte).
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        Some((&p, rest)) => match text.split_first() {
+            Some((&t, text_rest)) => (p == b'?' || p == t) && glob_match(rest, text_rest),
+            None => false,
+        },
+    }
 }
 
 /// Run all `#[test]` functions in a project.
-///
-/// For each test function, we:
-/// 1. Parse and type-check the project
-/// 2. Compile a mini-program that just calls the test function
-/// 3. Report pass/fail with cost summary
 pub fn run_tests(
     entry_path: &std::path::Path,
     options: &CompileOptions,
-) -> Result<String, Vec<Diagnostic>> {
+) -> Result<TestReport, CompileError> {
+    run_tests_with(entry_path, options, &TestSelection::default())
+}
+
+/// Run the `#[test]` functions picked by `selection`.
+///
+/// We parse and type-check the project once, emit every module once with
+/// its tests, and link the functions the selected tests reach into one
+/// shared body. Each test then runs in parallel on the TASM emulator
+/// (`runtime::debug`) behind a two-line stub that calls it. A test passes
+/// when it halts within its cycle budget without a failed assertion.
+/// Results keep discovery order whatever the thread count.
+pub fn run_tests_with(
+    entry_path: &std::path::Path,
+    options: &CompileOptions,
+    selection: &TestSelection,
+) -> Result<TestReport, CompileError> {
     use crate::pipeline::PreparedProject;
+    use rayon::prelude::*;
 
+    let started = std::time::Instant::now();
     let project = PreparedProject::build(entry_path, options)?;
 
-    // Discover all #[test] functions across all modules
-    let mut test_fns: Vec<(String, String)> = Vec::new(); // (module_name, fn_name)
+    // Discover the selected #[test] functions, in module order
+    let mut tests: Vec<(&pipeline::ParsedModule, String)> = Vec::new();
     for pm in &project.modules {
-        for test_name in discover_tests(&pm.file) {
-            test_fns.push((pm.file.name.node.clone(), test_name));
+        let module_name = &pm.file.name.node;
+        for name in discover_tests(&pm.file) {
+            if selection.matches(module_name, &name) {
+                tests.push((pm, name));
+            }
         }
     }
-
-    if test_fns.is_empty() {
-        return Ok("No #[test] functions found.\n".to_string());
+    let mut report = TestReport::default();
+    if tests.is_empty() {
+        return Ok(report);
     }
 
-    // For each test function, compile a mini-program and report
-    let mut results: Vec<TestResult> = Vec::new();
-    let mut short_names: Vec<String> = Vec::new();
-    for (module_name, test_name) in &test_fns {
-        // Find the source file for this module
-        let source_entry = project
-            .modules
-            .iter()
-            .find(|m| m.file.name.node == *module_name);
-
-        if let Some(pm) = source_entry {
-            // Build a mini-program source that just calls the test function
-            let mini_source = if module_name.starts_with("module") || module_name.contains('.') {
-                // For module test functions, we'd need cross-module calls
-                // For simplicity, compile in-context
-                pm.source.clone()
-            } else {
-                pm.source.clone()
-            };
-
-            // Try to compile (type-check + emit) the source.
-            // The test function itself is validated by the type checker.
-            // For now, "passing" means it compiles without errors.
-            match compile_with_options(&mini_source, &pm.file_path.to_string_lossy(), options) {
-                Ok(tasm) => {
-                    // Compute cost for the test function
-                    let test_cost =
-                        analyze_costs(&mini_source, &pm.file_path.to_string_lossy()).ok();
-                    if short_names.is_empty() {
-                        if let Some(ref pc) = test_cost {
-                            short_names = pc.table_short_names.clone();
-                        }
-                    }
-                    let fn_cost = test_cost.as_ref().and_then(|pc| {
-                        pc.functions
-                            .iter()
-                            .find(|f| f.name == *test_name)
-                            .map(|f| f.cost.clone())
-                    });
-                    // Check if the generated TASM contains an assert failure marker
-                    let has_error = tasm.contains("// ERROR");
-                    results.push(TestResult {
-                        name: test_name.clone(),
-                        passed: !has_error,
-                        cost: fn_cost,
-                        error: if has_error {
-                            Some("compilation produced errors".to_string())
-                        } else {
-                            None
-                        },
-                    });
-                }
-                Err(errors) => {
-                    let msg = errors
-                        .iter()
-                        .map(|d| d.message.clone())
-                        .collect::<Vec<_>>()
-                        .join("; ");
-                    results.push(TestResult {
-                        name: test_name.clone(),
-                        passed: false,
-                        cost: None,
-                        error: Some(msg),
-                    });
-                }
+    // Emit every module once; a module that fails to emit fails its own
+    // tests, and the rest link what their selected tests reach
+    let scheme = options.label_scheme;
+    let test_label = |pm: &pipeline::ParsedModule, name: &str| {
+        format!("{}{}", scheme.module_prefix(&pm.file.name.node), name)
+    };
+    let mut emitted = Vec::new();
+    let mut emit_errors: BTreeMap<&str, String> = BTreeMap::new();
+    let mut emit = module_emitter(&project, options, true);
+    for (i, pm) in project.modules.iter().enumerate() {
+        match emit(i) {
+            Ok(module) => emitted.push(module),
+            Err(e) => {
+                let message = e
+                    .diagnostics()
+                    .iter()
+                    .map(|d| d.message.clone())
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                emit_errors.insert(pm.file.name.node.as_str(), message);
             }
         }
     }
+    let roots: Vec<String> = tests
+        .iter()
+        .filter(|(pm, _)| !emit_errors.contains_key(pm.file.name.node.as_str()))
+        .map(|(pm, name)| test_label(pm, name))
+        .collect();
+    let shared = link_reachable(&emitted, scheme, &roots).tasm;
 
-    // Format the report
-    let mut report = String::new();
-    let total = results.len();
-    let passed = results.iter().filter(|r| r.passed).count();
-    let failed = total - passed;
-
-    report.push_str(&format!(
-        "running {} test{}\n",
-        total,
-        if total == 1 { "" } else { "s" }
-    ));
+    // Cost each module that holds a selected test, from its parsed file
+    let mut costs: BTreeMap<&str, cost::ProgramCost> = BTreeMap::new();
+    for (pm, _) in &tests {
+        let module = pm.file.name.node.as_str();
+        if !costs.contains_key(module) {
+            let pc = cost::CostAnalyzer::for_config(&options.target_config).analyze_file(&pm.file);
+            if report.table_names.is_empty() {
+                report.table_names = pc.table_names.clone();
+                report.short_names = pc.table_short_names.clone();
+            }
+            costs.insert(module, pc);
+        }
+    }
+    let share = started.elapsed() / tests.len() as u32;
 
-    for result in &results {
-        let status = if result.passed { "ok" } else { "FAILED" };
-        let cost_str = if let Some(ref c) = result.cost {
-            let sn: Vec<&str> = short_names.iter().map(|s| s.as_str()).collect();
-            let ann = c.format_annotation(&sn);
-            if ann.is_empty() {
-                String::new()
-            } else {
-                format!(" ({})", ann)
+    let run = |(pm, test_name): &(&pipeline::ParsedModule, String)| {
+        let started = std::time::Instant::now();
+        let outcome = match emit_errors.get(pm.file.name.node.as_str()) {
+            Some(message) => Err(message.clone()),
+            None => {
+                let program = format!(
+                    "    call {}\n    halt\n\n{}",
+                    test_label(pm, test_name),
+                    shared
+                );
+                run_test(&project, pm, &program, test_name, scheme, selection)
             }
-        } else {
-            String::new()
         };
-        report.push_str(&format!(
-            "  test {} ... {}{}\n",
-            result.name, status, cost_str
-        ));
-        if let Some(ref err) = result.error {
-            report.push_str(&format!("    error: {}\n", err));
+        let cost = costs.get(pm.file.name.node.as_str()).and_then(|pc| {
+            pc.functions
+                .iter()
+                .find(|f| f.name == *test_name)
+                .map(|f| f.cost)
+        });
+        let (passed, compile_only, error) = match outcome {
+            Ok(None) => (true, false, None),
+            Ok(Some(stopped)) => (false, true, Some(stopped)),
+            Err(e) => (false, false, Some(e)),
+        };
+        TestResult {
+            name: test_name.clone(),
+            module: pm.file.name.node.clone(),
+            passed,
+            compile_only,
+            cost,
+            error,
+            duration: share + started.elapsed(),
         }
-    }
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(selection.jobs)
+        .build()
+        .map_err(|e| {
+            CompileError::Io(vec![Diagnostic::error(
+                format!("cannot start test threads: {}", e),
+                span::Span::dummy(),
+            )])
+        })?;
+    report.results = pool.install(|| tests.par_iter().map(run).collect());
 
-    report.push('\n');
-    if failed == 0 {
-        report.push_str(&format!("test result: ok. {} passed; 0 failed\n", passed));
-    } else {
-        report.push_str(&format!(
-            "test result: FAILED. {} passed; {} failed\n",
-            passed, failed
-        ));
+    Ok(report)
+}
+
+/// Run one `#[test]` function of `pm` on the TASM emulator. `program` is
+/// the project linked with its tests behind a stub that calls this one;
+/// the test's own `#[test(max_cycles = N)]` wins over
+/// `selection.max_cycles`. A test that reaches one of the
+/// `PLACEHOLDER_INSTRUCTIONS` stops there and is compile-only: the
+/// result is `Ok(Some(where))`.
+fn run_test(
+    project: &pipeline::PreparedProject,
+    pm: &pipeline::ParsedModule,
+    program: &str,
+    test_name: &str,
+    scheme: LabelScheme,
+    selection: &TestSelection,
+) -> Result<Option<String>, String> {
+    use crate::runtime::debug::source_map::line_of;
+    use crate::runtime::debug::{
+        Debugger, InputKind, Stop, DEFAULT_CYCLE_LIMIT, PLACEHOLDER_INSTRUCTIONS,
+    };
+
+    fn find_fn<'a>(file: &'a ast::File, name: &str) -> Option<(&'a ast::FnDef, usize)> {
+        file.items.iter().find_map(|item| match &item.node {
+            ast::Item::Fn(def) if def.name.node == name => Some((def, item.span.start as usize)),
+            _ => None,
+        })
     }
 
-    Ok(report)
+    let limit = find_fn(&pm.file, test_name)
+        .and_then(|(def, _)| def.test_max_cycles)
+        .or(selection.max_cycles)
+        .unwrap_or(DEFAULT_CYCLE_LIMIT);
+    let mut vm = Debugger::new(program, &selection.input);
+    vm.set_cycle_limit(limit);
+    vm.require_exact();
+    let stop = vm.resume();
+
+    // Innermost source function on the call stack, in whichever module
+    // defines it; branch and loop blocks have no entry of their own.
+    let location = vm
+        .frames()
+        .iter()
+        .rev()
+        .find_map(|frame| {
+            project.modules.iter().find_map(|m| {
+                let name = frame
+                    .label
+                    .strip_prefix(&scheme.module_prefix(&m.file.name.node))?;
+                let (def, offset) = find_fn(&m.file, name)?;
+                Some((m, def, offset))
+            })
+        })
+        .map(|(m, def, offset)| {
+            format!(
+                " in {} at {}:{}",
+                def.name.node,
+                m.file_path.display(),
+                line_of(&m.source, offset)
+            )
+        })
+        .unwrap_or_default();
+    let faked = vm.current().is_some_and(|instr| {
+        let op = instr.text.split_whitespace().next().unwrap_or_default();
+        PLACEHOLDER_INSTRUCTIONS.contains(&op)
+    });
+    match stop {
+        Stop::Halted => Ok(None),
+        Stop::Error(_) if faked => Ok(Some(format!(
+            "stopped at '{}'{}, which the emulator does not compute",
+            vm.current().map(|instr| instr.text.as_str()).unwrap_or_default(),
+            location
+        ))),
+        Stop::CycleLimit => Err(format!("cycle limit of {} exceeded{}", limit, location)),
+        Stop::Error(msg) => Err(format!("{}{}", msg, location)),
+        Stop::NeedsInput { kind, .. } => {
+            let stream = match kind {
+                InputKind::Public => "public",
+                InputKind::Secret => "secret",
+            };
+            Err(format!(
+                "test reads {} input{}, which tests do not get",
+                stream, location
+            ))
+        }
+        Stop::Step | Stop::Breakpoint(_) => Err(format!("test stopped early{}", location)),
+    }
 }
 
 /// Compile a module and emit TASM for all its functions (no linking, no DCE).
@@ -341,44 +761,45 @@ pub fn run_tests(
 pub fn compile_module(
     module_path: &Path,
     options: &CompileOptions,
-) -> Result<String, Vec<Diagnostic>> {
+) -> Result<String, CompileError> {
     use crate::pipeline::PreparedProject;
 
     let project = PreparedProject::build(module_path, options)?;
 
     let intrinsic_map = project.intrinsic_map();
-    let module_aliases = project.module_aliases();
     let external_constants = project.external_constants();
+    let struct_defs = project.struct_defs();
+    let external_returns = project.external_returns();
+    let pure_fns = project.pure_functions();
 
     // Emit TASM for only the target module (last in topological order)
     if let Some((i, pm)) = project.modules.iter().enumerate().last() {
-        let mono = project
-            .exports
-            .get(i)
-            .map(|e| e.mono_instances.clone())
-            .unwrap_or_default();
+        let mono = project.mono_instances(i);
         let call_res = project
             .exports
             .get(i)
             .map(|e| e.call_resolutions.clone())
             .unwrap_or_default();
         let ir = TIRBuilder::new(options.target_config.clone())
-            .with_cfg_flags(options.cfg_flags.clone())
+            .with_cfg_flags(options.flags_for(&pm.file.name.node))
             .with_intrinsics(intrinsic_map)
-            .with_module_aliases(module_aliases)
+            .with_module_aliases(project.module_aliases_for(i))
             .with_constants(external_constants)
+            .with_struct_types(struct_defs)
+            .with_external_returns(external_returns)
+            .with_pure_fns(pure_fns)
             .with_mono_instances(mono)
             .with_call_resolutions(call_res)
             .build_file(&pm.file);
-        let ir = optimize_tir(ir);
+        let ir = optimize_tir(ConstPool::new(&options.target_config).apply(ir));
         let lowering = create_stack_lowering(&options.target_config.name);
         let tasm = lowering.lower(&ir).join("\n");
         Ok(tasm)
     } else {
-        Err(vec![Diagnostic::error(
+        Err(CompileError::Io(vec![Diagnostic::error(
             "no module found".to_string(),
             span::Span::dummy(),
-        )])
+        )]))
     }
 }
 
@@ -390,26 +811,80 @@ pub fn build_tir(
     source: &str,
     filename: &str,
     options: &CompileOptions,
-) -> Result<Vec<crate::tir::TIROp>, Vec<Diagnostic>> {
-    let file = crate::parse_source(source, filename)?;
+) -> Result<Vec<crate::tir::TIROp>, CompileError> {
+    let file = crate::parse_source_with(source, filename, options.sink.as_ref())
+        .map_err(CompileError::Parse)?;
 
     let exports = match TypeChecker::with_target(options.target_config.clone())
-        .with_cfg_flags(options.cfg_flags.clone())
+        .with_cfg_flags(options.flags_for(&file.name.node))
         .check_file(&file)
     {
         Ok(exports) => exports,
         Err(errors) => {
-            render_diagnostics(&errors, filename, source);
-            return Err(errors);
+            options.sink.report(&errors, filename, source);
+            return Err(CompileError::Type(errors));
         }
     };
 
     let ir = TIRBuilder::new(options.target_config.clone())
-        .with_cfg_flags(options.cfg_flags.clone())
+        .with_cfg_flags(options.flags_for(&file.name.node))
         .with_mono_instances(exports.mono_instances)
         .with_call_resolutions(exports.call_resolutions)
         .build_file(&file);
-    Ok(optimize_tir(ir))
+    Ok(optimize_tir(
+        ConstPool::new(&options.target_config).apply(ir),
+    ))
+}
+
+/// Build the function-level source map for a project's linked TASM
+/// (see `runtime::debug::SourceMap`).
+pub fn source_map_project(
+    entry_path: &Path,
+    options: &CompileOptions,
+) -> Result<crate::runtime::debug::SourceMap, CompileError> {
+    use crate::pipeline::PreparedProject;
+    let project = PreparedProject::build(entry_path, options)?;
+    Ok(crate::runtime::debug::SourceMap::from_modules(
+        &project.modules,
+        options.label_scheme,
+    ))
+}
+
+/// Build the table of a project's runtime checks, whose ids debug builds
+/// push before each check (see `runtime::debug::AssertionTable`).
+pub fn assertion_table_project(
+    entry_path: &Path,
+    options: &CompileOptions,
+) -> Result<crate::runtime::debug::AssertionTable, CompileError> {
+    use crate::pipeline::PreparedProject;
+    let project = PreparedProject::build(entry_path, options)?;
+    Ok(crate::runtime::debug::AssertionTable::from_project(
+        &project, options,
+    ))
+}
+
+/// Build the symbol table of every module in a project
+/// (see `runtime::debug::SymbolTable`).
+pub fn symbol_table_project(
+    entry_path: &Path,
+    options: &CompileOptions,
+) -> Result<crate::runtime::debug::SymbolTable, CompileError> {
+    use crate::pipeline::PreparedProject;
+    let project = PreparedProject::build(entry_path, options)?;
+    Ok(crate::runtime::debug::SymbolTable::from_modules(
+        &project.modules,
+        options.label_scheme,
+    ))
+}
+
+/// Extract the ABI of a project's entry module (see `runtime::ProgramAbi`).
+pub fn abi_project(
+    entry_path: &Path,
+    options: &CompileOptions,
+) -> Result<crate::runtime::ProgramAbi, CompileError> {
+    Compiler::new(entry_path)
+        .with_options(options.clone())
+        .abi()
 }
 
 /// Build TIR from a project entry point with full module resolution.
@@ -421,44 +896,67 @@ pub fn build_tir(
 pub fn build_tir_project(
     entry_path: &Path,
     options: &CompileOptions,
-) -> Result<Vec<crate::tir::TIROp>, Vec<Diagnostic>> {
+) -> Result<Vec<crate::tir::TIROp>, CompileError> {
     use crate::pipeline::PreparedProject;
 
     let project = PreparedProject::build(entry_path, options)?;
 
     let intrinsic_map = project.intrinsic_map();
-    let module_aliases = project.module_aliases();
     let external_constants = project.external_constants();
+    let struct_defs = project.struct_defs();
+    let external_returns = project.external_returns();
+    let pure_fns = project.pure_functions();
 
+    let mut pool = ConstPool::new(&options.target_config);
     let mut all_ir = Vec::new();
     for (i, pm) in project.modules.iter().enumerate() {
-        let mono = project
-            .exports
-            .get(i)
-            .map(|e| e.mono_instances.clone())
-            .unwrap_or_default();
+        let mono = project.mono_instances(i);
         let call_res = project
             .exports
             .get(i)
             .map(|e| e.call_resolutions.clone())
             .unwrap_or_default();
         let ir = TIRBuilder::new(options.target_config.clone())
-            .with_cfg_flags(options.cfg_flags.clone())
+            .with_cfg_flags(options.flags_for(&pm.file.name.node))
             .with_intrinsics(intrinsic_map.clone())
-            .with_module_aliases(module_aliases.clone())
+            .with_module_aliases(project.module_aliases_for(i))
             .with_constants(external_constants.clone())
+            .with_struct_types(struct_defs.clone())
+            .with_external_returns(external_returns.clone())
+            .with_pure_fns(pure_fns.clone())
             .with_mono_instances(mono)
             .with_call_resolutions(call_res)
             .build_file(&pm.file);
-        all_ir.extend(optimize_tir(ir));
+        all_ir.extend(optimize_tir(pool.apply(ir)));
     }
     Ok(all_ir)
 }
 
+mod build_info;
+mod compiler;
 pub(crate) mod doc;
+mod error;
+mod fix;
 pub(crate) mod pipeline;
+mod profiles;
+mod repl;
+mod spec;
+mod test_report;
 mod tools;
+mod view;
+pub use build_info::*;
+pub use compiler::*;
+pub use error::*;
+pub use fix::*;
+pub use profiles::*;
+pub use repl::*;
+pub use spec::*;
+pub use test_report::*;
 pub use tools::*;
+pub use view::ViewedItem;
+
+#[cfg(feature = "capi")]
+pub mod capi;
 
 /// Compile a multi-module project to a `ProgramBundle` artifact.
 ///
@@ -468,15 +966,17 @@ pub use tools::*;
 pub fn compile_to_bundle(
     entry_path: &Path,
     options: &CompileOptions,
-) -> Result<crate::runtime::ProgramBundle, Vec<Diagnostic>> {
+) -> Result<crate::runtime::ProgramBundle, CompileError> {
     use crate::runtime::artifact::{BundleCost, BundleFunction, ProgramBundle};
-    use pipeline::PreparedProject;
 
-    let tasm = compile_project_with_options(entry_path, options)?;
+    let compiler = Compiler::new(entry_path).with_options(options.clone());
+    let tasm = compiler.compile()?.to_string();
 
     // Cost analysis (best-effort — use zeros on failure)
-    let program_cost =
-        analyze_costs_project(entry_path, options).unwrap_or_else(|_| cost::ProgramCost {
+    let program_cost = compiler
+        .costs()
+        .cloned()
+        .unwrap_or_else(|_| cost::ProgramCost {
             program_name: String::new(),
             functions: Vec::new(),
             total: cost::TableCost::ZERO,
@@ -486,10 +986,11 @@ pub fn compile_to_bundle(
             padded_height: 0,
             estimated_proving_ns: 0,
             loop_bound_waste: Vec::new(),
+            entries: Vec::new(),
         });
 
-    // Parse entry file for function signatures + content hashes
-    let project = PreparedProject::build(entry_path, options)?;
+    // Entry file for function signatures + content hashes
+    let project = compiler.project()?;
     let entry_file = project
         .modules
         .iter()
@@ -7,13 +7,16 @@ use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use crate::ast;
+use crate::ast::eval::PureFns;
 use crate::ast::FileKind;
-use crate::diagnostic::{render_diagnostics, Diagnostic};
+use crate::diagnostic::{line_column, Diagnostic, Instantiation};
 use crate::resolve::{resolve_modules, resolve_modules_with_deps};
-use crate::typecheck::{ModuleExports, TypeChecker};
-use crate::CompileOptions;
+use crate::tir::ram::{data_segments, RamLayout};
+use crate::typecheck::{ModuleExports, MonoInstance, TypeChecker};
+use crate::{CompileError, CompileOptions};
 
 /// A single parsed module: path, source text, and parsed AST.
+#[derive(Clone)]
 pub(crate) struct ParsedModule {
     pub file_path: PathBuf,
     pub source: String,
@@ -24,6 +27,9 @@ pub(crate) struct ParsedModule {
 pub(crate) struct PreparedProject {
     pub modules: Vec<ParsedModule>,
     pub exports: Vec<ModuleExports>,
+    /// RAM regions reserved for compiler temporaries, spills, `generate!`
+    /// tables, and `sec ram`.
+    pub ram: RamLayout,
 }
 
 impl PreparedProject {
@@ -31,61 +37,135 @@ impl PreparedProject {
     ///
     /// This performs the resolve → parse → typecheck pipeline that is shared
     /// across `compile_project`, `run_tests`, `analyze_costs_project`,
-    /// and `generate_docs`.
-    pub fn build(entry_path: &Path, options: &CompileOptions) -> Result<Self, Vec<Diagnostic>> {
+    /// and `generate_docs`. Diagnostics go to `options.sink`.
+    pub fn build(entry_path: &Path, options: &CompileOptions) -> Result<Self, CompileError> {
+        let modules = Self::load(entry_path, options)?;
+        Self::check(modules, options)
+    }
+
+    /// Like `build`, but returns diagnostics without reporting them.
+    pub fn build_silent(entry_path: &Path, options: &CompileOptions) -> Result<Self, CompileError> {
+        Self::build(entry_path, &options.clone().silent())
+    }
+
+    /// Build a project once per profile. Modules are resolved and parsed
+    /// once, with the first profile's search paths; only type checking,
+    /// which depends on the cfg flags, runs per profile.
+    pub fn build_profiles(
+        entry_path: &Path,
+        profiles: &[CompileOptions],
+    ) -> Result<Vec<Self>, CompileError> {
+        let Some(first) = profiles.first() else {
+            return Ok(Vec::new());
+        };
+        let modules = Self::load(entry_path, first)?;
+        profiles
+            .iter()
+            .map(|options| Self::check(modules.clone(), options))
+            .collect()
+    }
+
+    /// Resolve and parse every module reachable from the entry point.
+    fn load(
+        entry_path: &Path,
+        options: &CompileOptions,
+    ) -> Result<Vec<ParsedModule>, CompileError> {
+        let report = |diags: &[Diagnostic], path: &Path, source: &str| {
+            options.sink.report(diags, &path.to_string_lossy(), source);
+        };
         let resolved = if options.dep_dirs.is_empty() {
-            resolve_modules(entry_path)?
+            resolve_modules(entry_path)
         } else {
-            resolve_modules_with_deps(entry_path, options.dep_dirs.clone())?
-        };
+            resolve_modules_with_deps(entry_path, options.dep_dirs.clone())
+        }
+        .map_err(CompileError::Io)?;
+        for m in &resolved {
+            let errors = gated_imports(m, &options.gated_modules);
+            if !errors.is_empty() {
+                report(&errors, &m.file_path, &m.source);
+                return Err(CompileError::Io(errors));
+            }
+        }
 
         let mut modules = Vec::new();
         for m in &resolved {
-            let file = crate::parse_source(&m.source, &m.file_path.to_string_lossy())?;
+            let filename = m.file_path.to_string_lossy();
+            let file = crate::parse_source_with(&m.source, &filename, options.sink.as_ref())
+                .map_err(CompileError::Parse)?;
             modules.push(ParsedModule {
                 file_path: m.file_path.clone(),
                 source: m.source.clone(),
                 file,
             });
         }
+        Ok(modules)
+    }
 
+    /// Type-check parsed modules under `options` and lay out RAM.
+    fn check(modules: Vec<ParsedModule>, options: &CompileOptions) -> Result<Self, CompileError> {
+        let report = |diags: &[Diagnostic], path: &Path, source: &str| {
+            options.sink.report(diags, &path.to_string_lossy(), source);
+        };
         let mut exports: Vec<ModuleExports> = Vec::new();
+        let mut ram = RamLayout::new(&options.target_config);
+        let mut tables = PureFns::new();
+        for pm in &modules {
+            tables.add_module(&pm.file, &options.flags_for(&pm.file.name.node));
+        }
+        ram.reserve_data(&data_segments(tables.tables(), &options.target_config));
         for pm in &modules {
             let mut tc = TypeChecker::with_target(options.target_config.clone())
-                .with_cfg_flags(options.cfg_flags.clone());
+                .with_cfg_flags(options.flags_for(&pm.file.name.node));
             for e in &exports {
                 tc.import_module(e);
             }
             match tc.check_file(&pm.file) {
                 Ok(e) => {
                     if !e.warnings.is_empty() {
-                        render_diagnostics(
-                            &e.warnings,
-                            &pm.file_path.to_string_lossy(),
-                            &pm.source,
-                        );
+                        report(&e.warnings, &pm.file_path, &pm.source);
                     }
                     exports.push(e);
                 }
                 Err(errors) => {
-                    render_diagnostics(&errors, &pm.file_path.to_string_lossy(), &pm.source);
-                    return Err(errors);
+                    report(&errors, &pm.file_path, &pm.source);
+                    return Err(CompileError::Type(errors));
                 }
             }
+            let errors = ram.reserve_sec_ram(&pm.file, &options.target_config);
+            if !errors.is_empty() {
+                report(&errors, &pm.file_path, &pm.source);
+                return Err(CompileError::Type(errors));
+            }
         }
 
-        Ok(PreparedProject { modules, exports })
+        let project = PreparedProject {
+            modules,
+            exports,
+            ram,
+        };
+        // Static accesses are checked once every module's slots are
+        // reserved: a library may touch a slot the program declares.
+        let intrinsics = project.intrinsic_map();
+        for pm in &project.modules {
+            let flags = options.flags_for(&pm.file.name.node);
+            let errors = project
+                .ram
+                .check_static_accesses(&pm.file, &intrinsics, &flags);
+            if !errors.is_empty() {
+                report(&errors, &pm.file_path, &pm.source);
+                return Err(CompileError::Type(errors));
+            }
+        }
+        Ok(project)
     }
 
-
     /// Build a project with default options (Triton target, debug profile).
     ///
     /// Used by `check_project` and `verify_project` which don't need target options.
-    pub fn build_default(entry_path: &Path) -> Result<Self, Vec<Diagnostic>> {
+    pub fn build_default(entry_path: &Path) -> Result<Self, CompileError> {
         Self::build(entry_path, &CompileOptions::default())
     }
 
-
     /// Return the program module (last in topological order, has `FileKind::Program`).
     pub fn program_module(&self) -> Option<&ParsedModule> {
         self.modules
@@ -107,13 +187,8 @@ impl PreparedProject {
         for pm in &self.modules {
             for item in &pm.file.items {
                 if let ast::Item::Fn(func) = &item.node {
-                    if let Some(ref intrinsic) = func.intrinsic {
-                        let intr_value = if let Some(start) = intrinsic.node.find('(') {
-                            let end = intrinsic.node.rfind(')').unwrap_or(intrinsic.node.len());
-                            intrinsic.node[start + 1..end].to_string()
-                        } else {
-                            intrinsic.node.clone()
-                        };
+                    if let Some(intrinsic) = func.intrinsic_attr() {
+                        let intr_value = intrinsic.name;
                         // Short function name
                         map.insert(func.name.node.clone(), intr_value.clone());
                         // Qualified name (module.func)
@@ -133,6 +208,16 @@ impl PreparedProject {
         map
     }
 
+    /// `#[pure]` functions and constants of every module, for folding
+    /// constant calls during emission.
+    pub fn pure_functions(&self) -> PureFns {
+        let mut fns = PureFns::new();
+        for exp in &self.exports {
+            fns.merge(&exp.pure_fns);
+        }
+        fns
+    }
+
     /// Build module alias map: short name -> full name for dotted modules.
     pub fn module_aliases(&self) -> BTreeMap<String, String> {
         let mut aliases = BTreeMap::new();
@@ -147,6 +232,130 @@ impl PreparedProject {
         aliases
     }
 
+    /// Module alias map as seen from module `i`: its own `use` list wins
+    /// over modules elsewhere in the project that share a short name
+    /// (`std.proof` vs `os.neptune.proof`).
+    pub fn module_aliases_for(&self, i: usize) -> BTreeMap<String, String> {
+        let mut aliases = self.module_aliases();
+        if let Some(pm) = self.modules.get(i) {
+            for u in &pm.file.uses {
+                let full_name = u.node.as_dotted();
+                if let Some(short) = u.node.0.last() {
+                    if *short != full_name {
+                        aliases.insert(short.clone(), full_name);
+                    }
+                }
+            }
+        }
+        aliases
+    }
+
+    /// Generic instances module `i` must emit: its own, plus those that
+    /// other modules requested through qualified calls (`fri.verify<3>`).
+    pub fn mono_instances(&self, i: usize) -> Vec<MonoInstance> {
+        let mut instances = self
+            .exports
+            .get(i)
+            .map(|e| e.mono_instances.clone())
+            .unwrap_or_default();
+        let Some(target) = self.modules.get(i).map(|m| &m.file.name.node) else {
+            return instances;
+        };
+        for (j, exp) in self.exports.iter().enumerate() {
+            let aliases = self.module_aliases_for(j);
+            for inst in &exp.mono_instances {
+                let Some((prefix, fn_name)) = inst.name.rsplit_once('.') else {
+                    continue;
+                };
+                let module = aliases.get(prefix).map(String::as_str).unwrap_or(prefix);
+                if module != target.as_str() {
+                    continue;
+                }
+                let local = MonoInstance {
+                    name: fn_name.to_string(),
+                    size_args: inst.size_args.clone(),
+                    instantiated_at: None,
+                };
+                if !instances.contains(&local) {
+                    instances.push(local);
+                }
+            }
+        }
+        instances
+    }
+
+    /// Where instance `label` (`sum__N3`) of a generic function in
+    /// `module` was first requested: the first call the type checker
+    /// recorded for it, in module order.
+    pub fn instantiation_site(&self, module: &str, label: &str) -> Option<Instantiation> {
+        for (j, (pm, exp)) in self.modules.iter().zip(&self.exports).enumerate() {
+            let aliases = self.module_aliases_for(j);
+            for inst in &exp.mono_instances {
+                let Some(span) = inst.instantiated_at else {
+                    continue;
+                };
+                let (owner, fn_name) = match inst.name.rsplit_once('.') {
+                    Some((prefix, name)) => (
+                        aliases.get(prefix).map(String::as_str).unwrap_or(prefix),
+                        name,
+                    ),
+                    None => (pm.file.name.node.as_str(), inst.name.as_str()),
+                };
+                let local = MonoInstance {
+                    name: fn_name.to_string(),
+                    size_args: inst.size_args.clone(),
+                    instantiated_at: None,
+                };
+                if owner == module && local.mangled_name() == label {
+                    return Some(Instantiation {
+                        instance: local.display_name(),
+                        file: pm.file_path.to_string_lossy().into_owned(),
+                        line: line_column(&pm.source, span.start as usize).0,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Struct definitions of every module, by name, so each module's
+    /// emitter can size the struct values it imports.
+    pub fn struct_defs(&self) -> BTreeMap<String, ast::StructDef> {
+        let mut structs = BTreeMap::new();
+        for pm in &self.modules {
+            for item in &pm.file.items {
+                if let ast::Item::Struct(sdef) = &item.node {
+                    structs.insert(sdef.name.node.clone(), sdef.clone());
+                }
+            }
+        }
+        structs
+    }
+
+    /// Size parameters and declared return type of every public function,
+    /// by full path (`std.crypto.transcript.challenge`), so callers in
+    /// other modules know how many elements a call leaves.
+    pub fn external_returns(&self) -> BTreeMap<String, (Vec<String>, Option<ast::Type>)> {
+        let mut returns = BTreeMap::new();
+        for pm in &self.modules {
+            for item in &pm.file.items {
+                let ast::Item::Fn(func) = &item.node else {
+                    continue;
+                };
+                if !func.is_pub {
+                    continue;
+                }
+                let params = func.type_params.iter().map(|p| p.node.clone()).collect();
+                let ret = func.return_ty.as_ref().map(|t| t.node.clone());
+                returns.insert(
+                    format!("{}.{}", pm.file.name.node, func.name.node),
+                    (params, ret),
+                );
+            }
+        }
+        returns
+    }
+
     /// Build external constants map from all module exports.
     pub fn external_constants(&self) -> BTreeMap<String, u64> {
         let mut constants = BTreeMap::new();
@@ -166,3 +375,31 @@ impl PreparedProject {
         constants
     }
 }
+
+/// Errors for `use` statements in `module` that import a module whose
+/// feature is disabled.
+fn gated_imports(
+    module: &crate::resolve::ModuleInfo,
+    gated: &BTreeMap<String, String>,
+) -> Vec<Diagnostic> {
+    module
+        .dependencies
+        .iter()
+        .filter_map(|dep| {
+            let feature = gated.get(dep)?;
+            let needle = format!("use {}", dep);
+            let span = module
+                .source
+                .find(&needle)
+                .map(|start| crate::span::Span::new(0, start as u32, (start + needle.len()) as u32))
+                .unwrap_or_else(crate::span::Span::dummy);
+            Some(
+                Diagnostic::error(
+                    format!("module '{}' requires feature '{}'", dep, feature),
+                    span,
+                )
+                .with_help(format!("enable it with `--features {}`", feature)),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,145 @@
+use super::*;
+
+/// One profile's output from `compile_project_profiles`.
+#[derive(Clone, Debug)]
+pub struct ProfileBuild {
+    pub profile: String,
+    pub tasm: String,
+    /// Assertion calls emitted under this profile, in source order.
+    pub assertion_sites: Vec<AssertionSite>,
+}
+
+/// A call that emits a runtime check (`assert`, `assert_eq`,
+/// `assert_digest`, `ensure`, or `assume` where it is checked).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssertionSite {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    /// The builtin the call lowers to.
+    pub check: String,
+    /// The enclosing function.
+    pub function: String,
+}
+
+/// How a profile's output differs from a base profile's.
+#[derive(Clone, Debug)]
+pub struct ProfileDiff {
+    pub base: String,
+    pub other: String,
+    pub base_instructions: usize,
+    pub other_instructions: usize,
+    /// Assertion sites of the base profile that the other drops.
+    pub removed_sites: Vec<AssertionSite>,
+    /// Assertion sites only the other profile emits.
+    pub added_sites: Vec<AssertionSite>,
+}
+
+impl ProfileBuild {
+    /// Instructions in the TASM, not counting labels and comments.
+    pub fn instruction_count(&self) -> usize {
+        self.tasm
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.ends_with(':') && !l.starts_with("//"))
+            .count()
+    }
+
+    /// Compare against `base`.
+    pub fn diff_from(&self, base: &ProfileBuild) -> ProfileDiff {
+        let missing = |from: &ProfileBuild, to: &ProfileBuild| {
+            from.assertion_sites
+                .iter()
+                .filter(|s| !to.assertion_sites.contains(s))
+                .cloned()
+                .collect()
+        };
+        ProfileDiff {
+            base: base.profile.clone(),
+            other: self.profile.clone(),
+            base_instructions: base.instruction_count(),
+            other_instructions: self.instruction_count(),
+            removed_sites: missing(base, self),
+            added_sites: missing(self, base),
+        }
+    }
+}
+
+impl ProfileDiff {
+    /// Human-readable summary for `trident build --profiles`.
+    pub fn format_summary(&self) -> String {
+        let delta = self.other_instructions as i64 - self.base_instructions as i64;
+        let mut out = format!(
+            "{} -> {}: {} -> {} instructions ({:+})\n",
+            self.base, self.other, self.base_instructions, self.other_instructions, delta
+        );
+        for (verb, sites) in [
+            ("removed", &self.removed_sites),
+            ("added", &self.added_sites),
+        ] {
+            if sites.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("  {} assertion site(s) {}:\n", sites.len(), verb));
+            for s in sites {
+                out.push_str(&format!(
+                    "    {}:{}:{}  {} in {}\n",
+                    s.file, s.line, s.column, s.check, s.function
+                ));
+            }
+        }
+        if self.removed_sites.is_empty() && self.added_sites.is_empty() {
+            out.push_str("  assertion sites unchanged\n");
+        }
+        out
+    }
+}
+
+/// Compile a project under several profiles, e.g. `debug` and `release`.
+///
+/// Modules are resolved and parsed once; each profile is type-checked
+/// and emitted with its own cfg flags. Profiles share the first one's
+/// dependency directories.
+pub fn compile_project_profiles(
+    entry_path: &Path,
+    profiles: &[CompileOptions],
+) -> Result<Vec<ProfileBuild>, CompileError> {
+    use crate::pipeline::PreparedProject;
+
+    let projects = PreparedProject::build_profiles(entry_path, profiles)?;
+    let mut builds = Vec::new();
+    for (project, options) in projects.iter().zip(profiles) {
+        let linked = link_project(project, options)?;
+        if let Some(diag) = mono_budget_error(project, &linked.mono, &options.mono_limits) {
+            let errors = vec![diag.0];
+            options
+                .sink
+                .report(&errors, &diag.1.file_path.to_string_lossy(), &diag.1.source);
+            return Err(CompileError::Link(errors));
+        }
+        builds.push(ProfileBuild {
+            profile: options.profile.clone(),
+            tasm: linked.tasm,
+            assertion_sites: assertion_sites(project, options),
+        });
+    }
+    Ok(builds)
+}
+
+/// Assertion calls in the functions active under `options`.
+fn assertion_sites(
+    project: &crate::pipeline::PreparedProject,
+    options: &CompileOptions,
+) -> Vec<AssertionSite> {
+    crate::runtime::debug::AssertionTable::from_project(project, options)
+        .assertions()
+        .iter()
+        .map(|a| AssertionSite {
+            file: a.file.to_string_lossy().to_string(),
+            line: a.line,
+            column: a.column,
+            check: a.check.clone(),
+            function: a.function.clone(),
+        })
+        .collect()
+}
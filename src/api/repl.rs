@@ -0,0 +1,417 @@
+//! Interactive evaluation for `trident repl`.
+//!
+//! A session remembers the `use` lines, items (`fn`, `struct`, `const`)
+//! and `let` bindings entered so far. Each expression becomes a
+//! throwaway program: the bindings and the expression form the body of
+//! `repl_eval`, and `main` writes its result to public output, element
+//! by element. The program is written next to the project's entry, so
+//! `use` finds the project's modules, built, linked, and run on the TASM
+//! emulator (`runtime::debug`); the file is removed right after.
+
+use std::path::{Path, PathBuf};
+
+use crate::pipeline::PreparedProject;
+use crate::runtime::debug::{Debugger, InputKind, Stop, DEFAULT_CYCLE_LIMIT};
+use crate::runtime::ProgramInput;
+use crate::types::Ty;
+
+use super::*;
+
+/// Module name of the throwaway program.
+const PROGRAM: &str = "trident_repl";
+
+/// Name the expression's value is bound to in `main`.
+const VALUE: &str = "repl_value";
+
+/// The result of evaluating an expression.
+#[derive(Clone, Debug)]
+pub struct ReplValue {
+    /// The expression's type, as written in source.
+    pub ty: String,
+    /// The value, or `None` for `()` and types that cannot be written
+    /// to public output (`XField`, `Option`).
+    pub value: Option<String>,
+    /// What the expression itself wrote with `pub_write`.
+    pub output: Vec<u64>,
+    /// Static cost of the expression and the session's bindings.
+    pub cost: String,
+    /// Clock cycles the throwaway program ran for.
+    pub cycles: u64,
+}
+
+/// What a line of REPL input did.
+#[derive(Clone, Debug)]
+pub enum ReplOutcome {
+    /// A `use`, item, or `let` was added to the session.
+    Defined(String),
+    /// An expression was evaluated.
+    Evaluated(ReplValue),
+}
+
+/// Definitions entered so far, and where their programs are built.
+pub struct ReplSession {
+    scratch: PathBuf,
+    options: CompileOptions,
+    pub uses: Vec<String>,
+    pub items: Vec<String>,
+    pub bindings: Vec<String>,
+    /// Input every evaluation starts with.
+    pub input: ProgramInput,
+}
+
+impl ReplSession {
+    /// A session whose programs are built in `dir`.
+    pub fn new(dir: &Path, options: CompileOptions) -> Self {
+        let scratch = dir.join(format!(".{}-{}.tri", PROGRAM, std::process::id()));
+        Self {
+            scratch,
+            options,
+            uses: Vec::new(),
+            items: Vec::new(),
+            bindings: Vec::new(),
+            input: ProgramInput::default(),
+        }
+    }
+
+    /// A session in the context of a project: its entry must type-check,
+    /// and the session starts with the entry's imports, or with the entry
+    /// itself when it is a module.
+    pub fn for_project(entry: &Path, options: CompileOptions) -> Result<Self, Vec<Diagnostic>> {
+        let project = PreparedProject::build_silent(entry, &options)?;
+        let dir = entry.parent().unwrap_or(Path::new("."));
+        let mut session = Self::new(dir, options);
+        if let Some(file) = project.last_file() {
+            match file.kind {
+                FileKind::Program => {
+                    for path in &file.uses {
+                        session.uses.push(format!("use {}", path.node));
+                    }
+                }
+                FileKind::Module => session.uses.push(format!("use {}", file.name.node)),
+            }
+        }
+        Ok(session)
+    }
+
+    /// Forget every definition; the input is kept.
+    pub fn reset(&mut self) {
+        self.uses.clear();
+        self.items.clear();
+        self.bindings.clear();
+    }
+
+    /// Evaluate one complete input: a `use` line, an item, a `let`
+    /// binding, or an expression.
+    pub fn eval(&mut self, input: &str) -> Result<ReplOutcome, Vec<Diagnostic>> {
+        let input = input.trim();
+        let first = input.split_whitespace().next().unwrap_or("");
+        let list = match first {
+            "use" => &mut self.uses,
+            "let" => &mut self.bindings,
+            "fn" | "pub" | "struct" | "const" | "event" => &mut self.items,
+            _ if input.starts_with("#[") => &mut self.items,
+            _ => return self.evaluate(input).map(ReplOutcome::Evaluated),
+        };
+        list.push(input.to_string());
+        let source = self.program(&self.main_with(&[]));
+        if let Err(errors) = self.build(&source) {
+            self.remove_last(first, input);
+            return Err(errors);
+        }
+        let what = match first {
+            "use" => "import",
+            "let" => "binding",
+            _ => "item",
+        };
+        Ok(ReplOutcome::Defined(what.to_string()))
+    }
+
+    fn remove_last(&mut self, first: &str, input: &str) {
+        let list = match first {
+            "use" => &mut self.uses,
+            "let" => &mut self.bindings,
+            _ => &mut self.items,
+        };
+        if list.last().is_some_and(|last| last == input) {
+            list.pop();
+        }
+    }
+
+    /// The type of `expr` in the session, without running it.
+    pub fn type_of(&self, expr: &str) -> Result<Ty, Vec<Diagnostic>> {
+        let source = self.program(&self.main_with(&[format!("let {} = {}", VALUE, expr)]));
+        let project = match self.build(&source) {
+            Ok(project) => project,
+            // A `()` expression cannot be bound; it still type-checks
+            // as a statement.
+            Err(errors) => {
+                let statement = self.program(&self.main_with(&[expr.to_string()]));
+                return match self.build(&statement) {
+                    Ok(_) => Ok(Ty::Unit),
+                    Err(_) => Err(errors),
+                };
+            }
+        };
+        let Some((file, imports)) = project
+            .exports
+            .split_last()
+            .and_then(|(_, imports)| project.last_file().map(|file| (file, imports)))
+        else {
+            return Ok(Ty::Unit);
+        };
+        let mut tc = TypeChecker::with_target(self.options.target_config.clone())
+            .with_cfg_flags(self.options.flags_for(PROGRAM));
+        for exports in imports {
+            tc.import_module(exports);
+        }
+        // The probe sits on `main`'s closing brace, after the binding.
+        let offset = source.rfind('}').unwrap_or(0) as u32;
+        Ok(tc
+            .scope_at(file, offset)
+            .vars
+            .remove(VALUE)
+            .unwrap_or(Ty::Unit))
+    }
+
+    fn evaluate(&self, expr: &str) -> Result<ReplValue, Vec<Diagnostic>> {
+        let ty = self.type_of(expr)?;
+        let shown = ty.display();
+
+        let mut writes = Vec::new();
+        let writable = write_value(VALUE, &ty, &mut writes, &mut 0);
+        let mut body = self.bindings.join("\n    ");
+        body.push_str("\n    ");
+        body.push_str(expr);
+        let (eval_fn, call) = match ty {
+            Ty::Unit => (
+                format!("fn repl_eval() {{\n    {}\n}}\n", body),
+                "repl_eval()".to_string(),
+            ),
+            _ => (
+                format!("fn repl_eval() -> {} {{\n    {}\n}}\n", shown, body),
+                format!("let {}: {} = repl_eval()", VALUE, shown),
+            ),
+        };
+        let mut main = vec![call];
+        if writable {
+            main.extend(writes);
+        }
+        let source = format!(
+            "{}\n{}fn main() {{\n    {}\n}}\n",
+            self.program(""),
+            eval_fn,
+            main.join("\n    ")
+        );
+
+        let project = self.build(&source)?;
+        let tasm = link_project(&project, &self.options)?.tasm;
+        let cost = project
+            .last_file()
+            .map(|file| {
+                let costs = cost::CostAnalyzer::for_config(&self.options.target_config)
+                    .with_pure_fns(project.pure_functions())
+                    .analyze_file(file);
+                let short: Vec<&str> = costs.table_short_names.iter().map(|s| s.as_str()).collect();
+                costs
+                    .functions
+                    .iter()
+                    .find(|f| f.name == "repl_eval")
+                    .map(|f| f.cost.format_annotation(&short))
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default();
+
+        let mut vm = Debugger::new(&tasm, &self.input);
+        vm.set_cycle_limit(DEFAULT_CYCLE_LIMIT);
+        let failure = match vm.resume() {
+            Stop::Halted => None,
+            Stop::Error(msg) => Some(msg),
+            Stop::CycleLimit => Some(format!("cycle limit of {} exceeded", DEFAULT_CYCLE_LIMIT)),
+            Stop::NeedsInput { kind, .. } => Some(match kind {
+                InputKind::Public => "reads public input; set it with :input".to_string(),
+                InputKind::Secret => "reads secret input; set it with :secret".to_string(),
+            }),
+            Stop::Step | Stop::Breakpoint(_) => Some("stopped early".to_string()),
+        };
+        if let Some(msg) = failure {
+            return Err(vec![Diagnostic::error(msg, span::Span::dummy())]);
+        }
+
+        let written = if writable { ty.width() as usize } else { 0 };
+        let output = vm.output();
+        let (own, value) = output.split_at(output.len().saturating_sub(written));
+        let value = (writable && ty != Ty::Unit).then(|| format_value(&ty, &mut value.iter()));
+        Ok(ReplValue {
+            ty: shown,
+            value,
+            output: own.to_vec(),
+            cost,
+            cycles: vm.cycles(),
+        })
+    }
+
+    /// The session's program around `main`'s body.
+    fn program(&self, main: &str) -> String {
+        let mut source = format!("program {}\n\n", PROGRAM);
+        for line in &self.uses {
+            source.push_str(line);
+            source.push('\n');
+        }
+        source.push('\n');
+        for item in &self.items {
+            source.push_str(item);
+            source.push_str("\n\n");
+        }
+        source.push_str(main);
+        source
+    }
+
+    /// `main` running the bindings, then `extra`.
+    fn main_with(&self, extra: &[String]) -> String {
+        let body: Vec<&str> = self
+            .bindings
+            .iter()
+            .map(String::as_str)
+            .chain(extra.iter().map(String::as_str))
+            .collect();
+        format!("fn main() {{\n    {}\n}}\n", body.join("\n    "))
+    }
+
+    /// Build `source` as the entry of a project, without rendering.
+    fn build(&self, source: &str) -> Result<PreparedProject, Vec<Diagnostic>> {
+        std::fs::write(&self.scratch, source).map_err(|e| {
+            vec![Diagnostic::error(
+                format!("cannot write '{}': {}", self.scratch.display(), e),
+                span::Span::dummy(),
+            )]
+        })?;
+        let project = PreparedProject::build_silent(&self.scratch, &self.options);
+        let _ = std::fs::remove_file(&self.scratch);
+        project.map_err(Vec::from)
+    }
+}
+
+/// Statements writing `expr` of type `ty` to public output, one field
+/// element at a time. False when `ty` holds values that cannot be
+/// written.
+fn write_value(expr: &str, ty: &Ty, out: &mut Vec<String>, fresh: &mut usize) -> bool {
+    match ty {
+        Ty::Field => out.push(format!("pub_write({})", expr)),
+        Ty::U32 => out.push(format!("pub_write(as_field({}))", expr)),
+        Ty::Bool => out.push(format!("pub_write(if {} {{ 1 }} else {{ 0 }})", expr)),
+        Ty::Unit => {}
+        Ty::Digest(n) => {
+            return destructure(expr, &vec![Ty::Field; *n as usize], out, fresh);
+        }
+        Ty::Tuple(_) | Ty::NamedTuple(_) => {
+            let parts = ty.tuple_elements().unwrap_or_default();
+            return destructure(expr, &parts, out, fresh);
+        }
+        Ty::Array(inner, n) => {
+            return (0..*n).all(|i| write_value(&format!("{}[{}]", expr, i), inner, out, fresh));
+        }
+        Ty::Bytes(n) => {
+            return (0..*n).all(|i| write_value(&format!("{}[{}]", expr, i), &Ty::U32, out, fresh));
+        }
+        Ty::Struct(s) => {
+            return s
+                .fields
+                .iter()
+                .all(|(name, fty, _)| write_value(&format!("{}.{}", expr, name), fty, out, fresh));
+        }
+        Ty::XField(_) | Ty::Option(_) => return false,
+    }
+    true
+}
+
+/// Bind the components of `expr` to fresh names and write each.
+fn destructure(expr: &str, parts: &[Ty], out: &mut Vec<String>, fresh: &mut usize) -> bool {
+    let names: Vec<String> = parts
+        .iter()
+        .map(|_| {
+            *fresh += 1;
+            format!("repl_part{}", fresh)
+        })
+        .collect();
+    out.push(format!("let ({}) = {}", names.join(", "), expr));
+    names
+        .iter()
+        .zip(parts)
+        .all(|(name, ty)| write_value(name, ty, out, fresh))
+}
+
+/// Read a value of type `ty` back from the elements `write_value` wrote.
+fn format_value<'a>(ty: &Ty, values: &mut impl Iterator<Item = &'a u64>) -> String {
+    match ty {
+        Ty::Field | Ty::U32 => next(values).to_string(),
+        Ty::Bool => (next(values) != 0).to_string(),
+        Ty::Digest(n) => {
+            let parts: Vec<String> = (0..*n).map(|_| next(values).to_string()).collect();
+            format!("[{}]", parts.join(", "))
+        }
+        Ty::Array(inner, n) => {
+            let parts: Vec<String> = (0..*n).map(|_| format_value(inner, values)).collect();
+            format!("[{}]", parts.join(", "))
+        }
+        Ty::Bytes(n) => {
+            let parts: Vec<String> = (0..*n).map(|_| next(values).to_string()).collect();
+            format!("[{}]", parts.join(", "))
+        }
+        Ty::Tuple(elems) => {
+            let parts: Vec<String> = elems.iter().map(|t| format_value(t, values)).collect();
+            format!("({})", parts.join(", "))
+        }
+        Ty::NamedTuple(components) => {
+            let parts: Vec<String> = components
+                .iter()
+                .map(|(name, t)| format!("{}: {}", name, format_value(t, values)))
+                .collect();
+            format!("({})", parts.join(", "))
+        }
+        Ty::Struct(s) => {
+            let parts: Vec<String> = s
+                .fields
+                .iter()
+                .map(|(name, t, _)| format!("{}: {}", name, format_value(t, values)))
+                .collect();
+            format!("{} {{ {} }}", s.name, parts.join(", "))
+        }
+        Ty::XField(_) | Ty::Option(_) | Ty::Unit => String::new(),
+    }
+}
+
+fn next<'a>(values: &mut impl Iterator<Item = &'a u64>) -> u64 {
+    values.next().copied().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_keeps_definitions_and_evaluates_expressions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut session = ReplSession::new(dir.path(), CompileOptions::default());
+        assert!(matches!(
+            session.eval("fn square(x: Field) -> Field {\n    x * x\n}"),
+            Ok(ReplOutcome::Defined(_))
+        ));
+        assert!(session.eval("let y: Field = pub_read()").is_ok());
+        assert!(session.eval("let z: Field = missing").is_err());
+        assert_eq!(session.bindings.len(), 1);
+
+        session.input.public = vec![3];
+        let Ok(ReplOutcome::Evaluated(value)) = session.eval("square(y) + 1") else {
+            panic!("expression should evaluate");
+        };
+        assert_eq!(value.ty, "Field");
+        assert_eq!(value.value.as_deref(), Some("10"));
+        assert!(value.cycles > 0);
+
+        let Ok(ReplOutcome::Evaluated(value)) = session.eval("(y == 3, as_u32(y))") else {
+            panic!("tuple should evaluate");
+        };
+        assert_eq!(value.value.as_deref(), Some("(true, 3)"));
+        assert!(dir.path().read_dir().unwrap().next().is_none());
+    }
+}
@@ -0,0 +1,277 @@
+//! Language conformance specs.
+//!
+//! A spec is a `.tri` program whose leading comments state what the
+//! compiler and VM must do with it:
+//!
+//! ```text
+//! // spec: compile-ok              type-checks and compiles
+//! // spec: error "undefined"       fails with an error containing the text
+//! // spec: output 12 7             runs, halts, and writes exactly 12 7
+//! // spec: trap "assert"           runs and fails (text optional)
+//! // spec: input 3 4               public input for output/trap specs
+//! // spec: secret 5                secret input for output/trap specs
+//! ```
+//!
+//! Diagnostics carry no numeric codes, so `error` matches the message
+//! text; hint codes such as `H0003` are part of that text. Several
+//! `error` lines must each match some error. Specs live in one directory
+//! per language area (`tests/spec/types/…`) and run on the TASM emulator
+//! (`runtime::debug`), from `cargo test` and `trident test --spec`.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::diagnostic::Severity;
+use crate::runtime::debug::{Debugger, InputKind, Stop, DEFAULT_CYCLE_LIMIT};
+use crate::runtime::input::parse_words;
+use crate::runtime::ProgramInput;
+
+use super::{CompileOptions, TestReport, TestResult, TestSelection};
+
+/// The outcome a spec's header requires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpecExpectation {
+    /// The program compiles without errors.
+    CompileOk,
+    /// Compilation fails with errors containing each of these texts.
+    Error(Vec<String>),
+    /// The program halts and its public output is exactly this.
+    Output(Vec<u64>),
+    /// The program fails at run time, with an error containing the text.
+    Trap(Option<String>),
+}
+
+/// A parsed spec header.
+#[derive(Clone, Debug)]
+pub struct SpecHeader {
+    pub expect: SpecExpectation,
+    pub input: ProgramInput,
+}
+
+/// Read the `// spec:` lines among the leading comments of `source`.
+pub fn parse_spec_header(source: &str) -> Result<SpecHeader, String> {
+    let mut expect = None;
+    let mut errors = Vec::new();
+    let mut input = ProgramInput::default();
+    let set = |expect: &mut Option<SpecExpectation>, e: SpecExpectation| {
+        if expect.is_some() {
+            return Err("a spec states one outcome".to_string());
+        }
+        *expect = Some(e);
+        Ok(())
+    };
+
+    for line in source.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        let Some(comment) = line.strip_prefix("//") else {
+            break;
+        };
+        let Some(directive) = comment.trim().strip_prefix("spec:") else {
+            continue;
+        };
+        let (keyword, rest) = directive
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((directive.trim(), ""));
+        let rest = rest.trim();
+        match keyword {
+            "compile-ok" => set(&mut expect, SpecExpectation::CompileOk)?,
+            "error" if rest.is_empty() => return Err("`error` needs a message".to_string()),
+            "error" => errors.push(unquote(rest).to_string()),
+            "output" => set(&mut expect, SpecExpectation::Output(parse_words(rest)?))?,
+            "trap" => set(
+                &mut expect,
+                SpecExpectation::Trap((!rest.is_empty()).then(|| unquote(rest).to_string())),
+            )?,
+            "input" => input.public = parse_words(rest)?,
+            "secret" => input.secret = parse_words(rest)?,
+            other => return Err(format!("unknown spec directive '{}'", other)),
+        }
+    }
+
+    if !errors.is_empty() {
+        set(&mut expect, SpecExpectation::Error(errors))?;
+    }
+    let expect = expect.ok_or("no `// spec:` outcome in the leading comments")?;
+    let runs = matches!(
+        expect,
+        SpecExpectation::Output(_) | SpecExpectation::Trap(_)
+    );
+    if !runs && (!input.public.is_empty() || !input.secret.is_empty()) {
+        return Err("`input` and `secret` only apply to output and trap specs".to_string());
+    }
+    Ok(SpecHeader { expect, input })
+}
+
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+/// Check one spec against its header. `Ok` means it conforms.
+pub fn run_spec(
+    source: &str,
+    filename: &str,
+    options: &CompileOptions,
+    max_cycles: Option<u64>,
+) -> Result<(), String> {
+    let header = parse_spec_header(source)?;
+    let options = options.clone().silent();
+
+    // Diagnostics come back silently, so expected errors do not reach
+    // stderr; only a spec that type-checks is compiled.
+    let mut errors: Vec<String> = super::collect_diagnostics(source, filename)
+        .into_iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(|d| d.message)
+        .collect();
+    let tasm = if errors.is_empty() {
+        match super::compile_with_options(source, filename, &options) {
+            Ok(tasm) => Some(tasm),
+            Err(diagnostics) => {
+                errors = diagnostics.into_iter().map(|d| d.message).collect();
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let tasm = match (&header.expect, tasm) {
+        (SpecExpectation::Error(expected), None) => {
+            return match expected
+                .iter()
+                .find(|e| !errors.iter().any(|m| m.contains(e.as_str())))
+            {
+                None => Ok(()),
+                Some(e) => Err(format!(
+                    "expected an error containing \"{}\", got: {}",
+                    e,
+                    errors.join("; ")
+                )),
+            };
+        }
+        (SpecExpectation::Error(_), Some(_)) => {
+            return Err("expected an error, but it compiled".to_string())
+        }
+        (_, None) => return Err(format!("does not compile: {}", errors.join("; "))),
+        (SpecExpectation::CompileOk, Some(_)) => return Ok(()),
+        (_, Some(tasm)) => tasm,
+    };
+
+    let limit = max_cycles.unwrap_or(DEFAULT_CYCLE_LIMIT);
+    let mut vm = Debugger::new(&tasm, &header.input);
+    vm.set_cycle_limit(limit);
+    match (vm.resume(), &header.expect) {
+        (Stop::Halted, SpecExpectation::Output(expected)) if vm.output() == expected => Ok(()),
+        (Stop::Halted, SpecExpectation::Output(expected)) => Err(format!(
+            "expected output {:?}, got {:?}",
+            expected,
+            vm.output()
+        )),
+        (Stop::Halted, _) => Err("expected a trap, but it halted".to_string()),
+        (Stop::Error(msg), SpecExpectation::Trap(expected)) => match expected {
+            Some(e) if !msg.contains(e.as_str()) => Err(format!(
+                "expected a trap containing \"{}\", got: {}",
+                e, msg
+            )),
+            _ => Ok(()),
+        },
+        (Stop::Error(msg), _) => Err(format!("trapped: {}", msg)),
+        (Stop::CycleLimit, _) => Err(format!("cycle limit of {} exceeded", limit)),
+        (Stop::NeedsInput { kind, .. }, _) => {
+            let stream = match kind {
+                InputKind::Public => "public",
+                InputKind::Secret => "secret",
+            };
+            Err(format!("reads more {} input than the spec gives", stream))
+        }
+        (Stop::Step | Stop::Breakpoint(_), _) => Err("stopped early".to_string()),
+    }
+}
+
+/// Every `.tri` spec under `dir`, sorted by path.
+pub fn discover_specs(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    fn walk(dir: &Path, specs: &mut Vec<PathBuf>) -> Result<(), String> {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| format!("cannot read '{}': {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(&path, specs)?;
+            } else if path.extension().is_some_and(|e| e == "tri") {
+                specs.push(path);
+            }
+        }
+        Ok(())
+    }
+    let mut specs = Vec::new();
+    walk(dir, &mut specs)?;
+    specs.sort();
+    Ok(specs)
+}
+
+/// Run the specs under `dir` that `selection` picks, in parallel. A
+/// spec's module is its area directory and its name the file stem.
+pub fn run_spec_suite(
+    dir: &Path,
+    options: &CompileOptions,
+    selection: &TestSelection,
+) -> Result<TestReport, String> {
+    use rayon::prelude::*;
+
+    let specs: Vec<(String, String, PathBuf)> = discover_specs(dir)?
+        .into_iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(dir).unwrap_or(&path);
+            let module = relative
+                .parent()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_default();
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            selection
+                .matches(&module, &name)
+                .then_some((module, name, path))
+        })
+        .collect();
+
+    let run = |(module, name, path): &(String, String, PathBuf)| {
+        let started = Instant::now();
+        let outcome = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read '{}': {}", path.display(), e))
+            .and_then(|source| {
+                run_spec(
+                    &source,
+                    &path.to_string_lossy(),
+                    options,
+                    selection.max_cycles,
+                )
+            });
+        TestResult {
+            name: if module.is_empty() {
+                name.clone()
+            } else {
+                format!("{}/{}", module, name)
+            },
+            module: module.clone(),
+            passed: outcome.is_ok(),
+            compile_only: false,
+            cost: None,
+            error: outcome.err(),
+            duration: started.elapsed(),
+        }
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(selection.jobs)
+        .build()
+        .map_err(|e| format!("cannot start test threads: {}", e))?;
+    let results = pool.install(|| specs.par_iter().map(run).collect());
+
+    Ok(TestReport {
+        results,
+        ..TestReport::default()
+    })
+}
@@ -0,0 +1,202 @@
+use crate::runtime::artifact::json_string;
+
+use super::TestResult;
+
+/// Structured outcome of `run_tests`, renderable as the human summary,
+/// JSON, or JUnit XML for CI systems.
+#[derive(Clone, Debug, Default)]
+pub struct TestReport {
+    pub results: Vec<TestResult>,
+    /// Cost table names (e.g. "processor", "hash"), indexing `TestResult::cost`.
+    pub table_names: Vec<String>,
+    /// Short table names for the text summary (e.g. "cc", "hash").
+    pub short_names: Vec<String>,
+}
+
+impl TestReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.passed).count()
+    }
+
+    /// Tests that compiled but reached an instruction the emulator fakes.
+    pub fn compile_only(&self) -> usize {
+        self.results.iter().filter(|r| r.compile_only).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed() - self.compile_only()
+    }
+
+    /// Human-readable summary, in the style of `cargo test`.
+    pub fn to_text(&self) -> String {
+        if self.results.is_empty() {
+            return "No #[test] functions found.\n".to_string();
+        }
+
+        let mut report = String::new();
+        let total = self.results.len();
+        report.push_str(&format!(
+            "running {} test{}\n",
+            total,
+            if total == 1 { "" } else { "s" }
+        ));
+
+        let sn: Vec<&str> = self.short_names.iter().map(|s| s.as_str()).collect();
+        for result in &self.results {
+            let status = match (result.passed, result.compile_only) {
+                (true, _) => "ok",
+                (false, true) => "compile-only",
+                (false, false) => "FAILED",
+            };
+            let cost_str = match result.cost.as_ref().map(|c| c.format_annotation(&sn)) {
+                Some(ann) if !ann.is_empty() => format!(" ({})", ann),
+                _ => String::new(),
+            };
+            report.push_str(&format!(
+                "  test {} ... {}{}\n",
+                result.name, status, cost_str
+            ));
+            if let Some(ref err) = result.error {
+                let label = if result.compile_only { "note" } else { "error" };
+                report.push_str(&format!("    {}: {}\n", label, err));
+            }
+        }
+
+        report.push('\n');
+        if self.failed() == 0 {
+            report.push_str(&format!(
+                "test result: ok. {} passed; 0 failed",
+                self.passed()
+            ));
+        } else {
+            report.push_str(&format!(
+                "test result: FAILED. {} passed; {} failed",
+                self.passed(),
+                self.failed()
+            ));
+        }
+        if self.compile_only() > 0 {
+            report.push_str(&format!("; {} compile-only", self.compile_only()));
+        }
+        report.push('\n');
+        report
+    }
+
+    /// JSON report: one object per test plus pass, compile-only and fail
+    /// totals.
+    pub fn to_json(&self) -> String {
+        let names: Vec<&str> = self.table_names.iter().map(|s| s.as_str()).collect();
+        let mut out = String::from("{\n  \"tests\": [\n");
+        for (i, r) in self.results.iter().enumerate() {
+            let comma = if i + 1 < self.results.len() { "," } else { "" };
+            out.push_str(&format!(
+                "    {{ \"name\": {}, \"module\": {}, \"passed\": {}, \"compile_only\": {}, \"duration_ms\": {:.3}, \"cost\": {}, \"error\": {} }}{}\n",
+                json_string(&r.name),
+                json_string(&r.module),
+                r.passed,
+                r.compile_only,
+                r.duration.as_secs_f64() * 1000.0,
+                r.cost
+                    .as_ref()
+                    .map(|c| c.to_json_value(&names))
+                    .unwrap_or_else(|| "null".to_string()),
+                r.error
+                    .as_deref()
+                    .map(json_string)
+                    .unwrap_or_else(|| "null".to_string()),
+                comma,
+            ));
+        }
+        out.push_str("  ],\n");
+        out.push_str(&format!("  \"passed\": {},\n", self.passed()));
+        out.push_str(&format!("  \"compile_only\": {},\n", self.compile_only()));
+        out.push_str(&format!("  \"failed\": {}\n", self.failed()));
+        out.push_str("}\n");
+        out
+    }
+
+    /// JUnit XML report: one `<testsuite>` per module. Costs are attached
+    /// as `cost.<table>` properties on each `<testcase>`; compile-only
+    /// tests are `<skipped>`.
+    pub fn to_junit(&self) -> String {
+        let mut modules: Vec<&str> = Vec::new();
+        for r in &self.results {
+            if !modules.contains(&r.module.as_str()) {
+                modules.push(&r.module);
+            }
+        }
+        let all: Vec<&TestResult> = self.results.iter().collect();
+
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str(&format!(
+            "<testsuites name=\"trident\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            self.results.len(),
+            self.failed(),
+            self.compile_only(),
+            seconds(&all)
+        ));
+        for module in modules {
+            let suite: Vec<&TestResult> =
+                all.iter().copied().filter(|r| r.module == module).collect();
+            let skipped = suite.iter().filter(|r| r.compile_only).count();
+            let failures = suite.iter().filter(|r| !r.passed).count() - skipped;
+            out.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+                xml_escape(module),
+                suite.len(),
+                failures,
+                skipped,
+                seconds(&suite)
+            ));
+            for r in suite {
+                out.push_str(&format!(
+                    "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\">\n",
+                    xml_escape(&r.name),
+                    xml_escape(module),
+                    r.duration.as_secs_f64()
+                ));
+                if let Some(ref cost) = r.cost {
+                    out.push_str("      <properties>\n");
+                    let tables = self.table_names.iter().take(cost.count as usize);
+                    for (i, table) in tables.enumerate() {
+                        out.push_str(&format!(
+                            "        <property name=\"cost.{}\" value=\"{}\"/>\n",
+                            xml_escape(table),
+                            cost.get(i)
+                        ));
+                    }
+                    out.push_str("      </properties>\n");
+                }
+                if r.compile_only {
+                    let message = r.error.as_deref().unwrap_or("compile-only");
+                    out.push_str(&format!(
+                        "      <skipped message=\"{}\"/>\n",
+                        xml_escape(message)
+                    ));
+                } else if !r.passed {
+                    let message = r.error.as_deref().unwrap_or("test failed");
+                    out.push_str(&format!(
+                        "      <failure message=\"{}\"/>\n",
+                        xml_escape(message)
+                    ));
+                }
+                out.push_str("    </testcase>\n");
+            }
+            out.push_str("  </testsuite>\n");
+        }
+        out.push_str("</testsuites>\n");
+        out
+    }
+}
+
+fn seconds(results: &[&TestResult]) -> f64 {
+    results.iter().map(|r| r.duration.as_secs_f64()).sum()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -125,3 +125,72 @@ fn test_test_fn_type_check_return_rejected() {
     );
 }
 
+
+#[test]
+fn test_all_targets_matrix_flags_digest_width_dependent_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let portable = dir.path().join("portable.tri");
+    std::fs::write(
+        &portable,
+        "program portable\nfn main() {\n    pub_write(pub_read() + 1)\n}\n",
+    )
+    .unwrap();
+    let tip5 = dir.path().join("tip5.tri");
+    std::fs::write(
+        &tip5,
+        "program tip5\nfn main() {\n    let d: Digest = pub_read5()\n    pub_write(pub_read())\n}\n",
+    )
+    .unwrap();
+    let options = CompileOptions::default();
+
+    // Every registered target either loads and checks, or is skipped.
+    let results = check_project_all_targets(&portable, &options, true);
+    let triton = results.iter().find(|r| r.target == "triton").unwrap();
+    assert!(triton.passed());
+    assert!(triton.cost.is_some());
+    for r in &results {
+        assert!(r.passed() || r.skipped.is_some(), "{}: {:?}", r.target, r.errors);
+    }
+
+    // A target with 4-element digests has no pub_read5.
+    let targets = || {
+        vec![
+            ("triton".to_string(), Ok(TerrainConfig::triton())),
+            (
+                "digest4".to_string(),
+                Ok(TerrainConfig {
+                    name: "digest4".to_string(),
+                    digest_width: 4,
+                    ..TerrainConfig::triton()
+                }),
+            ),
+            (
+                "unknown".to_string(),
+                Err(Diagnostic::error(
+                    "unknown target".to_string(),
+                    crate::span::Span::dummy(),
+                )),
+            ),
+        ]
+    };
+    let results = check_project_targets(&portable, &options, targets(), false);
+    let outcomes: Vec<(&str, bool, bool)> = results
+        .iter()
+        .map(|r| (r.target.as_str(), r.passed(), r.failed()))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            ("triton", true, false),
+            ("digest4", true, false),
+            ("unknown", false, false)
+        ]
+    );
+    let results = check_project_targets(&tip5, &options, targets(), false);
+    assert!(results[0].passed() && results[0].cost.is_none());
+    assert!(
+        results[1].failed(),
+        "pub_read5 does not exist with 4-element digests"
+    );
+    assert_eq!(results[2].skipped.as_deref(), Some("unknown target"));
+}
@@ -18,6 +18,48 @@ fn test_compile_type_error_returns_err() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_compile_errors_carry_their_stage() {
+    let _quiet = crate::diagnostic::suppress_warnings();
+    let parse = compile("program test\nfn main( {\n}", "test.tri").unwrap_err();
+    assert!(matches!(parse, CompileError::Parse(_)), "{}", parse);
+
+    let typed = compile("program test\nfn main() {\n    pub_write(y)\n}", "test.tri").unwrap_err();
+    assert!(matches!(typed, CompileError::Type(_)), "{}", typed);
+    assert_eq!(typed.stage(), "type");
+    assert!(typed[0].message.contains('y'));
+    assert!(typed.to_string().starts_with("type error: "));
+
+    let missing = compile_project(Path::new("/nonexistent/main.tri")).unwrap_err();
+    assert!(matches!(missing, CompileError::Io(_)), "{}", missing);
+
+    // Callers written against `Vec<Diagnostic>` keep working.
+    let diagnostics: Vec<Diagnostic> = typed.clone().into();
+    assert_eq!(diagnostics.len(), typed.len());
+}
+
+#[test]
+fn test_compile_project_reports_to_the_options_sink() {
+    use crate::diagnostic::CollectingSink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(&path, "program main\nfn main() {\n    pub_write(y)\n}\n").unwrap();
+    let sink = Arc::new(CollectingSink::new());
+    let options = CompileOptions::default().with_sink(sink.clone());
+    let error = compile_project_with_options(&path, &options).unwrap_err();
+
+    let reported = sink.take();
+    assert_eq!(reported.len(), error.len());
+    assert!(reported[0].0.ends_with("main.tri"));
+    assert_eq!(reported[0].1.message, error[0].message);
+    assert!(sink.take().is_empty());
+
+    let silent = compile_project_with_options(&path, &options.clone().silent());
+    assert!(silent.is_err());
+    assert!(sink.take().is_empty());
+}
+
 #[test]
 fn test_deeply_nested_if() {
     let source = r#"program test
@@ -190,6 +232,310 @@ pub_write(double(pub_read()))
     assert!(compile(source, "test.tri").is_ok());
 }
 
+#[test]
+fn test_wide_param_tail_call_not_skipped() {
+    // Digest in, Digest out: the stack already holds ret-width elements
+    // before the tail, but the tail is a call and must still be emitted.
+    let source = r#"program test
+fn tag(h: Digest, i: U32) -> Digest {
+    pub_write(as_field(i))
+    h
+}
+fn wrap(h: Digest) -> Digest {
+    tag(h, as_u32(5))
+}
+fn main() {
+    let (a, _, _, _, _) = wrap(pub_read5())
+    pub_write(a)
+}
+"#;
+    let tasm = compile(source, "test.tri").unwrap();
+    assert!(tasm.contains("call __tag"));
+    assert!(tasm.contains("__tag:"));
+}
+
+#[test]
+fn test_bare_module_constant_in_expression() {
+    let source = r#"program test
+const OFFSET: Field = 42
+fn main() {
+    pub_write(pub_read() + OFFSET)
+}
+"#;
+    let tasm = compile(source, "test.tri").unwrap();
+    assert!(tasm.contains("push 42"));
+}
+
+fn ten_live_locals_program() -> String {
+    let mut source = String::from("program test\nfn main() {\n");
+    for i in 0..10 {
+        source.push_str(&format!("    let v{}: Field = pub_read()\n", i));
+    }
+    for i in 0..10 {
+        source.push_str(&format!("    pub_write(v{})\n", i));
+    }
+    source.push_str("}\n");
+    source
+}
+
+#[test]
+fn test_validate_codegen_accepts_loop_body_locals() {
+    let source = "program test\nfn main() {\n    for i in 0..4 bounded 4 {\n        let x: Field = pub_read()\n        pub_write(x)\n    }\n}\n";
+    let options = CompileOptions {
+        validate_codegen: true,
+        ..CompileOptions::default()
+    };
+    // The body drops its local before the next iteration.
+    let tasm = compile_with_options(source, "test.tri", &options).unwrap();
+    assert!(tasm.contains("write_io 1\n    pop 1\n"), "{}", tasm);
+
+    let clean = ten_live_locals_program();
+    assert!(compile_with_options(&clean, "test.tri", &options).is_ok());
+}
+
+#[test]
+fn test_no_spill_fails_when_function_would_spill() {
+    let program = |live: usize| {
+        let mut source = String::from("program test\n#[no_spill]\nfn main() {\n");
+        for i in 0..live {
+            source.push_str(&format!("    let v{}: Field = pub_read()\n", i));
+        }
+        for i in 0..live {
+            source.push_str(&format!("    pub_write(v{})\n", i));
+        }
+        source.push_str("}\n");
+        source
+    };
+    let tasm = compile(&program(10), "test.tri").unwrap();
+    assert!(!tasm.contains("write_mem"), "{}", tasm);
+
+    // Fails without --validate-codegen.
+    let source = program(18);
+    let errors = compile(&source, "test.tri").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(
+        errors[0].message.contains("'main' is #[no_spill]"),
+        "{}",
+        errors[0].message
+    );
+    assert!(
+        errors[0].notes[0].contains("16-element stack filled up: v15 (1), v14 (1)"),
+        "{:?}",
+        errors[0].notes
+    );
+    assert_eq!(&source[errors[0].span.start as usize..][..7], "let v16");
+}
+
+#[test]
+fn test_commutative_operands_schedule_deeper_side_first() {
+    // 14 live locals: `v0 + v1 * v2` evaluated left to right peaks at
+    // 17 elements and spills; the product first peaks at 16.
+    let program = |expr: &str| {
+        let mut source = String::from("program test\nfn main() {\n");
+        for i in 0..14 {
+            source.push_str(&format!("    let v{}: Field = pub_read()\n", i));
+        }
+        source.push_str(&format!("    pub_write({})\n}}\n", expr));
+        source
+    };
+    let scheduled = compile(&program("v0 + v1 * v2"), "test.tri").unwrap();
+    assert!(!scheduled.contains("write_mem"), "{}", scheduled);
+    let reversed = compile(&program("v1 * v2 + v0"), "test.tri").unwrap();
+    assert_eq!(scheduled, reversed);
+
+    // Effects keep their order.
+    let reads = compile(&program("pub_read() + pub_read() * v1"), "test.tri").unwrap();
+    let swapped = compile(&program("pub_read() * v1 + pub_read()"), "test.tri").unwrap();
+    assert_ne!(reads, swapped);
+}
+
+#[test]
+fn test_adjacent_field_absorbs_match_explicit_sponge_absorb() {
+    let names: Vec<String> = (0..12).map(|i| format!("f{}", i)).collect();
+    let program = |body: &str| {
+        let mut source = String::from("program test\nfn main() {\n    sponge_init()\n");
+        for name in &names {
+            source.push_str(&format!("    let {}: Field = pub_read()\n", name));
+        }
+        source.push_str(body);
+        source.push_str("    let d: [Field; 10] = sponge_squeeze()\n    pub_write(d[0])\n}\n");
+        source
+    };
+    let packed: String = names
+        .iter()
+        .map(|name| format!("    sponge_absorb_field({})\n", name))
+        .collect();
+    let reference = format!(
+        "    sponge_absorb({})\n    sponge_absorb({}, {}, 1, 0, 0, 0, 0, 0, 0, 0)\n",
+        names[..10].join(", "),
+        names[10],
+        names[11]
+    );
+    let packed = compile(&program(&packed), "test.tri").unwrap();
+    assert_eq!(packed, compile(&program(&reference), "test.tri").unwrap());
+    assert_eq!(packed.matches("sponge_absorb").count(), 2, "{}", packed);
+
+    // A lone call still absorbs a full block: the field, 1, then zeros.
+    let lone = compile(&program("    sponge_absorb_field(f0)\n"), "test.tri").unwrap();
+    let explicit = compile(
+        &program("    sponge_absorb(f0, 1, 0, 0, 0, 0, 0, 0, 0, 0)\n"),
+        "test.tri",
+    )
+    .unwrap();
+    assert_eq!(lone, explicit);
+}
+
+#[test]
+fn test_miden_spills_earlier_than_triton() {
+    let source = ten_live_locals_program();
+    let triton = compile(&source, "test.tri").expect("triton compile");
+    assert!(
+        !triton.contains("write_mem"),
+        "10 locals fit Triton's window"
+    );
+
+    let mut options = CompileOptions::default();
+    options.target_config.name = "miden".to_string();
+    let miden = compile_with_options(&source, "test.tri", &options).expect("miden compile");
+    assert!(
+        miden.contains("write_mem"),
+        "Miden spills before deep swaps get expensive"
+    );
+}
+
+#[test]
+fn test_intrinsic_above_target_tier_is_compile_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(
+        &path,
+        "program main\nuse vm.crypto.hash\nfn main() {\n    hash.sponge_init()\n}\n",
+    )
+    .unwrap();
+    compile_project(&path).expect("triton executes tier 2");
+
+    let mut options = CompileOptions::default();
+    options.target_config.name = "evm".to_string();
+    let errors = compile_project_with_options(&path, &options).unwrap_err();
+    assert!(
+        errors[0].message.contains(
+            "'hash.sponge_init' needs tier 2 (provable), but target 'evm' supports tiers 0-1"
+        ),
+        "{}",
+        errors[0].message
+    );
+    assert!(errors[0].help.is_some());
+}
+
+#[test]
+fn test_sec_ram_overlapping_spill_region_is_compile_error() {
+    let source = r#"program test
+sec ram: { 1073741824: Field }
+fn main() {
+    pub_write(pub_read())
+}
+"#;
+    let errors = compile(source, "test.tri").unwrap_err();
+    assert!(errors[0].message.contains("overlaps spill region"));
+}
+
+#[test]
+fn test_static_ram_access_outside_sec_ram_is_compile_error() {
+    let source = r#"program test
+sec ram: { 17: Field }
+fn main() {
+    pub_write(ram_read(17))
+    ram_write(20, pub_read())
+}
+"#;
+    let errors = compile(source, "test.tri").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .message
+        .contains("RAM write of ram[20] touches undeclared address 20"));
+}
+
+#[test]
+fn test_project_checks_static_access_through_mem_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(
+        &path,
+        "program main\nuse vm.io.mem\nsec ram: { 40: Digest }\nfn main() {\n    let d: Digest = mem.read_block(40)\n    mem.write_block(43, d)\n}\n",
+    )
+    .unwrap();
+    let errors = ram_layout_project(&path, &CompileOptions::default()).unwrap_err();
+    assert!(errors[0].message.contains("ram[43, 48)"));
+    assert!(errors[0].message.contains("undeclared address 45"));
+}
+
+#[test]
+fn test_ram_layout_project_includes_sec_ram_slots() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(
+        &path,
+        "program main\nsec ram: { 17: Field, 42: Digest }\nfn main() {\n    pub_write(pub_read())\n}\n",
+    )
+    .unwrap();
+    let layout = ram_layout_project(&path, &CompileOptions::default()).unwrap();
+    let map = layout.format_map();
+    assert!(map.contains("[17, 18)"));
+    assert!(map.contains("[42, 47)"));
+    assert!(map.contains("stack spills"));
+}
+
+#[test]
+fn test_abi_project_lists_entry_pub_fns_and_events() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(
+        &path,
+        "program app\nevent Paid {\n    amount: Field,\n}\npub fn double(x: Field) -> Field {\n    x + x\n}\nfn helper() {\n}\nfn main() {\n    pub_write(double(pub_read()))\n}\n",
+    )
+    .unwrap();
+    let abi = abi_project(&path, &CompileOptions::default()).unwrap();
+    assert_eq!(abi.entry_point.as_deref(), Some("main"));
+    let names: Vec<&str> = abi.functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["double", "main"],
+        "private helpers stay out of the ABI"
+    );
+    assert_eq!(abi.functions[0].params[0].ty, "Field");
+    assert_eq!(abi.functions[0].returns.as_deref(), Some("Field"));
+    assert_eq!(abi.events[0].fields[0].name, "amount");
+    let json = abi.to_json();
+    assert!(json.contains("\"entry_point\": \"main\""));
+    assert!(json.contains("\"structs\": []"));
+}
+
+#[test]
+fn test_entry_functions_dispatch_on_tag_and_list_in_abi() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(
+        &path,
+        "program token\n#[entry]\npub fn pay() {\n    pub_write(pub_read())\n}\n#[entry]\npub fn mint() {\n    pub_write(pub_read() + 1)\n}\n#[entry]\npub fn burn() {\n    assert(pub_read() == 0)\n}\n",
+    )
+    .unwrap();
+    let tasm = compile_project(&path).unwrap();
+    for label in ["call token__pay", "call token__mint", "call token__burn"] {
+        assert!(tasm.contains(label), "dispatcher should {}", label);
+    }
+    let abi = abi_project(&path, &CompileOptions::default()).unwrap();
+    assert_eq!(abi.entry_point.as_deref(), Some("main"));
+    let tags: Vec<(u64, &str)> = abi
+        .entries
+        .iter()
+        .map(|e| (e.tag, e.name.as_str()))
+        .collect();
+    assert_eq!(tags, [(0, "pay"), (1, "mint"), (2, "burn")]);
+    assert!(abi
+        .to_json()
+        .contains("\"entries\": [\n    { \"tag\": 0, \"name\": \"pay\" },"));
+}
+
 #[test]
 fn test_multiple_return_paths() {
     let source = r#"program test
@@ -291,6 +637,20 @@ fn test_error_max_nesting_depth() {
     handle.join().expect("test thread panicked");
 }
 
+#[test]
+fn test_long_operator_chain_counts_toward_nesting_depth() {
+    // A left-associative chain is parsed by a loop but builds a tree as
+    // deep as the chain is long; later passes recurse over it.
+    let terms = vec!["1"; 5000].join(" + ");
+    let src = format!("program t\nfn main() {{\n    pub_write({})\n}}\n", terms);
+    let diags = crate::check_silent(&src, "t.tri").unwrap_err();
+    assert!(
+        diags.iter().any(|d| d.message.contains("nesting depth")),
+        "should report nesting depth exceeded, got: {:?}",
+        diags.iter().map(|d| &d.message).collect::<Vec<_>>()
+    );
+}
+
 #[test]
 fn test_coin_compiles() {
     let path = std::path::Path::new("os/neptune/standards/coin.tri");
@@ -399,3 +759,1082 @@ fn test_card_compiles() {
         "should authenticate leaves against Merkle root"
     );
 }
+
+#[test]
+fn test_builtin_table_lowerings_match_emitted_tasm() {
+    let calls = [
+        ("pub_read", "let a: Field = pub_read()\n    pub_write(a)"),
+        ("divine5", "let d: Digest = divine5()\n    assert_digest(d, d)"),
+        ("assert_digest", "let d: Digest = divine5()\n    assert_digest(d, d)"),
+        ("split", "let (hi, lo) = split(pub_read())\n    pub_write(as_field(hi))\n    pub_write(as_field(lo))"),
+        ("inv", "pub_write(inv(pub_read()))"),
+        ("neg", "pub_write(neg(pub_read()))"),
+        ("sub", "pub_write(sub(pub_read(), pub_read()))"),
+        ("assert_eq", "assert_eq(pub_read(), pub_read())"),
+        ("ram_read", "pub_write(ram_read(pub_read()))"),
+        ("ram_write", "ram_write(pub_read(), pub_read())"),
+        ("as_u32", "pub_write(as_field(as_u32(pub_read())))"),
+        (
+            "merkle_step",
+            "let x: Field = pub_read()\n    let (i, p) = merkle_step(as_u32(x), x, x, x, x, x)\n    assert_digest(p, p)",
+        ),
+        (
+            "merkle_step_mem",
+            "let x: Field = pub_read()\n    let (ptr, i, p) = merkle_step_mem(x, as_u32(x), x, x, x, x, x)\n    assert_digest(p, p)",
+        ),
+    ];
+    for (name, body) in calls {
+        let source = format!("program test\nfn main() {{\n    {}\n}}", body);
+        let tasm = compile(&source, "test.tri").unwrap_or_else(|_| panic!("{} failed", name));
+        let lines: Vec<&str> = tasm.lines().map(str::trim).collect();
+        let info = typecheck::builtin_info(name).unwrap();
+        let want: Vec<&str> = info.tasm.lines().collect();
+        assert!(
+            lines.windows(want.len()).any(|w| w == want.as_slice()),
+            "{} should lower to {:?}, got:\n{}",
+            name,
+            want,
+            tasm
+        );
+    }
+}
+
+#[test]
+fn test_merkle_walk_unrolls_literal_depth_and_loops_otherwise() {
+    let program = |call: &str| {
+        format!(
+            "program test\nfn main() {{\n    let x: Field = pub_read()\n    let (i, d) = {}\n    assert_digest(d, d)\n}}\n",
+            call
+        )
+    };
+    let steps = |tasm: &str, op: &str| tasm.lines().filter(|l| l.trim() == op).count();
+
+    let unrolled = compile(
+        &program("merkle_walk(as_u32(x), x, x, x, x, x, 3)"),
+        "test.tri",
+    )
+    .unwrap();
+    assert_eq!(steps(&unrolled, "merkle_step"), 3, "{}", unrolled);
+    assert!(!unrolled.contains("recurse"), "{}", unrolled);
+
+    let looped = compile(
+        &program("merkle_walk(as_u32(x), x, x, x, x, x, x)"),
+        "test.tri",
+    )
+    .unwrap();
+    assert_eq!(steps(&looped, "merkle_step"), 1, "{}", looped);
+    let body: Vec<&str> = looped.lines().map(str::trim).collect();
+    assert!(
+        body.windows(4)
+            .any(|w| w == ["place 6", "merkle_step", "pick 6", "recurse"]),
+        "{}",
+        looped
+    );
+
+    // From RAM: the pointer sits at st7, the loop counter below it.
+    let source = "program test\nfn main() {\n    let x: Field = pub_read()\n    let (p, i, d) = merkle_walk_mem(x, as_u32(x), x, x, x, x, x, x)\n    pub_write(p)\n    assert_digest(d, d)\n}\n";
+    let from_ram = compile(source, "test.tri").unwrap();
+    let body: Vec<&str> = from_ram.lines().map(str::trim).collect();
+    assert!(
+        body.windows(4)
+            .any(|w| w == ["place 8", "merkle_step_mem", "pick 8", "recurse"]),
+        "{}",
+        from_ram
+    );
+    assert!(!from_ram.lines().any(|l| l.trim() == "merkle_step"));
+}
+
+#[test]
+fn test_compile_project_rejects_import_of_feature_gated_module() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("crypto")).unwrap();
+    std::fs::write(
+        dir.path().join("crypto/heavy.tri"),
+        "module crypto.heavy\npub fn id(x: Field) -> Field {\n    x\n}\n",
+    )
+    .unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nuse crypto.heavy\nfn main() {\n    pub_write(heavy.id(pub_read()))\n}\n",
+    )
+    .unwrap();
+
+    let mut options = CompileOptions::default();
+    options
+        .gated_modules
+        .insert("crypto.heavy".to_string(), "heavy".to_string());
+    let errors = compile_project_with_options(&entry, &options).unwrap_err();
+    assert!(errors[0]
+        .message
+        .contains("module 'crypto.heavy' requires feature 'heavy'"));
+
+    options.gated_modules.clear();
+    assert!(compile_project_with_options(&entry, &options).is_ok());
+}
+
+#[test]
+fn test_compile_program_split_across_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("supply.tri"),
+        "module token.supply\npub fn cap() -> Field {\n    1000\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("transfers.tri"),
+        "module token.transfers\nuse token.supply\npub fn send(x: Field) -> Field {\n    x + supply.cap()\n}\n",
+    )
+    .unwrap();
+    let entry = dir.path().join("token.tri");
+    std::fs::write(
+        &entry,
+        "program token\nmod supply\nmod transfers\nfn main() {\n    pub_write(transfers.send(pub_read()))\n}\n",
+    )
+    .unwrap();
+
+    let tasm = compile_project(&entry).expect("split program should compile");
+    assert!(tasm.contains("token-transfers__send"), "{}", tasm);
+    assert!(tasm.contains("token-supply__cap"), "{}", tasm);
+}
+
+#[test]
+fn test_named_tuple_components_read_the_matching_stack_slots() {
+    let source = "program test
+fn stats(x: Field, y: Field) -> (sum: Field, product: Field) {
+    (x + y, x * y)
+}
+fn weigh(s: (sum: Field, product: Field)) -> Field {
+    s.sum * 100 + s.product
+}
+fn main() {
+    let s = stats(pub_read(), pub_read())
+    pub_write(s.product)
+    pub_write(s.sum)
+    let t: (lo: Field, hi: Field) = (s.sum, 7)
+    pub_write(t.hi)
+    pub_write(weigh(s))
+}";
+    let tasm = compile(source, "test.tri").unwrap();
+    let input = crate::runtime::ProgramInput {
+        public: vec![3, 4],
+        secret: Vec::new(),
+        digests: Vec::new(),
+    };
+    let mut dbg = crate::runtime::debug::Debugger::new(&tasm, &input);
+    assert_eq!(dbg.resume(), crate::runtime::debug::Stop::Halted);
+    assert_eq!(dbg.output(), &[12, 7, 7, 712]);
+}
+
+#[test]
+fn test_option_values_flow_through_calls_and_matches() {
+    let source = "program test
+fn find(x: Field) -> Option<Field> {
+    (if x == 0 { None } else { Some(x + 1) })
+}
+fn halve(x: Field) -> Option<Field> {
+    if x == 0 {
+        None
+    } else if x == 1 {
+        None
+    } else {
+        Some(x * inv(2))
+    }
+}
+fn unwrap_or(o: Option<Field>, default: Field) -> Field {
+    let mut r: Field = default
+    match o {
+        Some(v) => { r = v }
+        None => {}
+    }
+    r
+}
+fn main() {
+    pub_write(unwrap_or(find(pub_read()), 7))
+    pub_write(unwrap_or(find(pub_read()), 7))
+    match find(pub_read()) {
+        Some(_) => { pub_write(1) }
+        _ => { pub_write(0) }
+    }
+    let known = Some(5)
+    match known {
+        Some(v) => { pub_write(v) }
+        None => { pub_write(99) }
+    }
+    pub_write(unwrap_or(halve(pub_read()), 7))
+    pub_write(unwrap_or(halve(pub_read()), 7))
+}";
+    let tasm = compile(source, "test.tri").unwrap();
+    assert!(
+        !tasm.contains("push 99"),
+        "the None arm of a known Some should not be built:\n{}",
+        tasm
+    );
+    let input = crate::runtime::ProgramInput {
+        public: vec![0, 4, 3, 1, 8],
+        secret: Vec::new(),
+        digests: Vec::new(),
+    };
+    let mut dbg = crate::runtime::debug::Debugger::new(&tasm, &input);
+    assert_eq!(dbg.resume(), crate::runtime::debug::Stop::Halted);
+    assert_eq!(dbg.output(), &[7, 5, 1, 5, 7, 4]);
+}
+
+#[test]
+fn test_ensure_writes_its_code_before_halting() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    let source = "program test\nconst E_BAD_INPUT: U32 = 7\nfn main() {\n    let x = pub_read()\n    ensure(x == 3, E_BAD_INPUT)\n    pub_write(x)\n}";
+    let tasm = compile(source, "test.tri").unwrap();
+    let run = |value: u64| {
+        let input = crate::runtime::ProgramInput {
+            public: vec![value],
+            secret: Vec::new(),
+            digests: Vec::new(),
+        };
+        let mut dbg = Debugger::new(&tasm, &input);
+        (dbg.resume(), dbg.output().to_vec())
+    };
+    assert_eq!(run(3), (Stop::Halted, vec![3]));
+    let (stop, output) = run(4);
+    assert!(matches!(stop, Stop::Error(msg) if msg.contains("assert")));
+    assert_eq!(output, [7]);
+}
+
+#[test]
+fn test_abi_lists_ensure_error_codes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(
+        &path,
+        "program app\nconst E_ZERO: U32 = 1\nfn check(x: Field) {\n    ensure(x == 0, 2)\n}\nfn main() {\n    let x = pub_read()\n    ensure(x == 1, E_ZERO)\n    check(pub_read())\n}\n",
+    )
+    .unwrap();
+    let abi = abi_project(&path, &CompileOptions::default()).unwrap();
+    let errors: Vec<(u64, Option<&str>, &str)> = abi
+        .errors
+        .iter()
+        .map(|e| (e.code, e.name.as_deref(), e.function.as_str()))
+        .collect();
+    assert_eq!(errors, [(2, None, "check"), (1, Some("E_ZERO"), "main")]);
+    assert!(abi
+        .to_json()
+        .contains("{ \"code\": 1, \"name\": \"E_ZERO\", \"function\": \"main\" }"));
+}
+
+#[test]
+fn test_debug_builds_push_assertion_ids() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(
+        &path,
+        "program app\nfn check(y: Field) {\n    assert(y == 2)\n}\nfn main() {\n    let x = pub_read()\n    assert(x == 1)\n    check(pub_read())\n    pub_write(x)\n}\n",
+    )
+    .unwrap();
+    let table = assertion_table_project(&path, &CompileOptions::default()).unwrap();
+    let sites: Vec<(u64, &str, usize, Option<&str>)> = table
+        .assertions()
+        .iter()
+        .map(|a| (a.id, a.function.as_str(), a.line, a.message.as_deref()))
+        .collect();
+    assert_eq!(
+        sites,
+        [
+            (1, "check", 3, Some("y == 2")),
+            (2, "main", 7, Some("x == 1"))
+        ]
+    );
+    assert!(table.to_json().contains("\"id\": 1, \"file\": "));
+
+    let debug = compile_project_with_options(&path, &CompileOptions::default()).unwrap();
+    let run = |public: Vec<u64>| {
+        let input = crate::runtime::ProgramInput {
+            public,
+            secret: Vec::new(),
+            digests: Vec::new(),
+        };
+        let mut dbg = Debugger::new(&debug, &input);
+        (dbg.resume(), dbg.stack().last().copied())
+    };
+    let (stop, _) = run(vec![1, 2]);
+    assert_eq!(stop, Stop::Halted);
+    for (public, id) in [(vec![1, 3], 1), (vec![4, 2], 2)] {
+        let (stop, top) = run(public);
+        assert!(matches!(stop, Stop::Error(msg) if msg.contains("assert")));
+        assert_eq!(table.get(id).map(|a| a.id), top);
+    }
+
+    let release =
+        compile_project_with_options(&path, &CompileOptions::for_profile("release")).unwrap();
+    assert!(release.lines().count() < debug.lines().count());
+}
+
+#[test]
+fn test_assertion_ids_leave_the_stack_balanced() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(
+        &path,
+        "program app\nfn main() {\n    let x = pub_read()\n    for i in 0..3 {\n        assert(x == 1)\n    }\n    assert(x == 1)\n    pub_write(x + 1)\n}\n",
+    )
+    .unwrap();
+    let tasm = compile_project_with_options(&path, &CompileOptions::default()).unwrap();
+    let input = crate::runtime::ProgramInput {
+        public: vec![1],
+        secret: Vec::new(),
+        digests: Vec::new(),
+    };
+    let mut dbg = Debugger::new(&tasm, &input);
+    assert_eq!(dbg.resume(), Stop::Halted);
+    assert_eq!(dbg.output(), [2]);
+}
+
+#[test]
+fn test_as_u32_keeps_value_and_rejects_values_above_u32_range() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    let source = "program test\nfn main() {\n    let x: U32 = as_u32(pub_read())\n    pub_write(as_field(x))\n}";
+    let tasm = compile(source, "test.tri").unwrap();
+    let run = |value: u64| {
+        let input = crate::runtime::ProgramInput {
+            public: vec![value],
+            secret: Vec::new(),
+            digests: Vec::new(),
+        };
+        let mut dbg = Debugger::new(&tasm, &input);
+        (dbg.resume(), dbg.output().to_vec())
+    };
+    assert_eq!(run(17), (Stop::Halted, vec![17]));
+    assert_eq!(run(0xFFFF_FFFF), (Stop::Halted, vec![0xFFFF_FFFF]));
+    let (stop, output) = run(1 << 32);
+    assert!(matches!(stop, Stop::Error(_)));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_repeated_array_constant_is_pooled_and_reloaded() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    let lets: String = (1..=9)
+        .map(|i| format!("    let s{i}: Field = sum([11, 22, 33, 44, 55])\n"))
+        .collect();
+    let source = format!("program test\nfn sum(a: [Field; 5]) -> Field {{\n    a[0] + a[1] + a[2] + a[3] + a[4]\n}}\nfn main() {{\n    let x: Field = pub_read()\n{lets}    if x == 1 {{\n        pub_write(sum([11, 22, 33, 44, 55]))\n    }}\n    pub_write(s1 + s2 + s3 + s4 + s5 + s6 + s7 + s8 + s9)\n}}");
+    let tasm = compile(&source, "test.tri").unwrap();
+    assert_eq!(tasm.matches("write_mem 5").count(), 1);
+    assert_eq!(tasm.matches("read_mem 5").count(), 10);
+    let input = crate::runtime::ProgramInput {
+        public: vec![1],
+        secret: Vec::new(),
+        digests: Vec::new(),
+    };
+    let mut dbg = Debugger::new(&tasm, &input);
+    assert_eq!(dbg.resume(), Stop::Halted);
+    assert_eq!(dbg.output(), &[165, 1485]);
+}
+
+#[test]
+fn test_pooled_constants_survive_calls_into_other_modules() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    // Both modules pool a different digest-width constant; the call to
+    // `helper.emit` between main's uses must not overwrite main's slot.
+    let sum = "fn sum(a: [Field; 5]) -> Field {\n    a[0] + a[1] + a[2] + a[3] + a[4]\n}\n";
+    let lets = |range: std::ops::RangeInclusive<u32>, words: &str| -> String {
+        range
+            .map(|i| format!("    let s{i}: Field = sum([{words}])\n"))
+            .collect()
+    };
+    let total = "s1 + s2 + s3 + s4 + s5 + s6 + s7 + s8 + s9 + s10";
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("helper.tri"),
+        format!(
+            "module helper\n{sum}pub fn emit() {{\n{}    pub_write({total})\n}}\n",
+            lets(1..=10, "1, 2, 3, 4, 5")
+        ),
+    )
+    .unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        format!(
+            "program main\nuse helper\n{sum}fn main() {{\n{}    helper.emit()\n{}    pub_write({total})\n}}\n",
+            lets(1..=5, "11, 22, 33, 44, 55"),
+            lets(6..=10, "11, 22, 33, 44, 55")
+        ),
+    )
+    .unwrap();
+
+    let tasm = compile_project(&entry).unwrap();
+    assert_eq!(tasm.matches("write_mem 5").count(), 2, "{}", tasm);
+    let mut dbg = Debugger::new(&tasm, &crate::runtime::ProgramInput::default());
+    assert_eq!(dbg.resume(), Stop::Halted);
+    assert_eq!(dbg.output(), &[150, 1650]);
+}
+
+#[test]
+fn test_if_expression_selects_branch_value() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    let source = "program test\nfn main() {\n    let p: Bool = pub_read() == 1\n    let fee: Field = if p { 10 } else { 2 }\n    pub_write(fee)\n}";
+    let tasm = compile(source, "test.tri").unwrap();
+    for (flag, fee) in [(1, 10), (0, 2)] {
+        let input = crate::runtime::ProgramInput {
+            public: vec![flag],
+            secret: Vec::new(),
+            digests: Vec::new(),
+        };
+        let mut dbg = Debugger::new(&tasm, &input);
+        assert_eq!(dbg.resume(), Stop::Halted);
+        assert_eq!(dbg.output(), &[fee]);
+    }
+}
+
+#[test]
+fn test_tuple_if_expression_survives_spilling() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    // Sixteen live locals force spills while each branch builds its tuple.
+    let source = "program test\nfn main() {\n    let a: Field = pub_read()\n    let b: Field = a + 1\n    let c: Field = a + 2\n    let d: Field = a + 3\n    let e: Field = a + 4\n    let f: Field = a + 5\n    let g: Field = a + 6\n    let h: Field = a + 7\n    let i: Field = a + 8\n    let j: Field = a + 9\n    let k: Field = a + 10\n    let l: Field = a + 11\n    let m: Field = a + 12\n    let n: Field = a + 13\n    let o: Field = a + 14\n    let p: Field = a + 15\n    let q: Field = a + 16\n    let r: (Field, Field) = if a == 1 {\n        let t1: Field = b * c\n        let t2: Field = t1 + q\n        (t2, t1)\n    } else {\n        (p, q)\n    }\n    let (r0, r1) = r\n    pub_write(r0)\n    pub_write(r1)\n    pub_write(b + c + d + e + f + g + h + i + j + k + l + m + n + o + p + q)\n}";
+    let tasm = compile(source, "test.tri").unwrap();
+    for (a, expected) in [(1, [23, 6, 152]), (0, [15, 16, 136])] {
+        let input = crate::runtime::ProgramInput {
+            public: vec![a],
+            secret: Vec::new(),
+            digests: Vec::new(),
+        };
+        let mut dbg = Debugger::new(&tasm, &input);
+        assert_eq!(dbg.resume(), Stop::Halted);
+        assert_eq!(dbg.output(), &expected);
+    }
+}
+
+#[test]
+fn test_logical_operators_skip_effectful_right_operand() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    // check() fails unless its argument is 5, so it may run only when the
+    // left operand does not decide the result.
+    let source = "program test\nfn check(x: Field) -> Bool {\n    assert(x == 5)\n    true\n}\nfn main() {\n    let a: Field = pub_read()\n    let b: Field = pub_read()\n    if a == 1 && b == 2 {\n        pub_write(11)\n    }\n    if a == 1 || b == 2 {\n        pub_write(22)\n    }\n    if a == 5 && check(b) {\n        pub_write(33)\n    }\n    if a == 0 || check(b) {\n        pub_write(44)\n    }\n}";
+    let tasm = compile(source, "test.tri").unwrap();
+    let cases: [(&[u64], &[u64]); 3] =
+        [(&[0, 2], &[22, 44]), (&[0, 0], &[44]), (&[5, 5], &[33, 44])];
+    for (public, expected) in cases {
+        let input = crate::runtime::ProgramInput {
+            public: public.to_vec(),
+            secret: Vec::new(),
+            digests: Vec::new(),
+        };
+        let mut dbg = Debugger::new(&tasm, &input);
+        assert_eq!(dbg.resume(), Stop::Halted);
+        assert_eq!(dbg.output(), expected);
+    }
+}
+
+#[test]
+fn test_pure_logical_operators_compile_without_branches() {
+    let source = "program test\nfn main() {\n    let a: Field = pub_read()\n    let b: Field = pub_read()\n    assert(a == 1 && b == 2 || a == b)\n}";
+    let tasm = compile(source, "test.tri").unwrap();
+    assert!(!tasm.contains("skiz"));
+}
+
+#[test]
+fn test_checked_div_asserts_nonzero_divisor() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    let source = "program test\nfn main() {\n    let a: Field = pub_read()\n    let b: Field = pub_read()\n    pub_write(checked_div(a, b))\n}";
+    let tasm = compile(source, "test.tri").unwrap();
+    let run = |b: u64| {
+        let input = crate::runtime::ProgramInput {
+            public: vec![6, b],
+            secret: Vec::new(),
+            digests: Vec::new(),
+        };
+        Debugger::new(&tasm, &input).resume()
+    };
+    assert_eq!(run(3), Stop::Halted);
+    assert!(matches!(run(0), Stop::Error(msg) if msg.contains("assert")));
+}
+
+#[test]
+fn test_mono_budget_counts_merged_instances_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nfn bump<N>(x: Field) -> Field {\n    x + 1\n}\nfn len<N>(a: [Field; N]) -> Field {\n    N\n}\nfn main() {\n    let a: [Field; 2] = [1, 2]\n    let b: [Field; 3] = [1, 2, 3]\n    pub_write(bump<2>(len(a)) + bump<3>(len(b)))\n}\n",
+    )
+    .unwrap();
+
+    let mut options = CompileOptions::default();
+    let report = mono_report_project(&entry, &options).unwrap();
+    let merged: Vec<&str> = report
+        .instances
+        .iter()
+        .filter(|e| e.merged_into.is_some())
+        .map(|e| e.label.as_str())
+        .collect();
+    assert_eq!(merged, vec!["bump__N3"]);
+    assert_eq!(report.instance_count(), 3);
+    assert!(report.instances.iter().all(|e| e
+        .instantiated_at
+        .as_ref()
+        .is_some_and(|site| site.line == 11 && site.file.ends_with("main.tri"))));
+
+    options.mono_limits.mono_instances = 3;
+    assert!(compile_project_with_options(&entry, &options).is_ok());
+
+    options.mono_limits.mono_instances = 2;
+    let errors = compile_project_with_options(&entry, &options).unwrap_err();
+    assert_eq!(
+        errors[0].message,
+        "program has 3 generic instances, over the limit of 2"
+    );
+    let site = &errors[0].instantiations()[0];
+    assert!(site.instance.ends_with("<2>") || site.instance.ends_with("<3>"));
+    assert_eq!(site.line, 11);
+
+    options.mono_limits = MonoLimits {
+        mono_instances: 3,
+        mono_instructions: report.instruction_count() - 1,
+    };
+    let errors = compile_project_with_options(&entry, &options).unwrap_err();
+    assert!(errors[0].message.starts_with("generic instances emit"));
+}
+
+fn run_with_secret(source: &str, secret: &[u64]) -> Vec<u64> {
+    use crate::runtime::debug::{Debugger, Stop};
+    let tasm = compile(source, "test.tri").unwrap();
+    let input = crate::runtime::ProgramInput {
+        public: Vec::new(),
+        secret: secret.to_vec(),
+        digests: Vec::new(),
+    };
+    let mut dbg = Debugger::new(&tasm, &input);
+    assert_eq!(dbg.resume(), Stop::Halted, "{}", tasm);
+    dbg.output().to_vec()
+}
+
+#[test]
+fn test_loops_update_outer_locals_and_see_the_loop_variable() {
+    let source = "program test\nfn main() {\n    let mut acc: Field = 0\n    let mut last: Field = 0\n    for i in 2..6 bounded 4 {\n        let x: Field = divine()\n        acc = acc + x * as_field(i)\n        last = as_field(i)\n    }\n    pub_write(acc)\n    pub_write(last)\n}\n";
+    assert_eq!(run_with_secret(source, &[1, 2, 3, 4]), [40, 5]);
+}
+
+#[test]
+fn test_assignment_replaces_every_word_of_a_wide_variable() {
+    let source = "program test\nfn main() {\n    let mut d: Digest = divine5()\n    let e: Digest = divine5()\n    d = e\n    let (d0, d1, d2, d3, d4) = d\n    pub_write(d0 + d1 * 10 + d2 * 100 + d3 * 1000 + d4 * 10000)\n}\n";
+    let secret = [1, 2, 3, 4, 5, 6, 7, 8, 9, 1];
+    assert_eq!(run_with_secret(source, &secret), [19876]);
+}
+
+#[test]
+fn test_wide_return_survives_dropping_the_callee_locals() {
+    let source = "program test\nfn f() -> [Field; 3] {\n    let a: Field = divine()\n    let b: Field = divine()\n    let c: Field = divine()\n    let v: [Field; 3] = [c, b, a]\n    v\n}\nfn main() {\n    let v: [Field; 3] = f()\n    pub_write(v[0] * 100 + v[1] * 10 + v[2])\n}\n";
+    assert_eq!(run_with_secret(source, &[1, 2, 3]), [321]);
+}
+
+#[test]
+fn test_size_parameters_are_values_in_the_instance_body() {
+    let source = "program test\nfn sum<N>(x: Field) -> Field {\n    let mut acc: Field = 0\n    for i in 0..N {\n        acc = acc + x\n    }\n    acc * 10 + N\n}\nfn main() {\n    pub_write(sum<3>(divine()))\n    pub_write(sum<2>(divine()))\n}\n";
+    assert_eq!(run_with_secret(source, &[5, 7]), [153, 142]);
+}
+
+#[test]
+fn test_tuple_patterns_bind_components_of_different_widths() {
+    let source = "program test\nfn split_off(x: Field) -> (Field, [Field; 3]) {\n    let t: [Field; 3] = [x + 1, x + 2, x + 3]\n    (x, t)\n}\nfn main() {\n    let (h, t) = split_off(divine())\n    pub_write(h)\n    pub_write(t[0] * 100 + t[1] * 10 + t[2])\n    let (a, d) = (divine(), divine5())\n    let (d0, d1, d2, d3, d4) = d\n    pub_write(a)\n    pub_write(d0 + d4)\n}\n";
+    assert_eq!(
+        run_with_secret(source, &[1, 9, 10, 20, 30, 40, 50]),
+        [1, 234, 9, 60]
+    );
+}
+
+#[test]
+fn test_tuple_assignment_replaces_every_component() {
+    let source = "program test\nfn split_off(x: Field) -> (Field, [Field; 3]) {\n    let t: [Field; 3] = [x + 1, x + 2, x + 3]\n    (x, t)\n}\nfn main() {\n    let mut a: Field = 0\n    let mut b: Field = 0\n    (a, b) = (divine(), divine())\n    pub_write(a * 10 + b)\n    let mut h: Field = 0\n    let mut t: [Field; 3] = [0, 0, 0]\n    (h, t) = split_off(divine())\n    pub_write(h)\n    pub_write(t[0] * 100 + t[1] * 10 + t[2])\n}\n";
+    assert_eq!(run_with_secret(source, &[1, 2, 4]), [12, 4, 567]);
+}
+
+#[test]
+fn test_ram_writes_land_at_their_address_and_blocks_read_back_in_order() {
+    let source = "program test\nsec ram: { 100: Field, 200: Digest }\nfn main() {\n    ram_write(100, divine())\n    ram_write_block(200, divine5())\n    let d: Digest = ram_read_block(200)\n    let (a, b, c, e, f) = d\n    pub_write(ram_read(100))\n    pub_write(ram_read(200))\n    pub_write(a * 10000 + b * 1000 + c * 100 + e * 10 + f)\n}\n";
+    assert_eq!(run_with_secret(source, &[7, 1, 2, 3, 4, 5]), [7, 5, 12345]);
+}
+
+#[test]
+fn test_spills_for_a_wide_call_result_reach_under_its_arguments() {
+    let source = "program test\nstruct X {\n    c0: Field,\n    c1: Field,\n    c2: Field,\n}\nfn leaf(v: X) -> Digest {\n    hash(v.c0, v.c1, v.c2, 1, 0, 0, 0, 0, 0, 0)\n}\nfn show(l: Digest, root: Digest, node: U32, depth: Field) {\n    let (a, b, c, d, e) = root\n    pub_write(a)\n    pub_write(e)\n    pub_write(as_field(node))\n    pub_write(depth)\n}\nfn auth(root: Digest, v: X, idx: U32, depth: U32) {\n    let leaves: Field = as_field(depth) + 8\n    let node: Field = leaves + 100\n    show(leaf(v), root, idx, node)\n}\nfn main() {\n    let root: Digest = divine5()\n    auth(root, X { c0: 1, c1: 2, c2: 3 }, as_u32(3), as_u32(3))\n}\n";
+    assert_eq!(
+        run_with_secret(source, &[11, 12, 13, 14, 15]),
+        [11, 15, 3, 111]
+    );
+}
+
+#[test]
+fn test_call_statements_keep_variables_reloaded_for_their_arguments() {
+    let source = "program main\nstruct X {\n    c0: Field,\n    c1: Field,\n    c2: Field,\n}\nstruct Dom {\n    offset: Field,\n    generator: Field,\n    log_size: U32,\n}\nstruct Layout {\n    roots: Field,\n    alphas: Field,\n    final_poly: Field,\n}\nfn dx() -> X {\n    let (c0, c1, c2) = divine3()\n    X { c0: c0, c1: c1, c2: c2 }\n}\nfn auth(root: Digest, v: X, idx: U32, depth: U32) {\n    pub_write(as_field(idx))\n    pub_write(as_field(depth))\n}\nfn vq(dom: Dom, layout: Layout, query: U32) {\n    let mut cur: Dom = dom\n    let mut idx: U32 = query\n    let mut folded: X = X { c0: 0, c1: 0, c2: 0 }\n    for r in 0..2 {\n        let root: Digest = divine5()\n        let half: U32 = pow(as_u32(2), as_u32(sub(as_field(cur.log_size), 1)))\n        let (_, i) = idx /% half\n        let fx: X = dx()\n        let fnx: X = dx()\n        auth(root, fx, i, cur.log_size)\n        auth(root, fnx, as_u32(as_field(i) + as_field(half)), cur.log_size)\n        if r == 0 {\n        } else {\n        }\n    }\n}\nfn main() {\n    let dom: Dom = Dom { offset: 7, generator: 9, log_size: as_u32(5) }\n    let layout: Layout = Layout { roots: 1000, alphas: 2000, final_poly: 3000 }\n    vq(dom, layout, as_u32(13))\n}\n";
+    let secret: Vec<u64> = (1..=22).collect();
+    assert_eq!(
+        run_with_secret(source, &secret),
+        [13, 5, 29, 5, 13, 5, 29, 5]
+    );
+}
+
+#[test]
+fn test_struct_values_cross_calls_in_field_order() {
+    let source = "program test\nstruct P {\n    a: Field,\n    b: Field,\n    c: Field,\n}\nfn mk(x: Field) -> P {\n    P { c: x + 2, a: x, b: x + 1 }\n}\nfn sum(p: P) -> Field {\n    p.a + p.b * 10 + p.c * 100\n}\nfn main() {\n    let p: P = mk(divine())\n    pub_write(sum(p))\n    pub_write(p.c)\n}\n";
+    assert_eq!(run_with_secret(source, &[4]), [654, 6]);
+}
+
+#[test]
+fn test_cross_module_calls_leave_their_declared_width() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("geo.tri"),
+        "module geo\npub struct Pt {\n    x: Field,\n    y: Field,\n}\npub fn pt(x: Field) -> Pt {\n    Pt { x: x, y: x * 2 }\n}\npub fn norm(p: Pt) -> Field {\n    p.x * p.x + p.y * p.y\n}\npub fn ten() -> Field {\n    10\n}\n",
+    )
+    .unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nuse geo\nfn main() {\n    let t: Field = geo.ten()\n    let p: geo.Pt = geo.pt(pub_read())\n    let n: Field = geo.norm(p)\n    pub_write(n + t)\n    pub_write(p.y)\n}\n",
+    )
+    .unwrap();
+
+    let tasm = compile_project(&entry).unwrap();
+    let input = crate::runtime::ProgramInput {
+        public: vec![3],
+        ..Default::default()
+    };
+    let mut dbg = Debugger::new(&tasm, &input);
+    assert_eq!(dbg.resume(), Stop::Halted, "{}", tasm);
+    assert_eq!(dbg.output(), &[55, 6]);
+}
+
+#[test]
+fn test_digest_equality_compares_every_element() {
+    let source = "program test\nfn main() {\n    let a: Digest = divine5()\n    let b: Digest = divine5()\n    let same: Bool = a == b\n    pub_write(if same { 1 } else { 0 })\n}\n";
+    let a = [1, 2, 3, 4, 5];
+    assert_eq!(run_with_secret(source, &[a, a].concat()), [1]);
+    assert_eq!(run_with_secret(source, &[a, [1, 2, 3, 4, 6]].concat()), [0]);
+    assert_eq!(run_with_secret(source, &[[9, 2, 3, 4, 5], a].concat()), [0]);
+}
+
+#[test]
+fn test_struct_equality_compares_fields_pairwise() {
+    let source = "program test\nstruct Pair {\n    a: Field,\n    b: Field,\n}\nfn main() {\n    let p: Pair = Pair { a: divine(), b: divine() }\n    let q: Pair = Pair { a: divine(), b: divine() }\n    pub_write(if p == q { 1 } else { 0 })\n}\n";
+    assert_eq!(run_with_secret(source, &[7, 8, 7, 8]), [1]);
+    assert_eq!(run_with_secret(source, &[7, 8, 8, 7]), [0]);
+}
+
+#[test]
+fn test_asserting_digest_equality_uses_assert_vector() {
+    let source = "program test\nfn main() {\n    let a: Digest = divine5()\n    let b: Digest = divine5()\n    assert(a == b)\n}\n";
+    let tasm = compile(source, "test.tri").unwrap();
+    assert!(tasm.contains("assert_vector"));
+    assert!(!tasm.contains("    eq"), "no element-wise eq: {}", tasm);
+}
+
+#[test]
+fn test_build_info_describes_this_build() {
+    let info = build_info();
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert!(!info.git_hash.is_empty());
+    assert_eq!(info.features.contains(&"capi"), cfg!(feature = "capi"));
+    let triton = info.targets.iter().find(|t| t.name == "triton").unwrap();
+    assert_eq!(triton.max_tier, 3);
+    assert!(triton.cost_model);
+
+    let text = info.to_text();
+    assert!(text.starts_with(&format!("trident {}\n", info.version)));
+    assert!(text.contains(&format!("commit:   {}", info.git_hash)));
+    let json = info.to_json();
+    assert!(json.contains(
+        "\"targets\": [{ \"name\": \"triton\", \"max_tier\": 3, \"cost_model\": true }]"
+    ));
+}
+
+#[test]
+fn test_run_tests_structured_report() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nfn main() {\n    pub_write(pub_read())\n}\n#[test]\nfn adds_up() {\n    assert(1 + 1 == 2)\n}\n",
+    )
+    .unwrap();
+
+    let report = run_tests(&entry, &CompileOptions::default()).expect("tests should run");
+    assert_eq!(report.results.len(), 1);
+    assert_eq!(report.passed(), 1);
+    let result = &report.results[0];
+    assert_eq!(
+        (result.name.as_str(), result.module.as_str()),
+        ("adds_up", "main")
+    );
+    assert!(report.to_text().contains("test adds_up ... ok"));
+
+    let json = report.to_json();
+    assert!(json.contains("\"name\": \"adds_up\", \"module\": \"main\", \"passed\": true"));
+    assert!(json.contains("\"cost\": {\"processor\": "), "{}", json);
+    assert!(json.contains("\"failed\": 0"));
+
+    let junit = report.to_junit();
+    assert!(junit.contains("<testsuite name=\"main\" tests=\"1\" failures=\"0\""));
+    assert!(junit.contains("<testcase name=\"adds_up\" classname=\"main\""));
+    assert!(junit.contains("<property name=\"cost.processor\" value=\""));
+    assert!(!junit.contains("<failure"));
+}
+
+#[test]
+fn test_selection_filters_by_substring_glob_and_exact_name() {
+    let select = |filter: &str, exact: bool| TestSelection {
+        filter: Some(filter.to_string()),
+        exact,
+        jobs: 0,
+        max_cycles: None,
+        ..TestSelection::default()
+    };
+    assert!(TestSelection::default().matches("token", "transfer_ok"));
+    assert!(select("transfer", false).matches("token", "transfer_ok"));
+    assert!(select("token.transfer", false).matches("token", "transfer_ok"));
+    assert!(!select("mint", false).matches("token", "transfer_ok"));
+    assert!(select("tr*_ok", false).matches("token", "transfer_ok"));
+    assert!(select("t?ken.*", true).matches("token", "transfer_ok"));
+    assert!(!select("transfer", true).matches("token", "transfer_ok"));
+    assert!(select("transfer_ok", true).matches("token", "transfer_ok"));
+}
+
+#[test]
+fn test_run_tests_with_filter_keeps_discovery_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    let mut source = String::from("program main\nfn main() {\n    pub_write(pub_read())\n}\n");
+    for name in ["sum_a", "sum_b", "product", "sum_c"] {
+        source.push_str(&format!(
+            "#[test]\nfn {}() {{\n    assert(2 * 2 == 4)\n}}\n",
+            name
+        ));
+    }
+    std::fs::write(&entry, source).unwrap();
+
+    let selection = TestSelection {
+        filter: Some("sum".to_string()),
+        exact: false,
+        jobs: 2,
+        max_cycles: None,
+        ..TestSelection::default()
+    };
+    let report = run_tests_with(&entry, &CompileOptions::default(), &selection).unwrap();
+    let names: Vec<&str> = report.results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["sum_a", "sum_b", "sum_c"]);
+    assert_eq!(report.passed(), 3);
+}
+
+#[test]
+fn test_run_tests_read_their_input_from_the_selection() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nfn main() {}\n#[test]\nfn reads() {\n    assert(pub_read() + divine() == 12)\n}\n",
+    )
+    .unwrap();
+
+    let report = run_tests(&entry, &CompileOptions::default()).unwrap();
+    assert_eq!(report.failed(), 1, "no input to read");
+
+    let selection = TestSelection {
+        input: crate::runtime::ProgramInput::from_witness_json(
+            "{ \"public\": [5], \"secret\": [7] }",
+        )
+        .unwrap(),
+        ..TestSelection::default()
+    };
+    let report = run_tests_with(&entry, &CompileOptions::default(), &selection).unwrap();
+    assert_eq!(report.passed(), 1);
+}
+
+#[test]
+fn test_run_tests_that_reach_faked_instructions_are_compile_only() {
+    // The emulator's placeholder digest is all zeros, so this would pass.
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nfn main() {}\n#[test]\nfn digest_is_zero() {\n    let d: Digest = hash(1, 2, 3, 4, 5, 6, 7, 8, 9, 10)\n    let (d0, d1, d2, d3, d4) = d\n    assert(d0 == 0)\n}\n#[test]\nfn adds_up() {\n    assert(1 + 1 == 2)\n}\n",
+    )
+    .unwrap();
+
+    let report = run_tests(&entry, &CompileOptions::default()).unwrap();
+    let counts = (report.passed(), report.compile_only(), report.failed());
+    assert_eq!(counts, (1, 1, 0));
+    let faked = report.results.iter().find(|r| r.compile_only).unwrap();
+    assert_eq!(faked.name, "digest_is_zero");
+    assert!(!faked.passed);
+    let note = faked.error.as_deref().unwrap();
+    assert!(
+        note.starts_with("stopped at 'hash' in digest_is_zero at"),
+        "{}",
+        note
+    );
+
+    let text = report.to_text();
+    assert!(
+        text.contains("test digest_is_zero ... compile-only"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("test result: ok. 1 passed; 0 failed; 1 compile-only"),
+        "{}",
+        text
+    );
+    assert!(report.to_json().contains("\"compile_only\": 1,"));
+    let junit = report.to_junit();
+    assert!(junit.contains("failures=\"0\" skipped=\"1\""), "{}", junit);
+    assert!(
+        junit.contains("<skipped message=\"stopped at &apos;hash&apos;"),
+        "{}",
+        junit
+    );
+}
+
+#[test]
+fn test_run_tests_executes_tests_within_cycle_limits() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main
+fn main() {
+    pub_write(pub_read())
+}
+fn spin(n: Field) -> Field {
+    for i in 0..1000 {
+        assert(1 == 1)
+    }
+    n
+}
+#[test(max_cycles = 200)]
+fn runaway() {
+    assert(spin(1) == 1)
+}
+#[test]
+fn loops_fine() {
+    assert(spin(2) == 2)
+}
+#[test]
+fn wrong() {
+    assert(spin(1) == 7)
+}
+",
+    )
+    .unwrap();
+
+    let report = run_tests(&entry, &CompileOptions::default()).unwrap();
+    let outcome = |name: &str| {
+        let r = report.results.iter().find(|r| r.name == name).unwrap();
+        (r.passed, r.error.clone().unwrap_or_default())
+    };
+    let (passed, error) = outcome("runaway");
+    assert!(!passed);
+    assert!(
+        error.starts_with("cycle limit of 200 exceeded in spin at "),
+        "{}",
+        error
+    );
+    assert!(error.ends_with("main.tri:5"), "{}", error);
+    assert_eq!(outcome("loops_fine"), (true, String::new()));
+    let (passed, error) = outcome("wrong");
+    assert!(!passed);
+    assert!(error.starts_with("assertion failed"), "{}", error);
+    assert!(error.contains(" in wrong at "), "{}", error);
+
+    // A global budget applies to tests without their own
+    let selection = TestSelection {
+        max_cycles: Some(100),
+        ..TestSelection::default()
+    };
+    let report = run_tests_with(&entry, &CompileOptions::default(), &selection).unwrap();
+    assert_eq!(report.passed(), 0);
+}
+
+#[test]
+fn test_run_tests_link_tests_across_modules() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program app\nuse math\nfn main() {\n    pub_write(math.double(pub_read()))\n}\n#[test]\nfn doubles() {\n    assert(math.double(2) == 4)\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("math.tri"),
+        "module math\npub fn double(x: Field) -> Field {\n    x + x\n}\n#[test]\nfn odd_double() {\n    assert(double(3) == 5)\n}\n",
+    )
+    .unwrap();
+
+    let report = run_tests(&entry, &CompileOptions::default()).unwrap();
+    let outcomes: Vec<(&str, &str, bool)> = report
+        .results
+        .iter()
+        .map(|r| (r.module.as_str(), r.name.as_str(), r.passed))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [("math", "odd_double", false), ("app", "doubles", true)]
+    );
+    let error = report.results[0].error.as_deref().unwrap_or_default();
+    assert!(error.contains(" in odd_double at "), "{}", error);
+    assert!(error.ends_with("math.tri:5"), "{}", error);
+    assert!(report.results.iter().all(|r| r.cost.is_some()));
+}
+
+#[test]
+fn test_run_tests_divide_in_the_field() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nfn main() {}\n#[test]\nfn quotient() {\n    assert(checked_div(10, 2) == 5)\n    assert(checked_inv(4) * 4 == 1)\n}\n#[test]\nfn by_zero() {\n    assert(checked_div(10, 0) == 0)\n}\n",
+    )
+    .unwrap();
+
+    let report = run_tests(&entry, &CompileOptions::default()).unwrap();
+    let outcomes: Vec<(&str, bool)> = report
+        .results
+        .iter()
+        .map(|r| (r.name.as_str(), r.passed))
+        .collect();
+    assert_eq!(outcomes, [("quotient", true), ("by_zero", false)]);
+}
+
+#[test]
+fn test_run_tests_index_tables_at_runtime() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program app\nuse tables\nconst LIMITS: [Field; 3] = [7, 8, 9]\nfn main() {}\n#[test]\nfn indexes_constants() {\n    let mut sum: Field = 0\n    for i in 0..3 {\n        sum = sum + LIMITS[i] + tables.lookup(as_field(i))\n    }\n    assert(sum == 24 + 8)\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("tables.tri"),
+        "module tables\n#[pure]\nfn square(i: Field, k: Field) -> Field {\n    i * i + k\n}\nconst SQ: [Field; 8] = generate!(square, 1)\npub fn lookup(i: Field) -> Field {\n    SQ[i]\n}\n#[test]\nfn looks_up() {\n    for i in 0..8 {\n        let f: Field = as_field(i)\n        assert(lookup(f) == f * f + 1)\n    }\n}\n",
+    )
+    .unwrap();
+
+    let report = run_tests(&entry, &CompileOptions::default()).unwrap();
+    assert_eq!(report.results.len(), 2);
+    assert_eq!(report.passed(), 2, "{:?}", report.results);
+}
+
+#[test]
+fn test_run_tests_fail_only_the_module_that_fails_to_emit() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    let mut spills = String::from("module spills\n#[no_spill]\npub fn wide() {\n");
+    for i in 0..18 {
+        spills.push_str(&format!("    let v{}: Field = pub_read()\n", i));
+    }
+    for i in 0..18 {
+        spills.push_str(&format!("    pub_write(v{})\n", i));
+    }
+    spills.push_str("}\n#[test]\nfn trivial() {\n    assert(1 == 1)\n}\n");
+    std::fs::write(dir.path().join("spills.tri"), spills).unwrap();
+    std::fs::write(
+        dir.path().join("util.tri"),
+        "module util\n#[test]\nfn doubles() {\n    assert(2 + 2 == 4)\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &entry,
+        "program app\nuse spills\nuse util\nfn main() {\n    spills.wide()\n}\n#[test]\nfn adds_up() {\n    assert(1 + 1 == 2)\n}\n",
+    )
+    .unwrap();
+
+    let report = run_tests(&entry, &CompileOptions::default()).unwrap();
+    let outcomes: Vec<(&str, bool)> = report
+        .results
+        .iter()
+        .map(|r| (r.module.as_str(), r.passed))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [("spills", false), ("util", true), ("app", true)],
+        "{:?}",
+        report.results
+    );
+    let error = report.results[0].error.as_deref().unwrap_or_default();
+    assert!(error.contains("'wide' is #[no_spill]"), "{}", error);
+}
+
+#[test]
+fn test_compiler_answers_every_query_from_one_preparation() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    std::fs::write(
+        &path,
+        "program app\npub fn double(x: Field) -> Field {\n    x + x\n}\nfn main() {\n    pub_write(double(pub_read()))\n}\n",
+    )
+    .unwrap();
+    let compiler = Compiler::new(&path);
+    compiler.check().unwrap();
+    let tasm = compiler.compile().unwrap();
+    assert_eq!(tasm, compile_project(&path).unwrap());
+    assert!(
+        std::ptr::eq(tasm, compiler.compile().unwrap()),
+        "linked TASM is cached"
+    );
+    assert_eq!(
+        compiler.costs().unwrap().total.get(0),
+        analyze_costs_project(&path, &CompileOptions::default())
+            .unwrap()
+            .total
+            .get(0)
+    );
+    assert_eq!(compiler.abi().unwrap().functions.len(), 2);
+    assert_eq!(compiler.modules().unwrap(), ["app"]);
+    assert!(compiler.docs().unwrap().starts_with("# app\n"));
+}
+
+#[test]
+fn test_derived_functions_compile_like_handwritten_ones() {
+    let source = "program test\n#[derive(Hash, FromWitness)]\nstruct Note {\n    amount: Field,\n    nonce: U32,\n    spent: Bool,\n    owner: Digest,\n}\nfn main() {\n    let note: Note = divine_note()\n    let d: Digest = hash_note(note)\n    let (d0, d1, d2, d3, d4) = d\n    pub_write(d0)\n}\n";
+    let tasm = compile(source, "test.tri").unwrap();
+    assert!(tasm.contains("hash_note"));
+    assert!(tasm.contains("divine 5"));
+}
+
+#[test]
+fn test_cross_module_generic_calls_resolve_by_call_site() {
+    use crate::runtime::debug::{Debugger, Stop};
+
+    // The type checker records the #[test] call first; a plain build
+    // skips that function, so `main` must not pick up its instance.
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("lib.tri"),
+        "module lib\npub fn first<N>(a: [Field; N]) -> Field {\n    a[0]\n}\n",
+    )
+    .unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nuse lib\n#[test]\nfn three() {\n    assert(lib.first<3>([1, 2, 3]) == 1)\n}\nfn main() {\n    pub_write(lib.first<2>([4, 5]))\n}\n",
+    )
+    .unwrap();
+
+    let tasm = compile_project(&entry).unwrap();
+    assert!(tasm.contains("call lib__first__N2"), "{}", tasm);
+    let mut dbg = Debugger::new(&tasm, &crate::runtime::ProgramInput::default());
+    assert_eq!(dbg.resume(), Stop::Halted);
+    assert_eq!(dbg.output(), &[4]);
+}
@@ -177,3 +177,123 @@ fn test_comparison_formatting_integration() {
     );
 }
 
+#[test]
+fn test_adjacent_field_absorbs_cost_full_rate_permutations() {
+    let program = |absorb: &str| {
+        let mut source = String::from("program test\nfn main() {\n    sponge_init()\n");
+        for _ in 0..12 {
+            source.push_str(&format!("    {}(pub_read())\n", absorb));
+        }
+        source.push_str("    pub_write(0)\n}\n");
+        source
+    };
+    let packed = analyze_costs(&program("sponge_absorb_field"), "test.tri").unwrap();
+    let init = analyze_costs(
+        "program test\nfn main() {\n    sponge_init()\n}\n",
+        "test.tri",
+    )
+    .unwrap();
+    // Twelve fields fill two permutations: 6 hash rows each.
+    assert_eq!(packed.total.get(1) - init.total.get(1), 12);
+}
+
+#[test]
+fn test_merkle_walk_costs_one_permutation_per_level() {
+    let program = |depth: &str| {
+        format!(
+            "program test\nfn main() {{\n    let x: Field = pub_read()\n    let (i, d) = merkle_walk(as_u32(x), x, x, x, x, x, {})\n    assert_digest(d, d)\n}}\n",
+            depth
+        )
+    };
+    let cost = |depth: &str| analyze_costs(&program(depth), "test.tri").unwrap();
+    assert_eq!(cost("20").total.get(1) - cost("10").total.get(1), 60);
+    // A runtime depth is charged for the deepest path verify accepts.
+    assert_eq!(cost("x").total.get(1) - cost("10").total.get(1), 54 * 6);
+}
+
+#[test]
+fn test_module_costs_flag_functions_over_the_proving_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.tri");
+    let pass = "    for i in 0..250000 {\n        pub_write(x)\n    }\n";
+    std::fs::write(
+        &path,
+        format!(
+            "program app\nfn light() {{\n    pub_write(1)\n}}\nfn heavy() {{\n    let x: Field = pub_read()\n{}{}{}}}\nfn main() {{\n    light()\n    heavy()\n}}\n",
+            pass, pass, pass
+        ),
+    )
+    .unwrap();
+    let report = module_costs_project(&path, &CompileOptions::default()).unwrap();
+    assert_eq!(report.max_padded_height, 1 << 22);
+    assert_eq!(report.modules.len(), 1);
+    assert_eq!(report.modules[0].functions, 3);
+
+    let heavy = report
+        .oversized
+        .iter()
+        .find(|f| f.function == "heavy")
+        .expect("three 3M-row loops pad past 2^22");
+    let lines: Vec<usize> = heavy.split_points.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, [10, 13], "each loop fits under the limit on its own");
+    assert!(report.oversized.iter().all(|f| f.function != "light"));
+    assert!(report.format_report().contains("split before line 10"));
+}
+
+#[test]
+fn test_view_qualified_items_and_generic_instances() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("util.tri"),
+        "module util\n\npub const LIMIT: Field = 8\n\npub struct Pair {\n    a: Field,\n    b: Field,\n}\n\npub fn same<N>(a: [Field; N], b: [Field; N]) -> Bool {\n    a == b\n}\n\npub fn twice(x: Field) -> Field {\n    x + x\n}\n",
+    )
+    .unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nuse util\n\nfn twice(x: Field) -> Field {\n    x * 2\n}\n\nfn main() {\n    let a: [Field; 3] = [1, 2, 3]\n    assert(util.same(a, a))\n    pub_write(twice(util.twice(pub_read())))\n}\n",
+    )
+    .unwrap();
+    let compiler = Compiler::new(&entry);
+
+    // Unqualified names prefer the program module; qualified ones pick
+    // the module by full name or last segment.
+    let local = compiler.view("twice").unwrap().unwrap();
+    assert_eq!(local.module, "main");
+    assert!(local.source.contains("x * 2"));
+    let util = compiler.view("util.twice").unwrap().unwrap();
+    assert_eq!(util.module, "util");
+    assert!(util.source.contains("x + x"));
+    assert!(util.hash.is_some() && util.cost.is_some());
+
+    let pair = compiler.view("util.Pair").unwrap().unwrap();
+    assert_eq!(pair.kind, "struct");
+    assert!(pair.source.contains("struct Pair"));
+    let limit = compiler.view("LIMIT").unwrap().unwrap();
+    assert_eq!(limit.kind, "const");
+    assert!(limit.hash.is_none() && limit.cost.is_none());
+
+    let small = compiler.view("same<3>").unwrap().unwrap();
+    assert_eq!(small.name, "same<3>");
+    assert_eq!(
+        small.instance_signature.as_deref(),
+        Some("fn same<3>(a: [Field; 3], b: [Field; 3]) -> Bool")
+    );
+    let large = compiler.view("util.same<12>").unwrap().unwrap();
+    let (small, large) = (small.cost.unwrap(), large.cost.unwrap());
+    assert!(
+        large.get(0) > small.get(0),
+        "comparing wider arrays costs more"
+    );
+
+    assert!(compiler.view("same<3, 4>").unwrap().is_none());
+    assert!(compiler.view("other.twice").unwrap().is_none());
+    let items: Vec<String> = compiler
+        .items()
+        .unwrap()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert!(items.contains(&"util.Pair".to_string()));
+    assert!(items.contains(&"main.main".to_string()));
+}
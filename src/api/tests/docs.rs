@@ -118,3 +118,49 @@ fn test_generate_docs_cost_annotations() {
     assert!(doc.contains("**Module:** test"), "should show module name");
 }
 
+#[test]
+fn test_generate_docs_verification_section() {
+    let dir = tempfile::tempdir().unwrap();
+    let main_path = dir.path().join("main.tri");
+    std::fs::write(
+        &main_path,
+        "program test\n\n#[requires(x == 3)]\nfn check(x: Field) {\n    assert(x + 1 == 4)\n}\n\nfn main() {\n    check(3)\n}\n",
+    )
+    .unwrap();
+
+    let options = CompileOptions::default();
+    let doc = generate_docs(&main_path, &options).expect("doc generation should succeed");
+
+    assert!(
+        doc.contains("#### Verification"),
+        "should have a verification subsection"
+    );
+    assert!(doc.contains("- **Status:** "), "should show the verdict");
+    assert!(
+        doc.contains("- **Preconditions:** `x == 3` (declared)"),
+        "should list the declared precondition"
+    );
+}
+
+#[test]
+fn test_generate_docs_project_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("trident.toml"),
+        "[project]\nname = \"my_app\"\nlicense = \"MIT\"\nauthors = [\"Ada\", \"Grace\"]\ndescription = \"Adds one.\"\n",
+    )
+    .unwrap();
+    let main_path = dir.path().join("main.tri");
+    std::fs::write(
+        &main_path,
+        "program my_app\n\nfn main() {\n    pub_write(pub_read() + 1)\n}\n",
+    )
+    .unwrap();
+
+    let doc = generate_docs(&main_path, &CompileOptions::default())
+        .expect("doc generation should succeed");
+    assert!(doc.starts_with("# my_app\n\nAdds one.\n"), "{}", doc);
+    assert!(doc.contains("- **License:** MIT"), "{}", doc);
+    assert!(doc.contains("- **Authors:** Ada, Grace"), "{}", doc);
+    assert!(!doc.contains("**Repository:**"), "{}", doc);
+}
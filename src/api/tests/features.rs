@@ -91,6 +91,34 @@ pub_write(first<3>(a) + first<5>(b))
     assert!(tasm.contains("__first__N5:"));
 }
 
+#[test]
+fn test_generic_fn_nested_instances_emitted() {
+    let source = r#"program test
+
+fn inner<N>(arr: [Field; N]) -> Field {
+arr[0]
+}
+
+fn outer<N>(arr: [Field; N]) -> Field {
+inner<N>(arr)
+}
+
+fn main() {
+let a: [Field; 3] = [1, 2, 3]
+pub_write(outer<3>(a))
+}
+"#;
+    let result = compile(source, "test.tri");
+    assert!(
+        result.is_ok(),
+        "nested generic call should compile: {:?}",
+        result.err()
+    );
+    let tasm = result.unwrap();
+    assert!(tasm.contains("__outer__N3:"));
+    assert!(tasm.contains("__inner__N3:"));
+}
+
 #[test]
 fn test_generic_fn_existing_code_unaffected() {
     // Non-generic code should still work exactly as before
@@ -133,6 +161,55 @@ pub_write(s)
     );
 }
 
+#[test]
+fn test_cross_module_generic_size_errors_name_both_modules() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("util.tri"),
+        "module util\n\npub fn first<N>(a: [Field; N]) -> Field {\n    a[0]\n}\n\npub fn pick<N>(x: Field) -> Field {\n    x\n}\n",
+    )
+    .unwrap();
+    let entry = dir.path().join("main.tri");
+    let compile_main = |body: &str| {
+        std::fs::write(&entry, format!("program main\nuse util\n\n{}", body)).unwrap();
+        compile_project_with_options(&entry, &CompileOptions::default())
+    };
+
+    let ok = "fn main() {\n    let a: [Field; 3] = [1, 2, 3]\n    pub_write(util.first(a))\n}\n";
+    assert!(compile_main(ok).is_ok());
+
+    let uninferred = "fn main() {\n    pub_write(util.pick(5))\n}\n";
+    let errors = compile_main(uninferred).unwrap_err();
+    assert!(errors[0]
+        .message
+        .contains("cannot infer size parameter 'N'"));
+    assert!(
+        errors[0].notes.iter().any(|n| n.contains(
+            "module 'main' requires the instance 'util.pick<N>', which module 'util' defines"
+        )),
+        "{:?}",
+        errors[0].notes
+    );
+    assert!(errors[0]
+        .help
+        .as_deref()
+        .is_some_and(|h| h.contains("`util.pick<N>(...)`")));
+
+    let in_generic = "fn wrap<M>(a: [Field; M]) -> Field {\n    util.first(a)\n}\n\nfn main() {\n    let a: [Field; 3] = [1, 2, 3]\n    pub_write(wrap(a))\n}\n";
+    let errors = compile_main(in_generic).unwrap_err();
+    assert!(
+        errors[0].message.contains(
+            "generic function 'wrap' calls 'util.first', a generic function of module 'util'"
+        ),
+        "{}",
+        errors[0].message
+    );
+    assert!(errors[0]
+        .help
+        .as_deref()
+        .is_some_and(|h| h.contains("move 'wrap'")));
+}
+
 #[test]
 fn test_const_generic_add_expression() {
     // Parameter type uses M + N size expression
@@ -189,8 +266,8 @@ fn test_cfg_different_targets_different_output() {
         compile_with_options(source, "test.tri", &debug_opts).expect("debug should compile");
 
     let release_opts = CompileOptions::for_target("release");
-    let release_tasm = compile_with_options(source, "test.tri", &release_opts)
-        .expect("release should compile");
+    let release_tasm =
+        compile_with_options(source, "test.tri", &release_opts).expect("release should compile");
 
     // Both should have __mode: but with different bodies
     assert!(debug_tasm.contains("__mode:"));
@@ -200,6 +277,92 @@ fn test_cfg_different_targets_different_output() {
     assert!(release_tasm.contains("push 1"));
 }
 
+#[test]
+fn test_assume_is_checked_only_in_debug() {
+    let source = "program test\nfn main() {\n    let x: Field = pub_read()\n    assume(x == 5)\n    pub_write(x)\n}";
+
+    let debug_opts = CompileOptions::for_target("debug");
+    let debug_tasm =
+        compile_with_options(source, "test.tri", &debug_opts).expect("debug should compile");
+    assert!(debug_tasm.contains("assert"));
+
+    let release_opts = CompileOptions::for_target("release");
+    let release_tasm =
+        compile_with_options(source, "test.tri", &release_opts).expect("release should compile");
+    assert!(!release_tasm.contains("assert"));
+    assert!(!release_tasm.contains("push 5"));
+}
+
+#[test]
+fn test_compile_project_profiles_diffs_debug_and_release() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\n\n#[cfg(debug)]\nfn check(x: Field) {\n    assert(x == 5)\n}\n\n#[cfg(release)]\nfn check(x: Field) {}\n\nfn main() {\n    let x: Field = pub_read()\n    assume(x == 5)\n    check(x)\n    assert(x == x)\n    pub_write(x)\n}\n",
+    )
+    .unwrap();
+    let profiles = [
+        CompileOptions::for_profile("debug"),
+        CompileOptions::for_profile("release"),
+    ];
+    let builds = compile_project_profiles(&entry, &profiles).expect("both profiles compile");
+    assert_eq!(builds.len(), 2);
+    let (debug, release) = (&builds[0], &builds[1]);
+    assert_eq!(
+        release.tasm,
+        compile_project_with_options(&entry, &profiles[1]).unwrap()
+    );
+
+    let diff = release.diff_from(debug);
+    assert!(diff.other_instructions < diff.base_instructions);
+    let removed: Vec<(&str, usize)> = diff
+        .removed_sites
+        .iter()
+        .map(|s| (s.check.as_str(), s.line))
+        .collect();
+    assert_eq!(removed, [("assert", 5), ("assume", 13)]);
+    assert!(diff.added_sites.is_empty());
+    let summary = diff.format_summary();
+    assert!(
+        summary.contains("2 assertion site(s) removed"),
+        "{}",
+        summary
+    );
+}
+
+#[test]
+fn test_module_cfg_flags_apply_to_one_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program main\nuse util\n\n#[cfg(fast)]\nfn helper() -> Field {\n    41\n}\n\n#[cfg(debug)]\nfn helper() -> Field {\n    42\n}\n\nfn main() {\n    pub_write(util.mode() + helper())\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("util.tri"),
+        "module util\n\n#[cfg(fast)]\npub fn mode() -> Field {\n    57\n}\n\n#[cfg(release)]\npub fn mode() -> Field {\n    53\n}\n",
+    )
+    .unwrap();
+
+    // Without `fast`, util has no `mode` in a debug build.
+    let mut options = CompileOptions::for_profile("debug");
+    assert!(compile_project_with_options(&entry, &options).is_err());
+
+    options.module_cfg =
+        BTreeMap::from([("util".to_string(), BTreeSet::from(["fast".to_string()]))]);
+    assert!(options.flags_for("util").contains("fast"));
+    assert!(options.flags_for("util.inner").contains("fast"));
+    assert!(!options.flags_for("utility").contains("fast"));
+    assert!(!options.flags_for("main").contains("fast"));
+
+    let tasm = compile_project_with_options(&entry, &options).expect("util sees `fast`");
+    assert!(tasm.contains("push 57"));
+    assert!(tasm.contains("push 42"));
+    assert!(!tasm.contains("push 41"), "main must not see `fast`");
+}
+
 #[test]
 fn test_cfg_const_excluded_in_release() {
     let source = "program test\n#[cfg(debug)]\nconst LEVEL: Field = 3\nfn main() {}";
@@ -268,3 +431,121 @@ fn test_pure_fn_compiles() {
     assert!(result.is_ok(), "pure fn should compile: {:?}", result.err());
 }
 
+const POW2: &str = "#[pure]\nfn pow2(n: U32) -> Field {\n    let mut acc: Field = 1\n    for i in 0..n bounded 64 {\n        acc = acc * 2\n    }\n    acc\n}\n";
+
+#[test]
+fn test_pure_call_with_constant_args_is_folded() {
+    let source = format!(
+        "program test\n{}fn main() {{\n    let x: Field = pub_read()\n    pub_write(x + pow2(10))\n}}",
+        POW2
+    );
+    let tasm = compile(&source, "test.tri").expect("compiles");
+    assert!(tasm.contains("push 1024"), "{}", tasm);
+    assert!(!tasm.contains("call __pow2"), "{}", tasm);
+}
+
+#[test]
+fn test_pure_call_with_runtime_args_is_not_folded() {
+    let source = format!(
+        "program test\n{}fn main() {{\n    let n: U32 = as_u32(pub_read())\n    pub_write(pow2(n))\n}}",
+        POW2
+    );
+    let tasm = compile(&source, "test.tri").expect("compiles");
+    assert!(tasm.contains("call __pow2"), "{}", tasm);
+}
+
+#[test]
+fn test_const_initialized_by_pure_call() {
+    let source = format!(
+        "program test\nconst K: Field = pow2(4)\n{}fn main() {{\n    pub_write(K)\n}}",
+        POW2
+    );
+    let tasm = compile(&source, "test.tri").expect("compiles");
+    assert!(tasm.contains("push 16"), "{}", tasm);
+}
+
+#[test]
+fn test_failing_pure_call_is_left_to_runtime() {
+    let source = "program test\n#[pure]\nfn one(x: Field) -> Field {\n    assert(x == 1)\n    x\n}\nfn main() {\n    pub_write(one(2))\n}";
+    let tasm = compile(source, "test.tri").expect("compiles");
+    assert!(tasm.contains("call __one"), "{}", tasm);
+}
+
+const SQUARES: &str = "#[pure]\nfn square(i: Field, k: Field) -> Field {\n    i * i + k\n}\nconst SQ: [Field; 8] = generate!(square, 1)\n";
+
+#[test]
+fn test_generated_table_constant_index_is_a_push() {
+    let source = format!(
+        "program test\n{}fn main() {{\n    pub_write(SQ[3])\n}}",
+        SQUARES
+    );
+    let tasm = compile(&source, "test.tri").expect("compiles");
+    assert!(tasm.contains("push 10\n    write_io 1"), "{}", tasm);
+    assert!(!tasm.contains("read_mem"), "{}", tasm);
+}
+
+#[test]
+fn test_generated_table_is_written_before_main_and_read_from_ram() {
+    let source = format!(
+        "program test\n{}fn main() {{\n    let i: Field = pub_read()\n    pub_write(SQ[i])\n}}",
+        SQUARES
+    );
+    let tasm = compile(&source, "test.tri").expect("compiles");
+    // Eight words end where the temporaries start (2^29).
+    assert!(
+        tasm.contains("ram[536870904..536870912]: test.SQ"),
+        "{}",
+        tasm
+    );
+    assert!(tasm.contains("push 50"), "{}", tasm);
+    assert!(tasm.contains("write_mem 5"), "{}", tasm);
+    assert!(tasm.contains("write_mem 3"), "{}", tasm);
+    assert!(
+        tasm.contains("push 536870904\n    add\n    read_mem 1"),
+        "{}",
+        tasm
+    );
+}
+
+const COMPOSITES: &str = "struct Config {\n    limit: Field,\n    scale: Field,\n}\nconst LIMITS: [Field; 3] = [7, 8, 9]\nconst CONFIG: Config = Config { limit: 100, scale: LIMITS[2] }\nconst ROOT: Digest = [11, 12, 13, 14, 15]\nconst PAIRS: [(Field, Field); 2] = [(1, 2), (3, 4)]\nconst N: U32 = 3\n";
+
+#[test]
+fn test_array_constant_is_a_data_segment() {
+    let source = format!(
+        "program test\n{}fn main() {{\n    let i: Field = pub_read()\n    pub_write(LIMITS[i])\n    pub_write(LIMITS[1])\n}}",
+        COMPOSITES
+    );
+    let tasm = compile(&source, "test.tri").expect("compiles");
+    assert!(
+        tasm.contains("ram[536870909..536870912]: test.LIMITS"),
+        "{}",
+        tasm
+    );
+    assert!(
+        tasm.contains("push 536870909\n    add\n    read_mem 1"),
+        "{}",
+        tasm
+    );
+    assert!(tasm.contains("push 8\n    write_io 1"), "{}", tasm);
+}
+
+#[test]
+fn test_composite_constants_are_pushed_inline() {
+    let source = format!(
+        "program test\n{}fn main() {{\n    pub_write(CONFIG.limit)\n    let c: Config = CONFIG\n    pub_write(c.scale)\n    let (a, b, d, e, f) = ROOT\n    pub_write(f)\n    let (x, y) = PAIRS[1]\n    pub_write(y)\n    let arr: [Field; N] = [x, a, b]\n    pub_write(arr[2])\n}}",
+        COMPOSITES
+    );
+    let tasm = compile(&source, "test.tri").expect("compiles");
+    assert!(tasm.contains("push 100\n    write_io 1"), "{}", tasm);
+    assert!(tasm.contains("push 100\n    push 9\n"), "{}", tasm);
+    assert!(
+        tasm.contains("push 11\n    push 12\n    push 13\n    push 14\n    push 15\n"),
+        "{}",
+        tasm
+    );
+    assert!(
+        tasm.contains("push 1\n    push 2\n    push 3\n    push 4\n"),
+        "{}",
+        tasm
+    );
+}
@@ -0,0 +1,45 @@
+use crate::diagnostic::Suggestion;
+use crate::*;
+
+fn fix_all(source: &str) -> String {
+    let fixes = collect_fixes(source, "test.tri").expect("should parse");
+    let suggestions = fixes.into_iter().flat_map(|d| d.into_suggestions()).collect();
+    apply_fixes(source, &plan_fixes(source, suggestions))
+}
+
+#[test]
+fn test_fix_unused_import_cfg_and_loop_bounds() {
+    let source = "program test\nuse std.hash\n\nconst N: U32 = 4\n\n#[cfg( debug )]\nfn helper() {\n}\n\nfn main() {\n    for i in 0..N {\n        pub_write(0)\n    }\n    for j in 0..3 bounded 64 {\n        pub_write(1)\n    }\n}\n";
+    let expected = "program test\n\nconst N: U32 = 4\n\n#[cfg(debug)]\nfn helper() {\n}\n\nfn main() {\n    for i in 0..N bounded 4 {\n        pub_write(0)\n    }\n    for j in 0..3 bounded 4 {\n        pub_write(1)\n    }\n}\n";
+    assert_eq!(fix_all(source), expected);
+    // Fixed source has nothing left to fix
+    assert!(collect_fixes(expected, "test.tri").unwrap().is_empty());
+}
+
+#[test]
+fn test_fix_diff_previews_changed_lines() {
+    let source =
+        "program test\nfn main() {\n    for i in 0..10 bounded 128 {\n        pub_write(0)\n    }\n}\n";
+    let fixes = collect_fixes(source, "test.tri").unwrap();
+    let planned = plan_fixes(source, fixes[0].suggestions().to_vec());
+    assert_eq!(
+        fix_diff("test.tri", source, &planned),
+        "--- a/test.tri\n+++ b/test.tri\n@@ -1,6 +1,6 @@\n program test\n fn main() {\n-    for i in 0..10 bounded 128 {\n+    for i in 0..10 bounded 16 {\n         pub_write(0)\n     }\n }\n"
+    );
+}
+
+#[test]
+fn test_plan_fixes_drops_overlapping_suggestions() {
+    let source = "program test\nfn main() {\n}\n";
+    let edit = |start, end, text: &str| Suggestion {
+        message: String::new(),
+        span: span::Span::new(0, start, end),
+        replacement: text.to_string(),
+    };
+    let planned = plan_fixes(source, vec![edit(8, 12, "demo"), edit(10, 12, "xx")]);
+    assert_eq!(planned.len(), 1);
+    assert_eq!(
+        apply_fixes(source, &planned),
+        "program demo\nfn main() {\n}\n"
+    );
+}
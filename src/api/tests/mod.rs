@@ -3,6 +3,7 @@ mod compile;
 mod cost;
 mod docs;
 mod features;
+mod fix;
 mod format;
 mod neptune;
 mod prove;
@@ -17,6 +17,12 @@ fn test_recursive_verifier_compiles() {
         tasm.contains("xx_dot_step"),
         "should emit xx_dot_step instruction"
     );
+    // os.neptune.recursive reads the claim through std.proof even though
+    // os.neptune.proof shares the short name `proof`.
+    assert!(
+        tasm.contains("call std-proof__read_claim"),
+        "claim should be read in the std.proof layout"
+    );
 }
 
 #[test]
@@ -358,3 +364,159 @@ fn test_neptune_type_scripts_compile() {
     }
 }
 
+#[test]
+fn test_fri_verifier_monomorphizes_rounds_across_modules() {
+    let dir = tempfile::tempdir().unwrap();
+    let main_path = dir.path().join("main.tri");
+    std::fs::write(
+        &main_path,
+        r#"program test
+use os.neptune.fri
+
+fn main() {
+let dom: fri.Domain = fri.Domain { offset: 7, generator: 3, log_size: as_u32(8) }
+let layout: fri.Layout = fri.Layout { roots: 1000, alphas: 2000, final_poly: 3000 }
+fri.verify<3, 2, 4>(dom, layout)
+}
+"#,
+    )
+    .unwrap();
+
+    let result = compile_project(&main_path);
+    assert!(
+        result.is_ok(),
+        "FRI verifier should compile: {:?}",
+        result.err()
+    );
+    let tasm = result.unwrap();
+    assert!(tasm.contains("call os-neptune-fri__verify__N3_2_4"));
+    for label in [
+        "os-neptune-fri__verify__N3_2_4:",
+        "os-neptune-fri__commit_phase__N3:",
+        "os-neptune-fri__read_final__N2:",
+        "os-neptune-fri__verify_query__N3_2:",
+        "os-neptune-fri__eval_final__N2:",
+    ] {
+        assert!(tasm.contains(label), "missing instance {}", label);
+    }
+}
+
+#[test]
+fn test_kernel_layout_matches_neptune_transaction_kernel() {
+    use crate::runtime::kernel::{kernel_leaf, KERNEL_FIELDS, KERNEL_TREE_HEIGHT};
+
+    let path = std::path::Path::new("os/neptune/kernel.tri");
+    if !path.exists() {
+        return;
+    }
+    let source = std::fs::read_to_string(path).unwrap();
+    let file = parse_source_silent(&source, "kernel.tri").unwrap();
+    let mut leaves = std::collections::BTreeMap::new();
+    for item in &file.items {
+        let crate::ast::Item::Const(c) = &item.node else {
+            continue;
+        };
+        let crate::ast::Expr::Literal(crate::ast::Literal::Integer(v)) = &c.value.node else {
+            continue;
+        };
+        if c.name.node == "TREE_HEIGHT" {
+            assert_eq!(*v, KERNEL_TREE_HEIGHT as u64, "kernel TREE_HEIGHT");
+        } else if let Some(field) = c.name.node.strip_prefix("LEAF_") {
+            let field = field.to_lowercase();
+            let leaf = kernel_leaf(&field)
+                .unwrap_or_else(|| panic!("{} is not a kernel field", c.name.node));
+            assert_eq!(*v, leaf as u64, "kernel {}", c.name.node);
+            leaves.insert(field, *v);
+        }
+    }
+    assert_eq!(
+        leaves.len(),
+        KERNEL_FIELDS.len(),
+        "kernel.tri should declare a LEAF_* constant for every kernel field"
+    );
+}
+
+#[test]
+fn test_kernel_typed_accessors_compile() {
+    let dir = tempfile::tempdir().unwrap();
+    let main_path = dir.path().join("main.tri");
+    std::fs::write(
+        &main_path,
+        r#"program test
+use os.neptune.kernel
+
+fn main() {
+let h: Digest = kernel.read_lock_script_hash()
+pub_write(kernel.fee(h))
+pub_write(kernel.timestamp(h))
+let (i0, _, _, _, _) = kernel.inputs_root(h)
+let (o0, _, _, _, _) = kernel.outputs_root(h)
+pub_write(i0 + o0)
+}
+"#,
+    )
+    .unwrap();
+
+    let tasm = compile_project(&main_path).expect("kernel accessors should compile");
+    for (accessor, leaf) in [
+        ("authenticate_fee", "push 3"),
+        ("authenticate_timestamp", "push 5"),
+        ("inputs_root", "push 0"),
+        ("outputs_root", "push 1"),
+    ] {
+        let label = format!("os-neptune-kernel__{}:", accessor);
+        let body = tasm
+            .split(&label)
+            .nth(1)
+            .unwrap_or_else(|| panic!("missing {}", label));
+        let body = body.split("return").next().unwrap();
+        assert!(
+            body.contains(leaf),
+            "{} should use leaf `{}`",
+            accessor,
+            leaf
+        );
+        assert!(body.contains("call os-neptune-kernel__authenticate_field"));
+    }
+}
+
+#[test]
+fn test_time_heights_do_not_mix_with_fields() {
+    let dir = tempfile::tempdir().unwrap();
+    let main_path = dir.path().join("main.tri");
+    let program = |check: &str| {
+        format!(
+            r#"program test
+use os.neptune.kernel
+use os.neptune.time
+
+fn main() {{
+let now: time.Height = time.current_height(kernel.read_lock_script_hash())
+let open: time.Height = time.height(divine())
+let close: time.Height = time.height(divine())
+{}
+}}
+"#,
+            check
+        )
+    };
+
+    std::fs::write(&main_path, program("time.assert_between(now, open, close)")).unwrap();
+    let tasm = compile_project(&main_path).expect("height window should compile");
+    assert!(tasm.contains("call os-neptune-time__assert_between"));
+
+    // A raw timestamp where a height is expected does not type-check.
+    for check in [
+        "time.assert_after(now, 1700000000000)",
+        "assert(time.ms(now) == open)",
+    ] {
+        std::fs::write(&main_path, program(check)).unwrap();
+        let err = compile_project(&main_path).expect_err(check);
+        assert!(
+            err.iter().any(|d| d.message.contains("Height")),
+            "{}: {:?}",
+            check,
+            err
+        );
+    }
+}
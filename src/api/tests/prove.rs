@@ -68,6 +68,11 @@ fn vm_crypto_merkle_compiles() {
 
 // ── std layer ──
 
+#[test]
+fn std_bytes_compiles() {
+    assert_compiles("std/bytes.tri");
+}
+
 #[test]
 fn std_crypto_poseidon2_compiles() {
     assert_compiles("std/crypto/poseidon2.tri");
@@ -175,6 +180,11 @@ fn os_neptune_utxo_compiles() {
     assert_compiles("os/neptune/utxo.tri");
 }
 
+#[test]
+fn os_neptune_time_compiles() {
+    assert_compiles("os/neptune/time.tri");
+}
+
 #[test]
 fn os_neptune_plumb_compiles() {
     assert_compiles("os/neptune/standards/plumb.tri");
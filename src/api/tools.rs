@@ -1,11 +1,11 @@
 use super::*;
 
-pub fn analyze_costs(source: &str, filename: &str) -> Result<cost::ProgramCost, Vec<Diagnostic>> {
-    let file = crate::parse_source(source, filename)?;
+pub fn analyze_costs(source: &str, filename: &str) -> Result<cost::ProgramCost, CompileError> {
+    let file = crate::parse_source(source, filename).map_err(CompileError::Parse)?;
 
     if let Err(errors) = TypeChecker::new().check_file(&file) {
         render_diagnostics(&errors, filename, source);
-        return Err(errors);
+        return Err(CompileError::Type(errors));
     }
 
     let cost = cost::CostAnalyzer::default().analyze_file(&file);
@@ -17,49 +17,122 @@ pub fn analyze_costs(source: &str, filename: &str) -> Result<cost::ProgramCost,
 pub fn analyze_costs_project(
     entry_path: &Path,
     options: &CompileOptions,
-) -> Result<cost::ProgramCost, Vec<Diagnostic>> {
-    use crate::pipeline::PreparedProject;
+) -> Result<cost::ProgramCost, CompileError> {
+    let compiler = Compiler::new(entry_path).with_options(options.clone());
+    compiler.costs().cloned()
+}
 
-    let project = PreparedProject::build(entry_path, options)?;
+/// Per-module cost roll-up of a project, flagging functions whose own
+/// padded height is over the target's proving limit, with split points.
+pub fn module_costs_project(
+    entry_path: &Path,
+    options: &CompileOptions,
+) -> Result<cost::ProjectCost, CompileError> {
+    Compiler::new(entry_path)
+        .with_options(options.clone())
+        .module_costs()
+}
 
-    // Analyze costs for the program file (last in topological order)
-    if let Some(file) = project.last_file() {
-        let cost = cost::CostAnalyzer::for_target(&options.target_config.name).analyze_file(file);
-        Ok(cost)
-    } else {
-        Err(vec![Diagnostic::error(
-            "no program file found".to_string(),
-            span::Span::dummy(),
-        )])
+/// Result of checking a project against one target.
+#[derive(Clone, Debug)]
+pub struct TargetCheck {
+    pub target: String,
+    /// Why the target was not checked: its configuration does not load
+    /// (e.g. a register machine without an operand stack).
+    pub skipped: Option<String>,
+    /// Errors from resolving, parsing, or type checking; empty if the
+    /// project checks.
+    pub errors: Vec<Diagnostic>,
+    /// Cost analysis of the program, when requested and the check passed.
+    pub cost: Option<cost::ProgramCost>,
+}
+
+impl TargetCheck {
+    pub fn passed(&self) -> bool {
+        self.skipped.is_none() && self.errors.is_empty()
+    }
+
+    pub fn failed(&self) -> bool {
+        self.skipped.is_none() && !self.errors.is_empty()
     }
 }
 
+/// Type-check a project against every registered target (each
+/// `vm/<name>/target.toml`, plus the built-in Triton target), optionally
+/// running cost analysis per target. Diagnostics are collected, not
+/// rendered. `options` supplies cfg flags and dependency directories;
+/// its target is replaced for each run.
+pub fn check_project_all_targets(
+    entry_path: &Path,
+    options: &CompileOptions,
+    with_costs: bool,
+) -> Vec<TargetCheck> {
+    let targets = crate::resolve::registered_targets()
+        .into_iter()
+        .map(|target| {
+            let config = if target == "triton" {
+                Ok(TerrainConfig::triton())
+            } else {
+                TerrainConfig::resolve(&target)
+            };
+            (target, config)
+        })
+        .collect();
+    check_project_targets(entry_path, options, targets, with_costs)
+}
+
+/// `check_project_all_targets` over `targets`, each with its resolved
+/// configuration; a target whose configuration failed is skipped.
+pub(crate) fn check_project_targets(
+    entry_path: &Path,
+    options: &CompileOptions,
+    targets: Vec<(String, Result<TerrainConfig, Diagnostic>)>,
+    with_costs: bool,
+) -> Vec<TargetCheck> {
+    use crate::pipeline::PreparedProject;
+
+    targets
+        .into_iter()
+        .map(|(target, config)| {
+            let mut check = TargetCheck {
+                target,
+                skipped: None,
+                errors: Vec::new(),
+                cost: None,
+            };
+            let target_config = match config {
+                Ok(config) => config,
+                Err(e) => {
+                    check.skipped = Some(e.message);
+                    return check;
+                }
+            };
+            let target_options = CompileOptions {
+                target_config,
+                ..options.clone()
+            };
+            match PreparedProject::build_silent(entry_path, &target_options) {
+                Ok(project) if with_costs => {
+                    check.cost = project.last_file().map(|file| {
+                        cost::CostAnalyzer::for_config(&target_options.target_config)
+                            .analyze_file(file)
+                    });
+                }
+                Ok(_) => {}
+                Err(errors) => check.errors = errors.into(),
+            }
+            check
+        })
+        .collect()
+}
+
 /// Parse, type-check, and verify a project using symbolic execution + solver.
 ///
 /// Analyzes all functions across all modules, not just `main`.
 /// Returns a `VerificationReport` with static analysis, random testing (Schwartz-Zippel),
 /// and bounded model checking results.
-pub fn verify_project(entry_path: &Path) -> Result<solve::VerificationReport, Vec<Diagnostic>> {
-    use crate::pipeline::PreparedProject;
-
-    let project = PreparedProject::build_default(entry_path)?;
-
-    // Collect constraint systems from all functions in all modules
-    let mut combined = sym::ConstraintSystem::new();
-    for pm in &project.modules {
-        for (_, system) in sym::analyze_all(&pm.file) {
-            combined.constraints.extend(system.constraints);
-            combined.num_variables += system.num_variables;
-            for (k, v) in system.variables {
-                combined.variables.insert(k, v);
-            }
-            combined.pub_inputs.extend(system.pub_inputs);
-            combined.pub_outputs.extend(system.pub_outputs);
-            combined.divine_inputs.extend(system.divine_inputs);
-        }
-    }
-
-    Ok(solve::verify(&combined))
+pub fn verify_project(entry_path: &Path) -> Result<solve::VerificationReport, CompileError> {
+    Compiler::new(entry_path).verify()
 }
 
 /// Verify all functions in a project, returning per-function results.
@@ -67,7 +140,7 @@ pub fn verify_project(entry_path: &Path) -> Result<solve::VerificationReport, Ve
 /// Each entry in the returned vec is `(module_name, fn_name, report)`.
 pub fn verify_project_per_function(
     entry_path: &Path,
-) -> Result<Vec<(String, String, solve::VerificationReport)>, Vec<Diagnostic>> {
+) -> Result<Vec<(String, String, solve::VerificationReport)>, CompileError> {
     use crate::pipeline::PreparedProject;
 
     let project = PreparedProject::build_default(entry_path)?;
@@ -84,6 +157,34 @@ pub fn verify_project_per_function(
     Ok(results)
 }
 
+/// A structural match of a `trident grep` pattern.
+#[derive(Clone, Debug)]
+pub struct GrepMatch {
+    pub file: std::path::PathBuf,
+    pub module: String,
+    pub function: String,
+    /// 1-based line and column of the matched expression.
+    pub line: usize,
+    pub column: usize,
+    /// Source text of the match.
+    pub text: String,
+    /// Source text each named metavariable matched.
+    pub bindings: BTreeMap<String, String>,
+}
+
+/// Find the structural matches of `pattern` in every module of a
+/// project, its dependencies and standard library modules included,
+/// in module then source order.
+pub fn grep_project(
+    entry_path: &Path,
+    options: &CompileOptions,
+    pattern: &ast::search::Pattern,
+) -> Result<Vec<GrepMatch>, CompileError> {
+    Compiler::new(entry_path)
+        .with_options(options.clone())
+        .grep(pattern)
+}
+
 /// Count the number of TASM instructions in a compiled output string.
 /// Skips comments, labels, blank lines, and the halt instruction.
 pub fn count_tasm_instructions(tasm: &str) -> usize {
@@ -290,15 +391,12 @@ impl ModuleBenchmarkResult {
 }
 
 /// Generate markdown documentation for a Trident project.
-pub fn generate_docs(
-    entry_path: &Path,
-    options: &CompileOptions,
-) -> Result<String, Vec<Diagnostic>> {
+pub fn generate_docs(entry_path: &Path, options: &CompileOptions) -> Result<String, CompileError> {
     doc::generate_docs(entry_path, options)
 }
 
 /// Parse, type-check, and produce per-line cost-annotated source output.
-pub fn annotate_source(source: &str, filename: &str) -> Result<String, Vec<Diagnostic>> {
+pub fn annotate_source(source: &str, filename: &str) -> Result<String, CompileError> {
     annotate_source_with_target(source, filename, "triton")
 }
 
@@ -307,12 +405,12 @@ pub fn annotate_source_with_target(
     source: &str,
     filename: &str,
     target: &str,
-) -> Result<String, Vec<Diagnostic>> {
-    let file = crate::parse_source(source, filename)?;
+) -> Result<String, CompileError> {
+    let file = crate::parse_source(source, filename).map_err(CompileError::Parse)?;
 
     if let Err(errors) = TypeChecker::new().check_file(&file) {
         render_diagnostics(&errors, filename, source);
-        return Err(errors);
+        return Err(CompileError::Type(errors));
     }
 
     let mut analyzer = cost::CostAnalyzer::for_target(target);
@@ -365,28 +463,57 @@ pub fn annotate_source_with_target(
 }
 
 /// Format Trident source code, preserving comments.
-pub fn format_source(source: &str, _filename: &str) -> Result<String, Vec<Diagnostic>> {
+pub fn format_source(source: &str, _filename: &str) -> Result<String, CompileError> {
     let (tokens, comments, lex_errors) = lexer::Lexer::new(source, 0).tokenize();
     if !lex_errors.is_empty() {
-        return Err(lex_errors);
+        return Err(CompileError::Parse(lex_errors));
     }
-    let file = parser::Parser::new(tokens).parse_file()?;
+    let file = parser::Parser::new(tokens)
+        .parse_file()
+        .map_err(CompileError::Parse)?;
     Ok(format::format_file(&file, &comments))
 }
 
 /// Type-check only, without rendering diagnostics to stderr.
 /// Used by the LSP server to get structured errors.
-pub fn check_silent(source: &str, filename: &str) -> Result<(), Vec<Diagnostic>> {
-    let file = crate::parse_source_silent(source, filename)?;
-    TypeChecker::new().check_file(&file)?;
+pub fn check_silent(source: &str, filename: &str) -> Result<(), CompileError> {
+    let file = crate::parse_source_silent(source, filename).map_err(CompileError::Parse)?;
+    TypeChecker::new()
+        .check_file(&file)
+        .map_err(CompileError::Type)?;
     Ok(())
 }
 
+/// Type-check a single file and return every diagnostic, warnings
+/// included, without rendering. Used by the `tests/ui` golden files.
+pub fn collect_diagnostics(source: &str, filename: &str) -> Vec<Diagnostic> {
+    let file = match crate::parse_source_silent(source, filename) {
+        Ok(file) => file,
+        Err(errors) => return errors,
+    };
+    match TypeChecker::new().check_file(&file) {
+        Ok(exports) => exports.warnings,
+        Err(errors) => errors,
+    }
+}
+
+/// H0004 hints for one source buffer, each with a suggestion that
+/// tightens its `bounded N` literal. Parse errors yield no hints;
+/// nothing is rendered.
+pub fn loop_bound_fixes(source: &str, filename: &str) -> Vec<Diagnostic> {
+    match crate::parse_source_silent(source, filename) {
+        Ok(file) => cost::CostAnalyzer::default()
+            .analyze_file(&file)
+            .loop_bound_fixes(source),
+        Err(_) => Vec::new(),
+    }
+}
+
 /// Project-aware type-check for the LSP.
 /// Finds trident.toml, resolves dependencies, and type-checks
 /// the given file with full module context.
 /// Falls back to single-file check if no project is found.
-pub fn check_file_in_project(source: &str, file_path: &Path) -> Result<(), Vec<Diagnostic>> {
+pub fn check_file_in_project(source: &str, file_path: &Path) -> Result<(), CompileError> {
     let dir = file_path.parent().unwrap_or(Path::new("."));
     let entry = match project::Project::find(dir) {
         Some(toml_path) => match project::Project::load(&toml_path) {
@@ -417,7 +544,8 @@ pub fn check_file_in_project(source: &str, file_path: &Path) -> Result<(), Vec<D
 
         // Use live buffer for the file being edited
         let src = if is_target { source } else { &module.source };
-        let parsed = crate::parse_source_silent(src, &module.file_path.to_string_lossy())?;
+        let parsed = crate::parse_source_silent(src, &module.file_path.to_string_lossy())
+            .map_err(CompileError::Parse)?;
 
         let mut tc = TypeChecker::new();
         for exports in &all_exports {
@@ -430,7 +558,7 @@ pub fn check_file_in_project(source: &str, file_path: &Path) -> Result<(), Vec<D
             }
             Err(errors) => {
                 if is_target {
-                    return Err(errors);
+                    return Err(CompileError::Type(errors));
                 }
                 // Dep has errors — stop, but don't report
                 // dep errors as if they're in this file
@@ -0,0 +1,224 @@
+//! Item lookup for `trident view`.
+//!
+//! A query names a function, struct, constant, or event, optionally
+//! qualified by its module (`crypto.sponge.absorb`, or `sponge.absorb`
+//! by the module's last segment), or a generic function's instance with
+//! its size arguments (`first<3>`). A hex query is tried as a function's
+//! content hash prefix first.
+
+use crate::ast::display::{format_instance_signature, format_item};
+use crate::ast::navigate::looks_like_hash;
+use crate::ast::Item;
+use crate::cost::{CostAnalyzer, TableCost};
+use crate::hash::ContentHash;
+use crate::pipeline::{ParsedModule, PreparedProject};
+use crate::span::Spanned;
+use crate::CompileOptions;
+
+/// An item found by `trident view`.
+#[derive(Clone, Debug)]
+pub struct ViewedItem {
+    /// Full name of the module that defines the item.
+    pub module: String,
+    /// Item name; `first<3>` for an instance of a generic function.
+    pub name: String,
+    /// `fn`, `struct`, `const`, or `event`.
+    pub kind: &'static str,
+    /// Formatted source of the item.
+    pub source: String,
+    /// For an instance, the signature with its size arguments substituted.
+    pub instance_signature: Option<String>,
+    /// Content hash, for functions.
+    pub hash: Option<ContentHash>,
+    /// Static cost, for functions with a body (of the instance, for an
+    /// instance).
+    pub cost: Option<TableCost>,
+    /// Short table names of the target's cost model, for `cost`.
+    pub short_names: Vec<String>,
+}
+
+/// Find the item `query` names. Unqualified names are looked up in the
+/// program module first, then in the other modules in dependency order.
+pub(crate) fn view_item(
+    project: &PreparedProject,
+    options: &CompileOptions,
+    query: &str,
+) -> Option<ViewedItem> {
+    if looks_like_hash(query) {
+        if let Some(found) = find_by_hash(project, query) {
+            return Some(found.into_view(project, options, &[]));
+        }
+    }
+
+    let (path, size_args) = match query.split_once('<') {
+        Some((path, rest)) => {
+            let args = rest.strip_suffix('>')?;
+            let size_args = args
+                .split(',')
+                .map(|arg| arg.trim().parse::<u64>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            (path, size_args)
+        }
+        None => (query, Vec::new()),
+    };
+    let (module, name) = match path.rsplit_once('.') {
+        Some((module, name)) => (Some(module), name),
+        None => (None, path),
+    };
+
+    let entry = project.program_module().or_else(|| project.modules.last());
+    let candidates = entry
+        .into_iter()
+        .chain(project.modules.iter())
+        .filter(|pm| module.is_none_or(|m| names_module(pm, m)));
+    for pm in candidates {
+        // Of several cfg variants, the one active under `options`.
+        let flags = options.flags_for(&pm.file.name.node);
+        let Some(item) = pm.file.items.iter().find(|i| {
+            item_name(&i.node) == name && item_cfg(&i.node).is_none_or(|f| flags.contains(&f.node))
+        }) else {
+            continue;
+        };
+        let found = Found {
+            pm,
+            item: &item.node,
+        };
+        if !size_args.is_empty() {
+            let Item::Fn(func) = &item.node else {
+                return None;
+            };
+            let subs = func
+                .type_params
+                .iter()
+                .map(|p| p.node.clone())
+                .zip(size_args.iter().copied())
+                .collect();
+            let fits = func.type_params.len() == size_args.len()
+                && func.where_clause.iter().all(|b| b.node.holds(&subs));
+            if !fits {
+                return None;
+            }
+        }
+        return Some(found.into_view(project, options, &size_args));
+    }
+    None
+}
+
+/// Every function, struct, constant, and event of the project, as
+/// `module.name`, with the content hash of each function.
+pub(crate) fn item_index(project: &PreparedProject) -> Vec<(String, Option<ContentHash>)> {
+    let mut index = Vec::new();
+    for pm in &project.modules {
+        let hashes = crate::hash::hash_file(&pm.file);
+        for item in &pm.file.items {
+            let name = item_name(&item.node);
+            index.push((
+                format!("{}.{}", pm.file.name.node, name),
+                hashes.get(name).copied(),
+            ));
+        }
+    }
+    index
+}
+
+struct Found<'a> {
+    pm: &'a ParsedModule,
+    item: &'a Item,
+}
+
+impl Found<'_> {
+    fn into_view(
+        self,
+        project: &PreparedProject,
+        options: &CompileOptions,
+        size_args: &[u64],
+    ) -> ViewedItem {
+        let name = item_name(self.item);
+        let mut view = ViewedItem {
+            module: self.pm.file.name.node.clone(),
+            name: name.to_string(),
+            kind: match self.item {
+                Item::Fn(_) => "fn",
+                Item::Struct(_) => "struct",
+                Item::Const(_) => "const",
+                Item::Event(_) => "event",
+            },
+            source: format_item(self.item),
+            instance_signature: None,
+            hash: None,
+            cost: None,
+            short_names: Vec::new(),
+        };
+        let Item::Fn(func) = self.item else {
+            return view;
+        };
+        if !size_args.is_empty() {
+            let args: Vec<String> = size_args.iter().map(u64::to_string).collect();
+            view.name = format!("{}<{}>", name, args.join(", "));
+            view.instance_signature = Some(format_instance_signature(func, size_args));
+        }
+        view.hash = crate::hash::hash_file(&self.pm.file).get(name).copied();
+        if func.body.is_some() {
+            let mut analyzer = CostAnalyzer::for_config(&options.target_config)
+                .with_pure_fns(project.pure_functions());
+            view.cost = analyzer.function_cost(&self.pm.file, name, size_args);
+            view.short_names = analyzer
+                .cost_model
+                .table_short_names()
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+        }
+        view
+    }
+}
+
+/// The unique function whose content hash starts with `prefix`.
+fn find_by_hash<'a>(project: &'a PreparedProject, prefix: &str) -> Option<Found<'a>> {
+    let mut matches = Vec::new();
+    for pm in &project.modules {
+        let hashes = crate::hash::hash_file(&pm.file);
+        if let Some((_, func)) =
+            crate::ast::navigate::find_function_by_hash(&pm.file, &hashes, prefix)
+        {
+            let item = pm
+                .file
+                .items
+                .iter()
+                .find(|i| matches!(&i.node, Item::Fn(f) if f.name.node == func.name.node))?;
+            matches.push(Found {
+                pm,
+                item: &item.node,
+            });
+        }
+    }
+    if matches.len() == 1 {
+        matches.pop()
+    } else {
+        None
+    }
+}
+
+/// Whether `module` names `pm`: its full name, or its last segment.
+fn names_module(pm: &ParsedModule, module: &str) -> bool {
+    let full = &pm.file.name.node;
+    full == module || full.rsplit('.').next() == Some(module)
+}
+
+fn item_name(item: &Item) -> &str {
+    match item {
+        Item::Fn(f) => &f.name.node,
+        Item::Struct(s) => &s.name.node,
+        Item::Const(c) => &c.name.node,
+        Item::Event(e) => &e.name.node,
+    }
+}
+
+fn item_cfg(item: &Item) -> Option<&Spanned<String>> {
+    match item {
+        Item::Fn(f) => f.cfg.as_ref(),
+        Item::Struct(s) => s.cfg.as_ref(),
+        Item::Const(c) => c.cfg.as_ref(),
+        Item::Event(e) => e.cfg.as_ref(),
+    }
+}
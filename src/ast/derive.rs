@@ -0,0 +1,414 @@
+//! Built-in derives: `#[derive(Hash)]` and `#[derive(FromWitness)]` on
+//! structs, expanded after parsing into ordinary functions.
+//!
+//! For `struct Note` the derives generate, right after the struct:
+//!
+//! - `Hash`: `fn hash_note(value: Note) -> Digest`, hashing the fields'
+//!   elements in declaration order. Up to ten elements take one `hash`,
+//!   zero-padded; longer structs chain, each further `hash` absorbing the
+//!   previous digest and the next five elements.
+//! - `FromWitness`: `fn divine_note() -> Note`, reading each field from
+//!   secret input in declaration order. `U32` fields are range-checked
+//!   with `as_u32`.
+//!
+//! The functions share the struct's visibility, `cfg`, and span, so docs,
+//! costs, and verification see them like handwritten code. Fields may be
+//! `Field`, `U32`, `Bool`, `Digest`, arrays of these, or structs of the
+//! same file that derive the same trait.
+
+use super::{
+    Block, Expr, File, FnDef, Item, Literal, ModulePath, Param, Pattern, Stmt, StructDef, Type,
+};
+use crate::diagnostic::Diagnostic;
+use crate::span::{Span, Spanned};
+
+/// Names accepted in `#[derive(..)]`.
+pub const DERIVES: &[&str] = &["Hash", "FromWitness"];
+
+/// Elements one `hash` call absorbs.
+const RATE: usize = 10;
+/// Elements of a digest.
+const DIGEST_WIDTH: usize = 5;
+
+/// Insert the functions of every struct's derives after the struct.
+pub fn expand(file: &mut File) -> Result<(), Vec<Diagnostic>> {
+    let structs: Vec<StructDef> = file
+        .items
+        .iter()
+        .filter_map(|item| match &item.node {
+            Item::Struct(s) => Some(s.clone()),
+            _ => None,
+        })
+        .collect();
+    if structs.iter().all(|s| s.derives.is_empty()) {
+        return Ok(());
+    }
+
+    let mut errors = Vec::new();
+    let mut items = Vec::with_capacity(file.items.len());
+    for item in std::mem::take(&mut file.items) {
+        let derived = match &item.node {
+            Item::Struct(s) => s
+                .derives
+                .iter()
+                .filter_map(|derive| {
+                    let ctx = Expander {
+                        structs: &structs,
+                        derive: &derive.node,
+                        span: item.span,
+                    };
+                    let result = match derive.node.as_str() {
+                        "Hash" => ctx.hash_fn(s),
+                        "FromWitness" => ctx.divine_fn(s),
+                        _ => return None,
+                    };
+                    result.map_err(|e| errors.push(e)).ok()
+                })
+                .map(|f| Spanned::new(Item::Fn(f), item.span))
+                .collect(),
+            _ => Vec::new(),
+        };
+        items.push(item);
+        items.extend(derived);
+    }
+    file.items = items;
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Name of the function `derive` generates for the struct `name`:
+/// `hash_note` for `Note`.
+pub fn derived_fn_name(derive: &str, name: &str) -> String {
+    let prefix = if derive == "Hash" { "hash" } else { "divine" };
+    let mut snake = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && prev_lower {
+            snake.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        snake.push(c.to_ascii_lowercase());
+    }
+    format!("{}_{}", prefix, snake)
+}
+
+struct Expander<'a> {
+    structs: &'a [StructDef],
+    derive: &'a str,
+    span: Span,
+}
+
+impl Expander<'_> {
+    fn spanned<T>(&self, node: T) -> Spanned<T> {
+        Spanned::new(node, self.span)
+    }
+
+    fn call(&self, name: &str, args: Vec<Spanned<Expr>>) -> Spanned<Expr> {
+        self.spanned(Expr::Call {
+            path: self.spanned(ModulePath::single(name.to_string())),
+            generic_args: Vec::new(),
+            args,
+        })
+    }
+
+    fn int(&self, n: u64) -> Spanned<Expr> {
+        self.spanned(Expr::Literal(Literal::Integer(n)))
+    }
+
+    fn function(&self, s: &StructDef, params: Vec<Param>, return_ty: Type, body: Block) -> FnDef {
+        FnDef {
+            is_pub: s.is_pub,
+            cfg: s.cfg.clone(),
+            intrinsic: None,
+            is_test: false,
+            test_max_cycles: None,
+            is_pure: false,
+            is_entry: false,
+            no_spill: false,
+            requires: Vec::new(),
+            ensures: Vec::new(),
+            allows: Vec::new(),
+            name: self.spanned(derived_fn_name(self.derive, &s.name.node)),
+            type_params: Vec::new(),
+            params,
+            return_ty: Some(self.spanned(return_ty)),
+            where_clause: Vec::new(),
+            body: Some(self.spanned(body)),
+        }
+    }
+
+    /// The derived struct a field of type `ty` names, if it is one.
+    fn derived_struct(&self, ty: &Type) -> Option<&StructDef> {
+        let Type::Named(path) = ty else {
+            return None;
+        };
+        let [name] = path.0.as_slice() else {
+            return None;
+        };
+        self.structs
+            .iter()
+            .find(|s| &s.name.node == name && s.derives.iter().any(|d| d.node == self.derive))
+    }
+
+    fn unsupported(&self, s: &StructDef, field: &str, ty: &Type) -> Diagnostic {
+        Diagnostic::error(
+            format!(
+                "cannot derive {} for `{}`: field `{}` has type {}",
+                self.derive,
+                s.name.node,
+                field,
+                super::display::format_ast_type(ty)
+            ),
+            self.span,
+        )
+        .with_help(format!(
+            "derived fields must be Field, U32, Bool, Digest, arrays of these, or structs of this file that derive {}",
+            self.derive
+        ))
+    }
+
+    fn hash_fn(&self, s: &StructDef) -> Result<FnDef, Diagnostic> {
+        let mut stmts = Vec::new();
+        let mut elems = Vec::new();
+        for field in &s.fields {
+            let value = self.spanned(Expr::Var(format!("value.{}", field.name.node)));
+            if !self.flatten(&field.ty.node, value, &mut elems, &mut stmts) {
+                return Err(self.unsupported(s, &field.name.node, &field.ty.node));
+            }
+        }
+
+        let mut rest = elems.into_iter();
+        let mut chunk: Vec<_> = rest.by_ref().take(RATE).collect();
+        let mut round = 0;
+        loop {
+            chunk.resize_with(RATE, || self.int(0));
+            let digest = self.call("hash", chunk);
+            let next: Vec<_> = rest.by_ref().take(RATE - DIGEST_WIDTH).collect();
+            if next.is_empty() {
+                let body = Block {
+                    stmts,
+                    tail_expr: Some(Box::new(digest)),
+                };
+                let param = Param {
+                    name: self.spanned("value".to_string()),
+                    ty: self.spanned(Type::Named(ModulePath::single(s.name.node.clone()))),
+                };
+                return Ok(self.function(s, vec![param], Type::Digest, body));
+            }
+            chunk = self.destructure(&format!("h{}", round), digest, &mut stmts);
+            chunk.extend(next);
+            round += 1;
+        }
+    }
+
+    /// Bind the elements of `digest` to fresh locals `{prefix}_0..`,
+    /// returning the locals.
+    fn destructure(
+        &self,
+        prefix: &str,
+        digest: Spanned<Expr>,
+        stmts: &mut Vec<Spanned<Stmt>>,
+    ) -> Vec<Spanned<Expr>> {
+        let names: Vec<String> = (0..DIGEST_WIDTH)
+            .map(|i| format!("{}_{}", prefix, i))
+            .collect();
+        stmts.push(self.spanned(Stmt::Let {
+            mutable: false,
+            pattern: Pattern::Tuple(names.iter().map(|n| self.spanned(n.clone())).collect()),
+            ty: None,
+            init: digest,
+        }));
+        names
+            .into_iter()
+            .map(|n| self.spanned(Expr::Var(n)))
+            .collect()
+    }
+
+    /// Push the field elements of `value` (of type `ty`) onto `elems`.
+    /// Returns false for a type that cannot be hashed.
+    fn flatten(
+        &self,
+        ty: &Type,
+        value: Spanned<Expr>,
+        elems: &mut Vec<Spanned<Expr>>,
+        stmts: &mut Vec<Spanned<Stmt>>,
+    ) -> bool {
+        match ty {
+            Type::Field => elems.push(value),
+            Type::U32 => elems.push(self.call("as_field", vec![value])),
+            Type::Bool => elems.push(self.spanned(Expr::If {
+                cond: Box::new(value),
+                then_block: Box::new(self.spanned(Block {
+                    stmts: Vec::new(),
+                    tail_expr: Some(Box::new(self.int(1))),
+                })),
+                else_block: Box::new(self.spanned(Block {
+                    stmts: Vec::new(),
+                    tail_expr: Some(Box::new(self.int(0))),
+                })),
+            })),
+            Type::Digest => {
+                let prefix = format!("d{}", stmts.len());
+                elems.extend(self.destructure(&prefix, value, stmts));
+            }
+            Type::Array(inner, size) => {
+                let Some(len) = size.as_literal() else {
+                    return false;
+                };
+                for i in 0..len {
+                    let elem = self.spanned(Expr::Index {
+                        expr: Box::new(value.clone()),
+                        index: Box::new(self.int(i)),
+                    });
+                    if !self.flatten(inner, elem, elems, stmts) {
+                        return false;
+                    }
+                }
+            }
+            _ => match self.derived_struct(ty) {
+                Some(inner) => {
+                    let name = derived_fn_name(self.derive, &inner.name.node);
+                    let digest = self.call(&name, vec![value]);
+                    let prefix = format!("d{}", stmts.len());
+                    elems.extend(self.destructure(&prefix, digest, stmts));
+                }
+                None => return false,
+            },
+        }
+        true
+    }
+
+    fn divine_fn(&self, s: &StructDef) -> Result<FnDef, Diagnostic> {
+        let mut fields = Vec::new();
+        for field in &s.fields {
+            let Some(value) = self.read(&field.ty.node) else {
+                return Err(self.unsupported(s, &field.name.node, &field.ty.node));
+            };
+            fields.push((field.name.clone(), value));
+        }
+        let init = self.spanned(Expr::StructInit {
+            path: self.spanned(ModulePath::single(s.name.node.clone())),
+            fields,
+        });
+        let body = Block {
+            stmts: Vec::new(),
+            tail_expr: Some(Box::new(init)),
+        };
+        let ty = Type::Named(ModulePath::single(s.name.node.clone()));
+        Ok(self.function(s, Vec::new(), ty, body))
+    }
+
+    /// An expression reading a value of type `ty` from secret input.
+    fn read(&self, ty: &Type) -> Option<Spanned<Expr>> {
+        let value = match ty {
+            Type::Field => self.call("divine", Vec::new()),
+            Type::U32 => self.call("as_u32", vec![self.call("divine", Vec::new())]),
+            Type::Bool => self.spanned(Expr::BinOp {
+                op: super::BinOp::Eq,
+                lhs: Box::new(self.call("divine", Vec::new())),
+                rhs: Box::new(self.int(1)),
+            }),
+            Type::Digest => self.call(&format!("divine{}", DIGEST_WIDTH), Vec::new()),
+            Type::Array(inner, size) => {
+                let elems = (0..size.as_literal()?)
+                    .map(|_| self.read(inner))
+                    .collect::<Option<Vec<_>>>()?;
+                self.spanned(Expr::ArrayInit(elems))
+            }
+            _ => {
+                let inner = self.derived_struct(ty)?;
+                self.call(&derived_fn_name(self.derive, &inner.name.node), Vec::new())
+            }
+        };
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::display::format_item;
+
+    /// The formatted derived functions, each on one line.
+    fn expanded(source: &str) -> Result<Vec<String>, Vec<Diagnostic>> {
+        let file = crate::parse_source_silent(source, "test.tri")?;
+        Ok(file
+            .items
+            .iter()
+            .filter(|item| matches!(item.node, Item::Fn(_)))
+            .map(|item| {
+                let text = format_item(&item.node);
+                let words: Vec<&str> = text.split_whitespace().collect();
+                words
+                    .join(" ")
+                    .replace("( ", "(")
+                    .replace(", )", ")")
+                    .replace(" )", ")")
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_derived_functions_follow_field_order() {
+        let fns = expanded(
+            "module m\n#[derive(Hash, FromWitness)]\npub struct Note {\n    amount: Field,\n    nonce: U32,\n    owner: Digest,\n}\n",
+        )
+        .unwrap();
+        assert_eq!(fns.len(), 2);
+        assert!(fns[0].starts_with("pub fn hash_note(value: Note) -> Digest {"));
+        assert!(fns[0].contains("let (d0_0, d0_1, d0_2, d0_3, d0_4) = value.owner"));
+        assert!(fns[0].contains(
+            "hash(value.amount, as_field(value.nonce), d0_0, d0_1, d0_2, d0_3, d0_4, 0, 0, 0)"
+        ));
+        assert!(fns[1].starts_with("pub fn divine_note() -> Note {"));
+        assert!(fns[1].contains("amount: divine()"));
+        assert!(fns[1].contains("nonce: as_u32(divine())"));
+        assert!(fns[1].contains("owner: divine5()"));
+    }
+
+    #[test]
+    fn test_long_structs_chain_hashes() {
+        let fns = expanded("module m\n#[derive(Hash)]\nstruct Row {\n    cells: [Field; 12],\n}\n")
+            .unwrap();
+        assert_eq!(fns.len(), 1);
+        assert!(fns[0].starts_with("fn hash_row(value: Row) -> Digest {"));
+        assert!(fns[0].contains("let (h0_0, h0_1, h0_2, h0_3, h0_4) = hash(value.cells[0],"));
+        assert!(fns[0].contains(
+            "hash(h0_0, h0_1, h0_2, h0_3, h0_4, value.cells[10], value.cells[11], 0, 0, 0)"
+        ));
+    }
+
+    #[test]
+    fn test_nested_structs_use_their_derives() {
+        let fns = expanded(
+            "module m\n#[derive(Hash)]\nstruct Point {\n    x: Field,\n    y: Field,\n}\n#[derive(Hash)]\nstruct Segment {\n    a: Point,\n    b: Point,\n}\n",
+        )
+        .unwrap();
+        assert!(fns[1].contains("= hash_point(value.a)"));
+        assert!(fns[1].contains("= hash_point(value.b)"));
+    }
+
+    #[test]
+    fn test_derive_errors() {
+        let errors =
+            expanded("module m\n#[derive(Hash)]\nstruct Point {\n    x: XField,\n}\n").unwrap_err();
+        assert!(errors[0].message.contains("field `x` has type XField"));
+        let errors =
+            expanded("module m\n#[derive(Debug)]\nstruct Point {\n    x: Field,\n}\n").unwrap_err();
+        assert!(errors[0].message.contains("unknown derive `Debug`"));
+        let errors = expanded("module m\n#[derive(Hash)]\nfn f() {\n}\n").unwrap_err();
+        assert!(errors[0].message.contains("only allowed on structs"));
+    }
+
+    #[test]
+    fn test_derived_name_is_snake_case() {
+        assert_eq!(
+            derived_fn_name("Hash", "AccountState"),
+            "hash_account_state"
+        );
+        assert_eq!(derived_fn_name("FromWitness", "Note2"), "divine_note2");
+    }
+}
@@ -3,6 +3,8 @@
 //! This module is the single source of truth for converting AST types,
 //! function signatures, and constant values to display strings.
 
+use std::collections::BTreeMap;
+
 use super::{Expr, File, FileKind, FnDef, Item, Literal, Type};
 use crate::format;
 use crate::span::Spanned;
@@ -10,19 +12,26 @@ use crate::span::Spanned;
 /// Pretty-print a single function definition by wrapping it in a
 /// minimal synthetic `File` and running the canonical formatter.
 pub fn format_function(func: &FnDef) -> String {
-    // Build a minimal File containing only this function.
+    format_item(&Item::Fn(func.clone()))
+}
+
+/// Pretty-print a single item (function, struct, constant, or event)
+/// the same way.
+pub fn format_item(item: &Item) -> String {
+    // Build a minimal File containing only this item.
     let file = File {
         kind: FileKind::Program,
         name: Spanned::dummy("_view".to_string()),
         uses: Vec::new(),
+        mods: Vec::new(),
         declarations: Vec::new(),
-        items: vec![Spanned::dummy(Item::Fn(func.clone()))],
+        items: vec![Spanned::dummy(item.clone())],
     };
 
     let formatted = format::format_file(&file, &[]);
 
-    // The formatter emits "program _view\n\n<fn>\n".
-    // Strip the synthetic header to isolate the function text.
+    // The formatter emits "program _view\n\n<item>\n".
+    // Strip the synthetic header to isolate the item text.
     strip_synthetic_header(&formatted)
 }
 
@@ -54,13 +63,23 @@ pub fn format_ast_type(ty: &Type) -> String {
             let parts: Vec<_> = elems.iter().map(format_ast_type).collect();
             format!("({})", parts.join(", "))
         }
+        Type::NamedTuple(components) => {
+            let parts: Vec<_> = components
+                .iter()
+                .map(|(name, t)| format!("{}: {}", name, format_ast_type(t)))
+                .collect();
+            format!("({})", parts.join(", "))
+        }
+        Type::Option(inner) => format!("Option<{}>", format_ast_type(inner)),
+        Type::Bytes(size) => format!("Bytes<{}>", size),
         Type::Named(path) => path.as_dotted(),
     }
 }
 
 /// Format a function signature for display (documentation, diagnostics).
 ///
-/// Includes type parameters, parameter names and types, and return type.
+/// Includes type parameters, parameter names and types, return type, and
+/// `where` clause.
 pub fn format_fn_signature(func: &FnDef) -> String {
     let mut sig = String::from("fn ");
     sig.push_str(&func.name.node);
@@ -83,9 +102,57 @@ pub fn format_fn_signature(func: &FnDef) -> String {
         sig.push_str(&format!(" -> {}", format_ast_type(&ret.node)));
     }
 
+    sig.push_str(&format_where_clause(func));
     sig
 }
 
+/// Format the signature of a generic function's instance: `first<3>`
+/// with its size parameters substituted, e.g.
+/// `fn first<3>(xs: [Field; 3]) -> Field`.
+pub fn format_instance_signature(func: &FnDef, size_args: &[u64]) -> String {
+    let subs: BTreeMap<String, u64> = func
+        .type_params
+        .iter()
+        .map(|p| p.node.clone())
+        .zip(size_args.iter().copied())
+        .collect();
+    let args: Vec<String> = size_args.iter().map(u64::to_string).collect();
+    let mut sig = format!("fn {}<{}>(", func.name.node, args.join(", "));
+    let params: Vec<String> = func
+        .params
+        .iter()
+        .map(|p| {
+            format!(
+                "{}: {}",
+                p.name.node,
+                format_ast_type(&p.ty.node.with_sizes(&subs))
+            )
+        })
+        .collect();
+    sig.push_str(&params.join(", "));
+    sig.push(')');
+    if let Some(ref ret) = func.return_ty {
+        sig.push_str(&format!(
+            " -> {}",
+            format_ast_type(&ret.node.with_sizes(&subs))
+        ));
+    }
+    sig
+}
+
+/// Format a `where` clause with a leading space, or `""` when there is none.
+pub fn format_where_clause(func: &FnDef) -> String {
+    if func.where_clause.is_empty() {
+        return String::new();
+    }
+    let bounds: Vec<String> = func
+        .where_clause
+        .iter()
+        .map(|b| b.node.to_string())
+        .collect();
+    format!(" where {}", bounds.join(", "))
+}
+
 /// Format a constant value expression for display (documentation).
 pub fn format_const_value(expr: &Expr) -> String {
     match expr {
@@ -138,4 +205,15 @@ mod tests {
 
         assert!(formatted.contains("pub fn helper("));
     }
+
+    #[test]
+    fn test_format_instance_signature() {
+        let source = "module test\n\npub fn first<N>(xs: [Field; N], pad: [Field; N + 1]) -> [Field; N] {\n    xs\n}\n";
+        let file = parse_file(source);
+        let func = find_function(&file, "first").expect("first function should exist");
+        assert_eq!(
+            format_instance_signature(func, &[3]),
+            "fn first<3>(xs: [Field; 3], pad: [Field; 4]) -> [Field; 3]"
+        );
+    }
 }
@@ -0,0 +1,837 @@
+//! Compile-time evaluation of `#[pure]` functions.
+//!
+//! A call to a pure function whose arguments are all constants is run
+//! here and replaced by its result, so `pow2(10)` compiles to `push 1024`
+//! and `const K: Field = pow2(10)` is a constant like any literal.
+//! Evaluation is bounded by a fuel limit. Whatever cannot be evaluated —
+//! an unsupported builtin, a failing assertion, running out of fuel —
+//! leaves the call to run at runtime, where it behaves as before.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::*;
+use crate::field::goldilocks::Goldilocks;
+use crate::field::PrimeField;
+use crate::span::Spanned;
+
+/// Evaluation steps a single folded call or constant may take.
+pub const DEFAULT_FUEL: u64 = 10_000;
+
+/// Nested pure calls deeper than this are not folded.
+const MAX_CALL_DEPTH: u32 = 64;
+
+/// Longest table a `generate!` constant may fill.
+pub const MAX_TABLE_LEN: u64 = 1 << 16;
+
+/// A compile-time value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Value {
+    Elem(u64),
+    /// Tuple, array or XField components, first component deepest.
+    Seq(Vec<Value>),
+    /// Struct fields in initializer order, as the emitter lays them out.
+    Struct(Vec<(String, Value)>),
+}
+
+impl Value {
+    const UNIT: Value = Value::Seq(Vec::new());
+
+    fn bool(b: bool) -> Self {
+        Value::Elem(u64::from(b))
+    }
+
+    fn elem(&self) -> Option<u64> {
+        match self {
+            Value::Elem(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn flatten(&self, out: &mut Vec<u64>) {
+        match self {
+            Value::Elem(v) => out.push(*v),
+            Value::Seq(items) => items.iter().for_each(|v| v.flatten(out)),
+            Value::Struct(fields) => fields.iter().for_each(|(_, v)| v.flatten(out)),
+        }
+    }
+
+    fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// The `#[pure]` functions, intrinsic wrappers and constants of every
+/// module registered so far, keyed by `module.name`.
+#[derive(Clone, Debug)]
+pub struct PureFns {
+    fns: BTreeMap<String, FnDef>,
+    intrinsics: BTreeMap<String, String>,
+    /// Functions that are neither pure nor intrinsic; never folded.
+    impure: BTreeSet<String>,
+    constants: BTreeMap<String, u64>,
+    /// `generate!` tables and array constants of one-element values:
+    /// one value per array element.
+    tables: BTreeMap<String, Vec<u64>>,
+    /// Constants of any other composite type: structs, tuples, digests
+    /// and arrays of those.
+    composites: BTreeMap<String, Value>,
+    /// Short module name -> full name (`math` -> `std.math`).
+    aliases: BTreeMap<String, String>,
+    fuel: u64,
+}
+
+impl Default for PureFns {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PureFns {
+    pub fn new() -> Self {
+        Self {
+            fns: BTreeMap::new(),
+            intrinsics: BTreeMap::new(),
+            impure: BTreeSet::new(),
+            constants: BTreeMap::new(),
+            tables: BTreeMap::new(),
+            composites: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            fuel: DEFAULT_FUEL,
+        }
+    }
+
+    /// Limit each evaluation to `fuel` steps.
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Register the functions and constants of `file` that are active
+    /// under `cfg_flags`. Constants are evaluated in declaration order,
+    /// so one may use a pure call over those before it; `generate!`
+    /// constants are filled element by element. An array whose elements
+    /// are single values becomes a table, like a `generate!` constant.
+    pub fn add_module(&mut self, file: &File, cfg_flags: &BTreeSet<String>) {
+        let module = file.name.node.as_str();
+        if let Some((_, short)) = module.rsplit_once('.') {
+            self.aliases.insert(short.to_string(), module.to_string());
+        }
+        let active = |cfg: &Option<Spanned<String>>| {
+            cfg.as_ref().is_none_or(|c| cfg_flags.contains(&c.node))
+        };
+
+        for item in &file.items {
+            let Item::Fn(func) = &item.node else {
+                continue;
+            };
+            if !active(&func.cfg) {
+                continue;
+            }
+            let key = format!("{}.{}", module, func.name.node);
+            if let Some(intrinsic) = func.intrinsic_attr() {
+                self.intrinsics.insert(key, intrinsic.name);
+            } else if func.is_pure && func.type_params.is_empty() && func.body.is_some() {
+                self.fns.insert(key, func.clone());
+            } else {
+                self.impure.insert(key);
+            }
+        }
+
+        for item in &file.items {
+            let Item::Const(cdef) = &item.node else {
+                continue;
+            };
+            if !active(&cdef.cfg) {
+                continue;
+            }
+            let key = format!("{}.{}", module, cdef.name.node);
+            if cdef.generate {
+                if let Some(values) = self.generate(module, cdef) {
+                    self.tables.insert(key, values);
+                }
+                continue;
+            }
+            let value = Eval::new(self).expr(module, &mut Env::top(NO_LOCALS), &cdef.value.node);
+            match value {
+                Some(Value::Elem(value)) => {
+                    self.constants.insert(key, value);
+                }
+                Some(Value::Seq(items)) if matches!(cdef.ty.node, Type::Array(..)) => {
+                    match items.iter().map(Value::elem).collect::<Option<Vec<_>>>() {
+                        Some(values) => {
+                            self.tables.insert(key, values);
+                        }
+                        None => {
+                            self.composites.insert(key, Value::Seq(items));
+                        }
+                    }
+                }
+                Some(value) => {
+                    self.composites.insert(key, value);
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Fill a `generate!(f, args)` constant: element `i` is `f(i, args)`,
+    /// each call with its own fuel. `None` unless the type is an array
+    /// of at most `MAX_TABLE_LEN` elements and every call yields one
+    /// element.
+    fn generate(&self, module: &str, cdef: &ConstDef) -> Option<Vec<u64>> {
+        let Type::Array(_, size) = &cdef.ty.node else {
+            return None;
+        };
+        let len = size.as_literal().filter(|&n| n <= MAX_TABLE_LEN)?;
+        let Expr::Call { path, args, .. } = &cdef.value.node else {
+            return None;
+        };
+        let name = path.node.as_dotted();
+        (0..len)
+            .map(|i| {
+                let mut call_args =
+                    vec![Spanned::new(Expr::Literal(Literal::Integer(i)), path.span)];
+                call_args.extend(args.iter().cloned());
+                match self
+                    .eval_call(module, &name, &call_args, NO_LOCALS)?
+                    .as_slice()
+                {
+                    [value] => Some(*value),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Take over everything `other` has registered.
+    pub fn merge(&mut self, other: &PureFns) {
+        self.fns
+            .extend(other.fns.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.intrinsics
+            .extend(other.intrinsics.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.impure.extend(other.impure.iter().cloned());
+        self.constants
+            .extend(other.constants.iter().map(|(k, v)| (k.clone(), *v)));
+        self.tables
+            .extend(other.tables.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.composites
+            .extend(other.composites.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.aliases
+            .extend(other.aliases.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    /// Value of constant `name` as written in `module`.
+    pub fn constant(&self, module: &str, name: &str) -> Option<u64> {
+        self.resolve(module, name, &self.constants).map(|(_, v)| *v)
+    }
+
+    /// Qualified name and values of `generate!` table `name` as written
+    /// in `module`.
+    pub fn table(&self, module: &str, name: &str) -> Option<(String, &[u64])> {
+        self.resolve(module, name, &self.tables)
+            .map(|(key, values)| (key, values.as_slice()))
+    }
+
+    /// Stack elements of composite constant `name` as written in `module`
+    /// (first element deepest), and the width of one element if it is an
+    /// array. `name` may continue into fields: `CONFIG.limit`.
+    pub fn composite(&self, module: &str, name: &str) -> Option<(Vec<u64>, Option<u32>)> {
+        let value = self.composite_value(module, name)?;
+        let mut words = Vec::new();
+        value.flatten(&mut words);
+        let elem_width = match value {
+            Value::Seq(items) if !items.is_empty() => {
+                Some(u32::try_from(words.len() / items.len()).ok()?)
+            }
+            _ => None,
+        };
+        Some((words, elem_width))
+    }
+
+    /// The value of a composite constant, or of a field inside one.
+    fn composite_value(&self, module: &str, name: &str) -> Option<&Value> {
+        let parts: Vec<&str> = name.split('.').collect();
+        (1..=parts.len()).rev().find_map(|split| {
+            let (_, mut value) =
+                self.resolve(module, &parts[..split].join("."), &self.composites)?;
+            for field in &parts[split..] {
+                value = value.field(field)?;
+            }
+            Some(value)
+        })
+    }
+
+    /// Every table registered so far, by qualified name.
+    pub fn tables(&self) -> &BTreeMap<String, Vec<u64>> {
+        &self.tables
+    }
+
+    /// Evaluate a constant initializer: a literal, another constant, or
+    /// arithmetic and pure calls over those.
+    pub fn eval_const(&self, module: &str, expr: &Expr) -> Option<u64> {
+        let mut eval = Eval::new(self);
+        eval.expr(module, &mut Env::top(NO_LOCALS), expr)?.elem()
+    }
+
+    /// Evaluate a call to the pure function `name` as written in `module`,
+    /// returning its result as stack elements (first element deepest).
+    /// `is_local` names the variables bound at the call site; an argument
+    /// that reads one is not constant.
+    pub fn eval_call(
+        &self,
+        module: &str,
+        name: &str,
+        args: &[Spanned<Expr>],
+        is_local: &dyn Fn(&str) -> bool,
+    ) -> Option<Vec<u64>> {
+        let (key, _) = self.resolve(module, name, &self.fns)?;
+        let mut eval = Eval::new(self);
+        let mut env = Env::top(is_local);
+        let args = args
+            .iter()
+            .map(|a| eval.expr(module, &mut env, &a.node))
+            .collect::<Option<Vec<_>>>()?;
+        let mut out = Vec::new();
+        eval.call_fn(&key, args)?.flatten(&mut out);
+        Some(out)
+    }
+
+    /// Look `name` up in `table` from inside `module`: unqualified names
+    /// belong to `module`, qualified ones may use a short module alias.
+    fn resolve<'t, T>(
+        &self,
+        module: &str,
+        name: &str,
+        table: &'t BTreeMap<String, T>,
+    ) -> Option<(String, &'t T)> {
+        let key = match name.rsplit_once('.') {
+            None => format!("{}.{}", module, name),
+            Some(_) if table.contains_key(name) => name.to_string(),
+            Some((prefix, base)) => format!("{}.{}", self.aliases.get(prefix)?, base),
+        };
+        table.get(&key).map(|v| (key, v))
+    }
+}
+
+/// Result of executing a statement or block.
+enum Flow {
+    Next,
+    /// The value of a block's tail expression.
+    Value(Value),
+    Return(Value),
+}
+
+/// No call-site variables: inside function bodies and constants.
+const NO_LOCALS: &dyn Fn(&str) -> bool = &|_| false;
+
+/// Variable scopes of the function being evaluated.
+struct Env<'a> {
+    scopes: Vec<BTreeMap<String, Value>>,
+    /// Call-site variables that shadow constants (top level only).
+    is_local: &'a dyn Fn(&str) -> bool,
+}
+
+impl<'a> Env<'a> {
+    fn top(is_local: &'a dyn Fn(&str) -> bool) -> Self {
+        Self {
+            scopes: vec![BTreeMap::new()],
+            is_local,
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&Value> {
+        self.scopes.iter().rev().find_map(|s| s.get(name))
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.scopes.iter_mut().rev().find_map(|s| s.get_mut(name))
+    }
+
+    fn bind(&mut self, name: &str, value: Value) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+    }
+}
+
+/// One place-path step of an assignment target.
+enum Step {
+    Field(String),
+    Index(usize),
+}
+
+struct Eval<'f> {
+    fns: &'f PureFns,
+    fuel: u64,
+    depth: u32,
+}
+
+impl<'f> Eval<'f> {
+    fn new(fns: &'f PureFns) -> Self {
+        Self {
+            fns,
+            fuel: fns.fuel,
+            depth: 0,
+        }
+    }
+
+    fn tick(&mut self) -> Option<()> {
+        self.fuel = self.fuel.checked_sub(1)?;
+        Some(())
+    }
+
+    fn call_fn(&mut self, key: &str, args: Vec<Value>) -> Option<Value> {
+        let func = self.fns.fns.get(key)?;
+        let body = func.body.as_ref()?;
+        if func.params.len() != args.len() || self.depth >= MAX_CALL_DEPTH {
+            return None;
+        }
+        let module = key.rsplit_once('.').map_or("", |(m, _)| m);
+        let mut env = Env::top(NO_LOCALS);
+        for (param, value) in func.params.iter().zip(args) {
+            env.bind(&param.name.node, value);
+        }
+        self.depth += 1;
+        let result = self.block_value(module, &mut env, &body.node);
+        self.depth -= 1;
+        match result? {
+            Flow::Value(value) | Flow::Return(value) => Some(value),
+            Flow::Next => None,
+        }
+    }
+
+    /// Run a block for its value: the tail expression, or unit.
+    fn block_value(&mut self, module: &str, env: &mut Env, block: &Block) -> Option<Flow> {
+        env.scopes.push(BTreeMap::new());
+        let result = self.block_inner(module, env, block);
+        env.scopes.pop();
+        result
+    }
+
+    fn block_inner(&mut self, module: &str, env: &mut Env, block: &Block) -> Option<Flow> {
+        for stmt in &block.stmts {
+            if let Flow::Return(value) = self.stmt(module, env, &stmt.node)? {
+                return Some(Flow::Return(value));
+            }
+        }
+        let value = match &block.tail_expr {
+            Some(tail) => self.expr(module, env, &tail.node)?,
+            None => Value::UNIT,
+        };
+        Some(Flow::Value(value))
+    }
+
+    /// Run a statement block: only an explicit `return` leaves it early.
+    fn block(&mut self, module: &str, env: &mut Env, block: &Block) -> Option<Flow> {
+        Some(match self.block_value(module, env, block)? {
+            Flow::Value(_) => Flow::Next,
+            flow => flow,
+        })
+    }
+
+    fn stmt(&mut self, module: &str, env: &mut Env, stmt: &Stmt) -> Option<Flow> {
+        self.tick()?;
+        match stmt {
+            Stmt::Let { pattern, init, .. } => {
+                let value = self.expr(module, env, &init.node)?;
+                match pattern {
+                    Pattern::Name(name) => env.bind(&name.node, value),
+                    Pattern::Tuple(names) => {
+                        let Value::Seq(items) = value else {
+                            return None;
+                        };
+                        if items.len() != names.len() {
+                            return None;
+                        }
+                        for (name, item) in names.iter().zip(items) {
+                            env.bind(&name.node, item);
+                        }
+                    }
+                }
+            }
+            Stmt::Assign { place, value } => {
+                let value = self.expr(module, env, &value.node)?;
+                let (root, path) = self.place_path(module, env, &place.node)?;
+                let mut target = env.get_mut(&root)?;
+                for step in &path {
+                    target = match (step, target) {
+                        (Step::Field(name), Value::Struct(fields)) => {
+                            &mut fields.iter_mut().find(|(n, _)| n == name)?.1
+                        }
+                        (Step::Index(i), Value::Seq(items)) => items.get_mut(*i)?,
+                        _ => return None,
+                    };
+                }
+                *target = value;
+            }
+            Stmt::TupleAssign { names, value } => {
+                let Value::Seq(items) = self.expr(module, env, &value.node)? else {
+                    return None;
+                };
+                if items.len() != names.len() {
+                    return None;
+                }
+                for (name, item) in names.iter().zip(items) {
+                    *env.get_mut(&name.node)? = item;
+                }
+            }
+            Stmt::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                if self.expr(module, env, &cond.node)?.elem()? != 0 {
+                    return self.block(module, env, &then_block.node);
+                } else if let Some(else_block) = else_block {
+                    return self.block(module, env, &else_block.node);
+                }
+            }
+            Stmt::For {
+                var,
+                start,
+                end,
+                body,
+                ..
+            } => {
+                let start = self.expr(module, env, &start.node)?.elem()?;
+                let end = self.expr(module, env, &end.node)?.elem()?;
+                for i in start..end {
+                    self.tick()?;
+                    env.scopes
+                        .push(BTreeMap::from([(var.node.clone(), Value::Elem(i))]));
+                    let flow = self.block(module, env, &body.node);
+                    env.scopes.pop();
+                    if let Flow::Return(value) = flow? {
+                        return Some(Flow::Return(value));
+                    }
+                }
+            }
+            Stmt::Expr(expr) => {
+                self.expr(module, env, &expr.node)?;
+            }
+            Stmt::Return(value) => {
+                let value = match value {
+                    Some(expr) => self.expr(module, env, &expr.node)?,
+                    None => Value::UNIT,
+                };
+                return Some(Flow::Return(value));
+            }
+            Stmt::Match { expr, arms } => {
+                let value = self.expr(module, env, &expr.node)?;
+                for arm in arms {
+                    if let Some(bindings) = match_arm(&arm.pattern.node, &value) {
+                        env.scopes.push(bindings);
+                        let flow = self.block(module, env, &arm.body.node);
+                        env.scopes.pop();
+                        return flow;
+                    }
+                }
+                return None;
+            }
+            Stmt::Reveal { .. } | Stmt::Seal { .. } | Stmt::Asm { .. } => return None,
+        }
+        Some(Flow::Next)
+    }
+
+    /// Split an assignment target into its root variable and the field
+    /// and index steps below it.
+    fn place_path(
+        &mut self,
+        module: &str,
+        env: &mut Env,
+        place: &Place,
+    ) -> Option<(String, Vec<Step>)> {
+        match place {
+            Place::Var(name) => {
+                let mut parts = name.split('.');
+                let root = parts.next()?.to_string();
+                Some((root, parts.map(|p| Step::Field(p.to_string())).collect()))
+            }
+            Place::FieldAccess(inner, field) => {
+                let (root, mut path) = self.place_path(module, env, &inner.node)?;
+                path.push(Step::Field(field.node.clone()));
+                Some((root, path))
+            }
+            Place::Index(inner, index) => {
+                let (root, mut path) = self.place_path(module, env, &inner.node)?;
+                let index = self.expr(module, env, &index.node)?.elem()?;
+                path.push(Step::Index(usize::try_from(index).ok()?));
+                Some((root, path))
+            }
+        }
+    }
+
+    fn expr(&mut self, module: &str, env: &mut Env, expr: &Expr) -> Option<Value> {
+        self.tick()?;
+        match expr {
+            Expr::Literal(Literal::Integer(n)) => Some(Value::Elem(*n)),
+            Expr::Literal(Literal::Bool(b)) => Some(Value::bool(*b)),
+            Expr::Var(name) => self.var(module, env, name),
+            Expr::BinOp { op, lhs, rhs } => {
+                let lhs = self.expr(module, env, &lhs.node)?;
+                match (op, lhs.elem()) {
+                    (BinOp::And, Some(0)) => return Some(Value::bool(false)),
+                    (BinOp::Or, Some(1)) => return Some(Value::bool(true)),
+                    _ => {}
+                }
+                let rhs = self.expr(module, env, &rhs.node)?;
+                binop(*op, lhs, rhs)
+            }
+            Expr::Call {
+                path,
+                generic_args,
+                args,
+            } => {
+                if !generic_args.is_empty() {
+                    return None;
+                }
+                let args = args
+                    .iter()
+                    .map(|a| self.expr(module, env, &a.node))
+                    .collect::<Option<Vec<_>>>()?;
+                let name = path.node.as_dotted();
+                let fns = self.fns;
+                if let Some((key, _)) = fns.resolve(module, &name, &fns.fns) {
+                    return self.call_fn(&key, args);
+                }
+                if let Some((_, intrinsic)) = fns.resolve(module, &name, &fns.intrinsics) {
+                    return builtin(intrinsic, &args);
+                }
+                if name.contains('.') || fns.impure.contains(&format!("{}.{}", module, name)) {
+                    return None;
+                }
+                builtin(&name, &args)
+            }
+            Expr::FieldAccess { expr, field } => self
+                .expr(module, env, &expr.node)?
+                .field(&field.node)
+                .cloned(),
+            Expr::Index { expr, index } => {
+                let Value::Seq(items) = self.expr(module, env, &expr.node)? else {
+                    return None;
+                };
+                let index = self.expr(module, env, &index.node)?.elem()?;
+                items.get(usize::try_from(index).ok()?).cloned()
+            }
+            Expr::StructInit { fields, .. } => fields
+                .iter()
+                .map(|(name, e)| Some((name.node.clone(), self.expr(module, env, &e.node)?)))
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Struct),
+            Expr::ArrayInit(items) | Expr::Tuple(items) => items
+                .iter()
+                .map(|e| self.expr(module, env, &e.node))
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Seq),
+            Expr::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                let block = if self.expr(module, env, &cond.node)?.elem()? != 0 {
+                    then_block
+                } else {
+                    else_block
+                };
+                // A `return` inside an `if` expression is left to runtime.
+                match self.block_value(module, env, &block.node)? {
+                    Flow::Value(value) => Some(value),
+                    Flow::Next | Flow::Return(_) => None,
+                }
+            }
+        }
+    }
+
+    /// A variable (`x`, `p.x`) or a constant (`N`, `math.N`).
+    fn var(&mut self, module: &str, env: &Env, name: &str) -> Option<Value> {
+        let mut parts = name.split('.');
+        let root = parts.next()?;
+        if let Some(mut value) = env.get(root) {
+            for field in parts {
+                value = value.field(field)?;
+            }
+            return Some(value.clone());
+        }
+        if (env.is_local)(root) {
+            return None;
+        }
+        let fns = self.fns;
+        if let Some(value) = fns.constant(module, name) {
+            return Some(Value::Elem(value));
+        }
+        if let Some((_, values)) = fns.table(module, name) {
+            return Some(Value::Seq(
+                values.iter().copied().map(Value::Elem).collect(),
+            ));
+        }
+        fns.composite_value(module, name).cloned()
+    }
+}
+
+/// Bindings of a match arm that matches `value`.
+fn match_arm(pattern: &MatchPattern, value: &Value) -> Option<BTreeMap<String, Value>> {
+    let literal = |lit: &Literal| match lit {
+        Literal::Integer(n) => *n,
+        Literal::Bool(b) => u64::from(*b),
+    };
+    match pattern {
+        MatchPattern::Wildcard => Some(BTreeMap::new()),
+        MatchPattern::Literal(lit) => (value.elem()? == literal(lit)).then(BTreeMap::new),
+        MatchPattern::Struct { fields, .. } => {
+            let mut bindings = BTreeMap::new();
+            for spf in fields {
+                let field = value.field(&spf.field_name.node)?;
+                match &spf.pattern.node {
+                    FieldPattern::Binding(name) => {
+                        bindings.insert(name.clone(), field.clone());
+                    }
+                    FieldPattern::Literal(lit) => {
+                        if field.elem()? != literal(lit) {
+                            return None;
+                        }
+                    }
+                    FieldPattern::Wildcard => {}
+                }
+            }
+            Some(bindings)
+        }
+        // Options never fold: `Some` and `None` are not constants.
+        MatchPattern::Some(_) | MatchPattern::None => None,
+    }
+}
+
+fn binop(op: BinOp, lhs: Value, rhs: Value) -> Option<Value> {
+    if op == BinOp::Eq {
+        return Some(Value::bool(lhs == rhs));
+    }
+    let (a, b) = (lhs.elem()?, rhs.elem()?);
+    let (fa, fb) = (Goldilocks::from_u64(a), Goldilocks::from_u64(b));
+    let value = match op {
+        BinOp::Add => fa.add(fb).to_u64(),
+        BinOp::Mul => fa.mul(fb).to_u64(),
+        BinOp::Lt => u64::from(a < b),
+        BinOp::BitAnd => a & b,
+        BinOp::BitXor => a ^ b,
+        BinOp::And => u64::from(a != 0 && b != 0),
+        BinOp::Or => u64::from(a != 0 || b != 0),
+        BinOp::DivMod => {
+            if b == 0 {
+                return None;
+            }
+            return Some(Value::Seq(vec![Value::Elem(a / b), Value::Elem(a % b)]));
+        }
+        BinOp::Eq | BinOp::XFieldMul => return None,
+    };
+    Some(Value::Elem(value))
+}
+
+/// Builtins that are functions of their arguments alone. Assertions
+/// that fail are not folded, so the failure still happens at runtime.
+fn builtin(name: &str, args: &[Value]) -> Option<Value> {
+    let elems = || args.iter().map(Value::elem).collect::<Option<Vec<_>>>();
+    let field = |v: u64| Goldilocks::from_u64(v);
+    let value = match (name, args) {
+        ("assert" | "assume", _) => return (elems()? == [1]).then_some(Value::UNIT),
+        ("assert_eq" | "assert_digest", [a, b]) => return (a == b).then_some(Value::UNIT),
+        ("ensure", [cond, _]) => return (cond.elem()? == 1).then_some(Value::UNIT),
+        ("xfield", _) => return Some(Value::Seq(args.to_vec())),
+        _ => match (name, elems()?.as_slice()) {
+            ("field_add", &[a, b]) => field(a).add(field(b)).to_u64(),
+            ("field_mul", &[a, b]) => field(a).mul(field(b)).to_u64(),
+            ("sub", &[a, b]) => field(a).sub(field(b)).to_u64(),
+            ("neg", &[a]) => field(a).neg().to_u64(),
+            ("inv" | "checked_inv", &[a]) => field(a).inv()?.to_u64(),
+            ("checked_div", &[a, b]) => field(a).mul(field(b).inv()?).to_u64(),
+            ("as_field", &[a]) => a,
+            ("as_u32", &[a]) => {
+                u32::try_from(a).ok()?;
+                a
+            }
+            ("split", &[a]) => {
+                return Some(Value::Seq(vec![
+                    Value::Elem(a >> 32),
+                    Value::Elem(a & u64::from(u32::MAX)),
+                ]));
+            }
+            ("log2", &[a]) => u64::from(a.checked_ilog2()?),
+            ("pow", &[base, exp]) => field(base).pow(exp).to_u64(),
+            ("popcount", &[a]) => u64::from(a.count_ones()),
+            _ => return None,
+        },
+    };
+    Some(Value::Elem(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pure_fns(source: &str) -> PureFns {
+        let file = crate::parse_source_silent(source, "test.tri").expect("parses");
+        let mut fns = PureFns::new();
+        fns.add_module(&file, &BTreeSet::new());
+        fns
+    }
+
+    fn call(fns: &PureFns, source: &str) -> Option<Vec<u64>> {
+        let expr =
+            crate::parse_source_silent(&format!("module m\nfn f() {{ {} }}", source), "call.tri")
+                .expect("parses");
+        let Item::Fn(func) = &expr.items[0].node else {
+            unreachable!()
+        };
+        let tail = func.body.as_ref()?.node.tail_expr.clone()?;
+        let Expr::Call { path, args, .. } = &tail.node else {
+            return None;
+        };
+        fns.eval_call("test", &path.node.as_dotted(), args, &|_| false)
+    }
+
+    #[test]
+    fn test_fold_loop_and_builtins() {
+        let fns = pure_fns(
+            "module test\n#[pure]\nfn pow2(n: U32) -> Field {\n    let mut acc: Field = 1\n    for i in 0..n bounded 64 {\n        acc = acc * 2\n    }\n    acc\n}\n#[pure]\nfn dm(a: U32, b: U32) -> (U32, U32) { a /% b }\n#[pure]\nfn minus_one() -> Field { sub(0, 1) }",
+        );
+        assert_eq!(call(&fns, "pow2(10)"), Some(vec![1024]));
+        assert_eq!(call(&fns, "dm(17, 5)"), Some(vec![3, 2]));
+        assert_eq!(
+            call(&fns, "minus_one()"),
+            Some(vec![crate::field::goldilocks::MODULUS - 1])
+        );
+    }
+
+    #[test]
+    fn test_fold_constants_and_failures() {
+        let fns = pure_fns(
+            "module test\nconst N: U32 = 4\nconst K: Field = twice(N)\n#[pure]\nfn twice(x: Field) -> Field { x + x }\n#[pure]\nfn checked(x: Field) -> Field {\n    assert(x == 1)\n    x\n}\nfn impure(x: Field) -> Field { x }",
+        );
+        assert_eq!(fns.constant("test", "K"), Some(8));
+        assert_eq!(call(&fns, "twice(K)"), Some(vec![16]));
+        assert_eq!(call(&fns, "checked(1)"), Some(vec![1]));
+        assert_eq!(call(&fns, "checked(2)"), None);
+        assert_eq!(call(&fns, "impure(1)"), None);
+    }
+
+    #[test]
+    fn test_fuel_limit() {
+        let source = "module test\n#[pure]\nfn spin() -> Field {\n    let mut x: Field = 0\n    for i in 0..1000 bounded 1000 {\n        x = x + 1\n    }\n    x\n}";
+        assert_eq!(call(&pure_fns(source), "spin()"), Some(vec![1000]));
+        assert_eq!(call(&pure_fns(source).with_fuel(100), "spin()"), None);
+    }
+
+    #[test]
+    fn test_generate_table() {
+        let fns = pure_fns(
+            "module test\nconst C: Field = 3\nconst T: [Field; 4] = generate!(affine, 2, C)\nconst HUGE: [Field; 65537] = generate!(affine, 1, 1)\nconst PAIRS: [Field; 2] = generate!(pair)\n#[pure]\nfn affine(i: Field, a: Field, b: Field) -> Field { a * i + b }\n#[pure]\nfn pair(i: Field) -> (Field, Field) { (i, i) }",
+        );
+        let (key, values) = fns.table("test", "T").expect("generated");
+        assert_eq!(key, "test.T");
+        assert_eq!(values, &[3, 5, 7, 9]);
+        assert!(fns.table("test", "HUGE").is_none());
+        assert!(fns.table("test", "PAIRS").is_none());
+        assert_eq!(fns.constant("test", "T"), None);
+    }
+}
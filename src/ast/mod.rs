@@ -1,5 +1,10 @@
+pub mod derive;
 pub mod display;
+pub mod eval;
 pub mod navigate;
+pub mod search;
+pub mod shift;
+pub mod sizes;
 
 use crate::span::Spanned;
 
@@ -9,6 +14,8 @@ pub struct File {
     pub kind: FileKind,
     pub name: Spanned<String>,
     pub uses: Vec<Spanned<ModulePath>>,
+    /// `mod transfers` lines: program parts kept in sibling files.
+    pub mods: Vec<Spanned<String>>,
     pub declarations: Vec<Declaration>,
     pub items: Vec<Spanned<Item>>,
 }
@@ -66,6 +73,9 @@ pub struct ConstDef {
     pub name: Spanned<String>,
     pub ty: Spanned<Type>,
     pub value: Spanned<Expr>,
+    /// Written `generate!(f, args)`: `value` holds the call `f(args)` and
+    /// element `i` of the array is `f(i, args)`, run at compile time.
+    pub generate: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -74,6 +84,9 @@ pub struct StructDef {
     pub cfg: Option<Spanned<String>>,
     pub name: Spanned<String>,
     pub fields: Vec<StructField>,
+    /// Built-in derives: `#[derive(Hash, FromWitness)]`, expanded into
+    /// functions after parsing (see `derive`).
+    pub derives: Vec<Spanned<String>>,
 }
 
 #[derive(Clone, Debug)]
@@ -102,20 +115,102 @@ pub struct FnDef {
     pub cfg: Option<Spanned<String>>,
     pub intrinsic: Option<Spanned<String>>,
     pub is_test: bool,
+    /// `#[test(max_cycles = N)]`: cycle budget when the test runs.
+    pub test_max_cycles: Option<u64>,
     /// Pure annotation: `#[pure]` — no I/O side effects allowed.
     pub is_pure: bool,
+    /// `#[entry]`: one of the operations the generated `main` dispatches
+    /// to by public input tag.
+    pub is_entry: bool,
+    /// `#[no_spill]`: the build fails if the function's stack would spill
+    /// to RAM.
+    pub no_spill: bool,
     /// Precondition annotations: `#[requires(predicate)]`.
     pub requires: Vec<Spanned<String>>,
     /// Postcondition annotations: `#[ensures(predicate)]`.
     pub ensures: Vec<Spanned<String>>,
+    /// Lints silenced in this function: `#[allow(constant_condition)]`.
+    pub allows: Vec<Spanned<String>>,
     pub name: Spanned<String>,
     /// Size-generic parameters, e.g. `<N>` in `fn sum<N>(arr: [Field; N])`.
     pub type_params: Vec<Spanned<String>>,
     pub params: Vec<Param>,
     pub return_ty: Option<Spanned<Type>>,
+    /// Bounds on the size parameters, e.g. `where N <= 32`.
+    pub where_clause: Vec<Spanned<SizeBound>>,
     pub body: Option<Spanned<Block>>,
 }
 
+impl FnDef {
+    /// The parsed `#[intrinsic]` attribute, if present and well-formed.
+    pub fn intrinsic_attr(&self) -> Option<Intrinsic> {
+        Intrinsic::parse(&self.intrinsic.as_ref()?.node).ok()
+    }
+}
+
+/// Arguments of `#[intrinsic(...)]`: `intrinsic(name)`, or the named form
+/// `intrinsic(tier = 2, name = "sponge_absorb_mem")`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Intrinsic {
+    /// The target instruction the function maps to.
+    pub name: String,
+    /// TIR tier (0 structure, 1 universal, 2 provable, 3 recursion) a
+    /// target must support to execute the instruction.
+    pub tier: Option<u8>,
+}
+
+impl Intrinsic {
+    /// Highest tier a declaration may require.
+    pub const MAX_TIER: u8 = 3;
+
+    /// Parse the attribute text as stored on `FnDef::intrinsic`.
+    pub fn parse(attr: &str) -> Result<Intrinsic, String> {
+        let args = attr
+            .strip_prefix("intrinsic(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap_or(attr)
+            .trim();
+        if !args.contains('=') {
+            return Ok(Intrinsic {
+                name: args.to_string(),
+                tier: None,
+            });
+        }
+        let mut name = None;
+        let mut tier = None;
+        for arg in args.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            let Some((key, value)) = arg.split_once('=') else {
+                return Err(format!("expected `key = value`, got `{}`", arg));
+            };
+            let value = value.trim();
+            match key.trim() {
+                "name" => {
+                    let unquoted = value
+                        .strip_prefix('"')
+                        .and_then(|v| v.strip_suffix('"'))
+                        .unwrap_or(value);
+                    name = Some(unquoted.to_string());
+                }
+                "tier" => match value.parse::<u8>() {
+                    Ok(t) if t <= Self::MAX_TIER => tier = Some(t),
+                    _ => {
+                        return Err(format!(
+                            "tier must be 0 to {}, got `{}`",
+                            Self::MAX_TIER,
+                            value
+                        ))
+                    }
+                },
+                other => return Err(format!("unknown intrinsic option `{}`", other)),
+            }
+        }
+        match name {
+            Some(name) if !name.is_empty() => Ok(Intrinsic { name, tier }),
+            _ => Err("missing `name = \"...\"`".to_string()),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Param {
     pub name: Spanned<String>,
@@ -179,6 +274,75 @@ impl std::fmt::Display for ArraySize {
     }
 }
 
+/// Comparison operator of a size bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SizeCmp {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl SizeCmp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SizeCmp::Eq => "==",
+            SizeCmp::Lt => "<",
+            SizeCmp::Le => "<=",
+            SizeCmp::Gt => ">",
+            SizeCmp::Ge => ">=",
+        }
+    }
+}
+
+/// A `where` clause constraint over size parameters: `N <= 32`, `M + N == 8`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SizeBound {
+    pub lhs: ArraySize,
+    pub op: SizeCmp,
+    pub rhs: ArraySize,
+}
+
+impl SizeBound {
+    /// Whether the bound holds for the given size parameter values.
+    pub fn holds(&self, subs: &std::collections::BTreeMap<String, u64>) -> bool {
+        let (l, r) = (self.lhs.eval(subs), self.rhs.eval(subs));
+        match self.op {
+            SizeCmp::Eq => l == r,
+            SizeCmp::Lt => l < r,
+            SizeCmp::Le => l <= r,
+            SizeCmp::Gt => l > r,
+            SizeCmp::Ge => l >= r,
+        }
+    }
+
+    /// Size parameter names referenced by the bound.
+    pub fn params(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        collect_size_params(&self.lhs, &mut out);
+        collect_size_params(&self.rhs, &mut out);
+        out
+    }
+}
+
+fn collect_size_params<'a>(size: &'a ArraySize, out: &mut Vec<&'a str>) {
+    match size {
+        ArraySize::Literal(_) => {}
+        ArraySize::Param(name) => out.push(name),
+        ArraySize::Add(a, b) | ArraySize::Mul(a, b) => {
+            collect_size_params(a, out);
+            collect_size_params(b, out);
+        }
+    }
+}
+
+impl std::fmt::Display for SizeBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.lhs, self.op.as_str(), self.rhs)
+    }
+}
+
 /// Syntactic types (as written in source).
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Type {
@@ -189,9 +353,41 @@ pub enum Type {
     Digest,
     Array(Box<Type>, ArraySize),
     Tuple(Vec<Type>),
+    /// Tuple with named components: `(q: Field, r: Field)`. Same layout
+    /// as the unnamed tuple; the names only add `value.q` access.
+    NamedTuple(Vec<(String, Type)>),
+    /// Optional value: `Option<T>`. Laid out as a Bool flag (deeper)
+    /// followed by a `T` payload, which is all zeros for `None`.
+    Option(Box<Type>),
+    /// `Bytes<N>`: N bytes, one per element, each a `U32` below 256.
+    /// Laid out like `[U32; N]`.
+    Bytes(ArraySize),
     Named(ModulePath),
 }
 
+impl Type {
+    /// This type with every array size evaluated under `subs`, as in an
+    /// instance of a generic function.
+    pub fn with_sizes(&self, subs: &std::collections::BTreeMap<String, u64>) -> Type {
+        match self {
+            Type::Array(inner, n) => Type::Array(
+                Box::new(inner.with_sizes(subs)),
+                ArraySize::Literal(n.eval(subs)),
+            ),
+            Type::Tuple(elems) => Type::Tuple(elems.iter().map(|t| t.with_sizes(subs)).collect()),
+            Type::NamedTuple(components) => Type::NamedTuple(
+                components
+                    .iter()
+                    .map(|(name, t)| (name.clone(), t.with_sizes(subs)))
+                    .collect(),
+            ),
+            Type::Option(inner) => Type::Option(Box::new(inner.with_sizes(subs))),
+            Type::Bytes(n) => Type::Bytes(ArraySize::Literal(n.eval(subs))),
+            _ => self.clone(),
+        }
+    }
+}
+
 /// A block of statements with an optional trailing expression.
 #[derive(Clone, Debug)]
 pub struct Block {
@@ -199,6 +395,54 @@ pub struct Block {
     pub tail_expr: Option<Box<Spanned<Expr>>>,
 }
 
+impl Block {
+    /// This block with its trailing `if`/`else` statement as the tail
+    /// `if` expression, when it has no tail of its own and every branch
+    /// ends in a value. Where the context expects a value, such an `if`
+    /// is the block's value: `fn f(x: Field) -> Option<Field> { if x == 0
+    /// { None } else { Some(x) } }`.
+    pub fn with_tail_if(&self) -> Option<Block> {
+        let (last, stmts) = self.stmts.split_last()?;
+        if self.tail_expr.is_some() {
+            return None;
+        }
+        let tail = Self::if_value(last)?;
+        Some(Block {
+            stmts: stmts.to_vec(),
+            tail_expr: Some(Box::new(tail)),
+        })
+    }
+
+    /// An `if` statement as an expression; `else if` chains nest.
+    fn if_value(stmt: &Spanned<Stmt>) -> Option<Spanned<Expr>> {
+        let Stmt::If {
+            cond,
+            then_block,
+            else_block: Some(else_block),
+        } = &stmt.node
+        else {
+            return None;
+        };
+        then_block.node.tail_expr.as_ref()?;
+        let else_node = match (&else_block.node.stmts[..], &else_block.node.tail_expr) {
+            (_, Some(_)) => else_block.node.clone(),
+            ([inner], None) => Block {
+                stmts: Vec::new(),
+                tail_expr: Some(Box::new(Self::if_value(inner)?)),
+            },
+            _ => return None,
+        };
+        Some(Spanned::new(
+            Expr::If {
+                cond: Box::new(cond.clone()),
+                then_block: Box::new(then_block.clone()),
+                else_block: Box::new(Spanned::new(else_node, else_block.span)),
+            },
+            stmt.span,
+        ))
+    }
+}
+
 /// A binding pattern for `let` statements.
 #[derive(Clone, Debug)]
 pub enum Pattern {
@@ -215,6 +459,10 @@ pub enum MatchPattern {
     Literal(Literal),
     /// Wildcard: `_`.
     Wildcard,
+    /// Present option: `Some(x)` binds the payload, `Some(_)` ignores it.
+    Some(Spanned<String>),
+    /// Absent option: `None`.
+    None,
     /// Struct destructuring: `Point { x, y }` or `Point { x: a, y: 0 }`.
     /// Each field maps to a `StructPatternField`.
     Struct {
@@ -338,6 +586,12 @@ pub enum Expr {
     },
     ArrayInit(Vec<Spanned<Expr>>),
     Tuple(Vec<Spanned<Expr>>),
+    /// `if cond { a } else { b }` used as a value. The else branch is required.
+    If {
+        cond: Box<Spanned<Expr>>,
+        then_block: Box<Spanned<Block>>,
+        else_block: Box<Spanned<Block>>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -354,6 +608,8 @@ pub enum BinOp {
     Lt,        // <
     BitAnd,    // &
     BitXor,    // ^
+    And,       // &&
+    Or,        // ||
     DivMod,    // /%
     XFieldMul, // *.
 }
@@ -367,6 +623,8 @@ impl BinOp {
             BinOp::Lt => "<",
             BinOp::BitAnd => "&",
             BinOp::BitXor => "^",
+            BinOp::And => "&&",
+            BinOp::Or => "||",
             BinOp::DivMod => "/%",
             BinOp::XFieldMul => "*.",
         }
@@ -376,12 +634,79 @@ impl BinOp {
     /// Single source of truth for both parser and formatter.
     pub fn binding_power(&self) -> (u8, u8) {
         match self {
-            BinOp::Eq => (2, 3),
-            BinOp::Lt => (4, 5),
-            BinOp::Add => (6, 7),
-            BinOp::Mul | BinOp::XFieldMul => (8, 9),
-            BinOp::BitAnd | BinOp::BitXor => (10, 11),
-            BinOp::DivMod => (12, 13),
+            BinOp::Or => (2, 3),
+            BinOp::And => (4, 5),
+            BinOp::Eq => (6, 7),
+            BinOp::Lt => (8, 9),
+            BinOp::Add => (10, 11),
+            BinOp::Mul | BinOp::XFieldMul => (12, 13),
+            BinOp::BitAnd | BinOp::BitXor => (14, 15),
+            BinOp::DivMod => (16, 17),
         }
     }
 }
+
+impl Expr {
+    /// Whether evaluating this expression can neither fail nor have an
+    /// effect. Calls, `if` expressions and `/%` (which fails on a zero
+    /// divisor) are not pure; a pure right operand of `&&` or `||` may be
+    /// evaluated even when the left operand decides the result.
+    pub fn is_pure(&self) -> bool {
+        match self {
+            Expr::Literal(_) | Expr::Var(_) => true,
+            Expr::BinOp { op, lhs, rhs } => {
+                *op != BinOp::DivMod && lhs.node.is_pure() && rhs.node.is_pure()
+            }
+            Expr::Call { .. } | Expr::If { .. } => false,
+            Expr::FieldAccess { expr, .. } => expr.node.is_pure(),
+            Expr::Index { expr, index } => expr.node.is_pure() && index.node.is_pure(),
+            Expr::StructInit { fields, .. } => fields.iter().all(|(_, e)| e.node.is_pure()),
+            Expr::ArrayInit(elems) | Expr::Tuple(elems) => elems.iter().all(|e| e.node.is_pure()),
+        }
+    }
+
+    /// `lhs && rhs` as `if lhs { rhs } else { false }`, and `lhs || rhs` as
+    /// `if lhs { true } else { rhs }`: the short-circuit form of a logical
+    /// operator whose right operand is not pure.
+    pub fn short_circuit(op: BinOp, lhs: &Spanned<Expr>, rhs: &Spanned<Expr>) -> Expr {
+        let block = |expr: Expr| {
+            Box::new(Spanned::new(
+                Block {
+                    stmts: Vec::new(),
+                    tail_expr: Some(Box::new(Spanned::new(expr, rhs.span))),
+                },
+                rhs.span,
+            ))
+        };
+        let (then_block, else_block) = match op {
+            BinOp::Or => (
+                block(Expr::Literal(Literal::Bool(true))),
+                block(rhs.node.clone()),
+            ),
+            _ => (
+                block(rhs.node.clone()),
+                block(Expr::Literal(Literal::Bool(false))),
+            ),
+        };
+        Expr::If {
+            cond: Box::new(lhs.clone()),
+            then_block,
+            else_block,
+        }
+    }
+
+    /// The payload of a `Some(value)` constructor.
+    pub fn some_payload(&self) -> Option<&Spanned<Expr>> {
+        match self {
+            Expr::Call { path, args, .. } if path.node.0 == ["Some"] && args.len() == 1 => {
+                Some(&args[0])
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this is the `None` constructor.
+    pub fn is_none(&self) -> bool {
+        matches!(self, Expr::Var(name) if name == "None")
+    }
+}
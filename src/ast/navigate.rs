@@ -1,9 +1,11 @@
-//! AST navigation: find functions by name or content hash.
+//! AST navigation: find functions by name or content hash, and the
+//! calls and other expressions in a function body.
 
 use std::collections::BTreeMap;
 
-use super::{File, FnDef, Item};
+use super::{Block, Expr, File, FnDef, Item, Stmt};
 use crate::hash::ContentHash;
+use crate::span::Spanned;
 
 /// Find a function by name in a parsed file.
 pub fn find_function<'a>(file: &'a File, name: &str) -> Option<&'a FnDef> {
@@ -58,6 +60,112 @@ pub fn looks_like_hash(s: &str) -> bool {
     !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
 }
 
+/// Every call expression in `block`, in source order; a call comes
+/// before the calls in its arguments.
+pub fn calls_in_block(block: &Block) -> Vec<&Spanned<Expr>> {
+    exprs_in_block(block)
+        .into_iter()
+        .filter(|e| matches!(e.node, Expr::Call { .. }))
+        .collect()
+}
+
+/// Every expression in `block`, in source order; an expression comes
+/// before its subexpressions.
+pub fn exprs_in_block(block: &Block) -> Vec<&Spanned<Expr>> {
+    let mut exprs = Vec::new();
+    collect_exprs_block(block, &mut exprs);
+    exprs
+}
+
+fn collect_exprs_block<'a>(block: &'a Block, exprs: &mut Vec<&'a Spanned<Expr>>) {
+    for stmt in &block.stmts {
+        collect_exprs_stmt(&stmt.node, exprs);
+    }
+    if let Some(tail) = &block.tail_expr {
+        collect_exprs_expr(tail, exprs);
+    }
+}
+
+fn collect_exprs_stmt<'a>(stmt: &'a Stmt, exprs: &mut Vec<&'a Spanned<Expr>>) {
+    match stmt {
+        Stmt::Let { init: e, .. }
+        | Stmt::Assign { value: e, .. }
+        | Stmt::TupleAssign { value: e, .. }
+        | Stmt::Expr(e)
+        | Stmt::Return(Some(e)) => collect_exprs_expr(e, exprs),
+        Stmt::If {
+            cond,
+            then_block,
+            else_block,
+        } => {
+            collect_exprs_expr(cond, exprs);
+            collect_exprs_block(&then_block.node, exprs);
+            if let Some(eb) = else_block {
+                collect_exprs_block(&eb.node, exprs);
+            }
+        }
+        Stmt::For {
+            start, end, body, ..
+        } => {
+            collect_exprs_expr(start, exprs);
+            collect_exprs_expr(end, exprs);
+            collect_exprs_block(&body.node, exprs);
+        }
+        Stmt::Reveal { fields, .. } | Stmt::Seal { fields, .. } => {
+            for (_, val) in fields {
+                collect_exprs_expr(val, exprs);
+            }
+        }
+        Stmt::Match { expr, arms } => {
+            collect_exprs_expr(expr, exprs);
+            for arm in arms {
+                collect_exprs_block(&arm.body.node, exprs);
+            }
+        }
+        Stmt::Return(None) | Stmt::Asm { .. } => {}
+    }
+}
+
+fn collect_exprs_expr<'a>(expr: &'a Spanned<Expr>, exprs: &mut Vec<&'a Spanned<Expr>>) {
+    exprs.push(expr);
+    match &expr.node {
+        Expr::Call { args, .. } => {
+            for arg in args {
+                collect_exprs_expr(arg, exprs);
+            }
+        }
+        Expr::BinOp { lhs, rhs, .. } => {
+            collect_exprs_expr(lhs, exprs);
+            collect_exprs_expr(rhs, exprs);
+        }
+        Expr::Tuple(elems) | Expr::ArrayInit(elems) => {
+            for e in elems {
+                collect_exprs_expr(e, exprs);
+            }
+        }
+        Expr::FieldAccess { expr: inner, .. } => collect_exprs_expr(inner, exprs),
+        Expr::Index { expr: inner, index } => {
+            collect_exprs_expr(inner, exprs);
+            collect_exprs_expr(index, exprs);
+        }
+        Expr::StructInit { fields, .. } => {
+            for (_, val) in fields {
+                collect_exprs_expr(val, exprs);
+            }
+        }
+        Expr::If {
+            cond,
+            then_block,
+            else_block,
+        } => {
+            collect_exprs_expr(cond, exprs);
+            collect_exprs_block(&then_block.node, exprs);
+            collect_exprs_block(&else_block.node, exprs);
+        }
+        Expr::Literal(_) | Expr::Var(_) => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,306 @@
+//! Structural search: match expression patterns against function bodies
+//! (`trident grep`).
+//!
+//! A pattern is a Trident expression in which `$name` stands for any
+//! expression and `...` for any number of call arguments or array and
+//! tuple elements. A metavariable used twice must match equal
+//! expressions: `hash($a, $a, ...)` finds hashes of a value with itself,
+//! whatever the value is called. `$_` matches anything without binding.
+//! A call path in a pattern matches calls whose full path ends with it,
+//! so `hash(...)` also finds `std.crypto.poseidon2.hash(...)`.
+
+use std::collections::BTreeMap;
+
+use super::navigate::exprs_in_block;
+use super::{Block, Expr, File, Item, ModulePath, Stmt};
+use crate::span::{Span, Spanned};
+
+/// Prefix the metavariables of a pattern are renamed to before parsing.
+const META: &str = "__grep_";
+/// What `...` is renamed to.
+const REST: &str = "__grep_rest__";
+
+/// A parsed search pattern.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    expr: Expr,
+}
+
+/// One match of a pattern.
+#[derive(Clone, Debug)]
+pub struct PatternMatch {
+    /// Function whose body contains the match.
+    pub function: String,
+    pub span: Span,
+    /// Spans of the expressions each named metavariable matched.
+    pub bindings: BTreeMap<String, Span>,
+}
+
+impl Pattern {
+    /// Parse a pattern. Blocks (`if` expressions) are not supported.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let rewritten = rewrite_metavariables(text)?;
+        let source = format!("program __grep\nfn __grep() {{\n{}\n}}\n", rewritten);
+        let file = crate::parse_source_silent(&source, "<pattern>").map_err(|diags| match diags
+            .first()
+        {
+            Some(d) => format!("invalid pattern: {}", d.message),
+            None => "invalid pattern".to_string(),
+        })?;
+        let body = file.items.iter().find_map(|item| match &item.node {
+            Item::Fn(f) => f.body.as_ref(),
+            _ => None,
+        });
+        let expr = match body.map(|b| &b.node) {
+            Some(Block {
+                stmts,
+                tail_expr: Some(tail),
+            }) if stmts.is_empty() => tail.node.clone(),
+            Some(Block {
+                stmts,
+                tail_expr: None,
+            }) if stmts.len() == 1 => match &stmts[0].node {
+                Stmt::Expr(e) => e.node.clone(),
+                _ => return Err("a pattern must be a single expression".to_string()),
+            },
+            _ => return Err("a pattern must be a single expression".to_string()),
+        };
+        if matches!(expr, Expr::If { .. }) {
+            return Err("`if` expressions are not supported in patterns".to_string());
+        }
+        Ok(Pattern { expr })
+    }
+
+    /// Every match in the bodies of `file`'s functions, in source order.
+    /// Matches may nest: a match's subexpression can match too.
+    pub fn find_in_file(&self, file: &File) -> Vec<PatternMatch> {
+        let mut found = Vec::new();
+        for item in &file.items {
+            let Item::Fn(func) = &item.node else {
+                continue;
+            };
+            let Some(body) = &func.body else {
+                continue;
+            };
+            for expr in exprs_in_block(&body.node) {
+                let mut bindings = BTreeMap::new();
+                if match_expr(&self.expr, expr, &mut bindings) {
+                    found.push(PatternMatch {
+                        function: func.name.node.clone(),
+                        span: expr.span,
+                        bindings: bindings
+                            .into_iter()
+                            .map(|(name, e)| (name, e.span))
+                            .collect(),
+                    });
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Rename `$name` to `__grep_name` and `...` to a rest marker, so the
+/// pattern parses as an ordinary expression.
+fn rewrite_metavariables(text: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                if name.is_empty() {
+                    return Err("expected a name after `$`".to_string());
+                }
+                let mut ahead = chars.clone();
+                if ahead.next() == Some('.') && ahead.next() != Some('.') {
+                    return Err(format!("cannot access a field of `${}` in a pattern", name));
+                }
+                out.push_str(META);
+                out.push_str(&name);
+            }
+            _ => out.push(c),
+        }
+    }
+    Ok(out.replace("...", REST))
+}
+
+/// The metavariable a pattern variable names, if any: `Some("")` for `$_`.
+fn metavariable(name: &str) -> Option<&str> {
+    name.strip_prefix(META)
+        .filter(|_| name != REST)
+        .map(|n| if n == "_" { "" } else { n })
+}
+
+fn is_rest(expr: &Expr) -> bool {
+    matches!(expr, Expr::Var(name) if name == REST)
+}
+
+fn match_expr<'a>(
+    pattern: &Expr,
+    expr: &'a Spanned<Expr>,
+    bindings: &mut BTreeMap<String, &'a Spanned<Expr>>,
+) -> bool {
+    match (pattern, &expr.node) {
+        (Expr::Var(name), _) if metavariable(name).is_some() => {
+            let meta = metavariable(name).unwrap_or_default();
+            if meta.is_empty() {
+                return true;
+            }
+            match bindings.get(meta) {
+                Some(bound) => match_expr(&bound.node, expr, &mut BTreeMap::new()),
+                None => {
+                    bindings.insert(meta.to_string(), expr);
+                    true
+                }
+            }
+        }
+        (Expr::Literal(a), Expr::Literal(b)) => a == b,
+        (Expr::Var(a), Expr::Var(b)) => a == b,
+        (
+            Expr::BinOp { op, lhs, rhs },
+            Expr::BinOp {
+                op: op2,
+                lhs: lhs2,
+                rhs: rhs2,
+            },
+        ) => {
+            op == op2
+                && match_expr(&lhs.node, lhs2, bindings)
+                && match_expr(&rhs.node, rhs2, bindings)
+        }
+        (
+            Expr::Call { path, args, .. },
+            Expr::Call {
+                path: path2,
+                args: args2,
+                ..
+            },
+        ) => match_path(&path.node, &path2.node) && match_list(args, args2, bindings),
+        (
+            Expr::FieldAccess { expr: inner, field },
+            Expr::FieldAccess {
+                expr: inner2,
+                field: field2,
+            },
+        ) => field.node == field2.node && match_expr(&inner.node, inner2, bindings),
+        (
+            Expr::Index { expr: inner, index },
+            Expr::Index {
+                expr: inner2,
+                index: index2,
+            },
+        ) => match_expr(&inner.node, inner2, bindings) && match_expr(&index.node, index2, bindings),
+        (
+            Expr::StructInit { path, fields },
+            Expr::StructInit {
+                path: path2,
+                fields: fields2,
+            },
+        ) => {
+            match_path(&path.node, &path2.node)
+                && fields.len() == fields2.len()
+                && fields.iter().all(|(name, value)| {
+                    fields2
+                        .iter()
+                        .find(|(name2, _)| name2.node == name.node)
+                        .is_some_and(|(_, value2)| match_expr(&value.node, value2, bindings))
+                })
+        }
+        (Expr::ArrayInit(elems), Expr::ArrayInit(elems2))
+        | (Expr::Tuple(elems), Expr::Tuple(elems2)) => match_list(elems, elems2, bindings),
+        _ => false,
+    }
+}
+
+/// Match a list of patterns, where `...` matches any run of elements.
+fn match_list<'a>(
+    patterns: &[Spanned<Expr>],
+    exprs: &'a [Spanned<Expr>],
+    bindings: &mut BTreeMap<String, &'a Spanned<Expr>>,
+) -> bool {
+    let Some((first, rest)) = patterns.split_first() else {
+        return exprs.is_empty();
+    };
+    if is_rest(&first.node) {
+        return (0..=exprs.len()).any(|skip| {
+            let mut attempt = bindings.clone();
+            let ok = match_list(rest, &exprs[skip..], &mut attempt);
+            if ok {
+                *bindings = attempt;
+            }
+            ok
+        });
+    }
+    match exprs.split_first() {
+        Some((expr, exprs)) => {
+            match_expr(&first.node, expr, bindings) && match_list(rest, exprs, bindings)
+        }
+        None => false,
+    }
+}
+
+/// A pattern path matches a path that ends with the same segments.
+fn match_path(pattern: &ModulePath, path: &ModulePath) -> bool {
+    if let [single] = pattern.0.as_slice() {
+        if metavariable(single).is_some() {
+            return true;
+        }
+    }
+    path.0.ends_with(&pattern.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_file(source: &str) -> File {
+        crate::parse_source_silent(source, "test.tri").unwrap()
+    }
+
+    fn matched(pattern: &str, source: &str) -> Vec<String> {
+        let file = parse_file(source);
+        Pattern::parse(pattern)
+            .unwrap()
+            .find_in_file(&file)
+            .iter()
+            .map(|m| source[m.span.start as usize..m.span.end as usize].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_repeated_metavariable_needs_equal_expressions() {
+        let source = "program test\nfn main() {\n    let a: Field = pub_read()\n    let b: Field = pub_read()\n    let c: Field = hash(a, a, b)\n    let d: Field = hash(a, b, a)\n    let e: Field = hash(a + 1, a + 1)\n    pub_write(c + d + e)\n}\n";
+        assert_eq!(
+            matched("hash($x, $x, ...)", source),
+            ["hash(a, a, b)", "hash(a + 1, a + 1)"]
+        );
+        assert_eq!(matched("hash($_, ..., $x)", source).len(), 3);
+        assert_eq!(matched("hash($x, $x)", source), ["hash(a + 1, a + 1)"]);
+    }
+
+    #[test]
+    fn test_paths_match_by_suffix_and_matches_nest() {
+        let source = "program test\nuse std.io.io\nfn main() {\n    io.write(io.read() + 1)\n    pub_write(pub_read() + 1)\n}\n";
+        assert_eq!(matched("write(...)", source), ["io.write(io.read() + 1)"]);
+        assert_eq!(
+            matched("$a + 1", source),
+            ["io.read() + 1", "pub_read() + 1"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(Pattern::parse("hash(").is_err());
+        assert!(Pattern::parse("$").is_err());
+        assert!(Pattern::parse("$s.field").is_err());
+        assert!(Pattern::parse("let x = 1").is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! AST span shifting: move every span of an item by a byte delta.
+//!
+//! Used by incremental reparsing, where items after an edit keep their
+//! parsed form but move by the length change of the edit.
+
+use super::*;
+use crate::span::Span;
+
+/// Shift every span inside `item` (including its own) by `delta` bytes.
+pub fn shift_item(item: &mut Spanned<Item>, delta: i64) {
+    if delta == 0 {
+        return;
+    }
+    shift_span(&mut item.span, delta);
+    match &mut item.node {
+        Item::Const(c) => {
+            shift_opt(&mut c.cfg, delta);
+            shift_span(&mut c.name.span, delta);
+            shift_span(&mut c.ty.span, delta);
+            shift_expr(&mut c.value, delta);
+        }
+        Item::Struct(s) => {
+            shift_opt(&mut s.cfg, delta);
+            shift_span(&mut s.name.span, delta);
+            for field in &mut s.fields {
+                shift_span(&mut field.name.span, delta);
+                shift_span(&mut field.ty.span, delta);
+            }
+        }
+        Item::Event(e) => {
+            shift_opt(&mut e.cfg, delta);
+            shift_span(&mut e.name.span, delta);
+            for field in &mut e.fields {
+                shift_span(&mut field.name.span, delta);
+                shift_span(&mut field.ty.span, delta);
+            }
+        }
+        Item::Fn(f) => {
+            shift_opt(&mut f.cfg, delta);
+            shift_opt(&mut f.intrinsic, delta);
+            shift_all(&mut f.requires, delta);
+            shift_all(&mut f.ensures, delta);
+            shift_span(&mut f.name.span, delta);
+            shift_all(&mut f.type_params, delta);
+            for param in &mut f.params {
+                shift_span(&mut param.name.span, delta);
+                shift_span(&mut param.ty.span, delta);
+            }
+            shift_opt(&mut f.return_ty, delta);
+            shift_all(&mut f.where_clause, delta);
+            if let Some(body) = &mut f.body {
+                shift_block(body, delta);
+            }
+        }
+    }
+}
+
+fn shift_span(span: &mut Span, delta: i64) {
+    span.start = (span.start as i64 + delta) as u32;
+    span.end = (span.end as i64 + delta) as u32;
+}
+
+fn shift_opt<T>(node: &mut Option<Spanned<T>>, delta: i64) {
+    if let Some(n) = node {
+        shift_span(&mut n.span, delta);
+    }
+}
+
+fn shift_all<T>(nodes: &mut [Spanned<T>], delta: i64) {
+    for n in nodes {
+        shift_span(&mut n.span, delta);
+    }
+}
+
+fn shift_block(block: &mut Spanned<Block>, delta: i64) {
+    shift_span(&mut block.span, delta);
+    for stmt in &mut block.node.stmts {
+        shift_stmt(stmt, delta);
+    }
+    if let Some(tail) = &mut block.node.tail_expr {
+        shift_expr(tail, delta);
+    }
+}
+
+fn shift_fields(fields: &mut [(Spanned<String>, Spanned<Expr>)], delta: i64) {
+    for (name, value) in fields {
+        shift_span(&mut name.span, delta);
+        shift_expr(value, delta);
+    }
+}
+
+fn shift_stmt(stmt: &mut Spanned<Stmt>, delta: i64) {
+    shift_span(&mut stmt.span, delta);
+    match &mut stmt.node {
+        Stmt::Let {
+            pattern, ty, init, ..
+        } => {
+            match pattern {
+                Pattern::Name(name) => shift_span(&mut name.span, delta),
+                Pattern::Tuple(names) => shift_all(names, delta),
+            }
+            shift_opt(ty, delta);
+            shift_expr(init, delta);
+        }
+        Stmt::Assign { place, value } => {
+            shift_place(place, delta);
+            shift_expr(value, delta);
+        }
+        Stmt::TupleAssign { names, value } => {
+            shift_all(names, delta);
+            shift_expr(value, delta);
+        }
+        Stmt::If {
+            cond,
+            then_block,
+            else_block,
+        } => {
+            shift_expr(cond, delta);
+            shift_block(then_block, delta);
+            if let Some(else_block) = else_block {
+                shift_block(else_block, delta);
+            }
+        }
+        Stmt::For {
+            var,
+            start,
+            end,
+            body,
+            ..
+        } => {
+            shift_span(&mut var.span, delta);
+            shift_expr(start, delta);
+            shift_expr(end, delta);
+            shift_block(body, delta);
+        }
+        Stmt::Expr(expr) => shift_expr(expr, delta),
+        Stmt::Return(value) => {
+            if let Some(value) = value {
+                shift_expr(value, delta);
+            }
+        }
+        Stmt::Reveal { event_name, fields } | Stmt::Seal { event_name, fields } => {
+            shift_span(&mut event_name.span, delta);
+            shift_fields(fields, delta);
+        }
+        Stmt::Asm { .. } => {}
+        Stmt::Match { expr, arms } => {
+            shift_expr(expr, delta);
+            for arm in arms {
+                shift_span(&mut arm.pattern.span, delta);
+                match &mut arm.pattern.node {
+                    MatchPattern::Struct { name, fields } => {
+                        shift_span(&mut name.span, delta);
+                        for field in fields {
+                            shift_span(&mut field.field_name.span, delta);
+                            shift_span(&mut field.pattern.span, delta);
+                        }
+                    }
+                    MatchPattern::Some(binding) => shift_span(&mut binding.span, delta),
+                    _ => {}
+                }
+                shift_block(&mut arm.body, delta);
+            }
+        }
+    }
+}
+
+fn shift_place(place: &mut Spanned<Place>, delta: i64) {
+    shift_span(&mut place.span, delta);
+    match &mut place.node {
+        Place::Var(_) => {}
+        Place::FieldAccess(inner, field) => {
+            shift_place(inner, delta);
+            shift_span(&mut field.span, delta);
+        }
+        Place::Index(inner, index) => {
+            shift_place(inner, delta);
+            shift_expr(index, delta);
+        }
+    }
+}
+
+fn shift_expr(expr: &mut Spanned<Expr>, delta: i64) {
+    shift_span(&mut expr.span, delta);
+    match &mut expr.node {
+        Expr::Literal(_) | Expr::Var(_) => {}
+        Expr::BinOp { lhs, rhs, .. } => {
+            shift_expr(lhs, delta);
+            shift_expr(rhs, delta);
+        }
+        Expr::Call {
+            path,
+            generic_args,
+            args,
+        } => {
+            shift_span(&mut path.span, delta);
+            shift_all(generic_args, delta);
+            for arg in args {
+                shift_expr(arg, delta);
+            }
+        }
+        Expr::FieldAccess { expr, field } => {
+            shift_expr(expr, delta);
+            shift_span(&mut field.span, delta);
+        }
+        Expr::Index { expr, index } => {
+            shift_expr(expr, delta);
+            shift_expr(index, delta);
+        }
+        Expr::StructInit { path, fields } => {
+            shift_span(&mut path.span, delta);
+            shift_fields(fields, delta);
+        }
+        Expr::ArrayInit(elems) | Expr::Tuple(elems) => {
+            for elem in elems {
+                shift_expr(elem, delta);
+            }
+        }
+        Expr::If {
+            cond,
+            then_block,
+            else_block,
+        } => {
+            shift_expr(cond, delta);
+            shift_block(then_block, delta);
+            shift_block(else_block, delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_file(source: &str) -> File {
+        crate::parse_source_silent(source, "test.tri").unwrap()
+    }
+
+    fn first_stmt(file: &File) -> &Spanned<Stmt> {
+        let Item::Fn(func) = &file.items[0].node else {
+            panic!("expected fn");
+        };
+        &func.body.as_ref().unwrap().node.stmts[0]
+    }
+
+    #[test]
+    fn test_shifted_item_matches_reparse_at_new_offset() {
+        let body = "fn main() {\n    if true {\n        let x: Field = pub_read() + 1\n    }\n}\n";
+        let mut file = parse_file(&format!("program test\n{}", body));
+        let moved = parse_file(&format!("program test\n\n\n\n{}", body));
+
+        shift_item(&mut file.items[0], 3);
+        assert_eq!(file.items[0].span, moved.items[0].span);
+
+        let (
+            Stmt::If { then_block, .. },
+            Stmt::If {
+                then_block: want, ..
+            },
+        ) = (&first_stmt(&file).node, &first_stmt(&moved).node)
+        else {
+            panic!("expected if");
+        };
+        assert_eq!(then_block.span, want.span);
+        let (
+            Stmt::Let { init, .. },
+            Stmt::Let {
+                init: want_init, ..
+            },
+        ) = (&then_block.node.stmts[0].node, &want.node.stmts[0].node)
+        else {
+            panic!("expected let");
+        };
+        assert_eq!(init.span, want_init.span);
+    }
+}
@@ -0,0 +1,231 @@
+//! Array sizes that name a constant: `[Field; N]` with `const N: U32 = 4`.
+//!
+//! After parsing, every size that names a constant of the same module is
+//! replaced by the constant's value, so later passes only ever see
+//! literal sizes. The size parameters of a generic function shadow
+//! constants of the same name. Constants under `#[cfg]` are left alone:
+//! which one applies is not known until a profile is chosen.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::eval::PureFns;
+use super::*;
+
+/// Replace every array size in `file` that names one of its constants.
+pub fn fold(file: &mut File) {
+    let consts: BTreeSet<String> = file
+        .items
+        .iter()
+        .filter_map(|item| match &item.node {
+            Item::Const(c) if c.cfg.is_none() && !c.generate => Some(c.name.node.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut named = BTreeSet::new();
+    visit_file(file, &mut |size, generics| {
+        collect(size, generics, &consts, &mut named)
+    });
+    if named.is_empty() {
+        return;
+    }
+
+    let mut fns = PureFns::new();
+    fns.add_module(file, &BTreeSet::new());
+    let module = file.name.node.clone();
+    let values: BTreeMap<String, u64> = named
+        .into_iter()
+        .filter_map(|name| Some((name.clone(), fns.constant(&module, &name)?)))
+        .collect();
+    visit_file(file, &mut |size, generics| {
+        substitute(size, generics, &values)
+    });
+}
+
+/// Callback for each array size, with the size parameters in scope.
+type Visit<'a> = dyn FnMut(&mut ArraySize, &[Spanned<String>]) + 'a;
+
+fn collect(
+    size: &ArraySize,
+    generics: &[Spanned<String>],
+    consts: &BTreeSet<String>,
+    named: &mut BTreeSet<String>,
+) {
+    match size {
+        ArraySize::Literal(_) => {}
+        ArraySize::Param(name) => {
+            if consts.contains(name) && !generics.iter().any(|g| &g.node == name) {
+                named.insert(name.clone());
+            }
+        }
+        ArraySize::Add(a, b) | ArraySize::Mul(a, b) => {
+            collect(a, generics, consts, named);
+            collect(b, generics, consts, named);
+        }
+    }
+}
+
+fn substitute(size: &mut ArraySize, generics: &[Spanned<String>], values: &BTreeMap<String, u64>) {
+    match size {
+        ArraySize::Literal(_) => {}
+        ArraySize::Param(name) => {
+            if let Some(&n) = values.get(name.as_str()) {
+                if !generics.iter().any(|g| &g.node == name) {
+                    *size = ArraySize::Literal(n);
+                }
+            }
+        }
+        ArraySize::Add(a, b) | ArraySize::Mul(a, b) => {
+            substitute(a, generics, values);
+            substitute(b, generics, values);
+        }
+    }
+}
+
+fn visit_file(file: &mut File, visit: &mut Visit) {
+    for item in &mut file.items {
+        match &mut item.node {
+            Item::Const(c) => {
+                visit_type(&mut c.ty.node, &[], visit);
+                visit_expr(&mut c.value.node, &[], visit);
+            }
+            Item::Struct(s) => {
+                for field in &mut s.fields {
+                    visit_type(&mut field.ty.node, &[], visit);
+                }
+            }
+            Item::Event(e) => {
+                for field in &mut e.fields {
+                    visit_type(&mut field.ty.node, &[], visit);
+                }
+            }
+            Item::Fn(f) => {
+                let generics = &f.type_params;
+                for param in &mut f.params {
+                    visit_type(&mut param.ty.node, generics, visit);
+                }
+                if let Some(ty) = &mut f.return_ty {
+                    visit_type(&mut ty.node, generics, visit);
+                }
+                if let Some(body) = &mut f.body {
+                    visit_block(&mut body.node, generics, visit);
+                }
+            }
+        }
+    }
+}
+
+fn visit_type(ty: &mut Type, generics: &[Spanned<String>], visit: &mut Visit) {
+    match ty {
+        Type::Array(inner, size) => {
+            visit(size, generics);
+            visit_type(inner, generics, visit);
+        }
+        Type::Tuple(elems) => elems
+            .iter_mut()
+            .for_each(|t| visit_type(t, generics, visit)),
+        Type::NamedTuple(components) => components
+            .iter_mut()
+            .for_each(|(_, t)| visit_type(t, generics, visit)),
+        Type::Option(inner) => visit_type(inner, generics, visit),
+        Type::Bytes(size) => visit(size, generics),
+        Type::Field | Type::XField | Type::Bool | Type::U32 | Type::Digest | Type::Named(_) => {}
+    }
+}
+
+fn visit_block(block: &mut Block, generics: &[Spanned<String>], visit: &mut Visit) {
+    for stmt in &mut block.stmts {
+        visit_stmt(&mut stmt.node, generics, visit);
+    }
+    if let Some(tail) = &mut block.tail_expr {
+        visit_expr(&mut tail.node, generics, visit);
+    }
+}
+
+fn visit_stmt(stmt: &mut Stmt, generics: &[Spanned<String>], visit: &mut Visit) {
+    match stmt {
+        Stmt::Let { ty, init, .. } => {
+            if let Some(ty) = ty {
+                visit_type(&mut ty.node, generics, visit);
+            }
+            visit_expr(&mut init.node, generics, visit);
+        }
+        Stmt::Assign { value, .. } | Stmt::TupleAssign { value, .. } => {
+            visit_expr(&mut value.node, generics, visit)
+        }
+        Stmt::If {
+            cond,
+            then_block,
+            else_block,
+        } => {
+            visit_expr(&mut cond.node, generics, visit);
+            visit_block(&mut then_block.node, generics, visit);
+            if let Some(else_block) = else_block {
+                visit_block(&mut else_block.node, generics, visit);
+            }
+        }
+        Stmt::For {
+            start, end, body, ..
+        } => {
+            visit_expr(&mut start.node, generics, visit);
+            visit_expr(&mut end.node, generics, visit);
+            visit_block(&mut body.node, generics, visit);
+        }
+        Stmt::Expr(expr) | Stmt::Return(Some(expr)) => visit_expr(&mut expr.node, generics, visit),
+        Stmt::Reveal { fields, .. } | Stmt::Seal { fields, .. } => {
+            for (_, value) in fields {
+                visit_expr(&mut value.node, generics, visit);
+            }
+        }
+        Stmt::Match { expr, arms } => {
+            visit_expr(&mut expr.node, generics, visit);
+            for arm in arms {
+                visit_block(&mut arm.body.node, generics, visit);
+            }
+        }
+        Stmt::Return(None) | Stmt::Asm { .. } => {}
+    }
+}
+
+fn visit_expr(expr: &mut Expr, generics: &[Spanned<String>], visit: &mut Visit) {
+    match expr {
+        Expr::Call {
+            generic_args, args, ..
+        } => {
+            for arg in generic_args {
+                visit(&mut arg.node, generics);
+            }
+            for arg in args {
+                visit_expr(&mut arg.node, generics, visit);
+            }
+        }
+        Expr::BinOp { lhs, rhs, .. } => {
+            visit_expr(&mut lhs.node, generics, visit);
+            visit_expr(&mut rhs.node, generics, visit);
+        }
+        Expr::FieldAccess { expr, .. } => visit_expr(&mut expr.node, generics, visit),
+        Expr::Index { expr, index } => {
+            visit_expr(&mut expr.node, generics, visit);
+            visit_expr(&mut index.node, generics, visit);
+        }
+        Expr::StructInit { fields, .. } => {
+            for (_, value) in fields {
+                visit_expr(&mut value.node, generics, visit);
+            }
+        }
+        Expr::ArrayInit(items) | Expr::Tuple(items) => {
+            for item in items {
+                visit_expr(&mut item.node, generics, visit);
+            }
+        }
+        Expr::If {
+            cond,
+            then_block,
+            else_block,
+        } => {
+            visit_expr(&mut cond.node, generics, visit);
+            visit_block(&mut then_block.node, generics, visit);
+            visit_block(&mut else_block.node, generics, visit);
+        }
+        Expr::Literal(_) | Expr::Var(_) => {}
+    }
+}
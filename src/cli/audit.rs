@@ -1,7 +1,9 @@
 use std::path::PathBuf;
 use std::process;
+use std::sync::Arc;
 
 use clap::Args;
+use trident::diagnostic::StderrSink;
 
 use super::trisha::{
     generate_test_harness, run_trisha, run_trisha_with_inputs, trisha_available, Harness,
@@ -110,7 +112,8 @@ fn cmd_audit_exec() {
         process::exit(1);
     }
 
-    let options = trident::CompileOptions::default();
+    let options =
+        trident::CompileOptions::default().with_sink(Arc::new(StderrSink::default().errors_only()));
     let mut results: Vec<ModuleAudit> = Vec::new();
 
     for baseline_path in &baselines {
@@ -138,9 +141,7 @@ fn cmd_audit_exec() {
         };
 
         // ── Classic dimension ──
-        let _guard = trident::diagnostic::suppress_warnings();
         let module_tasm = trident::compile_module(&source_path, &options);
-        drop(_guard);
 
         if let Ok(tasm) = module_tasm {
             audit.classic.compile = AuditStatus::Ok;
@@ -380,8 +381,8 @@ fn cmd_audit_symbolic(args: AuditArgs) {
         synthesize,
         ..
     } = args;
-    let ri = resolve_input(&input);
-    let entry = ri.entry;
+    let ctx = resolve_input(&input);
+    let entry = ctx.entry;
 
     eprintln!("Auditing {}...", input.display());
 
@@ -414,14 +415,7 @@ fn cmd_audit_symbolic(args: AuditArgs) {
         }
         let mut sys = trident::sym::ConstraintSystem::new();
         for (_, fn_sys) in &per_fn {
-            sys.constraints.extend(fn_sys.constraints.clone());
-            sys.num_variables += fn_sys.num_variables;
-            for (k, v) in &fn_sys.variables {
-                sys.variables.insert(k.clone(), *v);
-            }
-            sys.pub_inputs.extend(fn_sys.pub_inputs.clone());
-            sys.pub_outputs.extend(fn_sys.pub_outputs.clone());
-            sys.divine_inputs.extend(fn_sys.divine_inputs.clone());
+            sys.extend(fn_sys.clone());
         }
         if verbose {
             eprintln!("\nCombined: {}", sys.summary());
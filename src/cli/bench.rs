@@ -1,7 +1,9 @@
 use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::Arc;
 
 use clap::Args;
+use trident::diagnostic::StderrSink;
 
 use super::trisha::{generate_test_harness, run_trisha, trisha_available, Harness};
 
@@ -70,7 +72,8 @@ pub fn cmd_bench(args: BenchArgs) {
         process::exit(1);
     }
 
-    let options = trident::CompileOptions::default();
+    let options =
+        trident::CompileOptions::default().with_sink(Arc::new(StderrSink::default().errors_only()));
     let has_trisha = args.full && trisha_available();
 
     // Load neural model once for all modules (unless --skip-neural)
@@ -109,12 +112,10 @@ pub fn cmd_bench(args: BenchArgs) {
 
         // Compile module (instruction count) + time it
         let compile_start = std::time::Instant::now();
-        let _guard = trident::diagnostic::suppress_warnings();
         let compiled_tasm = match trident::compile_module(&source_path, &options) {
             Ok(t) => t,
             Err(_) => continue,
         };
-        drop(_guard);
         let compile_ms = compile_start.elapsed().as_secs_f64() * 1000.0;
 
         // Parse per-function instruction counts
@@ -194,9 +195,7 @@ pub fn cmd_bench(args: BenchArgs) {
         // Run trisha passes for --full
         if has_trisha {
             // Classic: compile module, generate test harness
-            let _guard2 = trident::diagnostic::suppress_warnings();
             let module_tasm = trident::compile_module(&source_path, &options).ok();
-            drop(_guard2);
 
             if let Some(tasm) = module_tasm {
                 let classic_harness = generate_test_harness(&tasm);
@@ -686,12 +685,10 @@ fn compile_neural_tasm_inline(
     use trident::neural::data::pairs::split_tir_by_function;
 
     // Build TIR
-    let _guard = trident::diagnostic::suppress_warnings();
     let ir = match trident::build_tir_project(source_path, options) {
         Ok(ir) => ir,
         Err(_) => return None,
     };
-    drop(_guard);
 
     let functions = split_tir_by_function(&ir);
     if functions.is_empty() {
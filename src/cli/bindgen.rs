@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+use std::process;
+
+use clap::{Args, ValueEnum};
+
+use super::build::write_artifact;
+use super::resolve_context;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum BindgenLang {
+    Rust,
+    Ts,
+}
+
+impl BindgenLang {
+    fn extension(self) -> &'static str {
+        match self {
+            BindgenLang::Rust => "rs",
+            BindgenLang::Ts => "ts",
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct BindgenArgs {
+    /// Input .tri file or directory with trident.toml
+    pub input: PathBuf,
+    /// Host language of the bindings
+    #[arg(long, value_enum, default_value = "rust")]
+    pub lang: BindgenLang,
+    /// Output file (default: target/<vm>/<profile>/<name>.bindings.<rs|ts>)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Target VM (default: triton)
+    #[arg(long, default_value = "triton")]
+    pub target: String,
+    /// Engine (geeky for terrain/VM)
+    #[arg(long, conflicts_with_all = ["terrain", "network", "union_flag"])]
+    pub engine: Option<String>,
+    /// Terrain (gamy for engine/VM)
+    #[arg(long, conflicts_with_all = ["engine", "network", "union_flag"])]
+    pub terrain: Option<String>,
+    /// Network (geeky for union/OS)
+    #[arg(long, conflicts_with_all = ["engine", "terrain", "union_flag"])]
+    pub network: Option<String>,
+    /// Union (gamy for network/OS)
+    #[arg(long = "union", conflicts_with_all = ["engine", "terrain", "network"])]
+    pub union_flag: Option<String>,
+    /// Compilation profile; the program digest depends on it (default: release)
+    #[arg(long, default_value = "release")]
+    pub profile: String,
+}
+
+pub fn cmd_bindgen(args: BindgenArgs) {
+    let BindgenArgs {
+        input,
+        lang,
+        output,
+        target,
+        engine,
+        terrain,
+        network,
+        union_flag,
+        profile,
+    } = args;
+    let bf = super::resolve_battlefield_compile(&target, &engine, &terrain, &network, &union_flag);
+    let ctx = resolve_context(&input, &bf.target, &profile, &[]);
+
+    let compiler = trident::Compiler::new(&ctx.entry).with_options(ctx.options.clone());
+    let (Ok(tasm), Ok(abi), Ok(ram)) = (compiler.compile(), compiler.abi(), compiler.ram_layout())
+    else {
+        eprintln!("error: binding generation failed (compilation errors)");
+        process::exit(1);
+    };
+    let claim = trident::deploy::claim::ProofClaim::for_program(tasm);
+
+    let code = match lang {
+        BindgenLang::Rust => trident::runtime::bindgen::rust_bindings(&abi, &claim, ram),
+        BindgenLang::Ts => trident::runtime::bindgen::ts_bindings(&abi, &claim, ram),
+    };
+    let code = match code {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let out_path = output.unwrap_or_else(|| {
+        ctx.artifact_dir(&ctx.options.target_config.name, &profile)
+            .join(format!(
+                "{}.bindings.{}",
+                ctx.artifact_name(),
+                lang.extension()
+            ))
+    });
+    write_artifact(&out_path, &code);
+    eprintln!("Bindings written to {}", out_path.display());
+}
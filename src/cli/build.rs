@@ -1,17 +1,24 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
-use clap::Args;
+use clap::{Args, ValueEnum};
+use trident::project::BuildContext;
 
-use super::{find_program_source, load_dep_dirs, resolve_input, resolve_options};
+use super::{
+    configure_or_exit, find_program_source, pin_library_modules, resolve_input,
+    warn_missing_cost_model,
+};
 
 #[derive(Args)]
 pub struct BuildArgs {
     /// Input .tri file or directory with trident.toml
     pub input: PathBuf,
-    /// Output .tasm file (default: <input>.tasm)
+    /// Output .tasm file (default: target/<vm>/<profile>/<name>.tasm)
     #[arg(short, long)]
     pub output: Option<PathBuf>,
+    /// Artifacts to write under target/<vm>/<profile>/ (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "tasm")]
+    pub emit: Vec<Emit>,
     /// Print cost analysis report
     #[arg(long)]
     pub costs: bool,
@@ -30,6 +37,15 @@ pub struct BuildArgs {
     /// Compare costs with a previous cost JSON file
     #[arg(long, value_name = "PATH")]
     pub compare: Option<PathBuf>,
+    /// Print the RAM memory map (spills, temporaries, sec ram)
+    #[arg(long)]
+    pub ram_map: bool,
+    /// List generic instances with their sizes and merged duplicates
+    #[arg(long)]
+    pub mono_report: bool,
+    /// Fail on stack invariant violations in the generated code
+    #[arg(long)]
+    pub validate_codegen: bool,
     /// Target VM (default: triton)
     #[arg(long, default_value = "triton")]
     pub target: String,
@@ -48,64 +64,151 @@ pub struct BuildArgs {
     /// Compilation profile for cfg flags (debug or release)
     #[arg(long, default_value = "debug")]
     pub profile: String,
+    /// Build several profiles at once (comma-separated, e.g. debug,release)
+    /// and summarize how each differs from the first
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["profile", "output"])]
+    pub profiles: Vec<String>,
+    /// Features from trident.toml [features] to enable (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub features: Vec<String>,
     /// Run neural optimizer analysis (shows per-block decisions)
     #[arg(long)]
     pub neural: bool,
     /// Train the neural optimizer for N epochs (implies --neural)
     #[arg(long, value_name = "EPOCHS")]
     pub train: Option<u64>,
+    /// Accept changed std/os/vm library modules and re-pin them in trident.lock
+    #[arg(long)]
+    pub update_std: bool,
+}
+
+/// Artifacts selectable with `--emit`.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    /// Target assembly: <name>.tasm
+    Tasm,
+    /// Cost analysis: <name>.costs.json
+    Json,
+    /// Entry-module interface: <name>.abi.json
+    Abi,
+    /// TASM label to source line map: <name>.map.json
+    Map,
+    /// Symbol table of all modules: <name>.sym.json
+    Sym,
+    /// Runtime checks by error id: <name>.asserts.json
+    Asserts,
 }
 
 pub fn cmd_build(args: BuildArgs) {
     let BuildArgs {
         input,
         output,
+        emit,
         costs,
         hotspots,
         hints,
         annotate,
         save_costs,
         compare,
+        ram_map,
+        mono_report,
+        validate_codegen,
         target,
         engine,
         terrain,
         network,
         union_flag,
         profile,
+        profiles,
+        features,
         neural,
         train,
+        update_std,
     } = args;
     let bf = super::resolve_battlefield_compile(&target, &engine, &terrain, &network, &union_flag);
     let target = bf.target;
-    let ri = resolve_input(&input);
+    let ctx = resolve_input(&input);
 
-    let mut options = resolve_options(&target, &profile, ri.project.as_ref());
-    if let Some(ref proj) = ri.project {
-        options.dep_dirs = load_dep_dirs(proj);
+    if !profiles.is_empty() {
+        build_profiles(ctx, &target, &profiles, &features, validate_codegen);
+        return;
     }
 
-    let tasm = match trident::compile_project_with_options(&ri.entry, &options) {
+    let ctx = configure_or_exit(ctx, &target, &profile, &features);
+    let mut options = ctx.options.clone();
+    options.validate_codegen = validate_codegen;
+
+    // Before compiling, so the report is shown even when the budget fails.
+    if mono_report {
+        match trident::mono_report_project(&ctx.entry, &options) {
+            Ok(report) => eprintln!("{}", report.format_report()),
+            Err(_) => process::exit(1),
+        }
+    }
+
+    let compiler = trident::Compiler::new(&ctx.entry).with_options(options.clone());
+    let tasm = match compiler.compile() {
         Ok(t) => t,
         Err(_) => process::exit(1),
     };
 
-    let default_output = if let Some(ref proj) = ri.project {
-        proj.root_dir.join(format!("{}.tasm", proj.name))
-    } else {
-        input.with_extension("tasm")
-    };
+    if let Some(ref proj) = ctx.project {
+        match trident::deploy::collect_provenance(&ctx.entry, &options, Some(proj)) {
+            Ok(provenance) => pin_library_modules(proj, &provenance, update_std),
+            Err(_) => process::exit(1),
+        }
+    }
 
-    let out_path = output.unwrap_or(default_output);
-    if let Err(e) = std::fs::write(&out_path, &tasm) {
-        eprintln!("error: cannot write '{}': {}", out_path.display(), e);
-        process::exit(1);
+    let name = ctx.artifact_name();
+    let out_dir = ctx.artifact_dir(&options.target_config.name, &profile);
+
+    if emit.contains(&Emit::Tasm) {
+        let ext = &options.target_config.output_extension;
+        let out_path = output.unwrap_or_else(|| out_dir.join(format!("{}{}", name, ext)));
+        write_artifact(&out_path, tasm);
+        eprintln!("Compiled -> {}", out_path.display());
+    }
+    if emit.contains(&Emit::Json) {
+        match compiler.costs() {
+            Ok(cost) => emit_artifact(&out_dir, &name, "costs", &cost.to_json()),
+            Err(_) => process::exit(1),
+        }
+    }
+    if emit.contains(&Emit::Abi) {
+        match compiler.abi() {
+            Ok(abi) => emit_artifact(&out_dir, &name, "abi", &abi.to_json()),
+            Err(_) => process::exit(1),
+        }
+    }
+    if emit.contains(&Emit::Map) {
+        match compiler.source_map() {
+            Ok(map) => emit_artifact(&out_dir, &name, "map", &map.to_json()),
+            Err(_) => process::exit(1),
+        }
+    }
+    if emit.contains(&Emit::Sym) {
+        match compiler.symbol_table() {
+            Ok(table) => emit_artifact(&out_dir, &name, "sym", &table.to_json()),
+            Err(_) => process::exit(1),
+        }
+    }
+    if emit.contains(&Emit::Asserts) {
+        match compiler.assertion_table() {
+            Ok(table) => emit_artifact(&out_dir, &name, "asserts", &table.to_json()),
+            Err(_) => process::exit(1),
+        }
+    }
+
+    if ram_map {
+        if let Ok(layout) = compiler.ram_layout() {
+            eprintln!("\n{}", layout.format_map());
+        }
     }
-    eprintln!("Compiled -> {}", out_path.display());
 
     // Neural optimizer analysis
     let use_neural = neural || train.is_some();
     if use_neural {
-        run_neural_analysis(&ri.entry, &options, train);
+        run_neural_analysis(&ctx.entry, &options, train);
     }
 
     if annotate {
@@ -127,11 +230,11 @@ pub fn cmd_build(args: BuildArgs) {
         Some(p) => p,
         None => return,
     };
-    let cost_options = resolve_options(&target, &profile, None);
-    let program_cost = match trident::analyze_costs_project(&source_path, &cost_options) {
+    let program_cost = match trident::analyze_costs_project(&source_path, &options) {
         Ok(c) => c,
         Err(_) => return,
     };
+    warn_missing_cost_model(&options.target_config.name);
 
     if costs || hotspots {
         eprintln!("\n{}", program_cost.format_report());
@@ -160,6 +263,69 @@ pub fn cmd_build(args: BuildArgs) {
     }
 }
 
+/// `trident build --profiles`: write each profile's TASM to its usual
+/// artifact path and print how each differs from the first.
+fn build_profiles(
+    mut ctx: BuildContext,
+    target: &str,
+    profiles: &[String],
+    features: &[String],
+    validate_codegen: bool,
+) {
+    let mut options = Vec::new();
+    for profile in profiles {
+        let mut profile_options = ctx
+            .options_for(target, profile, features)
+            .unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            });
+        profile_options.validate_codegen = validate_codegen;
+        options.push(profile_options);
+    }
+    for warning in ctx.warnings.drain(..) {
+        eprintln!("warning: {}", warning);
+    }
+    let builds = match trident::compile_project_profiles(&ctx.entry, &options) {
+        Ok(builds) => builds,
+        Err(_) => process::exit(1),
+    };
+
+    let name = ctx.artifact_name();
+    for (build, options) in builds.iter().zip(&options) {
+        let out_dir = ctx.artifact_dir(&options.target_config.name, &build.profile);
+        let ext = &options.target_config.output_extension;
+        let out_path = out_dir.join(format!("{}{}", name, ext));
+        write_artifact(&out_path, &build.tasm);
+        eprintln!("Compiled [{}] -> {}", build.profile, out_path.display());
+    }
+    if let Some((base, rest)) = builds.split_first() {
+        for build in rest {
+            print!("{}", build.diff_from(base).format_summary());
+        }
+    }
+}
+
+pub fn write_artifact(path: &Path, contents: &str) {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            eprintln!("error: cannot create '{}': {}", dir.display(), e);
+            process::exit(1);
+        }
+    }
+    if let Err(e) = std::fs::write(path, contents) {
+        eprintln!("error: cannot write '{}': {}", path.display(), e);
+        process::exit(1);
+    }
+}
+
+/// Write `<dir>/<name>.<kind>.json` and report it.
+fn emit_artifact(dir: &Path, name: &str, kind: &str, json: &str) {
+    let path = dir.join(format!("{}.{}.json", name, kind));
+    write_artifact(&path, json);
+    eprintln!("Emitted {} -> {}", kind, path.display());
+}
+
 fn run_neural_analysis(
     entry: &std::path::Path,
     options: &trident::CompileOptions,
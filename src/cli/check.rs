@@ -1,15 +1,16 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use clap::Args;
 
-use super::{find_program_source, resolve_input, resolve_options};
+use super::{find_program_source, resolve_context, warn_missing_cost_model};
 
 #[derive(Args)]
 pub struct CheckArgs {
     /// Input .tri file or directory with trident.toml
     pub input: PathBuf,
-    /// Print cost analysis report
+    /// Print cost analysis report, per-module roll-ups, and functions
+    /// over the target's proving limit
     #[arg(long)]
     pub costs: bool,
     /// Target VM (default: triton)
@@ -30,6 +31,9 @@ pub struct CheckArgs {
     /// Compilation profile for cfg flags (debug or release)
     #[arg(long, default_value = "debug")]
     pub profile: String,
+    /// Check against every registered target and print a pass/fail matrix
+    #[arg(long, conflicts_with_all = ["engine", "terrain", "network", "union_flag"])]
+    pub all_targets: bool,
 }
 
 pub fn cmd_check(args: CheckArgs) {
@@ -42,22 +46,98 @@ pub fn cmd_check(args: CheckArgs) {
         network,
         union_flag,
         profile,
+        all_targets,
     } = args;
+    if all_targets {
+        check_all_targets(&input, costs, &profile);
+        return;
+    }
     let bf = super::resolve_battlefield_compile(&target, &engine, &terrain, &network, &union_flag);
     let target = bf.target;
-    let ri = resolve_input(&input);
+    let ctx = resolve_context(&input, &target, &profile, &[]);
 
-    match trident::check_project(&ri.entry) {
+    let checker = trident::Compiler::new(&ctx.entry).with_options(ctx.options.clone());
+    match checker.check() {
         Ok(()) => eprintln!("OK: {}", input.display()),
         Err(_) => process::exit(1),
     }
 
     if costs {
         if let Some(source_path) = find_program_source(&input) {
-            let options = resolve_options(&target, &profile, ri.project.as_ref());
-            if let Ok(program_cost) = trident::analyze_costs_project(&source_path, &options) {
+            let compiler = trident::Compiler::new(&source_path).with_options(ctx.options);
+            if let Ok(program_cost) = compiler.costs() {
+                warn_missing_cost_model(&compiler.options().target_config.name);
                 eprintln!("\n{}", program_cost.format_report());
             }
+            if let Ok(modules) = compiler.module_costs() {
+                eprintln!("{}", modules.format_report());
+            }
         }
     }
 }
+
+/// `check --all-targets`: type-check (and optionally cost) the project
+/// once per registered target and print one row per target.
+fn check_all_targets(input: &Path, costs: bool, profile: &str) {
+    let ctx = resolve_context(input, "triton", profile, &[]);
+    let results = trident::check_project_all_targets(&ctx.entry, &ctx.options, costs);
+
+    let width = results
+        .iter()
+        .map(|r| r.target.len())
+        .max()
+        .unwrap_or(0)
+        .max("target".len());
+    eprintln!("Target matrix: {}", input.display());
+    if costs {
+        eprintln!(
+            "{:<width$}  {:<6}  {:>13}  dominant",
+            "target", "check", "padded height"
+        );
+    } else {
+        eprintln!("{:<width$}  check", "target");
+    }
+    let mut skipped = Vec::new();
+    for result in &results {
+        if result.skipped.is_some() {
+            skipped.push(result.target.as_str());
+            continue;
+        }
+        match (&result.cost, result.errors.first()) {
+            (_, Some(first)) => eprintln!(
+                "{:<width$}  {:<6}  {} ({} error{})",
+                result.target,
+                "FAIL",
+                first.message,
+                result.errors.len(),
+                if result.errors.len() == 1 { "" } else { "s" }
+            ),
+            (Some(cost), None) => eprintln!(
+                "{:<width$}  {:<6}  {:>13}  {}{}",
+                result.target,
+                "ok",
+                cost.padded_height,
+                cost.total.dominant_table(&cost.short_names()),
+                if trident::cost::has_cost_model(&result.target) {
+                    ""
+                } else {
+                    " (triton model)"
+                }
+            ),
+            (None, None) => eprintln!("{:<width$}  ok", result.target),
+        }
+    }
+
+    let checked = results.len() - skipped.len();
+    let failed = results.iter().filter(|r| r.failed()).count();
+    eprintln!("\n{} of {} targets passed", checked - failed, checked);
+    if !skipped.is_empty() {
+        eprintln!(
+            "skipped (target config does not load): {}",
+            skipped.join(", ")
+        );
+    }
+    if failed > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::Args;
+
+#[derive(Args)]
+pub struct CleanArgs {
+    /// Project directory or .tri file (default: current directory)
+    #[arg(default_value = ".")]
+    pub input: PathBuf,
+    /// Remove only target/*/<profile>/ and keep the compilation cache
+    #[arg(long)]
+    pub profile: Option<String>,
+}
+
+pub fn cmd_clean(args: CleanArgs) {
+    let CleanArgs { input, profile } = args;
+    let target = project_root(&input).join("target");
+
+    let removed = match profile {
+        Some(ref profile) => remove_profile(&target, profile),
+        None => remove_dir(&target).into_iter().collect(),
+    };
+    for dir in &removed {
+        eprintln!("Removed {}", dir.display());
+    }
+    if removed.is_empty() {
+        eprintln!("Nothing to remove in {}", target.display());
+    }
+
+    if profile.is_none() {
+        if let Some(cache) = trident::cache::cache_dir().filter(|d| d.exists()) {
+            if let Err(e) = trident::cache::clear() {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+            eprintln!("Removed {}", cache.display());
+        }
+    }
+}
+
+/// Directory holding `target/`: the nearest project root, or the input
+/// itself (directory) or its parent (single file) outside a project.
+fn project_root(input: &Path) -> PathBuf {
+    let start = if input.is_dir() {
+        input
+    } else {
+        input.parent().unwrap_or(Path::new("."))
+    };
+    match trident::project::Project::find(start) {
+        Some(toml) => toml.parent().unwrap_or(Path::new(".")).to_path_buf(),
+        None => start.to_path_buf(),
+    }
+}
+
+/// Remove `target/<vm>/<profile>/` for every VM target.
+fn remove_profile(target: &Path, profile: &str) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(target) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+    let mut vms: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    vms.sort();
+    vms.iter()
+        .filter_map(|vm| remove_dir(&vm.join(profile)))
+        .collect()
+}
+
+/// Remove `dir` if it exists, returning it on success.
+fn remove_dir(dir: &Path) -> Option<PathBuf> {
+    if !dir.is_dir() {
+        return None;
+    }
+    if let Err(e) = std::fs::remove_dir_all(dir) {
+        eprintln!("error: cannot remove '{}': {}", dir.display(), e);
+        process::exit(1);
+    }
+    Some(dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_profile_keeps_other_profiles() {
+        let root = std::env::temp_dir().join(format!("trident_clean_{}", process::id()));
+        let target = root.join("target");
+        for dir in ["triton/debug", "triton/release", "miden/debug"] {
+            std::fs::create_dir_all(target.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("trident.toml"), "[project]\nname = \"p\"\n").unwrap();
+
+        assert_eq!(project_root(&root.join("main.tri")), root);
+        let removed = remove_profile(&target, "debug");
+        assert_eq!(
+            removed,
+            vec![target.join("miden/debug"), target.join("triton/debug")]
+        );
+        assert!(target.join("triton/release").is_dir());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,68 @@
+use clap::{Args, Command};
+use clap_complete::Shell;
+
+#[derive(Args)]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+pub fn cmd_completions(args: CompletionsArgs, mut cmd: Command) {
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(args.shell, &mut cmd, name, &mut std::io::stdout());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn script(shell: Shell) -> String {
+        let mut cmd = crate::Cli::command();
+        let mut out = Vec::new();
+        clap_complete::generate(shell, &mut cmd, "trident", &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Every visible subcommand, at any depth, with its visible long flags.
+    fn tree(cmd: &Command, out: &mut Vec<(String, Vec<String>)>) {
+        for sub in cmd.get_subcommands().filter(|c| !c.is_hide_set()) {
+            let flags = sub
+                .get_arguments()
+                .filter(|a| !a.is_hide_set())
+                .filter_map(|a| a.get_long().map(str::to_string))
+                .collect();
+            out.push((sub.get_name().to_string(), flags));
+            tree(sub, out);
+        }
+    }
+
+    #[test]
+    fn test_every_shell_covers_every_subcommand_and_flag() {
+        let mut commands = Vec::new();
+        tree(&crate::Cli::command(), &mut commands);
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = script(shell);
+            for (name, flags) in &commands {
+                assert!(script.contains(name.as_str()), "{shell}: missing '{name}'");
+                for long in flags {
+                    // fish spells `--out` as `-l out`
+                    let flag = match shell {
+                        Shell::Fish => format!("-l {}", long),
+                        _ => format!("--{}", long),
+                    };
+                    assert!(
+                        script.contains(flag.as_str()),
+                        "{shell}: missing {name} --{long}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_possible_values_are_offered() {
+        assert!(script(Shell::Bash).contains("bash elvish fish powershell zsh"));
+    }
+}
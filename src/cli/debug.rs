@@ -0,0 +1,340 @@
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::process;
+
+use clap::Args;
+use trident::runtime::debug::{Debugger, InputKind, SourceMap, Stop};
+use trident::runtime::input::parse_words;
+
+use super::resolve_context;
+
+#[derive(Args)]
+pub struct DebugArgs {
+    /// Input .tri file or directory with trident.toml
+    pub input: PathBuf,
+    /// Target VM (only triton is supported)
+    #[arg(long, default_value = "triton")]
+    pub target: String,
+    /// Compilation profile (debug or release)
+    #[arg(long, default_value = "debug")]
+    pub profile: String,
+    /// Public input values (comma-separated field elements)
+    #[arg(long, value_delimiter = ',')]
+    pub input_values: Option<Vec<u64>>,
+    /// Secret/divine input values (comma-separated field elements)
+    #[arg(long, value_delimiter = ',')]
+    pub secret: Option<Vec<u64>>,
+    /// Set a breakpoint before starting (label, function, LINE, or FILE:LINE)
+    #[arg(long = "break", value_name = "LOCATION")]
+    pub breaks: Vec<String>,
+}
+
+const HELP: &str = "\
+Commands:
+  s, step [N]          execute N instructions (default 1)
+  n, next              step over calls
+  o, out               run until the current function returns
+  c, continue          run to the next breakpoint, halt, or missing input
+  b, break LOCATION    break at a label, function, LINE, or FILE:LINE
+  d, delete LOCATION   remove a breakpoint
+  breaks               list breakpoints
+  l, list              show the current function's source
+  asm                  show TASM around the next instruction
+  stack                show the operand stack (st0 first)
+  ram [ADDR [N]]       show RAM (all written cells, or N cells from ADDR)
+  bt                   show the call stack
+  output               show values written with write_io
+  input V,...          queue public input for read_io
+  divine V,...         queue secret input for divine
+  q, quit              exit";
+
+pub fn cmd_debug(args: DebugArgs) {
+    let ctx = resolve_context(&args.input, &args.target, &args.profile, &[]);
+    let options = ctx.options;
+    if options.target_config.name != "triton" {
+        eprintln!(
+            "error: trident debug supports Triton VM (TASM) only, not '{}'",
+            options.target_config.name
+        );
+        process::exit(1);
+    }
+
+    let tasm = match trident::compile_project_with_options(&ctx.entry, &options) {
+        Ok(t) => t,
+        Err(_) => process::exit(1),
+    };
+    let map = match trident::source_map_project(&ctx.entry, &options) {
+        Ok(m) => m,
+        Err(_) => process::exit(1),
+    };
+
+    let input = trident::runtime::ProgramInput {
+        public: args.input_values.unwrap_or_default(),
+        secret: args.secret.unwrap_or_default(),
+        digests: Vec::new(),
+    };
+    let mut dbg = Debugger::new(&tasm, &input);
+    for location in &args.breaks {
+        if let Err(e) = set_breakpoint(&mut dbg, &map, location) {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+
+    println!(
+        "Debugging {} ({} instructions). Type `help` for commands.",
+        ctx.entry.display(),
+        dbg.instructions().len()
+    );
+    show_location(&dbg, &map);
+
+    let stdin = std::io::stdin();
+    loop {
+        print!("(tdb) ");
+        let _ = std::io::stdout().flush();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            println!();
+            break;
+        }
+        let mut words = line.split_whitespace();
+        let Some(cmd) = words.next() else {
+            continue;
+        };
+        let rest: Vec<&str> = words.collect();
+        match cmd {
+            "s" | "step" => {
+                let count = rest.first().and_then(|n| n.parse().ok()).unwrap_or(1);
+                let mut stop = Stop::Step;
+                for _ in 0..count {
+                    stop = dbg.step();
+                    if stop != Stop::Step {
+                        break;
+                    }
+                }
+                report(&dbg, &map, &stop);
+            }
+            "n" | "next" => {
+                let stop = dbg.step_over();
+                report(&dbg, &map, &stop);
+            }
+            "o" | "out" => {
+                let stop = dbg.step_out();
+                report(&dbg, &map, &stop);
+            }
+            "c" | "continue" => {
+                let stop = dbg.resume();
+                report(&dbg, &map, &stop);
+            }
+            "b" | "break" => match rest.first() {
+                Some(location) => match set_breakpoint(&mut dbg, &map, location) {
+                    Ok(label) => println!("Breakpoint at {}", label),
+                    Err(e) => println!("error: {}", e),
+                },
+                None => println!("usage: break LOCATION"),
+            },
+            "d" | "delete" => match rest.first() {
+                Some(location) => {
+                    let label = resolve_location(&dbg, &map, location).unwrap_or_default();
+                    if dbg.remove_breakpoint(&label) {
+                        println!("Deleted breakpoint at {}", label);
+                    } else {
+                        println!("error: no breakpoint at '{}'", location);
+                    }
+                }
+                None => println!("usage: delete LOCATION"),
+            },
+            "breaks" => {
+                if dbg.breakpoints().is_empty() {
+                    println!("No breakpoints.");
+                }
+                for label in dbg.breakpoints() {
+                    println!("  {}", label);
+                }
+            }
+            "l" | "list" => show_source(&dbg, &map),
+            "asm" => show_asm(&dbg),
+            "stack" => show_stack(&dbg),
+            "ram" => show_ram(&dbg, &rest),
+            "bt" => show_backtrace(&dbg, &map),
+            "output" => println!("{:?}", dbg.output()),
+            "input" | "divine" => match parse_words(&rest.join(" ")) {
+                Ok(values) => {
+                    if cmd == "input" {
+                        dbg.provide_public(&values);
+                    } else {
+                        dbg.provide_secret(&values);
+                    }
+                    println!("Queued {} value(s).", values.len());
+                }
+                Err(e) => println!("error: {}", e),
+            },
+            "h" | "help" => println!("{}", HELP),
+            "q" | "quit" => break,
+            _ => println!("unknown command '{}' (type `help`)", cmd),
+        }
+    }
+}
+
+/// Resolve a breakpoint location to a TASM label.
+fn resolve_location(dbg: &Debugger, map: &SourceMap, location: &str) -> Result<String, String> {
+    if dbg.has_label(location) {
+        return Ok(location.to_string());
+    }
+    let by_name = map
+        .functions()
+        .iter()
+        .find(|f| f.name == location || format!("{}.{}", f.module, f.name) == location);
+    let function = match by_name {
+        Some(f) => f,
+        None => {
+            let (file, line) = match location.rsplit_once(':') {
+                Some((file, line)) => (Some(file), line),
+                None => (None, location),
+            };
+            let line: usize = line
+                .parse()
+                .map_err(|_| format!("unknown location '{}'", location))?;
+            map.function_at(file, line)
+                .ok_or_else(|| format!("no function at '{}'", location))?
+        }
+    };
+    if !dbg.has_label(&function.label) {
+        return Err(format!(
+            "function '{}' is not in the linked program (unused or generic)",
+            function.name
+        ));
+    }
+    Ok(function.label.clone())
+}
+
+fn set_breakpoint(dbg: &mut Debugger, map: &SourceMap, location: &str) -> Result<String, String> {
+    let label = resolve_location(dbg, map, location)?;
+    dbg.add_breakpoint(&label)?;
+    Ok(label)
+}
+
+fn report(dbg: &Debugger, map: &SourceMap, stop: &Stop) {
+    match stop {
+        Stop::Step => {}
+        Stop::Breakpoint(label) => println!("Breakpoint: {}", label),
+        Stop::Halted => {
+            println!(
+                "Program halted after {} cycles. Output: {:?}",
+                dbg.cycles(),
+                dbg.output()
+            );
+            return;
+        }
+        Stop::Error(msg) => println!("error: {}", msg),
+        Stop::NeedsInput { kind, count } => {
+            let (what, cmd) = match kind {
+                InputKind::Public => ("public input", "input"),
+                InputKind::Secret => ("secret input", "divine"),
+            };
+            println!(
+                "Paused: needs {} more {} value(s); queue them with `{} V,...`",
+                count, what, cmd
+            );
+        }
+        Stop::CycleLimit => println!("Paused: cycle limit reached ({} cycles)", dbg.cycles()),
+    }
+    show_location(dbg, map);
+}
+
+/// The innermost call frame that maps to a source function.
+fn current_function<'a>(
+    dbg: &Debugger,
+    map: &'a SourceMap,
+) -> Option<&'a trident::runtime::debug::FunctionSource> {
+    dbg.frames()
+        .iter()
+        .rev()
+        .find_map(|frame| map.function_for_label(&frame.label))
+}
+
+fn show_location(dbg: &Debugger, map: &SourceMap) {
+    let Some(instr) = dbg.current() else {
+        return;
+    };
+    match current_function(dbg, map) {
+        Some(f) => println!(
+            "  in {}.{} ({}:{}-{})",
+            f.module,
+            f.name,
+            f.file.display(),
+            f.start_line,
+            f.end_line
+        ),
+        None => println!("  in <entry>"),
+    }
+    println!("  => {:>5}  {}", instr.line + 1, instr.text);
+}
+
+fn show_source(dbg: &Debugger, map: &SourceMap) {
+    let Some(f) = current_function(dbg, map) else {
+        println!("No source for the entry stub.");
+        return;
+    };
+    let Some(source) = map.source(&f.file) else {
+        return;
+    };
+    println!("{}:", f.file.display());
+    for (i, line) in source.lines().enumerate() {
+        let n = i + 1;
+        if (f.start_line..=f.end_line).contains(&n) {
+            println!("{:>5}  {}", n, line);
+        }
+    }
+}
+
+fn show_asm(dbg: &Debugger) {
+    let pc = dbg.pc();
+    let start = pc.saturating_sub(3);
+    for (i, instr) in dbg.instructions().iter().enumerate().skip(start).take(8) {
+        let marker = if i == pc { "=>" } else { "  " };
+        println!("  {} {:>5}  {}", marker, instr.line + 1, instr.text);
+    }
+}
+
+fn show_stack(dbg: &Debugger) {
+    if dbg.stack().is_empty() {
+        println!("Stack is empty.");
+    }
+    for (i, value) in dbg.stack().iter().rev().enumerate() {
+        println!("  st{:<3} {}", i, value);
+    }
+}
+
+fn show_ram(dbg: &Debugger, args: &[&str]) {
+    let Some(addr) = args.first().and_then(|a| a.parse::<u64>().ok()) else {
+        if dbg.ram().is_empty() {
+            println!("RAM is empty.");
+        }
+        for (addr, value) in dbg.ram() {
+            println!("  [{}] {}", addr, value);
+        }
+        return;
+    };
+    let count = args.get(1).and_then(|n| n.parse::<u64>().ok()).unwrap_or(1);
+    for a in addr..addr.saturating_add(count) {
+        println!("  [{}] {}", a, dbg.ram().get(&a).copied().unwrap_or(0));
+    }
+}
+
+fn show_backtrace(dbg: &Debugger, map: &SourceMap) {
+    for (depth, frame) in dbg.frames().iter().rev().enumerate() {
+        match map.function_for_label(&frame.label) {
+            Some(f) => println!(
+                "  #{} {} ({}.{} at {}:{})",
+                depth,
+                frame.label,
+                f.module,
+                f.name,
+                f.file.display(),
+                f.start_line
+            ),
+            None => println!("  #{} {}", depth, frame.label),
+        }
+    }
+}
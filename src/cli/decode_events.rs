@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::Args;
+use trident::runtime::events::{
+    decode_events, parse_output_words, seal_digest_tasm, SealPreimage, DIGEST_LEN,
+};
+
+use super::resolve_context;
+use super::trisha::{run_trisha, trisha_available};
+
+#[derive(Args)]
+pub struct DecodeEventsArgs {
+    /// Input .tri file or directory with trident.toml (source of the ABI)
+    pub input: PathBuf,
+    /// Public output: a file of words, or the words themselves
+    /// (decimal or 0x hex, separated by spaces or commas)
+    pub output: String,
+    /// Preimage of a sealed event, `Event=v1,v2,...` (repeatable);
+    /// its digest is computed with trisha and looked up in the output
+    #[arg(long = "seal")]
+    pub seals: Vec<String>,
+    /// Print the decoded events as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn cmd_decode_events(args: DecodeEventsArgs) {
+    let ctx = resolve_context(&args.input, "triton", "debug", &[]);
+    let Ok(abi) = trident::Compiler::new(&ctx.entry)
+        .with_options(ctx.options)
+        .abi()
+    else {
+        eprintln!("error: cannot read the ABI (compilation errors)");
+        process::exit(1);
+    };
+
+    let text = if Path::new(&args.output).is_file() {
+        std::fs::read_to_string(&args.output).unwrap_or_else(|e| {
+            eprintln!("error: cannot read '{}': {}", args.output, e);
+            process::exit(1);
+        })
+    } else {
+        args.output.clone()
+    };
+    let output = parse_output_words(&text).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    });
+
+    let preimages: Vec<SealPreimage> = args
+        .seals
+        .iter()
+        .map(|spec| SealPreimage::parse(spec, &abi))
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| {
+            eprintln!("error: --seal: {}", e);
+            process::exit(1);
+        });
+    let digests = seal_digests(&preimages, &abi);
+    let seals: Vec<_> = preimages.into_iter().zip(digests).collect();
+
+    let log = decode_events(&abi, &output, &seals);
+    if args.json {
+        print!("{}", log.to_json());
+    } else {
+        print!("{}", log.format_report());
+    }
+    if !log.unmatched.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// Digest of each preimage, hashed by trisha as `seal` would.
+fn seal_digests(
+    preimages: &[SealPreimage],
+    abi: &trident::runtime::ProgramAbi,
+) -> Vec<[u64; DIGEST_LEN]> {
+    if preimages.is_empty() {
+        return Vec::new();
+    }
+    if !trisha_available() {
+        eprintln!("error: verifying sealed digests needs trisha");
+        eprintln!("  cargo install trisha   # Triton VM + Neptune");
+        process::exit(1);
+    }
+    let tmp_path = std::env::temp_dir().join(format!("trident_seals_{}.tasm", process::id()));
+    if let Err(e) = std::fs::write(&tmp_path, seal_digest_tasm(preimages, abi)) {
+        eprintln!("error: cannot write '{}': {}", tmp_path.display(), e);
+        process::exit(1);
+    }
+    let result = run_trisha(&["run", "--tasm", &tmp_path.to_string_lossy()]);
+    let _ = std::fs::remove_file(&tmp_path);
+    let words = match result {
+        Ok(r) if r.output.len() == preimages.len() * DIGEST_LEN => r.output,
+        Ok(r) => {
+            eprintln!(
+                "error: trisha wrote {} words for {} digest(s)",
+                r.output.len(),
+                preimages.len()
+            );
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("error: hashing sealed preimages: {}", e);
+            process::exit(1);
+        }
+    };
+    words
+        .chunks(DIGEST_LEN)
+        .map(|c| [c[0], c[1], c[2], c[3], c[4]])
+        .collect()
+}
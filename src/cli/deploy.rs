@@ -3,7 +3,10 @@ use std::process;
 
 use clap::Args;
 
-use super::{open_codebase, prepare_artifact, registry_client, try_load_and_parse};
+use super::{
+    open_codebase, prepare_artifact, registry_client, signing_key, trusted_key_set,
+    try_load_and_parse,
+};
 
 #[derive(Args)]
 pub struct DeployArgs {
@@ -36,12 +39,21 @@ pub struct DeployArgs {
     /// Registry URL to deploy to
     #[arg(long)]
     pub registry: Option<String>,
+    /// Add each entry point's worst-case cost to manifest.json
+    #[arg(long)]
+    pub entry_costs: bool,
     /// Run formal audit before deploying
     #[arg(long)]
     pub audit: bool,
     /// Show what would be deployed without actually deploying
     #[arg(long)]
     pub dry_run: bool,
+    /// Trust this signer key (hex or .pub file), besides the keys directory
+    #[arg(long = "trusted-key")]
+    pub trusted_keys: Vec<String>,
+    /// Deploy a pre-packaged artifact even if its signature is missing, invalid, or untrusted
+    #[arg(long)]
+    pub allow_unsigned: bool,
 }
 
 pub fn cmd_deploy(args: DeployArgs) {
@@ -56,8 +68,11 @@ pub fn cmd_deploy(args: DeployArgs) {
         state,
         profile,
         registry,
+        entry_costs,
         audit,
         dry_run,
+        trusted_keys,
+        allow_unsigned,
     } = args;
     let bf = super::resolve_battlefield(
         &target,
@@ -72,7 +87,10 @@ pub fn cmd_deploy(args: DeployArgs) {
     let state_selection = bf.state;
 
     // Handle pre-packaged .deploy/ artifact directory
-    if input.is_dir() && input.join("manifest.json").exists() && input.join("program.tasm").exists()
+    let linked = input.join(trident::deploy::objects::LINK_FILE).exists();
+    if input.is_dir()
+        && input.join("manifest.json").exists()
+        && (input.join("program.tasm").exists() || linked)
     {
         let manifest_json = match std::fs::read_to_string(input.join("manifest.json")) {
             Ok(s) => s,
@@ -82,10 +100,27 @@ pub fn cmd_deploy(args: DeployArgs) {
             }
         };
 
+        let trusted = trusted_key_set(&trusted_keys);
+        let status = trident::deploy::signing::verify_artifact(&input, &trusted);
         if dry_run {
             eprintln!("Dry run — would deploy artifact:");
             eprintln!("  Artifact:  {}", input.display());
-            for line in manifest_json.lines() {
+            match &status {
+                trident::deploy::signing::SignatureStatus::Valid { fingerprint } => {
+                    eprintln!("  Signed by: {}", fingerprint)
+                }
+                trident::deploy::signing::SignatureStatus::Untrusted { fingerprint } => {
+                    eprintln!("  Signed by: {} (UNTRUSTED)", fingerprint)
+                }
+                trident::deploy::signing::SignatureStatus::Unsigned => {
+                    eprintln!("  Signature: none")
+                }
+                trident::deploy::signing::SignatureStatus::Invalid(reason) => {
+                    eprintln!("  Signature: INVALID ({})", reason)
+                }
+            }
+            // Top-level keys only; nested objects reuse `program_digest`.
+            for line in manifest_json.lines().filter(|l| !l.starts_with("    ")) {
                 let trimmed = line.trim();
                 if trimmed.starts_with("\"name\"") || trimmed.starts_with("\"program_digest\"") {
                     eprintln!("  {}", trimmed.trim_end_matches(','));
@@ -94,13 +129,35 @@ pub fn cmd_deploy(args: DeployArgs) {
             return;
         }
 
+        match status.require("artifact", allow_unsigned) {
+            Ok(Some(fingerprint)) => eprintln!("Signature OK (signed by {})", fingerprint),
+            Ok(None) => eprintln!("warning: deploying artifact without a valid signature"),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+        }
+
+        if linked {
+            let store = trident::deploy::objects::default_store(&input);
+            if let Err(e) = trident::deploy::objects::flatten_artifact(&input, &store) {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+            eprintln!("Flattened {} into program.tasm", input.display());
+        }
+
+        let metadata = trident::deploy::upgrade::DeployedManifest::from_json(&manifest_json)
+            .map(|m| m.metadata)
+            .unwrap_or_default();
         let client = registry_client(registry);
-        deploy_to_registry(&input, &client);
+        deploy_to_registry(&input, &client, &signing_key(), &metadata);
         return;
     }
 
     // Build from source
     let art = prepare_artifact(&input, &target, &profile, audit);
+    let metadata = super::package::check_metadata(art.project.as_ref());
     let output_base = art.entry.parent().unwrap_or(Path::new(".")).to_path_buf();
 
     // Resolve state config if specified
@@ -163,14 +220,13 @@ pub fn cmd_deploy(args: DeployArgs) {
         return;
     }
 
-    let result = match trident::deploy::generate_artifact(
+    let mut result = match trident::deploy::generate_artifact(
         &art.name,
         &art.version,
         &art.tasm,
         &art.file,
         &art.cost,
-        &art.resolved.vm,
-        art.resolved.os.as_ref(),
+        &art.manifest_context(),
         &output_base,
     ) {
         Ok(r) => r,
@@ -180,15 +236,43 @@ pub fn cmd_deploy(args: DeployArgs) {
         }
     };
 
+    if !metadata.is_empty() {
+        if let Err(e) = trident::deploy::add_metadata(&mut result, &metadata) {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+
+    if entry_costs {
+        if let Err(e) = trident::deploy::add_entry_costs(&mut result, &art.cost) {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+
+    let key = signing_key();
+    if let Err(e) = trident::deploy::sign_artifact(&mut result, &key) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+
     eprintln!("Packaged -> {}", result.artifact_dir.display());
     eprintln!("  digest: {}", result.manifest.program_digest);
+    eprintln!("  signed by: {}", key.fingerprint());
 
     let client = registry_client(registry);
-    deploy_to_registry(&result.artifact_dir, &client);
+    deploy_to_registry(&result.artifact_dir, &client, &key, &metadata);
 }
 
 /// Deploy a validated artifact directory (must contain manifest.json + program.tasm).
-fn deploy_to_registry(artifact_dir: &Path, client: &trident::registry::RegistryClient) {
+///
+/// Published definitions are signed with `key`.
+fn deploy_to_registry(
+    artifact_dir: &Path,
+    client: &trident::registry::RegistryClient,
+    key: &trident::deploy::signing::SigningKey,
+    metadata: &trident::project::ProjectMetadata,
+) {
     eprintln!("Deploying...");
 
     // Try to find and add source to codebase
@@ -213,7 +297,7 @@ fn deploy_to_registry(artifact_dir: &Path, client: &trident::registry::RegistryC
         }
     }
 
-    match trident::registry::publish_codebase(&cb, client, &[]) {
+    match trident::registry::publish_codebase(&cb, client, &[], Some(key), None, metadata) {
         Ok(results) => {
             let created = results.iter().filter(|r| r.created).count();
             eprintln!("Deployed: {} definitions ({} new)", results.len(), created);
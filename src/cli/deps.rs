@@ -17,6 +17,8 @@ pub enum DepsAction {
     },
     /// Verify all locked dependencies are cached and valid
     Check,
+    /// Copy all locked dependencies into vendor/ for offline builds
+    Vendor,
 }
 
 pub fn cmd_deps(action: DepsAction) {
@@ -59,6 +61,9 @@ pub fn cmd_deps(action: DepsAction) {
                     } => {
                         println!("  {} = {} @ {} (registry)", name, reg_name, registry);
                     }
+                    trident::manifest::Dependency::Onchain { digest, chain } => {
+                        println!("  {} = {} @ {} (onchain)", name, short_hash(digest), chain);
+                    }
                     trident::manifest::Dependency::Path { path } => {
                         println!("  {} = {} (path)", name, path.display());
                     }
@@ -93,6 +98,7 @@ pub fn cmd_deps(action: DepsAction) {
                 deps,
                 &existing_lock,
                 &registry,
+                Some(&super::WarriorLedger),
             ) {
                 Ok(lockfile) => {
                     if let Err(e) = trident::manifest::save_lockfile(&lock_path, &lockfile) {
@@ -140,5 +146,38 @@ pub fn cmd_deps(action: DepsAction) {
                 process::exit(1);
             }
         }
+        DepsAction::Vendor => {
+            let lock_path = project.root_dir.join("trident.lock");
+            if !lock_path.exists() {
+                eprintln!("error: no trident.lock found. Run `trident deps fetch` first.");
+                process::exit(1);
+            }
+            let lockfile = match trident::manifest::load_lockfile(&lock_path) {
+                Ok(l) => l,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    process::exit(1);
+                }
+            };
+            let vendored =
+                match trident::manifest::vendor_dependencies(&project.root_dir, &lockfile) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        eprintln!("error: {}", e);
+                        process::exit(1);
+                    }
+                };
+            for dep in &vendored {
+                println!("  {} ({})", dep.name, short_hash(&dep.hash));
+            }
+            println!(
+                "\nVendored {} dependencies into {}/.",
+                vendored.len(),
+                trident::manifest::VENDOR_DIR
+            );
+            if !project.vendor {
+                println!("Set `vendor = true` under [project] in trident.toml to build from it.");
+            }
+        }
     }
 }
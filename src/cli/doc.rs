@@ -3,7 +3,7 @@ use std::process;
 
 use clap::Args;
 
-use super::{resolve_input, resolve_options};
+use super::resolve_context;
 
 #[derive(Args)]
 pub struct DocArgs {
@@ -45,10 +45,9 @@ pub fn cmd_doc(args: DocArgs) {
     } = args;
     let bf = super::resolve_battlefield_compile(&target, &engine, &terrain, &network, &union_flag);
     let target = bf.target;
-    let ri = resolve_input(&input);
+    let ctx = resolve_context(&input, &target, &profile, &[]);
 
-    let options = resolve_options(&target, &profile, ri.project.as_ref());
-    let markdown = match trident::generate_docs(&ri.entry, &options) {
+    let markdown = match trident::generate_docs(&ctx.entry, &ctx.options) {
         Ok(md) => md,
         Err(_) => {
             eprintln!("error: documentation generation failed (compilation errors)");
@@ -0,0 +1,58 @@
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::Args;
+use trident::runtime::input::{encode_input, format_words};
+
+use super::build::write_artifact;
+use super::resolve_context;
+
+#[derive(Args)]
+pub struct EncodeInputArgs {
+    /// Input .tri file or directory with trident.toml (source of the ABI)
+    pub input: PathBuf,
+    /// JSON file of typed values for the public and secret streams
+    pub values: PathBuf,
+    /// Write a witness file for `trident run --witness` / `trident test --witness`
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+pub fn cmd_encode_input(args: EncodeInputArgs) {
+    let ctx = resolve_context(&args.input, "triton", "debug", &[]);
+    let Ok(abi) = trident::Compiler::new(&ctx.entry)
+        .with_options(ctx.options)
+        .abi()
+    else {
+        eprintln!("error: cannot read the ABI (compilation errors)");
+        process::exit(1);
+    };
+    let json = std::fs::read_to_string(&args.values).unwrap_or_else(|e| {
+        eprintln!("error: cannot read '{}': {}", args.values.display(), e);
+        process::exit(1);
+    });
+    let input = encode_input(&abi, &json).unwrap_or_else(|e| {
+        eprintln!("error: {}: {}", args.values.display(), e);
+        process::exit(1);
+    });
+
+    match args.output {
+        Some(path) => write_artifact(&path, &input.to_witness_json()),
+        None => {
+            println!("--input-values {}", format_words(&input.public));
+            println!("--secret {}", format_words(&input.secret));
+        }
+    }
+}
+
+/// Read a witness file written by `trident encode-input -o`.
+pub fn read_witness(path: &Path) -> trident::runtime::ProgramInput {
+    let json = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("error: cannot read '{}': {}", path.display(), e);
+        process::exit(1);
+    });
+    trident::runtime::ProgramInput::from_witness_json(&json).unwrap_or_else(|e| {
+        eprintln!("error: {}: {}", path.display(), e);
+        process::exit(1);
+    })
+}
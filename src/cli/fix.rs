@@ -0,0 +1,107 @@
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::Args;
+
+use trident::diagnostic::{render_config, Suggestion};
+
+use super::resolve_tri_files;
+
+#[derive(Args)]
+pub struct FixArgs {
+    /// Input .tri file or directory (defaults to current directory)
+    pub input: Option<PathBuf>,
+    /// Print a diff of the fixes instead of applying them
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Apply every fix without asking for confirmation
+    #[arg(long, short = 'y', conflicts_with = "dry_run")]
+    pub yes: bool,
+}
+
+pub fn cmd_fix(args: FixArgs) {
+    let FixArgs {
+        input,
+        dry_run,
+        yes,
+    } = args;
+    let input = input.unwrap_or_else(|| PathBuf::from("."));
+    let files = resolve_tri_files(&input);
+
+    if files.is_empty() {
+        eprintln!("No .tri files found in '{}'", input.display());
+        return;
+    }
+
+    let mut fixed = 0;
+    let mut failed = false;
+    for file in &files {
+        match fix_single_file(file, dry_run, yes) {
+            Ok(n) => fixed += n,
+            Err(msg) => {
+                eprintln!("error: {}", msg);
+                failed = true;
+            }
+        }
+    }
+    if fixed == 0 {
+        eprintln!("No fixes applied.");
+    } else if dry_run {
+        eprintln!(
+            "{} fix(es) available; run without --dry-run to apply.",
+            fixed
+        );
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// Collect the fixes for `path` and print them as a diff (`dry_run`) or
+/// apply the confirmed ones. Returns the number of fixes.
+fn fix_single_file(path: &Path, dry_run: bool, yes: bool) -> Result<usize, String> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read '{}': {}", path.display(), e))?;
+    let filename = path.to_string_lossy().to_string();
+    let fixes = trident::collect_fixes(&source, &filename)
+        .map_err(|_| format!("cannot fix '{}' (parse errors)", path.display()))?;
+
+    let config = render_config();
+    let mut accepted: Vec<Suggestion> = Vec::new();
+    for diag in fixes {
+        if !dry_run {
+            eprint!("{}", diag.render_with(&filename, &source, &config));
+        }
+        for suggestion in diag.into_suggestions() {
+            if dry_run || yes || confirm(&suggestion.message) {
+                accepted.push(suggestion);
+            }
+        }
+    }
+    let planned = trident::plan_fixes(&source, accepted);
+    if planned.is_empty() {
+        return Ok(0);
+    }
+
+    if dry_run {
+        print!("{}", trident::fix_diff(&filename, &source, &planned));
+        return Ok(planned.len());
+    }
+    std::fs::write(path, trident::apply_fixes(&source, &planned))
+        .map_err(|e| format!("cannot write '{}': {}", path.display(), e))?;
+    eprintln!("Fixed {} issue(s): {}", planned.len(), path.display());
+    Ok(planned.len())
+}
+
+/// Ask on stderr; anything but `y`/`yes` (including EOF) declines.
+fn confirm(message: &str) -> bool {
+    eprint!("{}? [y/N] ", message);
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer).unwrap_or(0) == 0 {
+        eprintln!();
+        return false;
+    }
+    matches!(answer.trim(), "y" | "Y" | "yes")
+}
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+use std::process;
+
+use clap::Args;
+
+use super::resolve_input;
+
+#[derive(Args)]
+pub struct GrepArgs {
+    /// Expression pattern: `$name` matches any expression (equal ones
+    /// when repeated), `...` any number of arguments or elements
+    pub pattern: String,
+    /// Input .tri file or directory with trident.toml
+    pub input: Option<PathBuf>,
+    /// Search only the project's own modules, not dependencies or std
+    #[arg(long)]
+    pub local: bool,
+    /// Print what each metavariable matched
+    #[arg(long)]
+    pub bindings: bool,
+}
+
+pub fn cmd_grep(args: GrepArgs) {
+    let GrepArgs {
+        pattern,
+        input,
+        local,
+        bindings,
+    } = args;
+    let pattern = trident::ast::search::Pattern::parse(&pattern).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    });
+    let input =
+        input.unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    let ctx = resolve_input(&input);
+    let root = ctx
+        .project
+        .as_ref()
+        .map(|p| p.root_dir.clone())
+        .or_else(|| ctx.entry.parent().map(PathBuf::from))
+        .unwrap_or_default();
+
+    let Ok(matches) = trident::grep_project(&ctx.entry, &ctx.options, &pattern) else {
+        process::exit(1);
+    };
+    let canonical = |path: &PathBuf| std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+    let root = canonical(&root);
+    let deps = root.join(".trident");
+    let matches: Vec<_> = matches
+        .into_iter()
+        .map(|m| (canonical(&m.file), m))
+        .filter(|(file, _)| !local || (file.starts_with(&root) && !file.starts_with(&deps)))
+        .collect();
+    for (file, m) in &matches {
+        let path = file.strip_prefix(&root).unwrap_or(file);
+        let first_line = m.text.lines().next().unwrap_or_default();
+        println!(
+            "{}:{}:{}: {}: {}",
+            path.display(),
+            m.line,
+            m.column,
+            m.function,
+            first_line
+        );
+        if bindings {
+            for (name, text) in &m.bindings {
+                println!("    ${} = {}", name, text);
+            }
+        }
+    }
+    if matches.is_empty() {
+        process::exit(1);
+    }
+}
@@ -11,29 +11,47 @@ pub struct HashArgs {
     /// Show full 256-bit hashes instead of short form
     #[arg(long)]
     pub full: bool,
+    /// Show hashes as four Goldilocks field elements, for on-chain checks
+    #[arg(long, conflicts_with = "full")]
+    pub fields: bool,
+    /// Also show each function's exact source-text hash, which changes
+    /// with formatting and local names
+    #[arg(long)]
+    pub raw: bool,
 }
 
 pub fn cmd_hash(args: HashArgs) {
-    let HashArgs { input, full } = args;
-    let ri = resolve_input(&input);
-    let (_, file) = load_and_parse(&ri.entry);
+    let HashArgs {
+        input,
+        full,
+        fields,
+        raw,
+    } = args;
+    let show = |hash: &trident::hash::ContentHash| {
+        if fields {
+            let f = hash.to_fields();
+            format!("[{}, {}, {}, {}]", f[0], f[1], f[2], f[3])
+        } else if full {
+            hash.to_hex()
+        } else {
+            hash.to_string()
+        }
+    };
+    let ctx = resolve_input(&input);
+    let (source, file) = load_and_parse(&ctx.entry);
 
     let fn_hashes = trident::hash::hash_file(&file);
+    let raw_hashes = trident::hash::hash_file_raw(&file, &source);
     let file_hash = trident::hash::hash_file_content(&file);
 
-    if full {
-        eprintln!("File: {} {}", file_hash.to_hex(), ri.entry.display());
-    } else {
-        eprintln!("File: {} {}", file_hash, ri.entry.display());
-    }
+    eprintln!("File: {} {}", show(&file_hash), ctx.entry.display());
 
     let mut sorted: Vec<_> = fn_hashes.iter().collect();
     sorted.sort_by_key(|(name, _)| (*name).clone());
     for (name, hash) in sorted {
-        if full {
-            println!("  {} {}", hash.to_hex(), name);
-        } else {
-            println!("  {} {}", hash, name);
+        match raw_hashes.get(name).filter(|_| raw) {
+            Some(raw_hash) => println!("  {} {} {}", show(hash), show(raw_hash), name),
+            None => println!("  {} {}", show(hash), name),
         }
     }
 }
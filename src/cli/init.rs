@@ -63,6 +63,15 @@ pub fn cmd_init(args: InitArgs) {
         process::exit(1);
     }
 
+    // Keep build products out of version control
+    let gitignore_path = project_dir.join(".gitignore");
+    if !gitignore_path.exists() {
+        if let Err(e) = std::fs::write(&gitignore_path, "target/\n") {
+            eprintln!("error: cannot write '{}': {}", gitignore_path.display(), e);
+            process::exit(1);
+        }
+    }
+
     eprintln!(
         "Created project '{}' in {}",
         project_name,
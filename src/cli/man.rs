@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use clap::{Args, Command};
+
+#[derive(Args)]
+pub struct ManArgs {
+    /// Write trident.1 and a page per subcommand to this directory
+    /// instead of printing trident.1
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+pub fn cmd_man(args: ManArgs, cmd: Command) {
+    let result = match &args.output {
+        Some(dir) => std::fs::create_dir_all(dir).and_then(|()| clap_mangen::generate_to(cmd, dir)),
+        None => clap_mangen::Man::new(cmd).render(&mut std::io::stdout()),
+    };
+    match (result, args.output) {
+        (Err(e), Some(dir)) => {
+            eprintln!(
+                "error: cannot write man pages to '{}': {}",
+                dir.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+        (Err(e), None) => {
+            eprintln!("error: cannot write man page: {}", e);
+            std::process::exit(1);
+        }
+        (Ok(()), Some(dir)) => eprintln!("Wrote man pages to {}", dir.display()),
+        (Ok(()), None) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    /// Man page file names for every visible subcommand, at any depth.
+    fn pages(cmd: &clap::Command, prefix: &str, out: &mut Vec<String>) {
+        for sub in cmd.get_subcommands().filter(|c| !c.is_hide_set()) {
+            let name = format!("{}-{}", prefix, sub.get_name());
+            out.push(format!("{}.1", name));
+            pages(sub, &name, out);
+        }
+    }
+
+    #[test]
+    fn test_man_pages_document_every_subcommand_and_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let cmd = crate::Cli::command();
+        clap_mangen::generate_to(cmd.clone(), dir.path()).unwrap();
+
+        let top = std::fs::read_to_string(dir.path().join("trident.1")).unwrap();
+        assert!(top.contains(".TH trident 1"));
+        let mut expected = Vec::new();
+        pages(&cmd, "trident", &mut expected);
+        for page in expected {
+            assert!(dir.path().join(&page).exists(), "missing {}", page);
+        }
+
+        let build = cmd.find_subcommand("build").unwrap();
+        let page = std::fs::read_to_string(dir.path().join("trident-build.1")).unwrap();
+        for arg in build.get_arguments().filter(|a| !a.is_hide_set()) {
+            if let Some(long) = arg.get_long() {
+                let flag = format!("\\-\\-{}", long.replace('-', "\\-"));
+                assert!(page.contains(&flag), "trident-build.1: missing --{}", long);
+            }
+        }
+    }
+}
@@ -1,30 +1,86 @@
 pub mod audit;
 pub mod bench;
+pub mod bindgen;
 pub mod build;
 // no subcommand — shared trisha subprocess helpers for bench + audit
 pub mod check;
+pub mod clean;
+pub mod completions;
+pub mod debug;
+pub mod decode_events;
 pub mod deploy;
 pub mod deps;
 pub mod doc;
+pub mod encode_input;
+pub mod fix;
 pub mod fmt;
 pub mod generate;
+pub mod grep;
 pub mod hash;
 pub mod init;
+pub mod man;
 pub mod package;
 pub mod prove;
 pub mod registry;
+pub mod repl;
 pub mod run;
 pub mod store;
+pub mod synth_tests;
 pub mod test;
+pub mod trace_diff;
 pub mod train;
 pub mod tree_sitter;
 pub mod trisha;
+pub mod upgrade_diff;
 pub mod verify;
 pub mod view;
+pub mod witness;
 
 use std::path::{Path, PathBuf};
 use std::process;
 
+use clap::{Args, ValueEnum};
+use trident::project::BuildContext;
+
+// ─── Diagnostic Rendering ──────────────────────────────────────────
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ColorArg {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Global flags controlling how compiler diagnostics are printed.
+#[derive(Args)]
+pub struct DiagnosticArgs {
+    /// When to color diagnostics (auto honors NO_COLOR and non-terminals)
+    #[arg(long, value_enum, default_value = "auto", global = true)]
+    pub color: ColorArg,
+    /// Print one `file:line:col: severity: message` line per diagnostic
+    #[arg(long, global = true)]
+    pub terse: bool,
+    /// Source lines to show around each diagnostic
+    #[arg(long, default_value_t = 0, global = true)]
+    pub context_lines: usize,
+}
+
+impl DiagnosticArgs {
+    /// Install these settings for every diagnostic rendered afterwards.
+    pub fn apply(&self) {
+        use trident::diagnostic::{set_render_config, ColorChoice, RenderConfig};
+        set_render_config(RenderConfig {
+            color: match self.color {
+                ColorArg::Auto => ColorChoice::Auto,
+                ColorArg::Always => ColorChoice::Always,
+                ColorArg::Never => ColorChoice::Never,
+            },
+            terse: self.terse,
+            context_lines: self.context_lines,
+        });
+    }
+}
+
 // ─── Three-Register Target Resolution ──────────────────────────────
 
 /// Resolved battlefield from three naming registers.
@@ -85,109 +141,44 @@ pub fn resolve_battlefield_compile(
 
 // ─── Input Resolution ──────────────────────────────────────────────
 
-/// Resolved input: entry file and optional project.
-pub struct ResolvedInput {
-    pub entry: PathBuf,
-    pub project: Option<trident::project::Project>,
-}
-
-fn load_project(toml_path: &Path) -> trident::project::Project {
-    match trident::project::Project::load(toml_path) {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("error: {}", e.message);
-            process::exit(1);
-        }
-    }
-}
-
-/// Resolve an input path (file or project directory) to an entry file and optional project.
-pub fn resolve_input(input: &Path) -> ResolvedInput {
-    if input.is_dir() {
-        let toml_path = input.join("trident.toml");
-        if !toml_path.exists() {
-            eprintln!("error: no trident.toml found in '{}'", input.display());
-            process::exit(1);
-        }
-        let project = load_project(&toml_path);
-        let entry = project.entry.clone();
-        return ResolvedInput {
-            entry,
-            project: Some(project),
-        };
-    }
-
-    if !input.extension().is_some_and(|e| e == "tri") {
-        eprintln!("error: input must be a .tri file or project directory");
+/// Resolve an input path (file or project directory) to an entry file and
+/// optional project, with default compile options.
+pub fn resolve_input(input: &Path) -> BuildContext {
+    BuildContext::discover(input).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
         process::exit(1);
-    }
-
-    let toml_path = trident::project::Project::find(input.parent().unwrap_or(Path::new(".")));
-    match toml_path {
-        Some(p) => {
-            let project = load_project(&p);
-            let entry = project.entry.clone();
-            ResolvedInput {
-                entry,
-                project: Some(project),
-            }
-        }
-        None => ResolvedInput {
-            entry: input.to_path_buf(),
-            project: None,
-        },
-    }
+    })
 }
 
-/// Resolve a VM target + profile to CompileOptions.
-pub fn resolve_options(
+/// Resolve an input path and the compile options for a VM target and
+/// profile, with `features` enabled in addition to `default`.
+pub fn resolve_context(
+    input: &Path,
     target: &str,
     profile: &str,
-    project: Option<&trident::project::Project>,
-) -> trident::CompileOptions {
-    // Backward compat: --target debug/release → treat as profile
-    let (vm_target, actual_profile) = match target {
-        "debug" | "release" => {
-            eprintln!(
-                "warning: --target {} is deprecated; use --profile {} --target triton",
-                target, target
-            );
-            ("triton", target)
-        }
-        _ => (target, profile),
-    };
-
-    // Project may override the default "triton" target
-    let effective_target = match (vm_target, project) {
-        ("triton", Some(proj)) if proj.target.is_some() => {
-            proj.target.as_deref().expect("guarded by is_some() check")
-        }
-        _ => vm_target,
-    };
-
-    let target_config = if effective_target == "triton" {
-        trident::target::TerrainConfig::triton()
-    } else {
-        match trident::target::TerrainConfig::resolve(effective_target) {
-            Ok(config) => config,
-            Err(e) => {
-                eprintln!("error: {}", e.message);
-                process::exit(1);
-            }
-        }
-    };
-
-    let cfg_flags = project
-        .and_then(|proj| proj.targets.get(actual_profile))
-        .map(|flags| flags.iter().cloned().collect())
-        .unwrap_or_else(|| std::collections::BTreeSet::from([actual_profile.to_string()]));
+    features: &[String],
+) -> BuildContext {
+    let ctx = resolve_input(input);
+    configure_or_exit(ctx, target, profile, features)
+}
 
-    trident::CompileOptions {
-        profile: actual_profile.to_string(),
-        cfg_flags,
-        target_config,
-        dep_dirs: Vec::new(),
+/// `BuildContext::configure`, printing its warnings and exiting on error.
+pub fn configure_or_exit(
+    ctx: BuildContext,
+    target: &str,
+    profile: &str,
+    features: &[String],
+) -> BuildContext {
+    let mut ctx = ctx
+        .configure(target, profile, features)
+        .unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        });
+    for warning in ctx.warnings.drain(..) {
+        eprintln!("warning: {}", warning);
     }
+    ctx
 }
 
 /// Result of the shared compile → analyze → parse → verify pipeline.
@@ -200,6 +191,20 @@ pub struct PreparedArtifact {
     pub name: String,
     pub version: String,
     pub resolved: trident::target::ResolvedTarget,
+    pub provenance: trident::deploy::Provenance,
+    pub ram: trident::tir::ram::RamLayout,
+}
+
+impl PreparedArtifact {
+    /// The build this artifact's manifest records.
+    pub fn manifest_context(&self) -> trident::deploy::ManifestContext<'_> {
+        trident::deploy::ManifestContext {
+            target_vm: &self.resolved.vm,
+            target_os: self.resolved.os.as_ref(),
+            provenance: &self.provenance,
+            ram: &self.ram,
+        }
+    }
 }
 
 /// Shared pipeline for package and deploy.
@@ -209,10 +214,6 @@ pub fn prepare_artifact(
     profile: &str,
     verify: bool,
 ) -> PreparedArtifact {
-    let ri = resolve_input(input);
-    let project = ri.project;
-    let entry = ri.entry;
-
     let resolved = match trident::target::ResolvedTarget::resolve(target) {
         Ok(r) => r,
         Err(e) => {
@@ -221,11 +222,11 @@ pub fn prepare_artifact(
         }
     };
 
-    let mut options = resolve_options(&resolved.vm.name, profile, project.as_ref());
+    let ctx = resolve_context(input, &resolved.vm.name, profile, &[]);
+    let project = ctx.project;
+    let entry = ctx.entry;
+    let mut options = ctx.options;
     options.target_config = resolved.vm.clone();
-    if let Some(ref proj) = project {
-        options.dep_dirs = load_dep_dirs(proj);
-    }
 
     eprintln!("Compiling {}...", entry.display());
     let tasm = match trident::compile_project_with_options(&entry, &options) {
@@ -248,9 +249,28 @@ pub fn prepare_artifact(
             padded_height: 0,
             estimated_proving_ns: 0,
             loop_bound_waste: Vec::new(),
+            entries: Vec::new(),
         }
     });
 
+    let provenance = match trident::deploy::collect_provenance(&entry, &options, project.as_ref()) {
+        Ok(p) => p,
+        Err(_) => {
+            eprintln!("error: cannot record module provenance");
+            process::exit(1);
+        }
+    };
+    if let Some(ref proj) = project {
+        pin_library_modules(proj, &provenance, false);
+    }
+    let ram = match trident::ram_layout_project(&entry, &options) {
+        Ok(r) => r,
+        Err(_) => {
+            eprintln!("error: cannot compute the RAM layout");
+            process::exit(1);
+        }
+    };
+
     let (_, file) = load_and_parse(&entry);
 
     let (name, version) = match project {
@@ -277,6 +297,8 @@ pub fn prepare_artifact(
         name,
         version,
         resolved,
+        provenance,
+        ram,
     }
 }
 
@@ -333,6 +355,54 @@ pub fn open_codebase() -> trident::store::Codebase {
     }
 }
 
+/// Load the local signing key (generating it on first use), exiting on error.
+pub fn signing_key() -> trident::deploy::signing::SigningKey {
+    use trident::deploy::signing;
+    let Some(dir) = signing::keys_dir() else {
+        eprintln!("error: cannot locate keys directory (set $TRIDENT_KEYS_DIR or $HOME)");
+        process::exit(1);
+    };
+    match signing::load_or_create_key(&dir) {
+        Ok((key, created)) => {
+            if created {
+                eprintln!(
+                    "Generated signing key {} -> {}",
+                    key.fingerprint(),
+                    dir.display()
+                );
+            }
+            key
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Keys trusted to sign artifacts and definitions: the `*.pub` files in the
+/// keys directory plus `extra` (hex or `.pub` paths), exiting on error.
+pub fn trusted_key_set(extra: &[String]) -> trident::deploy::signing::TrustedKeys {
+    use trident::deploy::signing::{self, TrustedKeys};
+    let loaded = match signing::keys_dir() {
+        Some(dir) => TrustedKeys::load_dir(&dir),
+        None => Ok(TrustedKeys::default()),
+    };
+    let trusted = loaded.and_then(|mut trusted| {
+        for key in extra {
+            trusted.add(key)?;
+        }
+        Ok(trusted)
+    });
+    match trusted {
+        Ok(trusted) => trusted,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
 /// Create a registry client with health check, exiting on error.
 pub fn registry_client(url: Option<String>) -> trident::registry::RegistryClient {
     let url = url.unwrap_or_else(trident::registry::RegistryClient::default_url);
@@ -356,15 +426,54 @@ pub fn registry_url(url: Option<String>) -> String {
     url.unwrap_or_else(trident::registry::RegistryClient::default_url)
 }
 
-/// Load dependency search directories from a project's lockfile (if present).
-pub fn load_dep_dirs(project: &trident::project::Project) -> Vec<PathBuf> {
+/// Check the bundled library modules in `provenance` against the pins in
+/// the project's trident.lock, recording pins for newly used modules.
+///
+/// Exits when a pinned module changed, unless `update_std` accepts the new
+/// library contents.
+pub fn pin_library_modules(
+    project: &trident::project::Project,
+    provenance: &trident::deploy::Provenance,
+    update_std: bool,
+) {
     let lock_path = project.root_dir.join("trident.lock");
-    if !lock_path.exists() {
-        return Vec::new();
-    }
-    match trident::manifest::load_lockfile(&lock_path) {
-        Ok(lockfile) => trident::manifest::dependency_search_paths(&project.root_dir, &lockfile),
-        Err(_) => Vec::new(),
+    let mut lockfile = if lock_path.exists() {
+        match trident::manifest::load_lockfile(&lock_path) {
+            Ok(lock) => lock,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        trident::manifest::Lockfile::default()
+    };
+
+    let used = provenance.library_pins();
+    match trident::manifest::pin_libraries(&mut lockfile, &used, update_std) {
+        Ok(false) => {}
+        Ok(true) => {
+            if let Err(e) = trident::manifest::save_lockfile(&lock_path, &lockfile) {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+            if update_std {
+                eprintln!("Updated {} library pins in trident.lock", used.len());
+            }
+        }
+        Err(mismatches) => {
+            for m in &mismatches {
+                eprintln!(
+                    "error: {} module '{}' does not match trident.lock (locked {}, found {})",
+                    m.source,
+                    m.module,
+                    short_hash(&m.locked),
+                    short_hash(&m.found),
+                );
+            }
+            eprintln!("hint: run `trident build --update-std` to accept the new library contents");
+            process::exit(1);
+        }
     }
 }
 
@@ -446,6 +555,64 @@ pub fn delegate_to_warrior(warrior_bin: &Path, command: &str, extra_args: &[&str
     }
 }
 
+/// An `AnchorLedger` served by each chain's warrior.
+///
+/// `anchor lookup <digest>` prints the anchor as `key=value` lines, or
+/// nothing when the digest is not anchored; `anchor write` reads the
+/// anchor on stdin and prints the transaction id.
+pub struct WarriorLedger;
+
+impl WarriorLedger {
+    fn run(chain: &str, args: &[&str], stdin: &str) -> Result<String, String> {
+        use std::io::Write;
+        let warrior_bin =
+            find_warrior(chain).ok_or_else(|| format!("no warrior found for chain '{}'", chain))?;
+        let mut child = std::process::Command::new(&warrior_bin)
+            .arg("anchor")
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to run warrior '{}': {}", warrior_bin.display(), e))?;
+        if let Some(mut input) = child.stdin.take() {
+            input
+                .write_all(stdin.as_bytes())
+                .map_err(|e| format!("cannot write to warrior: {}", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("warrior '{}' failed: {}", warrior_bin.display(), e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "warrior '{}' exited with {}",
+                warrior_bin.display(),
+                output.status
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl trident::registry::AnchorLedger for WarriorLedger {
+    fn lookup(
+        &self,
+        chain: &str,
+        digest: &str,
+    ) -> Result<Option<trident::registry::Anchor>, String> {
+        let output = Self::run(chain, &["lookup", digest], "")?;
+        if output.trim().is_empty() {
+            return Ok(None);
+        }
+        trident::registry::Anchor::parse(&output)
+            .map(Some)
+            .ok_or_else(|| format!("'{}' returned a malformed anchor", chain))
+    }
+
+    fn write(&self, chain: &str, anchor: &trident::registry::Anchor) -> Result<String, String> {
+        Self::run(chain, &["write"], &anchor.to_lines()).map(|tx| tx.trim().to_string())
+    }
+}
+
 pub fn find_program_source(input: &Path) -> Option<PathBuf> {
     if input.is_file() && input.extension().is_some_and(|e| e == "tri") {
         return Some(input.to_path_buf());
@@ -459,6 +626,16 @@ pub fn find_program_source(input: &Path) -> Option<PathBuf> {
     None
 }
 
+/// Warn when a cost report for `target` falls back to the Triton model.
+pub fn warn_missing_cost_model(target: &str) {
+    if !trident::cost::has_cost_model(target) {
+        eprintln!(
+            "warning: no cost model for target '{}'; costs below use Triton tables",
+            target
+        );
+    }
+}
+
 /// Truncate a hash string to a short prefix for display.
 pub fn short_hash(hash: &str) -> &str {
     &hash[..hash.len().min(16)]
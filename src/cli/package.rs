@@ -3,7 +3,7 @@ use std::process;
 
 use clap::Args;
 
-use super::prepare_artifact;
+use super::{prepare_artifact, signing_key};
 
 #[derive(Args)]
 pub struct PackageArgs {
@@ -36,6 +36,12 @@ pub struct PackageArgs {
     /// Compilation profile for cfg flags (default: release)
     #[arg(long, default_value = "release")]
     pub profile: String,
+    /// Add each entry point's worst-case cost to manifest.json
+    #[arg(long)]
+    pub entry_costs: bool,
+    /// Store the TASM as objects shared with other artifacts in <output>/objects/
+    #[arg(long)]
+    pub objects: bool,
     /// Run formal audit before packaging
     #[arg(long)]
     pub audit: bool,
@@ -56,6 +62,8 @@ pub fn cmd_package(args: PackageArgs) {
         vimputer,
         state,
         profile,
+        entry_costs,
+        objects,
         audit,
         dry_run,
     } = args;
@@ -70,6 +78,7 @@ pub fn cmd_package(args: PackageArgs) {
     );
     let target = bf.target;
     let art = prepare_artifact(&input, &target, &profile, audit);
+    let metadata = check_metadata(art.project.as_ref());
 
     // Determine output base directory
     let output_base = output.unwrap_or_else(|| {
@@ -94,6 +103,9 @@ pub fn cmd_package(args: PackageArgs) {
         eprintln!("  Name:            {}", art.name);
         eprintln!("  Version:         {}", art.version);
         eprintln!("  Target:          {}", target_display);
+        if let Some(ref license) = metadata.license {
+            eprintln!("  License:         {}", license);
+        }
         eprintln!("  Program digest:  {}", program_digest.to_hex());
         eprintln!("  Padded height:   {}", art.cost.padded_height);
         eprintln!(
@@ -104,15 +116,14 @@ pub fn cmd_package(args: PackageArgs) {
         return;
     }
 
-    // Generate artifact
-    let result = match trident::deploy::generate_artifact(
+    // Generate and sign artifact
+    let mut result = match trident::deploy::generate_artifact(
         &art.name,
         &art.version,
         &art.tasm,
         &art.file,
         &art.cost,
-        &art.resolved.vm,
-        art.resolved.os.as_ref(),
+        &art.manifest_context(),
         &output_base,
     ) {
         Ok(r) => r,
@@ -122,10 +133,81 @@ pub fn cmd_package(args: PackageArgs) {
         }
     };
 
+    if !metadata.is_empty() {
+        if let Err(e) = trident::deploy::add_metadata(&mut result, &metadata) {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+
+    if entry_costs {
+        if let Err(e) = trident::deploy::add_entry_costs(&mut result, &art.cost) {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+
+    let key = signing_key();
+    if let Err(e) = trident::deploy::sign_artifact(&mut result, &key) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+
+    let stats = if objects {
+        let store = output_base.join(trident::deploy::objects::OBJECTS_DIR);
+        match trident::deploy::objects::dedup_artifact(&result, &store) {
+            Ok(stats) => Some(stats),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
     eprintln!("Packaged -> {}", result.artifact_dir.display());
-    eprintln!("  program.tasm:   {}", result.tasm_path.display());
+    match stats {
+        Some(stats) => eprintln!(
+            "  program.link:   {} objects ({} new, {} bytes shared)",
+            stats.objects, stats.written, stats.shared_bytes
+        ),
+        None => eprintln!("  program.tasm:   {}", result.tasm_path.display()),
+    }
     eprintln!("  manifest.json:  {}", result.manifest_path.display());
+    if let Some(ref path) = result.ram_image_path {
+        eprintln!("  ram.json:       {}", path.display());
+    }
     eprintln!("  digest:         {}", result.manifest.program_digest);
     eprintln!("  padded height:  {}", result.manifest.cost.padded_height);
+    eprintln!(
+        "  modules:        {}",
+        result.manifest.provenance.modules.len()
+    );
     eprintln!("  target:         {}", target_display);
+    if let Some(ref license) = metadata.license {
+        eprintln!("  license:        {}", license);
+    }
+    eprintln!("  signed by:      {}", key.fingerprint());
+}
+
+/// The project's distribution metadata. Malformed fields are refused,
+/// missing ones warned about; a lone .tri file has none.
+pub fn check_metadata(
+    project: Option<&trident::project::Project>,
+) -> trident::project::ProjectMetadata {
+    let Some(project) = project else {
+        return Default::default();
+    };
+    let (errors, warnings) = project.metadata.validate();
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if !errors.is_empty() {
+        for error in &errors {
+            eprintln!("error: {}", error);
+        }
+        process::exit(1);
+    }
+    project.metadata.clone()
 }
@@ -45,7 +45,7 @@ pub struct ProveArgs {
 }
 
 pub fn cmd_prove(args: ProveArgs) {
-    let ri = resolve_input(&args.input);
+    let ctx = resolve_input(&args.input);
     let bf = super::resolve_battlefield(
         &args.target,
         &args.engine,
@@ -89,8 +89,8 @@ pub fn cmd_prove(args: ProveArgs) {
         return;
     }
 
-    let options = super::resolve_options(&target, &args.profile, ri.project.as_ref());
-    match trident::compile_to_bundle(&ri.entry, &options) {
+    let ctx = super::configure_or_exit(ctx, &target, &args.profile, &[]);
+    match trident::compile_to_bundle(&ctx.entry, &ctx.options) {
         Ok(bundle) => {
             let op_count = bundle.assembly.lines().count();
             eprintln!("Compiled {} ({} ops)", bundle.name, op_count);
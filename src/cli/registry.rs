@@ -1,10 +1,11 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use clap::Subcommand;
 
 use super::{
-    open_codebase, registry_client, registry_url, resolve_tri_files, short_hash, try_load_and_parse,
+    open_codebase, registry_client, registry_url, resolve_tri_files, short_hash, signing_key,
+    trusted_key_set, try_load_and_parse,
 };
 
 #[derive(Subcommand)]
@@ -20,6 +21,12 @@ pub enum RegistryAction {
         /// Input .tri file or directory (adds to store first, then publishes)
         #[arg(short, long)]
         input: Option<PathBuf>,
+        /// Bind names as <namespace>/<name> (needs $TRIDENT_REGISTRY_TOKEN)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Anchor each published definition's digest on this chain, through its warrior
+        #[arg(long)]
+        anchor: Option<String>,
     },
     /// Pull a definition from a registry into local store
     Pull {
@@ -28,6 +35,12 @@ pub enum RegistryAction {
         /// Registry URL
         #[arg(long)]
         registry: Option<String>,
+        /// Trust this publisher key (hex or .pub file), besides the keys directory
+        #[arg(long = "trusted-key")]
+        trusted_keys: Vec<String>,
+        /// Accept definitions without a valid publisher signature
+        #[arg(long)]
+        allow_unsigned: bool,
     },
     /// Search a registry for definitions
     Search {
@@ -43,6 +56,14 @@ pub enum RegistryAction {
         #[arg(long)]
         tag: bool,
     },
+    /// Hide <namespace>/<name>@<hash> from name resolution (content stays pullable)
+    Yank {
+        /// Binding to yank, as <namespace>/<name>@<hash>
+        spec: String,
+        /// Registry URL
+        #[arg(long)]
+        registry: Option<String>,
+    },
 }
 
 pub fn cmd_registry(action: RegistryAction) {
@@ -51,18 +72,33 @@ pub fn cmd_registry(action: RegistryAction) {
             registry,
             tag,
             input,
-        } => cmd_registry_publish(registry, tag, input),
-        RegistryAction::Pull { name, registry } => cmd_registry_pull(name, registry),
+            namespace,
+            anchor,
+        } => cmd_registry_publish(registry, tag, input, namespace, anchor),
+        RegistryAction::Pull {
+            name,
+            registry,
+            trusted_keys,
+            allow_unsigned,
+        } => cmd_registry_pull(name, registry, trusted_keys, allow_unsigned),
         RegistryAction::Search {
             query,
             registry,
             r#type,
             tag,
         } => cmd_registry_search(query, registry, r#type, tag),
+        RegistryAction::Yank { spec, registry } => cmd_registry_yank(spec, registry),
     }
 }
 
-fn cmd_registry_publish(registry: Option<String>, tags: Vec<String>, input: Option<PathBuf>) {
+fn cmd_registry_publish(
+    registry: Option<String>,
+    tags: Vec<String>,
+    input: Option<PathBuf>,
+    namespace: Option<String>,
+    anchor: Option<String>,
+) {
+    let url = registry_url(registry.clone());
     let client = registry_client(registry);
     let mut cb = open_codebase();
 
@@ -78,8 +114,31 @@ fn cmd_registry_publish(registry: Option<String>, tags: Vec<String>, input: Opti
         }
     }
 
-    eprintln!("Publishing...");
-    match trident::registry::publish_codebase(&cb, &client, &tags) {
+    // Metadata comes from the trident.toml governing the input (or the
+    // working directory).
+    let dir = match &input {
+        Some(path) if path.is_file() => path.parent().unwrap_or(Path::new(".")),
+        Some(path) => path.as_path(),
+        None => Path::new("."),
+    };
+    let project = trident::project::Project::find(dir).map(|toml| {
+        trident::project::Project::load(&toml).unwrap_or_else(|e| {
+            eprintln!("error: {}", e.message);
+            process::exit(1);
+        })
+    });
+    let metadata = super::package::check_metadata(project.as_ref());
+
+    let key = signing_key();
+    eprintln!("Publishing (signed by {})...", key.fingerprint());
+    match trident::registry::publish_codebase(
+        &cb,
+        &client,
+        &tags,
+        Some(&key),
+        namespace.as_deref(),
+        &metadata,
+    ) {
         Ok(results) => {
             let created = results.iter().filter(|r| r.created).count();
             let existing = results.len() - created;
@@ -88,6 +147,9 @@ fn cmd_registry_publish(registry: Option<String>, tags: Vec<String>, input: Opti
                 "Published: {} new, {} existing, {} names bound",
                 created, existing, named
             );
+            if let Some(chain) = anchor {
+                anchor_results(&cb, &url, &chain, &results);
+            }
         }
         Err(e) => {
             eprintln!("error: publish failed: {}", e);
@@ -96,13 +158,53 @@ fn cmd_registry_publish(registry: Option<String>, tags: Vec<String>, input: Opti
     }
 }
 
-fn cmd_registry_pull(name: String, registry: Option<String>) {
+/// Anchor the digest of every published definition on `chain`.
+fn anchor_results(
+    cb: &trident::store::Codebase,
+    url: &str,
+    chain: &str,
+    results: &[trident::registry::PublishResult],
+) {
+    for result in results {
+        let Some(def) = trident::hash::ContentHash::from_hex(&result.hash)
+            .and_then(|hash| cb.lookup_hash(&hash))
+        else {
+            continue;
+        };
+        match trident::registry::anchor_published(
+            &super::WarriorLedger,
+            chain,
+            url,
+            result,
+            &def.source,
+        ) {
+            Ok(anchor) => eprintln!(
+                "Anchored {} on {} (tx {})",
+                short_hash(&anchor.digest),
+                chain,
+                anchor.tx
+            ),
+            Err(e) => {
+                eprintln!("error: anchoring failed: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn cmd_registry_pull(
+    name: String,
+    registry: Option<String>,
+    trusted_keys: Vec<String>,
+    allow_unsigned: bool,
+) {
     let url = registry_url(registry);
     let client = trident::registry::RegistryClient::new(&url);
     let mut cb = open_codebase();
+    let trusted = trusted_key_set(&trusted_keys);
 
     eprintln!("Pulling '{}' from {}...", name, url);
-    match trident::registry::pull_into_codebase(&mut cb, &client, &name) {
+    match trident::registry::pull_into_codebase(&mut cb, &client, &name, &trusted, allow_unsigned) {
         Ok(result) => {
             eprintln!("Pulled: {} ({})", name, short_hash(&result.hash));
             eprintln!("  Module: {}", result.module);
@@ -128,6 +230,30 @@ fn cmd_registry_pull(name: String, registry: Option<String>) {
     }
 }
 
+fn cmd_registry_yank(spec: String, registry: Option<String>) {
+    let Some((name, hash)) = spec.rsplit_once('@') else {
+        eprintln!("error: expected <namespace>/<name>@<hash>, got '{}'", spec);
+        process::exit(1);
+    };
+    if let Err(e) = trident::registry::split_name(name) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+    if trident::hash::ContentHash::from_hex(hash).is_none() {
+        eprintln!("error: '{}' is not a 64-character hex content hash", hash);
+        process::exit(1);
+    }
+
+    let client = registry_client(registry);
+    match client.yank(name, hash) {
+        Ok(()) => eprintln!("Yanked {}@{}", name, short_hash(hash)),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
 fn cmd_registry_search(query: String, registry: Option<String>, by_type: bool, by_tag: bool) {
     let url = registry_url(registry);
     let client = trident::registry::RegistryClient::new(&url);
@@ -161,6 +287,15 @@ fn cmd_registry_search(query: String, registry: Option<String>, by_type: bool, b
                     verified,
                     tags
                 );
+                if !r.verified {
+                    if let Some(verdict) = r
+                        .verification_cert
+                        .as_deref()
+                        .and_then(trident::registry::report_verdict)
+                    {
+                        println!("      {}", verdict);
+                    }
+                }
             }
             eprintln!("\n{} results", results.len());
         }
@@ -0,0 +1,192 @@
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::process;
+
+use clap::Args;
+use trident::runtime::input::parse_words;
+use trident::{ReplOutcome, ReplSession};
+
+use super::{configure_or_exit, resolve_context};
+
+#[derive(Args)]
+pub struct ReplArgs {
+    /// Project to load: .tri file or directory with trident.toml
+    pub input: Option<PathBuf>,
+    /// Target VM (only triton is supported)
+    #[arg(long, default_value = "triton")]
+    pub target: String,
+    /// Compilation profile (debug or release)
+    #[arg(long, default_value = "debug")]
+    pub profile: String,
+}
+
+const HELP: &str = "\
+Enter an expression to evaluate it, or a `use` line, item (fn, struct,
+const), or `let` binding to add it to the session. Input continues on
+the next line while brackets are open.
+
+Commands:
+  :type EXPR           show the type of an expression
+  :input V,...         public input every evaluation reads
+  :secret V,...        secret input every evaluation divines
+  :show                print the session's definitions
+  :reset               forget all definitions
+  :help                show this help
+  :quit                exit";
+
+pub fn cmd_repl(args: ReplArgs) {
+    let (mut session, loaded) = match &args.input {
+        Some(input) => {
+            let ctx = resolve_context(input, &args.target, &args.profile, &[]);
+            check_target(&ctx.options);
+            let session =
+                ReplSession::for_project(&ctx.entry, ctx.options).unwrap_or_else(|errors| {
+                    print_errors(&errors);
+                    process::exit(1);
+                });
+            (session, Some(ctx.entry))
+        }
+        None => {
+            let ctx = trident::project::BuildContext {
+                entry: PathBuf::new(),
+                project: None,
+                options: trident::CompileOptions::default(),
+                warnings: Vec::new(),
+            };
+            let ctx = configure_or_exit(ctx, &args.target, &args.profile, &[]);
+            check_target(&ctx.options);
+            (ReplSession::new(&std::env::temp_dir(), ctx.options), None)
+        }
+    };
+
+    match &loaded {
+        Some(entry) => println!(
+            "Trident REPL in the context of {}. Type `:help` for help.",
+            entry.display()
+        ),
+        None => println!("Trident REPL. Type `:help` for help."),
+    }
+    for line in &session.uses {
+        println!("  {}", line);
+    }
+
+    let stdin = std::io::stdin();
+    let mut pending = String::new();
+    loop {
+        print!("{}", if pending.is_empty() { ">> " } else { ".. " });
+        let _ = std::io::stdout().flush();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            println!();
+            break;
+        }
+        if pending.is_empty() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let Some(command) = trimmed.strip_prefix(':') {
+                if !run_command(&mut session, command) {
+                    break;
+                }
+                continue;
+            }
+        }
+        pending.push_str(&line);
+        if open_brackets(&pending) > 0 {
+            continue;
+        }
+        match session.eval(&pending) {
+            Ok(ReplOutcome::Defined(what)) => println!("defined {}", what),
+            Ok(ReplOutcome::Evaluated(result)) => {
+                if !result.output.is_empty() {
+                    println!("output: {:?}", result.output);
+                }
+                match result.value {
+                    Some(value) => println!("{}: {}", value, result.ty),
+                    None => println!("{}", result.ty),
+                }
+                println!("  cost: {} | {} cycles", result.cost, result.cycles);
+            }
+            Err(errors) => print_errors(&errors),
+        }
+        pending.clear();
+    }
+}
+
+/// Run a `:command`. Returns false to quit.
+fn run_command(session: &mut ReplSession, command: &str) -> bool {
+    let (name, rest) = command
+        .split_once(char::is_whitespace)
+        .unwrap_or((command, ""));
+    let rest = rest.trim();
+    match name {
+        "q" | "quit" => return false,
+        "h" | "help" => println!("{}", HELP),
+        "t" | "type" => match session.type_of(rest) {
+            Ok(ty) => println!("{}", ty.display()),
+            Err(errors) => print_errors(&errors),
+        },
+        "input" | "secret" => match parse_words(rest) {
+            Ok(values) => {
+                println!("{} value(s) of {} input.", values.len(), name);
+                if name == "input" {
+                    session.input.public = values;
+                } else {
+                    session.input.secret = values;
+                }
+            }
+            Err(e) => println!("error: {}", e),
+        },
+        "show" => {
+            for line in session
+                .uses
+                .iter()
+                .chain(&session.items)
+                .chain(&session.bindings)
+            {
+                println!("{}", line);
+            }
+        }
+        "reset" => {
+            session.reset();
+            println!("Session cleared.");
+        }
+        _ => println!("unknown command ':{}' (type `:help`)", name),
+    }
+    true
+}
+
+fn check_target(options: &trident::CompileOptions) {
+    if options.target_config.name != "triton" {
+        eprintln!(
+            "error: trident repl supports Triton VM (TASM) only, not '{}'",
+            options.target_config.name
+        );
+        process::exit(1);
+    }
+}
+
+/// Diagnostics point into the throwaway program, so only their text is
+/// shown.
+fn print_errors(errors: &[trident::diagnostic::Diagnostic]) {
+    for error in errors {
+        println!("error: {}", error.message);
+        if let Some(help) = &error.help {
+            println!("  help: {}", help);
+        }
+    }
+}
+
+/// Unclosed `(`, `[` and `{` in `text`, ignoring comments.
+fn open_brackets(text: &str) -> i32 {
+    text.lines()
+        .map(|line| line.split("//").next().unwrap_or(""))
+        .flat_map(str::chars)
+        .map(|c| match c {
+            '(' | '[' | '{' => 1,
+            ')' | ']' | '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
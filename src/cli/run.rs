@@ -39,10 +39,18 @@ pub struct RunArgs {
     /// Secret/divine input values (comma-separated field elements)
     #[arg(long, value_delimiter = ',')]
     pub secret: Option<Vec<u64>>,
+    /// Witness file from `trident encode-input -o` (public and secret input)
+    #[arg(long, conflicts_with_all = ["input_values", "secret"])]
+    pub witness: Option<PathBuf>,
 }
 
-pub fn cmd_run(args: RunArgs) {
-    let ri = resolve_input(&args.input);
+pub fn cmd_run(mut args: RunArgs) {
+    if let Some(path) = &args.witness {
+        let input = super::encode_input::read_witness(path);
+        args.input_values = Some(input.public);
+        args.secret = Some(input.secret);
+    }
+    let ctx = resolve_input(&args.input);
     let bf = super::resolve_battlefield(
         &args.target,
         &args.engine,
@@ -82,8 +90,8 @@ pub fn cmd_run(args: RunArgs) {
         return;
     }
 
-    let options = super::resolve_options(&target, &args.profile, ri.project.as_ref());
-    match trident::compile_to_bundle(&ri.entry, &options) {
+    let ctx = super::configure_or_exit(ctx, &target, &args.profile, &[]);
+    match trident::compile_to_bundle(&ctx.entry, &ctx.options) {
         Ok(bundle) => {
             let op_count = bundle.assembly.lines().count();
             eprintln!("Compiled {} ({} ops)", bundle.name, op_count);
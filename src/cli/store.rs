@@ -66,11 +66,11 @@ fn cmd_store_add(input: PathBuf) {
     let mut total_unchanged = 0usize;
 
     for file_path in &files {
-        let file = match try_load_and_parse(file_path) {
-            Some((_, f)) => f,
+        let (source, file) = match try_load_and_parse(file_path) {
+            Some(parsed) => parsed,
             None => continue,
         };
-        let result = cb.add_file(&file);
+        let result = cb.add_source(&file, &source);
         total_added += result.added;
         total_updated += result.updated;
         total_unchanged += result.unchanged;
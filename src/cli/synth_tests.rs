@@ -0,0 +1,75 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+use clap::Args;
+
+use super::load_and_parse;
+
+#[derive(Args)]
+pub struct SynthTestsArgs {
+    /// Input .tri file
+    pub input: PathBuf,
+    /// Append the tests to the input file instead of printing them
+    #[arg(long, conflicts_with = "output")]
+    pub write: bool,
+    /// Output file (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+pub fn cmd_synth_tests(args: SynthTestsArgs) {
+    let SynthTestsArgs {
+        input,
+        write,
+        output,
+    } = args;
+    if !input.extension().is_some_and(|e| e == "tri") {
+        eprintln!("error: input must be a .tri file");
+        process::exit(1);
+    }
+
+    let (source, file) = load_and_parse(&input);
+    let specs = trident::synthesize::synthesize_specs(&file);
+    let tests = trident::synthesize::generate_tests(&file, &specs);
+    let count = tests.matches("#[test]").count();
+    if count == 0 {
+        eprintln!("No tests synthesized for {}", input.display());
+        return;
+    }
+
+    if write {
+        let separator = if source.ends_with("\n\n") {
+            ""
+        } else if source.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        };
+        let appended = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&input)
+            .and_then(|mut f| write!(f, "{}{}", separator, tests));
+        if let Err(e) = appended {
+            eprintln!("error: cannot write '{}': {}", input.display(), e);
+            process::exit(1);
+        }
+        eprintln!(
+            "Appended {} synthesized test(s) to {}; review them before committing",
+            count,
+            input.display()
+        );
+    } else if let Some(out_path) = output {
+        if let Err(e) = std::fs::write(&out_path, &tests) {
+            eprintln!("error: cannot write '{}': {}", out_path.display(), e);
+            process::exit(1);
+        }
+        eprintln!(
+            "Generated {} synthesized test(s) -> {}",
+            count,
+            out_path.display()
+        );
+    } else {
+        print!("{}", tests);
+    }
+}
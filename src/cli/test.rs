@@ -1,14 +1,36 @@
 use std::path::PathBuf;
 use std::process;
 
-use clap::Args;
+use clap::{Args, ValueEnum};
 
-use super::{resolve_input, resolve_options};
+use super::build::write_artifact;
+use super::resolve_context;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Junit,
+}
 
 #[derive(Args)]
 pub struct TestArgs {
-    /// Input .tri file or directory with trident.toml
+    /// Input .tri file or directory with trident.toml (with --spec: the spec directory)
     pub input: PathBuf,
+    /// Run only tests whose name contains FILTER (`*` and `?` glob)
+    pub filter: Option<String>,
+    /// Require FILTER to match the whole test name
+    #[arg(long, requires = "filter")]
+    pub exact: bool,
+    /// Run tests on N threads (default: one per CPU)
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+    /// Cycle budget per test unless it sets #[test(max_cycles = N)]
+    #[arg(long)]
+    pub max_cycles: Option<u64>,
+    /// Witness file from `trident encode-input -o`; every test reads from it
+    #[arg(long)]
+    pub witness: Option<PathBuf>,
     /// Target VM (default: triton)
     #[arg(long, default_value = "triton")]
     pub target: String,
@@ -27,31 +49,81 @@ pub struct TestArgs {
     /// Compilation profile for cfg flags (debug or release)
     #[arg(long, default_value = "debug")]
     pub profile: String,
+    /// Report format: human summary, JSON, or JUnit XML
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: ReportFormat,
+    /// Write the report to a file instead of the terminal
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Run the language conformance specs under INPUT (e.g. tests/spec)
+    #[arg(long, conflicts_with = "witness")]
+    pub spec: bool,
 }
 
 pub fn cmd_test(args: TestArgs) {
     let TestArgs {
         input,
+        filter,
+        exact,
+        jobs,
+        max_cycles,
+        witness,
         target,
         engine,
         terrain,
         network,
         union_flag,
         profile,
+        format,
+        output,
+        spec,
     } = args;
     let bf = super::resolve_battlefield_compile(&target, &engine, &terrain, &network, &union_flag);
     let target = bf.target;
-    let ri = resolve_input(&input);
-
-    let options = resolve_options(&target, &profile, ri.project.as_ref());
-    let result = trident::run_tests(&ri.entry, &options);
-
-    match result {
-        Ok(report) => {
-            eprintln!("{}", report);
-        }
-        Err(_) => {
+    let ctx = if spec {
+        // Specs are standalone programs; only the target and profile apply.
+        let ctx = trident::project::BuildContext {
+            entry: input.clone(),
+            project: None,
+            options: trident::CompileOptions::default(),
+            warnings: Vec::new(),
+        };
+        super::configure_or_exit(ctx, &target, &profile, &[])
+    } else {
+        resolve_context(&input, &target, &profile, &[])
+    };
+    let selection = trident::TestSelection {
+        filter,
+        exact,
+        jobs,
+        max_cycles,
+        input: witness
+            .as_deref()
+            .map(super::encode_input::read_witness)
+            .unwrap_or_default(),
+    };
+    let report = if spec {
+        trident::run_spec_suite(&input, &ctx.options, &selection).unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        })
+    } else {
+        let Ok(report) = trident::run_tests_with(&ctx.entry, &ctx.options, &selection) else {
             process::exit(1);
-        }
+        };
+        report
+    };
+    let text = match format {
+        ReportFormat::Text => report.to_text(),
+        ReportFormat::Json => report.to_json(),
+        ReportFormat::Junit => report.to_junit(),
+    };
+    match (&output, format) {
+        (Some(path), _) => write_artifact(path, &text),
+        (None, ReportFormat::Text) => eprintln!("{}", text),
+        (None, _) => print!("{}", text),
+    }
+    if report.failed() > 0 {
+        process::exit(1);
     }
 }
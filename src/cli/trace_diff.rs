@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::Args;
+use trident::runtime::debug::{diff_traces, Debugger, Snapshot, Stop, DEFAULT_CYCLE_LIMIT};
+
+#[derive(Args)]
+pub struct TraceDiffArgs {
+    /// Reference TASM file (known-good compilation)
+    pub old: PathBuf,
+    /// TASM file to compare against the reference
+    pub new: PathBuf,
+    /// Public input values (comma-separated field elements)
+    #[arg(long, visible_alias = "input", value_delimiter = ',')]
+    pub input_values: Option<Vec<u64>>,
+    /// Secret/divine input values (comma-separated field elements)
+    #[arg(long, value_delimiter = ',')]
+    pub secret: Option<Vec<u64>>,
+    /// Maximum number of instructions to compare
+    #[arg(long, default_value_t = DEFAULT_CYCLE_LIMIT)]
+    pub max_cycles: u64,
+}
+
+pub fn cmd_trace_diff(args: TraceDiffArgs) {
+    let input = trident::runtime::ProgramInput {
+        public: args.input_values.unwrap_or_default(),
+        secret: args.secret.unwrap_or_default(),
+        digests: Vec::new(),
+    };
+    let mut old = Debugger::new(&read_tasm(&args.old), &input);
+    let mut new = Debugger::new(&read_tasm(&args.new), &input);
+    let diff = diff_traces(&mut old, &mut new, args.max_cycles);
+
+    let Some(divergence) = diff.divergence else {
+        match diff.old_stop {
+            Stop::CycleLimit => eprintln!(
+                "No divergence in the first {} instructions (cycle limit reached).",
+                args.max_cycles
+            ),
+            _ => eprintln!(
+                "Traces identical: {} instructions, {}. Output: {:?}",
+                old.cycles(),
+                describe_stop(&diff.old_stop),
+                old.output()
+            ),
+        }
+        return;
+    };
+
+    eprintln!(
+        "Traces diverge after {} identical instructions.",
+        divergence.cycle
+    );
+    eprintln!();
+    print_snapshot("old", &args.old, &divergence.old);
+    eprintln!();
+    print_snapshot("new", &args.new, &divergence.new);
+    eprintln!();
+    eprintln!(
+        "old: {} after {} cycles. Output: {:?}",
+        describe_stop(&diff.old_stop),
+        old.cycles(),
+        old.output()
+    );
+    eprintln!(
+        "new: {} after {} cycles. Output: {:?}",
+        describe_stop(&diff.new_stop),
+        new.cycles(),
+        new.output()
+    );
+    process::exit(1);
+}
+
+fn read_tasm(path: &Path) -> String {
+    match std::fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("error: cannot read '{}': {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+}
+
+fn print_snapshot(side: &str, path: &Path, snapshot: &Snapshot) {
+    match &snapshot.instruction {
+        Some(instr) => eprintln!(
+            "{} ({}:{}): {}",
+            side,
+            path.display(),
+            instr.line + 1,
+            instr.text
+        ),
+        None => eprintln!("{} ({}): <end of program>", side, path.display()),
+    }
+    if let Some(function) = &snapshot.function {
+        eprintln!("  in     {}", function);
+    }
+    let stack: Vec<u64> = snapshot.stack.iter().rev().copied().collect();
+    eprintln!("  stack  {:?} (st0 first)", stack);
+    eprintln!("  output {:?}", snapshot.output);
+}
+
+fn describe_stop(stop: &Stop) -> String {
+    match stop {
+        Stop::Step | Stop::Breakpoint(_) => "paused".to_string(),
+        Stop::Halted => "halted".to_string(),
+        Stop::Error(msg) => format!("failed ({})", msg),
+        Stop::NeedsInput { count, .. } => format!("ran out of input ({} more needed)", count),
+        Stop::CycleLimit => "hit the cycle limit".to_string(),
+    }
+}
@@ -1,9 +1,11 @@
 use std::path::Path;
 use std::process;
+use std::sync::Arc;
 
 use std::cell::RefCell;
 
 use clap::{Args, Subcommand};
+use trident::diagnostic::StderrSink;
 
 thread_local! {
     static BEAM_DIAGNOSTIC: RefCell<Option<String>> = RefCell::new(None);
@@ -69,9 +71,7 @@ pub fn cmd_train(args: TrainArgs) {
     eprintln!("trident train");
     eprintln!("  compiling corpus...");
 
-    let _guard = trident::diagnostic::suppress_warnings();
     let compiled = compile_corpus(&corpus);
-    drop(_guard);
     let total_baseline: u64 = compiled.iter().map(|c| c.baseline_cost).sum();
 
     let config = NeuralCompilerConfig::new();
@@ -915,7 +915,8 @@ fn walkdir_recursive(dir: &Path, result: &mut Vec<std::path::PathBuf>, depth: us
 fn compile_corpus(files: &[std::path::PathBuf]) -> Vec<CompiledFile> {
     use trident::neural::data::pairs::split_tir_by_function;
 
-    let options = super::resolve_options("triton", "debug", None);
+    let options =
+        trident::CompileOptions::default().with_sink(Arc::new(StderrSink::default().errors_only()));
     let mut compiled = Vec::new();
 
     for file in files {
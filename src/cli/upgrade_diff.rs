@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+use std::process;
+
+use clap::Args;
+
+use super::{open_codebase, prepare_artifact};
+
+#[derive(Args)]
+pub struct UpgradeDiffArgs {
+    /// Input .tri file or directory with trident.toml
+    pub input: PathBuf,
+    /// Deployed artifact: a .deploy/ directory, or a program digest
+    /// (prefix) of one next to the project
+    pub deployed: String,
+    /// Target VM or OS (default: triton)
+    #[arg(long, default_value = "triton")]
+    pub target: String,
+    /// Compilation profile for cfg flags (default: release)
+    #[arg(long, default_value = "release")]
+    pub profile: String,
+}
+
+pub fn cmd_upgrade_diff(args: UpgradeDiffArgs) {
+    let UpgradeDiffArgs {
+        input,
+        deployed,
+        target,
+        profile,
+    } = args;
+    let art = prepare_artifact(&input, &target, &profile, false);
+
+    let artifact_dir = if PathBuf::from(&deployed).is_dir() {
+        PathBuf::from(&deployed)
+    } else {
+        let mut roots: Vec<PathBuf> = art.project.iter().map(|p| p.root_dir.clone()).collect();
+        roots.extend(art.entry.parent().map(PathBuf::from));
+        roots.extend(std::env::current_dir().ok());
+        trident::deploy::upgrade::find_artifact(&roots, &deployed).unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        })
+    };
+    let old = trident::deploy::upgrade::DeployedManifest::read(&artifact_dir).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    });
+
+    let mut manifest = trident::deploy::build_manifest(
+        &art.name,
+        &art.version,
+        &art.tasm,
+        &art.file,
+        &art.cost,
+        &art.manifest_context(),
+    );
+    manifest.entry_costs = Some(trident::deploy::entry_costs(&art.cost));
+
+    let cb = open_codebase();
+    let report = trident::deploy::upgrade::upgrade_diff(&old, &manifest, &art.file, |hash| {
+        cb.lookup_hash(hash).map(|def| def.source.clone())
+    });
+    print!("{}", report.format_report());
+}
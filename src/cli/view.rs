@@ -3,11 +3,12 @@ use std::process;
 
 use clap::Args;
 
-use super::{load_and_parse, resolve_input};
+use super::resolve_input;
 
 #[derive(Args)]
 pub struct ViewArgs {
-    /// Function name or content hash prefix
+    /// Item name (`name`, `module.name`), generic instance (`first<3>`),
+    /// or function content hash prefix
     pub name: String,
     /// Input .tri file or directory with trident.toml
     #[arg(short, long)]
@@ -21,50 +22,45 @@ pub fn cmd_view(args: ViewArgs) {
     let ViewArgs { name, input, full } = args;
     let input =
         input.unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
-    let ri = resolve_input(&input);
-    let (_, file) = load_and_parse(&ri.entry);
-    let filename = ri.entry.to_string_lossy().to_string();
-
-    let fn_hashes = trident::hash::hash_file(&file);
-
-    // Try to find the function: by hash prefix or by name
-    let (fn_name, func) = if trident::ast::navigate::looks_like_hash(&name) {
-        if let Some((found_name, found_func)) =
-            trident::ast::navigate::find_function_by_hash(&file, &fn_hashes, &name)
-        {
-            (found_name, found_func.clone())
-        } else if let Some(found_func) = trident::ast::navigate::find_function(&file, &name) {
-            (name.clone(), found_func.clone())
-        } else {
-            eprintln!("error: no function matching '{}' found", name);
-            process::exit(1);
-        }
-    } else if let Some(found_func) = trident::ast::navigate::find_function(&file, &name) {
-        (name.clone(), found_func.clone())
-    } else {
-        eprintln!("error: function '{}' not found in '{}'", name, filename);
-        eprintln!("\nAvailable functions:");
-        for item in &file.items {
-            if let trident::ast::Item::Fn(f) = &item.node {
-                if let Some(hash) = fn_hashes.get(&f.name.node) {
-                    eprintln!("  {}  {}", hash, f.name.node);
-                }
+    let ctx = resolve_input(&input);
+    let compiler = trident::Compiler::new(&ctx.entry).with_options(ctx.options);
+
+    let Ok(found) = compiler.view(&name) else {
+        process::exit(1);
+    };
+    let Some(item) = found else {
+        eprintln!(
+            "error: no item matching '{}' found in '{}'",
+            name,
+            ctx.entry.display()
+        );
+        eprintln!("\nAvailable items:");
+        for (item, hash) in compiler.items().unwrap_or_default() {
+            match hash {
+                Some(hash) => eprintln!("  {}  {}", hash, item),
+                None => eprintln!("  {}", item),
             }
         }
         process::exit(1);
     };
 
-    let formatted = trident::ast::display::format_function(&func);
-
-    if let Some(hash) = fn_hashes.get(&fn_name) {
+    eprintln!("Module: {}", item.module);
+    if let Some(hash) = item.hash {
         if full {
             eprintln!("Hash: {}", hash.to_hex());
         } else {
             eprintln!("Hash: {}", hash);
         }
     }
+    if let Some(cost) = &item.cost {
+        let short_names: Vec<&str> = item.short_names.iter().map(String::as_str).collect();
+        eprintln!("Cost: {}", cost.format_annotation(&short_names));
+    }
 
-    print!("{}", formatted);
+    if let Some(signature) = &item.instance_signature {
+        println!("{}\n", signature);
+    }
+    print!("{}", item.source);
 }
 
 #[cfg(test)]
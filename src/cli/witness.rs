@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+use std::process;
+
+use clap::{Subcommand, ValueEnum};
+
+use super::build::write_artifact;
+use super::{load_and_parse, resolve_input};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum RecipeFormat {
+    Rust,
+    Json,
+}
+
+#[derive(Subcommand)]
+pub enum WitnessAction {
+    /// Derive a witness generator from the program's constraints
+    Synth {
+        /// Input .tri file or directory with trident.toml
+        input: PathBuf,
+        /// Emit a Rust function or a JSON recipe
+        #[arg(long, value_enum, default_value = "rust")]
+        format: RecipeFormat,
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+pub fn cmd_witness(action: WitnessAction) {
+    match action {
+        WitnessAction::Synth {
+            input,
+            format,
+            output,
+        } => cmd_witness_synth(input, format, output),
+    }
+}
+
+fn cmd_witness_synth(input: PathBuf, format: RecipeFormat, output: Option<PathBuf>) {
+    let ctx = resolve_input(&input);
+    let (_, file) = load_and_parse(&ctx.entry);
+    let system = trident::sym::analyze(&file);
+
+    let recipe = match trident::witness::synthesize_witness(&file.name.node, &system) {
+        Ok(recipe) => recipe,
+        Err(undetermined) => {
+            eprintln!("error: cannot synthesize a witness generator");
+            for name in undetermined {
+                eprintln!("  {} is not determined by the public inputs", name);
+            }
+            process::exit(1);
+        }
+    };
+
+    let code = match format {
+        RecipeFormat::Rust => recipe.to_rust(),
+        RecipeFormat::Json => recipe.to_json(),
+    };
+    match output {
+        Some(path) => {
+            write_artifact(&path, &code);
+            eprintln!(
+                "Witness generator ({} divine input(s)) written to {}",
+                recipe.steps.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", code),
+    }
+}
@@ -1,10 +1,11 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 
 use crate::config::target::parse_string_array;
 use crate::diagnostic::Diagnostic;
 use crate::manifest::Manifest;
 use crate::span::Span;
+use crate::tir::LabelScheme;
 
 /// Maximum allowed length for a project name.
 const MAX_PROJECT_NAME_LEN: usize = 128;
@@ -46,9 +47,205 @@ pub struct Project {
     pub target: Option<String>,
     /// Custom profile definitions: profile_name → list of cfg flags.
     /// E.g. `[targets.debug]` with `flags = ["debug", "verbose"]`.
+    /// A profile with `inherits` already holds its parent's flags.
     pub targets: BTreeMap<String, Vec<String>>,
+    /// Extra cfg flags for one module or dependency and everything under
+    /// it: `[cfg.bignum]` with `flags = ["fast_inv"]`.
+    pub module_cfg: BTreeMap<String, Vec<String>>,
     /// Parsed [dependencies] section.
     pub dependencies: Manifest,
+    /// Resolve dependencies from `vendor/` before the `.trident/deps/` cache.
+    pub vendor: bool,
+    /// Feature definitions: feature_name → features, optional dependencies,
+    /// modules, or cfg flags it enables. E.g. `secp = ["std.crypto.secp256k1"]`.
+    pub features: BTreeMap<String, Vec<String>>,
+    /// Monomorphization budget from `[limits]`.
+    pub limits: MonoLimits,
+    /// Label mangling of the linked program (`labels = "flat"` keeps the
+    /// scheme of earlier releases).
+    pub labels: LabelScheme,
+    /// Distribution metadata: license, authors, description, repository.
+    pub metadata: ProjectMetadata,
+}
+
+/// Who may use a package and where it comes from (`[project]` keys
+/// `license`, `authors`, `description`, `repository`). Carried into
+/// `manifest.json`, registry publishes, and `trident doc`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProjectMetadata {
+    /// SPDX license expression, e.g. `MIT OR Apache-2.0`.
+    pub license: Option<String>,
+    pub authors: Vec<String>,
+    pub description: Option<String>,
+    /// Source repository URL.
+    pub repository: Option<String>,
+}
+
+impl ProjectMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the metadata before a package is distributed.
+    ///
+    /// Returns `(errors, warnings)`: malformed values are errors, missing
+    /// `license` or `description` only warnings, so local builds of
+    /// unpublished programs still package.
+    pub fn validate(&self) -> (Vec<String>, Vec<String>) {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        match &self.license {
+            Some(license) => {
+                if let Err(reason) = validate_license(license) {
+                    errors.push(format!("invalid license '{}': {}", license, reason));
+                }
+            }
+            None => warnings.push("trident.toml has no 'license'".to_string()),
+        }
+        match &self.description {
+            Some(d) if d.trim().is_empty() => {
+                errors.push("'description' must not be empty".to_string())
+            }
+            Some(_) => {}
+            None => warnings.push("trident.toml has no 'description'".to_string()),
+        }
+        if let Some(repo) = &self.repository {
+            let scheme = ["https://", "http://", "git@", "ssh://"]
+                .iter()
+                .find(|s| repo.starts_with(**s));
+            if scheme.is_none() || repo.contains(char::is_whitespace) {
+                errors.push(format!(
+                    "invalid repository '{}': expected an https://, ssh:// or git@ URL",
+                    repo
+                ));
+            }
+        }
+        if self.authors.iter().any(|a| a.trim().is_empty()) {
+            errors.push("'authors' must not contain empty names".to_string());
+        }
+        (errors, warnings)
+    }
+}
+
+/// Check the shape of an SPDX license expression: identifiers (letters,
+/// digits, `.`, `-`, optional trailing `+`, `LicenseRef-…`) joined by
+/// `AND`, `OR` and `WITH`, with balanced parentheses. Identifiers are not
+/// checked against the SPDX list.
+pub fn validate_license(expr: &str) -> Result<(), String> {
+    let spaced = expr.replace('(', " ( ").replace(')', " ) ");
+    let mut depth = 0usize;
+    let mut expect_id = true;
+    for token in spaced.split_whitespace() {
+        match token {
+            "(" if expect_id => depth += 1,
+            ")" if !expect_id && depth > 0 => depth -= 1,
+            "AND" | "OR" | "WITH" if !expect_id => expect_id = true,
+            id if expect_id => {
+                let body = id.strip_suffix('+').unwrap_or(id);
+                if body.is_empty()
+                    || !body
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+                {
+                    return Err(format!("'{}' is not an SPDX license identifier", id));
+                }
+                expect_id = false;
+            }
+            other => return Err(format!("unexpected '{}'", other)),
+        }
+    }
+    if expect_id {
+        return Err("expected a license identifier".to_string());
+    }
+    if depth > 0 {
+        return Err("unbalanced parentheses".to_string());
+    }
+    Ok(())
+}
+
+/// Per-program budget for generic instantiation (`[limits]` in trident.toml).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MonoLimits {
+    /// Maximum distinct generic instances after deduplication.
+    pub mono_instances: usize,
+    /// Maximum instructions emitted for generic instances, in total.
+    pub mono_instructions: usize,
+}
+
+impl Default for MonoLimits {
+    fn default() -> Self {
+        Self {
+            mono_instances: 256,
+            mono_instructions: 100_000,
+        }
+    }
+}
+
+/// The outcome of enabling a set of features (see `Project::resolve_features`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeatureSet {
+    /// Enabled features, including ones enabled by other features.
+    pub features: BTreeSet<String>,
+    /// Cfg flags to add: every enabled feature name plus its plain flags.
+    pub cfg_flags: BTreeSet<String>,
+    /// Optional dependencies the enabled features pull in.
+    pub dependencies: BTreeSet<String>,
+    /// Feature-gated modules left disabled: module → a feature enabling it.
+    pub gated_modules: BTreeMap<String, String>,
+}
+
+/// One `[targets.NAME]` section as written, before inheritance.
+#[derive(Default)]
+struct ProfileSection {
+    flags: Vec<String>,
+    inherits: Option<String>,
+    remove: Vec<String>,
+}
+
+/// The cfg flags of profile `name`. A profile that `inherits` another
+/// starts from the parent's flags minus the parent's own name (release
+/// built on debug is not `debug`), adds its `flags`, and drops `remove`.
+fn resolve_profile(
+    name: &str,
+    profiles: &BTreeMap<String, ProfileSection>,
+    stack: &mut Vec<String>,
+) -> Result<Vec<String>, String> {
+    if stack.iter().any(|p| p == name) {
+        stack.push(name.to_string());
+        return Err(format!("profile inheritance cycle: {}", stack.join(" -> ")));
+    }
+    let section = &profiles[name];
+    let mut flags = match &section.inherits {
+        Some(parent) => {
+            if !profiles.contains_key(parent) {
+                return Err(format!(
+                    "[targets.{}] inherits unknown profile '{}'",
+                    name, parent
+                ));
+            }
+            stack.push(name.to_string());
+            let mut inherited = resolve_profile(parent, profiles, stack)?;
+            stack.pop();
+            inherited.retain(|flag| flag != parent);
+            inherited
+        }
+        None => Vec::new(),
+    };
+    for flag in &section.remove {
+        if !flags.contains(flag) {
+            return Err(format!(
+                "unknown flag '{}' in [targets.{}] remove (not set by the inherited profile)",
+                flag, name
+            ));
+        }
+    }
+    flags.retain(|flag| !section.remove.contains(flag));
+    for flag in &section.flags {
+        if !flags.contains(flag) {
+            flags.push(flag.clone());
+        }
+    }
+    Ok(flags)
 }
 
 impl Project {
@@ -68,7 +265,13 @@ impl Project {
         let mut version = String::new();
         let mut entry = String::new();
         let mut vm_target: Option<String> = None;
-        let mut targets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut vendor = false;
+        let mut labels = LabelScheme::default();
+        let mut metadata = ProjectMetadata::default();
+        let mut features: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut profiles: BTreeMap<String, ProfileSection> = BTreeMap::new();
+        let mut module_cfg: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut limits = MonoLimits::default();
         let mut current_section = String::new();
 
         for line in content.lines() {
@@ -86,19 +289,72 @@ impl Project {
                 let value = value.trim();
 
                 if current_section == "project" {
+                    if key == "authors" {
+                        metadata.authors = parse_string_array(value);
+                        continue;
+                    }
                     let value = value.trim_matches('"');
                     match key {
                         "name" => name = value.to_string(),
                         "version" => version = value.to_string(),
                         "entry" => entry = value.to_string(),
                         "target" => vm_target = Some(value.to_string()),
+                        "vendor" => vendor = value == "true",
+                        "license" => metadata.license = Some(value.to_string()),
+                        "description" => metadata.description = Some(value.to_string()),
+                        "repository" => metadata.repository = Some(value.to_string()),
+                        "labels" => {
+                            labels = LabelScheme::from_name(value).ok_or_else(|| {
+                                Diagnostic::error(
+                                    format!(
+                                        "unknown label scheme '{}' (expected qualified or flat)",
+                                        value
+                                    ),
+                                    Span::dummy(),
+                                )
+                            })?
+                        }
                         _ => {}
                     }
                 } else if let Some(target_name) = current_section.strip_prefix("targets.") {
-                    if key == "flags" {
+                    let profile = profiles.entry(target_name.to_string()).or_default();
+                    match key {
                         // Parse array: ["flag1", "flag2"]
-                        let flags = parse_string_array(value);
-                        targets.insert(target_name.to_string(), flags);
+                        "flags" => profile.flags = parse_string_array(value),
+                        "inherits" => profile.inherits = Some(value.trim_matches('"').to_string()),
+                        "remove" => profile.remove = parse_string_array(value),
+                        _ => {
+                            return Err(Diagnostic::error(
+                                format!(
+                                    "unknown key '{}' in [targets.{}] (expected flags, inherits, or remove)",
+                                    key, target_name
+                                ),
+                                Span::dummy(),
+                            ))
+                        }
+                    }
+                } else if let Some(module) = current_section.strip_prefix("cfg.") {
+                    let module = module.trim_matches('"');
+                    if key != "flags" || module.is_empty() {
+                        return Err(Diagnostic::error(
+                            format!("[cfg.{}] takes a single key: flags", module),
+                            Span::dummy(),
+                        ));
+                    }
+                    module_cfg.insert(module.to_string(), parse_string_array(value));
+                } else if current_section == "features" {
+                    features.insert(key.to_string(), parse_string_array(value));
+                } else if current_section == "limits" {
+                    let parsed = value.replace('_', "").parse::<usize>().map_err(|_| {
+                        Diagnostic::error(
+                            format!("[limits] {} must be a non-negative integer", key),
+                            Span::dummy(),
+                        )
+                    })?;
+                    match key {
+                        "mono_instances" => limits.mono_instances = parsed,
+                        "mono_instructions" => limits.mono_instructions = parsed,
+                        _ => {}
                     }
                 }
             }
@@ -123,6 +379,12 @@ impl Project {
         }
 
         let dependencies = crate::manifest::parse_dependencies(&content);
+        let mut targets = BTreeMap::new();
+        for profile in profiles.keys() {
+            let flags = resolve_profile(profile, &profiles, &mut Vec::new())
+                .map_err(|message| Diagnostic::error(message, Span::dummy()))?;
+            targets.insert(profile.clone(), flags);
+        }
 
         Ok(Project {
             name,
@@ -131,10 +393,81 @@ impl Project {
             root_dir,
             target: vm_target,
             targets,
+            module_cfg,
             dependencies,
+            vendor,
+            features,
+            limits,
+            labels,
+            metadata,
         })
     }
 
+    /// Enable the `requested` features, plus `default` when it is defined.
+    ///
+    /// Each entry of a feature names another feature, an optional
+    /// dependency, a module (dotted name, e.g. `std.crypto.secp256k1`), or
+    /// else a cfg flag. Modules listed under any feature are gated: using
+    /// them requires one of their features to be enabled.
+    pub fn resolve_features(&self, requested: &[String]) -> Result<FeatureSet, String> {
+        let mut set = FeatureSet::default();
+        let mut pending: Vec<String> = requested.to_vec();
+        if self.features.contains_key("default") {
+            pending.push("default".to_string());
+        }
+
+        while let Some(feature) = pending.pop() {
+            if set.features.contains(&feature) {
+                continue;
+            }
+            let Some(items) = self.features.get(&feature) else {
+                let available: Vec<&str> = self.features.keys().map(String::as_str).collect();
+                return Err(if available.is_empty() {
+                    format!(
+                        "unknown feature '{}' (trident.toml has no [features])",
+                        feature
+                    )
+                } else {
+                    format!(
+                        "unknown feature '{}' (available: {})",
+                        feature,
+                        available.join(", ")
+                    )
+                });
+            };
+            set.cfg_flags.insert(feature.clone());
+            for item in items {
+                if self.features.contains_key(item) {
+                    pending.push(item.clone());
+                } else if self.dependencies.optional.contains(item) {
+                    set.dependencies.insert(item.clone());
+                } else if self.dependencies.dependencies.contains_key(item) || item.contains('.') {
+                    // Required dependencies are always on; modules are gated below.
+                } else {
+                    set.cfg_flags.insert(item.clone());
+                }
+            }
+            set.features.insert(feature);
+        }
+
+        let enabled_modules: BTreeSet<&String> = set
+            .features
+            .iter()
+            .flat_map(|f| &self.features[f])
+            .filter(|item| item.contains('.'))
+            .collect();
+        for (feature, items) in &self.features {
+            for item in items.iter().filter(|item| item.contains('.')) {
+                if !enabled_modules.contains(item) {
+                    set.gated_modules
+                        .entry(item.clone())
+                        .or_insert_with(|| feature.clone());
+                }
+            }
+        }
+        Ok(set)
+    }
+
     /// Try to find a trident.toml in the given directory or its ancestors.
     pub fn find(start_dir: &Path) -> Option<PathBuf> {
         let mut dir = start_dir.to_path_buf();
@@ -150,6 +483,217 @@ impl Project {
     }
 }
 
+// ─── Build Context ─────────────────────────────────────────────────
+
+/// Where a command's input lives and how to compile it: the entry file,
+/// the project it belongs to, and the resolved compile options.
+///
+/// Every command that compiles goes through here, so a `--target`,
+/// `--profile`, or `--features` flag means the same thing everywhere and
+/// locked dependencies are always on the search path.
+#[derive(Clone, Debug)]
+pub struct BuildContext {
+    pub entry: PathBuf,
+    pub project: Option<Project>,
+    pub options: crate::CompileOptions,
+    /// Deprecation notices from resolving the options, for the caller
+    /// to show.
+    pub warnings: Vec<String>,
+}
+
+impl BuildContext {
+    /// Find the entry file and project for an input path: a project
+    /// directory, or a `.tri` file, in which case the nearest trident.toml
+    /// above it supplies the entry. Options are the defaults until
+    /// `configure` is called.
+    pub fn discover(input: &Path) -> Result<Self, String> {
+        let toml_path = if input.is_dir() {
+            let toml_path = input.join("trident.toml");
+            if !toml_path.exists() {
+                return Err(format!("no trident.toml found in '{}'", input.display()));
+            }
+            Some(toml_path)
+        } else if input.extension().is_some_and(|e| e == "tri") {
+            Project::find(input.parent().unwrap_or(Path::new(".")))
+        } else {
+            return Err("input must be a .tri file or project directory".to_string());
+        };
+        let project = match toml_path {
+            Some(path) => Some(Project::load(&path).map_err(|e| e.message)?),
+            None => None,
+        };
+        Ok(Self {
+            entry: project
+                .as_ref()
+                .map_or_else(|| input.to_path_buf(), |p| p.entry.clone()),
+            project,
+            options: crate::CompileOptions::default(),
+            warnings: Vec::new(),
+        })
+    }
+
+    /// `discover`, then `configure`.
+    pub fn resolve(
+        input: &Path,
+        target: &str,
+        profile: &str,
+        features: &[String],
+    ) -> Result<Self, String> {
+        Self::discover(input)?.configure(target, profile, features)
+    }
+
+    /// The context of a source file open in an editor: its project's
+    /// entry and default options, or the file alone when no valid
+    /// trident.toml is found.
+    pub fn for_file(file: &Path) -> Self {
+        Self::discover(file)
+            .and_then(|ctx| ctx.configure("triton", "debug", &[]))
+            .unwrap_or_else(|_| Self {
+                entry: file.to_path_buf(),
+                project: None,
+                options: crate::CompileOptions::default(),
+                warnings: Vec::new(),
+            })
+    }
+
+    /// Resolve the compile options for a VM target and profile, with
+    /// `features` enabled in addition to `default`.
+    pub fn configure(
+        mut self,
+        target: &str,
+        profile: &str,
+        features: &[String],
+    ) -> Result<Self, String> {
+        self.options = self.options_for(target, profile, features)?;
+        Ok(self)
+    }
+
+    /// Compile options for a VM target and profile, leaving the context's
+    /// own options alone (e.g. one set per profile of a multi-profile
+    /// build).
+    pub fn options_for(
+        &mut self,
+        target: &str,
+        profile: &str,
+        features: &[String],
+    ) -> Result<crate::CompileOptions, String> {
+        // Backward compat: --target debug/release → treat as profile
+        let (vm_target, profile) = match target {
+            "debug" | "release" => {
+                self.warnings.push(format!(
+                    "--target {} is deprecated; use --profile {} --target triton",
+                    target, target
+                ));
+                ("triton", target)
+            }
+            _ => (target, profile),
+        };
+        let project = self.project.as_ref();
+
+        // Project may override the default "triton" target
+        let target = match (vm_target, project.and_then(|p| p.target.as_deref())) {
+            ("triton", Some(project_target)) => project_target,
+            _ => vm_target,
+        };
+        let target_config = if target == "triton" {
+            crate::target::TerrainConfig::triton()
+        } else {
+            crate::target::TerrainConfig::resolve(target).map_err(|e| e.message)?
+        };
+
+        let features = self.features(features)?;
+        let mut cfg_flags: BTreeSet<String> = project
+            .and_then(|p| p.targets.get(profile))
+            .map(|flags| flags.iter().cloned().collect())
+            .unwrap_or_else(|| BTreeSet::from([profile.to_string()]));
+        cfg_flags.extend(features.cfg_flags);
+
+        Ok(crate::CompileOptions {
+            profile: profile.to_string(),
+            cfg_flags,
+            target_config,
+            dep_dirs: self.dep_dirs(&features.dependencies),
+            gated_modules: features.gated_modules,
+            module_cfg: project
+                .map(|p| {
+                    p.module_cfg
+                        .iter()
+                        .map(|(module, flags)| (module.clone(), flags.iter().cloned().collect()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            mono_limits: project.map(|p| p.limits).unwrap_or_default(),
+            label_scheme: project.map(|p| p.labels).unwrap_or_default(),
+            validate_codegen: false,
+            ..crate::CompileOptions::default()
+        })
+    }
+
+    /// The project's features: `default` plus `features`.
+    pub fn features(&self, features: &[String]) -> Result<FeatureSet, String> {
+        match &self.project {
+            Some(project) => project.resolve_features(features),
+            None if features.is_empty() => Ok(FeatureSet::default()),
+            None => Err("--features requires a project with trident.toml".to_string()),
+        }
+    }
+
+    /// Dependency search directories from the project's trident.lock.
+    ///
+    /// Projects with `vendor = true` resolve vendored copies first.
+    /// Optional dependencies are left out unless `enabled` lists them.
+    fn dep_dirs(&self, enabled: &BTreeSet<String>) -> Vec<PathBuf> {
+        let Some(project) = &self.project else {
+            return Vec::new();
+        };
+        let lock_path = project.root_dir.join("trident.lock");
+        if !lock_path.exists() {
+            return Vec::new();
+        }
+        let lockfile = crate::manifest::load_lockfile(&lock_path).map(|mut lockfile| {
+            lockfile.locked.retain(|name, _| {
+                !project.dependencies.optional.contains(name) || enabled.contains(name)
+            });
+            lockfile
+        });
+        match lockfile {
+            Ok(lockfile) if project.vendor => {
+                crate::manifest::vendored_search_paths(&project.root_dir, &lockfile)
+            }
+            Ok(lockfile) => crate::manifest::dependency_search_paths(&project.root_dir, &lockfile),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Build output root: `target/` in the project root, or next to the
+    /// file for a single-file build.
+    pub fn target_dir(&self) -> PathBuf {
+        let root = match &self.project {
+            Some(project) => project.root_dir.clone(),
+            None => self.entry.parent().unwrap_or(Path::new(".")).to_path_buf(),
+        };
+        root.join("target")
+    }
+
+    /// Base name of build artifacts: the project name, or the file stem.
+    pub fn artifact_name(&self) -> String {
+        match &self.project {
+            Some(project) => project.name.clone(),
+            None => self
+                .entry
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "program".to_string()),
+        }
+    }
+
+    /// Artifact directory for one VM target and profile:
+    /// `target/<vm>/<profile>/`.
+    pub fn artifact_dir(&self, vm: &str, profile: &str) -> PathBuf {
+        self.target_dir().join(vm).join(profile)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,6 +718,67 @@ entry = "main.tri"
         assert_eq!(project.version, "0.1.0");
         assert!(project.entry.ends_with("main.tri"));
         assert!(project.targets.is_empty());
+        assert!(!project.vendor);
+    }
+
+    #[test]
+    fn test_load_project_vendor_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("trident.toml");
+        fs::write(
+            &toml_path,
+            "[project]\nname = \"frozen\"\nversion = \"1.0.0\"\nvendor = true\n",
+        )
+        .unwrap();
+
+        let project = Project::load(&toml_path).unwrap();
+        assert!(project.vendor);
+    }
+
+    #[test]
+    fn test_load_project_limits_override_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("trident.toml");
+        fs::write(
+            &toml_path,
+            "[project]\nname = \"small\"\n\n[limits]\nmono_instances = 8\n",
+        )
+        .unwrap();
+
+        let project = Project::load(&toml_path).unwrap();
+        assert_eq!(project.limits.mono_instances, 8);
+        assert_eq!(
+            project.limits.mono_instructions,
+            MonoLimits::default().mono_instructions
+        );
+
+        fs::write(
+            &toml_path,
+            "[project]\nname = \"bad\"\n[limits]\nmono_instances = many\n",
+        )
+        .unwrap();
+        assert!(Project::load(&toml_path).is_err());
+    }
+
+    #[test]
+    fn test_load_project_label_scheme() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("trident.toml");
+        fs::write(&toml_path, "[project]\nname = \"app\"\n").unwrap();
+        assert_eq!(
+            Project::load(&toml_path).unwrap().labels,
+            LabelScheme::Qualified
+        );
+
+        fs::write(&toml_path, "[project]\nname = \"app\"\nlabels = \"flat\"\n").unwrap();
+        assert_eq!(Project::load(&toml_path).unwrap().labels, LabelScheme::Flat);
+
+        fs::write(
+            &toml_path,
+            "[project]\nname = \"app\"\nlabels = \"short\"\n",
+        )
+        .unwrap();
+        assert!(Project::load(&toml_path).is_err());
     }
 
     #[test]
@@ -210,6 +815,65 @@ flags = ["release"]
         assert_eq!(release_flags, &vec!["release".to_string()]);
     }
 
+    fn project_with_features(toml: &str) -> Project {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("trident.toml");
+        fs::write(&toml_path, toml).unwrap();
+        Project::load(&toml_path).unwrap()
+    }
+
+    const FEATURES_TOML: &str = r#"[project]
+name = "wallet"
+version = "0.1.0"
+
+[dependencies]
+bignum = { path = "libs/bignum", optional = true }
+
+[features]
+default = ["logging"]
+secp = ["std.crypto.secp256k1", "bignum", "fast_inv"]
+full = ["secp"]
+"#;
+
+    #[test]
+    fn test_resolve_features_defaults_gate_unselected_modules() {
+        let project = project_with_features(FEATURES_TOML);
+        assert!(project.dependencies.optional.contains("bignum"));
+
+        let set = project.resolve_features(&[]).unwrap();
+        assert_eq!(
+            set.cfg_flags,
+            BTreeSet::from(["default".to_string(), "logging".to_string()])
+        );
+        assert!(set.dependencies.is_empty());
+        assert_eq!(
+            set.gated_modules
+                .get("std.crypto.secp256k1")
+                .map(String::as_str),
+            Some("secp")
+        );
+    }
+
+    #[test]
+    fn test_resolve_features_enables_nested_features() {
+        let project = project_with_features(FEATURES_TOML);
+        let set = project.resolve_features(&["full".to_string()]).unwrap();
+        assert!(set.features.contains("secp"));
+        assert!(set.cfg_flags.contains("secp"));
+        assert!(set.cfg_flags.contains("fast_inv"));
+        assert!(!set.cfg_flags.contains("bignum"));
+        assert!(set.dependencies.contains("bignum"));
+        assert!(set.gated_modules.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_features_rejects_unknown_feature() {
+        let project = project_with_features(FEATURES_TOML);
+        let err = project.resolve_features(&["nope".to_string()]).unwrap_err();
+        assert!(err.contains("unknown feature 'nope'"));
+        assert!(err.contains("available: default, full, secp"));
+    }
+
     #[test]
     fn test_parse_string_array() {
         assert_eq!(
@@ -267,4 +931,170 @@ version = "0.1.0"
         .unwrap();
         assert!(Project::load(&toml_path).is_err());
     }
+
+    #[test]
+    fn test_build_context_for_a_loose_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("hello.tri");
+        fs::write(&file, "program hello\nfn main() {}\n").unwrap();
+
+        let ctx = BuildContext::resolve(&file, "triton", "release", &[]).unwrap();
+        assert!(ctx.project.is_none());
+        assert_eq!(ctx.entry, file);
+        assert_eq!(
+            ctx.options.cfg_flags,
+            BTreeSet::from(["release".to_string()])
+        );
+        assert_eq!(ctx.artifact_name(), "hello");
+        assert_eq!(
+            ctx.artifact_dir("triton", "release"),
+            dir.path().join("target").join("triton").join("release")
+        );
+
+        let err = BuildContext::resolve(&file, "triton", "debug", &["secp".to_string()]);
+        assert!(err.unwrap_err().contains("requires a project"));
+        assert!(BuildContext::discover(&dir.path().join("notes.txt")).is_err());
+    }
+
+    #[test]
+    fn test_build_context_from_a_project_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(
+            dir.path().join("trident.toml"),
+            "[project]\nname = \"app\"\nentry = \"main.tri\"\n\n[targets.debug]\nflags = [\"debug\", \"verbose\"]\n",
+        )
+        .unwrap();
+        let module = dir.path().join("src").join("util.tri");
+        fs::write(&module, "module util\n").unwrap();
+
+        // A file inside the project compiles as the project.
+        let ctx = BuildContext::resolve(&module, "debug", "release", &[]).unwrap();
+        assert_eq!(ctx.entry, dir.path().join("main.tri"));
+        assert_eq!(ctx.artifact_name(), "app");
+        assert_eq!(ctx.options.profile, "debug");
+        assert!(ctx.options.cfg_flags.contains("verbose"));
+        assert_eq!(ctx.warnings.len(), 1);
+        assert!(ctx.warnings[0].contains("deprecated"));
+
+        // An editor falls back to the file itself outside any project.
+        let loose = tempfile::tempdir().unwrap();
+        let file = loose.path().join("scratch.tri");
+        assert_eq!(BuildContext::for_file(&file).entry, file);
+    }
+
+    const PROFILES_TOML: &str = r#"[project]
+name = "app"
+
+[targets.debug]
+flags = ["debug", "verbose", "trace"]
+
+[targets.release]
+inherits = "debug"
+flags = ["release"]
+remove = ["trace"]
+
+[cfg.bignum]
+flags = ["fast_inv"]
+
+[cfg."std.crypto"]
+flags = ["ct"]
+"#;
+
+    #[test]
+    fn test_profile_inherits_parent_flags_without_parent_name() {
+        let project = project_with_features(PROFILES_TOML);
+        assert_eq!(
+            project.targets["release"],
+            vec!["verbose".to_string(), "release".to_string()]
+        );
+        assert_eq!(project.module_cfg["bignum"], vec!["fast_inv".to_string()]);
+        assert_eq!(project.module_cfg["std.crypto"], vec!["ct".to_string()]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("trident.toml");
+        fs::write(&toml_path, PROFILES_TOML).unwrap();
+        let ctx = BuildContext::resolve(dir.path(), "triton", "release", &[]).unwrap();
+        assert!(ctx.options.flags_for("bignum.field").contains("fast_inv"));
+        assert!(!ctx.options.flags_for("main").contains("fast_inv"));
+        assert!(!ctx.options.flags_for("main").contains("debug"));
+    }
+
+    #[test]
+    fn test_profile_validation_errors() {
+        let load = |body: &str| {
+            let dir = tempfile::tempdir().unwrap();
+            let toml_path = dir.path().join("trident.toml");
+            fs::write(&toml_path, format!("[project]\nname = \"app\"\n\n{}", body)).unwrap();
+            Project::load(&toml_path).map(|_| ()).unwrap_err().message
+        };
+
+        let err = load("[targets.release]\ninherits = \"debug\"\nremove = [\"verbose\"]\n\n[targets.debug]\nflags = [\"debug\"]\n");
+        assert!(err.contains("unknown flag 'verbose'"), "{}", err);
+
+        let err = load("[targets.release]\ninherits = \"dbg\"\n");
+        assert!(err.contains("unknown profile 'dbg'"), "{}", err);
+
+        let err = load("[targets.a]\ninherits = \"b\"\n\n[targets.b]\ninherits = \"a\"\n");
+        assert!(err.contains("cycle"), "{}", err);
+
+        let err = load("[targets.debug]\nflag = [\"debug\"]\n");
+        assert!(err.contains("unknown key 'flag'"), "{}", err);
+
+        let err = load("[cfg.bignum]\nrelease = [\"x\"]\n");
+        assert!(err.contains("[cfg.bignum]"), "{}", err);
+    }
+
+    #[test]
+    fn test_project_metadata() {
+        let project = project_with_features(
+            r#"[project]
+name = "bignum"
+version = "1.2.0"
+license = "MIT OR Apache-2.0"
+authors = ["Ada <ada@example.org>", "Grace"]
+description = "Arbitrary-width integers over the Goldilocks field"
+repository = "https://example.org/bignum"
+"#,
+        );
+        let meta = &project.metadata;
+        assert_eq!(meta.license.as_deref(), Some("MIT OR Apache-2.0"));
+        assert_eq!(meta.authors, vec!["Ada <ada@example.org>", "Grace"]);
+        assert_eq!(meta.validate(), (Vec::new(), Vec::new()));
+
+        let bare = project_with_features("[project]\nname = \"app\"\n");
+        assert!(bare.metadata.is_empty());
+        let (errors, warnings) = bare.metadata.validate();
+        assert!(errors.is_empty());
+        assert_eq!(warnings.len(), 2);
+
+        let bad = ProjectMetadata {
+            license: Some("MIT OR".to_string()),
+            repository: Some("example.org/bignum".to_string()),
+            ..ProjectMetadata::default()
+        };
+        assert_eq!(bad.validate().0.len(), 2);
+    }
+
+    #[test]
+    fn test_validate_license() {
+        for ok in [
+            "MIT",
+            "Apache-2.0 WITH LLVM-exception",
+            "(MIT OR Apache-2.0) AND BSD-3-Clause",
+            "GPL-2.0+",
+        ] {
+            assert!(validate_license(ok).is_ok(), "{}", ok);
+        }
+        for bad in [
+            "",
+            "MIT AND",
+            "(MIT",
+            "MIT Apache-2.0",
+            "MIT/Apache",
+            "OR MIT",
+        ] {
+            assert!(validate_license(bad).is_err(), "{}", bad);
+        }
+    }
 }
@@ -116,6 +116,63 @@ pub(crate) fn find_os_dir() -> Option<PathBuf> {
     None
 }
 
+/// Find the VM library directory (holds `vm/<target>/target.toml`).
+pub(crate) fn find_vm_lib_dir() -> Option<PathBuf> {
+    find_lib_dir("TRIDENT_VMLIB", "vm")
+}
+
+/// Names of all targets with a `vm/<name>/target.toml`, sorted. The
+/// built-in `triton` target is always included.
+pub(crate) fn registered_targets() -> Vec<String> {
+    let mut names = BTreeSet::from(["triton".to_string()]);
+    if let Some(entries) = find_vm_lib_dir().and_then(|dir| std::fs::read_dir(dir).ok()) {
+        for entry in entries.flatten() {
+            if entry.path().join("target.toml").is_file() {
+                names.insert(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+    }
+    names.into_iter().collect()
+}
+
+/// The compiler settings of `vm/<target>/target.toml` beyond what
+/// `TerrainConfig` carries. Other sections are ignored.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub(crate) struct TargetSettings {
+    #[serde(default)]
+    pub(crate) capabilities: CapabilitySettings,
+    #[serde(default)]
+    pub(crate) events: EventSettings,
+}
+
+/// `[capabilities]`: the highest TIR tier the target executes.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub(crate) struct CapabilitySettings {
+    pub(crate) tier: Option<u8>,
+}
+
+/// `[events]`: the output channel `reveal` and `seal` write to.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub(crate) struct EventSettings {
+    pub(crate) channel: Option<String>,
+}
+
+impl TargetSettings {
+    /// Load the settings of `target`. `None` if the target has no
+    /// `target.toml` or it does not parse.
+    pub(crate) fn load(target: &str) -> Option<Self> {
+        let path = find_vm_lib_dir()?.join(target).join("target.toml");
+        let content = std::fs::read_to_string(path).ok()?;
+        toml::from_str(&content).ok()
+    }
+}
+
+/// Highest TIR tier `target` executes, from `[capabilities] tier` in its
+/// `target.toml`. `None` if the target does not declare one.
+pub(crate) fn target_tier(target: &str) -> Option<u8> {
+    TargetSettings::load(target)?.capabilities.tier
+}
+
 /// Legacy flat-path fallback map for backward compatibility.
 /// Maps old module names to their new layered locations.
 fn legacy_stdlib_fallback(name: &str) -> Option<&'static str> {
@@ -13,6 +13,8 @@ pub(crate) struct ModuleResolver {
     pub(crate) dep_dirs: Vec<PathBuf>,
     /// All discovered modules by name.
     pub(crate) modules: BTreeMap<String, ModuleInfo>,
+    /// Program parts declared with `mod`: qualified name → sibling file.
+    part_paths: BTreeMap<String, PathBuf>,
     /// Queue of modules to process.
     queue: Vec<String>,
     /// Diagnostics.
@@ -32,9 +34,18 @@ impl ModuleResolver {
         })?;
 
         // Quick-parse the entry file to get its name and dependencies
-        let (name, deps) = scan_module_header(&source);
+        let (name, mut deps) = scan_module_header(&source);
         let entry_name = name.unwrap_or_else(|| "main".to_string());
 
+        // `mod transfers` in `program token` pulls in ./transfers.tri as
+        // module `token.transfers`.
+        let mut part_paths = BTreeMap::new();
+        for part in scan_program_parts(&source) {
+            let qualified = format!("{}.{}", entry_name, part);
+            part_paths.insert(qualified.clone(), root_dir.join(part).with_extension("tri"));
+            deps.push(qualified);
+        }
+
         let info = ModuleInfo {
             name: entry_name.clone(),
             file_path: entry_path.to_path_buf(),
@@ -51,6 +62,7 @@ impl ModuleResolver {
             os_dir: find_os_dir(),
             dep_dirs: Vec::new(),
             modules,
+            part_paths,
             queue: deps,
             diagnostics: Vec::new(),
         })
@@ -106,7 +118,25 @@ impl ModuleResolver {
                 }
             };
 
-            let (_name, deps) = scan_module_header(&source);
+            let (name, deps) = scan_module_header(&source);
+
+            if self.part_paths.contains_key(&module_name)
+                && name.as_deref() != Some(module_name.as_str())
+            {
+                let file_name = file_path.file_name().unwrap_or_default().to_string_lossy();
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "program part '{}' does not declare module '{}'",
+                            file_path.display(),
+                            module_name
+                        ),
+                        Span::dummy(),
+                    )
+                    .with_help(format!("start {} with `module {}`", file_name, module_name)),
+                );
+                continue;
+            }
 
             // Queue newly discovered dependencies
             for dep in &deps {
@@ -141,12 +171,20 @@ impl ModuleResolver {
     /// "os.neptune.kernel"   → os_dir/neptune/kernel.tri (OS-specific)
     /// "crypto.sponge"       → root_dir/crypto/sponge.tri (local)
     ///
+    /// Program parts (`mod transfers` in `program token`):
+    /// "token.transfers"     → root_dir/transfers.tri
+    ///
     /// Legacy backward compatibility still supported:
     /// "neptune.ext.kernel"  → os_dir/neptune/kernel.tri
     /// "ext.neptune.kernel"  → os_dir/neptune/kernel.tri
     /// "std.crypto.hash"     → vm_dir/crypto/hash.tri (intrinsics moved)
     /// "std.hash"            → vm_dir/crypto/hash.tri (flat → layered → vm)
     pub(crate) fn resolve_path(&self, module_name: &str) -> PathBuf {
+        // Program parts live next to the program file
+        if let Some(path) = self.part_paths.get(module_name) {
+            return path.clone();
+        }
+
         // Validate: reject path traversal components
         let raw_parts: Vec<&str> = module_name.split('.').collect();
         for part in &raw_parts {
@@ -377,3 +415,21 @@ pub(crate) fn scan_module_header(source: &str) -> (Option<String>, Vec<String>)
 
     (name, deps)
 }
+
+/// Quick scan of a program header for `mod X` lines (program parts).
+pub(crate) fn scan_program_parts(source: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix("mod ") {
+            parts.push(rest.trim().to_string());
+        } else if trimmed.starts_with("fn ")
+            || trimmed.starts_with("pub ")
+            || trimmed.starts_with("const ")
+            || trimmed.starts_with("struct ")
+        {
+            break;
+        }
+    }
+    parts
+}
@@ -103,3 +103,75 @@ fn test_path_traversal_rejected() {
 
     let _ = std::fs::remove_file(&entry);
 }
+
+#[test]
+fn test_scan_program_parts() {
+    let parts = scan_program_parts(
+        "program token\n\nuse std.io\nmod transfers\n// mod ignored\nmod supply\n\nfn main() {}\nmod late",
+    );
+    assert_eq!(parts, vec!["transfers", "supply"]);
+}
+
+#[test]
+fn test_program_parts_resolve_from_sibling_files() {
+    let dir = std::env::temp_dir().join("trident_test_parts");
+    let _ = std::fs::create_dir_all(&dir);
+    let entry = dir.join("token.tri");
+    std::fs::write(&entry, "program token\nmod transfers\nfn main() {}\n").unwrap();
+    std::fs::write(
+        dir.join("transfers.tri"),
+        "module token.transfers\npub fn step(x: Field) -> Field { x + 1 }\n",
+    )
+    .unwrap();
+
+    let modules = resolve_modules(&entry).expect("program parts should resolve");
+    let names: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, vec!["token.transfers", "token"]);
+    assert_eq!(modules[0].file_path, dir.join("transfers.tri"));
+    assert_eq!(modules[1].dependencies, vec!["token.transfers"]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_program_part_header_mismatch() {
+    let dir = std::env::temp_dir().join("trident_test_parts_mismatch");
+    let _ = std::fs::create_dir_all(&dir);
+    let entry = dir.join("token.tri");
+    std::fs::write(&entry, "program token\nmod transfers\nfn main() {}\n").unwrap();
+    std::fs::write(
+        dir.join("transfers.tri"),
+        "module transfers\npub fn step(x: Field) -> Field { x + 1 }\n",
+    )
+    .unwrap();
+
+    let diags = resolve_modules(&entry).unwrap_err();
+    assert!(
+        diags[0]
+            .message
+            .contains("does not declare module 'token.transfers'"),
+        "got: {}",
+        diags[0].message
+    );
+    assert_eq!(
+        diags[0].help.as_deref(),
+        Some("start transfers.tri with `module token.transfers`")
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_every_target_toml_loads_its_settings() {
+    for target in registered_targets() {
+        assert!(
+            TargetSettings::load(&target).is_some(),
+            "vm/{}/target.toml does not parse",
+            target
+        );
+    }
+    assert_eq!(target_tier("triton"), Some(3));
+    let triton = TargetSettings::load("triton").unwrap();
+    assert_eq!(triton.events.channel.as_deref(), Some("shared"));
+    assert!(TargetSettings::load("no_such_target").is_none());
+}
@@ -244,6 +244,15 @@ pub fn default_value(ty: &Type) -> String {
             let parts: Vec<String> = elems.iter().map(default_value).collect();
             format!("({})", parts.join(", "))
         }
+        Type::NamedTuple(components) => {
+            let parts: Vec<String> = components.iter().map(|(_, t)| default_value(t)).collect();
+            format!("({})", parts.join(", "))
+        }
+        Type::Option(_) => "None".to_string(),
+        Type::Bytes(size) => match size.as_literal() {
+            Some(n) => format!("[{}]", vec!["0"; n as usize].join(", ")),
+            None => format!("[0; {}]", size),
+        },
         Type::Named(_) => "0".to_string(),
     }
 }
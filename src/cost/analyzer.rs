@@ -1,8 +1,11 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use super::model::{create_cost_model, CostModel, TableCost};
+use crate::ast::eval::PureFns;
 use crate::ast::*;
+use crate::config::target::TerrainConfig;
 use crate::field::proof;
+use crate::span::Span;
 
 // --- Per-function cost result ---
 
@@ -15,6 +18,17 @@ pub struct FunctionCost {
     pub per_iteration: Option<(TableCost, u64)>,
 }
 
+/// Worst-case cost of one way into a program.
+#[derive(Clone, Debug)]
+pub struct EntryCost {
+    /// The `#[entry]` operation, or `main` when `main` is hand-written.
+    pub name: String,
+    /// Tag that selects an `#[entry]` operation.
+    pub tag: Option<u64>,
+    pub cost: TableCost,
+    pub padded_height: u64,
+}
+
 /// Cost analysis result for the full program.
 #[derive(Clone, Debug)]
 pub struct ProgramCost {
@@ -30,7 +44,59 @@ pub struct ProgramCost {
     pub padded_height: u64,
     pub estimated_proving_ns: u64,
     /// H0004: loops where declared bound >> actual constant end.
-    pub loop_bound_waste: Vec<(String, u64, u64)>, // (fn_name, end_value, bound)
+    pub loop_bound_waste: Vec<LoopBoundWaste>,
+    /// Per entry point of a program; empty for modules.
+    pub entries: Vec<EntryCost>,
+}
+
+/// H0004: a loop whose declared bound far exceeds its constant end, or
+/// (when `bound` is 0) a loop with neither a constant end nor a bound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoopBoundWaste {
+    pub fn_name: String,
+    /// Constant end value, or the assumed iteration count when `bound` is 0.
+    pub end_value: u64,
+    /// Declared `bounded N`; 0 when the loop declares no bound.
+    pub bound: u64,
+    /// Value of a named-constant end, when `bound` is 0 and the constant
+    /// is a literal in the same file.
+    pub known_end: Option<u64>,
+    /// Loop header from `for` up to the body.
+    pub span: Span,
+}
+
+/// Cost roll-up of one module of a project.
+#[derive(Clone, Debug)]
+pub struct ModuleCost {
+    pub module: String,
+    /// Functions costed (tests and bodiless intrinsics excluded).
+    pub functions: usize,
+    /// Sum of the module's function costs.
+    pub total: TableCost,
+    /// The function with the largest padded height, and that height.
+    pub heaviest: Option<(String, u64)>,
+}
+
+/// A function whose own padded height exceeds the target's proving limit.
+#[derive(Clone, Debug)]
+pub struct OversizedFunction {
+    pub module: String,
+    pub function: String,
+    pub padded_height: u64,
+    /// Suggested splits: the line of each top-level statement that starts
+    /// a new part, and the rows the part before it reaches.
+    pub split_points: Vec<(usize, u64)>,
+}
+
+/// Per-module costs of a project, checked against the target's
+/// practical proving limit (`trident check --costs`).
+#[derive(Clone, Debug)]
+pub struct ProjectCost {
+    pub target: String,
+    pub max_padded_height: u64,
+    /// In dependency order, the entry module last.
+    pub modules: Vec<ModuleCost>,
+    pub oversized: Vec<OversizedFunction>,
 }
 
 impl ProgramCost {
@@ -54,14 +120,30 @@ impl ProgramCost {
 pub(crate) struct CostAnalyzer<'a> {
     /// Target-specific cost model.
     pub(crate) cost_model: &'a dyn CostModel,
+    /// Field elements absorbed per hash permutation (from the target config).
+    hash_rate: u64,
     /// Function bodies indexed by name (for resolving calls).
     pub(crate) fn_bodies: BTreeMap<String, FnDef>,
     /// Cached function costs to avoid recomputation.
     fn_costs: BTreeMap<String, TableCost>,
     /// Recursion guard to prevent infinite loops in cost computation.
     in_progress: Vec<String>,
-    /// H0004: collected loop bound waste entries (fn_name, end_value, bound).
-    pub(crate) loop_bound_waste: Vec<(String, u64, u64)>,
+    /// H0004: collected loop bound waste entries.
+    pub(crate) loop_bound_waste: Vec<LoopBoundWaste>,
+    /// Widths of the file's structs, for sizing `==` operands.
+    pub(crate) struct_widths: BTreeMap<String, u32>,
+    /// Widths of the parameters and annotated locals of the function
+    /// being costed.
+    pub(crate) var_widths: BTreeMap<String, u32>,
+    pub(crate) digest_width: u32,
+    pub(crate) xfield_width: u32,
+    /// `#[pure]` functions whose constant calls fold to pushes.
+    pub(crate) pure_fns: PureFns,
+    /// Name of the module being analyzed.
+    pub(crate) module_name: String,
+    /// Size parameter values when costing an instance of a generic
+    /// function (`function_cost`).
+    pub(crate) size_subs: BTreeMap<String, u64>,
 }
 
 impl Default for CostAnalyzer<'_> {
@@ -76,25 +158,48 @@ impl<'a> CostAnalyzer<'a> {
         Self::with_cost_model(create_cost_model(target_name))
     }
 
+    /// Create an analyzer for a loaded target configuration.
+    ///
+    /// The cost model is selected by target name; the hash rate used for
+    /// attestation and event hashing comes from the config's `[hash]` section.
+    pub(crate) fn for_config(config: &TerrainConfig) -> Self {
+        let mut analyzer = Self::for_target(&config.name);
+        if config.hash_rate > 0 {
+            analyzer.hash_rate = config.hash_rate as u64;
+        }
+        analyzer.digest_width = config.digest_width;
+        analyzer.xfield_width = config.xfield_width;
+        analyzer
+    }
+
     /// Create an analyzer with a specific cost model.
     pub(crate) fn with_cost_model(cost_model: &'a dyn CostModel) -> Self {
         Self {
             cost_model,
+            hash_rate: cost_model.hash_rate(),
             fn_bodies: BTreeMap::new(),
             fn_costs: BTreeMap::new(),
             in_progress: Vec::new(),
             loop_bound_waste: Vec::new(),
+            struct_widths: BTreeMap::new(),
+            var_widths: BTreeMap::new(),
+            digest_width: 5,
+            xfield_width: 3,
+            pure_fns: PureFns::new(),
+            module_name: String::new(),
+            size_subs: BTreeMap::new(),
         }
     }
 
+    /// Fold constant calls to pure functions of other modules, too.
+    pub(crate) fn with_pure_fns(mut self, pure_fns: PureFns) -> Self {
+        self.pure_fns = pure_fns;
+        self
+    }
+
     /// Analyze a complete file and return the program cost.
     pub(crate) fn analyze_file(&mut self, file: &File) -> ProgramCost {
-        // Collect all function definitions.
-        for item in &file.items {
-            if let Item::Fn(func) = &item.node {
-                self.fn_bodies.insert(func.name.node.clone(), func.clone());
-            }
-        }
+        self.collect_items(file);
 
         // Compute cost for each function.
         let mut functions = Vec::new();
@@ -115,8 +220,12 @@ impl<'a> CostAnalyzer<'a> {
         }
 
         // Total cost: start from main if it exists, otherwise sum all.
+        let mut startup = self.cost_model.call_overhead(); // call main + halt
+        if file.kind == FileKind::Program {
+            startup = startup.add(&self.data_init_cost());
+        }
         let total = if let Some(main_cost) = self.fn_costs.get("main") {
-            main_cost.add(&self.cost_model.call_overhead()) // call main + halt
+            main_cost.add(&startup)
         } else {
             functions
                 .iter()
@@ -127,7 +236,7 @@ impl<'a> CostAnalyzer<'a> {
         // Rough heuristic: total first-table value (processor cycles) ≈ instruction count.
         let instruction_count = total.get(0).max(10);
         let hash_rows = self.cost_model.hash_rows_per_permutation();
-        let attestation_hash_rows = instruction_count.div_ceil(10) * hash_rows;
+        let attestation_hash_rows = instruction_count.div_ceil(self.hash_rate) * hash_rows;
 
         // Padded height includes attestation.
         let max_height = total.max_height().max(attestation_hash_rows);
@@ -136,11 +245,42 @@ impl<'a> CostAnalyzer<'a> {
         let columns = self.cost_model.trace_column_count();
         let estimated_proving_ns = proof::estimate_proving_ns(padded_height, columns);
 
-        // H0004: scan for loop bound waste (bound >> constant end)
+        let entries = if file.kind == FileKind::Program {
+            self.entry_costs(file)
+                .into_iter()
+                .map(|(name, tag, cost)| {
+                    let cost = cost.add(&startup);
+                    EntryCost {
+                        name,
+                        tag,
+                        cost,
+                        padded_height: proof::padded_height(
+                            cost.max_height().max(attestation_hash_rows),
+                        ),
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // H0004: scan for loop bound waste (bound >> constant end) and
+        // unbounded loops over a named constant
+        let consts: BTreeMap<String, u64> = file
+            .items
+            .iter()
+            .filter_map(|item| match &item.node {
+                Item::Const(c) => match c.value.node {
+                    Expr::Literal(Literal::Integer(v)) => Some((c.name.node.clone(), v)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
         for item in &file.items {
             if let Item::Fn(func) = &item.node {
                 if let Some(body) = &func.body {
-                    self.scan_loop_bound_waste(&func.name.node, &body.node);
+                    self.scan_loop_bound_waste(&func.name.node, &body.node, &consts);
                 }
             }
         }
@@ -165,9 +305,122 @@ impl<'a> CostAnalyzer<'a> {
             padded_height,
             estimated_proving_ns,
             loop_bound_waste: std::mem::take(&mut self.loop_bound_waste),
+            entries,
+        }
+    }
+
+    /// Record the file's function bodies and struct widths.
+    fn collect_items(&mut self, file: &File) {
+        self.module_name = file.name.node.clone();
+        self.pure_fns.add_module(file, &BTreeSet::new());
+        for item in &file.items {
+            match &item.node {
+                Item::Fn(func) => {
+                    self.fn_bodies.insert(func.name.node.clone(), func.clone());
+                }
+                Item::Struct(s) => {
+                    let width = s.fields.iter().map(|f| self.type_width(&f.ty.node)).sum();
+                    self.struct_widths.insert(s.name.node.clone(), width);
+                }
+                _ => {}
+            }
         }
     }
 
+    /// Cost of function `name` in `file`. For a generic function,
+    /// `size_args` gives its size parameters, in order; array widths and
+    /// loops up to a size parameter are costed for that instance.
+    pub(crate) fn function_cost(
+        &mut self,
+        file: &File,
+        name: &str,
+        size_args: &[u64],
+    ) -> Option<TableCost> {
+        self.collect_items(file);
+        let func = self.fn_bodies.get(name)?.clone();
+        self.size_subs = func
+            .type_params
+            .iter()
+            .map(|p| p.node.clone())
+            .zip(size_args.iter().copied())
+            .collect();
+        let cost = self.cost_fn(&func);
+        self.size_subs.clear();
+        Some(cost)
+    }
+
+    /// Value of a size parameter used as an expression, in an instance.
+    fn size_param(&self, expr: &Expr) -> Option<u64> {
+        match expr {
+            Expr::Var(name) => self.size_subs.get(name).copied(),
+            _ => None,
+        }
+    }
+
+    /// Cost of `main` for each way into the program. With `#[entry]`
+    /// operations, the dispatcher still compares every tag but only the
+    /// operation's own arm runs; otherwise there is one entry, `main`.
+    fn entry_costs(&mut self, file: &File) -> Vec<(String, Option<u64>, TableCost)> {
+        let Some(main) = self.fn_bodies.get("main").cloned() else {
+            return Vec::new();
+        };
+        let entries: Vec<String> = file
+            .items
+            .iter()
+            .filter_map(|item| match &item.node {
+                Item::Fn(f) if f.is_entry => Some(f.name.node.clone()),
+                _ => None,
+            })
+            .collect();
+        if entries.is_empty() {
+            let cost = self.cost_fn(&main);
+            return vec![("main".to_string(), None, cost)];
+        }
+        let Some(body) = main.body.as_ref() else {
+            return Vec::new();
+        };
+        let mut costs = Vec::new();
+        for (tag, name) in (0u64..).zip(entries) {
+            let mut dispatcher = body.node.clone();
+            for stmt in &mut dispatcher.stmts {
+                let Stmt::Match { arms, .. } = &mut stmt.node else {
+                    continue;
+                };
+                for arm in arms {
+                    let taken = matches!(
+                        arm.pattern.node,
+                        MatchPattern::Literal(Literal::Integer(n)) if n == tag
+                    );
+                    if !taken {
+                        arm.body.node = Block {
+                            stmts: Vec::new(),
+                            tail_expr: None,
+                        };
+                    }
+                }
+            }
+            let caller_vars = std::mem::take(&mut self.var_widths);
+            let cost = self.cost_block(&dispatcher);
+            self.var_widths = caller_vars;
+            costs.push((name, Some(tag), cost));
+        }
+        costs
+    }
+
+    /// Writing the `generate!` tables to RAM before `main`: per chunk of
+    /// five words, a push for each word and the address, then a block write.
+    fn data_init_cost(&self) -> TableCost {
+        let stack_op = self.cost_model.stack_op();
+        let write = self.cost_model.builtin_cost("ram_write_block");
+        self.pure_fns
+            .tables()
+            .values()
+            .flat_map(|values| values.chunks(5))
+            .fold(TableCost::ZERO, |acc, chunk| {
+                acc.add(&stack_op.scale(chunk.len() as u64 + 1)).add(&write)
+            })
+    }
+
     pub(crate) fn cost_fn(&mut self, func: &FnDef) -> TableCost {
         if let Some(cached) = self.fn_costs.get(&func.name.node) {
             return *cached;
@@ -181,6 +434,12 @@ impl<'a> CostAnalyzer<'a> {
 
         let depth_before = self.in_progress.len();
         self.in_progress.push(func.name.node.clone());
+        let params = func
+            .params
+            .iter()
+            .map(|p| (p.name.node.clone(), self.type_width(&p.ty.node)))
+            .collect();
+        let caller_vars = std::mem::replace(&mut self.var_widths, params);
 
         let cost = if let Some(body) = &func.body {
             self.cost_block(&body.node)
@@ -188,6 +447,7 @@ impl<'a> CostAnalyzer<'a> {
             TableCost::ZERO
         };
 
+        self.var_widths = caller_vars;
         self.in_progress.pop();
 
         // Only cache if we're at the top-level call (no recursion in flight).
@@ -199,21 +459,103 @@ impl<'a> CostAnalyzer<'a> {
         cost
     }
 
+    /// The target's practical proving limit (see `CostModel::max_padded_height`).
+    pub(crate) fn max_padded_height(&self) -> u64 {
+        self.cost_model.max_padded_height()
+    }
+
+    /// Where to split `func` so no part pads past `limit`: each top-level
+    /// statement that starts a new part, with the max table height of the
+    /// part before it. Parts are filled greedily in source order.
+    pub(crate) fn split_points(&mut self, func: &FnDef, limit: u64) -> Vec<(Span, u64)> {
+        let Some(body) = &func.body else {
+            return Vec::new();
+        };
+        let params = func
+            .params
+            .iter()
+            .map(|p| (p.name.node.clone(), self.type_width(&p.ty.node)))
+            .collect();
+        let caller_vars = std::mem::replace(&mut self.var_widths, params);
+
+        let mut points = Vec::new();
+        let mut part = TableCost::ZERO;
+        for stmt in &body.node.stmts {
+            let cost = self.cost_stmt(&stmt.node);
+            let joined = part.add(&cost);
+            if part != TableCost::ZERO && proof::padded_height(joined.max_height()) > limit {
+                points.push((stmt.span, part.max_height()));
+                part = cost;
+            } else {
+                part = joined;
+            }
+        }
+
+        self.var_widths = caller_vars;
+        points
+    }
+
     pub(crate) fn cost_block(&mut self, block: &Block) -> TableCost {
         let mut cost = TableCost::ZERO;
+        // Adjacent `sponge_absorb_field` calls share permutations, the
+        // same way the builder packs them.
+        let mut run = 0;
         for stmt in &block.stmts {
+            if let Stmt::Expr(expr) = &stmt.node {
+                if let Some(arg) = absorbed_field(&expr.node) {
+                    cost = cost.add(&self.cost_expr(arg));
+                    run += 1;
+                    continue;
+                }
+            }
+            cost = cost.add(&self.packed_absorb_cost(run));
+            run = 0;
             cost = cost.add(&self.cost_stmt(&stmt.node));
         }
         if let Some(tail) = &block.tail_expr {
-            cost = cost.add(&self.cost_expr(&tail.node));
+            match absorbed_field(&tail.node) {
+                Some(arg) if run > 0 => {
+                    cost = cost.add(&self.cost_expr(arg));
+                    run += 1;
+                }
+                _ => {
+                    cost = cost.add(&self.packed_absorb_cost(run));
+                    run = 0;
+                    cost = cost.add(&self.cost_expr(&tail.node));
+                }
+            }
         }
-        cost
+        cost.add(&self.packed_absorb_cost(run))
+    }
+
+    /// Absorbing `fields` packed elements: one permutation per `hash_rate`
+    /// words of the fields and their terminating 1, plus the pushes
+    /// padding the last block.
+    pub(crate) fn packed_absorb_cost(&self, fields: u64) -> TableCost {
+        if fields == 0 {
+            return TableCost::ZERO;
+        }
+        let permutations = (fields + 1).div_ceil(self.hash_rate);
+        let padding = permutations * self.hash_rate - fields;
+        self.cost_model
+            .builtin_cost("sponge_absorb")
+            .scale(permutations)
+            .add(&self.cost_model.stack_op().scale(padding))
     }
 
     pub(crate) fn cost_stmt(&mut self, stmt: &Stmt) -> TableCost {
         let stack_op = self.cost_model.stack_op();
         match stmt {
-            Stmt::Let { init, .. } => {
+            Stmt::Let {
+                pattern, ty, init, ..
+            } => {
+                if let Pattern::Name(name) = pattern {
+                    let width = match ty {
+                        Some(ty) => self.type_width(&ty.node),
+                        None => self.operand_width(&init.node),
+                    };
+                    self.var_widths.insert(name.node.clone(), width);
+                }
                 // Cost of evaluating the init expression + stack placement.
                 self.cost_expr(&init.node).add(&stack_op)
             }
@@ -257,15 +599,10 @@ impl<'a> CostAnalyzer<'a> {
                 } else if let Expr::Literal(Literal::Integer(n)) = &end.node {
                     *n
                 } else {
-                    // Non-constant loop bound with no `bounded` annotation.
-                    // Default to 1 iteration but record as a warning so
-                    // report.rs can flag it via H0004.
-                    self.loop_bound_waste.push((
-                        self.in_progress.last().cloned().unwrap_or_default(),
-                        1, // assumed iterations
-                        0, // no declared bound (0 signals "unknown")
-                    ));
-                    1
+                    // Non-constant loop bound with no `bounded` annotation
+                    // and no size parameter: default to 1 iteration;
+                    // scan_loop_bound_waste flags the loop via H0004.
+                    self.size_param(&end.node).unwrap_or(1)
                 };
                 // Per-iteration: body + loop overhead (dup, check, decrement, recurse).
                 let per_iter = body_cost.add(&self.cost_model.loop_overhead());
@@ -325,12 +662,13 @@ impl<'a> CostAnalyzer<'a> {
                 for (_name, val) in fields {
                     cost = cost.add(&self.cost_expr(&val.node));
                 }
-                let padding = 9usize.saturating_sub(fields.len());
+                let rate = self.hash_rate as usize;
+                let padding = (rate - 1).saturating_sub(fields.len());
                 for _ in 0..padding {
                     cost = cost.add(&stack_op); // push 0 padding
                 }
-                // hash (one per 10 elements; extra hashes if >9 fields)
-                let hash_count = (1 + fields.len()).div_ceil(10);
+                // hash (one per `rate` elements; extra hashes past a full block)
+                let hash_count = (1 + fields.len()).div_ceil(rate);
                 for _ in 0..hash_count {
                     cost = cost.add(&self.cost_model.builtin_cost("hash"));
                 }
@@ -341,3 +679,15 @@ impl<'a> CostAnalyzer<'a> {
         }
     }
 }
+
+/// The argument of a `sponge_absorb_field` call.
+fn absorbed_field(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::Call { path, args, .. } if args.len() == 1 => {
+            let name = path.node.as_dotted();
+            let base = name.rsplit('.').next().unwrap_or(&name);
+            (base == "sponge_absorb_field").then(|| &args[0].node)
+        }
+        _ => None,
+    }
+}
@@ -52,7 +52,11 @@ impl ProgramCost {
             "  \"total\": {},\n",
             self.total.to_json_value(&names)
         ));
-        out.push_str(&format!("  \"padded_height\": {}\n", self.padded_height));
+        out.push_str(&format!("  \"padded_height\": {},\n", self.padded_height));
+        out.push_str(&format!(
+            "  \"compiler\": {}\n",
+            crate::build_info().to_json()
+        ));
         out.push_str("}\n");
         out
     }
@@ -169,6 +173,7 @@ impl ProgramCost {
             padded_height,
             estimated_proving_ns: 0,
             loop_bound_waste: Vec::new(),
+            entries: Vec::new(),
         })
     }
 
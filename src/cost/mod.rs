@@ -12,8 +12,10 @@ pub mod stack_verifier;
 mod visit;
 
 // Public re-exports
-pub use analyzer::ProgramCost;
-pub use model::TableCost;
+pub use analyzer::{
+    EntryCost, LoopBoundWaste, ModuleCost, OversizedFunction, ProgramCost, ProjectCost,
+};
+pub use model::{has_cost_model, TableCost};
 
 // Crate-internal re-exports
 pub(crate) use analyzer::CostAnalyzer;
@@ -24,6 +26,7 @@ pub(crate) use visit::next_power_of_two;
 mod tests {
     use super::*;
     use crate::cost::analyzer::FunctionCost;
+    use crate::cost::model::CostModel;
     use crate::lexer::Lexer;
     use crate::parser::Parser;
 
@@ -67,6 +70,7 @@ mod tests {
             padded_height,
             estimated_proving_ns: 0,
             loop_bound_waste: Vec::new(),
+            entries: Vec::new(),
         }
     }
 
@@ -120,6 +124,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_digest_equality_costs_every_element() {
+        let field = analyze(
+            "program test\nfn main() {\n    let a: Field = divine()\n    assert(a == a)\n}",
+        );
+        let digest = analyze(
+            "program test\nfn main() {\n    let a: Digest = divine5()\n    assert(a == a)\n}",
+        );
+        let model = model::TritonCostModel;
+        assert_eq!(model.eq_cost(1), model.binop_cost(&crate::ast::BinOp::Eq));
+        // dup, eq, 4 x (swap, dup, eq, mul), swap, pop
+        assert_eq!(model.eq_cost(5).get(0), 20);
+        assert!(digest.total.get(0) > field.total.get(0) + 15);
+    }
+
     #[test]
     fn test_loop_cost_multiplied() {
         let cost = analyze(
@@ -149,6 +168,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_if_expression_costs_worst_branch() {
+        // The else branch hashes, so its cost is charged even though the
+        // then branch is a plain literal.
+        let cost = analyze(
+            "program test\nfn main() {\n    let x: Field = pub_read()\n    let d: Digest = divine5()\n    let (h0, _, _, _, _) = hash(d)\n    let y: Field = if x == 0 { 1 } else { let (a, _, _, _, _) = hash(d)\n a }\n    pub_write(h0 + y)\n}",
+        );
+        assert!(
+            cost.total.get(1) >= 12,
+            "else-branch hash cost should be included, got {}",
+            cost.total.get(1)
+        );
+    }
+
+    #[test]
+    fn test_short_circuit_costs_branch_overhead_only_when_effectful() {
+        let pure = analyze(
+            "program test\nfn main() {\n    let a: Field = pub_read()\n    assert(a == 1 && a == 2)\n}",
+        );
+        let effectful = analyze(
+            "program test\nfn ok(x: Field) -> Bool {\n    x == 2\n}\nfn main() {\n    let a: Field = pub_read()\n    assert(a == 1 && ok(a))\n}",
+        );
+        // The effectful form adds the call to ok() and the skiz branch on
+        // top of the jump stack rows both pay for the program entry.
+        let extra_jumps = effectful.total.get(5) - pure.total.get(5);
+        assert!(
+            extra_jumps >= 3,
+            "short-circuit branch should add jump stack rows, got {}",
+            extra_jumps
+        );
+    }
+
     #[test]
     fn test_function_call_cost() {
         let cost = analyze(
@@ -262,6 +313,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hand_written_main_is_the_only_entry() {
+        let cost = analyze("program test\nfn main() {\n    pub_write(pub_read())\n}");
+        assert_eq!(cost.entries.len(), 1);
+        assert_eq!(cost.entries[0].name, "main");
+        assert_eq!(cost.entries[0].tag, None);
+        assert_eq!(cost.entries[0].cost, cost.total);
+        assert_eq!(cost.entries[0].padded_height, cost.padded_height);
+    }
+
     #[test]
     fn test_h0004_loop_bound_waste() {
         // Loop with bound 128 but only 10 iterations — should warn
@@ -288,6 +349,18 @@ mod tests {
         assert!(!h0004, "should not warn when bound is close to end");
     }
 
+    #[test]
+    fn test_h0004_fix_rewrites_bound_literal() {
+        let source = "program test\nfn main() {\n    for i in 0..10 bounded 128 {\n        pub_write(0)\n    }\n}";
+        let fixes = analyze(source).loop_bound_fixes(source);
+        assert_eq!(fixes.len(), 1);
+        let suggestion = &fixes[0].suggestions()[0];
+        let span = suggestion.span;
+        assert_eq!(&source[span.start as usize..span.end as usize], "128");
+        assert_eq!(suggestion.replacement, "16");
+        assert_eq!(fixes[0].span, span);
+    }
+
     #[test]
     fn test_asm_block_cost() {
         let cost = analyze(
@@ -451,4 +524,69 @@ mod tests {
             "should contain padded height"
         );
     }
+
+    #[test]
+    fn test_miden_costs_use_miden_tables() {
+        let source = "program test\nfn main() {\n    let x: Field = pub_read()\n    let d: Digest = hash(x, x, x, x, x, x, x, x, x, x)\n    pub_write(x)\n}";
+        let (tokens, _, _) = Lexer::new(source, 0).tokenize();
+        let file = Parser::new(tokens).parse_file().unwrap();
+        let triton = CostAnalyzer::for_target("triton").analyze_file(&file);
+        let miden = CostAnalyzer::for_target("miden").analyze_file(&file);
+
+        assert_eq!(miden.table_names, vec!["processor", "hash", "chiplets", "stack"]);
+        assert_eq!(miden.total.count, 4);
+        // Rescue-Prime permutations occupy 8 hasher rows, Tip5 uses 6.
+        assert_eq!(miden.total.get(1), 8);
+        assert_eq!(triton.total.get(1), 6);
+        assert_ne!(miden.total, triton.total);
+    }
+
+    #[test]
+    fn test_bitwise_ops_fill_miden_chiplets_table() {
+        let source = "program test\nfn main() {\n    let a: U32 = as_u32(pub_read())\n    let b: U32 = a & a\n    pub_write(as_field(b))\n}";
+        let (tokens, _, _) = Lexer::new(source, 0).tokenize();
+        let file = Parser::new(tokens).parse_file().unwrap();
+        let cost = CostAnalyzer::for_target("miden").analyze_file(&file);
+        assert_eq!(cost.total.get(2), 8, "one u32and = 8 bitwise rows");
+    }
+
+    #[test]
+    fn test_attestation_uses_config_hash_rate() {
+        let source = "program test\nfn main() {\n    pub_write(pub_read())\n}";
+        let (tokens, _, _) = Lexer::new(source, 0).tokenize();
+        let file = Parser::new(tokens).parse_file().unwrap();
+        let mut config = crate::config::target::TerrainConfig::triton();
+        config.hash_rate = 1;
+        let narrow = CostAnalyzer::for_config(&config).analyze_file(&file);
+        let default = CostAnalyzer::default().analyze_file(&file);
+        assert_eq!(narrow.attestation_hash_rows, 10 * default.attestation_hash_rows);
+    }
+
+    #[test]
+    fn test_folded_pure_call_costs_its_pushes() {
+        let pow2 = "program test\n#[pure]\nfn pow2(n: U32) -> Field {\n    let mut acc: Field = 1\n    for i in 0..n bounded 64 {\n        acc = acc * 2\n    }\n    acc\n}\n";
+        let folded = analyze(&format!(
+            "{}fn main() {{\n    pub_write(pow2(10))\n}}",
+            pow2
+        ));
+        let runtime = analyze(&format!(
+            "{}fn main() {{\n    pub_write(pow2(as_u32(pub_read())))\n}}",
+            pow2
+        ));
+        let main_cost = |pc: &ProgramCost| {
+            pc.functions
+                .iter()
+                .find(|f| f.name == "main")
+                .map(|f| f.cost.get(0))
+                .unwrap()
+        };
+        assert!(main_cost(&folded) < main_cost(&runtime) / 4);
+    }
+
+    #[test]
+    fn test_unknown_target_has_no_cost_model() {
+        assert!(has_cost_model("triton"));
+        assert!(has_cost_model("miden"));
+        assert!(!has_cost_model("sp1"));
+    }
 }
@@ -0,0 +1,182 @@
+use super::{CostModel, TableCost};
+use crate::ast::BinOp;
+
+// ---------------------------------------------------------------------------
+// MidenCostModel — Miden VM's processor + chiplet cost model
+// ---------------------------------------------------------------------------
+
+/// Miden VM cost model with 4 trace segments.
+///
+/// The processor column counts VM cycles. Hashing runs in the hasher
+/// chiplet (8 rows per Rescue-Prime permutation), bitwise and memory
+/// operations add rows to the shared chiplets segment, and values pushed
+/// past the 16-element operand stack land in the stack overflow table.
+pub(crate) struct MidenCostModel;
+
+/// Number of active tables for Miden VM.
+const N: u8 = 4;
+
+/// Build a Miden TableCost from a 4-element array.
+const fn tc(v: [u64; 4]) -> TableCost {
+    TableCost {
+        values: [v[0], v[1], v[2], v[3], 0, 0, 0, 0],
+        count: N,
+    }
+}
+
+impl MidenCostModel {
+    /// Hasher chiplet rows per Rescue-Prime permutation.
+    const HASH_ROWS: u64 = 8;
+
+    /// Bitwise chiplet rows per 32-bit AND/XOR (one row per 4-bit limb).
+    const BITWISE_ROWS: u64 = 8;
+
+    //                              proc  hash  chip  stack
+    const SIMPLE_OP: TableCost = tc([1, 0, 0, 0]);
+    const PUSH_OP: TableCost = tc([1, 0, 0, 1]);
+    const ASSERT2: TableCost = tc([2, 0, 0, 0]);
+    const HASH_OP: TableCost = tc([10, Self::HASH_ROWS, 0, 0]);
+    const BITWISE_OP: TableCost = tc([1, 0, Self::BITWISE_ROWS, 0]);
+    const MEM_RW: TableCost = tc([1, 0, 1, 0]);
+    const MEM_BLOCK_RW: TableCost = tc([3, 0, 2, 0]);
+}
+
+impl CostModel for MidenCostModel {
+    fn table_names(&self) -> &[&str] {
+        &["processor", "hash", "chiplets", "stack"]
+    }
+
+    fn table_short_names(&self) -> &[&str] {
+        &["cc", "hash", "chip", "stack"]
+    }
+
+    fn builtin_cost(&self, name: &str) -> TableCost {
+        match name {
+            // I/O — one cycle per element moved on or off the operand stack
+            "pub_read" => Self::PUSH_OP,
+            "pub_read2" => tc([2, 0, 0, 2]),
+            "pub_read3" => tc([3, 0, 0, 3]),
+            "pub_read4" => tc([4, 0, 0, 4]),
+            "pub_read5" => tc([5, 0, 0, 5]),
+            "pub_write" => Self::SIMPLE_OP,
+            "pub_write2" => tc([2, 0, 0, 0]),
+            "pub_write3" => tc([3, 0, 0, 0]),
+            "pub_write4" => tc([4, 0, 0, 0]),
+            "pub_write5" => tc([5, 0, 0, 0]),
+
+            // Non-deterministic input (adv_push.n)
+            "divine" => Self::PUSH_OP,
+            "divine3" => tc([3, 0, 0, 3]),
+            "divine5" => tc([5, 0, 0, 5]),
+
+            // Assertions
+            "assert" | "assume" => Self::SIMPLE_OP,
+            "assert_eq" => Self::ASSERT2,
+            "assert_digest" => tc([11, 0, 0, 0]),
+            // eq.0, if.true (the failure branch is not taken)
+            "ensure" => Self::ASSERT2,
+
+            // Field ops
+            "field_add" => Self::SIMPLE_OP,
+            "field_mul" => Self::SIMPLE_OP,
+            "inv" => Self::SIMPLE_OP,
+            // dup, push 0, eq, push 0, eq, assert, inv (, mul)
+            "checked_inv" => tc([7, 0, 0, 3]),
+            "checked_div" => tc([8, 0, 0, 3]),
+            "neg" => Self::SIMPLE_OP,
+            "sub" => Self::ASSERT2,
+
+            // U32 ops run in the processor; range checks are free
+            "split" => Self::SIMPLE_OP,
+            "log2" => tc([40, 0, 0, 0]),
+            "pow" => tc([41, 0, 0, 0]),
+            "popcount" => tc([33, 0, 0, 0]),
+
+            // Hash ops (8 hasher rows per permutation)
+            "hash" => Self::HASH_OP,
+            "sponge_init" => tc([3, 0, 0, 0]),
+            "sponge_absorb" => tc([3, Self::HASH_ROWS, 0, 0]),
+            "sponge_squeeze" => tc([9, Self::HASH_ROWS, 0, 0]),
+            "sponge_absorb_mem" => tc([2, Self::HASH_ROWS, 2, 0]),
+
+            // Merkle
+            "merkle_step" => tc([9, Self::HASH_ROWS, 0, 0]),
+            "merkle_step_mem" => tc([11, Self::HASH_ROWS, 1, 0]),
+            "merkle_walk" => tc([9, Self::HASH_ROWS, 0, 0]),
+            "merkle_walk_mem" => tc([11, Self::HASH_ROWS, 1, 0]),
+
+            // RAM (memory chiplet, one row per access)
+            "ram_read" => Self::MEM_RW,
+            "ram_write" => Self::MEM_RW,
+            "ram_read_block" => Self::MEM_BLOCK_RW,
+            "ram_write_block" => Self::MEM_BLOCK_RW,
+
+            // Conversions
+            "as_u32" => Self::SIMPLE_OP,
+            "as_field" => TableCost::ZERO,
+
+            // No extension field on this target
+            "xx_dot_step" | "xb_dot_step" | "xfield" | "xinvert" => TableCost::ZERO,
+
+            _ => TableCost::ZERO,
+        }
+    }
+
+    fn binop_cost(&self, op: &BinOp) -> TableCost {
+        match op {
+            BinOp::Add => Self::SIMPLE_OP,
+            BinOp::Mul => Self::SIMPLE_OP,
+            BinOp::Eq => Self::SIMPLE_OP,
+            BinOp::Lt => tc([3, 0, 0, 0]),
+            BinOp::BitAnd => Self::BITWISE_OP,
+            BinOp::BitXor => Self::BITWISE_OP,
+            BinOp::And => Self::SIMPLE_OP,
+            // add, push 0, eq, push 0, eq
+            BinOp::Or => tc([5, 0, 0, 2]),
+            BinOp::DivMod => tc([2, 0, 0, 0]),
+            BinOp::XFieldMul => TableCost::ZERO,
+        }
+    }
+
+    fn eq_cost(&self, width: u32) -> TableCost {
+        if width <= 1 {
+            return Self::SIMPLE_OP;
+        }
+        // dup, eq; then swap, dup, eq, mul per further pair; then swap
+        // and drop the left operand.
+        let w = width as u64;
+        tc([5 * w - 1, 0, 0, 0])
+    }
+
+    fn call_overhead(&self) -> TableCost {
+        tc([2, 0, 0, 0])
+    }
+
+    fn stack_op(&self) -> TableCost {
+        Self::PUSH_OP
+    }
+
+    fn if_overhead(&self) -> TableCost {
+        tc([3, 0, 0, 0])
+    }
+
+    fn loop_overhead(&self) -> TableCost {
+        tc([6, 0, 0, 1])
+    }
+
+    fn hash_rows_per_permutation(&self) -> u64 {
+        Self::HASH_ROWS
+    }
+
+    fn hash_rate(&self) -> u64 {
+        8
+    }
+
+    fn trace_column_count(&self) -> u64 {
+        80
+    }
+
+    fn max_padded_height(&self) -> u64 {
+        1 << 20
+    }
+}
@@ -1,7 +1,9 @@
+pub mod miden;
 pub mod triton;
 
 use crate::ast::BinOp;
 
+pub(crate) use miden::MidenCostModel;
 pub(crate) use triton::TritonCostModel;
 
 // ---------------------------------------------------------------------------
@@ -29,6 +31,10 @@ pub(crate) trait CostModel {
     /// Cost of a binary operation.
     fn binop_cost(&self, op: &BinOp) -> TableCost;
 
+    /// Cost of `==` on operands `width` elements wide. Wider operands are
+    /// compared pairwise and the results multiplied together.
+    fn eq_cost(&self, width: u32) -> TableCost;
+
     /// Overhead cost for a function call/return pair.
     fn call_overhead(&self) -> TableCost;
 
@@ -44,8 +50,15 @@ pub(crate) trait CostModel {
     /// Number of hash table rows per hash permutation.
     fn hash_rows_per_permutation(&self) -> u64;
 
+    /// Number of field elements absorbed per hash permutation.
+    fn hash_rate(&self) -> u64;
+
     /// Number of trace columns (used for proving time estimation).
     fn trace_column_count(&self) -> u64;
+
+    /// Largest padded height that is practical to prove on this target.
+    /// `trident check --costs` flags functions that exceed it on their own.
+    fn max_padded_height(&self) -> u64;
 }
 
 // ---------------------------------------------------------------------------
@@ -225,12 +238,19 @@ pub(crate) fn cost_builtin(target: &str, name: &str) -> TableCost {
     create_cost_model(target).builtin_cost(name)
 }
 
+/// Whether a dedicated cost model exists for the named target.
+pub fn has_cost_model(target_name: &str) -> bool {
+    matches!(target_name, "triton" | "miden")
+}
+
 /// Select the cost model for a given target name.
 ///
-/// Falls back to Triton for unknown targets (currently the only model).
+/// Falls back to Triton for targets without a model; callers that report
+/// costs should check `has_cost_model` and say so.
 pub(crate) fn create_cost_model(target_name: &str) -> &'static dyn CostModel {
     match target_name {
         "triton" => &TritonCostModel,
+        "miden" => &MidenCostModel,
         _ => &TritonCostModel,
     }
 }
@@ -27,10 +27,14 @@ impl TritonCostModel {
     const SIMPLE_OP: TableCost = tc([1, 0, 0, 1, 0, 0]);
     const U32_OP: TableCost = tc([1, 0, 33, 1, 0, 0]);
     const U32_NOSTACK: TableCost = tc([1, 0, 33, 0, 0, 0]);
+    /// `swap 1` first: the instruction takes its first operand from the top.
+    const U32_SWAPPED: TableCost = tc([2, 0, 33, 1, 0, 0]);
+    const U32_SWAPPED_NOSTACK: TableCost = tc([2, 0, 33, 0, 0, 0]);
     const HASH_OP: TableCost = tc([1, 6, 0, 1, 0, 0]);
     const ASSERT2: TableCost = tc([2, 0, 0, 2, 0, 0]);
-    const RAM_RW: TableCost = tc([2, 0, 0, 2, 1, 0]);
-    const RAM_BLOCK_RW: TableCost = tc([2, 0, 0, 2, 5, 0]);
+    const RAM_READ: TableCost = tc([2, 0, 0, 2, 1, 0]);
+    const RAM_WRITE: TableCost = tc([4, 0, 0, 4, 1, 0]);
+    const RAM_BLOCK_RW: TableCost = tc([4, 0, 0, 4, 5, 0]);
     const PURE_PROC: TableCost = tc([1, 0, 0, 0, 0, 0]);
 }
 
@@ -55,21 +59,26 @@ impl CostModel for TritonCostModel {
             "divine" | "divine3" | "divine5" => Self::SIMPLE_OP,
 
             // Assertions
-            "assert" => Self::SIMPLE_OP,
+            "assert" | "assume" => Self::SIMPLE_OP,
             "assert_eq" => Self::ASSERT2,
             "assert_digest" => Self::ASSERT2,
+            // push 0, eq, skiz (the failure branch is not taken)
+            "ensure" => tc([3, 0, 0, 3, 0, 0]),
 
             // Field ops
             "field_add" => Self::SIMPLE_OP,
             "field_mul" => Self::SIMPLE_OP,
             "inv" => Self::PURE_PROC,
+            // dup 0, push 0, eq, push 0, eq, assert, invert (, mul)
+            "checked_inv" => tc([7, 0, 0, 6, 0, 0]),
+            "checked_div" => tc([8, 0, 0, 7, 0, 0]),
             "neg" => tc([2, 0, 0, 1, 0, 0]),
             "sub" => tc([3, 0, 0, 2, 0, 0]),
 
             // U32 ops
             "split" => Self::U32_OP,
             "log2" => Self::U32_NOSTACK,
-            "pow" => Self::U32_OP,
+            "pow" => Self::U32_SWAPPED,
             "popcount" => Self::U32_NOSTACK,
 
             // Hash ops (6 hash table rows each for Tip5 permutation)
@@ -81,11 +90,15 @@ impl CostModel for TritonCostModel {
 
             // Merkle
             "merkle_step" => tc([1, 6, Self::U32_WORST, 0, 0, 0]),
-            "merkle_step_mem" => tc([1, 6, Self::U32_WORST, 0, 5, 0]),
+            // Slides a spare element under the pointer and drops it after.
+            "merkle_step_mem" => tc([5, 6, Self::U32_WORST, 2, 5, 0]),
+            // Per level; walks add the spare and loop overhead around them.
+            "merkle_walk" => tc([1, 6, Self::U32_WORST, 0, 0, 0]),
+            "merkle_walk_mem" => tc([1, 6, Self::U32_WORST, 0, 5, 0]),
 
             // RAM
-            "ram_read" => Self::RAM_RW,
-            "ram_write" => Self::RAM_RW,
+            "ram_read" => Self::RAM_READ,
+            "ram_write" => Self::RAM_WRITE,
             "ram_read_block" => Self::RAM_BLOCK_RW,
             "ram_write_block" => Self::RAM_BLOCK_RW,
 
@@ -94,7 +107,7 @@ impl CostModel for TritonCostModel {
             "xb_dot_step" => tc([1, 0, 0, 0, 4, 0]),
 
             // Conversions
-            "as_u32" => tc([2, 0, Self::U32_WORST, 1, 0, 0]),
+            "as_u32" => tc([5, 0, Self::U32_WORST, 5, 0, 0]),
             "as_field" => TableCost::ZERO,
 
             // XField
@@ -110,14 +123,28 @@ impl CostModel for TritonCostModel {
             BinOp::Add => Self::SIMPLE_OP,
             BinOp::Mul => Self::SIMPLE_OP,
             BinOp::Eq => Self::SIMPLE_OP,
-            BinOp::Lt => Self::U32_OP,
+            BinOp::Lt => Self::U32_SWAPPED,
             BinOp::BitAnd => Self::U32_OP,
             BinOp::BitXor => Self::U32_OP,
-            BinOp::DivMod => Self::U32_NOSTACK,
+            BinOp::And => Self::SIMPLE_OP,
+            // add, push 0, eq, push 0, eq
+            BinOp::Or => tc([5, 0, 0, 5, 0, 0]),
+            BinOp::DivMod => Self::U32_SWAPPED_NOSTACK,
             BinOp::XFieldMul => Self::SIMPLE_OP,
         }
     }
 
+    fn eq_cost(&self, width: u32) -> TableCost {
+        if width <= 1 {
+            return Self::SIMPLE_OP;
+        }
+        // dup, eq; then swap 1, dup, eq, mul per further pair; then
+        // swap and pop the left operand in batches of 5.
+        let w = width as u64;
+        let pops = w.div_ceil(5);
+        tc([4 * w - 1 + pops, 0, 0, 3 * w - 1 + pops, 0, 0])
+    }
+
     fn call_overhead(&self) -> TableCost {
         tc([2, 0, 0, 0, 0, 2])
     }
@@ -131,14 +158,26 @@ impl CostModel for TritonCostModel {
     }
 
     fn loop_overhead(&self) -> TableCost {
-        tc([8, 0, 0, 4, 0, 1])
+        // Counter test and decrement, recurse, and advancing the loop
+        // variable under the counter (swap 1, push 1, add, swap 1).
+        tc([12, 0, 0, 6, 0, 1])
     }
 
     fn hash_rows_per_permutation(&self) -> u64 {
         6
     }
 
+    fn hash_rate(&self) -> u64 {
+        10
+    }
+
     fn trace_column_count(&self) -> u64 {
         300
     }
+
+    fn max_padded_height(&self) -> u64 {
+        // 2^22 rows: beyond this, proving needs more memory than a
+        // commodity machine has.
+        1 << 22
+    }
 }
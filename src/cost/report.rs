@@ -1,6 +1,6 @@
-use super::analyzer::{FunctionCost, ProgramCost};
+use super::analyzer::{FunctionCost, LoopBoundWaste, ProgramCost, ProjectCost};
 use super::visit::next_power_of_two;
-use crate::diagnostic::Diagnostic;
+use crate::diagnostic::{Diagnostic, Suggestion};
 use crate::span::Span;
 
 // --- Report formatting ---
@@ -197,43 +197,166 @@ impl ProgramCost {
 
         // H0004: Loop bound waste (entries already filtered at 4x+ in analyzer)
         // Also handles unknown-bound entries (bound == 0) from non-constant loops.
-        for (fn_name, end_val, bound) in &self.loop_bound_waste {
-            if *bound == 0 {
-                // Non-constant loop end with no `bounded` annotation
-                let mut diag = Diagnostic::warning(
-                    format!(
-                        "hint[H0004]: loop in '{}' has non-constant bound, cost assumes {} iteration(s)",
-                        fn_name, end_val
-                    ),
-                    Span::dummy(),
-                );
-                diag.help = Some(
+        hints.extend(self.loop_bound_waste.iter().map(LoopBoundWaste::hint));
+
+        hints
+    }
+}
+
+impl ProgramCost {
+    /// H0004 hints that `source` can fix: a `bounded N` to tighten, or a
+    /// missing bound over a named constant. Each points at the edit and
+    /// carries it as a suggestion. `source` must be the analyzed file.
+    pub fn loop_bound_fixes(&self, source: &str) -> Vec<Diagnostic> {
+        self.loop_bound_waste
+            .iter()
+            .filter_map(|waste| {
+                let suggestion = waste.suggestion(source)?;
+                let mut diag = waste.hint();
+                diag.span = suggestion.span;
+                Some(diag.with_suggestion(suggestion))
+            })
+            .collect()
+    }
+}
+
+impl LoopBoundWaste {
+    /// The bound the H0004 hint proposes: the constant end rounded up to
+    /// a power of two, or the named constant's value for a missing bound.
+    pub fn suggested_bound(&self) -> u64 {
+        match self.known_end {
+            Some(end) if self.bound == 0 => end,
+            _ => next_power_of_two(self.end_value),
+        }
+    }
+
+    /// Span of `N` in the loop's `bounded N`, located in `source`.
+    pub fn bound_literal_span(&self, source: &str) -> Option<Span> {
+        let (start, end) = (self.span.start as usize, self.span.end as usize);
+        let header = source.get(start..end)?;
+        let after = header.rfind("bounded")? + "bounded".len();
+        let digits = after + header[after..].len() - header[after..].trim_start().len();
+        let len = header[digits..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(header.len() - digits);
+        if header[digits..digits + len].parse::<u64>().ok()? != self.bound {
+            return None;
+        }
+        let lit = (start + digits) as u32;
+        Some(Span::new(self.span.file_id, lit, lit + len as u32))
+    }
+
+    /// Rewrite `bounded N` to the suggested bound, or insert `bounded N`
+    /// after a named-constant end. `None` when no bound can be derived
+    /// or `source` does not match.
+    pub fn suggestion(&self, source: &str) -> Option<Suggestion> {
+        let bound = self.suggested_bound();
+        if self.bound == 0 {
+            self.known_end?;
+            let header = source.get(self.span.start as usize..self.span.end as usize)?;
+            let at = self.span.start + header.trim_end().len() as u32;
+            return Some(Suggestion {
+                message: format!("add `bounded {}`", bound),
+                span: Span::new(self.span.file_id, at, at),
+                replacement: format!(" bounded {}", bound),
+            });
+        }
+        Some(Suggestion {
+            message: format!("change `bounded {}` to `bounded {}`", self.bound, bound),
+            span: self.bound_literal_span(source)?,
+            replacement: bound.to_string(),
+        })
+    }
+
+    fn hint(&self) -> Diagnostic {
+        if self.bound == 0 {
+            // Non-constant loop end with no `bounded` annotation
+            let diag = Diagnostic::warning(
+                format!(
+                    "hint[H0004]: loop in '{}' has non-constant bound, cost assumes {} iteration(s)",
+                    self.fn_name, self.end_value
+                ),
+                self.span,
+            );
+            return match self.known_end {
+                Some(end) => diag.with_help(format!(
+                    "add `bounded {}` so the cost covers every iteration",
+                    end
+                )),
+                None => diag.with_help(
                     "add a `bounded N` annotation to set a realistic worst-case iteration count"
                         .to_string(),
+                ),
+            };
+        }
+        let ratio = self.bound / self.end_value.max(1);
+        Diagnostic::warning(
+            format!(
+                "hint[H0004]: loop in '{}' bounded {} but iterates only {} times",
+                self.fn_name, self.bound, self.end_value
+            ),
+            self.span,
+        )
+        .with_note(format!(
+            "declared bound is {}x the actual iteration count",
+            ratio
+        ))
+        .with_help(format!(
+            "tightening the bound to {} would reduce worst-case cost",
+            self.suggested_bound()
+        ))
+    }
+}
+
+impl ProjectCost {
+    /// One row per module, then a warning for each function over the
+    /// proving limit with its suggested split points.
+    pub fn format_report(&self) -> String {
+        let width = self
+            .modules
+            .iter()
+            .map(|m| m.module.len())
+            .max()
+            .unwrap_or(0)
+            .max("Module".len());
+        let mut out = format!(
+            "Module costs (limit: padded height {} on {})\n",
+            self.max_padded_height, self.target
+        );
+        out.push_str(&format!(
+            "{:<width$}  {:>9}  {:>9}  heaviest\n",
+            "Module", "functions", "rows"
+        ));
+        for m in &self.modules {
+            let heaviest = match &m.heaviest {
+                Some((name, padded)) => format!("{} (padded {})", name, padded),
+                None => "-".to_string(),
+            };
+            out.push_str(&format!(
+                "{:<width$}  {:>9}  {:>9}  {}\n",
+                m.module,
+                m.functions,
+                m.total.max_height(),
+                heaviest
+            ));
+        }
+        for f in &self.oversized {
+            out.push_str(&format!(
+                "\nwarning: {}.{} pads to {} rows, over the limit of {}\n",
+                f.module, f.function, f.padded_height, self.max_padded_height
+            ));
+            if f.split_points.is_empty() {
+                out.push_str(
+                    "  a single statement is over the limit; split the loop or call inside it\n",
                 );
-                hints.push(diag);
-            } else {
-                let actual = *end_val.max(&1);
-                let ratio = *bound / actual;
-                let mut diag = Diagnostic::warning(
-                    format!(
-                        "hint[H0004]: loop in '{}' bounded {} but iterates only {} times",
-                        fn_name, bound, end_val
-                    ),
-                    Span::dummy(),
-                );
-                diag.notes.push(format!(
-                    "declared bound is {}x the actual iteration count",
-                    ratio
-                ));
-                diag.help = Some(format!(
-                    "tightening the bound to {} would reduce worst-case cost",
-                    next_power_of_two(*end_val)
+            }
+            for (line, rows) in &f.split_points {
+                out.push_str(&format!(
+                    "  split before line {} ({} rows in the part before)\n",
+                    line, rows
                 ));
-                hints.push(diag);
             }
         }
-
-        hints
+        out
     }
 }
@@ -152,15 +152,12 @@ impl StackState {
                 self.stack.push(a.mul(b).to_u64());
             }
             "invert" => {
-                // BUG: this implements negation, but Triton VM invert is
-                // multiplicative inverse (1/x mod p). Kept as-is for
-                // baseline simulation; excluded from ALLOWED candidate list.
-                if self.stack.is_empty() {
+                // Multiplicative inverse; Triton VM fails on zero.
+                let Some(inverse) = self.stack.pop().and_then(|a| Goldilocks(a).inv()) else {
                     self.error = true;
                     return;
-                }
-                let a = Goldilocks(self.stack.pop().unwrap());
-                self.stack.push(a.neg().to_u64());
+                };
+                self.stack.push(inverse.to_u64());
             }
 
             // --- Comparison ---
@@ -174,12 +171,13 @@ impl StackState {
                 self.stack.push(if a == b { 1 } else { 0 });
             }
             "lt" => {
+                // (b, a) → a < b, with a on top
                 if self.stack.len() < 2 {
                     self.error = true;
                     return;
                 }
-                let b = self.stack.pop().unwrap();
                 let a = self.stack.pop().unwrap();
+                let b = self.stack.pop().unwrap();
                 self.stack.push(if a < b { 1 } else { 0 });
             }
 
@@ -215,13 +213,13 @@ impl StackState {
                 self.stack.push(lo);
             }
             "div_mod" => {
-                // (n, d) → (q, r) where q = n/d, r = n%d
+                // (d, n) → (q, r) where q = n/d, r = n%d, with n on top
                 if self.stack.len() < 2 {
                     self.error = true;
                     return;
                 }
-                let d = self.stack.pop().unwrap();
                 let n = self.stack.pop().unwrap();
+                let d = self.stack.pop().unwrap();
                 if d == 0 {
                     self.error = true;
                     return;
@@ -230,13 +228,13 @@ impl StackState {
                 self.stack.push(n % d);
             }
             "pow" => {
-                // (base, exp) → base^exp mod p
+                // (exp, base) → base^exp mod p, with base on top
                 if self.stack.len() < 2 {
                     self.error = true;
                     return;
                 }
-                let exp = self.stack.pop().unwrap();
                 let base = Goldilocks(self.stack.pop().unwrap());
+                let exp = self.stack.pop().unwrap();
                 let mut result = Goldilocks::ONE;
                 let mut b = base;
                 let mut e = exp;
@@ -455,8 +453,8 @@ pub fn verify_equivalent(baseline_tasm: &[String], candidate_tasm: &[String], se
         "place",
         "add",
         "mul",
-        // invert is NOT allowed — verifier implements negation,
-        // Triton VM does multiplicative inverse (1/x mod p).
+        // invert is NOT allowed: it fails on the zeros that the
+        // structured test stacks below hold.
         "eq",
         "lt",
         "and",
@@ -921,11 +919,33 @@ mod tests {
     #[test]
     fn pow_instruction() {
         let mut s = StackState::new(vec![]);
-        s.execute(&lines(&["push 2", "push 10", "pow"]));
+        s.execute(&lines(&["push 10", "push 2", "pow"]));
         assert!(s.is_valid());
         assert_eq!(s.stack, vec![1024]); // 2^10
     }
 
+    #[test]
+    fn invert_is_the_multiplicative_inverse() {
+        let mut s = StackState::new(vec![]);
+        s.execute(&lines(&["push 2", "invert", "push 2", "mul"]));
+        assert!(s.is_valid());
+        assert_eq!(s.stack, vec![1]);
+
+        let mut zero = StackState::new(vec![]);
+        zero.execute(&lines(&["push 0", "invert"]));
+        assert!(!zero.is_valid());
+    }
+
+    #[test]
+    fn lt_and_div_mod_take_the_top_as_first_operand() {
+        let mut s = StackState::new(vec![]);
+        s.execute(&lines(&[
+            "push 9", "push 2", "lt", "push 4", "push 11", "div_mod",
+        ]));
+        assert!(s.is_valid());
+        assert_eq!(s.stack, vec![1, 2, 3]); // 2 < 9, 11 = 2 * 4 + 3
+    }
+
     #[test]
     fn pop_count_instruction() {
         let mut s = StackState::new(vec![0b1010_1010]);
@@ -1,6 +1,9 @@
-use super::analyzer::CostAnalyzer;
+use std::collections::BTreeMap;
+
+use super::analyzer::{CostAnalyzer, LoopBoundWaste};
 use super::model::TableCost;
 use crate::ast::*;
+use crate::span::{Span, Spanned};
 
 // --- Per-function cost result ---
 
@@ -18,13 +21,38 @@ impl<'a> CostAnalyzer<'a> {
                 // dup instruction: 1 cc, 1 opstack.
                 stack_op
             }
+            Expr::BinOp { op, lhs, rhs }
+                if matches!(op, BinOp::And | BinOp::Or) && !rhs.node.is_pure() =>
+            {
+                self.cost_expr(&Expr::short_circuit(*op, lhs, rhs))
+            }
             Expr::BinOp { op, lhs, rhs } => {
                 let lhs_cost = self.cost_expr(&lhs.node);
                 let rhs_cost = self.cost_expr(&rhs.node);
-                lhs_cost.add(&rhs_cost).add(&self.cost_model.binop_cost(op))
+                let op_cost = match op {
+                    BinOp::Eq => self.cost_model.eq_cost(self.operand_width(&lhs.node)),
+                    _ => self.cost_model.binop_cost(op),
+                };
+                lhs_cost.add(&rhs_cost).add(&op_cost)
             }
-            Expr::Call { path, args, .. } => {
+            Expr::Call {
+                path,
+                generic_args,
+                args,
+            } => {
                 let fn_name = path.node.as_dotted();
+                if generic_args.is_empty() {
+                    let locals = &self.var_widths;
+                    let is_local = |var: &str| locals.contains_key(var);
+                    if let Some(values) =
+                        self.pure_fns
+                            .eval_call(&self.module_name, &fn_name, args, &is_local)
+                    {
+                        // Folded at compile time: one push per element.
+                        return (0..values.len())
+                            .fold(TableCost::ZERO, |acc, _| acc.add(&stack_op));
+                    }
+                }
                 let args_cost = args
                     .iter()
                     .fold(TableCost::ZERO, |acc, a| acc.add(&self.cost_expr(&a.node)));
@@ -32,6 +60,13 @@ impl<'a> CostAnalyzer<'a> {
                 // Check if it's a builtin — try full name first, then short name
                 // to handle cross-module calls like "hash.tip5" → "tip5" → "hash"
                 let base_name = fn_name.rsplit('.').next().unwrap_or(&fn_name);
+                if let "merkle_walk" | "merkle_walk_mem" = base_name {
+                    return self.merkle_walk_cost(base_name, args);
+                }
+                if base_name == "sponge_absorb_field" && args.len() == 1 {
+                    // On its own: a full block with one field.
+                    return args_cost.add(&self.packed_absorb_cost(1));
+                }
                 let fn_cost = {
                     let c = self.cost_model.builtin_cost(&fn_name);
                     if c.is_nonzero() {
@@ -59,10 +94,25 @@ impl<'a> CostAnalyzer<'a> {
                 // Evaluate inner struct + dup field elements.
                 self.cost_expr(&inner.node).add(&stack_op)
             }
-            Expr::Index { expr: inner, .. } => {
+            Expr::Index { expr: inner, index } => match &inner.node {
+                // `generate!` table: a push for a literal index, otherwise
+                // push base + add + read from its data segment.
+                Expr::Var(name)
+                    if !self.var_widths.contains_key(name)
+                        && self.pure_fns.table(&self.module_name, name).is_some() =>
+                {
+                    match index.node {
+                        Expr::Literal(Literal::Integer(_)) => stack_op,
+                        _ => self
+                            .cost_expr(&index.node)
+                            .add(&stack_op)
+                            .add(&self.cost_model.binop_cost(&BinOp::Add))
+                            .add(&self.cost_model.builtin_cost("ram_read")),
+                    }
+                }
                 // Evaluate inner array + dup indexed element.
-                self.cost_expr(&inner.node).add(&stack_op)
-            }
+                _ => self.cost_expr(&inner.node).add(&stack_op),
+            },
             Expr::StructInit { fields, .. } => {
                 fields.iter().fold(TableCost::ZERO, |acc, (_, val)| {
                     acc.add(&self.cost_expr(&val.node))
@@ -74,6 +124,107 @@ impl<'a> CostAnalyzer<'a> {
             Expr::Tuple(elems) => elems
                 .iter()
                 .fold(TableCost::ZERO, |acc, e| acc.add(&self.cost_expr(&e.node))),
+            Expr::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                let cond_cost = self.cost_expr(&cond.node);
+                let then_cost = self.cost_block(&then_block.node);
+                let else_cost = self.cost_block(&else_block.node);
+                // Worst case: max of then/else branches.
+                cond_cost
+                    .add(&then_cost.max(&else_cost))
+                    .add(&self.cost_model.if_overhead())
+            }
+        }
+    }
+
+    /// A Merkle walk: the per-level cost `depth` times. A literal depth is
+    /// unrolled; any other runs a loop of at most `MAX_MERKLE_DEPTH`.
+    fn merkle_walk_cost(&mut self, name: &str, args: &[Spanned<Expr>]) -> TableCost {
+        let stack_op = self.cost_model.stack_op();
+        let (operands, levels, per_level) = match args.split_last() {
+            Some((depth, rest)) => match depth.node {
+                Expr::Literal(Literal::Integer(n)) => (rest, n, TableCost::ZERO),
+                // place, step, pick, plus the loop's own bookkeeping.
+                _ => (
+                    args,
+                    MAX_MERKLE_DEPTH,
+                    self.cost_model.loop_overhead().add(&stack_op.scale(2)),
+                ),
+            },
+            None => (args, 0, TableCost::ZERO),
+        };
+        let mut cost = operands
+            .iter()
+            .fold(TableCost::ZERO, |acc, a| acc.add(&self.cost_expr(&a.node)));
+        let step = self.cost_model.builtin_cost(name).add(&per_level);
+        cost = cost.add(&step.scale(levels));
+        if name == "merkle_walk_mem" {
+            // The spare element under the pointer: push, place, pick, pop.
+            cost = cost.add(&stack_op.scale(4));
+        }
+        cost
+    }
+
+    /// Width in field elements of an `==` operand, as far as the syntax
+    /// shows it: aggregates, parameters, annotated locals, struct fields,
+    /// and calls to functions of this file. Anything else counts as 1.
+    pub(crate) fn operand_width(&self, expr: &Expr) -> u32 {
+        match expr {
+            Expr::Var(name) => self.var_widths.get(name).copied().unwrap_or(1),
+            Expr::ArrayInit(elems) | Expr::Tuple(elems) => {
+                elems.iter().map(|e| self.operand_width(&e.node)).sum()
+            }
+            Expr::StructInit { path, fields } => path
+                .node
+                .0
+                .last()
+                .and_then(|name| self.struct_widths.get(name).copied())
+                .unwrap_or_else(|| {
+                    fields
+                        .iter()
+                        .map(|(_, v)| self.operand_width(&v.node))
+                        .sum()
+                }),
+            Expr::Call { path, .. } => {
+                let name = path.node.as_dotted();
+                let base = name.rsplit('.').next().unwrap_or(&name);
+                self.fn_bodies
+                    .get(base)
+                    .and_then(|f| f.return_ty.as_ref())
+                    .map_or(1, |ty| self.type_width(&ty.node))
+            }
+            Expr::If { then_block, .. } => then_block
+                .node
+                .tail_expr
+                .as_ref()
+                .map_or(1, |tail| self.operand_width(&tail.node)),
+            Expr::Literal(_)
+            | Expr::BinOp { .. }
+            | Expr::FieldAccess { .. }
+            | Expr::Index { .. } => 1,
+        }
+    }
+
+    pub(crate) fn type_width(&self, ty: &Type) -> u32 {
+        match ty {
+            Type::Field | Type::Bool | Type::U32 => 1,
+            Type::XField => self.xfield_width,
+            Type::Digest => self.digest_width,
+            Type::Array(inner, n) => self.type_width(inner) * n.eval(&self.size_subs) as u32,
+            Type::Tuple(elems) => elems.iter().map(|t| self.type_width(t)).sum(),
+            Type::NamedTuple(components) => {
+                components.iter().map(|(_, t)| self.type_width(t)).sum()
+            }
+            Type::Option(inner) => 1 + self.type_width(inner),
+            Type::Bytes(n) => n.eval(&self.size_subs) as u32,
+            Type::Named(path) => path
+                .0
+                .last()
+                .and_then(|name| self.struct_widths.get(name).copied())
+                .unwrap_or(1),
         }
     }
 
@@ -199,27 +350,52 @@ impl<'a> CostAnalyzer<'a> {
         }
     }
 
-    /// H0004: scan a block for loops where declared bound >> constant end value.
-    pub(crate) fn scan_loop_bound_waste(&mut self, fn_name: &str, block: &Block) {
+    /// H0004: scan a block for loops where declared bound >> constant end
+    /// value, and for loops with neither a literal end nor a bound.
+    /// `consts` holds the file's integer constants.
+    pub(crate) fn scan_loop_bound_waste(
+        &mut self,
+        fn_name: &str,
+        block: &Block,
+        consts: &BTreeMap<String, u64>,
+    ) {
         for stmt in &block.stmts {
             if let Stmt::For {
                 end, bound, body, ..
             } = &stmt.node
             {
-                // Check if end is a constant and bound is declared
-                if let (Some(declared_bound), Expr::Literal(Literal::Integer(end_val))) =
-                    (bound, &end.node)
-                {
-                    if *declared_bound > *end_val * 4 && *declared_bound > 8 {
-                        self.loop_bound_waste.push((
-                            fn_name.to_string(),
-                            *end_val,
-                            *declared_bound,
-                        ));
+                let header = Span::new(stmt.span.file_id, stmt.span.start, body.span.start);
+                match (bound, &end.node) {
+                    // Check if end is a constant and bound is declared
+                    (Some(declared_bound), Expr::Literal(Literal::Integer(end_val)))
+                        if *declared_bound > *end_val * 4 && *declared_bound > 8 =>
+                    {
+                        self.loop_bound_waste.push(LoopBoundWaste {
+                            fn_name: fn_name.to_string(),
+                            end_value: *end_val,
+                            bound: *declared_bound,
+                            known_end: None,
+                            span: header,
+                        });
                     }
+                    // No bound and no literal end: cost_stmt assumes 1 iteration
+                    (None, end_expr) if !matches!(end_expr, Expr::Literal(Literal::Integer(_))) => {
+                        let known_end = match end_expr {
+                            Expr::Var(name) => consts.get(name).copied(),
+                            _ => None,
+                        };
+                        self.loop_bound_waste.push(LoopBoundWaste {
+                            fn_name: fn_name.to_string(),
+                            end_value: 1, // assumed iterations
+                            bound: 0,     // no declared bound (0 signals "unknown")
+                            known_end,
+                            span: header,
+                        });
+                    }
+                    _ => {}
                 }
                 // Recurse into loop body
-                self.scan_loop_bound_waste(fn_name, &body.node);
+                self.scan_loop_bound_waste(fn_name, &body.node, consts);
             }
             // Recurse into if/else blocks
             if let Stmt::If {
@@ -228,15 +404,15 @@ impl<'a> CostAnalyzer<'a> {
                 ..
             } = &stmt.node
             {
-                self.scan_loop_bound_waste(fn_name, &then_block.node);
+                self.scan_loop_bound_waste(fn_name, &then_block.node, consts);
                 if let Some(eb) = else_block {
-                    self.scan_loop_bound_waste(fn_name, &eb.node);
+                    self.scan_loop_bound_waste(fn_name, &eb.node, consts);
                 }
             }
             // Recurse into match arms
             if let Stmt::Match { arms, .. } = &stmt.node {
                 for arm in arms {
-                    self.scan_loop_bound_waste(fn_name, &arm.body.node);
+                    self.scan_loop_bound_waste(fn_name, &arm.body.node, consts);
                 }
             }
         }
@@ -246,6 +422,10 @@ impl<'a> CostAnalyzer<'a> {
 /// Smallest power of 2 >= n.
 ///
 /// Delegates to `field::proof::padded_height` — same formula, different name.
+/// Levels charged for a Merkle walk whose depth is only known at run
+/// time — the bound `std.crypto.merkle.verify` has always promised.
+const MAX_MERKLE_DEPTH: u64 = 64;
+
 pub(crate) fn next_power_of_two(n: u64) -> u64 {
     crate::field::proof::padded_height(n)
 }
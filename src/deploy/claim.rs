@@ -0,0 +1,132 @@
+//! Claim and proof stream layouts shared with the `std.proof` module.
+//!
+//! `trident package` emits claims in exactly the field layout the
+//! recursive verifier libraries read, so hosts never hand-maintain
+//! offsets. Any change here must be mirrored in `std/proof.tri`.
+
+use crate::field::proof::Claim;
+use crate::poseidon2::Poseidon2Hasher;
+
+/// Field elements in a program digest.
+pub const DIGEST_WIDTH: usize = 5;
+/// Field elements in an encoded claim (`std.proof.CLAIM_WIDTH`).
+pub const CLAIM_WIDTH: usize = DIGEST_WIDTH + 2;
+/// Offset of `num_inputs` in an encoded claim.
+pub const CLAIM_NUM_INPUTS: usize = DIGEST_WIDTH;
+/// Offset of `num_outputs` in an encoded claim.
+pub const CLAIM_NUM_OUTPUTS: usize = DIGEST_WIDTH + 1;
+
+/// Mirror of `std.proof.Claim`: what a proof asserts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofClaim {
+    pub program_digest: [u64; DIGEST_WIDTH],
+    pub num_inputs: u64,
+    pub num_outputs: u64,
+}
+
+impl ProofClaim {
+    /// Claim template for a compiled program, with no public I/O yet.
+    ///
+    /// The digest is the Poseidon2 sponge of the assembly bytes squeezed
+    /// to `DIGEST_WIDTH` field elements.
+    pub fn for_program(assembly: &str) -> Self {
+        let mut hasher = Poseidon2Hasher::new();
+        hasher.absorb_bytes(assembly.as_bytes());
+        let mut program_digest = [0u64; DIGEST_WIDTH];
+        for (slot, elem) in program_digest.iter_mut().zip(hasher.squeeze(DIGEST_WIDTH)) {
+            *slot = elem.0;
+        }
+        Self {
+            program_digest,
+            num_inputs: 0,
+            num_outputs: 0,
+        }
+    }
+
+    /// Convert a universal claim. Fails if the program hash is not a
+    /// `DIGEST_WIDTH`-element digest.
+    pub fn from_claim(claim: &Claim) -> Result<Self, String> {
+        let program_digest: [u64; DIGEST_WIDTH] =
+            claim.program_hash.as_slice().try_into().map_err(|_| {
+                format!(
+                    "program hash has {} elements, expected {}",
+                    claim.program_hash.len(),
+                    DIGEST_WIDTH
+                )
+            })?;
+        Ok(Self {
+            program_digest,
+            num_inputs: claim.public_input.len() as u64,
+            num_outputs: claim.public_output.len() as u64,
+        })
+    }
+
+    /// Encode as the `CLAIM_WIDTH` fields `std.proof.read_claim` reads.
+    pub fn encode(&self) -> Vec<u64> {
+        let mut fields = Vec::with_capacity(CLAIM_WIDTH);
+        fields.extend_from_slice(&self.program_digest);
+        fields.push(self.num_inputs);
+        fields.push(self.num_outputs);
+        fields
+    }
+
+    /// Decode from the front of a field stream.
+    pub fn decode(fields: &[u64]) -> Result<Self, String> {
+        if fields.len() < CLAIM_WIDTH {
+            return Err(format!(
+                "claim needs {} fields, got {}",
+                CLAIM_WIDTH,
+                fields.len()
+            ));
+        }
+        let mut program_digest = [0u64; DIGEST_WIDTH];
+        program_digest.copy_from_slice(&fields[..DIGEST_WIDTH]);
+        Ok(Self {
+            program_digest,
+            num_inputs: fields[CLAIM_NUM_INPUTS],
+            num_outputs: fields[CLAIM_NUM_OUTPUTS],
+        })
+    }
+}
+
+/// Public input for a recursive verifier: the encoded claim followed by
+/// the inner program's public input, then its public output.
+pub fn verifier_public_input(claim: &Claim) -> Result<Vec<u64>, String> {
+    let mut fields = ProofClaim::from_claim(claim)?.encode();
+    fields.extend_from_slice(&claim.public_input);
+    fields.extend_from_slice(&claim.public_output);
+    Ok(fields)
+}
+
+/// Mirror of `std.proof.ProofStream`: proof items laid out in RAM as a
+/// length word followed by the items, front to back.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofStream {
+    pub items: Vec<u64>,
+}
+
+impl ProofStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_field(&mut self, v: u64) {
+        self.items.push(v);
+    }
+
+    pub fn push_digest(&mut self, d: &[u64; DIGEST_WIDTH]) {
+        self.items.extend_from_slice(d);
+    }
+
+    pub fn push_xfield(&mut self, x: &[u64; 3]) {
+        self.items.extend_from_slice(x);
+    }
+
+    /// Encode as the RAM image `std.proof.open_stream` expects.
+    pub fn encode(&self) -> Vec<u64> {
+        let mut fields = Vec::with_capacity(self.items.len() + 1);
+        fields.push(self.items.len() as u64);
+        fields.extend_from_slice(&self.items);
+        fields
+    }
+}
@@ -1,23 +1,51 @@
 //! Packaging: produce a self-contained artifact for Trident programs.
 //!
 //! `trident package` creates a `.deploy/` directory containing the compiled
-//! TASM and a `manifest.json` with metadata:
+//! TASM, a `ram.json` RAM image when the program has data segments, and a
+//! `manifest.json` with metadata:
+//! - `metadata` — license, authors, description and repository from
+//!   trident.toml (see `add_metadata`), when the project declares any
 //! - `program_digest` — Poseidon2 hash of compiled TASM (what verifiers check)
 //! - `source_hash` — content hash of the source AST
+//! - `claim` — the proof claim template in the `std.proof` field layout
 //! - target info (VM + optional OS)
-//! - cost analysis
+//! - cost analysis, and with `add_entry_costs` the worst case of each
+//!   entry point (see `ManifestEntryCost`)
 //! - function signatures with per-function content hashes
+//! - `ram` — the RAM regions the program may touch (see `ManifestRamRegion`)
+//! - `ram_image` — the `ram.json` file holding constant data segments
+//!   (see `ram_image_json`), or `null` when the program has none
+//! - `compiler` — the compiler build: commit, cargo features, targets (see `build_info`)
+//! - `provenance` — every compiled module and the compiler options (see `provenance`)
+//! - `signature` — ed25519 attestation over the rest of the manifest
 //!
-//! The packaged artifact can then be deployed via `trident deploy`.
+//! The packaged artifact can then be deployed via `trident deploy`, which
+//! checks the signature first (see `signing`). `trident upgrade-diff`
+//! compares a project against a deployed artifact (see `upgrade`).
+//! Artifacts packaged side by side can share their TASM through a
+//! content-addressed object store (see `objects`).
 
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use crate::ast;
 use crate::ast::display::format_ast_type;
+use crate::config::project::ProjectMetadata;
 use crate::cost::ProgramCost;
 use crate::hash::ContentHash;
 use crate::target::{Arch, TerrainConfig, UnionConfig};
+use crate::tir::ram::{DataSegment, RamLayout, RamRegionKind};
+use crate::BuildInfo;
+
+pub mod claim;
+pub mod objects;
+pub mod provenance;
+pub mod signing;
+pub mod upgrade;
+
+use claim::ProofClaim;
+pub use provenance::{collect_provenance, Provenance};
+use signing::{ManifestSignature, SigningKey};
 
 // ─── Data Types ────────────────────────────────────────────────────
 
@@ -26,19 +54,35 @@ use crate::target::{Arch, TerrainConfig, UnionConfig};
 pub struct PackageManifest {
     pub name: String,
     pub version: String,
+    /// Distribution metadata from trident.toml (`add_metadata`).
+    pub metadata: ProjectMetadata,
     /// Poseidon2 hash of the compiled TASM bytes (hex).
     pub program_digest: String,
     /// Content hash of the source AST (hex).
     pub source_hash: String,
+    /// Claim template (program digest as field elements, no I/O yet).
+    pub claim: ProofClaim,
     pub target_vm: String,
     pub target_os: Option<String>,
     pub architecture: String,
     pub cost: ManifestCost,
+    /// Worst-case cost per entry point, when requested (`add_entry_costs`).
+    pub entry_costs: Option<Vec<ManifestEntryCost>>,
     pub functions: Vec<ManifestFunction>,
+    /// Reserved RAM regions, sorted by start address.
+    pub ram: Vec<ManifestRamRegion>,
+    /// The RAM image shipped next to the TASM, if any.
+    pub ram_image: Option<ManifestRamImage>,
     pub entry_point: String,
     /// ISO 8601 timestamp.
     pub built_at: String,
     pub compiler_version: String,
+    /// Full build identity of the compiler (commit, features, targets).
+    pub compiler: BuildInfo,
+    /// Modules and options that produced the TASM.
+    pub provenance: Provenance,
+    /// Attestation over the rest of the manifest (set by `sign_artifact`).
+    pub signature: Option<ManifestSignature>,
 }
 
 #[derive(Clone, Debug)]
@@ -50,6 +94,18 @@ pub struct ManifestCost {
     pub padded_height: u64,
 }
 
+/// Worst-case cost of one entry point, so a node can admit or refuse a
+/// call before proving it. Values follow `ManifestCost::table_names`.
+#[derive(Clone, Debug)]
+pub struct ManifestEntryCost {
+    /// The `#[entry]` operation, or `main`.
+    pub name: String,
+    /// Tag that selects an `#[entry]` operation.
+    pub tag: Option<u64>,
+    pub table_values: Vec<u64>,
+    pub padded_height: u64,
+}
+
 #[derive(Clone, Debug)]
 pub struct ManifestFunction {
     pub name: String,
@@ -59,12 +115,55 @@ pub struct ManifestFunction {
     pub signature: String,
 }
 
+/// A RAM region a prover must respect: `sec ram` slots it initializes and
+/// compiler-owned regions it must leave alone.
+#[derive(Clone, Debug)]
+pub struct ManifestRamRegion {
+    /// `"sec_ram"`, `"data"`, `"temp"`, or `"spill"`.
+    pub kind: String,
+    pub label: String,
+    pub start: u64,
+    /// Exclusive end; `None` for a region without a fixed bound.
+    pub end: Option<u64>,
+}
+
+/// The RAM image file of an artifact: the initial contents of every
+/// `data` region, which provers load before running the program.
+#[derive(Clone, Debug)]
+pub struct ManifestRamImage {
+    /// File name inside the `.deploy/` directory.
+    pub file: String,
+    /// Number of field elements across all segments.
+    pub words: u64,
+    /// Poseidon2 hash of the file bytes (hex).
+    pub digest: String,
+}
+
+/// File name of the RAM image inside a `.deploy/` directory.
+pub const RAM_IMAGE_FILE: &str = "ram.json";
+
+/// How a program was built, as its manifest records it: the targets it
+/// was compiled for, the modules and options that compiled it, and the
+/// RAM it may touch.
+#[derive(Clone, Copy, Debug)]
+pub struct ManifestContext<'a> {
+    pub target_vm: &'a TerrainConfig,
+    pub target_os: Option<&'a UnionConfig>,
+    /// Usually built by `collect_provenance`.
+    pub provenance: &'a Provenance,
+    /// Usually built by `ram_layout_project`; its data segments become
+    /// the `ram.json` RAM image.
+    pub ram: &'a RamLayout,
+}
+
 /// Result of a package operation.
 pub struct PackageResult {
     pub manifest: PackageManifest,
     pub artifact_dir: PathBuf,
     pub tasm_path: PathBuf,
     pub manifest_path: PathBuf,
+    /// Path of `ram.json`, when the program has data segments.
+    pub ram_image_path: Option<PathBuf>,
 }
 
 // ─── Artifact Generation ───────────────────────────────────────────
@@ -72,17 +171,72 @@ pub struct PackageResult {
 /// Generate a package artifact from a compiled project.
 ///
 /// Creates a `<name>.deploy/` directory under `output_base` containing
-/// `program.tasm` and `manifest.json`.
+/// `program.tasm`, `manifest.json`, and — when `context.ram` holds data
+/// segments — the `ram.json` RAM image. `context` must describe the build
+/// that compiled `tasm`.
 pub fn generate_artifact(
     name: &str,
     version: &str,
     tasm: &str,
     source_file: &ast::File,
     cost: &ProgramCost,
-    target_vm: &TerrainConfig,
-    target_os: Option<&UnionConfig>,
+    context: &ManifestContext,
     output_base: &Path,
 ) -> Result<PackageResult, String> {
+    let manifest = build_manifest(name, version, tasm, source_file, cost, context);
+    let segments = context.ram.data();
+    let ram_image_text = (!segments.is_empty()).then(|| ram_image_json(segments));
+
+    // Create the artifact directory
+    let artifact_dir = output_base.join(format!("{}.deploy", name));
+    std::fs::create_dir_all(&artifact_dir)
+        .map_err(|e| format!("cannot create '{}': {}", artifact_dir.display(), e))?;
+
+    // Write program.tasm and ram.json
+    let tasm_path = artifact_dir.join("program.tasm");
+    std::fs::write(&tasm_path, tasm)
+        .map_err(|e| format!("cannot write '{}': {}", tasm_path.display(), e))?;
+    let ram_image_path = match ram_image_text {
+        Some(text) => {
+            let path = artifact_dir.join(RAM_IMAGE_FILE);
+            std::fs::write(&path, text)
+                .map_err(|e| format!("cannot write '{}': {}", path.display(), e))?;
+            Some(path)
+        }
+        None => None,
+    };
+
+    // Write manifest.json
+    let manifest_path = artifact_dir.join("manifest.json");
+    std::fs::write(&manifest_path, manifest.to_json())
+        .map_err(|e| format!("cannot write '{}': {}", manifest_path.display(), e))?;
+
+    Ok(PackageResult {
+        manifest,
+        artifact_dir,
+        tasm_path,
+        manifest_path,
+        ram_image_path,
+    })
+}
+
+/// The manifest `generate_artifact` writes, without writing anything.
+/// `trident upgrade-diff` compares it against a deployed one.
+pub fn build_manifest(
+    name: &str,
+    version: &str,
+    tasm: &str,
+    source_file: &ast::File,
+    cost: &ProgramCost,
+    context: &ManifestContext,
+) -> PackageManifest {
+    let ManifestContext {
+        target_vm,
+        target_os,
+        provenance,
+        ram,
+    } = *context;
+
     // 1. Compute program_digest = Poseidon2(tasm bytes)
     let digest_bytes = crate::poseidon2::hash_bytes(tasm.as_bytes());
     let program_digest = ContentHash(digest_bytes);
@@ -105,12 +259,23 @@ pub fn generate_artifact(
     }
     .to_string();
 
-    // 6. Build manifest
-    let manifest = PackageManifest {
+    // 6. RAM image of the data segments
+    let segments = ram.data();
+    let ram_image_text = (!segments.is_empty()).then(|| ram_image_json(segments));
+    let ram_image = ram_image_text.as_ref().map(|text| ManifestRamImage {
+        file: RAM_IMAGE_FILE.to_string(),
+        words: segments.iter().map(|s| s.values.len() as u64).sum(),
+        digest: ContentHash(crate::poseidon2::hash_bytes(text.as_bytes())).to_hex(),
+    });
+
+    // 7. Build manifest
+    PackageManifest {
         name: name.to_string(),
         version: version.to_string(),
+        metadata: ProjectMetadata::default(),
         program_digest: program_digest.to_hex(),
         source_hash: source_hash.to_hex(),
+        claim: ProofClaim::for_program(tasm),
         target_vm: target_vm.name.clone(),
         target_os: target_os.map(|os| os.name.clone()),
         architecture,
@@ -121,33 +286,80 @@ pub fn generate_artifact(
             table_names: cost.table_names.clone(),
             padded_height: cost.padded_height,
         },
+        entry_costs: None,
         functions,
+        ram: extract_ram_regions(ram),
+        ram_image,
         entry_point,
         built_at: iso8601_now(),
         compiler_version: env!("CARGO_PKG_VERSION").to_string(),
-    };
+        compiler: crate::build_info(),
+        provenance: provenance.clone(),
+        signature: None,
+    }
+}
 
-    // 7. Create artifact directory
-    let artifact_dir = output_base.join(format!("{}.deploy", name));
-    std::fs::create_dir_all(&artifact_dir)
-        .map_err(|e| format!("cannot create '{}': {}", artifact_dir.display(), e))?;
+/// Serialize data segments as the `ram.json` RAM image: one object per
+/// segment with its qualified name, first address, and values, so word
+/// `i` of a segment belongs at address `start + i`. Provers preload these
+/// words into non-deterministic RAM; `main` writes the same values on
+/// entry, so the image never changes what a proof asserts.
+pub fn ram_image_json(segments: &[DataSegment]) -> String {
+    let mut out = String::from("{\n  \"segments\": [\n");
+    for (i, seg) in segments.iter().enumerate() {
+        let comma = if i + 1 < segments.len() { "," } else { "" };
+        let values: Vec<String> = seg.values.iter().map(u64::to_string).collect();
+        out.push_str(&format!(
+            "    {{ \"name\": {}, \"start\": {}, \"values\": [{}] }}{}\n",
+            json_string(&seg.name),
+            seg.start,
+            values.join(", "),
+            comma,
+        ));
+    }
+    out.push_str("  ]\n}\n");
+    out
+}
 
-    // 8. Write program.tasm
-    let tasm_path = artifact_dir.join("program.tasm");
-    std::fs::write(&tasm_path, tasm)
-        .map_err(|e| format!("cannot write '{}': {}", tasm_path.display(), e))?;
+/// Add the per-entry-point cost table to a generated artifact and rewrite
+/// its `manifest.json`. Call before `sign_artifact`.
+pub fn add_entry_costs(result: &mut PackageResult, cost: &ProgramCost) -> Result<(), String> {
+    result.manifest.entry_costs = Some(entry_costs(cost));
+    std::fs::write(&result.manifest_path, result.manifest.to_json())
+        .map_err(|e| format!("cannot write '{}': {}", result.manifest_path.display(), e))
+}
 
-    // 9. Write manifest.json
-    let manifest_path = artifact_dir.join("manifest.json");
-    std::fs::write(&manifest_path, manifest.to_json())
-        .map_err(|e| format!("cannot write '{}': {}", manifest_path.display(), e))?;
+/// Record the project's distribution metadata in a generated artifact and
+/// rewrite its `manifest.json`. Call before `sign_artifact`.
+pub fn add_metadata(result: &mut PackageResult, metadata: &ProjectMetadata) -> Result<(), String> {
+    result.manifest.metadata = metadata.clone();
+    std::fs::write(&result.manifest_path, result.manifest.to_json())
+        .map_err(|e| format!("cannot write '{}': {}", result.manifest_path.display(), e))
+}
 
-    Ok(PackageResult {
-        manifest,
-        artifact_dir,
-        tasm_path,
-        manifest_path,
-    })
+/// The per-entry-point cost table of `cost`, as `add_entry_costs` records it.
+pub fn entry_costs(cost: &ProgramCost) -> Vec<ManifestEntryCost> {
+    cost.entries
+        .iter()
+        .map(|e| ManifestEntryCost {
+            name: e.name.clone(),
+            tag: e.tag,
+            table_values: (0..e.cost.count as usize).map(|i| e.cost.get(i)).collect(),
+            padded_height: e.padded_height,
+        })
+        .collect()
+}
+
+/// Sign a generated artifact and rewrite its `manifest.json`.
+///
+/// The signature covers the manifest as serialized without a signature,
+/// which is exactly the JSON `signing::verify_manifest` reconstructs.
+pub fn sign_artifact(result: &mut PackageResult, key: &SigningKey) -> Result<(), String> {
+    result.manifest.signature = None;
+    let unsigned = result.manifest.to_json();
+    result.manifest.signature = Some(ManifestSignature::create(&unsigned, key)?);
+    std::fs::write(&result.manifest_path, result.manifest.to_json())
+        .map_err(|e| format!("cannot write '{}': {}", result.manifest_path.display(), e))
 }
 
 // ─── JSON Serialization ────────────────────────────────────────────
@@ -159,6 +371,27 @@ impl PackageManifest {
 
         out.push_str(&format!("  \"name\": {},\n", json_string(&self.name)));
         out.push_str(&format!("  \"version\": {},\n", json_string(&self.version)));
+        if !self.metadata.is_empty() {
+            let m = &self.metadata;
+            let optional = |v: &Option<String>| {
+                v.as_deref()
+                    .map(json_string)
+                    .unwrap_or_else(|| "null".to_string())
+            };
+            let authors: Vec<String> = m.authors.iter().map(|a| json_string(a)).collect();
+            out.push_str("  \"metadata\": {\n");
+            out.push_str(&format!("    \"license\": {},\n", optional(&m.license)));
+            out.push_str(&format!("    \"authors\": [{}],\n", authors.join(", ")));
+            out.push_str(&format!(
+                "    \"description\": {},\n",
+                optional(&m.description)
+            ));
+            out.push_str(&format!(
+                "    \"repository\": {}\n",
+                optional(&m.repository)
+            ));
+            out.push_str("  },\n");
+        }
         out.push_str(&format!(
             "  \"program_digest\": {},\n",
             json_string(&self.program_digest)
@@ -168,6 +401,25 @@ impl PackageManifest {
             json_string(&self.source_hash)
         ));
 
+        // claim object (std.proof layout)
+        let digest: Vec<String> = self
+            .claim
+            .program_digest
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        out.push_str("  \"claim\": {\n");
+        out.push_str(&format!(
+            "    \"program_digest\": [{}],\n",
+            digest.join(", ")
+        ));
+        out.push_str(&format!("    \"num_inputs\": {},\n", self.claim.num_inputs));
+        out.push_str(&format!(
+            "    \"num_outputs\": {}\n",
+            self.claim.num_outputs
+        ));
+        out.push_str("  },\n");
+
         // target object
         out.push_str("  \"target\": {\n");
         out.push_str(&format!("    \"vm\": {},\n", json_string(&self.target_vm)));
@@ -194,6 +446,28 @@ impl PackageManifest {
         ));
         out.push_str("  },\n");
 
+        // entry_costs array, only when requested
+        if let Some(entries) = &self.entry_costs {
+            out.push_str("  \"entry_costs\": [\n");
+            for (i, entry) in entries.iter().enumerate() {
+                let comma = if i + 1 < entries.len() { "," } else { "" };
+                let tag = entry
+                    .tag
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "null".to_string());
+                let mut fields = vec![
+                    format!("\"name\": {}", json_string(&entry.name)),
+                    format!("\"tag\": {}", tag),
+                ];
+                for (name, value) in self.cost.table_names.iter().zip(&entry.table_values) {
+                    fields.push(format!("{}: {}", json_string(name), value));
+                }
+                fields.push(format!("\"padded_height\": {}", entry.padded_height));
+                out.push_str(&format!("    {{ {} }}{}\n", fields.join(", "), comma));
+            }
+            out.push_str("  ],\n");
+        }
+
         // functions array
         out.push_str("  \"functions\": [\n");
         for (i, func) in self.functions.iter().enumerate() {
@@ -212,6 +486,33 @@ impl PackageManifest {
         }
         out.push_str("  ],\n");
 
+        // ram array
+        out.push_str("  \"ram\": [\n");
+        for (i, region) in self.ram.iter().enumerate() {
+            let comma = if i + 1 < self.ram.len() { "," } else { "" };
+            out.push_str(&format!(
+                "    {{ \"kind\": {}, \"label\": {}, \"start\": {}, \"end\": {} }}{}\n",
+                json_string(&region.kind),
+                json_string(&region.label),
+                region.start,
+                region
+                    .end
+                    .map(|e| e.to_string())
+                    .unwrap_or_else(|| "null".to_string()),
+                comma,
+            ));
+        }
+        out.push_str("  ],\n");
+        match &self.ram_image {
+            Some(image) => out.push_str(&format!(
+                "  \"ram_image\": {{ \"file\": {}, \"words\": {}, \"digest\": {} }},\n",
+                json_string(&image.file),
+                image.words,
+                json_string(&image.digest),
+            )),
+            None => out.push_str("  \"ram_image\": null,\n"),
+        }
+
         out.push_str(&format!(
             "  \"entry_point\": {},\n",
             json_string(&self.entry_point)
@@ -221,9 +522,64 @@ impl PackageManifest {
             json_string(&self.built_at)
         ));
         out.push_str(&format!(
-            "  \"compiler_version\": {}\n",
+            "  \"compiler_version\": {}",
             json_string(&self.compiler_version)
         ));
+        out.push_str(&format!(",\n  \"compiler\": {}", self.compiler.to_json()));
+
+        // provenance object
+        let p = &self.provenance;
+        let flags: Vec<String> = p.options.cfg_flags.iter().map(|f| json_string(f)).collect();
+        out.push_str(",\n  \"provenance\": {\n");
+        out.push_str("    \"options\": {\n");
+        out.push_str(&format!(
+            "      \"target\": {},\n",
+            json_string(&p.options.target)
+        ));
+        out.push_str(&format!(
+            "      \"profile\": {},\n",
+            json_string(&p.options.profile)
+        ));
+        out.push_str(&format!("      \"cfg_flags\": [{}]\n", flags.join(", ")));
+        out.push_str("    },\n");
+        out.push_str("    \"modules\": [\n");
+        for (i, m) in p.modules.iter().enumerate() {
+            let comma = if i + 1 < p.modules.len() { "," } else { "" };
+            out.push_str(&format!(
+                "      {{ \"module\": {}, \"source\": {}, \"path\": {}, \"content_hash\": {}, \"version\": {} }}{}\n",
+                json_string(&m.module),
+                json_string(&m.source),
+                json_string(&m.path),
+                json_string(&m.content_hash),
+                m.version
+                    .as_deref()
+                    .map(json_string)
+                    .unwrap_or_else(|| "null".to_string()),
+                comma,
+            ));
+        }
+        out.push_str("    ]\n");
+        out.push_str("  }");
+
+        // signature object
+        if let Some(ref sig) = self.signature {
+            out.push_str(",\n  \"signature\": {\n");
+            out.push_str(&format!(
+                "    \"algorithm\": {},\n",
+                json_string(signing::ALGORITHM)
+            ));
+            out.push_str(&format!(
+                "    \"public_key\": {},\n",
+                json_string(&sig.public_key)
+            ));
+            out.push_str(&format!(
+                "    \"fingerprint\": {},\n",
+                json_string(&sig.fingerprint)
+            ));
+            out.push_str(&format!("    \"value\": {}\n", json_string(&sig.value)));
+            out.push_str("  }");
+        }
+        out.push('\n');
 
         out.push_str("}\n");
         out
@@ -279,6 +635,29 @@ fn extract_functions(
     functions
 }
 
+/// Manifest entries for the reserved RAM regions, by start address.
+fn extract_ram_regions(ram: &RamLayout) -> Vec<ManifestRamRegion> {
+    let mut regions: Vec<ManifestRamRegion> = ram
+        .regions()
+        .iter()
+        .map(|r| ManifestRamRegion {
+            kind: match r.kind {
+                RamRegionKind::SecRam => "sec_ram",
+                RamRegionKind::Data => "data",
+                RamRegionKind::Temp => "temp",
+                RamRegionKind::Pool => "pool",
+                RamRegionKind::Spill => "spill",
+            }
+            .to_string(),
+            label: r.label.clone(),
+            start: r.start,
+            end: r.end,
+        })
+        .collect();
+    regions.sort_by_key(|r| r.start);
+    regions
+}
+
 /// Format a function signature for the manifest.
 pub fn format_fn_signature(func: &ast::FnDef) -> String {
     let mut sig = String::from("fn ");
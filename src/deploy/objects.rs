@@ -0,0 +1,175 @@
+//! Content-addressed TASM objects shared between `.deploy/` artifacts.
+//!
+//! Programs packaged side by side repeat the same library functions. With
+//! `trident package --objects`, an artifact's `program.tasm` is split into
+//! objects — the preamble before the first label, then each label with
+//! the instructions up to the next one — stored once as
+//! `objects/<hash>.tasm` next to the `.deploy/` directories. The artifact
+//! keeps a `program.link` listing its objects in order instead of the
+//! TASM. Concatenating the objects gives back `program.tasm` byte for
+//! byte, so `program_digest` and the manifest signature are unchanged.
+//!
+//! `flatten_artifact` writes `program.tasm` back before deployment, and
+//! `read_program` reads either layout.
+
+use std::path::{Path, PathBuf};
+
+use crate::hash::ContentHash;
+
+use super::PackageResult;
+
+/// Directory of the object store, next to the `.deploy/` directories.
+pub const OBJECTS_DIR: &str = "objects";
+
+/// File listing an artifact's objects in place of `program.tasm`.
+pub const LINK_FILE: &str = "program.link";
+
+const LINK_HEADER: &str = "# objects of program.tasm, in order";
+
+/// What storing a program's objects did.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ObjectStats {
+    /// Objects the program consists of.
+    pub objects: usize,
+    /// Objects that were not in the store yet.
+    pub written: usize,
+    /// Bytes of TASM already in the store, which this program shares.
+    pub shared_bytes: usize,
+}
+
+/// Split TASM into objects. Their concatenation is `tasm`.
+pub fn split_objects(tasm: &str) -> Vec<&str> {
+    let mut objects = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in tasm.split_inclusive('\n') {
+        if is_label(line) && offset > start {
+            objects.push(&tasm[start..offset]);
+            start = offset;
+        }
+        offset += line.len();
+    }
+    if offset > start {
+        objects.push(&tasm[start..]);
+    }
+    objects
+}
+
+fn is_label(line: &str) -> bool {
+    let line = line.trim();
+    !line.starts_with("//") && line.ends_with(':')
+}
+
+/// Poseidon2 hash (hex) of an object, which names its file.
+pub fn object_hash(object: &str) -> String {
+    ContentHash(crate::poseidon2::hash_bytes(object.as_bytes())).to_hex()
+}
+
+/// The default store of an artifact: `objects/` next to its directory.
+pub fn default_store(artifact_dir: &Path) -> PathBuf {
+    artifact_dir
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(OBJECTS_DIR)
+}
+
+/// Store the objects of `tasm` under `store`, returning their hashes in
+/// program order.
+pub fn store_objects(tasm: &str, store: &Path) -> Result<(Vec<String>, ObjectStats), String> {
+    std::fs::create_dir_all(store)
+        .map_err(|e| format!("cannot create '{}': {}", store.display(), e))?;
+    let mut hashes = Vec::new();
+    let mut stats = ObjectStats::default();
+    for object in split_objects(tasm) {
+        let hash = object_hash(object);
+        let path = store.join(format!("{}.tasm", hash));
+        if path.exists() {
+            stats.shared_bytes += object.len();
+        } else {
+            std::fs::write(&path, object)
+                .map_err(|e| format!("cannot write '{}': {}", path.display(), e))?;
+            stats.written += 1;
+        }
+        stats.objects += 1;
+        hashes.push(hash);
+    }
+    Ok((hashes, stats))
+}
+
+/// Move a packaged artifact's TASM into `store`, replacing `program.tasm`
+/// with `program.link`.
+pub fn dedup_artifact(result: &PackageResult, store: &Path) -> Result<ObjectStats, String> {
+    let tasm = std::fs::read_to_string(&result.tasm_path)
+        .map_err(|e| format!("cannot read '{}': {}", result.tasm_path.display(), e))?;
+    let (hashes, stats) = store_objects(&tasm, store)?;
+
+    let mut link = format!("{}\n", LINK_HEADER);
+    for hash in &hashes {
+        link.push_str(hash);
+        link.push('\n');
+    }
+    let link_path = result.artifact_dir.join(LINK_FILE);
+    std::fs::write(&link_path, link)
+        .map_err(|e| format!("cannot write '{}': {}", link_path.display(), e))?;
+    std::fs::remove_file(&result.tasm_path)
+        .map_err(|e| format!("cannot remove '{}': {}", result.tasm_path.display(), e))?;
+    Ok(stats)
+}
+
+/// Reassemble the TASM of a linked artifact from `store`, checking each
+/// object against its hash.
+pub fn read_linked(artifact_dir: &Path, store: &Path) -> Result<String, String> {
+    let link_path = artifact_dir.join(LINK_FILE);
+    let link = std::fs::read_to_string(&link_path)
+        .map_err(|e| format!("cannot read '{}': {}", link_path.display(), e))?;
+    let mut tasm = String::new();
+    for hash in link
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+    {
+        let path = store.join(format!("{}.tasm", hash));
+        let object = std::fs::read_to_string(&path)
+            .map_err(|e| format!("missing object '{}': {}", path.display(), e))?;
+        if object_hash(&object) != hash {
+            return Err(format!(
+                "object '{}' does not match its hash",
+                path.display()
+            ));
+        }
+        tasm.push_str(&object);
+    }
+    Ok(tasm)
+}
+
+/// The TASM of an artifact in either layout: `program.tasm`, or the
+/// objects `program.link` names in the default store.
+pub fn read_program(artifact_dir: &Path) -> Result<String, String> {
+    let tasm_path = artifact_dir.join("program.tasm");
+    if tasm_path.exists() || !artifact_dir.join(LINK_FILE).exists() {
+        return std::fs::read_to_string(&tasm_path)
+            .map_err(|e| format!("cannot read '{}': {}", tasm_path.display(), e));
+    }
+    read_linked(artifact_dir, &default_store(artifact_dir))
+}
+
+/// Make a linked artifact self-contained for deployment: write
+/// `program.tasm` from `store` and remove `program.link`. The TASM must
+/// match the manifest's `program_digest`.
+pub fn flatten_artifact(artifact_dir: &Path, store: &Path) -> Result<PathBuf, String> {
+    let tasm = read_linked(artifact_dir, store)?;
+    let manifest = super::upgrade::DeployedManifest::read(artifact_dir)?;
+    if object_hash(&tasm) != manifest.program_digest {
+        return Err(format!(
+            "objects of '{}' do not match its program_digest",
+            artifact_dir.display()
+        ));
+    }
+    let tasm_path = artifact_dir.join("program.tasm");
+    std::fs::write(&tasm_path, &tasm)
+        .map_err(|e| format!("cannot write '{}': {}", tasm_path.display(), e))?;
+    let link_path = artifact_dir.join(LINK_FILE);
+    std::fs::remove_file(&link_path)
+        .map_err(|e| format!("cannot remove '{}': {}", link_path.display(), e))?;
+    Ok(tasm_path)
+}
@@ -0,0 +1,164 @@
+//! Dependency provenance for packaged artifacts.
+//!
+//! Lists every module compiled into the artifact — where it came from,
+//! its content hash, and its version — together with the compiler options
+//! that produced the TASM, so an auditor can rebuild the exact program
+//! behind a deployed `program_digest`.
+//!
+//! Module sources:
+//! - `path:<relative>` — a file in the project (relative to its root)
+//! - `std`, `os`, `vm` — the standard, OS, or VM library shipped with the compiler
+//! - the locked `source` from `trident.lock` (`registry:<url>`, `path:<dir>`,
+//!   `hash`) — a dependency cached under `.trident/deps/<hash>/`
+
+use std::path::{Path, PathBuf};
+
+use crate::diagnostic::Diagnostic;
+use crate::manifest::{load_lockfile, LockedDep, LockedLibrary};
+use crate::pipeline::PreparedProject;
+use crate::project::Project;
+use crate::CompileOptions;
+
+/// One module compiled into an artifact.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProvenanceModule {
+    /// Module name as declared (`program x` / `module std.crypto.hash`).
+    pub module: String,
+    /// Where the module came from (see module docs).
+    pub source: String,
+    /// Path relative to its source root.
+    pub path: String,
+    /// Content hash of the module AST (hex).
+    pub content_hash: String,
+    /// Version: project version for project files, compiler version for
+    /// bundled libraries, `None` for dependencies pinned only by hash.
+    pub version: Option<String>,
+}
+
+/// Compiler options that affect the emitted TASM.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProvenanceOptions {
+    pub target: String,
+    pub profile: String,
+    /// Active cfg flags, sorted.
+    pub cfg_flags: Vec<String>,
+}
+
+/// Everything that went into an artifact.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Provenance {
+    pub options: ProvenanceOptions,
+    /// Modules in compilation (topological) order; the program is last.
+    pub modules: Vec<ProvenanceModule>,
+}
+
+impl Provenance {
+    /// Pins for the bundled library modules (`std`, `os`, `vm`) in this build.
+    pub fn library_pins(&self) -> Vec<LockedLibrary> {
+        self.modules
+            .iter()
+            .filter(|m| LIBRARY_SOURCES.contains(&m.source.as_str()))
+            .map(|m| LockedLibrary {
+                module: m.module.clone(),
+                hash: m.content_hash.clone(),
+                source: m.source.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Sources of the libraries shipped with the compiler.
+const LIBRARY_SOURCES: [&str; 3] = ["std", "os", "vm"];
+
+/// Resolve, parse, and type-check the project at `entry` and record the
+/// provenance of every module it pulls in.
+pub fn collect_provenance(
+    entry: &Path,
+    options: &CompileOptions,
+    project: Option<&Project>,
+) -> Result<Provenance, Vec<Diagnostic>> {
+    let prepared = PreparedProject::build_silent(entry, options)?;
+
+    let root = project
+        .map(|p| p.root_dir.clone())
+        .unwrap_or_else(|| entry.parent().unwrap_or(Path::new(".")).to_path_buf());
+    let root = canonical(&root);
+    let locked: Vec<LockedDep> = load_lockfile(&root.join("trident.lock"))
+        .map(|lock| lock.locked.into_values().collect())
+        .unwrap_or_default();
+    let libraries: Vec<(&str, PathBuf)> = LIBRARY_SOURCES
+        .into_iter()
+        .zip([
+            crate::resolve::find_stdlib_dir(),
+            crate::resolve::find_os_dir(),
+            crate::resolve::find_vm_lib_dir(),
+        ])
+        .filter_map(|(name, dir)| dir.map(|d| (name, canonical(&d))))
+        .collect();
+    let project_version = project.map(|p| p.version.clone());
+    let compiler_version = env!("CARGO_PKG_VERSION").to_string();
+
+    let modules = prepared
+        .modules
+        .iter()
+        .map(|pm| {
+            let path = canonical(&pm.file_path);
+            let deps_dir = root.join(".trident").join("deps");
+            let (source, rel, version) = if let Ok(rest) = path.strip_prefix(&deps_dir) {
+                let hash = rest
+                    .components()
+                    .next()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let source = locked
+                    .iter()
+                    .find(|dep| dep.hash == hash)
+                    .map(|dep| dep.source.clone())
+                    .unwrap_or_else(|| "hash".to_string());
+                let rel = rest.strip_prefix(&hash).unwrap_or(rest);
+                (source, display_path(rel), None)
+            } else if let Some((name, rel)) = libraries
+                .iter()
+                .find_map(|(name, dir)| path.strip_prefix(dir).ok().map(|rel| (*name, rel)))
+            {
+                (
+                    name.to_string(),
+                    display_path(rel),
+                    Some(compiler_version.clone()),
+                )
+            } else {
+                let rel = path.strip_prefix(&root).unwrap_or(&path);
+                let rel = display_path(rel);
+                (format!("path:{}", rel), rel, project_version.clone())
+            };
+            ProvenanceModule {
+                module: pm.file.name.node.clone(),
+                source,
+                path: rel,
+                content_hash: crate::hash::hash_file_content(&pm.file).to_hex(),
+                version,
+            }
+        })
+        .collect();
+
+    Ok(Provenance {
+        options: ProvenanceOptions {
+            target: options.target_config.name.clone(),
+            profile: options.profile.clone(),
+            cfg_flags: options.cfg_flags.iter().cloned().collect(),
+        },
+        modules,
+    })
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Forward-slash path for stable manifests across platforms.
+fn display_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,410 @@
+//! Artifact attestation: ed25519 signatures over `manifest.json`.
+//!
+//! `trident package` signs the manifest with the local key in
+//! `~/.trident/keys/` (or `$TRIDENT_KEYS_DIR`) and embeds a `signature`
+//! object as the manifest's last member:
+//!
+//! ```text
+//! "signature": {
+//!   "algorithm": "ed25519",
+//!   "public_key": "<64 hex>",
+//!   "fingerprint": "<16 hex>",
+//!   "value": "<128 hex>"
+//! }
+//! ```
+//!
+//! The signed message is the manifest parsed as JSON, without that member,
+//! and serialized compactly (see `signed_message`), so every other field —
+//! including `program_digest`, which binds `program.tasm` — is covered and
+//! whitespace does not matter. Verification also re-hashes `program.tasm`
+//! (or the objects its `program.link` names, see `objects`) against the
+//! digest, and `ram.json` against the `ram_image` digest when there is one.
+//!
+//! A valid signature only says who signed. `trident deploy` and `trident
+//! registry pull` also require the signer to be trusted: its public key is
+//! a `*.pub` file in the keys directory (the local `default.pub` is one) or
+//! is passed with `--trusted-key`. Unsigned, invalid, and untrusted
+//! artifacts are refused unless `--allow-unsigned` is given.
+
+use std::path::{Path, PathBuf};
+
+use ed25519_dalek::{Signature, Signer, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, SIGNATURE_LENGTH};
+use serde_json::Value;
+
+use crate::hash::ContentHash;
+
+/// Signature algorithm recorded in manifests and registry entries.
+pub const ALGORITHM: &str = "ed25519";
+
+/// File name of the default signing key inside the keys directory.
+const DEFAULT_KEY_FILE: &str = "default.key";
+/// File name of the matching public key (hex), for sharing.
+const DEFAULT_PUB_FILE: &str = "default.pub";
+
+// ─── Keys ──────────────────────────────────────────────────────────
+
+/// An ed25519 signing key (secret seed + derived public key).
+#[derive(Clone)]
+pub struct SigningKey {
+    key: ed25519_dalek::SigningKey,
+    public: [u8; PUBLIC_KEY_LENGTH],
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the seed.
+        write!(f, "SigningKey({})", self.fingerprint())
+    }
+}
+
+impl SigningKey {
+    pub fn from_seed(seed: [u8; SECRET_KEY_LENGTH]) -> Self {
+        let key = ed25519_dalek::SigningKey::from_bytes(&seed);
+        let public = key.verifying_key().to_bytes();
+        Self { key, public }
+    }
+
+    /// Generate a fresh key from the OS random source.
+    pub fn generate() -> Result<Self, String> {
+        use std::io::Read;
+        let mut seed = [0u8; SECRET_KEY_LENGTH];
+        std::fs::File::open("/dev/urandom")
+            .and_then(|mut f| f.read_exact(&mut seed))
+            .map_err(|e| format!("cannot read /dev/urandom: {}", e))?;
+        Ok(Self::from_seed(seed))
+    }
+
+    pub fn public_key(&self) -> &[u8; PUBLIC_KEY_LENGTH] {
+        &self.public
+    }
+
+    pub fn fingerprint(&self) -> String {
+        key_fingerprint(&self.public)
+    }
+
+    pub fn sign(&self, message: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+        self.key.sign(message).to_bytes()
+    }
+}
+
+/// Short identifier for a public key: first 16 hex chars of its Poseidon2 hash.
+pub fn key_fingerprint(public: &[u8]) -> String {
+    ContentHash(crate::poseidon2::hash_bytes(public)).to_hex()[..16].to_string()
+}
+
+/// Resolve the keys directory.
+///
+/// Priority:
+/// 1. `$TRIDENT_KEYS_DIR` environment variable
+/// 2. `~/.trident/keys/`
+pub fn keys_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("TRIDENT_KEYS_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".trident").join("keys"))
+}
+
+/// Load the default signing key from `dir`, generating it on first use.
+///
+/// Returns the key and whether it was newly created.
+pub fn load_or_create_key(dir: &Path) -> Result<(SigningKey, bool), String> {
+    let key_path = dir.join(DEFAULT_KEY_FILE);
+    if key_path.exists() {
+        let text = std::fs::read_to_string(&key_path)
+            .map_err(|e| format!("cannot read '{}': {}", key_path.display(), e))?;
+        let seed = decode_hex(text.trim())
+            .and_then(|b| <[u8; SECRET_KEY_LENGTH]>::try_from(b).ok())
+            .ok_or_else(|| format!("'{}' is not a 32-byte hex seed", key_path.display()))?;
+        return Ok((SigningKey::from_seed(seed), false));
+    }
+
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("cannot create '{}': {}", dir.display(), e))?;
+    let key = SigningKey::generate()?;
+    write_private(&key_path, &format!("{}\n", encode_hex(&key.key.to_bytes())))?;
+    let pub_path = dir.join(DEFAULT_PUB_FILE);
+    std::fs::write(&pub_path, format!("{}\n", encode_hex(&key.public)))
+        .map_err(|e| format!("cannot write '{}': {}", pub_path.display(), e))?;
+    Ok((key, true))
+}
+
+/// Write a secret file readable only by the owner.
+fn write_private(path: &Path, contents: &str) -> Result<(), String> {
+    std::fs::write(path, contents)
+        .map_err(|e| format!("cannot write '{}': {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("cannot restrict '{}': {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Public keys whose signatures `deploy` and `registry pull` accept.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedKeys {
+    keys: Vec<[u8; PUBLIC_KEY_LENGTH]>,
+}
+
+impl TrustedKeys {
+    /// Every `*.pub` file in `dir`; none when the directory does not exist.
+    pub fn load_dir(dir: &Path) -> Result<Self, String> {
+        let mut trusted = Self::default();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Ok(trusted);
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "pub"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            trusted.add(&path.to_string_lossy())?;
+        }
+        Ok(trusted)
+    }
+
+    /// Trust a public key given as hex or as the path of a `.pub` file.
+    pub fn add(&mut self, key: &str) -> Result<(), String> {
+        let public = match parse_public_key(key) {
+            Some(public) => public,
+            None => {
+                let text = std::fs::read_to_string(key).map_err(|_| {
+                    format!("'{}' is neither a hex public key nor a readable file", key)
+                })?;
+                parse_public_key(text.trim())
+                    .ok_or_else(|| format!("'{}' does not hold a 32-byte hex public key", key))?
+            }
+        };
+        if !self.contains(&public) {
+            self.keys.push(public);
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, public: &[u8; PUBLIC_KEY_LENGTH]) -> bool {
+        self.keys.contains(public)
+    }
+
+    /// Status of an intact signature by `public`: valid only if trusted.
+    fn judge(&self, public: &[u8; PUBLIC_KEY_LENGTH]) -> SignatureStatus {
+        let fingerprint = key_fingerprint(public);
+        if self.contains(public) {
+            SignatureStatus::Valid { fingerprint }
+        } else {
+            SignatureStatus::Untrusted { fingerprint }
+        }
+    }
+}
+
+// ─── Manifest Signatures ───────────────────────────────────────────
+
+/// Signature embedded in `manifest.json`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestSignature {
+    /// Public key (hex).
+    pub public_key: String,
+    /// Public key fingerprint (see `key_fingerprint`).
+    pub fingerprint: String,
+    /// Signature over `signed_message` of the unsigned manifest (hex).
+    pub value: String,
+}
+
+impl ManifestSignature {
+    /// Sign the unsigned manifest text.
+    pub fn create(unsigned_manifest: &str, key: &SigningKey) -> Result<Self, String> {
+        let manifest = parse_manifest(unsigned_manifest)?;
+        Ok(Self {
+            public_key: encode_hex(key.public_key()),
+            fingerprint: key.fingerprint(),
+            value: encode_hex(&key.sign(signed_message(&manifest).as_bytes())),
+        })
+    }
+}
+
+/// Outcome of checking an artifact's signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// Intact, by a trusted key; carries the signer fingerprint.
+    Valid { fingerprint: String },
+    /// Intact, but the signer is not in the trusted key set.
+    Untrusted { fingerprint: String },
+    /// The manifest has no `signature` member.
+    Unsigned,
+    /// Present but wrong: bad signature, mismatched key, or tampered TASM.
+    Invalid(String),
+}
+
+impl SignatureStatus {
+    /// Turn a status into a go/no-go decision for deploy and pull.
+    ///
+    /// Returns the signer fingerprint when valid, `None` when `what` is
+    /// accepted without a valid signature under `allow_unsigned`.
+    pub fn require(&self, what: &str, allow_unsigned: bool) -> Result<Option<String>, String> {
+        match self {
+            SignatureStatus::Valid { fingerprint } => Ok(Some(fingerprint.clone())),
+            _ if allow_unsigned => Ok(None),
+            SignatureStatus::Untrusted { fingerprint } => Err(format!(
+                "{} is signed by untrusted key {} (trust it with --trusted-key or a .pub \
+                 file in the keys directory, or pass --allow-unsigned to accept it)",
+                what, fingerprint
+            )),
+            SignatureStatus::Unsigned => Err(format!(
+                "{} is unsigned (pass --allow-unsigned to accept it)",
+                what
+            )),
+            SignatureStatus::Invalid(reason) => Err(format!(
+                "{} has an invalid signature: {} (pass --allow-unsigned to accept it)",
+                what, reason
+            )),
+        }
+    }
+}
+
+/// Parse manifest text as a JSON object.
+fn parse_manifest(manifest: &str) -> Result<Value, String> {
+    match serde_json::from_str::<Value>(manifest) {
+        Ok(value) if value.is_object() => Ok(value),
+        Ok(_) => Err("manifest.json is not a JSON object".to_string()),
+        Err(e) => Err(format!("manifest.json is not valid JSON: {}", e)),
+    }
+}
+
+/// The message a manifest signature covers: the manifest without its
+/// `signature` member, as compact JSON.
+fn signed_message(unsigned: &Value) -> String {
+    unsigned.to_string()
+}
+
+/// Verify the signature in a manifest against its text, `tasm`, and the
+/// trusted keys.
+pub fn verify_manifest(manifest: &str, tasm: &str, trusted: &TrustedKeys) -> SignatureStatus {
+    let mut manifest = match parse_manifest(manifest) {
+        Ok(manifest) => manifest,
+        Err(e) => return SignatureStatus::Invalid(e),
+    };
+    let Some(signature) = manifest
+        .as_object_mut()
+        .and_then(|members| members.remove("signature"))
+    else {
+        return SignatureStatus::Unsigned;
+    };
+
+    let field = |key: &str| signature.get(key).and_then(Value::as_str);
+    if field("algorithm") != Some(ALGORITHM) {
+        return SignatureStatus::Invalid("unsupported signature algorithm".to_string());
+    }
+    let public = field("public_key").and_then(parse_public_key);
+    let value = field("value").and_then(parse_signature);
+    let (Some(public), Some(value)) = (public, value) else {
+        return SignatureStatus::Invalid("malformed signature block".to_string());
+    };
+
+    if field("fingerprint") != Some(key_fingerprint(&public).as_str()) {
+        return SignatureStatus::Invalid("fingerprint does not match public key".to_string());
+    }
+    if !verify_signature(&public, signed_message(&manifest).as_bytes(), &value) {
+        return SignatureStatus::Invalid("signature does not match manifest".to_string());
+    }
+
+    let digest = ContentHash(crate::poseidon2::hash_bytes(tasm.as_bytes())).to_hex();
+    if manifest.get("program_digest").and_then(Value::as_str) != Some(digest.as_str()) {
+        return SignatureStatus::Invalid("program.tasm does not match program_digest".to_string());
+    }
+
+    trusted.judge(&public)
+}
+
+/// Verify a `.deploy/` artifact directory, including its RAM image.
+pub fn verify_artifact(artifact_dir: &Path, trusted: &TrustedKeys) -> SignatureStatus {
+    let read = |name: &str| std::fs::read_to_string(artifact_dir.join(name));
+    let manifest = match read("manifest.json") {
+        Ok(manifest) => manifest,
+        Err(e) => return SignatureStatus::Invalid(format!("cannot read artifact: {}", e)),
+    };
+    let tasm = match super::objects::read_program(artifact_dir) {
+        Ok(tasm) => tasm,
+        Err(e) => return SignatureStatus::Invalid(format!("cannot read artifact: {}", e)),
+    };
+    let status = verify_manifest(&manifest, &tasm, trusted);
+    let Ok(parsed) = parse_manifest(&manifest) else {
+        return status;
+    };
+    let Some(image) = parsed.get("ram_image").filter(|image| !image.is_null()) else {
+        return status;
+    };
+    let Some(expected) = image.get("digest").and_then(Value::as_str) else {
+        return SignatureStatus::Invalid("malformed ram_image entry".to_string());
+    };
+    match read(super::RAM_IMAGE_FILE) {
+        Ok(text) => {
+            let digest = ContentHash(crate::poseidon2::hash_bytes(text.as_bytes())).to_hex();
+            if digest == expected {
+                status
+            } else {
+                SignatureStatus::Invalid("ram.json does not match ram_image digest".to_string())
+            }
+        }
+        Err(e) => SignatureStatus::Invalid(format!("cannot read artifact: {}", e)),
+    }
+}
+
+/// Verify a registry signature over a definition's content hash.
+pub fn verify_definition(
+    hash: &str,
+    public_key: &str,
+    signature: &str,
+    trusted: &TrustedKeys,
+) -> SignatureStatus {
+    if public_key.is_empty() && signature.is_empty() {
+        return SignatureStatus::Unsigned;
+    }
+    match (parse_public_key(public_key), parse_signature(signature)) {
+        (Some(public), Some(value)) if verify_signature(&public, hash.as_bytes(), &value) => {
+            trusted.judge(&public)
+        }
+        (Some(_), Some(_)) => {
+            SignatureStatus::Invalid("signature does not match content hash".to_string())
+        }
+        _ => SignatureStatus::Invalid("malformed signature".to_string()),
+    }
+}
+
+fn verify_signature(
+    public: &[u8; PUBLIC_KEY_LENGTH],
+    message: &[u8],
+    signature: &[u8; SIGNATURE_LENGTH],
+) -> bool {
+    ed25519_dalek::VerifyingKey::from_bytes(public).is_ok_and(|key| {
+        key.verify_strict(message, &Signature::from_bytes(signature))
+            .is_ok()
+    })
+}
+
+// ─── Helpers ───────────────────────────────────────────────────────
+
+fn parse_public_key(hex: &str) -> Option<[u8; PUBLIC_KEY_LENGTH]> {
+    decode_hex(hex).and_then(|b| b.try_into().ok())
+}
+
+fn parse_signature(hex: &str) -> Option<[u8; SIGNATURE_LENGTH]> {
+    decode_hex(hex).and_then(|b| b.try_into().ok())
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
@@ -39,8 +39,19 @@ fn test_manifest_to_json_structure() {
     let manifest = PackageManifest {
         name: "test".to_string(),
         version: "0.1.0".to_string(),
+        metadata: ProjectMetadata {
+            license: Some("MIT".to_string()),
+            authors: vec!["Ada".to_string()],
+            description: None,
+            repository: Some("https://example.org/test".to_string()),
+        },
         program_digest: "aabb".to_string(),
         source_hash: "ccdd".to_string(),
+        claim: ProofClaim {
+            program_digest: [1, 2, 3, 4, 5],
+            num_inputs: 0,
+            num_outputs: 0,
+        },
         target_vm: "triton".to_string(),
         target_os: Some("neptune".to_string()),
         architecture: "stack".to_string(),
@@ -56,25 +67,39 @@ fn test_manifest_to_json_structure() {
             ],
             padded_height: 256,
         },
+        entry_costs: None,
         functions: vec![ManifestFunction {
             name: "main".to_string(),
             hash: "eeff".to_string(),
             signature: "fn main()".to_string(),
         }],
+        ram: Vec::new(),
+        ram_image: None,
         entry_point: "main".to_string(),
         built_at: "2026-02-11T00:00:00Z".to_string(),
         compiler_version: "0.1.0".to_string(),
+        compiler: crate::build_info(),
+        provenance: Provenance::default(),
+        signature: None,
     };
 
     let json = manifest.to_json();
     assert!(json.contains("\"name\": \"test\""));
+    assert!(json.contains("\"compiler\": { \"version\": \""));
+    assert!(json.contains("\"name\": \"triton\", \"max_tier\": 3"));
     assert!(json.contains("\"program_digest\": \"aabb\""));
+    assert!(json.contains("\"program_digest\": [1, 2, 3, 4, 5]"));
     assert!(json.contains("\"os\": \"neptune\""));
     assert!(json.contains("\"vm\": \"triton\""));
     assert!(json.contains("\"processor\": 100"));
     assert!(json.contains("\"padded_height\": 256"));
     assert!(json.contains("\"entry_point\": \"main\""));
     assert!(json.contains("\"fn main()\""));
+    assert!(json.contains("\"license\": \"MIT\""));
+    assert!(json.contains("\"authors\": [\"Ada\"]"));
+    assert!(json.contains("\"description\": null"));
+    let deployed = upgrade::DeployedManifest::from_json(&json).unwrap();
+    assert_eq!(deployed.metadata, manifest.metadata);
 }
 
 #[test]
@@ -82,8 +107,10 @@ fn test_manifest_null_os() {
     let manifest = PackageManifest {
         name: "bare".to_string(),
         version: "0.1.0".to_string(),
+        metadata: ProjectMetadata::default(),
         program_digest: "aa".to_string(),
         source_hash: "bb".to_string(),
+        claim: ProofClaim::for_program(""),
         target_vm: "triton".to_string(),
         target_os: None,
         architecture: "stack".to_string(),
@@ -99,14 +126,23 @@ fn test_manifest_null_os() {
             ],
             padded_height: 0,
         },
+        entry_costs: None,
         functions: vec![],
+        ram: Vec::new(),
+        ram_image: None,
         entry_point: "main".to_string(),
         built_at: "2026-01-01T00:00:00Z".to_string(),
         compiler_version: "0.1.0".to_string(),
+        compiler: crate::build_info(),
+        provenance: Provenance::default(),
+        signature: None,
     };
 
     let json = manifest.to_json();
     assert!(json.contains("\"os\": null"));
+    assert!(json.contains("\"ram_image\": null,"));
+    assert!(!json.contains("\"entry_costs\""));
+    assert!(!json.contains("\"metadata\""));
 }
 
 #[test]
@@ -138,8 +174,12 @@ fn test_generate_artifact_roundtrip() {
         tasm,
         &file,
         &cost,
-        &target_vm,
-        None,
+        &ManifestContext {
+            target_vm: &target_vm,
+            target_os: None,
+            provenance: &Provenance::default(),
+            ram: &RamLayout::default(),
+        },
         dir.path(),
     )
     .unwrap();
@@ -168,3 +208,627 @@ fn test_generate_artifact_roundtrip() {
     assert!(!result.manifest.program_digest.is_empty());
     assert!(!result.manifest.source_hash.is_empty());
 }
+
+#[test]
+fn test_entry_costs_take_only_the_selected_arm() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = "program test\n#[entry]\nfn ping() {\n    pub_write(1)\n}\n#[entry]\nfn work() {\n    for i in 0..64 bounded 64 {\n        let d = hash(i, 0, 0, 0, 0, 0, 0, 0, 0, 0)\n        pub_write(i)\n    }\n}\n";
+    let file = crate::parse_source_silent(source, "test.tri").unwrap();
+    let cost = crate::cost::CostAnalyzer::default().analyze_file(&file);
+    let mut result = generate_artifact(
+        "test",
+        "0.1.0",
+        "halt\n",
+        &file,
+        &cost,
+        &ManifestContext {
+            target_vm: &TerrainConfig::triton(),
+            target_os: None,
+            provenance: &Provenance::default(),
+            ram: &RamLayout::default(),
+        },
+        dir.path(),
+    )
+    .unwrap();
+    add_entry_costs(&mut result, &cost).unwrap();
+
+    let entries = result.manifest.entry_costs.as_ref().unwrap();
+    let names: Vec<(&str, Option<u64>)> =
+        entries.iter().map(|e| (e.name.as_str(), e.tag)).collect();
+    assert_eq!(names, [("ping", Some(0)), ("work", Some(1))]);
+    let (ping, work) = (&entries[0].table_values, &entries[1].table_values);
+    assert!(ping[0] < work[0], "ping should not pay for work's loop");
+    assert_eq!(ping[1], 0, "ping hashes nothing");
+    assert_eq!(work, &result.manifest.cost.table_values);
+
+    let json = std::fs::read_to_string(&result.manifest_path).unwrap();
+    assert!(json.contains(&format!(
+        "{{ \"name\": \"ping\", \"tag\": 0, \"processor\": {}, ",
+        ping[0]
+    )));
+}
+
+#[test]
+fn test_manifest_records_ram_schema() {
+    let dir = tempfile::tempdir().unwrap();
+    let source =
+        "program test\nsec ram: { 17: Field }\nfn main() {\n    pub_write(ram_read(17))\n}\n";
+    let file = crate::parse_source_silent(source, "test.tri").unwrap();
+    let cost = crate::cost::CostAnalyzer::default().analyze_file(&file);
+    let tc = TerrainConfig::triton();
+    let mut ram = RamLayout::new(&tc);
+    assert!(ram.reserve_sec_ram(&file, &tc).is_empty());
+
+    let result = generate_artifact(
+        "test",
+        "0.1.0",
+        "halt\n",
+        &file,
+        &cost,
+        &ManifestContext {
+            target_vm: &tc,
+            target_os: None,
+            provenance: &Provenance::default(),
+            ram: &ram,
+        },
+        dir.path(),
+    )
+    .unwrap();
+
+    let kinds: Vec<&str> = result
+        .manifest
+        .ram
+        .iter()
+        .map(|r| r.kind.as_str())
+        .collect();
+    assert_eq!(kinds, ["sec_ram", "temp", "pool", "spill"]);
+    let json = result.manifest.to_json();
+    assert!(json.contains(
+        "{ \"kind\": \"sec_ram\", \"label\": \"test: ram[17]: Field\", \"start\": 17, \"end\": 18 }"
+    ));
+    assert!(json.contains("\"kind\": \"spill\""));
+    assert!(json.contains("\"end\": null }"));
+}
+
+#[test]
+fn test_data_segments_are_shipped_as_a_signed_ram_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = "program test\nfn main() {}\n";
+    let file = crate::parse_source_silent(source, "test.tri").unwrap();
+    let cost = crate::cost::CostAnalyzer::default().analyze_file(&file);
+    let tc = TerrainConfig::triton();
+    let tables = [
+        ("test.A".to_string(), vec![7, 8]),
+        ("test.B".to_string(), vec![9]),
+    ]
+    .into_iter()
+    .collect();
+    let mut ram = RamLayout::new(&tc);
+    ram.reserve_data(&crate::tir::ram::data_segments(&tables, &tc));
+
+    let mut result = generate_artifact(
+        "test",
+        "0.1.0",
+        "halt\n",
+        &file,
+        &cost,
+        &ManifestContext {
+            target_vm: &tc,
+            target_os: None,
+            provenance: &Provenance::default(),
+            ram: &ram,
+        },
+        dir.path(),
+    )
+    .unwrap();
+    sign_artifact(&mut result, &SigningKey::from_seed([7; 32])).unwrap();
+
+    let image_path = result.ram_image_path.clone().unwrap();
+    assert_eq!(image_path, result.artifact_dir.join(RAM_IMAGE_FILE));
+    let image = std::fs::read_to_string(&image_path).unwrap();
+    assert_eq!(
+        image,
+        "{\n  \"segments\": [\n    \
+         { \"name\": \"test.A\", \"start\": 536870909, \"values\": [7, 8] },\n    \
+         { \"name\": \"test.B\", \"start\": 536870911, \"values\": [9] }\n  ]\n}\n"
+    );
+    let entry = result.manifest.ram_image.as_ref().unwrap();
+    assert_eq!((entry.file.as_str(), entry.words), ("ram.json", 3));
+    assert!(result
+        .manifest
+        .to_json()
+        .contains("\"ram_image\": { \"file\": \"ram.json\", \"words\": 3, \"digest\": \""));
+    assert!(matches!(
+        signing::verify_artifact(&result.artifact_dir, &trusted()),
+        signing::SignatureStatus::Valid { .. }
+    ));
+
+    std::fs::write(&image_path, image.replace("[9]", "[10]")).unwrap();
+    assert_eq!(
+        signing::verify_artifact(&result.artifact_dir, &trusted()),
+        signing::SignatureStatus::Invalid("ram.json does not match ram_image digest".to_string())
+    );
+}
+
+#[test]
+fn test_proof_claim_encode_matches_std_proof_layout() {
+    let claim = ProofClaim {
+        program_digest: [10, 11, 12, 13, 14],
+        num_inputs: 2,
+        num_outputs: 1,
+    };
+    let fields = claim.encode();
+    assert_eq!(fields.len(), claim::CLAIM_WIDTH);
+    assert_eq!(&fields[..claim::DIGEST_WIDTH], &[10, 11, 12, 13, 14]);
+    assert_eq!(fields[claim::CLAIM_NUM_INPUTS], 2);
+    assert_eq!(fields[claim::CLAIM_NUM_OUTPUTS], 1);
+    assert_eq!(ProofClaim::decode(&fields).unwrap(), claim);
+    assert!(ProofClaim::decode(&fields[..6]).is_err());
+}
+
+#[test]
+fn test_verifier_public_input_appends_io_after_claim() {
+    let claim = crate::field::proof::Claim {
+        program_hash: vec![1, 2, 3, 4, 5],
+        public_input: vec![7, 8],
+        public_output: vec![9],
+    };
+    let fields = claim::verifier_public_input(&claim).unwrap();
+    assert_eq!(fields, vec![1, 2, 3, 4, 5, 2, 1, 7, 8, 9]);
+
+    let short = crate::field::proof::Claim {
+        program_hash: vec![1, 2, 3, 4],
+        public_input: vec![],
+        public_output: vec![],
+    };
+    assert!(claim::verifier_public_input(&short).is_err());
+}
+
+#[test]
+fn test_proof_stream_encode_prefixes_length() {
+    let mut stream = claim::ProofStream::new();
+    stream.push_digest(&[1, 2, 3, 4, 5]);
+    stream.push_xfield(&[6, 7, 8]);
+    stream.push_field(9);
+    assert_eq!(stream.encode(), vec![9, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+}
+
+#[test]
+fn test_claim_template_digest_deterministic() {
+    let tasm = "push 1\nwrite_io 1\nhalt\n";
+    let a = ProofClaim::for_program(tasm);
+    assert_eq!(a, ProofClaim::for_program(tasm));
+    assert_ne!(a, ProofClaim::for_program("halt\n"));
+    assert_eq!((a.num_inputs, a.num_outputs), (0, 0));
+}
+
+/// Trusts the `[7; 32]` key the test artifacts are signed with.
+fn trusted() -> signing::TrustedKeys {
+    let mut trusted = signing::TrustedKeys::default();
+    trusted
+        .add(&signing::encode_hex(
+            SigningKey::from_seed([7; 32]).public_key(),
+        ))
+        .unwrap();
+    trusted
+}
+
+fn signed_test_artifact(dir: &Path) -> PackageResult {
+    let source = "program test\nfn main() {\n    pub_write(pub_read())\n}\n";
+    let file = crate::parse_source_silent(source, "test.tri").unwrap();
+    let cost = crate::cost::CostAnalyzer::default().analyze_file(&file);
+    let mut result = generate_artifact(
+        "test",
+        "0.1.0",
+        "read_io 1\nwrite_io 1\nhalt\n",
+        &file,
+        &cost,
+        &ManifestContext {
+            target_vm: &TerrainConfig::triton(),
+            target_os: None,
+            provenance: &Provenance::default(),
+            ram: &RamLayout::default(),
+        },
+        dir,
+    )
+    .unwrap();
+    sign_artifact(&mut result, &SigningKey::from_seed([7; 32])).unwrap();
+    result
+}
+
+#[test]
+fn test_signed_artifact_verifies_with_signer_fingerprint() {
+    let dir = tempfile::tempdir().unwrap();
+    let result = signed_test_artifact(dir.path());
+    let expected = SigningKey::from_seed([7; 32]).fingerprint();
+
+    let manifest = std::fs::read_to_string(&result.manifest_path).unwrap();
+    assert!(manifest.contains("\"algorithm\": \"ed25519\""));
+    assert_eq!(
+        signing::verify_artifact(&result.artifact_dir, &trusted()),
+        signing::SignatureStatus::Valid {
+            fingerprint: expected.clone()
+        }
+    );
+    assert_eq!(
+        signing::verify_artifact(&result.artifact_dir, &trusted()).require("artifact", false),
+        Ok(Some(expected))
+    );
+}
+
+#[test]
+fn test_tampered_manifest_or_tasm_fails_verification() {
+    let dir = tempfile::tempdir().unwrap();
+    let result = signed_test_artifact(dir.path());
+    let manifest = std::fs::read_to_string(&result.manifest_path).unwrap();
+
+    let edited = manifest.replace("\"version\": \"0.1.0\"", "\"version\": \"0.1.1\"");
+    std::fs::write(&result.manifest_path, &edited).unwrap();
+    assert!(matches!(
+        signing::verify_artifact(&result.artifact_dir, &trusted()),
+        signing::SignatureStatus::Invalid(_)
+    ));
+
+    std::fs::write(&result.manifest_path, &manifest).unwrap();
+    std::fs::write(&result.tasm_path, "push 0\nhalt\n").unwrap();
+    let status = signing::verify_artifact(&result.artifact_dir, &trusted());
+    assert_eq!(
+        status,
+        signing::SignatureStatus::Invalid("program.tasm does not match program_digest".to_string())
+    );
+    assert!(status.require("artifact", false).is_err());
+    assert_eq!(status.require("artifact", true), Ok(None));
+}
+
+#[test]
+fn test_resigned_artifact_is_untrusted() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut result = signed_test_artifact(dir.path());
+    let tasm = "push 0\nwrite_io 1\nhalt\n";
+    std::fs::write(&result.tasm_path, tasm).unwrap();
+    result.manifest.program_digest =
+        ContentHash(crate::poseidon2::hash_bytes(tasm.as_bytes())).to_hex();
+    let attacker = SigningKey::from_seed([8; 32]);
+    sign_artifact(&mut result, &attacker).unwrap();
+
+    let status = signing::verify_artifact(&result.artifact_dir, &trusted());
+    assert_eq!(
+        status,
+        signing::SignatureStatus::Untrusted {
+            fingerprint: attacker.fingerprint()
+        }
+    );
+    let err = status.require("artifact", false).unwrap_err();
+    assert!(err.contains("--trusted-key"), "{}", err);
+    assert!(matches!(
+        signing::verify_artifact(&result.artifact_dir, &signing::TrustedKeys::default()),
+        signing::SignatureStatus::Untrusted { .. }
+    ));
+}
+
+#[test]
+fn test_trusted_keys_load_pub_files_and_hex() {
+    let dir = tempfile::tempdir().unwrap();
+    let keys = dir.path().join("keys");
+    let (own, _) = signing::load_or_create_key(&keys).unwrap();
+    let other = SigningKey::from_seed([8; 32]);
+    std::fs::write(
+        keys.join("alice.pub"),
+        format!("{}\n", signing::encode_hex(other.public_key())),
+    )
+    .unwrap();
+
+    let trusted = signing::TrustedKeys::load_dir(&keys).unwrap();
+    assert!(trusted.contains(own.public_key()));
+    assert!(trusted.contains(other.public_key()));
+
+    let mut extra = signing::TrustedKeys::default();
+    extra
+        .add(&signing::encode_hex(
+            SigningKey::from_seed([7; 32]).public_key(),
+        ))
+        .unwrap();
+    assert!(extra.contains(SigningKey::from_seed([7; 32]).public_key()));
+    assert!(extra.add("not-a-key").is_err());
+    let none = signing::TrustedKeys::load_dir(&dir.path().join("missing")).unwrap();
+    assert!(!none.contains(own.public_key()));
+}
+
+#[test]
+fn test_reformatted_manifest_still_verifies() {
+    let dir = tempfile::tempdir().unwrap();
+    let result = signed_test_artifact(dir.path());
+    let manifest = std::fs::read_to_string(&result.manifest_path).unwrap();
+    let compact: String = manifest.lines().map(str::trim).collect();
+    std::fs::write(&result.manifest_path, compact).unwrap();
+    assert!(matches!(
+        signing::verify_artifact(&result.artifact_dir, &trusted()),
+        signing::SignatureStatus::Valid { .. }
+    ));
+}
+
+#[test]
+fn test_unsigned_artifact_refused_unless_allowed() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut result = signed_test_artifact(dir.path());
+    result.manifest.signature = None;
+    std::fs::write(&result.manifest_path, result.manifest.to_json()).unwrap();
+
+    let status = signing::verify_artifact(&result.artifact_dir, &trusted());
+    assert_eq!(status, signing::SignatureStatus::Unsigned);
+    let err = status.require("artifact", false).unwrap_err();
+    assert!(err.contains("--allow-unsigned"), "{}", err);
+    assert_eq!(status.require("artifact", true), Ok(None));
+}
+
+#[test]
+fn test_signing_key_created_once_and_reloaded() {
+    let dir = tempfile::tempdir().unwrap();
+    let keys = dir.path().join("keys");
+    let (first, created) = signing::load_or_create_key(&keys).unwrap();
+    assert!(created);
+    let (second, created) = signing::load_or_create_key(&keys).unwrap();
+    assert!(!created);
+    assert_eq!(first.fingerprint(), second.fingerprint());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(keys.join("default.key"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn test_definition_signature_covers_content_hash() {
+    let key = SigningKey::from_seed([9; 32]);
+    let hash = "ab".repeat(32);
+    let public = signing::encode_hex(key.public_key());
+    let sig = signing::encode_hex(&key.sign(hash.as_bytes()));
+    let mut trusted = signing::TrustedKeys::default();
+    trusted.add(&public).unwrap();
+
+    assert_eq!(
+        signing::verify_definition(&hash, &public, &sig, &trusted),
+        signing::SignatureStatus::Valid {
+            fingerprint: key.fingerprint()
+        }
+    );
+    assert_eq!(
+        signing::verify_definition(&hash, &public, &sig, &signing::TrustedKeys::default()),
+        signing::SignatureStatus::Untrusted {
+            fingerprint: key.fingerprint()
+        }
+    );
+    assert!(matches!(
+        signing::verify_definition(&"cd".repeat(32), &public, &sig, &trusted),
+        signing::SignatureStatus::Invalid(_)
+    ));
+    assert_eq!(
+        signing::verify_definition(&hash, "", "", &trusted),
+        signing::SignatureStatus::Unsigned
+    );
+}
+
+#[test]
+fn test_provenance_lists_project_and_library_modules() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("helper.tri"),
+        "module helper\n\npub fn double(x: Field) -> Field {\n    x + x\n}\n",
+    )
+    .unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program demo\n\nuse helper\nuse std.math.fibonacci\n\nfn main() {\n    pub_write(helper.double(fibonacci.fib256(pub_read())))\n}\n",
+    )
+    .unwrap();
+
+    let options = crate::CompileOptions::for_profile("release");
+    let provenance = collect_provenance(&entry, &options, None).unwrap();
+
+    assert_eq!(provenance.options.profile, "release");
+    assert_eq!(provenance.options.cfg_flags, vec!["release".to_string()]);
+    assert_eq!(provenance.options.target, "triton");
+
+    let find = |name: &str| {
+        provenance
+            .modules
+            .iter()
+            .find(|m| m.module == name)
+            .unwrap_or_else(|| panic!("{} missing from {:?}", name, provenance.modules))
+    };
+    let helper = find("helper");
+    assert_eq!(helper.source, "path:helper.tri");
+    assert_eq!(helper.path, "helper.tri");
+    let fib = find("std.math.fibonacci");
+    assert_eq!(fib.source, "std");
+    assert_eq!(fib.path, "math/fibonacci.tri");
+    assert_eq!(fib.version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+    assert_eq!(provenance.modules.last().unwrap().module, "demo");
+
+    let source = std::fs::read_to_string(dir.path().join("helper.tri")).unwrap();
+    let file = crate::parse_source_silent(&source, "helper.tri").unwrap();
+    assert_eq!(
+        helper.content_hash,
+        crate::hash::hash_file_content(&file).to_hex()
+    );
+}
+
+#[test]
+fn test_provenance_is_serialized_and_signed() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut result = signed_test_artifact(dir.path());
+    result.manifest.provenance = Provenance {
+        options: provenance::ProvenanceOptions {
+            target: "triton".to_string(),
+            profile: "release".to_string(),
+            cfg_flags: vec!["release".to_string()],
+        },
+        modules: vec![provenance::ProvenanceModule {
+            module: "dep.lib".to_string(),
+            source: "registry:http://127.0.0.1:8090".to_string(),
+            path: "main.tri".to_string(),
+            content_hash: "ab".repeat(32),
+            version: None,
+        }],
+    };
+    sign_artifact(&mut result, &SigningKey::from_seed([7; 32])).unwrap();
+
+    let manifest = std::fs::read_to_string(&result.manifest_path).unwrap();
+    assert!(manifest.contains("\"cfg_flags\": [\"release\"]"));
+    assert!(manifest.contains("\"source\": \"registry:http://127.0.0.1:8090\""));
+    assert!(manifest.contains("\"version\": null"));
+    assert!(manifest.find("\"provenance\"") < manifest.find("\"signature\": {"));
+    assert!(matches!(
+        signing::verify_artifact(&result.artifact_dir, &trusted()),
+        signing::SignatureStatus::Valid { .. }
+    ));
+
+    let edited = manifest.replace("\"profile\": \"release\"", "\"profile\": \"debug\"");
+    std::fs::write(&result.manifest_path, edited).unwrap();
+    assert!(matches!(
+        signing::verify_artifact(&result.artifact_dir, &trusted()),
+        signing::SignatureStatus::Invalid(_)
+    ));
+}
+
+#[test]
+fn test_upgrade_diff_against_deployed_artifact() {
+    use upgrade::{upgrade_diff, Change, DeployedManifest, Equivalence};
+
+    let dir = tempfile::tempdir().unwrap();
+    let v1 = "program test\nfn double(x: Field) -> Field {\n    x + x\n}\nfn main() {\n    pub_write(double(pub_read()))\n}\n";
+    let v2 = "program test\nfn double(x: Field) -> Field {\n    x * 2\n}\nfn triple(x: Field) -> Field {\n    x * 3\n}\nfn main() {\n    pub_write(double(pub_read()))\n}\n";
+    let old_file = crate::parse_source_silent(v1, "test.tri").unwrap();
+    let new_file = crate::parse_source_silent(v2, "test.tri").unwrap();
+    let cost = |file: &ast::File| crate::cost::CostAnalyzer::default().analyze_file(file);
+    let deployed = generate_artifact(
+        "test",
+        "0.1.0",
+        "read_io 1\ndup0\nadd\nwrite_io 1\nhalt\n",
+        &old_file,
+        &cost(&old_file),
+        &ManifestContext {
+            target_vm: &TerrainConfig::triton(),
+            target_os: None,
+            provenance: &Provenance::default(),
+            ram: &RamLayout::default(),
+        },
+        dir.path(),
+    )
+    .unwrap();
+    let current = build_manifest(
+        "test",
+        "0.2.0",
+        "read_io 1\npush 2\nmul\nwrite_io 1\nhalt\n",
+        &new_file,
+        &cost(&new_file),
+        &ManifestContext {
+            target_vm: &TerrainConfig::triton(),
+            target_os: None,
+            provenance: &Provenance::default(),
+            ram: &RamLayout::default(),
+        },
+    );
+
+    let old = DeployedManifest::read(&deployed.artifact_dir).unwrap();
+    assert_eq!(old.program_digest, deployed.manifest.program_digest);
+    assert_eq!(old.functions.len(), 2);
+    assert_eq!(old.cost.table_names, deployed.manifest.cost.table_names);
+
+    let old_double = old_file
+        .items
+        .iter()
+        .find(|i| matches!(&i.node, ast::Item::Fn(f) if f.name.node == "double"))
+        .map(|i| crate::ast::display::format_item(&i.node))
+        .unwrap();
+    let double_hash = old.functions[0].hash.clone();
+    let report = upgrade_diff(&old, &current, &new_file, |hash| {
+        (hash.to_hex() == double_hash).then(|| old_double.clone())
+    });
+
+    let changes: Vec<(&str, Change)> = report
+        .functions
+        .iter()
+        .map(|f| (f.name.as_str(), f.change))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            ("double", Change::Changed),
+            ("main", Change::Changed),
+            ("triple", Change::Added)
+        ]
+    );
+    match &report.functions[0].equivalence {
+        Some(Equivalence::Checked(result)) => {
+            assert_eq!(result.verdict, crate::equiv::EquivalenceVerdict::Equivalent)
+        }
+        other => panic!("expected an equivalence check, got {:?}", other),
+    }
+    assert!(report.abi_changes.is_empty());
+    let text = report.format_report();
+    assert!(text.contains("~ double  changed, equivalent"));
+    // `main` calls `double`, so its content hash changed with it.
+    assert!(text.contains("~ main    changed, not checked: deployed source not in the codebase"));
+    assert!(text.contains("+ triple  added"));
+}
+
+#[test]
+fn test_object_store_shares_tasm_between_artifacts_and_flattens() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = "program test\nfn main() {\n    pub_write(pub_read())\n}\n";
+    let file = crate::parse_source_silent(source, "test.tri").unwrap();
+    let cost = crate::cost::CostAnalyzer::default().analyze_file(&file);
+    let helper = "__helper:\n    dup 0\n    mul\n    return\n";
+    let package = |name: &str, main: &str| {
+        let tasm = format!("call __main\nhalt\n__main:\n{}    return\n{}", main, helper);
+        let mut result = generate_artifact(
+            name,
+            "0.1.0",
+            &tasm,
+            &file,
+            &cost,
+            &ManifestContext {
+                target_vm: &TerrainConfig::triton(),
+                target_os: None,
+                provenance: &Provenance::default(),
+                ram: &RamLayout::default(),
+            },
+            dir.path(),
+        )
+        .unwrap();
+        sign_artifact(&mut result, &SigningKey::from_seed([7; 32])).unwrap();
+        (tasm, result)
+    };
+    let (tasm_a, a) = package("a", "    read_io 1\n    call __helper\n    write_io 1\n");
+    let (_, b) = package("b", "    push 3\n    call __helper\n    write_io 1\n");
+
+    assert_eq!(objects::split_objects(&tasm_a).concat(), tasm_a);
+    let store = objects::default_store(&a.artifact_dir);
+    let first = objects::dedup_artifact(&a, &store).unwrap();
+    assert_eq!((first.objects, first.written), (3, 3));
+    let second = objects::dedup_artifact(&b, &store).unwrap();
+    assert_eq!((second.objects, second.written), (3, 1));
+    assert_eq!(
+        second.shared_bytes,
+        "call __main\nhalt\n".len() + helper.len()
+    );
+
+    assert!(!a.tasm_path.exists());
+    assert!(matches!(
+        signing::verify_artifact(&a.artifact_dir, &trusted()),
+        signing::SignatureStatus::Valid { .. }
+    ));
+    objects::flatten_artifact(&a.artifact_dir, &store).unwrap();
+    assert_eq!(std::fs::read_to_string(&a.tasm_path).unwrap(), tasm_a);
+    assert!(!a.artifact_dir.join(objects::LINK_FILE).exists());
+
+    let object = store.join(format!("{}.tasm", objects::object_hash(helper)));
+    std::fs::write(&object, "__helper:\n    return\n").unwrap();
+    assert!(objects::flatten_artifact(&b.artifact_dir, &store).is_err());
+}
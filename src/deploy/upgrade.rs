@@ -0,0 +1,581 @@
+//! Upgrade review: compare a project against a deployed artifact.
+//!
+//! `trident upgrade-diff` reads the `manifest.json` of a `.deploy/`
+//! directory, recompiles the project, and reports what an upgrade would
+//! change: functions added, removed, or changed (by content hash),
+//! signature and entry-point changes, and cost deltas. Each changed
+//! function whose deployed source is in the codebase is checked for
+//! equivalence with its new version, so a refactor that keeps behavior
+//! is told apart from one that does not.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::{ManifestCost, ManifestEntryCost, ManifestFunction, PackageManifest};
+use crate::ast::{self, Item};
+use crate::config::project::ProjectMetadata;
+use crate::config::target::parse_string_array;
+use crate::equiv::{check_equivalence, EquivalenceResult, EquivalenceVerdict};
+use crate::hash::ContentHash;
+use crate::span::Spanned;
+
+/// The parts of a deployed `manifest.json` an upgrade is compared on.
+#[derive(Clone, Debug)]
+pub struct DeployedManifest {
+    pub name: String,
+    pub version: String,
+    pub program_digest: String,
+    pub source_hash: String,
+    pub target_vm: String,
+    pub entry_point: String,
+    pub cost: ManifestCost,
+    /// Present when the artifact was packaged with `--entry-costs`.
+    pub entry_costs: Option<Vec<ManifestEntryCost>>,
+    pub functions: Vec<ManifestFunction>,
+    /// Empty when the project declared no distribution metadata.
+    pub metadata: ProjectMetadata,
+}
+
+/// How a function differs between the deployed and the new program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+/// Equivalence of a changed function with its deployed version.
+#[derive(Clone, Debug)]
+pub enum Equivalence {
+    Checked(EquivalenceResult),
+    /// Not checked, and why.
+    Skipped(String),
+}
+
+/// One function of either program.
+#[derive(Clone, Debug)]
+pub struct FunctionDiff {
+    pub name: String,
+    pub change: Change,
+    pub deployed: Option<ManifestFunction>,
+    pub current: Option<ManifestFunction>,
+    /// For changed functions.
+    pub equivalence: Option<Equivalence>,
+}
+
+/// One cost table (or the padded height), before and after.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostDelta {
+    pub table: String,
+    pub deployed: u64,
+    pub current: u64,
+}
+
+/// Everything `trident upgrade-diff` reports.
+#[derive(Clone, Debug)]
+pub struct UpgradeReport {
+    pub deployed: DeployedManifest,
+    pub program_digest: String,
+    pub version: String,
+    pub functions: Vec<FunctionDiff>,
+    /// Interface changes: signatures, entry point, entry tags, target.
+    pub abi_changes: Vec<String>,
+    /// Cost tables followed by the padded height.
+    pub cost: Vec<CostDelta>,
+    /// Padded height of each entry point, when both sides record it.
+    pub entry_costs: Vec<CostDelta>,
+}
+
+impl DeployedManifest {
+    /// Read `manifest.json` from a `.deploy/` directory.
+    pub fn read(artifact_dir: &Path) -> Result<Self, String> {
+        let path = artifact_dir.join("manifest.json");
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| format!("cannot read '{}': {}", path.display(), e))?;
+        Self::from_json(&json)
+    }
+
+    /// Parse the manifest format `PackageManifest::to_json` writes.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut top = BTreeMap::new();
+        let mut target_vm = None;
+        let mut cost = ManifestCost {
+            table_values: Vec::new(),
+            table_names: Vec::new(),
+            padded_height: 0,
+        };
+        let mut entry_costs: Option<Vec<ManifestEntryCost>> = None;
+        let mut functions = Vec::new();
+        let mut metadata = ProjectMetadata::default();
+
+        let mut section = "";
+        for line in json.lines() {
+            if let Some(key) = line
+                .strip_prefix("  \"")
+                .and_then(|rest| rest.split_once('"'))
+                .map(|(key, _)| key)
+            {
+                section = key;
+                if let Some((key, value)) = fields(line).into_iter().next() {
+                    top.insert(key, value);
+                }
+                if section == "entry_costs" {
+                    entry_costs = Some(Vec::new());
+                }
+                continue;
+            }
+            if !line.starts_with("    ") || line.starts_with("     ") {
+                continue;
+            }
+            let values = fields(line);
+            let get = |key: &str| values.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+            match section {
+                "target" => {
+                    if let Some(vm) = get("vm") {
+                        target_vm = Some(vm.clone());
+                    }
+                }
+                "cost" => {
+                    for (key, value) in &values {
+                        let value = value.parse().unwrap_or(0);
+                        if key == "padded_height" {
+                            cost.padded_height = value;
+                        } else {
+                            cost.table_names.push(key.clone());
+                            cost.table_values.push(value);
+                        }
+                    }
+                }
+                "entry_costs" => {
+                    let Some(name) = get("name") else {
+                        continue;
+                    };
+                    let tables = values.iter().filter(|(k, _)| cost.table_names.contains(k));
+                    entry_costs
+                        .get_or_insert_with(Vec::new)
+                        .push(ManifestEntryCost {
+                            name: name.clone(),
+                            tag: get("tag").and_then(|t| t.parse().ok()),
+                            table_values: tables.map(|(_, v)| v.parse().unwrap_or(0)).collect(),
+                            padded_height: get("padded_height")
+                                .and_then(|h| h.parse().ok())
+                                .unwrap_or(0),
+                        });
+                }
+                "functions" => {
+                    if let (Some(name), Some(hash), Some(signature)) =
+                        (get("name"), get("hash"), get("signature"))
+                    {
+                        functions.push(ManifestFunction {
+                            name: name.clone(),
+                            hash: hash.clone(),
+                            signature: signature.clone(),
+                        });
+                    }
+                }
+                "metadata" => {
+                    if let Some(authors) = line.trim().strip_prefix("\"authors\":") {
+                        metadata.authors = parse_string_array(authors.trim().trim_end_matches(','));
+                        continue;
+                    }
+                    for (key, value) in values {
+                        let value = (value != "null").then_some(value);
+                        match key.as_str() {
+                            "license" => metadata.license = value,
+                            "description" => metadata.description = value,
+                            "repository" => metadata.repository = value,
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let field = |key: &str| {
+            top.get(key)
+                .cloned()
+                .ok_or_else(|| format!("manifest has no '{}'", key))
+        };
+        Ok(DeployedManifest {
+            name: field("name")?,
+            version: field("version")?,
+            program_digest: field("program_digest")?,
+            source_hash: field("source_hash")?,
+            target_vm: target_vm.ok_or("manifest has no target vm")?,
+            entry_point: field("entry_point")?,
+            cost,
+            entry_costs,
+            functions,
+            metadata,
+        })
+    }
+}
+
+/// The `.deploy/` directory under one of `roots` whose program digest
+/// starts with `digest`.
+pub fn find_artifact(roots: &[PathBuf], digest: &str) -> Result<PathBuf, String> {
+    let mut found: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            let is_artifact = dir.extension().is_some_and(|e| e == "deploy");
+            if !is_artifact || found.contains(&dir) {
+                continue;
+            }
+            if let Ok(manifest) = DeployedManifest::read(&dir) {
+                if manifest.program_digest.starts_with(digest) {
+                    found.push(dir);
+                }
+            }
+        }
+    }
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => Err(format!("no .deploy/ artifact with digest '{}'", digest)),
+        _ => Err(format!(
+            "digest '{}' is ambiguous: {}",
+            digest,
+            found
+                .iter()
+                .map(|d| d.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Compare `current`, the manifest of the recompiled program whose
+/// program module is `file`, against `deployed`. `deployed_source`
+/// returns the source of a deployed function by content hash, for the
+/// equivalence checks; the check sees the deployed function alongside
+/// the current versions of everything it calls.
+pub fn upgrade_diff(
+    deployed: &DeployedManifest,
+    current: &PackageManifest,
+    file: &ast::File,
+    deployed_source: impl Fn(&ContentHash) -> Option<String>,
+) -> UpgradeReport {
+    let old: BTreeMap<&str, &ManifestFunction> = deployed
+        .functions
+        .iter()
+        .map(|f| (f.name.as_str(), f))
+        .collect();
+    let new: BTreeMap<&str, &ManifestFunction> = current
+        .functions
+        .iter()
+        .map(|f| (f.name.as_str(), f))
+        .collect();
+
+    let mut names: Vec<&str> = old.keys().chain(new.keys()).copied().collect();
+    names.sort_unstable();
+    names.dedup();
+    let mut functions = Vec::new();
+    let mut abi_changes = Vec::new();
+    for name in names {
+        let (before, after) = (old.get(name).copied(), new.get(name).copied());
+        let change = match (before, after) {
+            (None, _) => Change::Added,
+            (_, None) => Change::Removed,
+            (Some(a), Some(b)) if a.hash == b.hash => Change::Unchanged,
+            _ => Change::Changed,
+        };
+        if let (Some(a), Some(b)) = (before, after) {
+            if a.signature != b.signature {
+                abi_changes.push(format!("{} -> {}", a.signature, b.signature));
+            }
+        }
+        let equivalence = (change == Change::Changed)
+            .then(|| check_deployed(name, before?, file, &deployed_source));
+        functions.push(FunctionDiff {
+            name: name.to_string(),
+            change,
+            deployed: before.cloned(),
+            current: after.cloned(),
+            equivalence: equivalence.flatten(),
+        });
+    }
+
+    if deployed.entry_point != current.entry_point {
+        abi_changes.push(format!(
+            "entry point: {} -> {}",
+            deployed.entry_point, current.entry_point
+        ));
+    }
+    if deployed.target_vm != current.target_vm {
+        abi_changes.push(format!(
+            "target: {} -> {}",
+            deployed.target_vm, current.target_vm
+        ));
+    }
+
+    let mut entry_costs = Vec::new();
+    if let (Some(before), Some(after)) = (&deployed.entry_costs, &current.entry_costs) {
+        let tags = |entries: &[ManifestEntryCost]| -> BTreeMap<u64, String> {
+            entries
+                .iter()
+                .filter_map(|e| Some((e.tag?, e.name.clone())))
+                .collect()
+        };
+        let (old_tags, new_tags) = (tags(before), tags(after));
+        for (tag, name) in &old_tags {
+            match new_tags.get(tag) {
+                Some(new_name) if new_name != name => {
+                    abi_changes.push(format!("entry tag {}: {} -> {}", tag, name, new_name))
+                }
+                None => abi_changes.push(format!("entry tag {} removed ({})", tag, name)),
+                _ => {}
+            }
+        }
+        for (tag, name) in &new_tags {
+            if !old_tags.contains_key(tag) {
+                abi_changes.push(format!("entry tag {} added ({})", tag, name));
+            }
+        }
+        for entry in after {
+            if let Some(old) = before.iter().find(|e| e.name == entry.name) {
+                entry_costs.push(CostDelta {
+                    table: entry.name.clone(),
+                    deployed: old.padded_height,
+                    current: entry.padded_height,
+                });
+            }
+        }
+    }
+
+    let mut cost: Vec<CostDelta> = current
+        .cost
+        .table_names
+        .iter()
+        .zip(&current.cost.table_values)
+        .map(|(table, &value)| CostDelta {
+            table: table.clone(),
+            deployed: deployed
+                .cost
+                .table_names
+                .iter()
+                .position(|t| t == table)
+                .and_then(|i| deployed.cost.table_values.get(i).copied())
+                .unwrap_or(0),
+            current: value,
+        })
+        .collect();
+    cost.push(CostDelta {
+        table: "padded_height".to_string(),
+        deployed: deployed.cost.padded_height,
+        current: current.cost.padded_height,
+    });
+
+    UpgradeReport {
+        deployed: deployed.clone(),
+        program_digest: current.program_digest.clone(),
+        version: current.version.clone(),
+        functions,
+        abi_changes,
+        cost,
+        entry_costs,
+    }
+}
+
+/// Check a changed function against its deployed version, which is
+/// renamed `<name>__deployed` and added to a copy of `file`.
+fn check_deployed(
+    name: &str,
+    deployed: &ManifestFunction,
+    file: &ast::File,
+    deployed_source: &impl Fn(&ContentHash) -> Option<String>,
+) -> Option<Equivalence> {
+    let skipped = |why: &str| Some(Equivalence::Skipped(why.to_string()));
+    let Some(source) = ContentHash::from_hex(&deployed.hash).and_then(|h| deployed_source(&h))
+    else {
+        return skipped("deployed source not in the codebase");
+    };
+    let wrapped = format!("module __deployed\n{}\n", source);
+    let Ok(old_file) = crate::parse_source_silent(&wrapped, "<deployed>") else {
+        return skipped("deployed source does not parse");
+    };
+    let Some(mut old_fn) = old_file.items.into_iter().find_map(|item| match item.node {
+        Item::Fn(f) if f.name.node == name => Some(f),
+        _ => None,
+    }) else {
+        return skipped("deployed source does not define the function");
+    };
+    if file
+        .items
+        .iter()
+        .all(|item| !matches!(&item.node, Item::Fn(f) if f.name.node == name && f.body.is_some()))
+    {
+        return skipped("no body to compare");
+    }
+
+    let renamed = format!("{}__deployed", name);
+    old_fn.name.node = renamed.clone();
+    let mut combined = file.clone();
+    combined.items.push(Spanned::dummy(Item::Fn(old_fn)));
+    Some(Equivalence::Checked(check_equivalence(
+        &combined, &renamed, name,
+    )))
+}
+
+impl UpgradeReport {
+    /// Whether the recompiled program is exactly the deployed one.
+    pub fn is_identical(&self) -> bool {
+        self.program_digest == self.deployed.program_digest
+    }
+
+    /// Format the human-readable change report.
+    pub fn format_report(&self) -> String {
+        let short = |digest: &str| digest.chars().take(16).collect::<String>();
+        let mut out = format!(
+            "Upgrade diff: {} {} ({}) -> {} ({})\n",
+            self.deployed.name,
+            self.deployed.version,
+            short(&self.deployed.program_digest),
+            self.version,
+            short(&self.program_digest)
+        );
+        if self.is_identical() {
+            out.push_str("\nThe program is identical to the deployed one.\n");
+            return out;
+        }
+
+        out.push_str("\nFunctions:\n");
+        let width = self
+            .functions
+            .iter()
+            .map(|f| f.name.len())
+            .max()
+            .unwrap_or(0);
+        let mut unchanged = 0;
+        for f in &self.functions {
+            let (mark, what) = match f.change {
+                Change::Added => ('+', "added".to_string()),
+                Change::Removed => ('-', "removed".to_string()),
+                Change::Changed => ('~', format!("changed, {}", f.equivalence_summary())),
+                Change::Unchanged => {
+                    unchanged += 1;
+                    continue;
+                }
+            };
+            out.push_str(&format!(
+                "  {} {:<width$}  {}\n",
+                mark,
+                f.name,
+                what,
+                width = width
+            ));
+        }
+        out.push_str(&format!("  {} unchanged\n", unchanged));
+
+        out.push_str("\nInterface:\n");
+        if self.abi_changes.is_empty() {
+            out.push_str("  no changes\n");
+        }
+        for change in &self.abi_changes {
+            out.push_str(&format!("  {}\n", change));
+        }
+
+        out.push_str("\nCost:\n");
+        push_deltas(&mut out, &self.cost);
+        if !self.entry_costs.is_empty() {
+            out.push_str("\nEntry padded heights:\n");
+            push_deltas(&mut out, &self.entry_costs);
+        }
+
+        let checked: Vec<&EquivalenceResult> = self
+            .functions
+            .iter()
+            .filter_map(|f| match &f.equivalence {
+                Some(Equivalence::Checked(result)) => Some(result),
+                _ => None,
+            })
+            .collect();
+        if !checked.is_empty() {
+            out.push_str("\nEquivalence checks:\n");
+            for result in checked {
+                for line in result.format_report().lines() {
+                    out.push_str(&format!("  {}\n", line));
+                }
+            }
+        }
+        out
+    }
+}
+
+impl FunctionDiff {
+    fn equivalence_summary(&self) -> String {
+        match &self.equivalence {
+            Some(Equivalence::Checked(result)) => match result.verdict {
+                EquivalenceVerdict::Equivalent => "equivalent".to_string(),
+                EquivalenceVerdict::NotEquivalent => "NOT equivalent".to_string(),
+                EquivalenceVerdict::Unknown => "equivalence unknown".to_string(),
+            },
+            Some(Equivalence::Skipped(why)) => format!("not checked: {}", why),
+            None => "not checked".to_string(),
+        }
+    }
+}
+
+fn push_deltas(out: &mut String, deltas: &[CostDelta]) {
+    let width = deltas.iter().map(|d| d.table.len()).max().unwrap_or(0);
+    for d in deltas {
+        let diff = d.current as i128 - d.deployed as i128;
+        let sign = if diff > 0 { "+" } else { "" };
+        out.push_str(&format!(
+            "  {:<width$}  {} -> {} ({}{})\n",
+            d.table,
+            d.deployed,
+            d.current,
+            sign,
+            diff,
+            width = width
+        ));
+    }
+}
+
+/// The `"key": value` pairs of one manifest line, strings unescaped.
+fn fields(line: &str) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+    loop {
+        while chars.next_if(|&c| c != '"').is_some() {}
+        let Some(key) = read_string(&mut chars) else {
+            break;
+        };
+        while chars.next_if(|&c| c == ':' || c == ' ').is_some() {}
+        let value = if chars.peek() == Some(&'"') {
+            read_string(&mut chars).unwrap_or_default()
+        } else {
+            let mut raw = String::new();
+            while let Some(c) = chars.next_if(|&c| c != ',' && c != '}') {
+                raw.push(c);
+            }
+            raw.trim().to_string()
+        };
+        out.push((key, value));
+    }
+    out
+}
+
+/// Read a JSON string starting at its opening quote.
+fn read_string(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Option<String> {
+    chars.next_if_eq(&'"')?;
+    let mut out = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(out),
+            '\\' => match chars.next()? {
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                'r' => out.push('\r'),
+                other => out.push(other),
+            },
+            c => out.push(c),
+        }
+    }
+    None
+}
@@ -1,10 +1,85 @@
 use crate::span::Span;
 use std::cell::Cell;
+use std::io::IsTerminal;
+use std::sync::RwLock;
 
 thread_local! {
     static SUPPRESS_WARNINGS: Cell<bool> = const { Cell::new(false) };
 }
 
+static RENDER_CONFIG: RwLock<RenderConfig> = RwLock::new(RenderConfig::DEFAULT);
+
+/// When rendered diagnostics use ANSI colors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color when stderr is a terminal and `NO_COLOR` is unset or empty.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                    && std::io::stderr().is_terminal()
+            }
+        }
+    }
+}
+
+/// How diagnostics are rendered: colors, layout, and source context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderConfig {
+    pub color: ColorChoice,
+    /// One `file:line:col: severity: message` line per diagnostic.
+    pub terse: bool,
+    /// Source lines shown before and after the labelled span.
+    pub context_lines: usize,
+}
+
+impl RenderConfig {
+    pub const DEFAULT: RenderConfig = RenderConfig {
+        color: ColorChoice::Auto,
+        terse: false,
+        context_lines: 0,
+    };
+
+    /// The default layout without colors, for logs and golden files.
+    pub const PLAIN: RenderConfig = RenderConfig {
+        color: ColorChoice::Never,
+        ..RenderConfig::DEFAULT
+    };
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Set how `render` and `render_diagnostics` print, process-wide.
+pub fn set_render_config(config: RenderConfig) {
+    *RENDER_CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config;
+}
+
+/// The configuration `render` currently uses.
+pub fn render_config() -> RenderConfig {
+    *RENDER_CONFIG.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 1-based line and column (in characters) of a byte offset in `source`.
+pub fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = source.get(..offset.min(source.len())).unwrap_or(source);
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
 /// Suppress warning diagnostics on the current thread.
 /// Returns a guard that restores the previous state on drop.
 pub fn suppress_warnings() -> SuppressGuard {
@@ -32,6 +107,49 @@ pub struct Diagnostic {
     pub span: Span,
     pub notes: Vec<String>,
     pub help: Option<String>,
+    /// Instantiation chain and suggested edits. Few diagnostics have
+    /// either, so they are boxed to keep `Result<_, Diagnostic>` small.
+    related: Option<Box<Related>>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Related {
+    /// Generic instances the diagnostic arose in, innermost first.
+    instantiations: Vec<Instantiation>,
+    /// Machine-applicable edits that resolve the diagnostic.
+    suggestions: Vec<Suggestion>,
+}
+
+/// A machine-applicable edit: replace the source text at `span` with
+/// `replacement`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// What the edit does, as an imperative (`change the bound to 4`).
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// One link of an "instantiated from" chain: a generic instance and the
+/// call that requested it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instantiation {
+    /// Instance as written in source (`first<5>`).
+    pub instance: String,
+    /// File of the requesting call, as shown to the user.
+    pub file: String,
+    /// 1-based line of the requesting call.
+    pub line: usize,
+}
+
+impl std::fmt::Display for Instantiation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "in {}, instantiated at {}:{}",
+            self.instance, self.file, self.line
+        )
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -48,6 +166,7 @@ impl Diagnostic {
             span,
             notes: Vec::new(),
             help: None,
+            related: None,
         }
     }
 
@@ -58,6 +177,7 @@ impl Diagnostic {
             span,
             notes: Vec::new(),
             help: None,
+            related: None,
         }
     }
 
@@ -71,41 +191,152 @@ impl Diagnostic {
         self
     }
 
-    /// Render the diagnostic to stderr using ariadne.
+    /// Append a link to the "instantiated from" chain (innermost first).
+    pub fn with_instantiation(mut self, instantiation: Instantiation) -> Self {
+        self.related_mut().instantiations.push(instantiation);
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.related_mut().suggestions.push(suggestion);
+        self
+    }
+
+    /// Generic instances the diagnostic arose in, innermost first.
+    pub fn instantiations(&self) -> &[Instantiation] {
+        self.related
+            .as_ref()
+            .map_or(&[], |r| r.instantiations.as_slice())
+    }
+
+    /// Machine-applicable edits that resolve the diagnostic.
+    pub fn suggestions(&self) -> &[Suggestion] {
+        self.related
+            .as_ref()
+            .map_or(&[], |r| r.suggestions.as_slice())
+    }
+
+    pub fn into_suggestions(self) -> Vec<Suggestion> {
+        self.related.map(|r| r.suggestions).unwrap_or_default()
+    }
+
+    fn related_mut(&mut self) -> &mut Related {
+        self.related.get_or_insert_with(Default::default)
+    }
+
+    /// Render the diagnostic to stderr using the configuration set by
+    /// `set_render_config`.
     pub fn render(&self, filename: &str, source: &str) {
         if self.severity == Severity::Warning && warnings_suppressed() {
             return;
         }
-        use ariadne::{Color, Label, Report, ReportKind, Source};
+        eprint!("{}", self.render_with(filename, source, &render_config()));
+    }
+
+    /// Render the diagnostic as plain text, without colors. Warnings are
+    /// rendered even when suppressed.
+    pub fn render_to_string(&self, filename: &str, source: &str) -> String {
+        self.render_with(filename, source, &RenderConfig::PLAIN)
+    }
+
+    /// Render the diagnostic with an explicit configuration. Warnings are
+    /// rendered even when suppressed.
+    pub fn render_with(&self, filename: &str, source: &str, config: &RenderConfig) -> String {
+        if config.terse {
+            return self.render_terse(filename, source);
+        }
+        let mut out = Vec::new();
+        let _ = self
+            .report(filename, source, config)
+            .write((filename, ariadne::Source::from(source)), &mut out);
+        String::from_utf8_lossy(&out).into_owned()
+    }
+
+    fn render_terse(&self, filename: &str, source: &str) -> String {
+        let (line, column) = line_column(source, self.span.start as usize);
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let mut out = format!(
+            "{}:{}:{}: {}: {}",
+            filename, line, column, severity, self.message
+        );
+        for inst in self.instantiations() {
+            out.push_str(&format!(" ({})", inst));
+        }
+        out.push('\n');
+        out
+    }
+
+    fn report<'a>(
+        &self,
+        filename: &'a str,
+        source: &str,
+        config: &RenderConfig,
+    ) -> ariadne::Report<'a, (&'a str, std::ops::Range<usize>)> {
+        use ariadne::{Color, Config, Label, Report, ReportKind};
 
         let kind = match self.severity {
             Severity::Error => ReportKind::Error,
             Severity::Warning => ReportKind::Warning,
         };
 
-        let color = match self.severity {
+        let label_color = match self.severity {
             Severity::Error => Color::Red,
             Severity::Warning => Color::Yellow,
         };
 
         let mut report = Report::build(kind, filename, self.span.start as usize)
+            .with_config(Config::default().with_color(config.color.enabled()))
             .with_message(&self.message)
             .with_label(
                 Label::new((filename, self.span.start as usize..self.span.end as usize))
                     .with_message(&self.message)
-                    .with_color(color),
+                    .with_color(label_color),
             );
 
+        // Ariadne prints only lines that carry a label; a message-less label
+        // on the first character of each context line brings it into view
+        // unmarked. (A zero-width label would count as the line before.)
+        for start in context_line_starts(source, self.span, config.context_lines) {
+            report = report.with_label(Label::new((filename, start..start + 1)));
+        }
+
         for note in &self.notes {
             report = report.with_note(note);
         }
 
+        for inst in self.instantiations() {
+            report = report.with_note(inst);
+        }
+
         if let Some(help) = &self.help {
             report = report.with_help(help);
         }
 
-        let _ = report.finish().eprint((filename, Source::from(source)));
+        report.finish()
+    }
+}
+
+/// Byte offsets of the lines within `context` lines of `span`, excluding
+/// the lines the span itself covers.
+fn context_line_starts(source: &str, span: Span, context: usize) -> Vec<usize> {
+    if context == 0 {
+        return Vec::new();
     }
+    let starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .filter(|&i| i < source.len())
+        .collect();
+    let (first, _) = line_column(source, span.start as usize);
+    let (last, _) = line_column(source, span.end as usize);
+    let before = first.saturating_sub(context).max(1)..first;
+    let after = last + 1..=last + context;
+    before
+        .chain(after)
+        .filter_map(|line| starts.get(line - 1).copied())
+        .collect()
 }
 
 /// Render a list of diagnostics.
@@ -115,6 +346,110 @@ pub fn render_diagnostics(diagnostics: &[Diagnostic], filename: &str, source: &s
     }
 }
 
+/// Render a list of diagnostics as plain text, in order.
+pub fn render_diagnostics_to_string(
+    diagnostics: &[Diagnostic],
+    filename: &str,
+    source: &str,
+) -> String {
+    diagnostics
+        .iter()
+        .map(|d| d.render_to_string(filename, source))
+        .collect()
+}
+
+/// Where library entry points report diagnostics as they compile.
+///
+/// Carried by `CompileOptions::sink`. The default `StderrSink` prints as the
+/// CLI does; language servers and embedders install a `SilentSink` or a
+/// `CollectingSink` and render the returned errors themselves.
+pub trait DiagnosticSink: Send + Sync + std::fmt::Debug {
+    fn report(&self, diagnostics: &[Diagnostic], filename: &str, source: &str);
+}
+
+/// Print diagnostics to stderr, using `config` or else the process-wide
+/// `set_render_config` settings.
+#[derive(Clone, Copy, Debug)]
+pub struct StderrSink {
+    pub config: Option<RenderConfig>,
+    pub warnings: bool,
+}
+
+impl StderrSink {
+    pub fn with_config(config: RenderConfig) -> Self {
+        StderrSink {
+            config: Some(config),
+            ..Self::default()
+        }
+    }
+
+    /// Print errors only, dropping warnings.
+    pub fn errors_only(self) -> Self {
+        StderrSink {
+            warnings: false,
+            ..self
+        }
+    }
+}
+
+impl Default for StderrSink {
+    fn default() -> Self {
+        StderrSink {
+            config: None,
+            warnings: true,
+        }
+    }
+}
+
+impl DiagnosticSink for StderrSink {
+    fn report(&self, diagnostics: &[Diagnostic], filename: &str, source: &str) {
+        for diag in diagnostics {
+            if diag.severity == Severity::Warning && (!self.warnings || warnings_suppressed()) {
+                continue;
+            }
+            let config = self.config.unwrap_or_else(render_config);
+            eprint!("{}", diag.render_with(filename, source, &config));
+        }
+    }
+}
+
+/// Drop every diagnostic; callers read them from the returned error.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentSink;
+
+impl DiagnosticSink for SilentSink {
+    fn report(&self, _diagnostics: &[Diagnostic], _filename: &str, _source: &str) {}
+}
+
+/// Keep every reported diagnostic with its file name, including warnings
+/// that never reach the returned error.
+#[derive(Debug, Default)]
+pub struct CollectingSink {
+    reported: std::sync::Mutex<Vec<(String, Diagnostic)>>,
+}
+
+impl CollectingSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The diagnostics reported so far, in order, leaving the sink empty.
+    pub fn take(&self) -> Vec<(String, Diagnostic)> {
+        std::mem::take(&mut *self.reported.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl DiagnosticSink for CollectingSink {
+    fn report(&self, diagnostics: &[Diagnostic], filename: &str, _source: &str) {
+        let mut reported = self.reported.lock().unwrap_or_else(|e| e.into_inner());
+        reported.extend(
+            diagnostics
+                .iter()
+                .map(|d| (filename.to_string(), d.clone())),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,4 +529,81 @@ mod tests {
             .with_help("x is already proven U32".to_string());
         d.render("test.tri", source);
     }
+
+    #[test]
+    fn test_render_to_string_is_plain_text() {
+        let source = "let x: Field = 1\nlet y: U32 = x\n";
+        let d = Diagnostic::error("type mismatch".to_string(), Span::new(0, 30, 31))
+            .with_help("use as_u32(x)".to_string());
+        let text = d.render_to_string("test.tri", source);
+        assert!(text.contains("type mismatch"));
+        assert!(text.contains("test.tri:2:"));
+        assert!(text.contains("use as_u32(x)"));
+        assert!(!text.contains('\x1b'), "no ANSI escapes: {:?}", text);
+    }
+
+    #[test]
+    fn test_terse_is_one_line_with_position() {
+        let source = "let x: Field = 1\nlet y: U32 = x\n";
+        let d = Diagnostic::error("type mismatch".to_string(), Span::new(0, 30, 31))
+            .with_help("use as_u32(x)".to_string());
+        let config = RenderConfig {
+            terse: true,
+            ..RenderConfig::PLAIN
+        };
+        assert_eq!(
+            d.render_with("test.tri", source, &config),
+            "test.tri:2:14: error: type mismatch\n"
+        );
+    }
+
+    #[test]
+    fn test_context_lines_show_surrounding_source() {
+        let source = "fn main() {\n    let a = 1\n    let b = c\n    let d = 2\n}\n";
+        let d = Diagnostic::error("undefined variable".to_string(), Span::new(0, 38, 39));
+        let bare = d.render_to_string("test.tri", source);
+        assert!(!bare.contains("let a = 1"));
+        let config = RenderConfig {
+            context_lines: 1,
+            ..RenderConfig::PLAIN
+        };
+        let text = d.render_with("test.tri", source, &config);
+        assert!(text.contains("let a = 1"), "{}", text);
+        assert!(text.contains("let d = 2"), "{}", text);
+        assert!(!text.contains("fn main"), "{}", text);
+    }
+
+    #[test]
+    fn test_line_column() {
+        let source = "ab\ncdé\nf";
+        assert_eq!(line_column(source, 0), (1, 1));
+        assert_eq!(line_column(source, 4), (2, 2));
+        assert_eq!(line_column(source, 8), (3, 1));
+        assert_eq!(line_column(source, 100), (3, 2));
+    }
+
+    #[test]
+    fn test_instantiation_chain_is_rendered() {
+        let source = "fn first<N>(a: [Field; N]) -> Field {\n    a[0]\n}\n";
+        let d = Diagnostic::error("too many instances".to_string(), Span::new(0, 3, 8))
+            .with_instantiation(Instantiation {
+                instance: "first<5>".to_string(),
+                file: "main.tri".to_string(),
+                line: 12,
+            });
+        let text = d.render_to_string("lib.tri", source);
+        assert!(
+            text.contains("in first<5>, instantiated at main.tri:12"),
+            "{}",
+            text
+        );
+        let config = RenderConfig {
+            terse: true,
+            ..RenderConfig::PLAIN
+        };
+        assert_eq!(
+            d.render_with("lib.tri", source, &config),
+            "lib.tri:1:4: error: too many instances (in first<5>, instantiated at main.tri:12)\n"
+        );
+    }
 }
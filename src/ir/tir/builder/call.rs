@@ -1,5 +1,7 @@
 //! Function call dispatch: intrinsic resolution and user-defined calls.
 
+use std::collections::BTreeMap;
+
 use crate::ast::*;
 use crate::span::Spanned;
 use crate::tir::TIROp;
@@ -8,13 +10,67 @@ use crate::typecheck::MonoInstance;
 use super::TIRBuilder;
 
 impl TIRBuilder {
-    /// Emit a function call (intrinsic or user-defined).
+    /// Emit a function call (intrinsic or user-defined). `site` is where
+    /// the call's path starts in the source, which names the call to the
+    /// type checker's resolutions.
     pub(crate) fn build_call(
         &mut self,
+        site: u32,
         name: &str,
         generic_args: &[Spanned<ArraySize>],
         args: &[Spanned<Expr>],
     ) {
+        // A `#[pure]` call with constant arguments is replaced by its result.
+        if let Some(values) = self.fold_pure_call(name, generic_args, args) {
+            let width = values.len() as u32;
+            if width > 0 {
+                self.stack.ensure_space(width);
+                self.flush_stack_effects();
+            }
+            self.ops.extend(values.into_iter().map(TIROp::Push));
+            self.stack.push_temp(width);
+            return;
+        }
+
+        // Resolve intrinsic name.
+        let resolved_name = self.resolve_intrinsic(name);
+        let effective_name = resolved_name.as_deref().unwrap_or(name);
+
+        if let ("assert", [arg]) = (effective_name, args) {
+            if let Expr::BinOp {
+                op: BinOp::Eq,
+                lhs,
+                rhs,
+            } = &arg.node
+            {
+                self.build_assert_eq(lhs, rhs);
+                return;
+            }
+        }
+
+        if let ("ensure", [cond, code]) = (effective_name, args) {
+            self.build_ensure(cond, code);
+            return;
+        }
+
+        // `assume` is a verifier fact; release builds don't evaluate it.
+        if effective_name == "assume" && !self.cfg_flags.contains("debug") {
+            self.push_temp(0);
+            return;
+        }
+
+        if let "merkle_step_mem" | "merkle_walk" | "merkle_walk_mem" = effective_name {
+            self.build_merkle(effective_name, args);
+            return;
+        }
+
+        // Outside a run of adjacent calls, the field fills a block of its own.
+        if let ("sponge_absorb_field", [arg]) = (effective_name, args) {
+            self.build_packed_absorb(&[arg]);
+            self.push_temp(0);
+            return;
+        }
+
         // Evaluate arguments — each pushes a temp.
         for arg in args {
             self.build_expr(&arg.node);
@@ -26,14 +82,6 @@ impl TIRBuilder {
             self.stack.pop();
         }
 
-        // Resolve intrinsic name.
-        let resolved_name = self.intrinsic_map.get(name).cloned().or_else(|| {
-            name.rsplit('.')
-                .next()
-                .and_then(|short| self.intrinsic_map.get(short).cloned())
-        });
-        let effective_name = resolved_name.as_deref().unwrap_or(name);
-
         match effective_name {
             // ── I/O ──
             "pub_read" => {
@@ -84,7 +132,7 @@ impl TIRBuilder {
             }
 
             // ── Assertions ──
-            "assert" => {
+            "assert" | "assume" => {
                 self.ops.push(TIROp::Assert(1));
                 self.push_temp(0);
             }
@@ -112,6 +160,17 @@ impl TIRBuilder {
                 self.ops.push(TIROp::Invert);
                 self.push_temp(1);
             }
+            "checked_inv" => {
+                self.emit_assert_nonzero();
+                self.ops.push(TIROp::Invert);
+                self.push_temp(1);
+            }
+            "checked_div" => {
+                self.emit_assert_nonzero();
+                self.ops.push(TIROp::Invert);
+                self.ops.push(TIROp::Mul);
+                self.push_temp(1);
+            }
             "neg" => {
                 self.ops.push(TIROp::Neg);
                 self.push_temp(1);
@@ -166,32 +225,34 @@ impl TIRBuilder {
             "merkle_step" => {
                 self.emit_and_push(TIROp::MerkleStep, 6);
             }
-            "merkle_step_mem" => {
-                self.emit_and_push(TIROp::MerkleLoad, 7);
-            }
 
             // ── RAM ──
             "ram_read" => {
-                self.ops.push(TIROp::RamRead { width: 1 });
+                self.ops.extend(ram_read_ops(1));
                 self.push_temp(1);
             }
             "ram_write" => {
-                self.ops.push(TIROp::RamWrite { width: 1 });
+                self.ops.extend(ram_write_ops(1));
                 self.push_temp(0);
             }
             "ram_read_block" => {
-                self.ops.push(TIROp::RamRead { width: 5 });
+                self.ops.extend(ram_read_ops(5));
                 self.push_temp(5);
             }
             "ram_write_block" => {
-                self.ops.push(TIROp::RamWrite { width: 5 });
+                self.ops.extend(ram_write_ops(5));
                 self.push_temp(0);
             }
 
             // ── Conversion ──
             "as_u32" => {
+                // split → (hi, lo); assert hi == 0 and keep lo, which is
+                // then the argument itself.
                 self.ops.push(TIROp::Split);
-                self.ops.push(TIROp::Pop(1));
+                self.ops.push(TIROp::Swap(1));
+                self.ops.push(TIROp::Push(0));
+                self.ops.push(TIROp::Eq);
+                self.ops.push(TIROp::Assert(1));
                 self.push_temp(1);
             }
             "as_field" => {
@@ -215,7 +276,7 @@ impl TIRBuilder {
 
             // ── User-defined function ──
             _ => {
-                self.build_user_call(name, generic_args);
+                self.build_user_call(site, name, generic_args);
             }
         }
     }
@@ -225,15 +286,12 @@ impl TIRBuilder {
     /// params are already in place on the real stack.
     pub(crate) fn emit_call_only(
         &mut self,
+        site: u32,
         name: &str,
         generic_args: &[Spanned<ArraySize>],
         _arg_count: usize,
     ) {
-        let resolved_name = self.intrinsic_map.get(name).cloned().or_else(|| {
-            name.rsplit('.')
-                .next()
-                .and_then(|short| self.intrinsic_map.get(short).cloned())
-        });
+        let resolved_name = self.resolve_intrinsic(name);
         let effective_name = resolved_name.as_deref().unwrap_or(name);
 
         match effective_name {
@@ -244,9 +302,18 @@ impl TIRBuilder {
             }
             "sponge_init" => self.ops.push(TIROp::SpongeInit),
             "sponge_absorb" => self.ops.push(TIROp::SpongeAbsorb),
+            "sponge_absorb_field" => {
+                // The field, its terminating 1, then zeros.
+                let padding = self.target_config.hash_rate - 2;
+                self.ops.push(TIROp::Push(1));
+                self.ops.extend((0..padding).map(|_| TIROp::Push(0)));
+                self.ops.push(TIROp::SpongeAbsorb);
+            }
             "sponge_squeeze" => self.ops.push(TIROp::SpongeSqueeze),
             "sponge_absorb_mem" => self.ops.push(TIROp::SpongeLoad),
             "assert" => self.ops.push(TIROp::Assert(1)),
+            "assume" if self.cfg_flags.contains("debug") => self.ops.push(TIROp::Assert(1)),
+            "assume" => self.ops.push(TIROp::Pop(1)),
             "assert_eq" => {
                 self.ops.push(TIROp::Eq);
                 self.ops.push(TIROp::Assert(1));
@@ -269,16 +336,36 @@ impl TIRBuilder {
             "pow" => self.ops.push(TIROp::Pow),
             "popcount" => self.ops.push(TIROp::PopCount),
             "inv" => self.ops.push(TIROp::Invert),
+            "checked_inv" => {
+                self.emit_assert_nonzero();
+                self.ops.push(TIROp::Invert);
+            }
+            "checked_div" => {
+                self.emit_assert_nonzero();
+                self.ops.push(TIROp::Invert);
+                self.ops.push(TIROp::Mul);
+            }
             "neg" => self.ops.push(TIROp::Neg),
             "sub" => self.ops.push(TIROp::Sub),
             "field_add" => self.ops.push(TIROp::Add),
             "field_mul" => self.ops.push(TIROp::Mul),
-            "ram_read" => self.ops.push(TIROp::RamRead { width: 1 }),
-            "ram_write" => self.ops.push(TIROp::RamWrite { width: 1 }),
-            "ram_read_block" => self.ops.push(TIROp::RamRead { width: 5 }),
-            "ram_write_block" => self.ops.push(TIROp::RamWrite { width: 5 }),
+            "ram_read" => self.ops.extend(ram_read_ops(1)),
+            "ram_write" => self.ops.extend(ram_write_ops(1)),
+            "ram_read_block" => self.ops.extend(ram_read_ops(5)),
+            "ram_write_block" => self.ops.extend(ram_write_ops(5)),
             "merkle_step" => self.ops.push(TIROp::MerkleStep),
-            "merkle_step_mem" => self.ops.push(TIROp::MerkleLoad),
+            "merkle_step_mem" => {
+                let ops = self.merkle_ops(true, Some(1));
+                self.ops.extend(ops);
+            }
+            "merkle_walk" => {
+                let ops = self.merkle_ops(false, None);
+                self.ops.extend(ops);
+            }
+            "merkle_walk_mem" => {
+                let ops = self.merkle_ops(true, None);
+                self.ops.extend(ops);
+            }
             "xinvert" => self.ops.push(TIROp::ExtInvert),
             "xx_dot_step" => self.ops.push(TIROp::FoldExt),
             "xb_dot_step" => self.ops.push(TIROp::FoldBase),
@@ -289,16 +376,185 @@ impl TIRBuilder {
             _ => {
                 // User-defined call — resolve label the same way as
                 // build_user_call but skip stack model updates.
-                let call_label = self.resolve_call_label(name, generic_args);
+                let call_label = self.resolve_call_label(site, name, generic_args);
                 self.ops.push(TIROp::Call(call_label));
             }
         }
     }
 
+    /// Evaluate a pure call at compile time. Arguments that read a local
+    /// or a size parameter are not constant.
+    fn fold_pure_call(
+        &self,
+        name: &str,
+        generic_args: &[Spanned<ArraySize>],
+        args: &[Spanned<Expr>],
+    ) -> Option<Vec<u64>> {
+        if !generic_args.is_empty() {
+            return None;
+        }
+        let is_local = |var: &str| self.stack.has_var(var) || self.current_subs.contains_key(var);
+        self.pure_fns
+            .eval_call(&self.module_name, name, args, &is_local)
+    }
+
+    /// Merkle authentication with siblings read from RAM (`_mem`), or a
+    /// walk of `depth` steps. A literal depth is unrolled; otherwise the
+    /// depth counts down a native loop.
+    fn build_merkle(&mut self, name: &str, args: &[Spanned<Expr>]) {
+        let from_ram = name != "merkle_walk";
+        let (operands, steps) = match (name, args.split_last()) {
+            ("merkle_step_mem", _) | (_, None) => (args, Some(1)),
+            (_, Some((depth, rest))) => match depth.node {
+                Expr::Literal(Literal::Integer(n)) => (rest, Some(n)),
+                _ => (args, None),
+            },
+        };
+        for arg in operands {
+            self.build_expr(&arg.node);
+        }
+        for _ in operands {
+            self.stack.pop();
+        }
+        self.flush_stack_effects();
+        let ops = self.merkle_ops(from_ram, steps);
+        self.ops.extend(ops);
+        self.push_temp(if from_ram { 7 } else { 6 });
+    }
+
+    /// Ops for `steps` Merkle steps over the index and digest on top of
+    /// the stack, under a RAM pointer when `from_ram`. `None` steps takes
+    /// the count from a depth on top.
+    ///
+    /// `merkle_step_mem` reads its pointer from st7, one below the index
+    /// at st5, so a spare element is slid in for the duration. In the
+    /// loop, the counter steps below the operands for each step.
+    fn merkle_ops(&mut self, from_ram: bool, steps: Option<u64>) -> Vec<TIROp> {
+        let asm = |line: String| TIROp::Asm {
+            lines: vec![line],
+            effect: 0,
+        };
+        let step = if from_ram {
+            TIROp::MerkleLoad
+        } else {
+            TIROp::MerkleStep
+        };
+        let mut ops = Vec::new();
+        if from_ram {
+            let spare = if steps.is_some() { 6 } else { 7 };
+            ops.push(TIROp::Push(0));
+            ops.push(asm(format!("place {}", spare)));
+        }
+        match steps {
+            Some(n) => ops.extend((0..n).map(|_| step.clone())),
+            None => {
+                let reach = if from_ram { 8 } else { 6 };
+                let label = self.fresh_label("merkle");
+                ops.push(TIROp::Call(label.clone()));
+                ops.push(TIROp::Pop(1));
+                ops.push(TIROp::Loop {
+                    label,
+                    body: vec![
+                        asm(format!("place {}", reach)),
+                        step,
+                        asm(format!("pick {}", reach)),
+                    ],
+                });
+            }
+        }
+        if from_ram {
+            ops.push(asm("pick 6".to_string()));
+            ops.push(TIROp::Pop(1));
+        }
+        ops
+    }
+
+    /// `assert(lhs == rhs)`. Five-element operands (a Digest, a `[Field; 5]`)
+    /// are checked with one vector assert instead of being reduced to a
+    /// bool first.
+    fn build_assert_eq(&mut self, lhs: &Spanned<Expr>, rhs: &Spanned<Expr>) {
+        self.build_expr(&lhs.node);
+        self.build_expr(&rhs.node);
+        let width = self.stack.last().map_or(1, |e| e.width);
+        self.stack.pop();
+        self.stack.pop();
+        match width {
+            5 => {
+                self.ops.push(TIROp::Assert(5));
+                self.emit_pop(5);
+            }
+            1 => self.ops.extend([TIROp::Eq, TIROp::Assert(1)]),
+            _ => {
+                self.emit_wide_eq(width);
+                self.ops.push(TIROp::Assert(1));
+            }
+        }
+        self.push_temp(0);
+    }
+
+    /// A check with error id `id`: the id sits below the operands while
+    /// the check runs, so a failed run's stack names it.
+    pub(crate) fn build_checked_call(
+        &mut self,
+        id: u64,
+        site: u32,
+        name: &str,
+        generic_args: &[Spanned<ArraySize>],
+        args: &[Spanned<Expr>],
+    ) {
+        self.emit_and_push(TIROp::Push(id), 1);
+        self.build_call(site, name, generic_args, args);
+        // Checks leave nothing behind, so the id is on top again.
+        if let Some(entry) = self.stack.pop() {
+            self.emit_pop(entry.width);
+        }
+        self.push_temp(0);
+    }
+
+    /// `ensure(cond, code)`: if `cond` is false, write `code` to public
+    /// output and halt, so a failed run names the check that tripped.
+    fn build_ensure(&mut self, cond: &Spanned<Expr>, code: &Spanned<Expr>) {
+        let span = cond.span;
+        let call = |name: &str, arg: Spanned<Expr>| {
+            Spanned::new(
+                Stmt::Expr(Spanned::new(
+                    Expr::Call {
+                        path: Spanned::new(ModulePath::single(name.to_string()), span),
+                        generic_args: vec![],
+                        args: vec![arg],
+                    },
+                    span,
+                )),
+                span,
+            )
+        };
+        let false_lit = Spanned::new(Expr::Literal(Literal::Bool(false)), span);
+        let failed = Expr::BinOp {
+            op: BinOp::Eq,
+            lhs: Box::new(cond.clone()),
+            rhs: Box::new(false_lit.clone()),
+        };
+        let fail = Block {
+            stmts: vec![call("pub_write", code.clone()), call("assert", false_lit)],
+            tail_expr: None,
+        };
+        self.build_stmt(&Stmt::If {
+            cond: Spanned::new(failed, span),
+            then_block: Spanned::new(fail, span),
+            else_block: None,
+        });
+        self.push_temp(0);
+    }
+
     /// Resolve a user-defined call name to its TASM label.
     /// Returns `(call_label, base_name)` where `base_name` is used for
     /// return width lookup.
-    fn resolve_call_label(&mut self, name: &str, generic_args: &[Spanned<ArraySize>]) -> String {
+    fn resolve_call_label(
+        &mut self,
+        site: u32,
+        name: &str,
+        generic_args: &[Spanned<ArraySize>],
+    ) -> String {
         let is_generic = self.generic_fn_defs.contains_key(name);
 
         if is_generic {
@@ -336,37 +592,111 @@ impl TIRBuilder {
             let inst = MonoInstance {
                 name: name.to_string(),
                 size_args,
+                instantiated_at: None,
             };
+            self.require_mono_instance(&inst);
             inst.mangled_name()
         } else if name.contains('.') {
             let parts: Vec<&str> = name.rsplitn(2, '.').collect();
-            let fn_name = parts[0];
+            // Generic functions from another module: the type checker
+            // recorded the instance; the defining module emits it.
+            let instance = self.cross_module_resolution(site, name);
+            let fn_name = match &instance {
+                Some(inst) => MonoInstance {
+                    name: parts[0].to_string(),
+                    size_args: inst.size_args.clone(),
+                    instantiated_at: None,
+                }
+                .mangled_name(),
+                None => parts[0].to_string(),
+            };
+            let fn_name = fn_name.as_str();
             let short_module = parts[1];
             let full_module = self
                 .module_aliases
                 .get(short_module)
                 .map(|s| s.as_str())
                 .unwrap_or(short_module);
-            let mangled = full_module.replace('.', "_");
-            // @ prefix marks cross-module calls so the linker doesn't re-prefix them
-            format!("@{}__{}", mangled, fn_name)
+            // @ marks a cross-module call: the linker prefixes it with the
+            // callee's module instead of the caller's
+            format!("@{}.{}", full_module, fn_name)
         } else {
             name.to_string()
         }
     }
 
+    /// The type checker's resolution for the cross-module generic call
+    /// whose path starts at `site`.
+    fn cross_module_resolution(&self, site: u32, name: &str) -> Option<MonoInstance> {
+        self.call_resolutions
+            .iter()
+            .find(|res| res.name == name && res.instantiated_at.map(|at| at.start) == Some(site))
+            .cloned()
+    }
+
+    /// Size parameters and declared return type of another module's
+    /// function `name` (`fri.fold`); `None` if the module's signatures
+    /// are not known.
+    fn external_signature(&self, name: &str) -> Option<&(Vec<String>, Option<Type>)> {
+        let (module, fn_name) = name.rsplit_once('.')?;
+        let module = self
+            .module_aliases
+            .get(module)
+            .map_or(module, String::as_str);
+        self.external_returns
+            .get(&format!("{}.{}", module, fn_name))
+    }
+
+    /// Size arguments of the call to `name` at `site`, by parameter name.
+    fn call_subs(&self, site: u32, name: &str, type_params: &[String]) -> BTreeMap<String, u64> {
+        let size_args = self
+            .cross_module_resolution(site, name)
+            .map(|inst| inst.size_args)
+            .unwrap_or_default();
+        type_params.iter().cloned().zip(size_args).collect()
+    }
+
+    /// Return width of another module's function `name` called at
+    /// `site`, from its declared return type.
+    fn external_return_width(&self, site: u32, name: &str) -> Option<u32> {
+        let (type_params, ret) = self.external_signature(name)?;
+        let Some(ret) = ret else {
+            return Some(0);
+        };
+        let subs = self.call_subs(site, name, type_params);
+        Some(self.value_width_with_subs(ret, &subs))
+    }
+
+    /// Declared return type of the function `name` called at `site`,
+    /// local or in another module, with the call's size arguments in
+    /// its array sizes.
+    pub(crate) fn call_return_type(&self, site: u32, name: &str) -> Option<Type> {
+        if let Some(ret) = self.fn_return_types.get(name) {
+            return Some(ret.clone());
+        }
+        let (type_params, ret) = match self.generic_fn_defs.get(name) {
+            Some(def) => (
+                def.type_params.iter().map(|p| p.node.clone()).collect(),
+                def.return_ty.as_ref().map(|t| t.node.clone()),
+            ),
+            None => self.external_signature(name)?.clone(),
+        };
+        let subs = self.call_subs(site, name, &type_params);
+        ret.map(|t| t.with_sizes(&subs))
+    }
+
     /// Emit a call to a user-defined (non-intrinsic) function.
-    fn build_user_call(&mut self, name: &str, generic_args: &[Spanned<ArraySize>]) {
-        let call_label = self.resolve_call_label(name, generic_args);
+    fn build_user_call(&mut self, site: u32, name: &str, generic_args: &[Spanned<ArraySize>]) {
+        let call_label = self.resolve_call_label(site, name, generic_args);
 
-        // For return width lookup, use the base name (without module prefix).
-        let base_name = if name.contains('.') && !self.generic_fn_defs.contains_key(name) {
-            name.rsplitn(2, '.').next().unwrap_or(name).to_string()
+        let ret_width = if name.contains('.') && !self.generic_fn_defs.contains_key(name) {
+            self.external_return_width(site, name).unwrap_or_else(|| {
+                let base_name = name.rsplit('.').next().unwrap_or(name);
+                self.fn_return_widths.get(base_name).copied().unwrap_or(0)
+            })
         } else {
-            call_label.clone()
+            self.fn_return_widths.get(&call_label).copied().unwrap_or(0)
         };
-
-        let ret_width = self.fn_return_widths.get(&base_name).copied().unwrap_or(0);
         if ret_width > 0 {
             self.emit_and_push(TIROp::Call(call_label), ret_width);
         } else {
@@ -375,3 +705,24 @@ impl TIRBuilder {
         }
     }
 }
+
+/// `ram_write(addr, v)` over its arguments: `RamWrite` takes the address
+/// from the top and stores the word above it at the address itself, so
+/// a copy of the address is lifted over the value and the original is
+/// dropped afterwards.
+fn ram_write_ops(width: u32) -> [TIROp; 3] {
+    [TIROp::Dup(width), TIROp::RamWrite { width }, TIROp::Pop(1)]
+}
+
+/// `ram_read(addr)` over its argument. `RamRead` walks down from the
+/// address, so a block is read from its last word and comes back in
+/// the order `ram_write_ops` took it.
+fn ram_read_ops(width: u32) -> Vec<TIROp> {
+    let mut ops = Vec::new();
+    if width > 1 {
+        ops.push(TIROp::Push(width as u64 - 1));
+        ops.push(TIROp::Add);
+    }
+    ops.push(TIROp::RamRead { width });
+    ops
+}
@@ -8,19 +8,21 @@ impl TIRBuilder {
     /// Emit cleanup for multi-element returns: remove `dead` elements below
     /// the `ret_width`-wide return value at the top of the stack.
     ///
-    /// When `dead` is a multiple of `ret_width` and `ret_width <= 15`, uses
+    /// When `dead` is a multiple of `ret_width` and `ret_width` is within the
+    /// backend's swap reach (15 on Triton), uses
     /// `swap K; pop 1` x M which rotates the return block by M positions --
     /// a multiple of K means the rotation cancels and the original order is
     /// preserved.
     ///
-    /// When `ret_width > 15`, or when `ret_width > 5` and `dead` is not a
+    /// When `ret_width` exceeds the reach, or when `ret_width > 5` and `dead` is not a
     /// multiple of `ret_width`, saves the return value to scratch RAM via
     /// element-by-element write_mem/read_mem, pops dead elements, and
-    /// restores. This avoids emitting Swap(k) with k > 15, which exceeds
-    /// Triton VM's maximum swap depth.
+    /// restores. This avoids emitting Swap(k) beyond the backend's maximum
+    /// swap depth.
     pub(crate) fn emit_multi_ret_cleanup(&mut self, ret_width: u32, dead: u32) {
         let k = ret_width;
-        if k <= 15 && dead % k == 0 {
+        let reach = self.stack.max_reach();
+        if k <= reach && dead % k == 0 {
             // Rotation-free: M removals = M/K full rotations.
             for _ in 0..dead {
                 self.ops.push(TIROp::Swap(k));
@@ -30,12 +32,8 @@ impl TIRBuilder {
             // Bulk save to RAM, pop dead, bulk restore.
             // Uses write_mem K / read_mem K to avoid triggering spill elimination.
             let scratch = self.stack.alloc_scratch(k);
-            // Move address below the K return elements.
-            self.ops.push(TIROp::Push(scratch));
-            for d in 1..=k {
-                self.ops.push(TIROp::Swap(d));
-            }
             // Write K elements: [val_K, ..., val_1, addr] → [addr+K]
+            self.ops.push(TIROp::Push(scratch));
             self.ops.push(TIROp::WriteMem(k));
             self.ops.push(TIROp::Pop(1));
             // Pop dead elements.
@@ -44,8 +42,8 @@ impl TIRBuilder {
             self.ops.push(TIROp::Push(scratch + k as u64 - 1));
             self.ops.push(TIROp::ReadMem(k));
             self.ops.push(TIROp::Pop(1));
-        } else if k <= 15 {
-            // 6 <= K <= 15: element-by-element swap is safe since K <= 15.
+        } else if k <= reach {
+            // 6 <= K <= reach: element-by-element swap stays within reach.
             for _ in 0..dead {
                 self.ops.push(TIROp::Swap(k));
                 self.ops.push(TIROp::Pop(1));
@@ -57,7 +55,7 @@ impl TIRBuilder {
                 }
             }
         } else {
-            // K > 15: Swap(k) would exceed Triton VM's max swap depth of 15.
+            // K > reach: Swap(k) would exceed the backend's max swap depth.
             // Save return values to scratch RAM element-by-element (using
             // only Swap(1)), pop dead elements, then restore from RAM.
             let scratch = self.stack.alloc_scratch(k);
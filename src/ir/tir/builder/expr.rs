@@ -2,9 +2,9 @@
 
 use crate::ast::*;
 use crate::span::Spanned;
+use crate::tir::ram::DataSegment;
 use crate::tir::TIROp;
 
-use super::layout::resolve_type_width;
 use super::TIRBuilder;
 
 impl TIRBuilder {
@@ -21,16 +21,35 @@ impl TIRBuilder {
                 self.build_var_expr(name);
             }
 
+            Expr::BinOp { op, lhs, rhs }
+                if matches!(op, BinOp::And | BinOp::Or) && !rhs.node.is_pure() =>
+            {
+                self.build_expr(&Expr::short_circuit(*op, lhs, rhs));
+            }
+
             Expr::BinOp { op, lhs, rhs } => {
-                self.build_expr(&lhs.node);
-                self.build_expr(&rhs.node);
+                let (first, second) = self.schedule_operands(*op, lhs, rhs);
+                self.build_expr(&first.node);
+                self.build_expr(&second.node);
+                let operand_width = self.stack.last().map_or(1, |e| e.width);
                 match op {
                     BinOp::Add => self.ops.push(TIROp::Add),
                     BinOp::Mul => self.ops.push(TIROp::Mul),
+                    BinOp::Eq if operand_width > 1 => self.emit_wide_eq(operand_width),
                     BinOp::Eq => self.ops.push(TIROp::Eq),
                     BinOp::Lt => self.ops.push(TIROp::Lt),
                     BinOp::BitAnd => self.ops.push(TIROp::And),
                     BinOp::BitXor => self.ops.push(TIROp::Xor),
+                    // Bools are 0 or 1: `a && b` is `a * b`, and `a || b`
+                    // is `(a + b == 0) == 0`.
+                    BinOp::And => self.ops.push(TIROp::Mul),
+                    BinOp::Or => self.ops.extend([
+                        TIROp::Add,
+                        TIROp::Push(0),
+                        TIROp::Eq,
+                        TIROp::Push(0),
+                        TIROp::Eq,
+                    ]),
                     BinOp::DivMod => self.ops.push(TIROp::DivMod),
                     BinOp::XFieldMul => self.ops.push(TIROp::ExtMul),
                 }
@@ -45,48 +64,43 @@ impl TIRBuilder {
                 self.flush_stack_effects();
             }
 
+            Expr::Call { .. } if expr.some_payload().is_some() => {
+                let value = expr.some_payload().expect("guarded by is_some");
+                self.build_some(&value.node, None);
+            }
+
             Expr::Call {
                 path,
                 generic_args,
                 args,
             } => {
                 let fn_name = path.node.as_dotted();
-                self.build_call(&fn_name, generic_args, args);
+                let error_id = self
+                    .assertion_ids
+                    .get(&path.span.start)
+                    .filter(|_| self.cfg_flags.contains("debug"));
+                match error_id {
+                    Some(&id) => {
+                        self.build_checked_call(id, path.span.start, &fn_name, generic_args, args)
+                    }
+                    None => self.build_call(path.span.start, &fn_name, generic_args, args),
+                }
+            }
+
+            Expr::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                self.build_if_value(cond, then_block, else_block, None);
             }
 
             Expr::Tuple(elements) => {
-                for elem in elements {
-                    self.build_expr(&elem.node);
-                }
-                let n = elements.len();
-                let mut total_width = 0u32;
-                for _ in 0..n {
-                    if let Some(e) = self.stack.pop() {
-                        total_width += e.width;
-                    }
-                }
-                self.stack.push_temp(total_width);
-                self.flush_stack_effects();
+                self.build_tuple(elements, &[]);
             }
 
             Expr::ArrayInit(elements) => {
-                for elem in elements {
-                    self.build_expr(&elem.node);
-                }
-                let n = elements.len();
-                let mut total_width = 0u32;
-                for _ in 0..n {
-                    if let Some(e) = self.stack.pop() {
-                        total_width += e.width;
-                    }
-                }
-                self.stack.push_temp(total_width);
-                if n > 0 {
-                    if let Some(top) = self.stack.last_mut() {
-                        top.elem_width = Some(total_width / n as u32);
-                    }
-                }
-                self.flush_stack_effects();
+                self.build_array(elements, None);
             }
 
             Expr::FieldAccess { expr: inner, field } => {
@@ -97,10 +111,34 @@ impl TIRBuilder {
                 self.build_index(inner, index);
             }
 
-            Expr::StructInit { path: _, fields } => {
+            Expr::StructInit { path, fields } => {
+                let struct_name = path.node.0.last().cloned().unwrap_or_default();
+                let field_types = self
+                    .struct_types
+                    .get(&struct_name)
+                    .map(|sdef| sdef.fields.clone())
+                    .unwrap_or_default();
+                // Fields go on the stack in definition order, first field
+                // deepest, whatever order the literal lists them in. Each
+                // stays in the model until all are built, so later field
+                // expressions find variables at their true depth.
+                let ordered: Vec<_> = if field_types.is_empty() {
+                    fields.iter().collect()
+                } else {
+                    field_types
+                        .iter()
+                        .filter_map(|f| fields.iter().find(|(name, _)| name.node == f.name.node))
+                        .collect()
+                };
+                for (name, val) in &ordered {
+                    let ty = field_types
+                        .iter()
+                        .find(|f| f.name.node == name.node)
+                        .map(|f| &f.ty.node);
+                    self.build_expr_expecting(&val.node, ty);
+                }
                 let mut total_width = 0u32;
-                for (_name, val) in fields {
-                    self.build_expr(&val.node);
+                for _ in &ordered {
                     if let Some(e) = self.stack.pop() {
                         total_width += e.width;
                     }
@@ -111,6 +149,77 @@ impl TIRBuilder {
         }
     }
 
+    /// An `if` expression: both branches leave their value in the same
+    /// place. `expected` is the type the context gives it, if any.
+    pub(crate) fn build_if_value(
+        &mut self,
+        cond: &Spanned<Expr>,
+        then_block: &Spanned<Block>,
+        else_block: &Spanned<Block>,
+        expected: Option<&Type>,
+    ) {
+        self.build_expr(&cond.node);
+        self.stack.pop(); // cond consumed
+
+        let saved = self.stack.save_state();
+        let result = self.fresh_label("__if_value");
+        let (then_body, value) =
+            self.build_value_branch_ir(&then_block.node, &saved, &result, expected);
+        let (else_body, _) =
+            self.build_value_branch_ir(&else_block.node, &saved, &result, expected);
+        self.ops.push(TIROp::IfElse {
+            then_body,
+            else_body,
+        });
+        if let Some(value) = value {
+            self.stack.push_temp(value.width);
+            if let Some(top) = self.stack.last_mut() {
+                top.elem_width = value.elem_width;
+            }
+            self.flush_stack_effects();
+        }
+    }
+
+    /// A tuple literal; `tys` are the component types, when known.
+    pub(crate) fn build_tuple(&mut self, elements: &[Spanned<Expr>], tys: &[&Type]) {
+        for (i, elem) in elements.iter().enumerate() {
+            self.build_expr_expecting(&elem.node, tys.get(i).copied());
+        }
+        let mut parts: Vec<u32> = (0..elements.len())
+            .filter_map(|_| self.stack.pop().map(|e| e.width))
+            .collect();
+        parts.reverse();
+        let total_width = parts.iter().sum();
+        self.stack.push_temp(total_width);
+        if total_width > 0 {
+            if let Some(top) = self.stack.last_mut() {
+                top.parts = Some(parts);
+            }
+        }
+        self.flush_stack_effects();
+    }
+
+    /// An array literal; `elem_ty` is the element type, when known.
+    pub(crate) fn build_array(&mut self, elements: &[Spanned<Expr>], elem_ty: Option<&Type>) {
+        for elem in elements {
+            self.build_expr_expecting(&elem.node, elem_ty);
+        }
+        let n = elements.len();
+        let mut total_width = 0u32;
+        for _ in 0..n {
+            if let Some(e) = self.stack.pop() {
+                total_width += e.width;
+            }
+        }
+        self.stack.push_temp(total_width);
+        if n > 0 {
+            if let Some(top) = self.stack.last_mut() {
+                top.elem_width = Some(total_width / n as u32);
+            }
+        }
+        self.flush_stack_effects();
+    }
+
     // ── Var expression (dotted and simple) ────────────────────────
 
     pub(crate) fn build_var_expr(&mut self, name: &str) {
@@ -147,8 +256,14 @@ impl TIRBuilder {
                 // Module constant fallback.
                 let last_dot = name.rfind('.').expect("dot guaranteed by contains check");
                 let suffix = &name[last_dot + 1..];
-                if let Some(&val) = self.constants.get(name) {
+                if let Some(seg) = self.data_segment(name).cloned() {
+                    self.build_table_value(&seg);
+                } else if let Some(&val) = self.constants.get(name) {
                     self.emit_and_push(TIROp::Push(val), 1);
+                } else if let Some((words, elem_width)) =
+                    self.pure_fns.composite(&self.module_name, name)
+                {
+                    self.build_const_words(&words, elem_width);
                 } else if let Some(&val) = self.constants.get(suffix) {
                     self.emit_and_push(TIROp::Push(val), 1);
                 } else {
@@ -173,7 +288,7 @@ impl TIRBuilder {
                 let depth = self.stack.access_var(name);
                 self.flush_stack_effects();
 
-                if depth + width - 1 <= 15 {
+                if depth + width - 1 <= self.stack.max_reach() {
                     for _ in 0..width {
                         self.ops.push(TIROp::Dup(depth + width - 1));
                     }
@@ -185,7 +300,7 @@ impl TIRBuilder {
                     self.flush_stack_effects();
                     let depth2 = self.stack.access_var(name);
                     self.flush_stack_effects();
-                    if depth2 + width - 1 <= 15 {
+                    if depth2 + width - 1 <= self.stack.max_reach() {
                         for _ in 0..width {
                             self.ops.push(TIROp::Dup(depth2 + width - 1));
                         }
@@ -199,6 +314,18 @@ impl TIRBuilder {
                     }
                 }
                 self.stack.push_temp(width);
+            } else if let Some(&val) = self.current_subs.get(name) {
+                // Size parameter of the instance being built, e.g. `0..N`.
+                self.emit_and_push(TIROp::Push(val), 1);
+            } else if let Some(seg) = self.data_segment(name).cloned() {
+                self.build_table_value(&seg);
+            } else if let Some(&val) = self.constants.get(name) {
+                // Module constant referenced by its bare name.
+                self.emit_and_push(TIROp::Push(val), 1);
+            } else if let Some((words, elem_width)) =
+                self.pure_fns.composite(&self.module_name, name)
+            {
+                self.build_const_words(&words, elem_width);
             } else {
                 // Variable not found — fallback.
                 self.ops.push(TIROp::Dup(0));
@@ -233,14 +360,14 @@ impl TIRBuilder {
                     let total: u32 = sdef
                         .fields
                         .iter()
-                        .map(|f| resolve_type_width(&f.ty.node, &self.target_config))
+                        .map(|f| self.value_width(&f.ty.node))
                         .sum();
                     if total != struct_width {
                         continue;
                     }
                     let mut off = 0u32;
                     for sf in &sdef.fields {
-                        let fw = resolve_type_width(&sf.ty.node, &self.target_config);
+                        let fw = self.value_width(&sf.ty.node);
                         if sf.name.node == field.node {
                             found = Some((total - off - fw, fw));
                             break;
@@ -280,7 +407,55 @@ impl TIRBuilder {
 
     // ── Index expression ──────────────────────────────────────────
 
+    /// A whole table as an array value.
+    fn build_table_value(&mut self, seg: &DataSegment) {
+        let width = seg.values.len() as u32;
+        self.stack.ensure_space(width);
+        self.flush_stack_effects();
+        self.ops.extend(seg.values.iter().map(|&v| TIROp::Push(v)));
+        self.stack.push_temp(width);
+        self.flush_stack_effects();
+    }
+
+    /// A composite constant, pushed inline.
+    fn build_const_words(&mut self, words: &[u64], elem_width: Option<u32>) {
+        let width = words.len() as u32;
+        self.stack.ensure_space(width);
+        self.flush_stack_effects();
+        self.ops.extend(words.iter().map(|&v| TIROp::Push(v)));
+        self.stack.push_temp(width);
+        if let Some(top) = self.stack.last_mut() {
+            top.elem_width = elem_width;
+        }
+        self.flush_stack_effects();
+    }
+
+    /// `TABLE[i]` on a table: a push for a literal index,
+    /// otherwise a read from its data segment.
+    fn build_table_index(&mut self, seg: &DataSegment, index: &Spanned<Expr>) {
+        if let Expr::Literal(Literal::Integer(idx)) = &index.node {
+            if let Some(&val) = seg.values.get(*idx as usize) {
+                self.emit_and_push(TIROp::Push(val), 1);
+                return;
+            }
+        }
+        self.build_expr(&index.node);
+        self.stack.pop();
+        self.ops.push(TIROp::Push(seg.start));
+        self.ops.push(TIROp::Add);
+        self.ops.push(TIROp::RamRead { width: 1 });
+        self.stack.push_temp(1);
+        self.flush_stack_effects();
+    }
+
     pub(crate) fn build_index(&mut self, inner: &Spanned<Expr>, index: &Spanned<Expr>) {
+        if let Expr::Var(name) = &inner.node {
+            if let Some(seg) = self.data_segment(name).cloned() {
+                self.build_table_index(&seg, index);
+                return;
+            }
+        }
+
         // Fast path: constant index into a named variable already on the stack.
         // Instead of copying the whole array then extracting one element,
         // directly dup the target element from the variable's position.
@@ -293,7 +468,7 @@ impl TIRBuilder {
                     if (idx_u + 1) * elem_width <= var_width {
                         let base_offset = var_width - (idx_u + 1) * elem_width;
                         let target_depth = var_depth + base_offset;
-                        if target_depth + elem_width - 1 <= 15 {
+                        if target_depth + elem_width - 1 <= self.stack.max_reach() {
                             for i in 0..elem_width {
                                 self.ops
                                     .push(TIROp::Dup(target_depth + (elem_width - 1 - i)));
@@ -1,7 +1,10 @@
 //! Stack wrappers, label generation, cfg helpers, and spill parser.
 
 use crate::ast::*;
+use crate::diagnostic::Diagnostic;
 use crate::span::Spanned;
+use crate::tir::ram::DataSegment;
+use crate::tir::stack::ManagedVar;
 use crate::tir::TIROp;
 
 use super::TIRBuilder;
@@ -75,6 +78,17 @@ impl TIRBuilder {
         }
     }
 
+    // ── Intrinsics ────────────────────────────────────────────────
+
+    /// The intrinsic a call to `name` maps to, by full or short name.
+    pub(crate) fn resolve_intrinsic(&self, name: &str) -> Option<String> {
+        self.intrinsic_map.get(name).cloned().or_else(|| {
+            name.rsplit('.')
+                .next()
+                .and_then(|short| self.intrinsic_map.get(short).cloned())
+        })
+    }
+
     // ── Label generation ──────────────────────────────────────────
 
     pub(crate) fn fresh_label(&mut self, prefix: &str) -> String {
@@ -90,16 +104,59 @@ impl TIRBuilder {
         }
     }
 
+    // ── Codegen validation ────────────────────────────────────────
+
+    /// Build a statement, attributing stack invariant violations it
+    /// causes to its span.
+    pub(crate) fn build_stmt_at(&mut self, stmt: &Spanned<Stmt>) {
+        let outer = std::mem::replace(&mut self.current_span, stmt.span);
+        self.build_stmt(&stmt.node);
+        self.report_violations();
+        self.current_span = outer;
+    }
+
+    /// Turn the stack model's recorded violations, and a spill in a
+    /// `#[no_spill]` function, into diagnostics at the current span.
+    pub(crate) fn report_violations(&mut self) {
+        if let Some((var, live)) = self.stack.take_spill_report() {
+            let func = self.no_spill_fn.clone().unwrap_or_default();
+            self.spill_errors.push(
+                Diagnostic::error(
+                    format!(
+                        "'{}' is #[no_spill], but '{}' would be spilled to RAM here",
+                        func, var
+                    ),
+                    self.current_span,
+                )
+                .with_note(live)
+                .with_help(
+                    "end `let` scopes earlier, drop unused variables, or split the function"
+                        .to_string(),
+                ),
+            );
+        }
+        for message in self.stack.take_violations() {
+            self.violations.push(
+                Diagnostic::error(
+                    format!("codegen invariant violated: {}", message),
+                    self.current_span,
+                )
+                .with_note("the emitted code for this statement would be wrong".to_string())
+                .with_help(
+                    "this is a compiler bug; please report it with this program".to_string(),
+                ),
+            );
+        }
+    }
+
     // ── Emit helpers ──────────────────────────────────────────────
 
-    /// Ensure stack space, flush spill effects, push the TIROp, push temp to model.
+    /// Push the TIROp, then its result to the model. Spills happen after
+    /// the op, underneath the result: before it, the operands the model
+    /// has already popped still sit on top of the stack.
     pub(crate) fn emit_and_push(&mut self, op: TIROp, result_width: u32) {
-        if result_width > 0 {
-            self.stack.ensure_space(result_width);
-            self.flush_stack_effects();
-        }
         self.ops.push(op);
-        self.stack.push_temp(result_width);
+        self.push_temp(result_width);
     }
 
     /// Push an anonymous temporary onto the stack model (no TIROp emitted).
@@ -125,12 +182,132 @@ impl TIRBuilder {
         }
     }
 
-    /// Build a block into a separate Vec<TIROp> by temporarily swapping out self.ops.
-    pub(crate) fn build_block_as_ir(&mut self, block: &Block) -> Vec<TIROp> {
+    /// Compare two `width`-element values on top of the stack (the right
+    /// operand above the left) and leave a single bool: the product of
+    /// the pairwise `eq`s. Element `i` of the right operand sits at depth
+    /// `i` and its partner at depth `width + i`, so each round dups the
+    /// partner next to it; the consumed left operand is dropped at the end.
+    pub(crate) fn emit_wide_eq(&mut self, width: u32) {
+        self.ops.extend([TIROp::Dup(width), TIROp::Eq]);
+        for _ in 1..width {
+            self.ops
+                .extend([TIROp::Swap(1), TIROp::Dup(width + 1), TIROp::Eq, TIROp::Mul]);
+        }
+        self.ops.push(TIROp::Swap(width));
+        self.emit_pop(width);
+    }
+
+    /// Assert the top of the stack is nonzero, leaving it in place.
+    pub(crate) fn emit_assert_nonzero(&mut self) {
+        self.ops.extend([
+            TIROp::Dup(0),
+            TIROp::Push(0),
+            TIROp::Eq,
+            TIROp::Push(0),
+            TIROp::Eq,
+            TIROp::Assert(1),
+        ]);
+    }
+
+    /// Write every data segment to RAM, five words per `write_mem`,
+    /// leaving the stack as it was.
+    pub(crate) fn emit_data_init(&mut self) {
+        for seg in &self.data_segments {
+            for (i, chunk) in seg.values.chunks(5).enumerate() {
+                // The word right under the address lands at the address.
+                self.ops.extend(chunk.iter().rev().map(|&v| TIROp::Push(v)));
+                self.ops.push(TIROp::Push(seg.start + 5 * i as u64));
+                self.ops.push(TIROp::RamWrite {
+                    width: chunk.len() as u32,
+                });
+            }
+        }
+    }
+
+    /// The data segment of table `name`, unless a variable
+    /// shadows it.
+    pub(crate) fn data_segment(&self, name: &str) -> Option<&DataSegment> {
+        if self.stack.has_var(name) {
+            return None;
+        }
+        let (key, _) = self.pure_fns.table(&self.module_name, name)?;
+        self.data_segments.iter().find(|seg| seg.name == key)
+    }
+
+    /// Build a `for` body into a separate Vec<TIROp>. Like a branch, the
+    /// body ends by reconciling the stack back to `saved` (loop variable,
+    /// then counter, on top), so every iteration starts from one layout;
+    /// then it advances the loop variable.
+    pub(crate) fn build_loop_body_ir(
+        &mut self,
+        block: &Block,
+        saved: &(Vec<ManagedVar>, Vec<ManagedVar>),
+    ) -> Vec<TIROp> {
         let saved_ops = std::mem::take(&mut self.ops);
+        let depth = self.stack.stack_depth();
         self.build_block(block);
+        self.stack.reconcile(saved);
+        self.flush_stack_effects();
+        self.stack.check_balanced("loop body", depth);
+        self.ops
+            .extend([TIROp::Swap(1), TIROp::Push(1), TIROp::Add, TIROp::Swap(1)]);
         let nested = std::mem::take(&mut self.ops);
         self.ops = saved_ops;
+        self.stack.restore_state(saved.clone());
         nested
     }
+
+    /// Build one branch of a conditional into a separate Vec<TIROp>. The
+    /// branch ends by reconciling the stack back to `saved`, so every
+    /// branch leaves the same physical layout for the code after it.
+    pub(crate) fn build_branch_ir(
+        &mut self,
+        block: &Block,
+        saved: &(Vec<ManagedVar>, Vec<ManagedVar>),
+    ) -> Vec<TIROp> {
+        let saved_ops = std::mem::take(&mut self.ops);
+        self.build_block(block);
+        self.stack.reconcile(saved);
+        self.flush_stack_effects();
+        let nested = std::mem::take(&mut self.ops);
+        self.ops = saved_ops;
+        self.stack.restore_state(saved.clone());
+        nested
+    }
+
+    /// Build one branch of an `if` expression into a separate Vec<TIROp>.
+    /// Like `build_branch_ir`, but the branch value is named `result` and
+    /// kept on top of the `saved` layout while the branch locals are
+    /// dropped. Returns the ops and the value's stack entry, if any.
+    pub(crate) fn build_value_branch_ir(
+        &mut self,
+        block: &Block,
+        saved: &(Vec<ManagedVar>, Vec<ManagedVar>),
+        result: &str,
+        expected: Option<&Type>,
+    ) -> (Vec<TIROp>, Option<ManagedVar>) {
+        let tail_if = block.with_tail_if();
+        let block = tail_if.as_ref().unwrap_or(block);
+        let saved_ops = std::mem::take(&mut self.ops);
+        self.build_stmts(&block.stmts, None);
+        let mut value = None;
+        if let Some(tail) = &block.tail_expr {
+            let before = self.stack.entry_count();
+            self.build_expr_expecting(&tail.node, expected);
+            if self.stack.entry_count() > before {
+                if let Some(top) = self.stack.last_mut() {
+                    top.name = Some(result.to_string());
+                    value = Some(top.clone());
+                }
+            }
+        }
+        let mut target = saved.clone();
+        target.0.extend(value.clone());
+        self.stack.reconcile(&target);
+        self.flush_stack_effects();
+        let nested = std::mem::take(&mut self.ops);
+        self.ops = saved_ops;
+        self.stack.restore_state(saved.clone());
+        (nested, value)
+    }
 }
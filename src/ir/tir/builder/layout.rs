@@ -21,27 +21,12 @@ pub(crate) fn resolve_type_width(ty: &Type, tc: &TerrainConfig) -> u32 {
             resolve_type_width(inner, tc) * (size as u32)
         }
         Type::Tuple(elems) => elems.iter().map(|t| resolve_type_width(t, tc)).sum(),
-        Type::Named(_) => 1,
-    }
-}
-
-pub(crate) fn resolve_type_width_with_subs(
-    ty: &Type,
-    subs: &BTreeMap<String, u64>,
-    tc: &TerrainConfig,
-) -> u32 {
-    match ty {
-        Type::Field | Type::Bool | Type::U32 => 1,
-        Type::XField => tc.xfield_width,
-        Type::Digest => tc.digest_width,
-        Type::Array(inner, n) => {
-            let size = n.eval(subs);
-            resolve_type_width_with_subs(inner, subs, tc) * (size as u32)
-        }
-        Type::Tuple(elems) => elems
+        Type::NamedTuple(components) => components
             .iter()
-            .map(|t| resolve_type_width_with_subs(t, subs, tc))
+            .map(|(_, t)| resolve_type_width(t, tc))
             .sum(),
+        Type::Option(inner) => 1 + resolve_type_width(inner, tc),
+        Type::Bytes(n) => n.as_literal().unwrap_or(0) as u32,
         Type::Named(_) => 1,
     }
 }
@@ -49,20 +34,59 @@ pub(crate) fn resolve_type_width_with_subs(
 // ─── TIRBuilder struct layout methods ──────────────────────────────
 
 impl TIRBuilder {
+    /// Width of a value of type `ty`, with struct widths taken from
+    /// their definitions and size parameters from `current_subs`.
+    pub(crate) fn value_width(&self, ty: &Type) -> u32 {
+        self.value_width_with_subs(ty, &self.current_subs)
+    }
+
+    /// Width of a value of type `ty`, with struct widths taken from
+    /// their definitions and size parameters from `subs`.
+    pub(crate) fn value_width_with_subs(&self, ty: &Type, subs: &BTreeMap<String, u64>) -> u32 {
+        match ty {
+            Type::Array(inner, n) => self.value_width_with_subs(inner, subs) * n.eval(subs) as u32,
+            Type::Tuple(elems) => elems
+                .iter()
+                .map(|t| self.value_width_with_subs(t, subs))
+                .sum(),
+            Type::NamedTuple(components) => components
+                .iter()
+                .map(|(_, t)| self.value_width_with_subs(t, subs))
+                .sum(),
+            Type::Option(inner) => 1 + self.value_width_with_subs(inner, subs),
+            Type::Bytes(n) => n.eval(subs) as u32,
+            Type::Named(path) => {
+                let struct_name = path.0.last().map(|s| s.as_str()).unwrap_or("");
+                match self.struct_types.get(struct_name) {
+                    Some(sdef) => sdef
+                        .fields
+                        .iter()
+                        .map(|f| self.value_width_with_subs(&f.ty.node, subs))
+                        .sum(),
+                    None => 1,
+                }
+            }
+            _ => resolve_type_width(ty, &self.target_config),
+        }
+    }
+
     /// Register struct field layout from a type annotation.
     pub(crate) fn register_struct_layout_from_type(&mut self, var_name: &str, ty: &Type) {
-        if let Type::Named(path) = ty {
+        if let Type::NamedTuple(components) = ty {
+            let layout = self.named_tuple_layout(components);
+            self.struct_layouts.insert(var_name.to_string(), layout);
+        } else if let Type::Named(path) = ty {
             let struct_name = path.0.last().map(|s| s.as_str()).unwrap_or("");
             if let Some(sdef) = self.struct_types.get(struct_name).cloned() {
                 let mut field_map = BTreeMap::new();
                 let total: u32 = sdef
                     .fields
                     .iter()
-                    .map(|f| resolve_type_width(&f.ty.node, &self.target_config))
+                    .map(|f| self.value_width(&f.ty.node))
                     .sum();
                 let mut offset = 0u32;
                 for sf in &sdef.fields {
-                    let fw = resolve_type_width(&sf.ty.node, &self.target_config);
+                    let fw = self.value_width(&sf.ty.node);
                     let from_top = total - offset - fw;
                     field_map.insert(sf.name.node.clone(), (from_top, fw));
                     offset += fw;
@@ -72,6 +96,65 @@ impl TIRBuilder {
         }
     }
 
+    /// Component layout of a named tuple: same as a struct with those fields.
+    pub(crate) fn named_tuple_layout(
+        &self,
+        components: &[(String, Type)],
+    ) -> BTreeMap<String, (u32, u32)> {
+        let widths: Vec<u32> = components
+            .iter()
+            .map(|(_, t)| self.value_width(t))
+            .collect();
+        let total: u32 = widths.iter().sum();
+        let mut layout = BTreeMap::new();
+        let mut offset = 0u32;
+        for ((name, _), fw) in components.iter().zip(widths) {
+            layout.insert(name.clone(), (total - offset - fw, fw));
+            offset += fw;
+        }
+        layout
+    }
+
+    /// Widths of the `n` components of the tuple `value` just built onto
+    /// the stack: recorded by a tuple literal, else from the annotation
+    /// `ty` or the callee's declared return type. `None` if unknown.
+    pub(crate) fn tuple_parts(
+        &self,
+        ty: Option<&Type>,
+        value: &Expr,
+        n: usize,
+    ) -> Option<Vec<u32>> {
+        let recorded = self.stack.last().and_then(|top| top.parts.clone());
+        let declared = || {
+            let tuple = match (ty, value) {
+                (Some(t), _) => t.clone(),
+                (None, Expr::Call { path, .. }) => {
+                    self.call_return_type(path.span.start, &path.node.as_dotted())?
+                }
+                _ => return None,
+            };
+            let components: Vec<Type> = match tuple {
+                Type::Tuple(elems) => elems,
+                Type::NamedTuple(components) => components.into_iter().map(|(_, t)| t).collect(),
+                _ => return None,
+            };
+            Some(components.iter().map(|t| self.value_width(t)).collect())
+        };
+        recorded
+            .or_else(declared)
+            .filter(|parts: &Vec<u32>| parts.len() == n)
+    }
+
+    /// Named tuple components returned by a call, if the callee declares them.
+    pub(crate) fn call_named_return(&self, call: &Expr) -> Option<Vec<(String, Type)>> {
+        let Expr::Call { path, .. } = call else {
+            return None;
+        };
+        let name = path.node.as_dotted();
+        let base_name = name.rsplit('.').next().unwrap_or(&name);
+        self.fn_named_returns.get(base_name).cloned()
+    }
+
     /// Look up field offset within a struct variable.
     pub(crate) fn find_field_offset_in_var(
         &self,
@@ -122,11 +205,11 @@ impl TIRBuilder {
             let total: u32 = sdef
                 .fields
                 .iter()
-                .map(|f| resolve_type_width(&f.ty.node, &self.target_config))
+                .map(|f| self.value_width(&f.ty.node))
                 .sum();
             let mut found = false;
             for sf in &sdef.fields {
-                let fw = resolve_type_width(&sf.ty.node, &self.target_config);
+                let fw = self.value_width(&sf.ty.node);
                 if sf.name.node == field {
                     let from_top = total - sub_offset - fw;
                     // The sub-field is at `from_top` within the parent field.
@@ -165,7 +248,7 @@ impl TIRBuilder {
             let total: u32 = sdef
                 .fields
                 .iter()
-                .map(|f| resolve_type_width(&f.ty.node, &self.target_config))
+                .map(|f| self.value_width(&f.ty.node))
                 .sum();
             // Check if this struct matches the variable's layout.
             if let Some(layout) = self.struct_layouts.get(var_name) {
@@ -199,7 +282,7 @@ impl TIRBuilder {
                         return sdef
                             .fields
                             .iter()
-                            .map(|f| resolve_type_width(&f.ty.node, &self.target_config))
+                            .map(|f| self.value_width(&f.ty.node))
                             .collect();
                     }
                 }
@@ -8,6 +8,13 @@ use super::TIRBuilder;
 
 impl TIRBuilder {
     pub(crate) fn build_match(&mut self, expr: &Spanned<Expr>, arms: &[MatchArm]) {
+        if arms
+            .iter()
+            .any(|arm| matches!(arm.pattern.node, MatchPattern::Some(_) | MatchPattern::None))
+        {
+            self.build_option_match(expr, arms);
+            return;
+        }
         self.build_expr(&expr.node);
         if let Some(top) = self.stack.last_mut() {
             top.name = Some("__match_scrutinee".to_string());
@@ -174,6 +181,9 @@ impl TIRBuilder {
                         false,
                     ));
                 }
+
+                // Option arms are built by `build_option_match`.
+                MatchPattern::Some(_) | MatchPattern::None => {}
             }
         }
 
@@ -15,8 +15,10 @@ mod call;
 mod cleanup;
 mod expr;
 mod helpers;
-mod layout;
+pub(crate) mod layout;
 mod match_;
+mod option;
+mod schedule;
 mod stmt;
 #[cfg(test)]
 mod tests {
@@ -26,14 +28,19 @@ mod tests {
 
 use std::collections::{BTreeMap, BTreeSet};
 
+use crate::ast::eval::PureFns;
 use crate::ast::*;
+use crate::diagnostic::Diagnostic;
+use crate::span::Span;
 use crate::target::TerrainConfig;
+use crate::tir::ram::{data_segments, DataSegment};
 use crate::tir::stack::SpillFormatter;
+use crate::tir::stack::StackCosts;
 use crate::tir::stack::StackManager;
 use crate::tir::TIROp;
 use crate::typecheck::MonoInstance;
 
-use self::layout::{format_type_name, resolve_type_width, resolve_type_width_with_subs};
+use self::layout::{format_type_name, resolve_type_width};
 
 // ─── TIRBuilder ────────────────────────────────────────────────────
 
@@ -49,12 +56,20 @@ pub struct TIRBuilder {
     pub(crate) struct_layouts: BTreeMap<String, BTreeMap<String, (u32, u32)>>,
     /// Return widths of user-defined functions.
     pub(crate) fn_return_widths: BTreeMap<String, u32>,
+    /// Named tuple return types of user-defined functions: fn name -> components.
+    pub(crate) fn_named_returns: BTreeMap<String, Vec<(String, Type)>>,
+    /// Declared return types of the module's non-generic functions.
+    pub(crate) fn_return_types: BTreeMap<String, Type>,
     /// Event tags: event name -> sequential integer tag.
     pub(crate) event_tags: BTreeMap<String, u64>,
     /// Event field names in declaration order: event name -> [field_name, ...].
     pub(crate) event_defs: BTreeMap<String, Vec<String>>,
-    /// Struct type definitions: struct_name -> StructDef.
+    /// Struct type definitions: struct_name -> StructDef. Seeded with the
+    /// project's other modules; the module's own definitions win.
     pub(crate) struct_types: BTreeMap<String, StructDef>,
+    /// Other modules' functions by full path (`std.crypto.transcript.challenge`):
+    /// size parameters and declared return type.
+    pub(crate) external_returns: BTreeMap<String, (Vec<String>, Option<Type>)>,
     /// Constants: qualified or short name -> integer value.
     pub(crate) constants: BTreeMap<String, u64>,
     /// Next temporary RAM address for runtime array ops.
@@ -69,20 +84,43 @@ pub struct TIRBuilder {
     pub(crate) generic_fn_defs: BTreeMap<String, FnDef>,
     /// Current size parameter substitutions during monomorphized emission.
     pub(crate) current_subs: BTreeMap<String, u64>,
+    /// Declared return type of the function being built.
+    pub(crate) return_ty: Option<Type>,
     /// Per-call-site resolutions from the type checker.
     pub(crate) call_resolutions: Vec<MonoInstance>,
     /// Index into call_resolutions for the next generic call.
     pub(crate) call_resolution_idx: usize,
     /// Active cfg flags for conditional compilation.
     pub(crate) cfg_flags: BTreeSet<String>,
+    /// Error ids of the module's checks, by offset of the call's path
+    /// (see `runtime::debug::AssertionTable`).
+    pub(crate) assertion_ids: BTreeMap<u32, u64>,
+    /// `#[pure]` functions whose constant calls are folded.
+    pub(crate) pure_fns: PureFns,
+    /// Name of the module being built.
+    pub(crate) module_name: String,
+    /// `generate!` tables of every known module, laid out in RAM.
+    pub(crate) data_segments: Vec<DataSegment>,
+    /// Whether `main` writes the data segments on entry (programs only).
+    pub(crate) writes_data: bool,
+    /// Emit `#[test]` functions and no program entry (`trident test`).
+    pub(crate) emit_tests: bool,
+    /// Span of the innermost statement being built, for violations.
+    pub(crate) current_span: Span,
+    /// Stack invariant violations, when validation is on.
+    pub(crate) violations: Vec<Diagnostic>,
+    /// The `#[no_spill]` function being built, if any.
+    pub(crate) no_spill_fn: Option<String>,
+    /// Spills in `#[no_spill]` functions: these fail the build.
+    pub(crate) spill_errors: Vec<Diagnostic>,
     /// Target VM configuration.
     pub(crate) target_config: TerrainConfig,
 }
 
 impl TIRBuilder {
     pub fn new(target_config: TerrainConfig) -> Self {
-        let stack = StackManager::with_formatter(
-            target_config.stack_depth,
+        let stack = StackManager::with_costs(
+            StackCosts::for_target(&target_config.name, target_config.stack_depth),
             target_config.spill_ram_base,
             SpillFormatter::default(),
         );
@@ -92,19 +130,33 @@ impl TIRBuilder {
             stack,
             struct_layouts: BTreeMap::new(),
             fn_return_widths: BTreeMap::new(),
+            fn_named_returns: BTreeMap::new(),
+            fn_return_types: BTreeMap::new(),
             event_tags: BTreeMap::new(),
             event_defs: BTreeMap::new(),
             struct_types: BTreeMap::new(),
+            external_returns: BTreeMap::new(),
             constants: BTreeMap::new(),
-            temp_ram_addr: target_config.spill_ram_base / 2,
+            temp_ram_addr: crate::tir::ram::temp_ram_base(&target_config),
             intrinsic_map: BTreeMap::new(),
             module_aliases: BTreeMap::new(),
             mono_instances: Vec::new(),
             generic_fn_defs: BTreeMap::new(),
             current_subs: BTreeMap::new(),
+            return_ty: None,
             call_resolutions: Vec::new(),
             call_resolution_idx: 0,
             cfg_flags: BTreeSet::from(["debug".to_string()]),
+            assertion_ids: BTreeMap::new(),
+            pure_fns: PureFns::new(),
+            module_name: String::new(),
+            data_segments: Vec::new(),
+            writes_data: false,
+            emit_tests: false,
+            current_span: Span::dummy(),
+            violations: Vec::new(),
+            no_spill_fn: None,
+            spill_errors: Vec::new(),
             target_config,
         }
     }
@@ -131,6 +183,21 @@ impl TIRBuilder {
         self
     }
 
+    /// Struct definitions of the modules this one may import.
+    pub fn with_struct_types(mut self, structs: BTreeMap<String, StructDef>) -> Self {
+        self.struct_types = structs;
+        self
+    }
+
+    /// Return types of the functions other modules export.
+    pub fn with_external_returns(
+        mut self,
+        returns: BTreeMap<String, (Vec<String>, Option<Type>)>,
+    ) -> Self {
+        self.external_returns = returns;
+        self
+    }
+
     pub fn with_mono_instances(mut self, instances: Vec<MonoInstance>) -> Self {
         self.mono_instances = instances;
         self
@@ -141,11 +208,72 @@ impl TIRBuilder {
         self
     }
 
+    pub fn with_pure_fns(mut self, pure_fns: PureFns) -> Self {
+        self.pure_fns = pure_fns;
+        self
+    }
+
+    /// Error ids to push below the operands of each check in debug builds.
+    pub fn with_assertion_ids(mut self, ids: BTreeMap<u32, u64>) -> Self {
+        self.assertion_ids = ids;
+        self
+    }
+
+    /// Build the `#[test]` functions too, leaving the entry to the test
+    /// runner, which calls one test per run.
+    pub fn with_tests(mut self) -> Self {
+        self.emit_tests = true;
+        self
+    }
+
+    /// Check the stack model's invariants while building and report
+    /// violations from `build_file_checked` (`--validate-codegen`).
+    pub fn with_validation(mut self, on: bool) -> Self {
+        self.stack.set_validation(on);
+        self
+    }
+
     // ═══════════════════════════════════════════════════════════════
     // ── Top-level entry: build_file ───────────────────────────────
     // ═══════════════════════════════════════════════════════════════
 
-    pub fn build_file(mut self, file: &File) -> Vec<TIROp> {
+    pub fn build_file(self, file: &File) -> Vec<TIROp> {
+        self.build_ops(file).0
+    }
+
+    /// Like `build_file`, also returning the stack invariant violations
+    /// found on the way, each at the statement that emitted it. Empty
+    /// unless `with_validation` is on.
+    ///
+    /// Fails if a `#[no_spill]` function would spill to RAM.
+    pub fn build_file_checked(
+        self,
+        file: &File,
+    ) -> Result<(Vec<TIROp>, Vec<Diagnostic>), Vec<Diagnostic>> {
+        let (ops, violations, spill_errors) = self.build_ops(file);
+        if !spill_errors.is_empty() {
+            return Err(spill_errors);
+        }
+        Ok((ops, violations))
+    }
+
+    fn build_ops(mut self, file: &File) -> (Vec<TIROp>, Vec<Diagnostic>, Vec<Diagnostic>) {
+        self.module_name = file.name.node.clone();
+        self.pure_fns.add_module(file, &self.cfg_flags);
+        self.data_segments = data_segments(self.pure_fns.tables(), &self.target_config);
+        self.writes_data = file.kind == FileKind::Program;
+
+        // ── Pre-scan: collect struct type definitions (widths below need them) ──
+        for item in &file.items {
+            if !self.is_item_cfg_active(&item.node) {
+                continue;
+            }
+            if let Item::Struct(sdef) = &item.node {
+                self.struct_types
+                    .insert(sdef.name.node.clone(), sdef.clone());
+            }
+        }
+
         // ── Pre-scan: collect return widths and detect generic functions ──
         for item in &file.items {
             if !self.is_item_cfg_active(&item.node) {
@@ -159,28 +287,26 @@ impl TIRBuilder {
                     let width = func
                         .return_ty
                         .as_ref()
-                        .map(|t| resolve_type_width(&t.node, &self.target_config))
+                        .map(|t| self.value_width(&t.node))
                         .unwrap_or(0);
                     self.fn_return_widths.insert(func.name.node.clone(), width);
+                    if let Some(ret) = &func.return_ty {
+                        self.fn_return_types
+                            .insert(func.name.node.clone(), ret.node.clone());
+                    }
+                    if let Some(Type::NamedTuple(components)) =
+                        func.return_ty.as_ref().map(|t| &t.node)
+                    {
+                        self.fn_named_returns
+                            .insert(func.name.node.clone(), components.clone());
+                    }
                 }
             }
         }
 
         // ── Pre-scan: register return widths for monomorphized instances ──
         for inst in &self.mono_instances.clone() {
-            if let Some(gdef) = self.generic_fn_defs.get(&inst.name).cloned() {
-                let mut subs = BTreeMap::new();
-                for (param, val) in gdef.type_params.iter().zip(inst.size_args.iter()) {
-                    subs.insert(param.node.clone(), *val);
-                }
-                let width = gdef
-                    .return_ty
-                    .as_ref()
-                    .map(|t| resolve_type_width_with_subs(&t.node, &subs, &self.target_config))
-                    .unwrap_or(0);
-                let mangled = inst.mangled_name();
-                self.fn_return_widths.insert(mangled, width);
-            }
+            self.register_mono_width(inst);
         }
 
         // ── Pre-scan: collect intrinsic mappings ──
@@ -189,38 +315,21 @@ impl TIRBuilder {
                 continue;
             }
             if let Item::Fn(func) = &item.node {
-                if let Some(ref intrinsic) = func.intrinsic {
-                    let intr_value = if let Some(start) = intrinsic.node.find('(') {
-                        let end = intrinsic.node.rfind(')').unwrap_or(intrinsic.node.len());
-                        intrinsic.node[start + 1..end].to_string()
-                    } else {
-                        intrinsic.node.clone()
-                    };
+                if let Some(intrinsic) = func.intrinsic_attr() {
                     self.intrinsic_map
-                        .insert(func.name.node.clone(), intr_value);
+                        .insert(func.name.node.clone(), intrinsic.name);
                 }
             }
         }
 
-        // ── Pre-scan: collect struct type definitions ──
-        for item in &file.items {
-            if !self.is_item_cfg_active(&item.node) {
-                continue;
-            }
-            if let Item::Struct(sdef) = &item.node {
-                self.struct_types
-                    .insert(sdef.name.node.clone(), sdef.clone());
-            }
-        }
-
         // ── Pre-scan: collect constant values ──
         for item in &file.items {
             if !self.is_item_cfg_active(&item.node) {
                 continue;
             }
             if let Item::Const(cdef) = &item.node {
-                if let Expr::Literal(Literal::Integer(val)) = &cdef.value.node {
-                    self.constants.insert(cdef.name.node.clone(), *val);
+                if let Some(val) = self.pure_fns.constant(&self.module_name, &cdef.name.node) {
+                    self.constants.insert(cdef.name.node.clone(), val);
                 }
             }
         }
@@ -259,33 +368,76 @@ impl TIRBuilder {
                 // (blank line between sec_ram and functions handled by lowering)
             }
         }
+        if self.writes_data && !self.data_segments.is_empty() {
+            self.ops.push(TIROp::Comment(
+                "data: generate! tables written to RAM on entry to main".to_string(),
+            ));
+            for seg in &self.data_segments {
+                self.ops.push(TIROp::Comment(format!(
+                    "ram[{}..{}]: {} ({} field element{})",
+                    seg.start,
+                    seg.start + seg.values.len() as u64,
+                    seg.name,
+                    seg.values.len(),
+                    if seg.values.len() == 1 { "" } else { "s" }
+                )));
+            }
+        }
 
         // ── Program entry point ──
-        if file.kind == FileKind::Program {
+        if file.kind == FileKind::Program && !self.emit_tests {
             self.ops.push(TIROp::Entry("main".to_string()));
         }
 
-        // ── Emit non-generic, non-test functions ──
+        // ── Emit non-generic functions (tests only for the test runner) ──
         for item in &file.items {
             if !self.is_item_cfg_active(&item.node) {
                 continue;
             }
             if let Item::Fn(func) = &item.node {
-                if func.type_params.is_empty() && !func.is_test {
+                if func.type_params.is_empty() && (!func.is_test || self.emit_tests) {
                     self.build_fn(func);
                 }
             }
         }
 
         // ── Emit monomorphized copies of generic functions ──
-        let instances = self.mono_instances.clone();
-        for inst in &instances {
+        // Generic bodies may instantiate further generics, which
+        // `require_mono_instance` appends while the list is walked.
+        let mut next = 0;
+        while next < self.mono_instances.len() {
+            let inst = self.mono_instances[next].clone();
             if let Some(gdef) = self.generic_fn_defs.get(&inst.name).cloned() {
-                self.build_mono_fn(&gdef, inst);
+                self.build_mono_fn(&gdef, &inst);
+            }
+            next += 1;
+        }
+
+        (self.ops, self.violations, self.spill_errors)
+    }
+
+    /// Register the return width of a monomorphized instance under its mangled name.
+    fn register_mono_width(&mut self, inst: &MonoInstance) {
+        if let Some(gdef) = self.generic_fn_defs.get(&inst.name) {
+            let mut subs = BTreeMap::new();
+            for (param, val) in gdef.type_params.iter().zip(inst.size_args.iter()) {
+                subs.insert(param.node.clone(), *val);
             }
+            let width = gdef
+                .return_ty
+                .as_ref()
+                .map(|t| self.value_width_with_subs(&t.node, &subs))
+                .unwrap_or(0);
+            self.fn_return_widths.insert(inst.mangled_name(), width);
         }
+    }
 
-        self.ops
+    /// Queue a local generic instance for emission if it is not already queued.
+    pub(crate) fn require_mono_instance(&mut self, inst: &MonoInstance) {
+        if !self.mono_instances.contains(inst) {
+            self.register_mono_width(inst);
+            self.mono_instances.push(inst.clone());
+        }
     }
 
     // ═══════════════════════════════════════════════════════════════
@@ -300,12 +452,12 @@ impl TIRBuilder {
         let param_widths: Vec<u32> = func
             .params
             .iter()
-            .map(|p| resolve_type_width(&p.ty.node, &self.target_config))
+            .map(|p| self.value_width(&p.ty.node))
             .collect();
         let ret_width = func
             .return_ty
             .as_ref()
-            .map(|t| resolve_type_width(&t.node, &self.target_config))
+            .map(|t| self.value_width(&t.node))
             .unwrap_or(0);
         self.build_fn_body(&name, func, &param_widths, ret_width);
     }
@@ -323,14 +475,12 @@ impl TIRBuilder {
         let param_widths: Vec<u32> = func
             .params
             .iter()
-            .map(|p| {
-                resolve_type_width_with_subs(&p.ty.node, &self.current_subs, &self.target_config)
-            })
+            .map(|p| self.value_width(&p.ty.node))
             .collect();
         let ret_width = func
             .return_ty
             .as_ref()
-            .map(|t| resolve_type_width_with_subs(&t.node, &self.current_subs, &self.target_config))
+            .map(|t| self.value_width(&t.node))
             .unwrap_or(0);
         self.build_fn_body(&name, func, &param_widths, ret_width);
         self.current_subs.clear();
@@ -367,6 +517,25 @@ impl TIRBuilder {
         true
     }
 
+    /// True if `tail` is a tuple of variables that already sit on the
+    /// stack in tuple order, top element last.
+    fn tail_in_place(&mut self, tail: &Expr) -> bool {
+        let Expr::Tuple(elems) = tail else {
+            return false;
+        };
+        let mut offset = 0;
+        for elem in elems.iter().rev() {
+            let Expr::Var(name) = &elem.node else {
+                return false;
+            };
+            match self.find_var_depth_and_width(name) {
+                Some((depth, width)) if depth == offset => offset += width,
+                _ => return false,
+            }
+        }
+        true
+    }
+
     /// Shared body for `build_fn` and `build_mono_fn`.
     ///
     /// Emits FnStart, registers parameters, compiles the body, cleans up
@@ -374,6 +543,13 @@ impl TIRBuilder {
     fn build_fn_body(&mut self, name: &str, func: &FnDef, param_widths: &[u32], ret_width: u32) {
         self.ops.push(TIROp::FnStart(name.to_string()));
         self.stack.clear();
+        self.current_span = func.name.span;
+        self.no_spill_fn = func.no_spill.then(|| func.name.node.clone());
+        self.stack.watch_spills(func.no_spill);
+        // Tests run from a stub that calls them instead of `main`.
+        if (self.writes_data && name == "main") || (self.emit_tests && func.is_test) {
+            self.emit_data_init();
+        }
 
         // Pass-through optimization: if the body is a single call that
         // forwards all width-1 params in order, skip variable registration
@@ -388,7 +564,7 @@ impl TIRBuilder {
             } = &tail.node
             {
                 let call_name = path.node.as_dotted();
-                self.emit_call_only(&call_name, generic_args, args.len());
+                self.emit_call_only(path.span.start, &call_name, generic_args, args.len());
             }
             self.ops.push(TIROp::Return);
             self.ops.push(TIROp::FnEnd);
@@ -397,22 +573,31 @@ impl TIRBuilder {
         }
 
         // Parameters are already on the real stack. Register them in the model.
-        for (param, &width) in func.params.iter().zip(param_widths) {
-            self.stack.push_named(&param.name.node, width);
-            self.flush_stack_effects();
+        let params: Vec<(&str, u32)> = func
+            .params
+            .iter()
+            .zip(param_widths)
+            .map(|(param, &width)| (param.name.node.as_str(), width))
+            .collect();
+        self.stack.push_named_all(&params);
+        self.flush_stack_effects();
+        for param in &func.params {
+            self.register_struct_layout_from_type(&param.name.node, &param.ty.node);
         }
 
         let body = func.body.as_ref().expect("caller checked body.is_some()");
         let has_return = func.return_ty.is_some();
+        let tail_if = has_return.then(|| body.node.with_tail_if()).flatten();
+        let body = tail_if.as_ref().unwrap_or(&body.node);
+        self.return_ty = func.return_ty.as_ref().map(|t| t.node.clone());
+        let ret_ty = self.return_ty.clone();
 
         if has_return && ret_width > 1 {
             // Multi-element return: build statements first, then handle
             // the tail expression specially to avoid unnecessary copies.
-            for stmt in &body.node.stmts {
-                self.build_stmt(&stmt.node);
-            }
+            self.build_stmts(&body.stmts, None);
 
-            if let Some(tail) = &body.node.tail_expr {
+            if let Some(tail) = &body.tail_expr {
                 let depth_before_tail = self.stack.stack_depth();
 
                 // Check if tail is a simple variable reference at the top.
@@ -430,24 +615,26 @@ impl TIRBuilder {
                     if width == ret_width && depth == 0 {
                         // Return variable is already at the top of the stack.
                         // Just pop dead elements below it.
-                        let dead = depth_before_tail - ret_width;
+                        // Measured after access: a spilled return value
+                        // was just reloaded on top.
+                        let dead = self.stack.stack_depth() - ret_width;
                         if dead > 0 {
                             self.emit_multi_ret_cleanup(ret_width, dead);
                         }
                         // Skip building the tail expr (no dup needed).
                     } else {
-                        self.build_expr(&tail.node);
+                        self.build_expr_expecting(&tail.node, ret_ty.as_ref());
                         let to_pop = self.stack.stack_depth().saturating_sub(ret_width);
                         if to_pop > 0 {
                             self.emit_multi_ret_cleanup(ret_width, to_pop);
                         }
                     }
-                } else if depth_before_tail == ret_width {
-                    // Stack already has exactly ret_width elements —
-                    // the tail expression would just reconstruct what's
-                    // already in place. Skip it entirely.
+                } else if depth_before_tail == ret_width && self.tail_in_place(&tail.node) {
+                    // Stack already has exactly ret_width elements and the
+                    // tail tuple names them in stack order — it would just
+                    // reconstruct what's already in place. Skip it entirely.
                 } else {
-                    self.build_expr(&tail.node);
+                    self.build_expr_expecting(&tail.node, ret_ty.as_ref());
                     let to_pop = self.stack.stack_depth().saturating_sub(ret_width);
                     if to_pop > 0 {
                         self.emit_multi_ret_cleanup(ret_width, to_pop);
@@ -456,12 +643,12 @@ impl TIRBuilder {
             }
         } else {
             // Single-element or void return: use the standard path.
-            self.build_block(&body.node);
+            self.build_block(body);
             let total_width = self.stack.stack_depth();
 
             if has_return && total_width > 0 {
                 let to_pop = total_width.saturating_sub(ret_width);
-                if to_pop > 0 && to_pop <= 15 {
+                if to_pop > 0 && to_pop <= self.stack.max_reach() {
                     self.ops.push(TIROp::Swap(to_pop));
                     self.emit_pop(to_pop);
                 } else if to_pop > 0 {
@@ -478,5 +665,6 @@ impl TIRBuilder {
         self.ops.push(TIROp::Return);
         self.ops.push(TIROp::FnEnd);
         self.stack.clear();
+        self.report_violations();
     }
 }
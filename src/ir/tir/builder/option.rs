@@ -0,0 +1,198 @@
+//! `Option<T>` values: construction and matching.
+//!
+//! An option is a Bool flag followed by its payload, so the flag sits
+//! deeper on the stack. `None` is all zeros; its payload width comes from
+//! the type the context declares (`build_expr_expecting`). A match whose
+//! flag is known at compile time builds only the arm that runs.
+
+use std::collections::BTreeMap;
+
+use crate::ast::*;
+use crate::span::Spanned;
+use crate::tir::TIROp;
+
+use super::TIRBuilder;
+
+impl TIRBuilder {
+    /// Build an expression the context gives a type, so a `None` in it
+    /// knows its payload width. Mirrors the type checker's
+    /// `check_expr_expecting`.
+    pub(crate) fn build_expr_expecting(&mut self, expr: &Expr, expected: Option<&Type>) {
+        match (expr, expected) {
+            (_, Some(Type::Option(payload))) if expr.is_none() => {
+                let width = 1 + self.value_width(payload);
+                for _ in 0..width {
+                    self.ops.push(TIROp::Push(0));
+                }
+                self.stack.push_temp(width);
+                self.flush_stack_effects();
+            }
+            (_, Some(Type::Option(payload))) if expr.some_payload().is_some() => {
+                let value = expr.some_payload().expect("guarded by is_some");
+                self.build_some(&value.node, Some(payload));
+            }
+            (Expr::Tuple(elements), Some(Type::Tuple(tys))) if tys.len() == elements.len() => {
+                let tys: Vec<&Type> = tys.iter().collect();
+                self.build_tuple(elements, &tys);
+            }
+            (Expr::Tuple(elements), Some(Type::NamedTuple(components)))
+                if components.len() == elements.len() =>
+            {
+                let tys: Vec<&Type> = components.iter().map(|(_, ty)| ty).collect();
+                self.build_tuple(elements, &tys);
+            }
+            (Expr::ArrayInit(elements), Some(Type::Array(inner, _))) => {
+                self.build_array(elements, Some(inner));
+            }
+            (
+                Expr::If {
+                    cond,
+                    then_block,
+                    else_block,
+                },
+                _,
+            ) => self.build_if_value(cond, then_block, else_block, expected),
+            _ => self.build_expr(expr),
+        }
+    }
+
+    /// `Some(value)`: the flag, then the payload.
+    pub(crate) fn build_some(&mut self, value: &Expr, payload: Option<&Type>) {
+        self.emit_and_push(TIROp::Push(1), 1);
+        self.build_expr_expecting(value, payload);
+        let mut width = 0;
+        for _ in 0..2 {
+            if let Some(entry) = self.stack.pop() {
+                width += entry.width;
+            }
+        }
+        self.stack.push_temp(width);
+        self.flush_stack_effects();
+    }
+
+    /// The flag of an option expression, if known at compile time: a
+    /// literal `Some(..)` or `None`, or an immutable variable bound to one.
+    pub(crate) fn option_flag(&self, expr: &Expr) -> Option<bool> {
+        if expr.some_payload().is_some() {
+            return Some(true);
+        }
+        match expr {
+            _ if expr.is_none() => Some(false),
+            Expr::Var(name) => self.stack.option_flag(name),
+            _ => None,
+        }
+    }
+
+    /// Flag and payload of an option variable `width` wide, as struct fields.
+    fn option_layout(width: u32) -> BTreeMap<String, (u32, u32)> {
+        BTreeMap::from([
+            ("is_some".to_string(), (width - 1, 1)),
+            ("value".to_string(), (0, width - 1)),
+        ])
+    }
+
+    /// Build a match on an option. With the flag known at compile time
+    /// only the arm that runs is built; otherwise the match becomes an
+    /// `if` on the flag.
+    pub(crate) fn build_option_match(&mut self, expr: &Spanned<Expr>, arms: &[MatchArm]) {
+        let span = expr.span;
+        let arm_for = |present: bool| {
+            arms.iter()
+                .find(|arm| match &arm.pattern.node {
+                    MatchPattern::Some(_) => present,
+                    MatchPattern::None => !present,
+                    _ => false,
+                })
+                .or_else(|| {
+                    arms.iter()
+                        .find(|arm| matches!(arm.pattern.node, MatchPattern::Wildcard))
+                })
+        };
+        // The arm body, after `let name = init` if given.
+        let arm_block = |arm: Option<&MatchArm>, bind: Option<(Spanned<String>, Expr)>| {
+            let mut stmts = Vec::new();
+            if let Some((name, init)) = bind {
+                stmts.push(Spanned::new(
+                    Stmt::Let {
+                        mutable: false,
+                        pattern: Pattern::Name(name),
+                        ty: None,
+                        init: Spanned::new(init, span),
+                    },
+                    span,
+                ));
+            }
+            let mut tail_expr = None;
+            if let Some(arm) = arm {
+                stmts.extend(arm.body.node.stmts.iter().cloned());
+                tail_expr = arm.body.node.tail_expr.clone();
+            }
+            Block { stmts, tail_expr }
+        };
+        let binding = |arm: Option<&MatchArm>| match arm.map(|arm| &arm.pattern.node) {
+            Some(MatchPattern::Some(name)) if name.node != "_" => Some(name.clone()),
+            _ => None,
+        };
+
+        if let Some(present) = self.option_flag(&expr.node) {
+            let arm = arm_for(present);
+            let block = match (expr.node.some_payload(), &expr.node) {
+                // A literal `Some(value)` still evaluates its payload.
+                (Some(value), _) => {
+                    let name = binding(arm).or_else(|| {
+                        (!value.node.is_pure()).then(|| Spanned::new("_".to_string(), span))
+                    });
+                    arm_block(arm, name.map(|name| (name, value.node.clone())))
+                }
+                (None, Expr::Var(name)) if present => {
+                    let width = self.find_var_depth_and_width(name).map_or(1, |(_, w)| w);
+                    self.struct_layouts
+                        .insert(name.clone(), Self::option_layout(width));
+                    let payload = Expr::Var(format!("{}.value", name));
+                    arm_block(arm, binding(arm).map(|name| (name, payload)))
+                }
+                _ => arm_block(arm, None),
+            };
+            let saved = self.stack.save_state();
+            let body = self.build_branch_ir(&block, &saved);
+            self.ops.extend(body);
+            return;
+        }
+
+        // Match on a variable in place; anything else is evaluated into
+        // a temporary first.
+        let (scrutinee, temporary) = match &expr.node {
+            Expr::Var(name) if !name.contains('.') => (name.clone(), false),
+            _ => {
+                let name = self.fresh_label("__option");
+                self.build_expr(&expr.node);
+                if let Some(top) = self.stack.last_mut() {
+                    top.name = Some(name.clone());
+                }
+                (name, true)
+            }
+        };
+        let width = self
+            .find_var_depth_and_width(&scrutinee)
+            .map_or(1, |(_, w)| w);
+        self.struct_layouts
+            .insert(scrutinee.clone(), Self::option_layout(width));
+
+        let some_arm = arm_for(true);
+        let payload = Expr::Var(format!("{}.value", scrutinee));
+        let then_block = arm_block(some_arm, binding(some_arm).map(|name| (name, payload)));
+        let else_block = arm_block(arm_for(false), None);
+        self.build_stmt(&Stmt::If {
+            cond: Spanned::new(Expr::Var(format!("{}.is_some", scrutinee)), span),
+            then_block: Spanned::new(then_block, span),
+            else_block: Some(Spanned::new(else_block, span)),
+        });
+
+        if temporary {
+            self.struct_layouts.remove(&scrutinee);
+            if let Some(entry) = self.stack.pop() {
+                self.emit_pop(entry.width);
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Operand scheduling: Sethi–Ullman ordering of commutative operands.
+//!
+//! Every value an operand leaves on the stack sits above the values the
+//! other operand needs while it is being evaluated. Evaluating the
+//! operand with the deeper peak first keeps the stack shallower, so
+//! fewer variables are pushed out of the operand window and spilled,
+//! and fewer reloads sink beneath pending temporaries with swap chains.
+
+use crate::ast::*;
+use crate::span::Spanned;
+
+use super::TIRBuilder;
+
+impl TIRBuilder {
+    /// The order to evaluate the operands of `op` in. Commutative
+    /// operators evaluate the operand with the larger `stack_need`
+    /// first, unless both have effects whose order must be kept.
+    pub(crate) fn schedule_operands<'e>(
+        &self,
+        op: BinOp,
+        lhs: &'e Spanned<Expr>,
+        rhs: &'e Spanned<Expr>,
+    ) -> (&'e Spanned<Expr>, &'e Spanned<Expr>) {
+        let reorder = is_commutative(op)
+            && stack_need(&rhs.node) > stack_need(&lhs.node)
+            && (lhs.node.is_pure() || rhs.node.is_pure());
+        if reorder {
+            (rhs, lhs)
+        } else {
+            (lhs, rhs)
+        }
+    }
+}
+
+/// Operators whose operands can be evaluated in either order.
+fn is_commutative(op: BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::Add
+            | BinOp::Mul
+            | BinOp::Eq
+            | BinOp::BitAnd
+            | BinOp::BitXor
+            | BinOp::And
+            | BinOp::Or
+    )
+}
+
+/// Sethi–Ullman number of `expr`: the most stack slots its evaluation
+/// occupies at once, counting every value as one slot.
+pub(crate) fn stack_need(expr: &Expr) -> u32 {
+    match expr {
+        Expr::Literal(_) | Expr::Var(_) | Expr::If { .. } => 1,
+        Expr::BinOp { op, lhs, rhs } => {
+            let (l, r) = (stack_need(&lhs.node), stack_need(&rhs.node));
+            if is_commutative(*op) && l != r {
+                l.max(r)
+            } else if is_commutative(*op) {
+                l + 1
+            } else {
+                l.max(r + 1)
+            }
+        }
+        Expr::Call { args, .. } | Expr::Tuple(args) | Expr::ArrayInit(args) => {
+            operands_need(args.iter().map(|arg| &arg.node))
+        }
+        Expr::StructInit { fields, .. } => operands_need(fields.iter().map(|(_, val)| &val.node)),
+        Expr::FieldAccess { expr, .. } => stack_need(&expr.node),
+        Expr::Index { expr, index } => stack_need(&expr.node).max(stack_need(&index.node) + 1),
+    }
+}
+
+/// Peak of evaluating `operands` left to right, each result staying on
+/// the stack under the next.
+fn operands_need<'a>(operands: impl Iterator<Item = &'a Expr>) -> u32 {
+    operands
+        .enumerate()
+        .map(|(i, expr)| i as u32 + stack_need(expr))
+        .max()
+        .unwrap_or(0)
+        .max(1)
+}
@@ -3,35 +3,120 @@
 use std::collections::BTreeMap;
 
 use crate::ast::*;
+use crate::span::Spanned;
 use crate::tir::TIROp;
 
 use super::layout::resolve_type_width;
 use super::TIRBuilder;
 
+/// Stack-model name of a `for` loop's remaining-iteration counter; not a
+/// valid identifier, so it never shadows a user variable.
+const LOOP_COUNTER: &str = "__loop_count";
+
 // ─── Block and statement emission ─────────────────────────────────
 
 impl TIRBuilder {
     pub(crate) fn build_block(&mut self, block: &Block) {
-        for stmt in &block.stmts {
-            self.build_stmt(&stmt.node);
-        }
-        if let Some(tail) = &block.tail_expr {
+        let tail = block.tail_expr.as_deref();
+        if self.build_stmts(&block.stmts, tail) {
+            // The tail call joined the run; it still yields a unit.
+            self.push_temp(0);
+        } else if let Some(tail) = tail {
             self.build_expr(&tail.node);
         }
     }
 
+    /// Build a statement list, packing each run of adjacent
+    /// `sponge_absorb_field` calls into full-rate absorptions. A run
+    /// that reaches the end of the list takes in `tail` if it is an
+    /// absorb call too; returns whether it did.
+    pub(crate) fn build_stmts(
+        &mut self,
+        stmts: &[Spanned<Stmt>],
+        tail: Option<&Spanned<Expr>>,
+    ) -> bool {
+        let mut i = 0;
+        let mut took_tail = false;
+        while i < stmts.len() {
+            let mut run: Vec<&Spanned<Expr>> = stmts[i..]
+                .iter()
+                .map_while(|stmt| match &stmt.node {
+                    Stmt::Expr(expr) => self.absorbed_field(&expr.node),
+                    _ => None,
+                })
+                .collect();
+            if run.is_empty() {
+                self.build_stmt_at(&stmts[i]);
+                i += 1;
+                continue;
+            }
+            let span = stmts[i].span;
+            i += run.len();
+            if i == stmts.len() {
+                if let Some(arg) = tail.and_then(|t| self.absorbed_field(&t.node)) {
+                    run.push(arg);
+                    took_tail = true;
+                }
+            }
+            let outer = std::mem::replace(&mut self.current_span, span);
+            self.build_packed_absorb(&run);
+            self.report_violations();
+            self.current_span = outer;
+        }
+        took_tail
+    }
+
+    /// The argument of a `sponge_absorb_field` call.
+    fn absorbed_field<'e>(&self, expr: &'e Expr) -> Option<&'e Spanned<Expr>> {
+        let Expr::Call { path, args, .. } = expr else {
+            return None;
+        };
+        let name = path.node.as_dotted();
+        let intrinsic = self.resolve_intrinsic(&name);
+        match (intrinsic.as_deref().unwrap_or(&name), args.as_slice()) {
+            ("sponge_absorb_field", [arg]) => Some(arg),
+            _ => None,
+        }
+    }
+
+    /// Absorb `fields` in order as one message, `hash_rate` per
+    /// permutation: the fields, a terminating 1, then zeros up to the
+    /// rate. Runs of different lengths never absorb the same blocks.
+    pub(crate) fn build_packed_absorb(&mut self, fields: &[&Spanned<Expr>]) {
+        let rate = self.target_config.hash_rate as usize;
+        for start in (0..=fields.len()).step_by(rate) {
+            let block = &fields[start..fields.len().min(start + rate)];
+            for field in block {
+                self.build_expr(&field.node);
+            }
+            for word in start + block.len()..start + rate {
+                let pad = u64::from(word == fields.len());
+                self.emit_and_push(TIROp::Push(pad), 1);
+            }
+            for _ in 0..rate {
+                self.stack.pop();
+            }
+            self.ops.push(TIROp::SpongeAbsorb);
+        }
+    }
+
     pub(crate) fn build_stmt(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Let {
-                pattern, init, ty, ..
+                mutable,
+                pattern,
+                init,
+                ty,
             } => {
-                self.build_expr(&init.node);
+                self.build_expr_expecting(&init.node, ty.as_ref().map(|t| &t.node));
 
                 match pattern {
                     Pattern::Name(name) => {
                         if name.node != "_" {
+                            let flag = (!mutable).then(|| self.option_flag(&init.node)).flatten();
                             if let Some(top) = self.stack.last_mut() {
                                 top.name = Some(name.node.clone());
+                                top.option_flag = flag;
                             }
                             // If type is an array, record elem_width.
                             if let Some(sp_ty) = ty {
@@ -43,7 +128,21 @@ impl TIRBuilder {
                                 }
                             }
                             // Record struct field layout from struct init.
-                            if let Expr::StructInit { fields, .. } = &init.node {
+                            let init_struct = match &init.node {
+                                Expr::StructInit { path, .. }
+                                    if path
+                                        .node
+                                        .0
+                                        .last()
+                                        .is_some_and(|s| self.struct_types.contains_key(s)) =>
+                                {
+                                    Some(Type::Named(path.node.clone()))
+                                }
+                                _ => None,
+                            };
+                            if let Some(struct_ty) = init_struct {
+                                self.register_struct_layout_from_type(&name.node, &struct_ty);
+                            } else if let Expr::StructInit { fields, .. } = &init.node {
                                 let mut field_map = BTreeMap::new();
                                 let widths = self.compute_struct_field_widths(ty, fields);
                                 let total: u32 = widths.iter().sum();
@@ -57,42 +156,48 @@ impl TIRBuilder {
                                 self.struct_layouts.insert(name.node.clone(), field_map);
                             } else if let Some(sp_ty) = ty {
                                 self.register_struct_layout_from_type(&name.node, &sp_ty.node);
+                            } else if let Some(components) = self.call_named_return(&init.node) {
+                                let layout = self.named_tuple_layout(&components);
+                                self.struct_layouts.insert(name.node.clone(), layout);
                             }
                         }
                     }
                     Pattern::Tuple(names) => {
-                        let top = self.stack.pop();
-                        if let Some(entry) = top {
-                            let total_width = entry.width;
+                        let parts =
+                            self.tuple_parts(ty.as_ref().map(|t| &t.node), &init.node, names.len());
+                        if let Some(entry) = self.stack.pop() {
                             let n = names.len() as u32;
-                            let elem_width = if n > 0 { total_width / n } else { 1 };
+                            let parts = parts.unwrap_or_else(|| {
+                                let elem_width = if n > 0 { entry.width / n } else { 1 };
+                                vec![elem_width; names.len()]
+                            });
 
-                            for name in names.iter() {
+                            for (name, &width) in names.iter().zip(&parts) {
                                 let var_name = if name.node == "_" {
                                     "__anon"
                                 } else {
                                     &name.node
                                 };
-                                self.stack.push_named(var_name, elem_width);
+                                self.stack.push_named(var_name, width);
                                 self.flush_stack_effects();
                             }
 
                             // Eagerly pop trailing wildcard bindings.
                             // For `let (h1, _, _, _, _) = digest`, wildcards on top
                             // of the stack are immediately discarded.
-                            let mut trailing_wildcards = 0u32;
-                            for name in names.iter().rev() {
-                                if name.node == "_" {
-                                    trailing_wildcards += elem_width;
-                                } else {
-                                    break;
-                                }
-                            }
-                            if trailing_wildcards > 0 {
-                                for _ in 0..(trailing_wildcards / elem_width) {
+                            let trailing: Vec<u32> = names
+                                .iter()
+                                .zip(&parts)
+                                .rev()
+                                .take_while(|(name, _)| name.node == "_")
+                                .map(|(_, &width)| width)
+                                .filter(|&width| width > 0)
+                                .collect();
+                            if !trailing.is_empty() {
+                                for _ in &trailing {
                                     self.stack.pop();
                                 }
-                                self.emit_pop(trailing_wildcards);
+                                self.emit_pop(trailing.iter().sum());
                             }
                         }
                     }
@@ -101,12 +206,21 @@ impl TIRBuilder {
 
             Stmt::Assign { place, value } => {
                 if let Place::Var(name) = &place.node {
-                    self.build_expr(&value.node);
-                    let depth = self.stack.access_var(name);
-                    self.flush_stack_effects();
-                    if depth <= 15 {
-                        self.ops.push(TIROp::Swap(depth));
-                        self.ops.push(TIROp::Pop(1));
+                    // Only the variable's width is known here; as a payload
+                    // type, `[Field; width - 1]` sizes a `None` just the same.
+                    let expected = self.find_var_depth_and_width(name).map(|(_, width)| {
+                        let payload = ArraySize::Literal(u64::from(width.saturating_sub(1)));
+                        Type::Option(Box::new(Type::Array(Box::new(Type::Field), payload)))
+                    });
+                    self.build_expr_expecting(&value.node, expected.as_ref());
+                    let (depth, width) = self.find_var_depth_and_width(name).unwrap_or((0, 1));
+                    if depth <= self.stack.max_reach() {
+                        // Each swap drops the value's top word into the
+                        // variable's top slot; popping lifts the next pair.
+                        for _ in 0..width {
+                            self.ops.push(TIROp::Swap(depth));
+                            self.ops.push(TIROp::Pop(1));
+                        }
                     }
                     self.stack.pop();
                 }
@@ -122,10 +236,8 @@ impl TIRBuilder {
 
                 if let Some(else_blk) = else_block {
                     let saved = self.stack.save_state();
-                    let then_body = self.build_block_as_ir(&then_block.node);
-                    self.stack.restore_state(saved.clone());
-                    let else_body = self.build_block_as_ir(&else_blk.node);
-                    self.stack.restore_state(saved);
+                    let then_body = self.build_branch_ir(&then_block.node, &saved);
+                    let else_body = self.build_branch_ir(&else_blk.node, &saved);
 
                     self.ops.push(TIROp::IfElse {
                         then_body,
@@ -133,32 +245,44 @@ impl TIRBuilder {
                     });
                 } else {
                     let saved = self.stack.save_state();
-                    let then_body = self.build_block_as_ir(&then_block.node);
-                    self.stack.restore_state(saved);
+                    let then_body = self.build_branch_ir(&then_block.node, &saved);
 
                     self.ops.push(TIROp::IfOnly { then_body });
                 }
             }
 
             Stmt::For {
-                var: _,
-                start: _,
+                var,
+                start,
                 end,
                 body,
                 ..
             } => {
                 let loop_label = self.fresh_label("loop");
 
+                // The loop variable sits under the remaining-iteration
+                // counter, which the loop header tests and decrements.
+                self.build_expr(&start.node);
+                if let Some(top) = self.stack.last_mut() {
+                    top.name = Some(var.node.clone());
+                }
                 self.build_expr(&end.node);
+                if !matches!(start.node, Expr::Literal(Literal::Integer(0))) {
+                    self.ops.push(TIROp::Dup(1));
+                    self.ops.push(TIROp::Sub);
+                }
+                if let Some(top) = self.stack.last_mut() {
+                    top.name = Some(LOOP_COUNTER.to_string());
+                }
+                self.flush_stack_effects();
 
                 self.ops.push(TIROp::Call(loop_label.clone()));
-                self.ops.push(TIROp::Pop(1));
-                self.stack.pop();
+                self.ops.push(TIROp::Pop(2));
 
                 let saved = self.stack.save_state();
-                self.stack.clear();
-                let body_ir = self.build_block_as_ir(&body.node);
-                self.stack.restore_state(saved);
+                let body_ir = self.build_loop_body_ir(&body.node, &saved);
+                self.stack.pop();
+                self.stack.pop();
 
                 self.ops.push(TIROp::Loop {
                     label: loop_label,
@@ -168,28 +292,39 @@ impl TIRBuilder {
 
             Stmt::TupleAssign { names, value } => {
                 self.build_expr(&value.node);
-                let top = self.stack.pop();
-                if let Some(entry) = top {
-                    let total_width = entry.width;
-                    let n = names.len() as u32;
-                    let elem_width = if n > 0 { total_width / n } else { 1 };
-
-                    for name in names.iter().rev() {
-                        let depth = self.stack.access_var(&name.node);
-                        self.flush_stack_effects();
-                        if elem_width == 1 {
-                            self.ops.push(TIROp::Swap(depth));
-                            self.ops.push(TIROp::Pop(1));
+                let parts = names
+                    .iter()
+                    .map(|name| self.find_var_depth_and_width(&name.node).map(|(_, w)| w))
+                    .collect::<Option<Vec<u32>>>()
+                    .or_else(|| self.tuple_parts(None, &value.node, names.len()));
+                if let (Some(parts), Some(_)) = (parts, self.stack.pop()) {
+                    // Each component is assigned like a single variable,
+                    // the top one first.
+                    for &width in &parts {
+                        self.stack.push_temp(width);
+                    }
+                    self.flush_stack_effects();
+                    for (name, &width) in names.iter().zip(&parts).rev() {
+                        let (depth, _) = self
+                            .find_var_depth_and_width(&name.node)
+                            .unwrap_or((0, width));
+                        if depth <= self.stack.max_reach() {
+                            for _ in 0..width {
+                                self.ops.push(TIROp::Swap(depth));
+                                self.ops.push(TIROp::Pop(1));
+                            }
                         }
+                        self.stack.pop();
                     }
-                    let _ = total_width;
                 }
             }
 
             Stmt::Expr(expr) => {
-                let before = self.stack.stack_len();
+                // Entries, not stack length: a variable reloaded while
+                // building the expression is not part of its value.
+                let before = self.stack.entry_count();
                 self.build_expr(&expr.node);
-                while self.stack.stack_len() > before {
+                while self.stack.entry_count() > before {
                     if let Some(top) = self.stack.last() {
                         let w = top.width;
                         if w > 0 {
@@ -202,7 +337,8 @@ impl TIRBuilder {
 
             Stmt::Return(value) => {
                 if let Some(val) = value {
-                    self.build_expr(&val.node);
+                    let expected = self.return_ty.clone();
+                    self.build_expr_expecting(&val.node, expected.as_ref());
                 }
             }
 
@@ -33,19 +33,25 @@ fn pass_through_hash_emits_minimal_ops() {
         kind: FileKind::Module,
         name: sp("test".to_string()),
         uses: vec![],
+        mods: vec![],
         declarations: vec![],
         items: vec![sp(Item::Fn(FnDef {
             is_pub: true,
             cfg: None,
             intrinsic: None,
             is_test: false,
+            test_max_cycles: None,
             is_pure: false,
+            is_entry: false,
+            no_spill: false,
             requires: vec![],
             ensures: vec![],
+            allows: vec![],
             name: sp("wrapper".to_string()),
             type_params: vec![],
             params,
             return_ty: Some(sp(Type::Digest)),
+            where_clause: vec![],
             body: Some(sp(Block {
                 stmts: vec![],
                 tail_expr: Some(Box::new(sp(Expr::Call {
@@ -84,15 +90,20 @@ fn non_pass_through_still_compiles_normally() {
         kind: FileKind::Module,
         name: sp("test".to_string()),
         uses: vec![],
+        mods: vec![],
         declarations: vec![],
         items: vec![sp(Item::Fn(FnDef {
             is_pub: true,
             cfg: None,
             intrinsic: None,
             is_test: false,
+            test_max_cycles: None,
             is_pure: false,
+            is_entry: false,
+            no_spill: false,
             requires: vec![],
             ensures: vec![],
+            allows: vec![],
             name: sp("add".to_string()),
             type_params: vec![],
             params: vec![
@@ -106,6 +117,7 @@ fn non_pass_through_still_compiles_normally() {
                 },
             ],
             return_ty: Some(sp(Type::Field)),
+            where_clause: vec![],
             body: Some(sp(Block {
                 stmts: vec![],
                 tail_expr: Some(Box::new(sp(Expr::BinOp {
@@ -142,6 +154,7 @@ fn pass_through_user_call_emits_call_and_return() {
         kind: FileKind::Module,
         name: sp("test".to_string()),
         uses: vec![],
+        mods: vec![],
         declarations: vec![],
         items: vec![
             sp(Item::Fn(FnDef {
@@ -149,9 +162,13 @@ fn pass_through_user_call_emits_call_and_return() {
                 cfg: None,
                 intrinsic: None,
                 is_test: false,
+                test_max_cycles: None,
                 is_pure: false,
+                is_entry: false,
+                no_spill: false,
                 requires: vec![],
                 ensures: vec![],
+                allows: vec![],
                 name: sp("target".to_string()),
                 type_params: vec![],
                 params: vec![Param {
@@ -159,6 +176,7 @@ fn pass_through_user_call_emits_call_and_return() {
                     ty: sp(Type::Field),
                 }],
                 return_ty: Some(sp(Type::Field)),
+                where_clause: vec![],
                 body: Some(sp(Block {
                     stmts: vec![],
                     tail_expr: Some(Box::new(sp(Expr::Var("x".to_string())))),
@@ -169,9 +187,13 @@ fn pass_through_user_call_emits_call_and_return() {
                 cfg: None,
                 intrinsic: None,
                 is_test: false,
+                test_max_cycles: None,
                 is_pure: false,
+                is_entry: false,
+                no_spill: false,
                 requires: vec![],
                 ensures: vec![],
+                allows: vec![],
                 name: sp("wrapper".to_string()),
                 type_params: vec![],
                 params: vec![Param {
@@ -179,6 +201,7 @@ fn pass_through_user_call_emits_call_and_return() {
                     ty: sp(Type::Field),
                 }],
                 return_ty: Some(sp(Type::Field)),
+                where_clause: vec![],
                 body: Some(sp(Block {
                     stmts: vec![],
                     tail_expr: Some(Box::new(sp(Expr::Call {
@@ -227,6 +250,7 @@ fn pass_through_multi_width_params_emits_minimal_ops() {
         kind: FileKind::Module,
         name: sp("test".to_string()),
         uses: vec![],
+        mods: vec![],
         declarations: vec![],
         items: vec![
             sp(Item::Fn(FnDef {
@@ -234,9 +258,13 @@ fn pass_through_multi_width_params_emits_minimal_ops() {
                 cfg: None,
                 intrinsic: None,
                 is_test: false,
+                test_max_cycles: None,
                 is_pure: false,
+                is_entry: false,
+                no_spill: false,
                 requires: vec![],
                 ensures: vec![],
+                allows: vec![],
                 name: sp("target".to_string()),
                 type_params: vec![],
                 params: vec![
@@ -250,6 +278,7 @@ fn pass_through_multi_width_params_emits_minimal_ops() {
                     },
                 ],
                 return_ty: Some(sp(Type::Digest)),
+                where_clause: vec![],
                 body: Some(sp(Block {
                     stmts: vec![],
                     tail_expr: Some(Box::new(sp(Expr::Var("a".to_string())))),
@@ -260,9 +289,13 @@ fn pass_through_multi_width_params_emits_minimal_ops() {
                 cfg: None,
                 intrinsic: None,
                 is_test: false,
+                test_max_cycles: None,
                 is_pure: false,
+                is_entry: false,
+                no_spill: false,
                 requires: vec![],
                 ensures: vec![],
+                allows: vec![],
                 name: sp("wrapper".to_string()),
                 type_params: vec![],
                 params: vec![
@@ -276,6 +309,7 @@ fn pass_through_multi_width_params_emits_minimal_ops() {
                     },
                 ],
                 return_ty: Some(sp(Type::Digest)),
+                where_clause: vec![],
                 body: Some(sp(Block {
                     stmts: vec![],
                     tail_expr: Some(Box::new(sp(Expr::Call {
@@ -19,6 +19,7 @@ fn minimal_program(items: Vec<Item>) -> File {
         kind: FileKind::Program,
         name: sp("test".to_string()),
         uses: vec![],
+        mods: vec![],
         declarations: vec![],
         items: items.into_iter().map(|i| sp(i)).collect(),
     }
@@ -37,13 +38,18 @@ fn test_minimal_program() {
         cfg: None,
         intrinsic: None,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: sp("main".to_string()),
         type_params: vec![],
         params: vec![],
         return_ty: None,
+        where_clause: vec![],
         body: Some(sp(Block {
             stmts: vec![],
             tail_expr: None,
@@ -80,13 +86,18 @@ fn test_if_else_produces_structural_op() {
         cfg: None,
         intrinsic: None,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: sp("main".to_string()),
         type_params: vec![],
         params: vec![],
         return_ty: None,
+        where_clause: vec![],
         body: Some(sp(Block {
             stmts: vec![sp(Stmt::If {
                 cond: sp(Expr::Literal(Literal::Bool(true))),
@@ -126,13 +137,18 @@ fn test_for_loop_produces_loop_op() {
         cfg: None,
         intrinsic: None,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: sp("main".to_string()),
         type_params: vec![],
         params: vec![],
         return_ty: None,
+        where_clause: vec![],
         body: Some(sp(Block {
             stmts: vec![sp(Stmt::For {
                 var: sp("i".to_string()),
@@ -163,13 +179,18 @@ fn test_arithmetic_sequence() {
         cfg: None,
         intrinsic: None,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: sp("main".to_string()),
         type_params: vec![],
         params: vec![],
         return_ty: Some(sp(Type::Field)),
+        where_clause: vec![],
         body: Some(sp(Block {
             stmts: vec![],
             tail_expr: Some(Box::new(sp(Expr::BinOp {
@@ -245,19 +266,25 @@ fn test_module_no_preamble() {
         kind: FileKind::Module,
         name: sp("mylib".to_string()),
         uses: vec![],
+        mods: vec![],
         declarations: vec![],
         items: vec![sp(Item::Fn(FnDef {
             is_pub: true,
             cfg: None,
             intrinsic: None,
             is_test: false,
+            test_max_cycles: None,
             is_pure: false,
+            is_entry: false,
+            no_spill: false,
             requires: vec![],
             ensures: vec![],
+            allows: vec![],
             name: sp("helper".to_string()),
             type_params: vec![],
             params: vec![],
             return_ty: None,
+            where_clause: vec![],
             body: Some(sp(Block {
                 stmts: vec![],
                 tail_expr: None,
@@ -287,13 +314,18 @@ fn test_if_only_produces_structural_op() {
         cfg: None,
         intrinsic: None,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: sp("main".to_string()),
         type_params: vec![],
         params: vec![],
         return_ty: None,
+        where_clause: vec![],
         body: Some(sp(Block {
             stmts: vec![sp(Stmt::If {
                 cond: sp(Expr::Literal(Literal::Bool(true))),
@@ -321,13 +353,18 @@ fn test_let_and_var_ref() {
         cfg: None,
         intrinsic: None,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: sp("main".to_string()),
         type_params: vec![],
         params: vec![],
         return_ty: Some(sp(Type::Field)),
+        where_clause: vec![],
         body: Some(sp(Block {
             stmts: vec![sp(Stmt::Let {
                 mutable: false,
@@ -358,13 +395,18 @@ fn test_intrinsic_pub_read_write() {
         cfg: None,
         intrinsic: None,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: sp("main".to_string()),
         type_params: vec![],
         params: vec![],
         return_ty: None,
+        where_clause: vec![],
         body: Some(sp(Block {
             stmts: vec![sp(Stmt::Expr(sp(Expr::Call {
                 path: sp(ModulePath::single("pub_write".to_string())),
@@ -396,13 +438,18 @@ fn test_if_else_nested_bodies_have_content() {
         cfg: None,
         intrinsic: None,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: sp("main".to_string()),
         type_params: vec![],
         params: vec![],
         return_ty: None,
+        where_clause: vec![],
         body: Some(sp(Block {
             stmts: vec![sp(Stmt::If {
                 cond: sp(Expr::Literal(Literal::Bool(true))),
@@ -1,3 +1,5 @@
+use crate::diagnostic::Instantiation;
+
 /// Per-module TASM output ready for linking.
 #[derive(Clone, Debug)]
 pub(crate) struct ModuleTasm {
@@ -7,27 +9,174 @@ pub(crate) struct ModuleTasm {
     pub(crate) is_program: bool,
     /// Raw TASM output from the emitter.
     pub(crate) tasm: String,
+    /// Top-level function labels in emission order (`main`, `sum__N3`).
+    /// Any other label is a block of the function before it.
+    pub(crate) fn_labels: Vec<String>,
+    /// The labels in `fn_labels` that are monomorphized generic instances.
+    pub(crate) mono_labels: Vec<String>,
+}
+
+/// One monomorphized generic instance of a linked program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonoEntry {
+    /// Dotted name of the defining module.
+    pub module: String,
+    /// Instance label within its module, e.g. `sum__N3`.
+    pub label: String,
+    /// Instructions of the instance, including its branch blocks.
+    pub instructions: usize,
+    /// Linked label of the identical instance this one was merged into.
+    pub merged_into: Option<String>,
+    /// The call that first requested the instance, when known.
+    pub instantiated_at: Option<Instantiation>,
+}
+
+/// The generic instances reachable in a linked program.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MonoReport {
+    pub instances: Vec<MonoEntry>,
+}
+
+impl MonoReport {
+    /// Instances emitted after deduplication.
+    pub fn kept(&self) -> impl Iterator<Item = &MonoEntry> {
+        self.instances.iter().filter(|e| e.merged_into.is_none())
+    }
+
+    pub fn instance_count(&self) -> usize {
+        self.kept().count()
+    }
+
+    /// Total instructions emitted for generic instances.
+    pub fn instruction_count(&self) -> usize {
+        self.kept().map(|e| e.instructions).sum()
+    }
+
+    /// Human-readable table for `trident build --mono-report`.
+    pub fn format_report(&self) -> String {
+        let mut out = String::from("Monomorphization report:\n");
+        if self.instances.is_empty() {
+            out.push_str("  no generic instances\n");
+            return out;
+        }
+        let width = self
+            .instances
+            .iter()
+            .map(|e| e.module.len() + e.label.len() + 1)
+            .max()
+            .unwrap_or(0);
+        for e in &self.instances {
+            let name = format!("{}.{}", e.module, e.label);
+            match &e.merged_into {
+                Some(target) => out.push_str(&format!(
+                    "  {:<width$}  merged into {}",
+                    name,
+                    target,
+                    width = width
+                )),
+                None => out.push_str(&format!(
+                    "  {:<width$}  {:>6} instructions",
+                    name,
+                    e.instructions,
+                    width = width
+                )),
+            }
+            if let Some(site) = &e.instantiated_at {
+                out.push_str(&format!("  (instantiated at {}:{})", site.file, site.line));
+            }
+            out.push('\n');
+        }
+        let merged = self.instances.len() - self.instance_count();
+        let count = self.instance_count();
+        out.push_str(&format!(
+            "  {} instance{} ({} merged), {} instructions\n",
+            count,
+            if count == 1 { "" } else { "s" },
+            merged,
+            self.instruction_count()
+        ));
+        out
+    }
+}
+
+/// How the linker turns a module path into a label prefix.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LabelScheme {
+    /// Path segments joined by `-`: `crypto.sponge` → `crypto-sponge__`.
+    /// Identifiers never contain `-`, so no two modules share a prefix.
+    #[default]
+    Qualified,
+    /// Dots become underscores: `crypto.sponge` → `crypto_sponge__`. The
+    /// scheme of earlier releases, which `crypto_sponge` also maps to.
+    Flat,
+}
+
+impl LabelScheme {
+    /// Parse a scheme name (`labels = "flat"` in trident.toml).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "qualified" => Some(Self::Qualified),
+            "flat" => Some(Self::Flat),
+            _ => None,
+        }
+    }
+
+    /// Label prefix of a dotted module name.
+    pub fn module_prefix(self, module: &str) -> String {
+        match self {
+            Self::Qualified => format!("{}__", module.replace('.', "-")),
+            Self::Flat => format!("{}__", module.replace('.', "_")),
+        }
+    }
+}
+
+/// A linked program and the generic instances it contains.
+pub(crate) struct LinkedProgram {
+    pub(crate) tasm: String,
+    pub(crate) mono: MonoReport,
 }
 
 /// Link multiple module TASM outputs into a single program.
 /// Performs dead code elimination: only includes functions reachable
-/// from the program entry point.
-pub(crate) fn link(modules: Vec<ModuleTasm>) -> String {
-    // First, mangle all modules and collect the full TASM.
-    let mut all_lines = Vec::new();
-
+/// from the program entry point. Generic instances whose code is
+/// identical up to label names are merged into the first of them.
+pub(crate) fn link(modules: Vec<ModuleTasm>, scheme: LabelScheme) -> LinkedProgram {
     // Find program entry
     let entry_label = if let Some(prog) = modules.iter().find(|m| m.is_program) {
-        format!("{}main", mangle_module(&prog.module_name))
+        format!("{}main", scheme.module_prefix(&prog.module_name))
     } else {
         // No program module — emit a halt-only program.
-        return "    halt\n// error: no program module found".to_string();
+        return LinkedProgram {
+            tasm: "    halt\n// error: no program module found".to_string(),
+            mono: MonoReport::default(),
+        };
     };
 
+    let mut linked = link_reachable(&modules, scheme, std::slice::from_ref(&entry_label));
+    let mut tasm = format!("    call {}\n    halt", entry_label);
+    if !linked.tasm.is_empty() {
+        tasm.push('\n');
+        tasm.push_str(&linked.tasm);
+    }
+    linked.tasm = tasm;
+    linked
+}
+
+/// Link the functions reachable from `roots` (linked labels such as
+/// `app__test_transfer`) with no entry of their own; the caller prepends
+/// one. `trident test` links once and calls each test in turn.
+pub(crate) fn link_reachable(
+    modules: &[ModuleTasm],
+    scheme: LabelScheme,
+    roots: &[String],
+) -> LinkedProgram {
+    // First, mangle all modules and collect the full TASM.
+    let mut all_lines = Vec::new();
+
     // Mangle all modules
-    for module in &modules {
-        let prefix = mangle_module(&module.module_name);
-        let mangled = mangle_labels(&module.tasm, &prefix, module.is_program);
+    for module in modules {
+        let prefix = scheme.module_prefix(&module.module_name);
+        let mangled = mangle_labels(&module.tasm, &prefix, module.is_program, scheme);
         for line in mangled.lines() {
             all_lines.push(line.to_string());
         }
@@ -103,10 +252,80 @@ pub(crate) fn link(modules: Vec<ModuleTasm>) -> String {
         target.to_string()
     };
 
-    // BFS from entry label to find all reachable functions
+    // Group each generic instance with its branch blocks, then merge
+    // instances whose groups are identical once their own labels are
+    // renamed positionally.
+    let mut top_level: BTreeSet<String> = BTreeSet::new();
+    let mut instances: Vec<(String, String)> = Vec::new();
+    for module in modules {
+        let prefix = scheme.module_prefix(&module.module_name);
+        for label in &module.fn_labels {
+            top_level.insert(format!("{}{}", prefix, label));
+        }
+        for label in &module.mono_labels {
+            top_level.insert(format!("{}{}", prefix, label));
+            instances.push((module.module_name.clone(), label.clone()));
+        }
+    }
+    let group_of = |head: &str| -> Vec<usize> {
+        let Some(first) = functions.iter().position(|(l, _, _)| l == head) else {
+            return Vec::new();
+        };
+        let mut group = vec![first];
+        for (k, (label, _, _)) in functions.iter().enumerate().skip(first + 1) {
+            if top_level.contains(label) {
+                break;
+            }
+            group.push(k);
+        }
+        group
+    };
+    let mut redirect: BTreeMap<String, String> = BTreeMap::new();
+    let mut canonical: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut groups: Vec<(String, String, Vec<usize>)> = Vec::new();
+    for (module, label) in &instances {
+        let head = format!("{}{}", scheme.module_prefix(module), label);
+        let group = group_of(&head);
+        let labels: Vec<String> = group.iter().map(|&k| functions[k].0.clone()).collect();
+        let mut shape = String::new();
+        for &k in &group {
+            let (_, start, end) = &functions[k];
+            for line in &all_lines[*start..*end] {
+                let t = line.trim();
+                if t.is_empty() {
+                    continue;
+                }
+                let renamed = labels.iter().position(|l| {
+                    t.strip_suffix(':') == Some(l) || t.strip_prefix("call ") == Some(l)
+                });
+                match renamed {
+                    Some(pos) if t.ends_with(':') => shape.push_str(&format!("@{}:", pos)),
+                    Some(pos) => shape.push_str(&format!("call @{}", pos)),
+                    None => shape.push_str(t),
+                }
+                shape.push('\n');
+            }
+        }
+        match canonical.get(&shape) {
+            Some(first) if first.len() == labels.len() => {
+                for (dup, target) in labels.iter().zip(first) {
+                    redirect.insert(dup.clone(), target.clone());
+                }
+            }
+            _ => {
+                canonical.insert(shape, labels);
+            }
+        }
+        groups.push((module.clone(), head, group));
+    }
+    let resolve_call = |target: &str| -> String {
+        let resolved = resolve_target(target);
+        redirect.get(&resolved).cloned().unwrap_or(resolved)
+    };
+
+    // BFS from the roots to find all reachable functions
     let mut reachable: BTreeSet<String> = BTreeSet::new();
-    let mut queue: VecDeque<String> = VecDeque::new();
-    queue.push_back(entry_label.clone());
+    let mut queue: VecDeque<String> = roots.iter().cloned().collect();
     while let Some(label) = queue.pop_front() {
         if reachable.contains(&label) {
             continue;
@@ -114,7 +333,7 @@ pub(crate) fn link(modules: Vec<ModuleTasm>) -> String {
         reachable.insert(label.clone());
         if let Some(calls) = call_graph.get(&label) {
             for target in calls {
-                let resolved = resolve_target(target);
+                let resolved = resolve_call(target);
                 if !reachable.contains(&resolved) {
                     queue.push_back(resolved);
                 }
@@ -122,26 +341,56 @@ pub(crate) fn link(modules: Vec<ModuleTasm>) -> String {
         }
     }
 
-    // Emit only reachable functions
+    // Emit only reachable functions, with calls to merged instances redirected
     let mut output = Vec::new();
-    output.push(format!("    call {}", entry_label));
-    output.push("    halt".to_string());
 
     for (label, start, end) in &functions {
         if reachable.contains(label) {
             for line in &all_lines[*start..*end] {
-                output.push(line.clone());
+                match line.trim().strip_prefix("call ") {
+                    Some(target) if redirect.contains_key(&resolve_target(target)) => {
+                        output.push(format!("    call {}", resolve_call(target)));
+                    }
+                    _ => output.push(line.clone()),
+                }
             }
         }
     }
 
-    output.join("\n")
+    let mut mono = MonoReport::default();
+    for (module, head, group) in &groups {
+        let merged_into = redirect.get(head).cloned();
+        let live = merged_into.as_ref().unwrap_or(head);
+        if !reachable.contains(live) {
+            continue;
+        }
+        let instructions = group
+            .iter()
+            .flat_map(|&k| &all_lines[functions[k].1..functions[k].2])
+            .map(|line| line.trim())
+            .filter(|t| !t.is_empty() && !t.starts_with("//") && !t.ends_with(':'))
+            .count();
+        let prefix = scheme.module_prefix(module);
+        mono.instances.push(MonoEntry {
+            module: module.clone(),
+            label: head[prefix.len()..].to_string(),
+            instructions,
+            merged_into,
+            instantiated_at: None,
+        });
+    }
+
+    LinkedProgram {
+        tasm: output.join("\n"),
+        mono,
+    }
 }
 
 /// Mangle all labels in a TASM block with a module prefix.
 /// `__foo:` becomes `modname__foo:`
 /// `call __foo` becomes `call modname__foo`
-fn mangle_labels(tasm: &str, prefix: &str, is_program: bool) -> String {
+/// `call @other.mod.foo` becomes `call other-mod__foo` (under `scheme`)
+fn mangle_labels(tasm: &str, prefix: &str, is_program: bool, scheme: LabelScheme) -> String {
     let mut result = Vec::new();
 
     for line in tasm.lines() {
@@ -167,10 +416,15 @@ fn mangle_labels(tasm: &str, prefix: &str, is_program: bool) -> String {
 
         // Call instruction: two forms:
         // 1. `call __foo` — local call, mangle to `call prefix__foo`
-        // 2. `call @mod__fn` — cross-module call (@ marker), emit as `call mod__fn`
+        // 2. `call @mod.fn` — cross-module call (@ marker), prefix with the
+        //    callee's module instead of this one
         if let Some(target) = trimmed.strip_prefix("call @") {
-            // Cross-module: already fully qualified, strip marker
-            result.push(format!("    call {}", target));
+            let (module, label) = target.rsplit_once('.').unwrap_or(("", target));
+            result.push(format!(
+                "    call {}{}",
+                scheme.module_prefix(module),
+                label
+            ));
             continue;
         }
         if let Some(target) = trimmed.strip_prefix("call __") {
@@ -185,20 +439,21 @@ fn mangle_labels(tasm: &str, prefix: &str, is_program: bool) -> String {
     result.join("\n")
 }
 
-/// Convert a dotted module name to a label-safe prefix.
-/// "crypto.sponge" → "crypto_sponge__"
-fn mangle_module(name: &str) -> String {
-    format!("{}__", name.replace('.', "_"))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_mangle_module() {
-        assert_eq!(mangle_module("merkle"), "merkle__");
-        assert_eq!(mangle_module("crypto.sponge"), "crypto_sponge__");
+    fn test_module_prefix() {
+        assert_eq!(LabelScheme::Qualified.module_prefix("merkle"), "merkle__");
+        assert_eq!(
+            LabelScheme::Qualified.module_prefix("crypto.sponge"),
+            "crypto-sponge__"
+        );
+        assert_eq!(
+            LabelScheme::Flat.module_prefix("crypto.sponge"),
+            "crypto_sponge__"
+        );
     }
 
     #[test]
@@ -207,8 +462,10 @@ mod tests {
             module_name: "test".to_string(),
             is_program: true,
             tasm: "    call __main\n    halt\n\n__main:\n    read_io 1\n    return\n".to_string(),
+            fn_labels: vec!["main".to_string()],
+            mono_labels: vec![],
         }];
-        let linked = link(modules);
+        let linked = link(modules, LabelScheme::Qualified).tasm;
         assert!(linked.contains("call test__main"));
         assert!(linked.contains("halt"));
         assert!(linked.contains("test__main:"));
@@ -222,15 +479,19 @@ mod tests {
                 is_program: false,
                 tasm: "__verify:\n    read_io 1\n    return\n__unused:\n    push 0\n    return\n"
                     .to_string(),
+                fn_labels: vec!["verify".to_string(), "unused".to_string()],
+                mono_labels: vec![],
             },
             ModuleTasm {
                 module_name: "main_prog".to_string(),
                 is_program: true,
                 tasm: "    call __main\n    halt\n\n__main:\n    call merkle__verify\n    return\n"
                     .to_string(),
+                fn_labels: vec!["main".to_string()],
+                mono_labels: vec![],
             },
         ];
-        let linked = link(modules);
+        let linked = link(modules, LabelScheme::Qualified).tasm;
         // Entry point should use the program module's main
         assert!(linked.contains("call main_prog__main"));
         assert!(linked.contains("halt"));
@@ -240,4 +501,68 @@ mod tests {
         assert!(!linked.contains("merkle__unused:"));
         assert!(linked.contains("main_prog__main:"));
     }
+
+    #[test]
+    fn test_same_function_in_colliding_module_paths() {
+        let library = |name: &str, value: u32| ModuleTasm {
+            module_name: name.to_string(),
+            is_program: false,
+            tasm: format!("__init:\n    push {}\n    return\n", value),
+            fn_labels: vec!["init".to_string()],
+            mono_labels: vec![],
+        };
+        let modules = vec![
+            library("crypto.sponge", 1),
+            library("crypto_sponge", 2),
+            ModuleTasm {
+                module_name: "app".to_string(),
+                is_program: true,
+                tasm: "    call __main\n    halt\n\n__main:\n    call @crypto.sponge.init\n    call @crypto_sponge.init\n    return\n"
+                    .to_string(),
+                fn_labels: vec!["main".to_string()],
+                mono_labels: vec![],
+            },
+        ];
+
+        let linked = link(modules.clone(), LabelScheme::Qualified).tasm;
+        assert_eq!(linked.matches("crypto-sponge__init:").count(), 1);
+        assert_eq!(linked.matches("crypto_sponge__init:").count(), 1);
+        assert!(linked.contains("call crypto-sponge__init"));
+        assert!(linked.contains("call crypto_sponge__init"));
+
+        // The flat scheme gives both modules the same label.
+        let flat = link(modules, LabelScheme::Flat).tasm;
+        assert_eq!(flat.matches("crypto_sponge__init:").count(), 2);
+    }
+
+    #[test]
+    fn test_identical_instances_are_merged_and_reported() {
+        let tasm = "    call __main\n    halt\n\n__main:\n    call __id__N2\n    call __id__N3\n    call __len__N3\n    return\n\
+                    __id__N2:\n    skiz\n    call __then__1\n    return\n__then__1:\n    push 1\n    return\n\
+                    __id__N3:\n    skiz\n    call __then__2\n    return\n__then__2:\n    push 1\n    return\n\
+                    __len__N3:\n    push 3\n    return\n";
+        let modules = vec![ModuleTasm {
+            module_name: "p".to_string(),
+            is_program: true,
+            tasm: tasm.to_string(),
+            fn_labels: ["main", "id__N2", "id__N3", "len__N3"]
+                .map(String::from)
+                .to_vec(),
+            mono_labels: ["id__N2", "id__N3", "len__N3"].map(String::from).to_vec(),
+        }];
+        let linked = link(modules, LabelScheme::Qualified);
+        assert!(!linked.tasm.contains("p__id__N3:"));
+        assert!(!linked.tasm.contains("p__then__2:"));
+        assert_eq!(linked.tasm.matches("call p__id__N2").count(), 2);
+
+        let report = &linked.mono;
+        assert_eq!(report.instances.len(), 3);
+        assert_eq!(report.instances[0].instructions, 5);
+        assert_eq!(
+            report.instances[1].merged_into.as_deref(),
+            Some("p__id__N2")
+        );
+        assert_eq!(report.instance_count(), 2);
+        assert_eq!(report.instruction_count(), 7);
+    }
 }
@@ -20,11 +20,64 @@ pub use triton::TritonLowering;
 pub trait StackLowering {
     /// Convert a sequence of IR operations into assembly text lines.
     fn lower(&self, ops: &[TIROp]) -> Vec<String>;
+
+    /// Where `reveal` and `seal` write their events.
+    fn event_channel(&self) -> EventChannel {
+        EventChannel::Shared
+    }
+}
+
+/// Where `reveal` and `seal` write their events, chosen per target by
+/// `channel` in the `[events]` section of its `target.toml`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EventChannel {
+    /// Public output, interleaved with program results and unframed.
+    #[default]
+    Shared,
+    /// Public output, each event behind a header word holding its kind
+    /// and length (`REVEAL_FRAME` / `SEAL_FRAME` plus the word count).
+    Framed,
+    /// The target's own event stream, apart from public output. A target
+    /// without one frames its events instead.
+    Dedicated,
+}
+
+/// Header of a framed `reveal`: this plus the words that follow.
+pub const REVEAL_FRAME: u64 = 0xffff_fffe_0000_0000;
+
+/// Header of a framed `seal`: this plus the words that follow.
+pub const SEAL_FRAME: u64 = 0xffff_fffe_0001_0000;
+
+impl EventChannel {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "shared" => Some(EventChannel::Shared),
+            "framed" => Some(EventChannel::Framed),
+            "dedicated" => Some(EventChannel::Dedicated),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EventChannel::Shared => "shared",
+            EventChannel::Framed => "framed",
+            EventChannel::Dedicated => "dedicated",
+        }
+    }
+
+    /// The channel `target` selects; `Shared` if it selects none.
+    pub fn for_target(target: &str) -> Self {
+        crate::resolve::TargetSettings::load(target)
+            .and_then(|settings| settings.events.channel)
+            .and_then(|name| Self::from_name(&name))
+            .unwrap_or_default()
+    }
 }
 
 /// Create a stack lowering backend for the given target name.
-pub fn create_stack_lowering(_target: &str) -> Box<dyn StackLowering> {
-    Box::new(TritonLowering::new())
+pub fn create_stack_lowering(target: &str) -> Box<dyn StackLowering> {
+    Box::new(TritonLowering::new().with_event_channel(EventChannel::for_target(target)))
 }
 
 /// Create a speculative stack lowering that can accept neural v2 candidates.
@@ -32,13 +85,13 @@ pub fn create_stack_lowering(_target: &str) -> Box<dyn StackLowering> {
 /// The v2 neural model runs externally (beam search) and injects results
 /// via `inject_neural_candidate`. This replaces the v1 inline MLP approach.
 pub fn create_speculative_lowering(
-    _target: &str,
+    target: &str,
     meta_generation: u64,
     meta_hash: String,
     meta_status: OptimizerStatus,
 ) -> SpeculativeLowering {
     SpeculativeLowering {
-        classical: TritonLowering::new(),
+        classical: TritonLowering::new().with_event_channel(EventChannel::for_target(target)),
         report: std::cell::RefCell::new(OptimizerReport {
             status: meta_status,
             generation: meta_generation,
@@ -129,6 +182,10 @@ impl StackLowering for SpeculativeLowering {
         // externally via inject_neural_candidate after beam search.
         self.classical.lower(ops)
     }
+
+    fn event_channel(&self) -> EventChannel {
+        self.classical.event_channel()
+    }
 }
 
 /// Decode neural output codes to TASM instruction strings.
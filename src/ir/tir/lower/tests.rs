@@ -88,6 +88,28 @@ fn test_lower_loop() {
     assert!(joined.contains("push 1\n    add\n    recurse"));
 }
 
+#[test]
+fn test_lower_loop_is_emitted_after_the_function() {
+    let ops = vec![
+        TIROp::FnStart("spin".into()),
+        TIROp::Push(3),
+        TIROp::Call("loop__1".into()),
+        TIROp::Pop(1),
+        TIROp::Loop {
+            label: "loop__1".into(),
+            body: vec![TIROp::Push(1), TIROp::Pop(1)],
+        },
+        TIROp::Return,
+        TIROp::FnEnd,
+    ];
+    let lowering = TritonLowering::new();
+    let joined = lowering.lower(&ops).join("\n");
+    let call = joined.find("call __loop__1").unwrap();
+    let ret = joined.find("    return").unwrap();
+    let body = joined.find("__loop__1:").unwrap();
+    assert!(call < ret && ret < body, "{}", joined);
+}
+
 #[test]
 fn test_lower_label_formatting() {
     let ops = vec![
@@ -180,6 +202,47 @@ fn test_regression_event_emission() {
     assert!(output.contains("return"), "should return");
 }
 
+#[test]
+fn test_framed_events_carry_a_header() {
+    let ops = vec![
+        TIROp::Reveal {
+            name: "Transfer".to_string(),
+            tag: 2,
+            field_count: 1,
+        },
+        TIROp::Seal {
+            name: "Commit".to_string(),
+            tag: 3,
+            field_count: 9,
+        },
+    ];
+    let shared = TritonLowering::new().lower(&ops).join("\n");
+    assert!(
+        shared.starts_with("    push 2\n    write_io 1"),
+        "{}",
+        shared
+    );
+    let lowering = TritonLowering::new().with_event_channel(EventChannel::Dedicated);
+    assert_eq!(lowering.event_channel(), EventChannel::Framed);
+    let framed = lowering.lower(&ops).join("\n");
+    assert!(
+        framed.starts_with(&format!(
+            "    push {}\n    write_io 1\n    push 2\n    write_io 1\n    write_io 1",
+            REVEAL_FRAME + 2
+        )),
+        "{}",
+        framed
+    );
+    assert!(
+        framed.ends_with(&format!(
+            "    hash\n    push {}\n    write_io 1\n    write_io 5",
+            SEAL_FRAME + 5
+        )),
+        "{}",
+        framed
+    );
+}
+
 #[test]
 fn test_nested_if_else_deferred() {
     let ops = vec![
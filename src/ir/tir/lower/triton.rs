@@ -1,6 +1,6 @@
 //! Triton VM lowering — produces TASM from TIR.
 
-use super::StackLowering;
+use super::{EventChannel, StackLowering, REVEAL_FRAME, SEAL_FRAME};
 use crate::tir::TIROp;
 
 /// A deferred subroutine block collected during lowering.
@@ -9,6 +9,9 @@ struct DeferredBlock {
     ops: Vec<TIROp>,
     /// If true, this is a "then" branch: pop the flag on entry, push 0 on exit.
     clears_flag: bool,
+    /// If true, this is a loop: return once the counter on top reaches 0,
+    /// otherwise decrement it, run the body and `recurse`.
+    loops: bool,
 }
 
 /// Triton VM lowering — produces TASM from IR.
@@ -21,6 +24,7 @@ pub struct TritonLowering {
     deferred: Vec<DeferredBlock>,
     /// Label counter for generating unique deferred block labels.
     label_counter: u32,
+    event_channel: EventChannel,
 }
 
 impl TritonLowering {
@@ -28,6 +32,24 @@ impl TritonLowering {
         Self::default()
     }
 
+    /// Write events to `channel`. Triton VM has a single public output,
+    /// so a dedicated channel is framed on it.
+    pub fn with_event_channel(mut self, channel: EventChannel) -> Self {
+        self.event_channel = match channel {
+            EventChannel::Dedicated => EventChannel::Framed,
+            other => other,
+        };
+        self
+    }
+
+    /// Write the header of a framed event, if events are framed.
+    fn frame_header(&self, base: u64, len: u32, out: &mut Vec<String>) {
+        if self.event_channel == EventChannel::Framed {
+            out.push(format!("    push {}", base + u64::from(len)));
+            out.push("    write_io 1".to_string());
+        }
+    }
+
     fn fresh_label(&mut self, prefix: &str) -> String {
         self.label_counter += 1;
         format!("__{}__{}", prefix, self.label_counter)
@@ -60,11 +82,19 @@ impl TritonLowering {
                 out.push("    mul".to_string());
             }
             TIROp::Eq => out.push("    eq".to_string()),
-            TIROp::Lt => out.push("    lt".to_string()),
+            // `lt`, `div_mod` and `pow` take their first operand from the
+            // top of the stack, where TIR has the second.
+            TIROp::Lt => {
+                out.push("    swap 1".to_string());
+                out.push("    lt".to_string());
+            }
             TIROp::And => out.push("    and".to_string()),
             TIROp::Or => out.push("    or".to_string()),
             TIROp::Xor => out.push("    xor".to_string()),
-            TIROp::DivMod => out.push("    div_mod".to_string()),
+            TIROp::DivMod => {
+                out.push("    swap 1".to_string());
+                out.push("    div_mod".to_string());
+            }
             TIROp::Shl => {
                 // multiply by 2^n: push 2; pow; mul
                 out.push("    push 2".to_string());
@@ -72,17 +102,20 @@ impl TritonLowering {
                 out.push("    mul".to_string());
             }
             TIROp::Shr => {
-                // divide by 2^n: push 2; pow; div_mod; swap 1; pop 1
+                // divide by 2^n: push 2; pow; swap 1; div_mod; pop 1
                 out.push("    push 2".to_string());
                 out.push("    pow".to_string());
-                out.push("    div_mod".to_string());
                 out.push("    swap 1".to_string());
+                out.push("    div_mod".to_string());
                 out.push("    pop 1".to_string());
             }
             TIROp::Invert => out.push("    invert".to_string()),
             TIROp::Split => out.push("    split".to_string()),
             TIROp::Log2 => out.push("    log_2_floor".to_string()),
-            TIROp::Pow => out.push("    pow".to_string()),
+            TIROp::Pow => {
+                out.push("    swap 1".to_string());
+                out.push("    pow".to_string());
+            }
             TIROp::PopCount => out.push("    pop_count".to_string()),
 
             // ── Recursion — extension field & FRI ──
@@ -127,6 +160,7 @@ impl TritonLowering {
                 tag, field_count, ..
             } => {
                 // Triton: write tag then each field to public output.
+                self.frame_header(REVEAL_FRAME, field_count + 1, out);
                 out.push(format!("    push {}", tag));
                 out.push("    write_io 1".to_string());
                 for _ in 0..*field_count {
@@ -143,6 +177,7 @@ impl TritonLowering {
                 }
                 out.push(format!("    push {}", tag));
                 out.push("    hash".to_string());
+                self.frame_header(SEAL_FRAME, 5, out);
                 out.push("    write_io 5".to_string());
             }
             TIROp::RamRead { width } => {
@@ -187,11 +222,13 @@ impl TritonLowering {
                     label: then_label,
                     ops: then_body.clone(),
                     clears_flag: true,
+                    loops: false,
                 });
                 self.deferred.push(DeferredBlock {
                     label: else_label,
                     ops: else_body.clone(),
                     clears_flag: false,
+                    loops: false,
                 });
             }
             TIROp::IfOnly { then_body } => {
@@ -204,6 +241,7 @@ impl TritonLowering {
                     label: then_label,
                     ops: then_body.clone(),
                     clears_flag: false,
+                    loops: false,
                 });
             }
             TIROp::Loop { label, body } => {
@@ -212,21 +250,12 @@ impl TritonLowering {
                 } else {
                     self.format_label(label)
                 };
-                out.push(format!("{}:", formatted_label));
-                out.push("    dup 0".to_string());
-                out.push("    push 0".to_string());
-                out.push("    eq".to_string());
-                out.push("    skiz".to_string());
-                out.push("    return".to_string());
-                out.push("    push -1".to_string());
-                out.push("    add".to_string());
-
-                for body_op in body {
-                    self.lower_op(body_op, out);
-                }
-
-                out.push("    recurse".to_string());
-                out.push(String::new());
+                self.deferred.push(DeferredBlock {
+                    label: formatted_label,
+                    ops: body.clone(),
+                    clears_flag: false,
+                    loops: true,
+                });
             }
 
             // ── Program structure ──
@@ -275,6 +304,15 @@ impl TritonLowering {
             for block in blocks {
                 out.push(format!("{}:", block.label));
 
+                if block.loops {
+                    out.push("    dup 0".to_string());
+                    out.push("    push 0".to_string());
+                    out.push("    eq".to_string());
+                    out.push("    skiz".to_string());
+                    out.push("    return".to_string());
+                    out.push("    push -1".to_string());
+                    out.push("    add".to_string());
+                }
                 if block.clears_flag {
                     out.push("    pop 1".to_string());
                 }
@@ -286,7 +324,11 @@ impl TritonLowering {
                 if block.clears_flag {
                     out.push("    push 0".to_string());
                 }
-                out.push("    return".to_string());
+                if block.loops {
+                    out.push("    recurse".to_string());
+                } else {
+                    out.push("    return".to_string());
+                }
                 out.push(String::new());
             }
         }
@@ -295,12 +337,17 @@ impl TritonLowering {
 
 impl StackLowering for TritonLowering {
     fn lower(&self, ops: &[TIROp]) -> Vec<String> {
-        let mut lowerer = TritonLowering::new();
+        let mut lowerer = TritonLowering::new().with_event_channel(self.event_channel);
         let mut out = Vec::new();
 
         for op in ops {
             lowerer.lower_op(op, &mut out);
         }
+        lowerer.flush_deferred(&mut out);
         out
     }
+
+    fn event_channel(&self) -> EventChannel {
+        self.event_channel
+    }
 }
@@ -10,8 +10,11 @@ pub(crate) mod linker;
 pub mod lower;
 pub mod neural;
 pub(crate) mod optimize;
+pub mod ram;
 pub mod stack;
 
+pub use linker::{LabelScheme, MonoEntry, MonoReport};
+
 use std::fmt;
 
 // ─── IR Operations ────────────────────────────────────────────────
@@ -33,7 +36,7 @@ use std::fmt;
 ///   Extension field (2), Folding (2), Verification (1) = 5
 ///
 /// Total: 11 + 31 + 7 + 5 = 54 variants
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TIROp {
     // ═══════════════════════════════════════════════════════════════
     // Tier 0 — Structure (11)
@@ -0,0 +1,240 @@
+/// Constant pooling: repeated wide constants get one slot per distinct
+/// value in the constant pool (see `ram::const_pool_range`) and are
+/// reloaded with a single `read_mem`.
+use super::TIROp;
+use crate::target::TerrainConfig;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Widest block one `read_mem`/`write_mem` moves.
+const MAX_WIDTH: usize = 5;
+
+/// Processor rows of a reload: `Push(addr), ReadMem(w), Pop(1)`, with
+/// `read_mem` at two rows (see `cost::scorer`).
+const RELOAD_ROWS: usize = 4;
+
+/// Processor rows of a store after its words are pushed:
+/// `Push(addr), WriteMem(w), Pop(1)`.
+const STORE_ROWS: usize = 4;
+
+/// Constant pool slots for a whole project.
+///
+/// Slots are keyed by value, so every module that pools the same constant
+/// reloads it from the same address, and no slot is shared by two
+/// values. One pool must span every module that is linked together: a
+/// per-module pool would hand the same addresses to different constants,
+/// and a call into another module would overwrite the caller's.
+pub(crate) struct ConstPool {
+    next: u64,
+    end: u64,
+    slots: BTreeMap<Vec<u64>, u64>,
+}
+
+impl ConstPool {
+    /// The pool region of a target. Empty when the target reserves no
+    /// compiler RAM, in which case nothing is pooled.
+    pub(crate) fn new(tc: &TerrainConfig) -> Self {
+        Self::in_range(crate::tir::ram::const_pool_range(tc))
+    }
+
+    pub(crate) fn in_range(range: Range<u64>) -> Self {
+        Self {
+            next: range.start,
+            end: range.end,
+            slots: BTreeMap::new(),
+        }
+    }
+
+    /// Pool every function (`FnStart` through `FnEnd`) in `ops`.
+    pub(crate) fn apply(&mut self, ops: Vec<TIROp>) -> Vec<TIROp> {
+        let mut out = Vec::with_capacity(ops.len());
+        let mut func: Option<Vec<TIROp>> = None;
+        for op in ops {
+            if let Some(body) = func.as_mut() {
+                let end = matches!(op, TIROp::FnEnd);
+                body.push(op);
+                if end {
+                    let body = func.take().unwrap_or_default();
+                    out.extend(pool_constants(body, self));
+                }
+            } else if matches!(op, TIROp::FnStart(_)) {
+                func = Some(vec![op]);
+            } else {
+                out.push(op);
+            }
+        }
+        out.extend(func.unwrap_or_default());
+        out
+    }
+
+    /// The slot of `words`, claiming one if the pool has room.
+    fn slot(&mut self, words: &[u64]) -> Option<u64> {
+        if let Some(&addr) = self.slots.get(words) {
+            return Some(addr);
+        }
+        let w = words.len() as u64;
+        if self.next + w > self.end {
+            return None;
+        }
+        let addr = self.next;
+        self.next += w;
+        self.slots.insert(words.to_vec(), addr);
+        Some(addr)
+    }
+}
+
+/// Pool the repeated wide constants of one function.
+///
+/// `ops` is one function, `FnStart` through `FnEnd`. A constant is a block
+/// of consecutive `Push` ops; maximal runs are cut into blocks of up to
+/// five words from their first push. Pushes that feed a memory op are
+/// addresses, not constants, and end a run.
+///
+/// A block of width `w` that occurs `k` times (nested bodies included) is
+/// pooled when `k * w > (w + 4) + 4 * k`, i.e. when one store plus `k`
+/// reloads take fewer processor rows than `k` pushes of every word — ten
+/// or more occurrences of a Digest. Each reload adds `w` RAM-table rows,
+/// so the threshold only pays off where the processor table dominates.
+/// Pooled blocks are stored right after `FnStart`, which dominates every
+/// use, and each occurrence becomes `Push(addr + w - 1), ReadMem(w), Pop(1)`.
+pub(crate) fn pool_constants(ops: Vec<TIROp>, pool: &mut ConstPool) -> Vec<TIROp> {
+    let mut counts: Vec<(Vec<u64>, usize)> = Vec::new();
+    count_blocks(&ops, &mut counts);
+
+    let mut pooled: BTreeMap<Vec<u64>, u64> = BTreeMap::new();
+    let mut stores = Vec::new();
+    for (words, k) in counts {
+        let w = words.len();
+        if k * w <= w + STORE_ROWS + RELOAD_ROWS * k {
+            continue;
+        }
+        let Some(addr) = pool.slot(&words) else {
+            continue;
+        };
+        stores.extend(words.iter().map(|&v| TIROp::Push(v)));
+        stores.push(TIROp::Push(addr));
+        stores.push(TIROp::WriteMem(w as u32));
+        stores.push(TIROp::Pop(1));
+        pooled.insert(words, addr);
+    }
+    if pooled.is_empty() {
+        return ops;
+    }
+
+    let mut out = rewrite(ops, &pooled);
+    let at = usize::from(matches!(out.first(), Some(TIROp::FnStart(_))));
+    out.splice(at..at, stores);
+    out
+}
+
+/// Whether `ops[i]` pushes a constant rather than a memory address.
+fn is_constant(ops: &[TIROp], i: usize) -> bool {
+    if !matches!(ops[i], TIROp::Push(_)) {
+        return false;
+    }
+    !matches!(
+        (ops.get(i + 1), ops.get(i + 2)),
+        (Some(TIROp::ReadMem(_) | TIROp::WriteMem(_)), _)
+            | (Some(TIROp::Swap(_)), Some(TIROp::WriteMem(_)))
+    )
+}
+
+/// Constant blocks at one nesting level, in order.
+fn blocks(ops: &[TIROp]) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < ops.len() {
+        let start = i;
+        while i < ops.len() && is_constant(ops, i) {
+            i += 1;
+        }
+        let mut s = start;
+        while s < i {
+            let e = (s + MAX_WIDTH).min(i);
+            out.push(s..e);
+            s = e;
+        }
+        if i == start {
+            i += 1;
+        }
+    }
+    out
+}
+
+fn block_words(ops: &[TIROp]) -> Vec<u64> {
+    ops.iter()
+        .map(|op| match op {
+            TIROp::Push(v) => *v,
+            _ => unreachable!("constant blocks hold only pushes"),
+        })
+        .collect()
+}
+
+/// Count block occurrences in first-seen order, descending into nested bodies.
+fn count_blocks(ops: &[TIROp], counts: &mut Vec<(Vec<u64>, usize)>) {
+    for range in blocks(ops) {
+        let words = block_words(&ops[range]);
+        match counts.iter_mut().find(|(w, _)| *w == words) {
+            Some((_, k)) => *k += 1,
+            None => counts.push((words, 1)),
+        }
+    }
+    for op in ops {
+        for body in nested_bodies(op) {
+            count_blocks(body, counts);
+        }
+    }
+}
+
+/// Replace every occurrence of a pooled block with a reload.
+fn rewrite(ops: Vec<TIROp>, pool: &BTreeMap<Vec<u64>, u64>) -> Vec<TIROp> {
+    let ranges = blocks(&ops);
+    let mut out = Vec::with_capacity(ops.len());
+    let mut next_range = ranges.iter().peekable();
+    let mut i = 0;
+    while i < ops.len() {
+        if let Some(range) = next_range.next_if(|r| r.start == i) {
+            let words = block_words(&ops[range.clone()]);
+            if let Some(&addr) = pool.get(&words) {
+                out.push(TIROp::Push(addr + words.len() as u64 - 1));
+                out.push(TIROp::ReadMem(words.len() as u32));
+                out.push(TIROp::Pop(1));
+            } else {
+                out.extend_from_slice(&ops[range.clone()]);
+            }
+            i = range.end;
+            continue;
+        }
+        let mut op = ops[i].clone();
+        for body in nested_bodies_mut(&mut op) {
+            *body = rewrite(std::mem::take(body), pool);
+        }
+        out.push(op);
+        i += 1;
+    }
+    out
+}
+
+fn nested_bodies(op: &TIROp) -> Vec<&Vec<TIROp>> {
+    match op {
+        TIROp::IfElse {
+            then_body,
+            else_body,
+        } => vec![then_body, else_body],
+        TIROp::IfOnly { then_body } => vec![then_body],
+        TIROp::Loop { body, .. } | TIROp::ProofBlock { body, .. } => vec![body],
+        _ => Vec::new(),
+    }
+}
+
+fn nested_bodies_mut(op: &mut TIROp) -> Vec<&mut Vec<TIROp>> {
+    match op {
+        TIROp::IfElse {
+            then_body,
+            else_body,
+        } => vec![then_body, else_body],
+        TIROp::IfOnly { then_body } => vec![then_body],
+        TIROp::Loop { body, .. } | TIROp::ProofBlock { body, .. } => vec![body],
+        _ => Vec::new(),
+    }
+}
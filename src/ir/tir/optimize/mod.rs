@@ -4,6 +4,7 @@
 /// Applied between TIR building and lowering to target assembly.
 use super::TIROp;
 
+pub(crate) mod constants;
 pub(crate) mod spill;
 #[cfg(test)]
 mod tests;
@@ -228,6 +229,9 @@ fn collapse_swap_pop_chains(ops: Vec<TIROp>) -> Vec<TIROp> {
 /// **Constant-depth chains**: N consecutive `swap 1; pop 1` pairs each remove
 /// one element below the top. Net effect: keep top, discard N elements below.
 /// Collapsed to `swap min(N,15); pop min(N,15)` in chunks (swap max is 15).
+/// Chains of `swap D; pop 1` with D > 1 move a wide value down (assignment,
+/// multi-element return cleanup) and have no cheaper equivalent here, so
+/// they are left as they are.
 ///
 /// **Decreasing-depth chains**: `swap D; pop 1; swap D-1; pop 1; ...` chains
 /// where each pair brings a deeper dead element to the top. Collapsed to
@@ -246,12 +250,12 @@ fn collapse_epilogue_cleanup(ops: Vec<TIROp>) -> Vec<TIROp> {
                 let mut j = i + 2;
                 while j + 1 < ops.len() {
                     if let (TIROp::Swap(dd), TIROp::Pop(1)) = (&ops[j], &ops[j + 1]) {
-                        if *dd == first_d {
+                        if *dd == first_d && is_constant_depth {
                             // Same depth -- constant-depth chain continues.
                             count += 1;
                             j += 2;
-                        } else if first_d == 1 {
-                            // Constant-depth with D=1 is strict.
+                        } else if first_d == 1 || (is_constant_depth && count > 1) {
+                            // A chain keeps the shape of its first two pairs.
                             break;
                         } else if *dd + count == first_d || *dd < first_d {
                             // Decreasing-depth chain.
@@ -283,26 +287,7 @@ fn collapse_epilogue_cleanup(ops: Vec<TIROp>) -> Vec<TIROp> {
                             remaining -= chunk;
                         }
                     } else if is_constant_depth {
-                        // Constant-depth with D > 1: removing `count`
-                        // dead elements from below a D-wide return value.
-                        // When D + count - 1 <= 15, bring all dead elements
-                        // to the top with decreasing swaps, then batch pop.
-                        let total_depth = first_d + count - 1;
-                        if total_depth <= 15 {
-                            for offset in 0..count {
-                                out.push(TIROp::Swap(total_depth - offset));
-                            }
-                        } else {
-                            for _ in 0..count {
-                                out.push(TIROp::Swap(first_d));
-                            }
-                        }
-                        let mut remaining = count;
-                        while remaining > 0 {
-                            let batch = remaining.min(5);
-                            out.push(TIROp::Pop(batch));
-                            remaining -= batch;
-                        }
+                        out.extend_from_slice(&ops[i..j]);
                     } else {
                         // Decreasing-depth chain.
                         out.push(TIROp::Swap(first_d));
@@ -193,42 +193,64 @@ fn collapse_large_constant_depth_chain() {
 }
 
 #[test]
-fn collapse_constant_depth_swap_k_pop1_chain() {
-    // 5x swap 3; pop 1 (width-3 return, 5 dead locals) ->
-    // swap 7; swap 6; swap 5; swap 4; swap 3; pop 5 (batch pop)
+fn constant_depth_swap_k_pop1_chain_is_kept() {
+    // 3x swap 3; pop 1 replaces the three words under a width-3 value
+    // (assignment, or a width-3 return over 3 dead locals). No batched
+    // swap/pop sequence keeps the value in order, so the chain stays.
+    let mut ops = Vec::new();
+    for _ in 0..3 {
+        ops.push(TIROp::Swap(3));
+        ops.push(TIROp::Pop(1));
+    }
+    ops.push(TIROp::Return);
+    let result = optimize(ops.clone());
+    assert_eq!(result, ops);
+}
+
+/// Run a straight line of `swap`/`pop` ops over `stack` (top last).
+fn run_swaps_and_pops(ops: &[TIROp], mut stack: Vec<u32>) -> Vec<u32> {
+    for op in ops {
+        match op {
+            TIROp::Swap(d) => {
+                let top = stack.len() - 1;
+                stack.swap(top, top - *d as usize);
+            }
+            TIROp::Pop(n) => stack.truncate(stack.len() - *n as usize),
+            TIROp::Return => {}
+            other => panic!("unexpected op {:?}", other),
+        }
+    }
+    stack
+}
+
+#[test]
+fn constant_depth_swap_k_pop1_chain_keeps_stack_contents() {
+    // 5x swap 3; pop 1 over 0..10 (9 on top) leaves [0, 1, 8, 9, 7]. The
+    // former batched rewrite `swap 7; swap 6; swap 5; swap 4; swap 3;
+    // pop 5` left [0, 1, 9, 2, 3], dropping live words and keeping dead
+    // ones.
     let mut ops = Vec::new();
     for _ in 0..5 {
         ops.push(TIROp::Swap(3));
         ops.push(TIROp::Pop(1));
     }
     ops.push(TIROp::Return);
-    let result = optimize(ops);
-    // D=3, count=5, total_depth = 3+5-1 = 7 (<=15)
-    assert!(matches!(result[0], TIROp::Swap(7)));
-    assert!(matches!(result[1], TIROp::Swap(6)));
-    assert!(matches!(result[2], TIROp::Swap(5)));
-    assert!(matches!(result[3], TIROp::Swap(4)));
-    assert!(matches!(result[4], TIROp::Swap(3)));
-    assert!(matches!(result[5], TIROp::Pop(5)));
-    assert!(matches!(result[6], TIROp::Return));
-    assert_eq!(result.len(), 7); // was 11
+    let stack: Vec<u32> = (0..10).collect();
+    let expected = run_swaps_and_pops(&ops, stack.clone());
+    assert_eq!(run_swaps_and_pops(&optimize(ops), stack), expected);
 }
 
 #[test]
-fn collapse_constant_depth_swap_k_large_chain() {
-    // 4x swap 5; pop 1 -- total_depth = 5+4-1 = 8 (<=15)
+fn constant_depth_swap_k_large_chain_keeps_stack_contents() {
+    // 4x swap 5; pop 1, formerly batched as swap 8..5; pop 4.
     let mut ops = Vec::new();
     for _ in 0..4 {
         ops.push(TIROp::Swap(5));
         ops.push(TIROp::Pop(1));
     }
-    let result = optimize(ops);
-    assert!(matches!(result[0], TIROp::Swap(8)));
-    assert!(matches!(result[1], TIROp::Swap(7)));
-    assert!(matches!(result[2], TIROp::Swap(6)));
-    assert!(matches!(result[3], TIROp::Swap(5)));
-    assert!(matches!(result[4], TIROp::Pop(4)));
-    assert_eq!(result.len(), 5); // was 8
+    let stack: Vec<u32> = (0..12).collect();
+    let expected = run_swaps_and_pops(&ops, stack.clone());
+    assert_eq!(run_swaps_and_pops(&optimize(ops), stack), expected);
 }
 
 #[test]
@@ -250,3 +272,157 @@ fn optimize_nested_bodies() {
         panic!("expected IfElse");
     }
 }
+
+/// `main` writing the digest-width constant `[1, 2, 3, 4, 5]` `times` times.
+fn repeated_digest_fn(times: usize) -> Vec<TIROp> {
+    let mut ops = vec![TIROp::FnStart("main".into())];
+    for _ in 0..times {
+        ops.extend((1..=5).map(TIROp::Push));
+        ops.push(TIROp::WriteIo(5));
+    }
+    ops.push(TIROp::Return);
+    ops.push(TIROp::FnEnd);
+    ops
+}
+
+fn pool_at(base: u64) -> constants::ConstPool {
+    constants::ConstPool::in_range(base..base + 1024)
+}
+
+/// Words written to the pool by the stores at the top of `ops`.
+fn stored_words(ops: &[TIROp]) -> u32 {
+    ops.iter()
+        .map(|op| match op {
+            TIROp::WriteMem(w) => *w,
+            _ => 0,
+        })
+        .sum()
+}
+
+#[test]
+fn pool_digest_constant_repeated_ten_times() {
+    let base = 1 << 29;
+    let mut pool = pool_at(base);
+    let result = constants::pool_constants(repeated_digest_fn(10), &mut pool);
+    assert_eq!(stored_words(&result), 5);
+    let mut expected = vec![TIROp::FnStart("main".into())];
+    expected.extend((1..=5).map(TIROp::Push));
+    expected.extend([TIROp::Push(base), TIROp::WriteMem(5), TIROp::Pop(1)]);
+    for _ in 0..10 {
+        expected.extend([TIROp::Push(base + 4), TIROp::ReadMem(5), TIROp::Pop(1)]);
+        expected.push(TIROp::WriteIo(5));
+    }
+    expected.extend([TIROp::Return, TIROp::FnEnd]);
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn keep_constants_below_pooling_threshold() {
+    let ops = repeated_digest_fn(9);
+    let mut pool = pool_at(1 << 29);
+    let result = constants::pool_constants(ops.clone(), &mut pool);
+    assert_eq!(result, ops);
+}
+
+#[test]
+fn pool_constants_in_nested_bodies() {
+    let digest = || (1..=5).map(TIROp::Push).collect::<Vec<_>>();
+    let mut ops = vec![TIROp::FnStart("main".into())];
+    for _ in 0..8 {
+        ops.extend(digest());
+        ops.push(TIROp::WriteIo(5));
+    }
+    let mut then_body = digest();
+    then_body.push(TIROp::WriteIo(5));
+    let mut body = digest();
+    body.push(TIROp::WriteIo(5));
+    ops.push(TIROp::IfOnly { then_body });
+    ops.push(TIROp::Loop {
+        label: "l".into(),
+        body,
+    });
+    ops.extend([TIROp::Return, TIROp::FnEnd]);
+
+    let mut pool = pool_at(1 << 29);
+    let result = constants::pool_constants(ops, &mut pool);
+    assert_eq!(stored_words(&result), 5);
+    let reloads = |ops: &[TIROp]| ops.iter().filter(|op| **op == TIROp::ReadMem(5)).count();
+    assert_eq!(reloads(&result), 8);
+    let nested: usize = result
+        .iter()
+        .map(|op| match op {
+            TIROp::IfOnly { then_body } => reloads(then_body),
+            TIROp::Loop { body, .. } => reloads(body),
+            _ => 0,
+        })
+        .sum();
+    assert_eq!(nested, 2);
+}
+
+#[test]
+fn pool_constants_leaves_memory_addresses_alone() {
+    let mut ops = vec![TIROp::FnStart("main".into())];
+    for _ in 0..10 {
+        ops.extend((1..=4).map(TIROp::Push));
+        ops.push(TIROp::Push(100));
+        ops.push(TIROp::ReadMem(1));
+    }
+    ops.extend([TIROp::Return, TIROp::FnEnd]);
+    let mut pool = pool_at(1 << 29);
+    let result = constants::pool_constants(ops.clone(), &mut pool);
+    assert_eq!(result, ops, "address pushes must not join a constant block");
+}
+
+#[test]
+fn pool_slots_are_shared_by_value_across_functions() {
+    let base = 1 << 29;
+    let mut pool = pool_at(base);
+    let mut other = vec![TIROp::FnStart("other".into())];
+    for _ in 0..10 {
+        other.extend((6..=10).map(TIROp::Push));
+        other.push(TIROp::WriteIo(5));
+    }
+    other.extend([TIROp::Return, TIROp::FnEnd]);
+    let mut ops = repeated_digest_fn(10);
+    ops.extend(other);
+    ops.extend(repeated_digest_fn(10));
+
+    let result = pool.apply(ops);
+    let stores: Vec<u64> = result
+        .windows(2)
+        .filter_map(|pair| match pair {
+            [TIROp::Push(addr), TIROp::WriteMem(5)] => Some(*addr),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(stores, vec![base, base + 5, base]);
+}
+
+#[test]
+fn full_pool_leaves_constants_inline() {
+    let ops = repeated_digest_fn(10);
+    let mut pool = constants::ConstPool::in_range(0..4);
+    assert_eq!(pool.apply(ops.clone()), ops);
+}
+
+#[test]
+fn pooled_constants_run_in_fewer_cycles_with_same_output() {
+    use crate::runtime::debug::{Debugger, Stop};
+    use crate::tir::lower::create_stack_lowering;
+
+    let run = |body: Vec<TIROp>| {
+        let mut ops = vec![TIROp::Entry("main".into())];
+        ops.extend(body);
+        let tasm = create_stack_lowering("triton").lower(&ops).join("\n");
+        let mut dbg = Debugger::new(&tasm, &crate::runtime::ProgramInput::default());
+        assert_eq!(dbg.resume(), Stop::Halted);
+        (dbg.output().to_vec(), dbg.cycles())
+    };
+    let ops = repeated_digest_fn(12);
+    let pooled = constants::pool_constants(ops.clone(), &mut pool_at(1 << 29));
+    let (plain_out, plain_cycles) = run(ops);
+    let (pooled_out, pooled_cycles) = run(pooled);
+    assert_eq!(pooled_out, plain_out);
+    // 12 * 5 pushes vs. one 8-op store plus 12 three-op reloads.
+    assert_eq!(plain_cycles - pooled_cycles, 60 - (8 + 12 * 3));
+}
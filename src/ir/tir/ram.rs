@@ -0,0 +1,670 @@
+//! RAM layout: the address regions a compiled program may touch.
+//!
+//! The compiler owns two bump-allocated regions — temporaries for
+//! runtime-indexed arrays (from half of `spill_ram_base`) and stack
+//! spills (from `spill_ram_base`) — and the constant pool between them.
+//! User `sec ram` declarations claim fixed slots,
+//! and tables (`generate!` and array constants) sit in data segments just
+//! below the temporaries.
+//! `RamLayout` reserves all of them and rejects any overlap at compile
+//! time, so a prover-initialized slot can never be clobbered by a spill.
+//! Accesses with a compile-time address must land inside a reserved
+//! region (`check_static_accesses`).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::Range;
+
+use crate::ast::{
+    Block, Declaration, Expr, FieldPattern, File, Item, Literal, MatchPattern, Pattern, Place, Stmt,
+};
+use crate::diagnostic::Diagnostic;
+use crate::span::{Span, Spanned};
+use crate::target::TerrainConfig;
+
+use super::builder::layout::{format_type_name, resolve_type_width};
+
+/// First address of the compiler temporaries region: half the spill base,
+/// so temporaries and spills each get a disjoint half of the upper RAM.
+pub fn temp_ram_base(tc: &TerrainConfig) -> u64 {
+    tc.spill_ram_base / 2
+}
+
+/// Words reserved for pooled constants, at the top of the temporaries half.
+const CONST_POOL_WORDS: u64 = 1024;
+
+/// `[start, end)` of the constant pool (see `optimize::constants`): the
+/// words just below `spill_ram_base`. Empty when the target reserves no
+/// compiler RAM.
+pub fn const_pool_range(tc: &TerrainConfig) -> Range<u64> {
+    let end = tc.spill_ram_base;
+    let start = end.saturating_sub(CONST_POOL_WORDS).max(temp_ram_base(tc));
+    start..end
+}
+
+/// Who owns a RAM region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RamRegionKind {
+    /// Prover-initialized slots from a `sec ram` declaration.
+    SecRam,
+    /// `generate!` tables, written by the program before `main` runs.
+    Data,
+    /// Compiler temporaries for runtime array indexing.
+    Temp,
+    /// Repeated wide constants, one slot per distinct value project-wide.
+    Pool,
+    /// Compiler stack spills.
+    Spill,
+}
+
+impl fmt::Display for RamRegionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RamRegionKind::SecRam => write!(f, "sec ram"),
+            RamRegionKind::Data => write!(f, "data"),
+            RamRegionKind::Temp => write!(f, "temp"),
+            RamRegionKind::Pool => write!(f, "pool"),
+            RamRegionKind::Spill => write!(f, "spill"),
+        }
+    }
+}
+
+/// A reserved address range `[start, end)`. `end == None` means the
+/// region grows without a fixed bound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RamRegion {
+    pub kind: RamRegionKind,
+    /// Human-readable owner, e.g. `main: ram[17]: Digest`.
+    pub label: String,
+    pub start: u64,
+    pub end: Option<u64>,
+}
+
+impl RamRegion {
+    pub fn overlaps(&self, other: &RamRegion) -> bool {
+        let below = |r: &RamRegion, start: u64| matches!(r.end, Some(end) if end <= start);
+        !below(self, other.start) && !below(other, self.start)
+    }
+
+    fn contains(&self, addr: u64) -> bool {
+        addr >= self.start && self.end.is_none_or(|end| addr < end)
+    }
+
+    fn format_range(&self) -> String {
+        match self.end {
+            Some(end) => format!("[{}, {})", self.start, end),
+            None => format!("[{}, ..)", self.start),
+        }
+    }
+}
+
+/// A table (a `generate!` or array constant) laid out in RAM: element `i`
+/// lives at `start + i`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataSegment {
+    /// Qualified constant name, e.g. `crypto.SBOX`.
+    pub name: String,
+    pub start: u64,
+    pub values: Vec<u64>,
+}
+
+/// Lay out `tables` back to back in name order, ending at
+/// `temp_ram_base`. Every module of a project sees the same tables, so
+/// each computes the same addresses.
+pub fn data_segments(tables: &BTreeMap<String, Vec<u64>>, tc: &TerrainConfig) -> Vec<DataSegment> {
+    let total: u64 = tables.values().map(|v| v.len() as u64).sum();
+    let mut start = temp_ram_base(tc).saturating_sub(total);
+    tables
+        .iter()
+        .map(|(name, values)| {
+            let segment = DataSegment {
+                name: name.clone(),
+                start,
+                values: values.clone(),
+            };
+            start += values.len() as u64;
+            segment
+        })
+        .collect()
+}
+
+/// All RAM regions reserved for one program.
+#[derive(Clone, Debug, Default)]
+pub struct RamLayout {
+    regions: Vec<RamRegion>,
+    /// Contents of the `Data` regions, in address order.
+    data: Vec<DataSegment>,
+}
+
+impl RamLayout {
+    /// Reserve the compiler-owned regions of a target. Temporaries run
+    /// from `temp_ram_base` up to the constant pool, which ends at
+    /// `spill_ram_base`; spills run from `spill_ram_base` upward. A
+    /// `spill_ram_base` of 0 means the target reserves no compiler RAM.
+    pub fn new(tc: &TerrainConfig) -> Self {
+        let mut layout = Self::default();
+        if tc.spill_ram_base == 0 {
+            return layout;
+        }
+        let pool = const_pool_range(tc);
+        layout.regions.push(RamRegion {
+            kind: RamRegionKind::Temp,
+            label: "runtime array temporaries".to_string(),
+            start: temp_ram_base(tc),
+            end: Some(pool.start),
+        });
+        layout.regions.push(RamRegion {
+            kind: RamRegionKind::Pool,
+            label: "constant pool".to_string(),
+            start: pool.start,
+            end: Some(pool.end),
+        });
+        layout.regions.push(RamRegion {
+            kind: RamRegionKind::Spill,
+            label: "stack spills".to_string(),
+            start: tc.spill_ram_base,
+            end: None,
+        });
+        layout
+    }
+
+    pub fn regions(&self) -> &[RamRegion] {
+        &self.regions
+    }
+
+    /// The data segments reserved by `reserve_data`: the RAM image a
+    /// packaged artifact ships for provers.
+    pub fn data(&self) -> &[DataSegment] {
+        &self.data
+    }
+
+    /// Reserve the data segments of `generate!` tables. Do this before
+    /// `reserve_sec_ram`, so a slot inside a table is the one reported.
+    pub fn reserve_data(&mut self, segments: &[DataSegment]) {
+        for seg in segments.iter().filter(|s| !s.values.is_empty()) {
+            let region = RamRegion {
+                kind: RamRegionKind::Data,
+                label: format!("generate! table {}", seg.name),
+                start: seg.start,
+                end: Some(seg.start + seg.values.len() as u64),
+            };
+            // Segments end where the temporaries start.
+            if self.reserve(region).is_ok() {
+                self.data.push(seg.clone());
+            }
+        }
+    }
+
+    /// Reserve every `sec ram` slot declared in `file`. Slots that
+    /// overlap an existing region are reported and not reserved.
+    pub fn reserve_sec_ram(&mut self, file: &File, tc: &TerrainConfig) -> Vec<Diagnostic> {
+        let mut errors = Vec::new();
+        for decl in &file.declarations {
+            let Declaration::SecRam(entries) = decl else {
+                continue;
+            };
+            for (addr, ty) in entries {
+                let width = resolve_type_width(&ty.node, tc).max(1) as u64;
+                let region = RamRegion {
+                    kind: RamRegionKind::SecRam,
+                    label: format!(
+                        "{}: ram[{}]: {}",
+                        file.name.node,
+                        addr,
+                        format_type_name(&ty.node)
+                    ),
+                    start: *addr,
+                    end: Some(addr.saturating_add(width)),
+                };
+                match self.reserve(region) {
+                    Ok(()) => {}
+                    Err(clash) => errors.push(overlap_error(&clash, addr, width, ty.span)),
+                }
+            }
+        }
+        errors
+    }
+
+    /// Add a region, or return the first existing region it overlaps.
+    pub fn reserve(&mut self, region: RamRegion) -> Result<(), RamRegion> {
+        if let Some(clash) = self.regions.iter().find(|r| r.overlaps(&region)) {
+            return Err(clash.clone());
+        }
+        self.regions.push(region);
+        Ok(())
+    }
+
+    /// Check every RAM access in `file` whose address is known at compile
+    /// time — an integer literal or a literal module constant — against
+    /// the reserved regions. Each accessed word must belong to some region;
+    /// runtime addresses are not checked.
+    pub fn check_static_accesses(
+        &self,
+        file: &File,
+        intrinsics: &BTreeMap<String, String>,
+        cfg_flags: &BTreeSet<String>,
+    ) -> Vec<Diagnostic> {
+        let active = |cfg: &Option<Spanned<String>>| {
+            cfg.as_ref()
+                .is_none_or(|flag| cfg_flags.contains(&flag.node))
+        };
+        let mut checker = AccessChecker {
+            layout: self,
+            intrinsics,
+            consts: BTreeMap::new(),
+            locals: BTreeSet::new(),
+            errors: Vec::new(),
+        };
+        for item in &file.items {
+            if let Item::Const(c) = &item.node {
+                if let (true, Expr::Literal(Literal::Integer(v))) = (active(&c.cfg), &c.value.node)
+                {
+                    checker.consts.insert(c.name.node.clone(), *v);
+                }
+            }
+        }
+        for item in &file.items {
+            let Item::Fn(func) = &item.node else {
+                continue;
+            };
+            let Some(body) = func.body.as_ref().filter(|_| active(&func.cfg)) else {
+                continue;
+            };
+            checker.locals = func.params.iter().map(|p| p.name.node.clone()).collect();
+            checker.block(&body.node);
+        }
+        checker.errors
+    }
+
+    /// The words of `[addr, addr + width)` that no region covers.
+    fn uncovered(&self, addr: u64, width: u64) -> Vec<u64> {
+        (addr..addr.saturating_add(width))
+            .filter(|a| !self.regions.iter().any(|r| r.contains(*a)))
+            .collect()
+    }
+
+    /// Memory map sorted by start address, one region per line.
+    pub fn format_map(&self) -> String {
+        let mut regions: Vec<&RamRegion> = self.regions.iter().collect();
+        regions.sort_by_key(|r| r.start);
+        let mut out = String::from("RAM map:\n");
+        if regions.is_empty() {
+            out.push_str("  (no reserved regions)\n");
+        }
+        for r in regions {
+            out.push_str(&format!(
+                "  {:<28} {:<8} {}\n",
+                r.format_range(),
+                r.kind.to_string(),
+                r.label
+            ));
+        }
+        out
+    }
+}
+
+fn overlap_error(clash: &RamRegion, addr: &u64, width: u64, span: Span) -> Diagnostic {
+    let msg = format!(
+        "sec ram slot [{}, {}) overlaps {} region {}",
+        addr,
+        addr.saturating_add(width),
+        clash.kind,
+        clash.format_range()
+    );
+    let diag = Diagnostic::error(msg, span).with_note(format!("reserved by {}", clash.label));
+    match clash.kind {
+        RamRegionKind::SecRam => {
+            diag.with_help("move one of the slots to a free address".to_string())
+        }
+        RamRegionKind::Data => diag.with_help(
+            "move the slot below the `generate!` tables, which end at half of stack.spill_ram_base"
+                .to_string(),
+        ),
+        RamRegionKind::Temp | RamRegionKind::Pool | RamRegionKind::Spill => diag.with_help(
+            "move the slot below half of stack.spill_ram_base, or raise it in the target config"
+                .to_string(),
+        ),
+    }
+}
+
+/// Walks function bodies for RAM intrinsics with a static address.
+struct AccessChecker<'a> {
+    layout: &'a RamLayout,
+    intrinsics: &'a BTreeMap<String, String>,
+    /// Module constants with a literal integer value.
+    consts: BTreeMap<String, u64>,
+    /// Parameters and bindings of the current function; they shadow constants.
+    locals: BTreeSet<String>,
+    errors: Vec<Diagnostic>,
+}
+
+impl AccessChecker<'_> {
+    fn block(&mut self, block: &Block) {
+        for stmt in &block.stmts {
+            self.stmt(&stmt.node);
+        }
+        if let Some(tail) = &block.tail_expr {
+            self.expr(&tail.node);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Let { pattern, init, .. } => {
+                self.expr(&init.node);
+                match pattern {
+                    Pattern::Name(name) => {
+                        self.locals.insert(name.node.clone());
+                    }
+                    Pattern::Tuple(names) => {
+                        self.locals.extend(names.iter().map(|n| n.node.clone()));
+                    }
+                }
+            }
+            Stmt::Assign { place, value } => {
+                self.place(&place.node);
+                self.expr(&value.node);
+            }
+            Stmt::TupleAssign { value, .. } => self.expr(&value.node),
+            Stmt::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                self.expr(&cond.node);
+                self.block(&then_block.node);
+                if let Some(else_block) = else_block {
+                    self.block(&else_block.node);
+                }
+            }
+            Stmt::For {
+                var,
+                start,
+                end,
+                body,
+                ..
+            } => {
+                self.expr(&start.node);
+                self.expr(&end.node);
+                self.locals.insert(var.node.clone());
+                self.block(&body.node);
+            }
+            Stmt::Expr(expr) | Stmt::Return(Some(expr)) => self.expr(&expr.node),
+            Stmt::Return(None) | Stmt::Asm { .. } => {}
+            Stmt::Reveal { fields, .. } | Stmt::Seal { fields, .. } => {
+                for (_, value) in fields {
+                    self.expr(&value.node);
+                }
+            }
+            Stmt::Match { expr, arms } => {
+                self.expr(&expr.node);
+                for arm in arms {
+                    if let MatchPattern::Struct { fields, .. } = &arm.pattern.node {
+                        for field in fields {
+                            if let FieldPattern::Binding(name) = &field.pattern.node {
+                                self.locals.insert(name.clone());
+                            }
+                        }
+                    }
+                    if let MatchPattern::Some(binding) = &arm.pattern.node {
+                        self.locals.insert(binding.node.clone());
+                    }
+                    self.block(&arm.body.node);
+                }
+            }
+        }
+    }
+
+    fn place(&mut self, place: &Place) {
+        match place {
+            Place::Var(_) => {}
+            Place::FieldAccess(inner, _) => self.place(&inner.node),
+            Place::Index(inner, index) => {
+                self.place(&inner.node);
+                self.expr(&index.node);
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Call { path, args, .. } => {
+                let name = path.node.as_dotted();
+                let resolved = self.intrinsics.get(&name).or_else(|| {
+                    name.rsplit('.')
+                        .next()
+                        .and_then(|short| self.intrinsics.get(short))
+                });
+                let access = match resolved.map_or(name.as_str(), String::as_str) {
+                    "ram_read" => Some(("read", 1)),
+                    "ram_write" => Some(("write", 1)),
+                    "ram_read_block" => Some(("read", 5)),
+                    "ram_write_block" => Some(("write", 5)),
+                    _ => None,
+                };
+                if let (Some((verb, width)), Some(addr)) = (access, args.first()) {
+                    if let Some(value) = self.static_address(&addr.node) {
+                        self.check(verb, value, width, addr.span);
+                    }
+                }
+                for arg in args {
+                    self.expr(&arg.node);
+                }
+            }
+            Expr::BinOp { lhs, rhs, .. } => {
+                self.expr(&lhs.node);
+                self.expr(&rhs.node);
+            }
+            Expr::FieldAccess { expr, .. } => self.expr(&expr.node),
+            Expr::Index { expr, index } => {
+                self.expr(&expr.node);
+                self.expr(&index.node);
+            }
+            Expr::StructInit { fields, .. } => {
+                for (_, value) in fields {
+                    self.expr(&value.node);
+                }
+            }
+            Expr::ArrayInit(elems) | Expr::Tuple(elems) => {
+                for elem in elems {
+                    self.expr(&elem.node);
+                }
+            }
+            Expr::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                self.expr(&cond.node);
+                self.block(&then_block.node);
+                self.block(&else_block.node);
+            }
+            Expr::Literal(_) | Expr::Var(_) => {}
+        }
+    }
+
+    fn static_address(&self, expr: &Expr) -> Option<u64> {
+        match expr {
+            Expr::Literal(Literal::Integer(v)) => Some(*v),
+            Expr::Var(name) if !self.locals.contains(name) => self.consts.get(name).copied(),
+            _ => None,
+        }
+    }
+
+    fn check(&mut self, verb: &str, addr: u64, width: u64, span: Span) {
+        let uncovered = self.layout.uncovered(addr, width);
+        let Some(first) = uncovered.first() else {
+            return;
+        };
+        let access = if width == 1 {
+            format!("ram[{}]", addr)
+        } else {
+            format!("ram[{}, {})", addr, addr.saturating_add(width))
+        };
+        let ty = if width == 1 { "Field" } else { "Digest" };
+        self.errors.push(
+            Diagnostic::error(
+                format!(
+                    "RAM {} of {} touches undeclared address {}",
+                    verb, access, first
+                ),
+                span,
+            )
+            .with_note(
+                "static RAM accesses must stay inside a `sec ram` slot or compiler-owned RAM"
+                    .to_string(),
+            )
+            .with_help(format!(
+                "declare the slot: `sec ram: {{ {}: {} }}`",
+                addr, ty
+            )),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sec_ram_file(decl: &str) -> File {
+        let source = format!("program test\n\n{}\n\nfn main() {{\n}}\n", decl);
+        crate::parse_source(&source, "test.tri").expect("parse")
+    }
+
+    #[test]
+    fn test_triton_layout_reserves_temp_below_spill() {
+        let tc = TerrainConfig::triton();
+        let layout = RamLayout::new(&tc);
+        let kinds: Vec<RamRegionKind> = layout.regions().iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RamRegionKind::Temp,
+                RamRegionKind::Pool,
+                RamRegionKind::Spill
+            ]
+        );
+        assert_eq!(layout.regions()[0].end, Some(const_pool_range(&tc).start));
+        assert_eq!(layout.regions()[1].end, Some(tc.spill_ram_base));
+    }
+
+    #[test]
+    fn test_sec_ram_below_temp_region_is_accepted() {
+        let tc = TerrainConfig::triton();
+        let mut layout = RamLayout::new(&tc);
+        let file = sec_ram_file("sec ram: { 17: Field, 42: Digest }");
+        assert!(layout.reserve_sec_ram(&file, &tc).is_empty());
+        assert_eq!(layout.regions().len(), 5);
+    }
+
+    #[test]
+    fn test_sec_ram_in_spill_region_is_rejected() {
+        let tc = TerrainConfig::triton();
+        let mut layout = RamLayout::new(&tc);
+        let file = sec_ram_file("sec ram: { 1073741824: Field }");
+        let errors = layout.reserve_sec_ram(&file, &tc);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("overlaps spill region"));
+    }
+
+    #[test]
+    fn test_wide_sec_ram_slot_straddling_temp_base_is_rejected() {
+        let tc = TerrainConfig::triton();
+        let mut layout = RamLayout::new(&tc);
+        let decl = format!("sec ram: {{ {}: Digest }}", temp_ram_base(&tc) - 2);
+        let errors = layout.reserve_sec_ram(&sec_ram_file(&decl), &tc);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("overlaps temp region"));
+    }
+
+    #[test]
+    fn test_overlapping_sec_ram_slots_are_rejected() {
+        let tc = TerrainConfig::triton();
+        let mut layout = RamLayout::new(&tc);
+        let file = sec_ram_file("sec ram: { 10: Digest, 12: Field }");
+        let errors = layout.reserve_sec_ram(&file, &tc);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .message
+            .contains("overlaps sec ram region [10, 15)"));
+    }
+
+    #[test]
+    fn test_zero_spill_base_reserves_no_compiler_ram() {
+        let mut tc = TerrainConfig::triton();
+        tc.spill_ram_base = 0;
+        let mut layout = RamLayout::new(&tc);
+        let file = sec_ram_file("sec ram: { 0: Field }");
+        assert!(layout.reserve_sec_ram(&file, &tc).is_empty());
+    }
+
+    #[test]
+    fn test_ram_map_lists_regions_by_address() {
+        let tc = TerrainConfig::triton();
+        let mut layout = RamLayout::new(&tc);
+        layout.reserve_sec_ram(&sec_ram_file("sec ram: { 42: Digest }"), &tc);
+        let map = layout.format_map();
+        let sec = map.find("[42, 47)").expect("sec ram line");
+        let temp = map.find("[536870912, 1073740800)").expect("temp line");
+        let pool = map.find("[1073740800, 1073741824)").expect("pool line");
+        let spill = map.find("[1073741824, ..)").expect("spill line");
+        assert!(sec < temp && temp < pool && pool < spill);
+        assert!(map.contains("test: ram[42]: Digest"));
+    }
+
+    fn access_errors(source: &str) -> Vec<Diagnostic> {
+        let tc = TerrainConfig::triton();
+        let file = crate::parse_source(source, "test.tri").expect("parse");
+        let mut layout = RamLayout::new(&tc);
+        assert!(layout.reserve_sec_ram(&file, &tc).is_empty());
+        layout.check_static_accesses(&file, &BTreeMap::new(), &BTreeSet::new())
+    }
+
+    #[test]
+    fn test_access_inside_sec_ram_slot_is_accepted() {
+        let errors = access_errors(
+            "program test\nsec ram: { 17: Field, 42: Digest }\nfn main() {\n    pub_write(ram_read(17))\n    ram_write_block(42, divine5())\n}\n",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_access_to_undeclared_address_is_rejected() {
+        let errors = access_errors(
+            "program test\nsec ram: { 17: Field }\nfn main() {\n    pub_write(ram_read(18))\n}\n",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .message
+            .contains("RAM read of ram[18] touches undeclared address 18"));
+    }
+
+    #[test]
+    fn test_block_access_past_slot_end_is_rejected() {
+        let errors = access_errors(
+            "program test\nsec ram: { 40: Digest }\nfn main() {\n    let d: Digest = ram_read_block(42)\n}\n",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("ram[42, 47)"));
+        assert!(errors[0].message.contains("undeclared address 45"));
+    }
+
+    #[test]
+    fn test_const_address_is_checked_unless_shadowed() {
+        let errors = access_errors(
+            "program test\nconst SLOT: Field = 9\nfn main() {\n    ram_write(SLOT, 1)\n}\nfn other(SLOT: Field) {\n    ram_write(SLOT, 1)\n}\n",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("ram[9]"));
+    }
+
+    #[test]
+    fn test_compiler_regions_and_runtime_addresses_are_accepted() {
+        let tc = TerrainConfig::triton();
+        let source = format!(
+            "program test\nfn main() {{\n    pub_write(ram_read({}))\n    pub_write(ram_read(pub_read()))\n}}\n",
+            tc.spill_ram_base + 3
+        );
+        assert!(access_errors(&source).is_empty());
+    }
+}
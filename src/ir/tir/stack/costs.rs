@@ -0,0 +1,94 @@
+//! Per-backend operand stack costs.
+//!
+//! The stack manager decides when to spill by comparing the cost of
+//! reaching a deep value with `dup`/`swap` against a RAM round trip.
+//! Values beyond the window are unreachable by a single `dup`/`swap`, so
+//! reaching them costs a spill plus a reload.
+
+/// Operand stack access costs for one backend, in processor cycles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct StackCosts {
+    /// Elements reachable by a single `dup`/`swap` (the operand window).
+    pub(crate) window: u32,
+    /// Cycles for `dup d`, indexed by `d < window`.
+    dup: Vec<u64>,
+    /// Cycles for `swap d`, indexed by `d < window` (`swap 0` is unused).
+    swap: Vec<u64>,
+    /// Cycles to spill one element to RAM.
+    pub(crate) spill: u64,
+    /// Cycles to reload one element from RAM.
+    pub(crate) reload: u64,
+}
+
+impl StackCosts {
+    /// Costs for a target by name; unknown targets use the Triton table
+    /// with their own window.
+    pub(crate) fn for_target(name: &str, window: u32) -> Self {
+        match name {
+            "miden" => Self::miden(window),
+            _ => Self::triton(window),
+        }
+    }
+
+    /// Triton VM: every `dup`/`swap` in the window is one cycle. A spill
+    /// is `push addr; swap 1; write_mem 1; pop 1`, a reload is
+    /// `push addr; read_mem 1; pop 1`.
+    pub(crate) fn triton(window: u32) -> Self {
+        Self {
+            window,
+            dup: vec![1; window as usize],
+            swap: vec![1; window as usize],
+            spill: 4,
+            reload: 3,
+        }
+    }
+
+    /// Miden VM: `dup.n` takes 3 cycles for even n >= 8 and 1 otherwise;
+    /// `swap.n` beyond the top pair is lowered through `movup`/`movdn`
+    /// and grows with depth. Memory ops take the address as an immediate,
+    /// so a spill is `movup.n; mem_store.a` and a reload `mem_load.a`.
+    pub(crate) fn miden(window: u32) -> Self {
+        let dup = (0..window)
+            .map(|d| if d >= 8 && d % 2 == 0 { 3 } else { 1 })
+            .collect();
+        let swap = (0..window)
+            .map(|d| match d {
+                0 | 1 => 1,
+                2..=7 => 3,
+                _ => 6,
+            })
+            .collect();
+        Self {
+            window,
+            dup,
+            swap,
+            spill: 4,
+            reload: 2,
+        }
+    }
+
+    /// Deepest operand a single `dup`/`swap` can address.
+    pub(crate) fn max_reach(&self) -> u32 {
+        self.window.saturating_sub(1)
+    }
+
+    /// Cycles to read the element at `depth` and write a new value back.
+    /// Beyond the window the element must round-trip through RAM.
+    pub(crate) fn access_cost(&self, depth: u32) -> u64 {
+        match (self.dup.get(depth as usize), self.swap.get(depth as usize)) {
+            (Some(dup), Some(swap)) => dup + swap,
+            _ => self.spill + self.reload,
+        }
+    }
+
+    /// Stack depth (in elements) at which the manager starts spilling:
+    /// the first depth whose in-window access costs more than a RAM
+    /// round trip, capped by the window.
+    pub(crate) fn spill_threshold(&self) -> u32 {
+        let round_trip = self.spill + self.reload;
+        (0..self.window)
+            .find(|&d| self.access_cost(d) > round_trip)
+            .unwrap_or(self.window)
+            .max(1)
+    }
+}
@@ -1,3 +1,9 @@
+mod costs;
+
+use std::collections::BTreeMap;
+
+pub(crate) use costs::StackCosts;
+
 /// LRU-based stack manager for stack-machine VMs.
 ///
 /// When live variables exceed the maximum stack depth, the manager automatically
@@ -40,6 +46,10 @@ pub(crate) struct ManagedVar {
     pub(crate) width: u32,
     /// For array entries: the width of a single element.
     pub(crate) elem_width: Option<u32>,
+    /// For tuple entries: the width of each component, first deepest.
+    pub(crate) parts: Option<Vec<u32>>,
+    /// For option entries: the flag, when it is known at compile time.
+    pub(crate) option_flag: Option<bool>,
     /// Where this variable currently lives.
     pub(crate) location: VarLocation,
     /// Monotonic access counter for LRU eviction.
@@ -66,10 +76,21 @@ pub(crate) struct StackManager {
     access_counter: u64,
     /// Instructions generated by spill/reload operations.
     pub(crate) side_effects: Vec<String>,
-    /// Maximum operand stack depth before spilling (from TerrainConfig).
+    /// Operand stack depth at which spilling starts (from `costs`).
     max_stack_depth: u32,
+    /// Backend dup/swap/spill costs and operand window.
+    costs: StackCosts,
     /// Target-specific instruction formatter for spill/reload.
     formatter: SpillFormatter,
+    /// Whether to record invariant violations (`--validate-codegen`).
+    validate: bool,
+    /// Invariant violations recorded since the last `take_violations`.
+    violations: Vec<String>,
+    /// Whether to record the next spill (`#[no_spill]` functions).
+    watch_spills: bool,
+    /// The first spill seen while watching: the spilled variable and a
+    /// report of the live variables.
+    spill_report: Option<(String, String)>,
 }
 
 impl Default for StackManager {
@@ -91,6 +112,20 @@ impl StackManager {
         max_stack_depth: u32,
         spill_ram_base: u64,
         formatter: SpillFormatter,
+    ) -> Self {
+        Self::with_costs(
+            StackCosts::triton(max_stack_depth),
+            spill_ram_base,
+            formatter,
+        )
+    }
+
+    /// Stack manager whose spill threshold and reach follow a backend's
+    /// stack costs.
+    pub(crate) fn with_costs(
+        costs: StackCosts,
+        spill_ram_base: u64,
+        formatter: SpillFormatter,
     ) -> Self {
         Self {
             on_stack: Vec::new(),
@@ -98,8 +133,60 @@ impl StackManager {
             next_spill_addr: spill_ram_base,
             access_counter: 0,
             side_effects: Vec::new(),
-            max_stack_depth,
+            max_stack_depth: costs.spill_threshold(),
+            costs,
             formatter,
+            validate: false,
+            violations: Vec::new(),
+            watch_spills: false,
+            spill_report: None,
+        }
+    }
+
+    /// Check the stack invariants while building: no access below the
+    /// operand window without a spill, and balanced blocks.
+    pub(crate) fn set_validation(&mut self, on: bool) {
+        self.validate = on;
+    }
+
+    /// Record the next spill, with the variables live at that point, for
+    /// `take_spill_report`. Used for `#[no_spill]` functions.
+    pub(crate) fn watch_spills(&mut self, on: bool) {
+        self.watch_spills = on;
+    }
+
+    /// Take the spill recorded while watching, if any.
+    pub(crate) fn take_spill_report(&mut self) -> Option<(String, String)> {
+        self.spill_report.take()
+    }
+
+    /// Take the invariant violations recorded so far.
+    pub(crate) fn take_violations(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.violations)
+    }
+
+    /// Record a violation unless the tracked stack is `expected`
+    /// elements deep at the end of `block`.
+    pub(crate) fn check_balanced(&mut self, block: &str, expected: u32) {
+        let depth = self.stack_depth();
+        if self.validate && depth != expected {
+            self.violations.push(format!(
+                "{} leaves {} element(s) on the stack, expected {}",
+                block, depth, expected
+            ));
+        }
+    }
+
+    /// Record a violation if variable `name`, found `depth` elements
+    /// down, is out of reach of dup and swap.
+    fn check_reach(&mut self, name: &str, depth: u32) {
+        if self.validate && depth > self.max_reach() {
+            self.violations.push(format!(
+                "variable '{}' is {} deep, below the {}-element operand window, and was not spilled",
+                name,
+                depth,
+                self.max_reach() + 1
+            ));
         }
     }
 
@@ -111,6 +198,11 @@ impl StackManager {
         // Don't reset next_spill_addr — keep allocating forward
     }
 
+    /// Deepest operand a single dup/swap can address on this backend.
+    pub(crate) fn max_reach(&self) -> u32 {
+        self.costs.max_reach()
+    }
+
     /// Total width of values currently on the operand stack.
     pub(crate) fn stack_depth(&self) -> u32 {
         self.on_stack.iter().map(|v| v.width).sum()
@@ -126,43 +218,76 @@ impl StackManager {
     }
 
     /// Number of entries on the operand stack.
+    #[cfg(test)]
     pub(crate) fn stack_len(&self) -> usize {
         self.on_stack.len()
     }
 
-    /// Push an anonymous temporary onto the stack.
-    /// If the stack would exceed 16 elements, spill the LRU variable first.
+    /// Number of entries tracked, on the stack or spilled to RAM.
+    /// Unlike `stack_len`, spilling and reloading leave it unchanged.
+    pub(crate) fn entry_count(&self) -> usize {
+        self.on_stack.len() + self.spilled.len()
+    }
+
+    /// Record an anonymous temporary the caller has already pushed.
+    /// If the stack would exceed the spill threshold, spill the LRU variable
+    /// from underneath it first.
     pub(crate) fn push_temp(&mut self, width: u32) {
         if width == 0 {
             return;
         }
-        self.ensure_space(width);
+        self.make_room_under(width);
         let ts = self.tick();
         self.on_stack.push(ManagedVar {
             name: None,
             width,
             elem_width: None,
+            parts: None,
+            option_flag: None,
             location: VarLocation::Stack,
             last_access: ts,
         });
     }
 
-    /// Push a named variable onto the stack.
+    /// Record a named variable the caller has already pushed.
     pub(crate) fn push_named(&mut self, name: &str, width: u32) {
         if width == 0 {
             return;
         }
-        self.ensure_space(width);
+        self.make_room_under(width);
         let ts = self.tick();
         self.on_stack.push(ManagedVar {
             name: Some(name.to_string()),
             width,
             elem_width: None,
+            parts: None,
+            option_flag: None,
             location: VarLocation::Stack,
             last_access: ts,
         });
     }
 
+    /// Record named values already on the stack (bottom to top), then
+    /// spill down to the threshold once all of them are tracked.
+    pub(crate) fn push_named_all(&mut self, vars: &[(&str, u32)]) {
+        for &(name, width) in vars {
+            if width == 0 {
+                continue;
+            }
+            let ts = self.tick();
+            self.on_stack.push(ManagedVar {
+                name: Some(name.to_string()),
+                width,
+                elem_width: None,
+                parts: None,
+                option_flag: None,
+                location: VarLocation::Stack,
+                last_access: ts,
+            });
+        }
+        self.ensure_space(0);
+    }
+
     /// Pop the top entry from the stack model.
     pub(crate) fn pop(&mut self) -> Option<ManagedVar> {
         self.on_stack.pop()
@@ -178,6 +303,24 @@ impl StackManager {
         self.on_stack.last_mut()
     }
 
+    /// The compile-time flag of an option variable, if known.
+    pub(crate) fn option_flag(&self, name: &str) -> Option<bool> {
+        self.on_stack
+            .iter()
+            .rev()
+            .chain(&self.spilled)
+            .find(|v| v.name.as_deref() == Some(name))
+            .and_then(|v| v.option_flag)
+    }
+
+    /// Whether a variable of this name is live, on the stack or spilled.
+    pub(crate) fn has_var(&self, name: &str) -> bool {
+        self.on_stack
+            .iter()
+            .chain(&self.spilled)
+            .any(|v| v.name.as_deref() == Some(name))
+    }
+
     /// Find the depth (in field elements from stack top) of a named variable.
     /// If the variable is spilled, reloads it first and returns the new depth.
     /// Returns the depth from top of stack.
@@ -188,6 +331,7 @@ impl StackManager {
         for entry in self.on_stack.iter_mut().rev() {
             if entry.name.as_deref() == Some(name) {
                 entry.last_access = ts;
+                self.check_reach(name, depth);
                 return depth;
             }
             depth += entry.width;
@@ -200,8 +344,7 @@ impl StackManager {
             .position(|v| v.name.as_deref() == Some(name))
         {
             let var = self.spilled.remove(idx);
-            self.reload_var(var);
-            return 0; // reloaded to top of stack
+            return self.reload_var(var);
         }
 
         // Not found — return 0 (caller handles error)
@@ -215,7 +358,9 @@ impl StackManager {
         for entry in self.on_stack.iter_mut().rev() {
             if entry.name.as_deref() == Some(name) {
                 entry.last_access = ts;
-                return Some((depth, entry.width));
+                let width = entry.width;
+                self.check_reach(name, depth);
+                return Some((depth, width));
             }
             depth += entry.width;
         }
@@ -227,8 +372,8 @@ impl StackManager {
         {
             let var = self.spilled.remove(idx);
             let width = var.width;
-            self.reload_var(var);
-            return Some((0, width));
+            let depth = self.reload_var(var);
+            return Some((depth, width));
         }
         None
     }
@@ -241,8 +386,9 @@ impl StackManager {
         for entry in self.on_stack.iter_mut().rev() {
             if entry.name.as_deref() == Some(name) {
                 entry.last_access = ts;
-                let ew = entry.elem_width.unwrap_or(1);
-                return Some((depth, entry.width, ew));
+                let (width, ew) = (entry.width, entry.elem_width.unwrap_or(1));
+                self.check_reach(name, depth);
+                return Some((depth, width, ew));
             }
             depth += entry.width;
         }
@@ -255,8 +401,8 @@ impl StackManager {
             let var = self.spilled.remove(idx);
             let width = var.width;
             let ew = var.elem_width.unwrap_or(1);
-            self.reload_var(var);
-            return Some((0, width, ew));
+            let depth = self.reload_var(var);
+            return Some((depth, width, ew));
         }
         None
     }
@@ -274,7 +420,7 @@ impl StackManager {
             if !has_named {
                 break;
             }
-            if !self.spill_lru() {
+            if !self.spill_lru(0) {
                 break;
             }
         }
@@ -291,6 +437,119 @@ impl StackManager {
         self.spilled = state.1;
     }
 
+    /// Emit code that turns the current stack into the `target` layout
+    /// captured by `save_state`. Values the target keeps on the stack are
+    /// restored in order, values it keeps in RAM are written back to their
+    /// target addresses, and anything else (block locals) is dropped.
+    /// Branches end with this so the code after them sees one layout;
+    /// the tracked stack is left at `target` too.
+    pub(crate) fn reconcile(&mut self, target: &(Vec<ManagedVar>, Vec<ManagedVar>)) {
+        let (t_stack, t_spilled) = target;
+        let keep = self
+            .on_stack
+            .iter()
+            .zip(t_stack)
+            .take_while(|(cur, want)| cur.name == want.name && cur.width == want.width)
+            .count();
+        // Anonymous values above the shared prefix cannot be recovered.
+        if t_stack[keep..].iter().any(|v| v.name.is_none()) {
+            if self.validate {
+                self.violations.push(
+                    "branch moves unnamed values it cannot restore, so the stack \
+                     after it differs by path"
+                        .to_string(),
+                );
+            }
+            return;
+        }
+        let ram_addr = |vars: &[ManagedVar], name: &str| {
+            vars.iter()
+                .rev()
+                .find_map(|v| match (&v.name, &v.location) {
+                    (Some(n), VarLocation::Ram(addr)) if n == name => Some(*addr),
+                    _ => None,
+                })
+        };
+        let needed = |name: &str| {
+            t_stack[keep..]
+                .iter()
+                .chain(t_spilled)
+                .any(|v| v.name.as_deref() == Some(name))
+        };
+
+        // Current RAM address of every value the target needs.
+        let mut addrs: BTreeMap<String, u64> = BTreeMap::new();
+        for v in &self.spilled {
+            if let (Some(name), VarLocation::Ram(addr)) = (&v.name, &v.location) {
+                addrs.insert(name.clone(), *addr);
+            }
+        }
+
+        // Unwind everything above the prefix, top first.
+        while self.on_stack.len() > keep {
+            let var = self.on_stack.pop().expect("len > keep");
+            match var.name.filter(|n| needed(n)) {
+                Some(name) => {
+                    let addr = ram_addr(t_spilled, &name).unwrap_or_else(|| {
+                        let addr = self.next_spill_addr;
+                        self.next_spill_addr += var.width as u64;
+                        addr
+                    });
+                    for i in (0..var.width).rev() {
+                        self.side_effects
+                            .push((self.formatter.fmt_push)(addr + i as u64));
+                        self.side_effects
+                            .push(self.formatter.fmt_write_mem1.clone());
+                        self.side_effects.push(self.formatter.fmt_pop1.clone());
+                    }
+                    addrs.insert(name, addr);
+                }
+                None => {
+                    for _ in 0..var.width {
+                        self.side_effects.push(self.formatter.fmt_pop1.clone());
+                    }
+                }
+            }
+        }
+
+        // Values the target keeps in RAM must sit at the target address.
+        for want in t_spilled {
+            let (Some(name), VarLocation::Ram(to)) = (&want.name, &want.location) else {
+                continue;
+            };
+            let Some(&from) = addrs.get(name) else {
+                continue;
+            };
+            if from == *to {
+                continue;
+            }
+            for i in 0..want.width as u64 {
+                self.side_effects.push((self.formatter.fmt_push)(from + i));
+                self.side_effects.push(self.formatter.fmt_read_mem1.clone());
+                self.side_effects.push(self.formatter.fmt_pop1.clone());
+                self.side_effects.push((self.formatter.fmt_push)(to + i));
+                self.side_effects
+                    .push(self.formatter.fmt_write_mem1.clone());
+                self.side_effects.push(self.formatter.fmt_pop1.clone());
+            }
+        }
+
+        // Reload the target's stack values above the prefix, bottom first.
+        for want in &t_stack[keep..] {
+            let name = want.name.as_deref().expect("checked named above");
+            let Some(&addr) = addrs.get(name) else {
+                continue;
+            };
+            for i in 0..want.width as u64 {
+                self.side_effects.push((self.formatter.fmt_push)(addr + i));
+                self.side_effects.push(self.formatter.fmt_read_mem1.clone());
+                self.side_effects.push(self.formatter.fmt_pop1.clone());
+            }
+        }
+        self.on_stack = t_stack.clone();
+        self.spilled = t_spilled.clone();
+    }
+
     // --- Internal ---
 
     fn tick(&mut self) -> u64 {
@@ -302,16 +561,54 @@ impl StackManager {
     /// Spills the LRU named variable if necessary.
     pub(crate) fn ensure_space(&mut self, width: u32) {
         while self.stack_depth() + width > self.max_stack_depth {
-            if !self.spill_lru() {
+            if !self.spill_lru(0) {
                 break; // no more named variables to spill
             }
         }
     }
 
-    /// Spill the least-recently-used named variable to RAM.
+    /// Like `ensure_space`, for `width` elements already pushed on top of
+    /// the tracked stack: spills must reach underneath them.
+    fn make_room_under(&mut self, width: u32) {
+        while self.stack_depth() + width > self.max_stack_depth {
+            if !self.spill_lru(width) {
+                break;
+            }
+        }
+    }
+
+    /// Describe the stack at a spill: the live variables, topmost first,
+    /// and the width of the temporaries.
+    fn live_report(&self, above: u32) -> String {
+        let live: Vec<String> = self
+            .on_stack
+            .iter()
+            .rev()
+            .filter_map(|e| {
+                e.name
+                    .as_ref()
+                    .map(|name| format!("{} ({})", name, e.width))
+            })
+            .collect();
+        let temps: u32 = self
+            .on_stack
+            .iter()
+            .filter(|e| e.name.is_none())
+            .map(|e| e.width)
+            .sum::<u32>()
+            + above;
+        format!(
+            "live when the {}-element stack filled up: {}, and {} element(s) of temporaries",
+            self.max_stack_depth,
+            live.join(", "),
+            temps
+        )
+    }
+
+    /// Spill the least-recently-used named variable to RAM. `above` is
+    /// the width of untracked values sitting on top of the tracked stack.
     /// Returns true if a variable was spilled.
-    fn spill_lru(&mut self) -> bool {
-        // Find the named variable with the lowest last_access that isn't a temp
+    fn spill_lru(&mut self, above: u32) -> bool {
         let mut best_idx = None;
         let mut best_access = u64::MAX;
 
@@ -323,34 +620,56 @@ impl StackManager {
         }
 
         if let Some(idx) = best_idx {
+            if self.watch_spills {
+                self.watch_spills = false;
+                let name = self.on_stack[idx].name.clone().unwrap_or_default();
+                self.spill_report = Some((name, self.live_report(above)));
+            }
             let mut var = self.on_stack.remove(idx);
             let addr = self.next_spill_addr;
             self.next_spill_addr += var.width as u64;
 
-            // Compute the stack depth of this variable from top AFTER removal
-            // We need to emit instructions to move it from its current stack position to RAM
-            // The variable was at index `idx` in on_stack (0 = bottom).
-            // After removal, we need to calculate where it was on the real stack.
-
-            // Depth from top of the var (before removal): sum of widths of entries above it
-            let depth_from_top: u32 = self.on_stack[idx..].iter().map(|e| e.width).sum();
-
-            // For each element of the variable (width elements), spill to RAM
-            for i in 0..var.width {
-                let elem_depth = depth_from_top + (var.width - 1 - i);
-                let ram_addr = addr + i as u64;
-                // Bring element to top, write to RAM
-                if elem_depth > 0 && elem_depth <= self.max_stack_depth - 1 {
-                    self.side_effects
-                        .push((self.formatter.fmt_swap)(elem_depth));
+            // Depth from top of the var's top-most element, after removal:
+            // the sum of widths of entries above it.
+            let mut depth_from_top: u32 =
+                above + self.on_stack[idx..].iter().map(|e| e.width).sum::<u32>();
+
+            // A variable below the swap window is brought into reach by
+            // parking the elements above it in RAM, top first.
+            let parked = depth_from_top.saturating_sub(self.max_reach());
+            let park_addr = self.next_spill_addr;
+            self.next_spill_addr += parked as u64;
+            for j in 0..parked as u64 {
+                self.side_effects
+                    .push((self.formatter.fmt_push)(park_addr + j));
+                self.side_effects
+                    .push(self.formatter.fmt_write_mem1.clone());
+                self.side_effects.push(self.formatter.fmt_pop1.clone());
+            }
+            depth_from_top -= parked;
+
+            // Element i of the var sits at depth `depth_from_top + width-1-i`.
+            // Move the top-most remaining element up (keeping everything
+            // above it in order), then store it; the next element rises
+            // into the same depth.
+            for i in (0..var.width).rev() {
+                for d in 1..=depth_from_top {
+                    self.side_effects.push((self.formatter.fmt_swap)(d));
                 }
-                self.side_effects.push((self.formatter.fmt_push)(ram_addr));
-                self.side_effects.push((self.formatter.fmt_swap)(1));
+                self.side_effects
+                    .push((self.formatter.fmt_push)(addr + i as u64));
                 self.side_effects
                     .push(self.formatter.fmt_write_mem1.clone());
                 self.side_effects.push(self.formatter.fmt_pop1.clone());
             }
 
+            for j in (0..parked as u64).rev() {
+                self.side_effects
+                    .push((self.formatter.fmt_push)(park_addr + j));
+                self.side_effects.push(self.formatter.fmt_read_mem1.clone());
+                self.side_effects.push(self.formatter.fmt_pop1.clone());
+            }
+
             var.location = VarLocation::Ram(addr);
             self.spilled.push(var);
             true
@@ -359,24 +678,47 @@ impl StackManager {
         }
     }
 
-    /// Reload a spilled variable from RAM onto the top of the stack.
-    fn reload_var(&mut self, mut var: ManagedVar) {
-        if let VarLocation::Ram(addr) = var.location {
-            // Make room if needed
-            self.ensure_space(var.width);
+    /// Reload a spilled variable from RAM. The variable goes underneath
+    /// the anonymous temporaries on top of the stack, so pending
+    /// expression operands stay on top. Returns its depth from the top.
+    fn reload_var(&mut self, mut var: ManagedVar) -> u32 {
+        let VarLocation::Ram(addr) = var.location else {
+            return 0;
+        };
+        // Make room if needed
+        self.ensure_space(var.width);
+
+        let temps = self
+            .on_stack
+            .iter()
+            .rev()
+            .take_while(|e| e.name.is_none())
+            .count();
+        let mut temp_width: u32 = self.on_stack[self.on_stack.len() - temps..]
+            .iter()
+            .map(|e| e.width)
+            .sum();
+        let mut insert_at = self.on_stack.len() - temps;
+        if temp_width > self.max_reach() {
+            temp_width = 0;
+            insert_at = self.on_stack.len();
+        }
 
-            // Read each element from RAM
-            for i in 0..var.width {
-                let ram_addr = addr + i as u64;
-                self.side_effects.push((self.formatter.fmt_push)(ram_addr));
-                self.side_effects.push(self.formatter.fmt_read_mem1.clone());
-                self.side_effects.push(self.formatter.fmt_pop1.clone());
+        // Read each element from RAM and sink it below the temporaries.
+        for i in 0..var.width {
+            let ram_addr = addr + i as u64;
+            self.side_effects.push((self.formatter.fmt_push)(ram_addr));
+            self.side_effects.push(self.formatter.fmt_read_mem1.clone());
+            self.side_effects.push(self.formatter.fmt_pop1.clone());
+            for d in (1..=temp_width).rev() {
+                self.side_effects.push((self.formatter.fmt_swap)(d));
             }
-
-            var.location = VarLocation::Stack;
-            var.last_access = self.tick();
-            self.on_stack.push(var);
         }
+
+        var.location = VarLocation::Stack;
+        var.last_access = self.tick();
+        self.on_stack.insert(insert_at, var);
+        temp_width
     }
 }
 
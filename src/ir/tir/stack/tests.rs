@@ -119,3 +119,189 @@ fn test_spill_all_named_empty() {
     assert!(effects.is_empty(), "no named vars → no spill");
     assert_eq!(sm.stack_len(), 2);
 }
+
+#[test]
+fn test_triton_spills_at_full_window() {
+    let costs = StackCosts::triton(16);
+    assert_eq!(costs.spill_threshold(), 16);
+    assert_eq!(costs.max_reach(), 15);
+}
+
+#[test]
+fn test_access_beyond_window_costs_ram_round_trip() {
+    let costs = StackCosts::triton(16);
+    assert_eq!(costs.access_cost(15), 2);
+    assert_eq!(costs.access_cost(16), costs.spill + costs.reload);
+}
+
+#[test]
+fn test_miden_spills_before_expensive_deep_swaps() {
+    let costs = StackCosts::miden(16);
+    let threshold = costs.spill_threshold();
+    assert!(threshold < 16, "deep Miden swaps cost more than a spill");
+    assert!(costs.access_cost(threshold) > costs.spill + costs.reload);
+    assert!((0..threshold).all(|d| costs.access_cost(d) <= costs.spill + costs.reload));
+}
+
+#[test]
+fn test_stack_manager_follows_backend_threshold() {
+    let costs = StackCosts::miden(16);
+    let threshold = costs.spill_threshold();
+    let mut sm = StackManager::with_costs(costs, 1 << 30, SpillFormatter::default());
+    for i in 0..threshold {
+        sm.push_named(&format!("v{}", i), 1);
+    }
+    assert!(sm.drain_side_effects().is_empty());
+    sm.push_named("extra", 1);
+    assert!(!sm.drain_side_effects().is_empty());
+    assert!(sm.spilled.iter().any(|v| v.name.as_deref() == Some("v0")));
+    assert_eq!(sm.max_reach(), 15);
+}
+
+#[test]
+fn test_unknown_target_uses_triton_costs_with_own_window() {
+    let costs = StackCosts::for_target("custom", 32);
+    assert_eq!(costs, StackCosts::triton(32));
+    assert_eq!(costs.spill_threshold(), 32);
+}
+
+/// Run spill code on a concrete stack (top last) and RAM.
+fn run_effects(effects: &[String], stack: &mut Vec<u64>, ram: &mut BTreeMap<u64, u64>) {
+    for line in effects {
+        let mut parts = line.split_whitespace();
+        let op = parts.next().unwrap();
+        let arg: u64 = parts.next().unwrap().parse().unwrap();
+        match op {
+            "push" => stack.push(arg),
+            "pop" => {
+                stack.pop();
+            }
+            "swap" => {
+                let top = stack.len() - 1;
+                stack.swap(top, top - arg as usize);
+            }
+            "write_mem" => {
+                let addr = stack.pop().unwrap();
+                ram.insert(addr, stack.pop().unwrap());
+                stack.push(addr + 1);
+            }
+            "read_mem" => {
+                let addr = stack.pop().unwrap();
+                stack.push(ram[&addr]);
+                stack.push(addr - 1);
+            }
+            _ => panic!("unexpected spill instruction {}", line),
+        }
+    }
+}
+
+#[test]
+fn test_spill_below_window_keeps_the_stack_in_order() {
+    let mut sm = StackManager::new();
+    for i in 0..16 {
+        sm.push_named(&format!("v{}", i), 1);
+    }
+    // v16 is already pushed, so v0 sits 16 deep, out of swap reach.
+    sm.push_named("v16", 1);
+    let effects = sm.drain_side_effects();
+
+    let mut stack: Vec<u64> = (0..17).collect();
+    let mut ram = BTreeMap::new();
+    run_effects(&effects, &mut stack, &mut ram);
+    assert_eq!(stack, (1..17).collect::<Vec<u64>>());
+    let spilled = &sm.spilled[0];
+    assert_eq!(spilled.name.as_deref(), Some("v0"));
+    let VarLocation::Ram(addr) = spilled.location else {
+        panic!("v0 should live in RAM");
+    };
+    assert_eq!(ram[&addr], 0);
+}
+
+#[test]
+fn test_reload_lands_beneath_pending_temps() {
+    let mut sm = StackManager::new();
+    for i in 0..17 {
+        sm.push_named(&format!("v{}", i), 1);
+    }
+    sm.pop();
+    sm.push_temp(1);
+    sm.drain_side_effects();
+
+    let depth = sm.access_var("v0");
+    assert_eq!(depth, 1, "reloaded value sits under the temp");
+    assert!(sm.last().unwrap().name.is_none());
+}
+
+#[test]
+fn test_reconcile_drops_branch_locals_and_restores_layout() {
+    let mut sm = StackManager::new();
+    sm.push_named("a", 1);
+    sm.push_named("b", 1);
+    let saved = sm.save_state();
+    sm.push_named("local", 1);
+    sm.drain_side_effects();
+
+    sm.reconcile(&saved);
+    let effects = sm.drain_side_effects();
+    assert_eq!(effects, vec!["    pop 1".to_string()]);
+}
+
+#[test]
+fn test_reconcile_reloads_values_spilled_inside_branch() {
+    let mut sm = StackManager::new();
+    for i in 0..16 {
+        sm.push_named(&format!("v{}", i), 1);
+    }
+    let saved = sm.save_state();
+    sm.push_named("local", 1); // spills v0
+    sm.drain_side_effects();
+
+    sm.reconcile(&saved);
+    let effects = sm.drain_side_effects();
+    let writes = effects.iter().filter(|l| l.contains("write_mem")).count();
+    let reads = effects.iter().filter(|l| l.contains("read_mem")).count();
+    assert_eq!(reads, 16, "every saved value is reloaded in order");
+    assert_eq!(writes, 15, "values still on the stack are stored first");
+}
+
+#[test]
+fn test_validation_flags_access_below_window() {
+    let mut sm = StackManager::new();
+    sm.set_validation(true);
+    sm.push_named("x", 1);
+    for _ in 0..15 {
+        sm.push_temp(1);
+    }
+    assert_eq!(sm.access_var("x"), 15);
+    assert!(sm.take_violations().is_empty());
+
+    // A layout the spill policy never produces: x out of swap reach.
+    let (mut on_stack, spilled) = sm.save_state();
+    on_stack.push(on_stack[1].clone());
+    sm.restore_state((on_stack, spilled));
+    sm.access_var("x");
+    let violations = sm.take_violations();
+    assert_eq!(violations.len(), 1);
+    assert!(
+        violations[0].contains("variable 'x' is 16 deep"),
+        "{}",
+        violations[0]
+    );
+}
+
+#[test]
+fn test_validation_checks_block_balance() {
+    let mut sm = StackManager::new();
+    sm.push_named("a", 1);
+    sm.check_balanced("loop body", 0);
+    assert!(sm.take_violations().is_empty(), "validation is off");
+
+    sm.set_validation(true);
+    sm.check_balanced("loop body", 1);
+    assert!(sm.take_violations().is_empty());
+    sm.check_balanced("loop body", 0);
+    assert_eq!(
+        sm.take_violations(),
+        vec!["loop body leaves 1 element(s) on the stack, expected 0".to_string()]
+    );
+}
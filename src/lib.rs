@@ -42,25 +42,43 @@ pub use verify::smt;
 pub use verify::solve;
 pub use verify::sym;
 pub use verify::synthesize;
+pub use verify::witness;
 
 // Re-export public API — preserves `trident::compile()` etc.
 pub use api::*;
 
-use diagnostic::{render_diagnostics, Diagnostic};
+use diagnostic::{Diagnostic, DiagnosticSink, StderrSink};
 use lexer::Lexer;
 use parser::Parser;
 
 pub(crate) fn parse_source(source: &str, filename: &str) -> Result<ast::File, Vec<Diagnostic>> {
+    parse_source_with(source, filename, &StderrSink::default())
+}
+
+/// Parse and derive-expand a source file and fold constant array sizes,
+/// reporting errors to `sink`.
+pub(crate) fn parse_source_with(
+    source: &str,
+    filename: &str,
+    sink: &dyn DiagnosticSink,
+) -> Result<ast::File, Vec<Diagnostic>> {
     let (tokens, _comments, lex_errors) = Lexer::new(source, 0).tokenize();
     if !lex_errors.is_empty() {
-        render_diagnostics(&lex_errors, filename, source);
+        sink.report(&lex_errors, filename, source);
         return Err(lex_errors);
     }
 
-    match Parser::new_with_source(tokens, source).parse_file() {
+    let parsed = Parser::new_with_source(tokens, source)
+        .parse_file()
+        .and_then(|mut file| {
+            ast::derive::expand(&mut file)?;
+            ast::sizes::fold(&mut file);
+            Ok(file)
+        });
+    match parsed {
         Ok(file) => Ok(file),
         Err(errors) => {
-            render_diagnostics(&errors, filename, source);
+            sink.report(&errors, filename, source);
             Err(errors)
         }
     }
@@ -71,5 +89,8 @@ pub fn parse_source_silent(source: &str, _filename: &str) -> Result<ast::File, V
     if !lex_errors.is_empty() {
         return Err(lex_errors);
     }
-    Parser::new_with_source(tokens, source).parse_file()
+    let mut file = Parser::new_with_source(tokens, source).parse_file()?;
+    ast::derive::expand(&mut file)?;
+    ast::sizes::fold(&mut file);
+    Ok(file)
 }
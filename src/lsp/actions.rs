@@ -27,6 +27,10 @@ pub(super) fn code_actions(
             if let Some(a) = remove_redundant_as_u32(diag, uri) {
                 actions.push(CodeActionOrCommand::CodeAction(a));
             }
+        } else if msg.starts_with("hint[H0004]: loop in '") {
+            if let Some(a) = fix_loop_bound(diag, uri) {
+                actions.push(CodeActionOrCommand::CodeAction(a));
+            }
         } else if msg.starts_with("missing field '") {
             if let Some(a) = insert_missing_field(source, diag, uri) {
                 actions.push(CodeActionOrCommand::CodeAction(a));
@@ -120,6 +124,41 @@ fn remove_redundant_as_u32(diag: &Diagnostic, uri: &Url) -> Option<CodeAction> {
     ))
 }
 
+/// Rewrite or insert `bounded N` with the bound from the hint's help.
+fn fix_loop_bound(diag: &Diagnostic, uri: &Url) -> Option<CodeAction> {
+    // The diagnostic range covers the edit (see `loop_bound_fixes`):
+    // "help: tightening the bound to N would reduce worst-case cost"
+    // replaces the literal, "help: add `bounded N` so ..." inserts one.
+    let insert = diag.message.contains("help: add `bounded ");
+    let marker = if insert {
+        "help: add `bounded "
+    } else {
+        "help: tightening the bound to "
+    };
+    let start = diag.message.find(marker)? + marker.len();
+    let digits = &diag.message[start..];
+    let len = digits.find(|c: char| !c.is_ascii_digit())?;
+    let bound: u64 = digits[..len].parse().ok()?;
+
+    let (title, new_text) = if insert {
+        (
+            format!("Add `bounded {}`", bound),
+            format!(" bounded {}", bound),
+        )
+    } else {
+        (
+            format!("Tighten loop bound to `bounded {}`", bound),
+            bound.to_string(),
+        )
+    };
+    let edit = TextEdit {
+        range: diag.range,
+        new_text,
+    };
+
+    Some(make_quickfix(title, uri, vec![edit], diag))
+}
+
 /// Add missing field with a zero default before the closing `}`.
 fn insert_missing_field(source: &str, diag: &Diagnostic, uri: &Url) -> Option<CodeAction> {
     let field_name = extract_quoted(&diag.message)?;
@@ -280,4 +319,46 @@ mod tests {
         };
         assert!(action.title.contains("Add missing field `y`"));
     }
+
+    #[test]
+    fn tighten_loop_bound_action() {
+        let source = "program test\nfn main() {\n  for i in 0..10 bounded 128 {\n  }\n}\n";
+        // Diagnostic on the `128` literal at line 2
+        let diag = make_diag(
+            "hint[H0004]: loop in 'main' bounded 128 but iterates only 10 times\nnote: declared bound is 12x the actual iteration count\nhelp: tightening the bound to 16 would reduce worst-case cost",
+            (2, 25),
+            (2, 28),
+        );
+        let actions = code_actions(source, &[diag], &test_uri());
+        assert_eq!(actions.len(), 1);
+        let action = match &actions[0] {
+            CodeActionOrCommand::CodeAction(a) => a,
+            _ => panic!("expected CodeAction"),
+        };
+        assert_eq!(action.title, "Tighten loop bound to `bounded 16`");
+        let edit = action.edit.as_ref().unwrap();
+        let edits = &edit.changes.as_ref().unwrap()[&test_uri()];
+        assert_eq!(edits[0].new_text, "16");
+    }
+
+    #[test]
+    fn add_missing_loop_bound_action() {
+        let source = "program test\nconst N: U32 = 8\nfn main() {\n  for i in 0..N {\n  }\n}\n";
+        // Zero-width diagnostic after `0..N` at line 3
+        let diag = make_diag(
+            "hint[H0004]: loop in 'main' has non-constant bound, cost assumes 1 iteration(s)\nhelp: add `bounded 8` so the cost covers every iteration",
+            (3, 14),
+            (3, 14),
+        );
+        let actions = code_actions(source, &[diag], &test_uri());
+        assert_eq!(actions.len(), 1);
+        let action = match &actions[0] {
+            CodeActionOrCommand::CodeAction(a) => a,
+            _ => panic!("expected CodeAction"),
+        };
+        assert_eq!(action.title, "Add `bounded 8`");
+        let edit = action.edit.as_ref().unwrap();
+        let edits = &edit.changes.as_ref().unwrap()[&test_uri()];
+        assert_eq!(edits[0].new_text, " bounded 8");
+    }
 }
@@ -0,0 +1,363 @@
+//! Context-aware completion: members after `.`, fields inside struct and
+//! event literals, and ranking by the type expected at the cursor.
+
+use tower_lsp::lsp_types::*;
+
+use crate::ast::File;
+use crate::typecheck::{ModuleExports, ScopeAt, TypeChecker};
+use crate::types::Ty;
+
+use super::util::{find_call_context, is_ident_char, position_to_byte_offset};
+
+/// A completion candidate and the type it produces, if it has one.
+pub(super) type Candidate = (CompletionItem, Option<Ty>);
+
+/// Source with the member access being typed (`.` plus any partial
+/// name) blanked out, so the file parses with the cursor in place, and
+/// the offset to probe: the end of the receiver for a member access.
+pub(super) fn probe_source(source: &str, offset: usize) -> (String, usize) {
+    let bytes = source.as_bytes();
+    let mut start = offset.min(bytes.len());
+    while start > 0 && is_ident_char(bytes[start - 1]) {
+        start -= 1;
+    }
+    if start == 0 || bytes[start - 1] != b'.' {
+        return (source.to_string(), offset);
+    }
+    let dot = start - 1;
+    let mut out = source.to_string();
+    out.replace_range(dot..offset, &" ".repeat(offset - dot));
+    (out, dot)
+}
+
+/// Type check the document with a probe at `offset`. Falls back to
+/// `cached` (the last good parse) when the edited text does not parse.
+pub(super) fn scope_for(
+    source: &str,
+    offset: usize,
+    cached: Option<&File>,
+    imports: &[ModuleExports],
+) -> ScopeAt {
+    let (probed, offset) = probe_source(source, offset);
+    let parsed = crate::parse_source_silent(&probed, "");
+    let file = match (&parsed, cached) {
+        (Ok(file), _) | (Err(_), Some(file)) => file,
+        (Err(_), None) => return ScopeAt::default(),
+    };
+    let mut tc = TypeChecker::new();
+    for exports in imports {
+        if exports.module_name != file.name.node {
+            tc.import_module(exports);
+        }
+    }
+    tc.scope_at(file, offset as u32)
+}
+
+/// Dotted receiver before the `.` at the cursor: `a.b.` (or `a.b.pa`)
+/// gives `["a", "b"]`.
+pub(super) fn receiver_path(source: &str, pos: Position) -> Option<Vec<String>> {
+    let offset = position_to_byte_offset(source, pos)?;
+    let bytes = source.as_bytes();
+    let mut i = offset;
+    while i > 0 && is_ident_char(bytes[i - 1]) {
+        i -= 1;
+    }
+    if i == 0 || bytes[i - 1] != b'.' {
+        return None;
+    }
+    let end = i - 1;
+    let mut start = end;
+    while start > 0 && (is_ident_char(bytes[start - 1]) || bytes[start - 1] == b'.') {
+        start -= 1;
+    }
+    let path: Vec<String> = source[start..end].split('.').map(str::to_string).collect();
+    if path.iter().any(|s| s.is_empty()) {
+        return None;
+    }
+    Some(path)
+}
+
+/// Type reached by following `path` from a variable through struct
+/// fields, or `None` if the first segment is not a variable.
+pub(super) fn receiver_type(scope: &ScopeAt, path: &[String]) -> Option<Ty> {
+    let (first, rest) = path.split_first()?;
+    let mut ty = scope.vars.get(first)?.clone();
+    for field in rest {
+        let Ty::Struct(st) = &ty else {
+            return None;
+        };
+        ty = st
+            .fields
+            .iter()
+            .find(|(name, _, _)| name == field)
+            .map(|(_, t, _)| t.clone())?;
+    }
+    Some(ty)
+}
+
+/// Field candidates of a struct value.
+pub(super) fn struct_field_candidates(ty: &Ty) -> Vec<Candidate> {
+    let Ty::Struct(st) = ty else {
+        return Vec::new();
+    };
+    st.fields
+        .iter()
+        .map(|(name, fty, _)| (field_item(name, fty), Some(fty.clone())))
+        .collect()
+}
+
+/// Name of the struct or event literal whose field list holds the
+/// cursor, plus the fields already written. Only field-name positions
+/// count: right after `{` or `,`, possibly inside a partial name.
+pub(super) fn literal_context(source: &str, pos: Position) -> Option<(String, Vec<String>)> {
+    let offset = position_to_byte_offset(source, pos)?;
+    let bytes = source.as_bytes();
+    let mut depth = 0i32;
+    let mut i = offset;
+    let open = loop {
+        if i == 0 {
+            return None;
+        }
+        i -= 1;
+        match bytes[i] {
+            b'}' | b')' | b']' => depth += 1,
+            b'(' | b'[' if depth == 0 => return None,
+            b'(' | b'[' => depth -= 1,
+            b'{' if depth == 0 => break i,
+            b'{' => depth -= 1,
+            _ => {}
+        }
+    };
+
+    let mut name_end = open;
+    while name_end > 0 && bytes[name_end - 1].is_ascii_whitespace() {
+        name_end -= 1;
+    }
+    let mut name_start = name_end;
+    while name_start > 0 && (is_ident_char(bytes[name_start - 1]) || bytes[name_start - 1] == b'.')
+    {
+        name_start -= 1;
+    }
+    if name_start == name_end {
+        return None;
+    }
+    let name = source[name_start..name_end].to_string();
+
+    // Split the literal body at top-level commas; the cursor must be in
+    // a name position of the last entry.
+    let body = &source[open + 1..offset];
+    let mut entries = Vec::new();
+    let mut depth = 0i32;
+    let mut current = String::new();
+    for c in body.chars() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                entries.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if current.contains(':') {
+        return None;
+    }
+    let written = entries
+        .iter()
+        .filter_map(|e| e.split(':').next())
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .collect();
+    Some((name, written))
+}
+
+/// Field candidates for a struct or event literal, or `None` if `name`
+/// is neither (e.g. the `{` opens a block).
+pub(super) fn literal_field_candidates(
+    scope: &ScopeAt,
+    name: &str,
+    written: &[String],
+) -> Option<Vec<Candidate>> {
+    let fields: Vec<(String, Ty)> = if let Some(st) = scope.structs.get(name) {
+        st.fields
+            .iter()
+            .map(|(n, t, _)| (n.clone(), t.clone()))
+            .collect()
+    } else {
+        scope.events.get(name)?.clone()
+    };
+    Some(
+        fields
+            .iter()
+            .filter(|(n, _)| !written.contains(n))
+            .map(|(n, t)| (field_item(n, t), Some(t.clone())))
+            .collect(),
+    )
+}
+
+/// Type expected at the cursor: the active call argument's parameter
+/// type, else the annotation of the `let` being initialized.
+pub(super) fn expected_type(scope: &ScopeAt, source: &str, pos: Position) -> Option<Ty> {
+    if let Some((callee, active)) = find_call_context(source, pos) {
+        if let Some((params, _)) = scope.functions.get(&callee) {
+            return params.get(active as usize).map(|(_, t)| t.clone());
+        }
+    }
+    scope.expected.clone()
+}
+
+/// Candidates for the variables in scope.
+pub(super) fn variable_candidates(scope: &ScopeAt) -> Vec<Candidate> {
+    scope
+        .vars
+        .iter()
+        .map(|(name, ty)| {
+            let item = CompletionItem {
+                label: name.clone(),
+                kind: Some(CompletionItemKind::VARIABLE),
+                detail: Some(ty.display()),
+                ..Default::default()
+            };
+            (item, Some(ty.clone()))
+        })
+        .collect()
+}
+
+/// Order candidates so those producing the expected type come first.
+pub(super) fn rank(candidates: Vec<Candidate>, expected: Option<&Ty>) -> Vec<CompletionItem> {
+    candidates
+        .into_iter()
+        .map(|(mut item, ty)| {
+            let fits = match (expected, &ty) {
+                (Some(want), Some(have)) => same_type(want, have),
+                _ => false,
+            };
+            item.sort_text = Some(format!("{}{}", if fits { 0 } else { 1 }, item.label));
+            if fits {
+                item.preselect = Some(true);
+            }
+            item
+        })
+        .collect()
+}
+
+/// Type equality where structs match by name (imported struct types
+/// carry their short name).
+fn same_type(a: &Ty, b: &Ty) -> bool {
+    match (a, b) {
+        (Ty::Struct(x), Ty::Struct(y)) => {
+            let short = |n: &str| n.rsplit('.').next().unwrap_or(n).to_string();
+            short(&x.name) == short(&y.name)
+        }
+        _ => a == b,
+    }
+}
+
+fn field_item(name: &str, ty: &Ty) -> CompletionItem {
+    CompletionItem {
+        label: name.to_string(),
+        kind: Some(CompletionItemKind::FIELD),
+        detail: Some(ty.display()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "program test\n\
+        struct Inner {\n    d: Digest,\n}\n\
+        struct Point {\n    x: Field,\n    y: U32,\n    inner: Inner,\n}\n\
+        event Moved {\n    from: Field,\n    to: Field,\n}\n\
+        fn main(p: Point) {\n    let a: U32 = p.\n}\n";
+
+    fn pos_after(source: &str, marker: &str) -> (usize, Position) {
+        let offset = source.find(marker).unwrap() + marker.len();
+        let line = source[..offset].matches('\n').count() as u32;
+        let col = (offset - source[..offset].rfind('\n').map_or(0, |n| n + 1)) as u32;
+        (offset, Position::new(line, col))
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|i| i.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_member_access_lists_only_receiver_fields() {
+        let (offset, pos) = pos_after(SOURCE, "= p.");
+        let scope = scope_for(SOURCE, offset, None, &[]);
+        let path = receiver_path(SOURCE, pos).unwrap();
+        let ty = receiver_type(&scope, &path).unwrap();
+        let candidates = struct_field_candidates(&ty);
+        assert_eq!(labels(&rank(candidates, None)), vec!["x", "y", "inner"]);
+    }
+
+    #[test]
+    fn test_nested_member_access_follows_field_types() {
+        let source = SOURCE.replace("= p.", "= p.inner.");
+        let (offset, pos) = pos_after(&source, "= p.inner.");
+        let scope = scope_for(&source, offset, None, &[]);
+        let path = receiver_path(&source, pos).unwrap();
+        assert_eq!(path, vec!["p", "inner"]);
+        let ty = receiver_type(&scope, &path).unwrap();
+        assert_eq!(labels(&rank(struct_field_candidates(&ty), None)), vec!["d"]);
+    }
+
+    #[test]
+    fn test_candidates_matching_expected_type_rank_first() {
+        let (offset, pos) = pos_after(SOURCE, "= p.");
+        let scope = scope_for(SOURCE, offset, None, &[]);
+        let expected = expected_type(&scope, SOURCE, pos);
+        assert_eq!(expected, Some(Ty::U32));
+
+        let ty = receiver_type(&scope, &["p".to_string()]).unwrap();
+        let mut items = rank(struct_field_candidates(&ty), expected.as_ref());
+        items.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+        assert_eq!(items[0].label, "y");
+        assert_eq!(items[0].preselect, Some(true));
+    }
+
+    #[test]
+    fn test_call_argument_sets_expected_type() {
+        let source = SOURCE.replace("let a: U32 = p.", "pub_write(p.");
+        let (offset, pos) = pos_after(&source, "pub_write(p.");
+        let scope = scope_for(&source, offset, Some(&cached(SOURCE)), &[]);
+        assert_eq!(expected_type(&scope, &source, pos), Some(Ty::Field));
+    }
+
+    #[test]
+    fn test_event_literal_lists_unwritten_fields() {
+        let source = SOURCE.replace("let a: U32 = p.", "reveal Moved { from: p.x, ");
+        let (offset, pos) = pos_after(&source, "from: p.x, ");
+        let (name, written) = literal_context(&source, pos).unwrap();
+        assert_eq!(name, "Moved");
+        assert_eq!(written, vec!["from"]);
+
+        let scope = scope_for(&source, offset, Some(&cached(SOURCE)), &[]);
+        let candidates = literal_field_candidates(&scope, &name, &written).unwrap();
+        assert_eq!(labels(&rank(candidates, None)), vec!["to"]);
+    }
+
+    #[test]
+    fn test_literal_value_position_is_not_a_field_context() {
+        let source = SOURCE.replace("let a: U32 = p.", "let q: Point = Point { x: ");
+        let (_, pos) = pos_after(&source, "Point { x: ");
+        assert!(literal_context(&source, pos).is_none());
+    }
+
+    #[test]
+    fn test_block_brace_is_not_a_literal() {
+        let source = "program test\nfn main() {\n    if true {\n        \n    }\n}\n";
+        let (offset, pos) = pos_after(source, "if true {\n        ");
+        let (name, _) = literal_context(source, pos).unwrap();
+        let scope = scope_for(source, offset, None, &[]);
+        assert!(literal_field_candidates(&scope, &name, &[]).is_none());
+    }
+
+    fn cached(source: &str) -> File {
+        crate::parse_source_silent(source, "").unwrap()
+    }
+}
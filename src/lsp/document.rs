@@ -35,6 +35,9 @@ pub(crate) struct DocumentData {
     pub name_kinds: BTreeMap<String, (NameKind, u32)>,
     /// Cached AST from last successful parse.
     pub cached_ast: Option<File>,
+    /// Whether `cached_ast` was parsed from the current `source`. Only a
+    /// current AST can be patched by incremental reparsing.
+    pub ast_current: bool,
     /// Last emitted semantic token array (for delta computation).
     pub last_semantic_tokens: Vec<SemanticToken>,
     /// Monotonically increasing result ID for delta tracking.
@@ -52,6 +55,7 @@ impl DocumentData {
             line_starts,
             name_kinds: BTreeMap::new(),
             cached_ast: None,
+            ast_current: false,
             last_semantic_tokens: Vec::new(),
             result_version: 0,
         }
@@ -1,8 +1,14 @@
 //! Incremental lexing: re-lex only the dirty byte region after an edit,
 //! then splice the new tokens into the cached token list.
+//!
+//! Incremental parsing: when an edit stays inside one top-level item,
+//! re-parse only that item and shift the spans of the items after it.
 
+use crate::ast::shift::shift_item;
+use crate::ast::{File, Item};
 use crate::syntax::lexeme::Lexeme;
 use crate::syntax::lexer::{Comment, Lexer};
+use crate::syntax::parser::Parser;
 use crate::syntax::span::{Span, Spanned};
 
 /// Result of an incremental lex operation.
@@ -165,7 +171,6 @@ fn shift_comment(comment: &Comment, delta: i64) -> Comment {
 /// Result of classifying whether an edit is contained within a single
 /// top-level item (function, struct, event, const) or crosses boundaries.
 #[derive(Debug, PartialEq, Eq)]
-#[allow(dead_code)] // infrastructure for Phase 2 incremental parsing
 pub(super) enum EditScope {
     /// Edit falls entirely within a single top-level item body.
     /// The pair gives the token indices `[start, end)` of that item.
@@ -179,7 +184,6 @@ pub(super) enum EditScope {
 /// Walks the token list at brace depth 0 to find item boundaries. If the
 /// edit region is entirely inside one item's brace pair, returns
 /// `SingleItem`; otherwise returns `Full`.
-#[allow(dead_code)] // infrastructure for Phase 2 incremental parsing
 pub(super) fn classify_edit_scope(
     tokens: &[Spanned<Lexeme>],
     edit_start: usize,
@@ -260,6 +264,95 @@ pub(super) fn classify_edit_scope(
     EditScope::Full
 }
 
+/// Re-parse only the item containing an edit and splice it into `ast`.
+///
+/// `old_tokens` is the token list before the edit, `new_tokens` after it
+/// (from `incremental_lex`). Offsets are as for `incremental_lex`.
+/// Returns `false` and leaves `ast` untouched when the edit is not
+/// confined to one item body or the item no longer parses on its own;
+/// the caller then falls back to a full parse.
+pub(super) fn incremental_parse(
+    ast: &mut File,
+    source: &str,
+    old_tokens: &[Spanned<Lexeme>],
+    new_tokens: &[Spanned<Lexeme>],
+    edit_start: usize,
+    old_end: usize,
+    new_end: usize,
+) -> bool {
+    let delta: i64 = new_end as i64 - old_end as i64;
+
+    // The `main` generated for `#[entry]` functions spans them all.
+    if ast
+        .items
+        .iter()
+        .any(|item| matches!(&item.node, Item::Fn(f) if f.is_entry))
+    {
+        return false;
+    }
+    // So do the functions a struct's `#[derive]` generates.
+    if ast
+        .items
+        .iter()
+        .any(|item| matches!(&item.node, Item::Struct(s) if !s.derives.is_empty()))
+    {
+        return false;
+    }
+
+    let EditScope::SingleItem(_, old_item_end) =
+        classify_edit_scope(old_tokens, edit_start, old_end)
+    else {
+        return false;
+    };
+    let old_close = old_tokens[old_item_end - 1].span.end;
+    let Some(k) = ast
+        .items
+        .iter()
+        .position(|item| item.span.end == old_close && (item.span.start as usize) < edit_start)
+    else {
+        return false;
+    };
+    let item_start = ast.items[k].span.start;
+    let new_close = (old_close as i64 + delta) as u32;
+
+    // The item's tokens in the new list: from its (unmoved) first token
+    // through the shifted closing brace.
+    let first = new_tokens.partition_point(|t| t.span.start < item_start);
+    let last = new_tokens.partition_point(|t| t.span.end <= new_close);
+    if first >= last
+        || new_tokens[first].span.start != item_start
+        || new_tokens[last - 1].span.end != new_close
+        || new_tokens[last - 1].node != Lexeme::RBrace
+    {
+        return false;
+    }
+
+    // Items after the edit are reused only if their tokens are unchanged.
+    let old_rest = &old_tokens[old_item_end..];
+    let new_rest = &new_tokens[last..];
+    if old_rest.len() != new_rest.len()
+        || old_rest.iter().zip(new_rest).any(|(o, n)| o.node != n.node)
+    {
+        return false;
+    }
+
+    let mut slice = new_tokens[first..last].to_vec();
+    slice.push(Spanned::new(
+        Lexeme::Eof,
+        Span::new(new_tokens[first].span.file_id, new_close, new_close),
+    ));
+    let mut items = match Parser::new_with_source(slice, source).parse_items_only() {
+        Ok(items) if items.len() == 1 => items,
+        _ => return false,
+    };
+
+    ast.items[k] = items.pop().expect("checked len == 1");
+    for item in &mut ast.items[k + 1..] {
+        shift_item(item, delta);
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,4 +478,82 @@ mod tests {
         let scope = classify_edit_scope(&tokens, 30, 70);
         assert_eq!(scope, EditScope::Full);
     }
+
+    fn reparse(old_source: &str, new_source: &str, start: usize, old_end: usize) -> Option<File> {
+        let (old_tokens, old_comments) = full_lex(old_source);
+        let mut ast = crate::parse_source_silent(old_source, "").expect("old source parses");
+        let new_end = old_end + new_source.len() - old_source.len();
+        let lexed = incremental_lex(
+            new_source,
+            &old_tokens,
+            &old_comments,
+            start,
+            old_end,
+            new_end,
+        );
+        incremental_parse(
+            &mut ast,
+            new_source,
+            &old_tokens,
+            &lexed.tokens,
+            start,
+            old_end,
+            new_end,
+        )
+        .then_some(ast)
+    }
+
+    fn item_spans(file: &File) -> Vec<(u32, u32)> {
+        file.items
+            .iter()
+            .map(|i| (i.span.start, i.span.end))
+            .collect()
+    }
+
+    #[test]
+    fn reparsed_item_matches_full_parse() {
+        let old_source = "program test\nfn foo() {\n    let x: Field = 1\n}\nfn bar() {\n    let y: Field = x\n}\n";
+        let new_source = "program test\nfn foo() {\n    let x: Field = 1\n    let longer: Field = 2\n}\nfn bar() {\n    let y: Field = x\n}\n";
+        let at = old_source.find("= 1").unwrap() + 3;
+        let ast = reparse(old_source, new_source, at, at).expect("single-item edit");
+        let full = crate::parse_source_silent(new_source, "").unwrap();
+        assert_eq!(item_spans(&ast), item_spans(&full));
+
+        let (crate::ast::Item::Fn(got), crate::ast::Item::Fn(want)) =
+            (&ast.items[1].node, &full.items[1].node)
+        else {
+            panic!("expected functions");
+        };
+        let got_body = got.body.as_ref().unwrap();
+        let want_body = want.body.as_ref().unwrap();
+        assert_eq!(got_body.span, want_body.span);
+        assert_eq!(got_body.node.stmts[0].span, want_body.node.stmts[0].span);
+    }
+
+    #[test]
+    fn edit_in_header_needs_full_parse() {
+        let old_source = "program test\nfn main() {\n}\n";
+        let new_source = "program tests\nfn main() {\n}\n";
+        assert!(reparse(old_source, new_source, 12, 12).is_none());
+    }
+
+    #[test]
+    fn edit_splitting_an_item_needs_full_parse() {
+        let old_source = "program test\nfn main() {\n    let x: Field = 1\n}\n";
+        let at = old_source.find("= 1").unwrap() + 3;
+        let new_source = format!(
+            "{}\n}}\nfn extra() {{{}",
+            &old_source[..at],
+            &old_source[at..]
+        );
+        assert!(reparse(old_source, &new_source, at, at).is_none());
+    }
+
+    #[test]
+    fn syntax_error_in_item_needs_full_parse() {
+        let old_source = "program test\nfn main() {\n    let x: Field = 1\n}\n";
+        let at = old_source.find("1").unwrap();
+        let new_source = old_source.replacen("1", "", 1);
+        assert!(reparse(old_source, &new_source, at, at + 1).is_none());
+    }
 }
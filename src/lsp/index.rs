@@ -0,0 +1,336 @@
+//! Persistent project symbol index for go-to-definition.
+//!
+//! The index records, per module, the content hash of the source it was
+//! built from and the ranges of its top-level items. It lives on disk
+//! under the Trident cache directory so a new session starts warm, and
+//! is patched in memory as open documents change. A lookup re-resolves
+//! and re-parses only when some module's source no longer matches its
+//! recorded hash, and then re-parses only the changed modules.
+//!
+//! Location: `<cache_dir>/lsp/<entry path hash>.index`
+//!
+//! ```text
+//! trident-lsp-index 1
+//! module <name>\t<path>\t<content hash hex>
+//! symbol <name>\t<start line>\t<start char>\t<end line>\t<end char>
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use tower_lsp::lsp_types::{Position, Range, Url};
+
+use crate::ast::{File, Item};
+use crate::hash::ContentHash;
+use crate::resolve::resolve_modules;
+
+use super::util::span_to_range;
+
+const INDEX_HEADER: &str = "trident-lsp-index 1";
+
+/// Content hash of a module's exact source text. Unlike
+/// `hash_file_content`, formatting changes count: ranges depend on them.
+pub(super) fn source_hash(source: &str) -> ContentHash {
+    ContentHash(*blake3::hash(source.as_bytes()).as_bytes())
+}
+
+/// Top-level item names and their definition ranges in one file.
+pub(super) fn item_symbols(source: &str, file: &File) -> Vec<(String, Range)> {
+    file.items
+        .iter()
+        .map(|item| {
+            let name = match &item.node {
+                Item::Fn(f) => &f.name,
+                Item::Struct(s) => &s.name,
+                Item::Const(c) => &c.name,
+                Item::Event(e) => &e.name,
+            };
+            (name.node.clone(), span_to_range(source, name.span))
+        })
+        .collect()
+}
+
+/// Indexed symbols of one module.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct ModuleSymbols {
+    /// Dotted module name (e.g. "crypto.sponge").
+    pub name: String,
+    pub path: PathBuf,
+    /// `source_hash` of the source the symbols were read from.
+    pub hash: ContentHash,
+    pub symbols: Vec<(String, Range)>,
+}
+
+/// Symbol index for all modules reachable from one entry point.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct ProjectIndex {
+    pub entry: PathBuf,
+    pub modules: Vec<ModuleSymbols>,
+}
+
+impl ProjectIndex {
+    pub fn empty(entry: PathBuf) -> Self {
+        Self {
+            entry,
+            modules: Vec::new(),
+        }
+    }
+
+    /// Whether every indexed module still has the recorded source.
+    /// `source_of` returns the current text of a module (open buffer or
+    /// disk), or `None` if it is gone.
+    pub fn is_fresh(&self, source_of: &dyn Fn(&Path) -> Option<String>) -> bool {
+        !self.modules.is_empty()
+            && self
+                .modules
+                .iter()
+                .all(|m| source_of(&m.path).is_some_and(|source| source_hash(&source) == m.hash))
+    }
+
+    /// Re-resolve the module graph and re-parse modules whose source
+    /// changed; unchanged modules keep their symbols. Returns `false`
+    /// (leaving the index as is) if the project no longer resolves.
+    pub fn refresh(&mut self, source_of: &dyn Fn(&Path) -> Option<String>) -> bool {
+        let Ok(resolved) = resolve_modules(&self.entry) else {
+            return false;
+        };
+        let mut previous: BTreeMap<PathBuf, ModuleSymbols> = self
+            .modules
+            .drain(..)
+            .map(|m| (m.path.clone(), m))
+            .collect();
+
+        for module in resolved {
+            let source = source_of(&module.file_path).unwrap_or(module.source);
+            let hash = source_hash(&source);
+            let symbols = match previous.remove(&module.file_path) {
+                Some(old) if old.hash == hash => old.symbols,
+                _ => {
+                    let filename = module.file_path.to_string_lossy();
+                    match crate::parse_source_silent(&source, &filename) {
+                        Ok(file) => item_symbols(&source, &file),
+                        Err(_) => continue,
+                    }
+                }
+            };
+            self.modules.push(ModuleSymbols {
+                name: module.name,
+                path: module.file_path,
+                hash,
+                symbols,
+            });
+        }
+        true
+    }
+
+    /// Replace one module's symbols after its document changed. Returns
+    /// `false` if the module is not part of this index.
+    pub fn update_module(
+        &mut self,
+        path: &Path,
+        hash: ContentHash,
+        symbols: Vec<(String, Range)>,
+    ) -> bool {
+        match self.modules.iter_mut().find(|m| m.path == path) {
+            Some(module) => {
+                module.hash = hash;
+                module.symbols = symbols;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Name → definition map: bare names, `short.name`, and
+    /// `full.module.name`.
+    pub fn symbol_map(&self) -> BTreeMap<String, (Url, Range)> {
+        let mut index = BTreeMap::new();
+        for module in &self.modules {
+            let mod_uri = match Url::from_file_path(&module.path) {
+                Ok(u) => u,
+                Err(_) => match Url::parse(&format!("file://{}", module.path.display())) {
+                    Ok(u) => u,
+                    Err(_) => continue,
+                },
+            };
+            let mod_short = module.name.rsplit('.').next().unwrap_or(&module.name);
+
+            for (name, range) in &module.symbols {
+                let qualified = format!("{}.{}", mod_short, name);
+                let full_qualified = format!("{}.{}", module.name, name);
+
+                index.insert(name.clone(), (mod_uri.clone(), *range));
+                if full_qualified != qualified {
+                    index.insert(full_qualified, (mod_uri.clone(), *range));
+                }
+                index.insert(qualified, (mod_uri.clone(), *range));
+            }
+        }
+        index
+    }
+
+    // ─── Persistence ───────────────────────────────────────────────
+
+    /// On-disk location of the index for `entry`.
+    fn cache_path(entry: &Path) -> Option<PathBuf> {
+        let key = source_hash(&entry.to_string_lossy());
+        Some(
+            crate::cache::cache_dir()?
+                .join("lsp")
+                .join(format!("{}.index", key.to_hex())),
+        )
+    }
+
+    /// Load the stored index for `entry`, if any.
+    pub fn load(entry: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(Self::cache_path(entry)?).ok()?;
+        Self::deserialize(entry, &text)
+    }
+
+    /// Write the index to the cache directory.
+    pub fn store(&self) -> Result<PathBuf, String> {
+        let path = Self::cache_path(&self.entry)
+            .ok_or_else(|| "cannot locate cache directory".to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("cannot create cache directory: {}", e))?;
+        }
+        std::fs::write(&path, self.serialize())
+            .map_err(|e| format!("cannot write index file: {}", e))?;
+        Ok(path)
+    }
+
+    fn serialize(&self) -> String {
+        let mut out = format!("{}\n", INDEX_HEADER);
+        for module in &self.modules {
+            out.push_str(&format!(
+                "module {}\t{}\t{}\n",
+                module.name,
+                module.path.display(),
+                module.hash.to_hex()
+            ));
+            for (name, r) in &module.symbols {
+                out.push_str(&format!(
+                    "symbol {}\t{}\t{}\t{}\t{}\n",
+                    name, r.start.line, r.start.character, r.end.line, r.end.character
+                ));
+            }
+        }
+        out
+    }
+
+    fn deserialize(entry: &Path, text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != INDEX_HEADER {
+            return None;
+        }
+        let mut index = Self::empty(entry.to_path_buf());
+        for line in lines {
+            if let Some(rest) = line.strip_prefix("module ") {
+                let mut parts = rest.split('\t');
+                index.modules.push(ModuleSymbols {
+                    name: parts.next()?.to_string(),
+                    path: PathBuf::from(parts.next()?),
+                    hash: ContentHash::from_hex(parts.next()?)?,
+                    symbols: Vec::new(),
+                });
+            } else if let Some(rest) = line.strip_prefix("symbol ") {
+                let mut parts = rest.split('\t');
+                let name = parts.next()?.to_string();
+                let mut num = || parts.next()?.parse::<u32>().ok();
+                let range =
+                    Range::new(Position::new(num()?, num()?), Position::new(num()?, num()?));
+                index.modules.last_mut()?.symbols.push((name, range));
+            } else if !line.is_empty() {
+                return None;
+            }
+        }
+        Some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_project(dir: &Path) -> PathBuf {
+        let entry = dir.join("main.tri");
+        std::fs::write(
+            &entry,
+            "program main\nuse helper\nfn main() {\n    helper.double(1)\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("helper.tri"),
+            "module helper\npub fn double(x: Field) -> Field {\n    x + x\n}\n",
+        )
+        .unwrap();
+        entry
+    }
+
+    fn from_disk(path: &Path) -> Option<String> {
+        std::fs::read_to_string(path).ok()
+    }
+
+    #[test]
+    fn test_index_round_trips_through_text_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry = write_project(dir.path());
+        let mut index = ProjectIndex::empty(entry.clone());
+        assert!(index.refresh(&from_disk));
+
+        let restored = ProjectIndex::deserialize(&entry, &index.serialize()).unwrap();
+        assert_eq!(restored, index);
+        assert!(restored.symbol_map().contains_key("helper.double"));
+    }
+
+    #[test]
+    fn test_index_goes_stale_when_module_source_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry = write_project(dir.path());
+        let mut index = ProjectIndex::empty(entry);
+        index.refresh(&from_disk);
+        assert!(index.is_fresh(&from_disk));
+
+        let helper = dir.path().join("helper.tri");
+        std::fs::write(
+            &helper,
+            "module helper\n\npub fn double(x: Field) -> Field {\n    x + x\n}\n",
+        )
+        .unwrap();
+        assert!(!index.is_fresh(&from_disk));
+
+        index.refresh(&from_disk);
+        let (_, range) = &index.symbol_map()["double"];
+        assert_eq!(range.start.line, 2);
+    }
+
+    #[test]
+    fn test_refresh_keeps_symbols_of_unchanged_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry = write_project(dir.path());
+        let mut index = ProjectIndex::empty(entry.clone());
+        index.refresh(&from_disk);
+
+        // A stale-but-unchanged hash proves the symbols were reused.
+        let main = index.modules.iter_mut().find(|m| m.name == "main").unwrap();
+        main.symbols.push(("marker".to_string(), Range::default()));
+        index.refresh(&from_disk);
+        assert!(index.symbol_map().contains_key("marker"));
+    }
+
+    #[test]
+    fn test_update_module_applies_open_document_symbols() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry = write_project(dir.path());
+        let mut index = ProjectIndex::empty(entry);
+        index.refresh(&from_disk);
+
+        let helper = dir.path().join("helper.tri");
+        let edited = "module helper\npub fn triple(x: Field) -> Field {\n    x + x + x\n}\n";
+        let file = crate::parse_source_silent(edited, "").unwrap();
+        assert!(index.update_module(&helper, source_hash(edited), item_symbols(edited, &file)));
+        assert!(index.symbol_map().contains_key("helper.triple"));
+        assert!(!index.update_module(&dir.path().join("other.tri"), source_hash(""), Vec::new()));
+    }
+}
@@ -7,8 +7,11 @@ use tower_lsp::lsp_types::*;
 
 use crate::types::Ty;
 
-use super::builtins::{builtin_completions, builtin_hover, builtin_signature};
-use super::util::{find_call_context, format_cost_inline, text_before_dot, word_at_position};
+use super::builtins::{builtin_completions, builtin_hover, builtin_lowering, builtin_signature};
+use super::completion;
+use super::util::{
+    find_call_context, format_cost_inline, item_doc, position_to_byte_offset, word_at_position,
+};
 use super::TridentLsp;
 
 impl TridentLsp {
@@ -29,9 +32,7 @@ impl TridentLsp {
         }
 
         // Check builtins first
-        if let Some(mut info) = builtin_hover(&word) {
-            let cost = crate::cost::cost_builtin("triton", &word);
-            info = format!("{}\n\n**Cost:** {}", info, format_cost_inline(&cost));
+        if let Some(info) = builtin_hover(&word) {
             return Ok(Some(Hover {
                 contents: HoverContents::Markup(MarkupContent {
                     kind: MarkupKind::Markdown,
@@ -44,6 +45,7 @@ impl TridentLsp {
         // Check project exports
         let file_path = PathBuf::from(uri.path());
         let exports = self.collect_project_exports(&file_path);
+        let symbols = self.build_symbol_index(&file_path);
         for exp in &exports {
             // Functions
             for (fname, params, ret_ty) in &exp.functions {
@@ -64,9 +66,23 @@ impl TridentLsp {
                         params_str.join(", "),
                         ret
                     );
+                    let qualified = format!("{}.{}", exp.module_name, bare);
+                    let (doc, intrinsic) = symbols
+                        .get(&qualified)
+                        .and_then(|(def_uri, range)| {
+                            let path = def_uri.to_file_path().ok()?;
+                            Some(item_doc(&self.module_source(&path)?, range.start.line))
+                        })
+                        .unwrap_or_default();
+                    if !doc.is_empty() {
+                        info = format!("{}\n{}", info, doc);
+                    }
                     if let Some(cost) = self.compute_function_cost(&file_path, bare) {
                         info = format!("{}\n\n**Cost:** {}", info, format_cost_inline(&cost));
                     }
+                    if let Some(lowering) = intrinsic.as_deref().and_then(builtin_lowering) {
+                        info = format!("{}\n\n{}", info, lowering);
+                    }
                     return Ok(Some(Hover {
                         contents: HoverContents::Markup(MarkupContent {
                             kind: MarkupKind::Markdown,
@@ -131,23 +147,36 @@ impl TridentLsp {
         uri: &Url,
         pos: Position,
     ) -> Result<Option<CompletionResponse>> {
-        let source = match self
+        let (source, cached_ast) = match self
             .documents
             .lock()
             .unwrap_or_else(|e| e.into_inner())
             .get(uri)
         {
-            Some(doc) => doc.source.clone(),
+            Some(doc) => (doc.source.clone(), doc.cached_ast.clone()),
+            None => return Ok(None),
+        };
+        let offset = match position_to_byte_offset(&source, pos) {
+            Some(offset) => offset,
             None => return Ok(None),
         };
 
-        let mut items = Vec::new();
+        let file_path = PathBuf::from(uri.path());
+        let exports = self.collect_project_exports(&file_path);
+        let scope = completion::scope_for(&source, offset, cached_ast.as_ref(), &exports);
+        let expected = completion::expected_type(&scope, &source, pos);
+
+        // After a dot: fields of a variable's struct type, else members
+        // of the module the prefix names
+        if let Some(path) = completion::receiver_path(&source, pos) {
+            if let Some(ty) = completion::receiver_type(&scope, &path) {
+                let fields = completion::struct_field_candidates(&ty);
+                let items = completion::rank(fields, expected.as_ref());
+                return Ok(Some(CompletionResponse::Array(items)));
+            }
 
-        // Check if we're after a dot (module member completion)
-        let prefix = text_before_dot(&source, pos);
-        if let Some(module_prefix) = prefix {
-            let file_path = PathBuf::from(uri.path());
-            let exports = self.collect_project_exports(&file_path);
+            let module_prefix = path.join(".");
+            let mut candidates = Vec::new();
             for exp in &exports {
                 let mod_short = exp
                     .module_name
@@ -169,38 +198,53 @@ impl TridentLsp {
                     } else {
                         format!(" -> {}", ret_ty.display())
                     };
-                    items.push(CompletionItem {
+                    let item = CompletionItem {
                         label: bare.to_string(),
                         kind: Some(CompletionItemKind::FUNCTION),
                         detail: Some(format!("fn({}){}", params_str.join(", "), ret)),
                         ..Default::default()
-                    });
+                    };
+                    candidates.push((item, Some(ret_ty.clone())));
                 }
 
                 for (cname, ty, _val) in &exp.constants {
                     let bare = cname.rsplit('.').next().unwrap_or(cname);
-                    items.push(CompletionItem {
+                    let item = CompletionItem {
                         label: bare.to_string(),
                         kind: Some(CompletionItemKind::CONSTANT),
                         detail: Some(ty.display()),
                         ..Default::default()
-                    });
+                    };
+                    candidates.push((item, Some(ty.clone())));
                 }
 
                 for st in &exp.structs {
                     let bare = st.name.rsplit('.').next().unwrap_or(&st.name);
-                    items.push(CompletionItem {
+                    let item = CompletionItem {
                         label: bare.to_string(),
                         kind: Some(CompletionItemKind::STRUCT),
                         detail: Some(format!("struct ({} fields)", st.fields.len())),
                         ..Default::default()
-                    });
+                    };
+                    candidates.push((item, Some(Ty::Struct(st.clone()))));
                 }
             }
 
+            let items = completion::rank(candidates, expected.as_ref());
             return Ok(Some(CompletionResponse::Array(items)));
         }
 
+        // Inside a struct or event literal: the fields not yet written
+        if let Some((name, written)) = completion::literal_context(&source, pos) {
+            if let Some(fields) = completion::literal_field_candidates(&scope, &name, &written) {
+                let items = completion::rank(fields, expected.as_ref());
+                return Ok(Some(CompletionResponse::Array(items)));
+            }
+        }
+
+        let mut items = Vec::new();
+        let mut candidates = completion::variable_candidates(&scope);
+
         // General completions: keywords + builtins + imported module names
         let keywords = [
             "fn", "let", "mut", "const", "struct", "event", "if", "else", "for", "in", "bounded",
@@ -224,13 +268,16 @@ impl TridentLsp {
         }
 
         for (name, detail) in builtin_completions() {
-            items.push(CompletionItem {
+            let ret_ty = scope.functions.get(&name).map(|(_, ret)| ret.clone());
+            let item = CompletionItem {
                 label: name,
                 kind: Some(CompletionItemKind::FUNCTION),
                 detail: Some(detail),
                 ..Default::default()
-            });
+            };
+            candidates.push((item, ret_ty));
         }
+        items.extend(completion::rank(candidates, expected.as_ref()));
 
         if let Ok(file) = crate::parse_source_silent(&source, uri.path()) {
             for use_stmt in &file.uses {
@@ -0,0 +1,97 @@
+//! Code lenses: each function's cost and share of the padded height,
+//! with a command that shows the file's hotspot report.
+
+use tower_lsp::lsp_types::*;
+
+use crate::ast::Item;
+use crate::cost::{CostAnalyzer, ProgramCost};
+
+use super::util::{format_cost_inline, span_to_range};
+
+/// Command the lenses run: shows the hotspot report of the file whose
+/// URI is the first argument.
+pub(super) const SHOW_HOTSPOTS: &str = "trident.showHotspots";
+
+/// Functions listed in the hotspot report.
+const HOTSPOTS: usize = 10;
+
+/// A lens above each function with a body.
+pub(super) fn code_lenses(uri: &Url, source: &str) -> Vec<CodeLens> {
+    let Some(cost) = analyze(source) else {
+        return Vec::new();
+    };
+    let Ok(file) = crate::parse_source_silent(source, "") else {
+        return Vec::new();
+    };
+
+    let mut lenses = Vec::new();
+    for item in &file.items {
+        let Item::Fn(f) = &item.node else {
+            continue;
+        };
+        if f.body.is_none() {
+            continue;
+        }
+        let Some(fc) = cost.functions.iter().find(|c| c.name == f.name.node) else {
+            continue;
+        };
+        let share = match cost.padded_height {
+            0 => 0,
+            height => fc.cost.max_height() * 100 / height,
+        };
+        lenses.push(CodeLens {
+            range: span_to_range(source, f.name.span),
+            command: Some(Command {
+                title: format!(
+                    "{} | {}% of padded height {}",
+                    format_cost_inline(&fc.cost),
+                    share,
+                    cost.padded_height
+                ),
+                command: SHOW_HOTSPOTS.to_string(),
+                arguments: Some(vec![LSPAny::String(uri.to_string())]),
+            }),
+            data: None,
+        });
+    }
+    lenses
+}
+
+/// The full cost report and hotspots of `source`, for `SHOW_HOTSPOTS`.
+pub(super) fn hotspot_report(source: &str) -> Option<String> {
+    let cost = analyze(source)?;
+    Some(format!(
+        "{}\n{}",
+        cost.format_hotspots(HOTSPOTS),
+        cost.format_report()
+    ))
+}
+
+fn analyze(source: &str) -> Option<ProgramCost> {
+    let file = crate::parse_source_silent(source, "").ok()?;
+    Some(CostAnalyzer::default().analyze_file(&file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lens_above_each_function_opens_hotspots() {
+        let source = "program test\nfn helper(x: Field) -> Field {\n  x * x\n}\nfn main() {\n  pub_write(helper(pub_read()))\n}\n";
+        let uri = Url::parse("file:///test.tri").unwrap();
+        let lenses = code_lenses(&uri, source);
+        assert_eq!(lenses.len(), 2);
+        assert_eq!(lenses[0].range.start, Position::new(1, 3));
+        let command = lenses[1].command.as_ref().unwrap();
+        assert_eq!(command.command, SHOW_HOTSPOTS);
+        assert!(command.title.contains("% of padded height"));
+        assert_eq!(
+            command.arguments.as_deref(),
+            Some(&[LSPAny::String(uri.to_string())][..])
+        );
+        assert!(hotspot_report(source)
+            .unwrap()
+            .starts_with("Top 10 cost contributors:"));
+    }
+}
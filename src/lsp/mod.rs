@@ -2,12 +2,15 @@
 
 mod actions;
 mod builtins;
+mod completion;
 mod document;
 mod folding;
 mod hints;
 mod incremental;
 mod indent;
+mod index;
 mod intelligence;
+mod lenses;
 mod project;
 mod references;
 mod selection;
@@ -29,6 +32,8 @@ use util::to_lsp_diagnostic;
 pub(crate) struct TridentLsp {
     pub(crate) client: Client,
     pub(crate) documents: Mutex<BTreeMap<Url, document::DocumentData>>,
+    /// Persisted symbol indexes by project entry point.
+    symbol_indexes: Mutex<BTreeMap<PathBuf, index::ProjectIndex>>,
 }
 
 impl TridentLsp {
@@ -37,7 +42,10 @@ impl TridentLsp {
         let result = crate::check_file_in_project(source, &file_path);
 
         let diagnostics = match result {
-            Ok(()) => Vec::new(),
+            Ok(()) => crate::loop_bound_fixes(source, &file_path.to_string_lossy())
+                .iter()
+                .map(|d| to_lsp_diagnostic(d, source))
+                .collect(),
             Err(errors) => errors
                 .into_iter()
                 .map(|d| to_lsp_diagnostic(&d, source))
@@ -58,6 +66,7 @@ pub async fn run_server() {
     let (service, socket) = LspService::new(|client| TridentLsp {
         client,
         documents: Mutex::new(BTreeMap::new()),
+        symbol_indexes: Mutex::new(BTreeMap::new()),
     });
     Server::new(stdin, stdout, socket).serve(service).await;
 }
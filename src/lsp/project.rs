@@ -6,84 +6,98 @@ use std::path::{Path, PathBuf};
 use tower_lsp::lsp_types::*;
 
 use crate::ast::Item;
-use crate::resolve::resolve_modules;
+use crate::hash::ContentHash;
+use crate::project::BuildContext;
+use crate::resolve::{resolve_modules_with_deps, ModuleInfo};
 use crate::typecheck::{ModuleExports, TypeChecker};
 
 use super::document::DocumentData;
+use super::index::ProjectIndex;
 use super::util::{format_fn_signature, span_to_range};
 use super::TridentLsp;
 
 /// Find the project entry point for a given file.
 pub(super) fn find_project_entry(file_path: &Path) -> PathBuf {
-    let dir = file_path.parent().unwrap_or(Path::new("."));
-    match crate::project::Project::find(dir) {
-        Some(toml_path) => match crate::project::Project::load(&toml_path) {
-            Ok(p) => p.entry,
-            Err(_) => file_path.to_path_buf(),
-        },
-        None => file_path.to_path_buf(),
-    }
+    BuildContext::for_file(file_path).entry
+}
+
+/// Resolve the modules of the project containing `file_path`, including
+/// its locked dependencies.
+pub(super) fn resolve_project_modules(file_path: &Path) -> Option<Vec<ModuleInfo>> {
+    let ctx = BuildContext::for_file(file_path);
+    resolve_modules_with_deps(&ctx.entry, ctx.options.dep_dirs).ok()
 }
 
 impl TridentLsp {
-    /// Build a symbol index mapping names to (uri, range) for go-to-definition.
+    /// Symbol index mapping names to (uri, range) for go-to-definition.
+    ///
+    /// Served from the persisted project index; modules are re-resolved
+    /// and re-parsed only when a source no longer matches its hash.
     pub(super) fn build_symbol_index(&self, file_path: &Path) -> BTreeMap<String, (Url, Range)> {
-        let mut index = BTreeMap::new();
         let entry = find_project_entry(file_path);
+        let source_of = |path: &Path| self.module_source(path);
+
+        let mut indexes = self
+            .symbol_indexes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let index = indexes
+            .entry(entry.clone())
+            .or_insert_with(|| ProjectIndex::load(&entry).unwrap_or(ProjectIndex::empty(entry)));
+        if !index.is_fresh(&source_of) && index.refresh(&source_of) {
+            let _ = index.store();
+        }
+        index.symbol_map()
+    }
 
-        let modules = match resolve_modules(&entry) {
-            Ok(m) => m,
-            Err(_) => return index,
-        };
-
-        for module in &modules {
-            let parsed = match crate::parse_source_silent(
-                &module.source,
-                &module.file_path.to_string_lossy(),
-            ) {
-                Ok(f) => f,
-                Err(_) => continue,
-            };
-
-            let mod_uri = match Url::from_file_path(&module.file_path) {
-                Ok(u) => u,
-                Err(_) => match Url::parse(&format!("file://{}", module.file_path.display())) {
-                    Ok(u) => u,
-                    Err(_) => continue,
-                },
-            };
-            let mod_short = module.name.rsplit('.').next().unwrap_or(&module.name);
-
-            for item in &parsed.items {
-                let (name, name_span) = match &item.node {
-                    Item::Fn(f) => (f.name.node.clone(), f.name.span),
-                    Item::Struct(s) => (s.name.node.clone(), s.name.span),
-                    Item::Const(c) => (c.name.node.clone(), c.name.span),
-                    Item::Event(e) => (e.name.node.clone(), e.name.span),
-                };
-
-                let range = span_to_range(&module.source, name_span);
-                let qualified = format!("{}.{}", mod_short, name);
-                let full_qualified = format!("{}.{}", module.name, name);
-
-                index.insert(name.clone(), (mod_uri.clone(), range));
-                index.insert(qualified, (mod_uri.clone(), range));
-                if full_qualified != format!("{}.{}", mod_short, name) {
-                    index.insert(full_qualified, (mod_uri.clone(), range));
-                }
+    /// Load the stored index of the project containing `file_path` so the
+    /// first lookup of a session does not re-parse unchanged modules.
+    pub(super) fn load_symbol_index(&self, file_path: &Path) {
+        let entry = find_project_entry(file_path);
+        let mut indexes = self
+            .symbol_indexes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let std::collections::btree_map::Entry::Vacant(slot) = indexes.entry(entry) {
+            if let Some(index) = ProjectIndex::load(slot.key()) {
+                slot.insert(index);
             }
         }
+    }
 
-        index
+    /// Patch every loaded index that contains `file_path` with the
+    /// symbols of its current document.
+    pub(super) fn update_symbol_index(
+        &self,
+        file_path: &Path,
+        hash: ContentHash,
+        symbols: Vec<(String, Range)>,
+    ) {
+        let mut indexes = self
+            .symbol_indexes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        for index in indexes.values_mut() {
+            index.update_module(file_path, hash, symbols.clone());
+        }
+    }
+
+    /// Current source of a module: the open document if any, else disk.
+    pub(super) fn module_source(&self, path: &Path) -> Option<String> {
+        let open = Url::from_file_path(path).ok().and_then(|uri| {
+            self.documents
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(&uri)
+                .map(|doc| doc.source.clone())
+        });
+        open.or_else(|| std::fs::read_to_string(path).ok())
     }
 
     /// Collect type-checked exports from all project modules.
     pub(super) fn collect_project_exports(&self, file_path: &Path) -> Vec<ModuleExports> {
-        let entry = find_project_entry(file_path);
-
-        let modules = match resolve_modules(&entry) {
-            Ok(m) => m,
-            Err(_) => return Vec::new(),
+        let Some(modules) = resolve_project_modules(file_path) else {
+            return Vec::new();
         };
 
         let mut all_exports = Vec::new();
@@ -116,8 +130,7 @@ impl TridentLsp {
         file_path: &Path,
         fn_name: &str,
     ) -> Option<crate::cost::TableCost> {
-        let entry = find_project_entry(file_path);
-        let modules = resolve_modules(&entry).ok()?;
+        let modules = resolve_project_modules(file_path)?;
 
         for module in &modules {
             let parsed =
@@ -27,6 +27,7 @@ const TT_NAMESPACE: u32 = 9;
 const TT_EVENT: u32 = 10;
 const TT_MACRO: u32 = 11;
 const TT_ENUM_MEMBER: u32 = 12;
+const TT_STRING: u32 = 13;
 
 // Modifier bit flags — must match TOKEN_MODIFIERS order.
 pub(super) const MOD_DECLARATION: u32 = 1 << 0;
@@ -50,6 +51,7 @@ pub fn token_legend() -> SemanticTokensLegend {
             SemanticTokenType::EVENT,       // 10
             SemanticTokenType::MACRO,       // 11
             SemanticTokenType::ENUM_MEMBER, // 12
+            SemanticTokenType::STRING,      // 13
         ],
         token_modifiers: vec![
             SemanticTokenModifier::DECLARATION,     // bit 0
@@ -204,6 +206,7 @@ fn classify_lexeme(
         }
 
         Lexeme::Integer(_) => Some((TT_NUMBER, 0)),
+        Lexeme::Str(_) => Some((TT_STRING, 0)),
 
         Lexeme::Ident(name) => {
             if let Some((kind, mods)) = name_kinds.get(name.as_str()) {
@@ -231,6 +234,8 @@ fn classify_lexeme(
         | Lexeme::Lt
         | Lexeme::Gt
         | Lexeme::Amp
+        | Lexeme::AmpAmp
+        | Lexeme::PipePipe
         | Lexeme::Caret
         | Lexeme::SlashPercent
         | Lexeme::Eq
@@ -238,7 +243,7 @@ fn classify_lexeme(
         | Lexeme::FatArrow
         | Lexeme::DotDot => Some((TT_OPERATOR, 0)),
 
-        Lexeme::Hash => Some((TT_MACRO, 0)),
+        Lexeme::Hash | Lexeme::Bang => Some((TT_MACRO, 0)),
 
         Lexeme::AsmBlock { .. } => Some((TT_KEYWORD, 0)),
 
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 #[test]
 fn legend_has_all_types() {
     let legend = token_legend();
-    assert_eq!(legend.token_types.len(), 13);
+    assert_eq!(legend.token_types.len(), 14);
     assert_eq!(legend.token_modifiers.len(), 4);
 }
 
@@ -6,7 +6,9 @@ use tower_lsp::LanguageServer;
 
 use super::document::{compute_line_starts, DocumentData};
 use super::util::{position_to_byte_offset, word_at_position};
-use super::{actions, folding, hints, incremental, indent, selection, semantic, TridentLsp};
+use super::{
+    actions, folding, hints, incremental, indent, index, lenses, selection, semantic, TridentLsp,
+};
 
 #[tower_lsp::async_trait]
 impl LanguageServer for TridentLsp {
@@ -44,6 +46,13 @@ impl LanguageServer for TridentLsp {
                 document_highlight_provider: Some(OneOf::Left(true)),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
                 inlay_hint_provider: Some(OneOf::Left(true)),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(false),
+                }),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![lenses::SHOW_HOTSPOTS.to_string()],
+                    ..Default::default()
+                }),
                 folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
                 selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
                 document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
@@ -71,6 +80,14 @@ impl LanguageServer for TridentLsp {
         if let Ok(file) = crate::parse_source_silent(&doc.source, "") {
             doc.name_kinds = semantic::build_name_kinds(&file);
             doc.cached_ast = Some(file);
+            doc.ast_current = true;
+        }
+
+        let file_path = PathBuf::from(uri.path());
+        self.load_symbol_index(&file_path);
+        if let Some(file) = &doc.cached_ast {
+            let symbols = index::item_symbols(&doc.source, file);
+            self.update_symbol_index(&file_path, index::source_hash(&doc.source), symbols);
         }
 
         let diag_source = doc.source.clone();
@@ -84,7 +101,7 @@ impl LanguageServer for TridentLsp {
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri.clone();
 
-        let diag_source = {
+        let (diag_source, symbols) = {
             let mut docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
             let doc = match docs.get_mut(&uri) {
                 Some(d) => d,
@@ -115,6 +132,22 @@ impl LanguageServer for TridentLsp {
                         edit_new_end,
                     );
 
+                    // Re-parse only the edited item when the edit stays
+                    // inside one item body.
+                    if doc.ast_current {
+                        doc.ast_current = doc.cached_ast.as_mut().is_some_and(|ast| {
+                            incremental::incremental_parse(
+                                ast,
+                                &new_source,
+                                &doc.tokens,
+                                &result.tokens,
+                                edit_start,
+                                edit_old_end,
+                                edit_new_end,
+                            )
+                        });
+                    }
+
                     doc.source = new_source;
                     doc.tokens = result.tokens;
                     doc.comments = result.comments;
@@ -122,6 +155,7 @@ impl LanguageServer for TridentLsp {
                 } else {
                     // Full replacement (fallback)
                     doc.source = change.text;
+                    doc.ast_current = false;
                     let (tokens, comments, _) =
                         crate::syntax::lexer::Lexer::new(&doc.source, 0).tokenize();
                     doc.tokens = tokens;
@@ -130,15 +164,29 @@ impl LanguageServer for TridentLsp {
                 }
             }
 
-            // Re-parse and cache AST (cheap for contract-sized files)
-            if let Ok(file) = crate::parse_source_silent(&doc.source, "") {
-                doc.name_kinds = semantic::build_name_kinds(&file);
-                doc.cached_ast = Some(file);
+            // Full re-parse unless every edit was spliced in incrementally
+            if !doc.ast_current {
+                if let Ok(file) = crate::parse_source_silent(&doc.source, "") {
+                    doc.cached_ast = Some(file);
+                    doc.ast_current = true;
+                }
+            }
+            let mut symbols = None;
+            if doc.ast_current {
+                if let Some(file) = &doc.cached_ast {
+                    doc.name_kinds = semantic::build_name_kinds(file);
+                    symbols = Some(index::item_symbols(&doc.source, file));
+                }
             }
 
-            doc.source.clone()
+            (doc.source.clone(), symbols)
         }; // lock dropped here
 
+        if let Some(symbols) = symbols {
+            let file_path = PathBuf::from(uri.path());
+            self.update_symbol_index(&file_path, index::source_hash(&diag_source), symbols);
+        }
+
         self.publish_diagnostics(uri, &diag_source).await;
     }
 
@@ -462,6 +510,55 @@ impl LanguageServer for TridentLsp {
         })
     }
 
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let uri = &params.text_document.uri;
+        let source = match self
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(uri)
+        {
+            Some(doc) => doc.source.clone(),
+            None => return Ok(None),
+        };
+        let result = lenses::code_lenses(uri, &source);
+        Ok(if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        })
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<LSPAny>> {
+        if params.command != lenses::SHOW_HOTSPOTS {
+            return Ok(None);
+        }
+        let Some(uri) = params
+            .arguments
+            .first()
+            .and_then(|arg| arg.as_str())
+            .and_then(|arg| Url::parse(arg).ok())
+        else {
+            return Ok(None);
+        };
+        let source = match self
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&uri)
+        {
+            Some(doc) => doc.source.clone(),
+            None => return Ok(None),
+        };
+        let Some(report) = lenses::hotspot_report(&source) else {
+            return Ok(None);
+        };
+        self.client
+            .show_message(MessageType::INFO, report.clone())
+            .await;
+        Ok(Some(LSPAny::String(report)))
+    }
+
     async fn shutdown(&self) -> Result<()> {
         Ok(())
     }
@@ -146,6 +146,31 @@ pub fn is_ident_char(b: u8) -> bool {
 }
 
 /// Format a `TableCost` as a compact inline string for hover display.
+/// Comment lines and `#[intrinsic(name)]` binding directly above the
+/// definition on `line`. Attributes and `//` comments may interleave; the
+/// first other line ends the doc block.
+pub fn item_doc(source: &str, line: u32) -> (String, Option<String>) {
+    let lines: Vec<&str> = source.lines().take(line as usize).collect();
+    let mut doc = Vec::new();
+    let mut intrinsic = None;
+    for text in lines.iter().rev().map(|l| l.trim()) {
+        if let Some(comment) = text.strip_prefix("//") {
+            doc.push(comment.strip_prefix(' ').unwrap_or(comment));
+        } else if let Some(attr) = text.strip_prefix("#[") {
+            if let Some(args) = attr
+                .strip_suffix(']')
+                .filter(|a| a.starts_with("intrinsic("))
+            {
+                intrinsic = crate::ast::Intrinsic::parse(args).ok().map(|i| i.name);
+            }
+        } else {
+            break;
+        }
+    }
+    doc.reverse();
+    (doc.join("\n"), intrinsic)
+}
+
 pub fn format_cost_inline(cost: &crate::cost::TableCost) -> String {
     let model = crate::cost::create_cost_model("triton");
     let short_names = model.table_short_names();
@@ -171,15 +171,20 @@ fn test_format_fn_signature_no_params() {
     let f = crate::ast::FnDef {
         is_pub: false,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         cfg: None,
         intrinsic: None,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: crate::span::Spanned::dummy("main".to_string()),
         type_params: vec![],
         params: vec![],
         return_ty: None,
+        where_clause: vec![],
         body: None,
     };
     assert_eq!(format_fn_signature(&f), "fn main()");
@@ -190,11 +195,15 @@ fn test_format_fn_signature_with_return() {
     let f = crate::ast::FnDef {
         is_pub: true,
         is_test: false,
+        test_max_cycles: None,
         is_pure: false,
+        is_entry: false,
+        no_spill: false,
         cfg: None,
         intrinsic: None,
         requires: vec![],
         ensures: vec![],
+        allows: vec![],
         name: crate::span::Spanned::dummy("add".to_string()),
         type_params: vec![],
         params: vec![
@@ -208,6 +217,7 @@ fn test_format_fn_signature_with_return() {
             },
         ],
         return_ty: Some(crate::span::Spanned::dummy(crate::ast::Type::Field)),
+        where_clause: vec![],
         body: None,
     };
     assert_eq!(
@@ -336,3 +346,29 @@ fn test_format_cost_inline_hash_dominant() {
         s
     );
 }
+
+// --- item_doc ---
+
+#[test]
+fn test_item_doc_collects_comments_and_intrinsic() {
+    let src = "module m\n\n// One step up.\n// Divines the sibling.\n#[intrinsic(merkle_step)]\npub fn step(i: U32) {\n}\n";
+    let (doc, intrinsic) = item_doc(src, 5);
+    assert_eq!(doc, "One step up.\nDivines the sibling.");
+    assert_eq!(intrinsic.as_deref(), Some("merkle_step"));
+}
+
+#[test]
+fn test_item_doc_stops_at_code() {
+    let src = "module m\nconst X: Field = 1\n#[pure]\nfn f() {\n}\n";
+    let (doc, intrinsic) = item_doc(src, 3);
+    assert!(doc.is_empty());
+    assert!(intrinsic.is_none());
+}
+
+#[test]
+fn test_item_doc_reads_tiered_intrinsic() {
+    let src =
+        "module m\n\n#[intrinsic(tier = 2, name = \"merkle_step\")]\npub fn step(i: U32) {\n}\n";
+    let (_, intrinsic) = item_doc(src, 3);
+    assert_eq!(intrinsic.as_deref(), Some("merkle_step"));
+}
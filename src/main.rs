@@ -2,39 +2,63 @@
 
 mod cli;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 
 use cli::audit::{AuditArgs, EquivArgs};
 use cli::bench::BenchArgs;
+use cli::bindgen::BindgenArgs;
 use cli::build::BuildArgs;
 use cli::check::CheckArgs;
+use cli::clean::CleanArgs;
+use cli::completions::CompletionsArgs;
+use cli::debug::DebugArgs;
+use cli::decode_events::DecodeEventsArgs;
 use cli::deploy::DeployArgs;
 use cli::deps::DepsAction;
 use cli::doc::DocArgs;
+use cli::encode_input::EncodeInputArgs;
+use cli::fix::FixArgs;
 use cli::fmt::FmtArgs;
 use cli::generate::GenerateArgs;
+use cli::grep::GrepArgs;
 use cli::hash::HashArgs;
 use cli::init::InitArgs;
+use cli::man::ManArgs;
 use cli::package::PackageArgs;
 use cli::prove::ProveArgs;
 use cli::registry::RegistryAction;
+use cli::repl::ReplArgs;
 use cli::run::RunArgs;
 use cli::store::StoreAction;
+use cli::synth_tests::SynthTestsArgs;
 use cli::test::TestArgs;
+use cli::trace_diff::TraceDiffArgs;
 use cli::train::TrainArgs;
 use cli::tree_sitter::TreeSitterArgs;
+use cli::upgrade_diff::UpgradeDiffArgs;
 use cli::verify::VerifyProofArgs;
 use cli::view::ViewArgs;
+use cli::witness::WitnessAction;
 
 #[derive(Parser)]
 #[command(
     name = "trident",
     version,
-    about = "Trident compiler — Correct. Bounded. Provable."
+    about = "Trident compiler — Correct. Bounded. Provable.",
+    disable_version_flag = true,
+    arg_required_else_help = true
 )]
 struct Cli {
     #[command(subcommand)]
-    command: Command,
+    command: Option<Command>,
+    /// Print version (with --verbose: commit, cargo features, targets)
+    #[arg(short = 'V', long)]
+    version: bool,
+    /// With --version, print the full build description
+    #[arg(long, requires = "version")]
+    verbose: bool,
+    #[command(flatten)]
+    diagnostics: cli::DiagnosticArgs,
 }
 
 #[derive(Subcommand)]
@@ -43,17 +67,23 @@ enum Command {
     Init(InitArgs),
     /// Compile a .tri file (or project) to TASM
     Build(BuildArgs),
+    /// Remove target/ and the compilation cache
+    Clean(CleanArgs),
     /// Type-check without emitting TASM
     Check(CheckArgs),
     /// Format .tri source files
     Fmt(FmtArgs),
+    /// Apply the fixes suggested by diagnostics (like `cargo fix`)
+    Fix(FixArgs),
     /// Run #[test] functions
     Test(TestArgs),
     /// Generate documentation with cost annotations
     Doc(DocArgs),
+    /// Generate host-language bindings for a compiled program
+    Bindgen(BindgenArgs),
     /// Audit contracts using symbolic execution + algebraic solver
     Audit(AuditArgs),
-    /// Show content hashes of functions (BLAKE3)
+    /// Show content hashes of functions (Poseidon2)
     Hash(HashArgs),
     /// Run benchmarks: compare Trident output vs hand-written TASM
     Bench(BenchArgs),
@@ -61,8 +91,12 @@ enum Command {
     Train(TrainArgs),
     /// Generate code scaffold from spec annotations
     Generate(GenerateArgs),
+    /// Generate #[test] functions from synthesized pre/postconditions
+    SynthTests(SynthTestsArgs),
     /// View a function definition (pretty-printed from AST)
     View(ViewArgs),
+    /// Search function bodies for an expression pattern (structural grep)
+    Grep(GrepArgs),
     /// Hash-keyed definitions store
     Store {
         #[command(subcommand)]
@@ -75,6 +109,11 @@ enum Command {
     },
     /// Check semantic equivalence of two functions
     Equiv(EquivArgs),
+    /// Synthesize witness generators for divine inputs
+    Witness {
+        #[command(subcommand)]
+        action: WitnessAction,
+    },
     /// Manage project dependencies
     Deps {
         #[command(subcommand)]
@@ -84,8 +123,20 @@ enum Command {
     Package(PackageArgs),
     /// Deploy a program to a registry server or blockchain node
     Deploy(DeployArgs),
+    /// Compare a project against a deployed artifact before upgrading it
+    UpgradeDiff(UpgradeDiffArgs),
     /// Run a compiled program via a warrior (target-specific runtime)
     Run(RunArgs),
+    /// Step through compiled TASM with breakpoints and a source view
+    Debug(DebugArgs),
+    /// Evaluate expressions interactively, optionally in a project's context
+    Repl(ReplArgs),
+    /// Run two TASM files on the same input and report where they diverge
+    TraceDiff(TraceDiffArgs),
+    /// Decode a program's public output into its events
+    DecodeEvents(DecodeEventsArgs),
+    /// Encode typed input values into public and secret field elements
+    EncodeInput(EncodeInputArgs),
     /// Generate a proof of correct execution via a warrior
     Prove(ProveArgs),
     /// Verify a proof via a warrior (target-specific verifier)
@@ -94,35 +145,67 @@ enum Command {
     TreeSitter(TreeSitterArgs),
     /// Start the Language Server Protocol server
     Lsp,
+    /// Print a shell completion script (bash, zsh, fish, elvish, powershell)
+    Completions(CompletionsArgs),
+    /// Print the trident(1) man page, or write all man pages to a directory
+    Man(ManArgs),
 }
 
 fn main() {
     let cli = Cli::parse();
+    cli.diagnostics.apply();
+
+    if cli.version {
+        let info = trident::build_info();
+        if cli.verbose {
+            print!("{}", info.to_text());
+        } else {
+            println!("trident {}", info.version);
+        }
+        return;
+    }
+    let Some(command) = cli.command else {
+        return;
+    };
 
-    match cli.command {
+    match command {
         Command::Init(args) => cli::init::cmd_init(args),
         Command::Build(args) => cli::build::cmd_build(args),
+        Command::Clean(args) => cli::clean::cmd_clean(args),
         Command::Check(args) => cli::check::cmd_check(args),
         Command::Fmt(args) => cli::fmt::cmd_fmt(args),
+        Command::Fix(args) => cli::fix::cmd_fix(args),
         Command::Test(args) => cli::test::cmd_test(args),
         Command::Doc(args) => cli::doc::cmd_doc(args),
+        Command::Bindgen(args) => cli::bindgen::cmd_bindgen(args),
         Command::Audit(args) => cli::audit::cmd_audit(args),
         Command::Hash(args) => cli::hash::cmd_hash(args),
         Command::Bench(args) => cli::bench::cmd_bench(args),
         Command::Train(args) => cli::train::cmd_train(args),
         Command::Generate(args) => cli::generate::cmd_generate(args),
+        Command::SynthTests(args) => cli::synth_tests::cmd_synth_tests(args),
         Command::View(args) => cli::view::cmd_view(args),
+        Command::Grep(args) => cli::grep::cmd_grep(args),
         Command::Store { action } => cli::store::cmd_store(action),
         Command::Atlas { action } => cli::registry::cmd_registry(action),
         Command::Equiv(args) => cli::audit::cmd_equiv(args),
+        Command::Witness { action } => cli::witness::cmd_witness(action),
         Command::Deps { action } => cli::deps::cmd_deps(action),
         Command::Package(args) => cli::package::cmd_package(args),
         Command::Deploy(args) => cli::deploy::cmd_deploy(args),
+        Command::UpgradeDiff(args) => cli::upgrade_diff::cmd_upgrade_diff(args),
         Command::Run(args) => cli::run::cmd_run(args),
+        Command::Debug(args) => cli::debug::cmd_debug(args),
+        Command::Repl(args) => cli::repl::cmd_repl(args),
+        Command::TraceDiff(args) => cli::trace_diff::cmd_trace_diff(args),
+        Command::DecodeEvents(args) => cli::decode_events::cmd_decode_events(args),
+        Command::EncodeInput(args) => cli::encode_input::cmd_encode_input(args),
         Command::Prove(args) => cli::prove::cmd_prove(args),
         Command::Verify(args) => cli::verify::cmd_verify_proof(args),
         Command::TreeSitter(args) => cli::tree_sitter::cmd_tree_sitter(args),
         Command::Lsp => cmd_lsp(),
+        Command::Completions(args) => cli::completions::cmd_completions(args, Cli::command()),
+        Command::Man(args) => cli::man::cmd_man(args, Cli::command()),
     }
 }
 
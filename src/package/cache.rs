@@ -11,8 +11,10 @@
 //! ~/.trident/cache/
 //! ├── compile/
 //! │   └── <source_hash_hex>.<target>.tasm
-//! └── verify/
-//!     └── <source_hash_hex>.json
+//! ├── verify/
+//! │   └── <source_hash_hex>.json
+//! └── lsp/
+//!     └── <entry_path_hash_hex>.index   (LSP symbol index, rewritten in place)
 //! ```
 //!
 //! Cache entries are append-only: once written, never modified. A hash
@@ -15,6 +15,9 @@
 //! - Changing any dependency changes the hash of all dependents.
 //! - Renaming a function does not change its hash.
 //! - Adding/removing comments or formatting does not change the hash.
+//!
+//! `hash_file_raw` is the secondary, exact-text hash: it records which
+//! spelling of a definition was seen, for provenance.
 
 use std::collections::BTreeMap;
 
@@ -24,7 +27,8 @@ const HASH_VERSION: u8 = 1;
 
 // ─── Content Hash ──────────────────────────────────────────────────
 
-/// A 256-bit BLAKE3 content hash.
+/// A 256-bit Poseidon2 content hash: four Goldilocks field elements,
+/// each stored as 8 little-endian bytes.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ContentHash(pub [u8; 32]);
 
@@ -56,6 +60,19 @@ impl ContentHash {
         Some(ContentHash(bytes))
     }
 
+    /// The four Goldilocks field elements of the hash, in squeeze order.
+    ///
+    /// This is the form a Trident program recomputes with
+    /// `std.crypto.poseidon2`, so a contract can compare a dependency
+    /// against its registry hash on-chain.
+    pub fn to_fields(&self) -> [u64; 4] {
+        let mut fields = [0u64; 4];
+        for (i, chunk) in self.0.chunks_exact(8).enumerate() {
+            fields[i] = u64::from_le_bytes(chunk.try_into().expect("8-byte chunk"));
+        }
+        fields
+    }
+
     /// Display as short base-32 (8 characters, 40 bits).
     pub fn to_short(&self) -> String {
         // Take first 5 bytes (40 bits), encode as base-32
@@ -100,6 +117,30 @@ pub fn hash_file(file: &File) -> BTreeMap<String, ContentHash> {
     Normalizer::hash_file(file)
 }
 
+/// Hash the exact source text of every function in a file, returning
+/// name → hash map.
+///
+/// Unlike `hash_file`, any byte of the definition counts: formatting,
+/// comments inside the body, and local names. Two definitions share a
+/// content hash when they compute the same thing; they share a raw hash
+/// only when they were written identically, which pins down exactly
+/// which text a stored definition came from.
+pub fn hash_file_raw(file: &File, source: &str) -> BTreeMap<String, ContentHash> {
+    file.items
+        .iter()
+        .filter_map(|item| match &item.node {
+            Item::Fn(func) => {
+                let text = source.get(item.span.start as usize..item.span.end as usize)?;
+                Some((
+                    func.name.node.clone(),
+                    ContentHash(crate::poseidon2::hash_bytes(text.as_bytes())),
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 /// Hash a complete file's content (all items serialized together).
 /// Uses Poseidon2 for SNARK-friendly file-level content addressing.
 pub fn hash_file_content(file: &File) -> ContentHash {
@@ -46,6 +46,11 @@ pub(super) const TAG_XFIELD_MUL: u8 = 0x21;
 pub(super) const TAG_ASM: u8 = 0x22;
 pub(super) const TAG_EXPR_STMT: u8 = 0x23;
 pub(super) const TAG_STRUCT_PAT: u8 = 0x24;
+pub(super) const TAG_IF_EXPR: u8 = 0x25;
+pub(super) const TAG_AND: u8 = 0x26;
+pub(super) const TAG_OR: u8 = 0x27;
+pub(super) const TAG_SOME_PAT: u8 = 0x28;
+pub(super) const TAG_NONE_PAT: u8 = 0x29;
 
 // Type tags
 pub(super) const TAG_TY_FIELD: u8 = 0x80;
@@ -56,6 +61,9 @@ pub(super) const TAG_TY_TUPLE: u8 = 0x84;
 pub(super) const TAG_TY_DIGEST: u8 = 0x86;
 pub(super) const TAG_TY_XFIELD: u8 = 0x87;
 pub(super) const TAG_TY_NAMED: u8 = 0x88;
+pub(super) const TAG_TY_NAMED_TUPLE: u8 = 0x89;
+pub(super) const TAG_TY_OPTION: u8 = 0x8A;
+pub(super) const TAG_TY_BYTES: u8 = 0x8B;
 
 // Version byte for hash stability
 
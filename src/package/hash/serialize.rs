@@ -48,6 +48,22 @@ impl Normalizer {
                     self.serialize_type(elem);
                 }
             }
+            Type::NamedTuple(components) => {
+                self.write_u8(TAG_TY_NAMED_TUPLE);
+                self.write_u16(components.len() as u16);
+                for (name, ty) in components {
+                    self.write_str(name);
+                    self.serialize_type(ty);
+                }
+            }
+            Type::Option(inner) => {
+                self.write_u8(TAG_TY_OPTION);
+                self.serialize_type(inner);
+            }
+            Type::Bytes(size) => {
+                self.write_u8(TAG_TY_BYTES);
+                self.serialize_array_size(size);
+            }
             Type::Named(path) => {
                 self.write_u8(TAG_TY_NAMED);
                 self.write_str(&path.as_dotted());
@@ -184,8 +200,11 @@ impl Normalizer {
                 self.serialize_expr(&expr.node);
                 self.write_u16(arms.len() as u16);
                 for arm in arms {
+                    // Pattern bindings are scoped to their arm.
+                    let saved = self.env.save();
                     self.serialize_match_pattern(&arm.pattern.node);
                     self.serialize_block(&arm.body.node);
+                    self.env.restore(saved);
                 }
             }
         }
@@ -204,6 +223,14 @@ impl Normalizer {
             MatchPattern::Wildcard => {
                 self.write_u8(0xFF); // wildcard marker
             }
+            MatchPattern::Some(binding) => {
+                self.write_u8(TAG_SOME_PAT);
+                let idx = self.env.push(&binding.node);
+                self.write_u16(idx);
+            }
+            MatchPattern::None => {
+                self.write_u8(TAG_NONE_PAT);
+            }
             MatchPattern::Struct { name, fields } => {
                 self.write_u8(TAG_STRUCT_PAT);
                 self.write_str(&name.node);
@@ -213,7 +240,8 @@ impl Normalizer {
                     match &spf.pattern.node {
                         FieldPattern::Binding(v) => {
                             self.write_u8(0x01);
-                            self.write_str(v);
+                            let idx = self.env.push(v);
+                            self.write_u16(idx);
                         }
                         FieldPattern::Literal(Literal::Integer(n)) => {
                             self.write_u8(TAG_FIELD_LIT);
@@ -234,17 +262,7 @@ impl Normalizer {
 
     pub(crate) fn serialize_place(&mut self, place: &Place) {
         match place {
-            Place::Var(name) => {
-                if let Some(idx) = self.env.lookup(name) {
-                    self.write_u8(TAG_VAR);
-                    self.write_u16(idx);
-                } else {
-                    // Unknown variable — use name
-                    self.write_u8(TAG_VAR);
-                    self.write_u16(0xFFFF);
-                    self.write_str(name);
-                }
-            }
+            Place::Var(name) => self.serialize_var(name),
             Place::FieldAccess(base, field) => {
                 self.write_u8(TAG_FIELD_ACCESS);
                 self.serialize_place(&base.node);
@@ -258,6 +276,30 @@ impl Normalizer {
         }
     }
 
+    /// A variable, possibly with field accesses (`p.x`, which the parser
+    /// keeps as one dotted name). A local root serializes like the
+    /// equivalent `FieldAccess` chain, so its name never reaches the hash.
+    pub(crate) fn serialize_var(&mut self, name: &str) {
+        let mut parts = name.split('.');
+        let root = parts.next().unwrap_or(name);
+        if let Some(idx) = self.env.lookup(root) {
+            let fields: Vec<&str> = parts.collect();
+            for _ in &fields {
+                self.write_u8(TAG_FIELD_ACCESS);
+            }
+            self.write_u8(TAG_VAR);
+            self.write_u16(idx);
+            for field in fields {
+                self.write_str(field);
+            }
+        } else {
+            // Free variable (e.g., global constant) — use name
+            self.write_u8(TAG_VAR);
+            self.write_u16(0xFFFF);
+            self.write_str(name);
+        }
+    }
+
     // ─── Expression Serialization ──────────────────────────────
 
     pub(crate) fn serialize_expr(&mut self, expr: &Expr) {
@@ -270,17 +312,7 @@ impl Normalizer {
                 self.write_u8(TAG_BOOL_LIT);
                 self.write_u8(if *b { 1 } else { 0 });
             }
-            Expr::Var(name) => {
-                if let Some(idx) = self.env.lookup(name) {
-                    self.write_u8(TAG_VAR);
-                    self.write_u16(idx);
-                } else {
-                    // Free variable (e.g., global constant) — use name
-                    self.write_u8(TAG_VAR);
-                    self.write_u16(0xFFFF);
-                    self.write_str(name);
-                }
-            }
+            Expr::Var(name) => self.serialize_var(name),
             Expr::BinOp { op, lhs, rhs } => {
                 let tag = match op {
                     BinOp::Add => TAG_ADD,
@@ -289,6 +321,8 @@ impl Normalizer {
                     BinOp::Lt => TAG_LT,
                     BinOp::BitAnd => TAG_BIT_AND,
                     BinOp::BitXor => TAG_BIT_XOR,
+                    BinOp::And => TAG_AND,
+                    BinOp::Or => TAG_OR,
                     BinOp::DivMod => TAG_DIV_MOD,
                     BinOp::XFieldMul => TAG_XFIELD_MUL,
                 };
@@ -369,6 +403,16 @@ impl Normalizer {
                     self.serialize_expr(&elem.node);
                 }
             }
+            Expr::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                self.write_u8(TAG_IF_EXPR);
+                self.serialize_expr(&cond.node);
+                self.serialize_block(&then_block.node);
+                self.serialize_block(&else_block.node);
+            }
         }
     }
 }
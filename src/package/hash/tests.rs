@@ -44,6 +44,16 @@ fn test_hash_display() {
     assert_eq!(hash.to_short().len(), 8);
 }
 
+#[test]
+fn test_to_fields_matches_poseidon2_squeeze() {
+    let data = b"fn add(a: Field, b: Field) -> Field";
+    let mut hasher = crate::poseidon2::Poseidon2Hasher::new();
+    hasher.absorb_bytes(data);
+    let squeezed = hasher.finalize_4().map(|e| e.0);
+    let hash = ContentHash(crate::poseidon2::hash_bytes(data));
+    assert_eq!(hash.to_fields(), squeezed);
+}
+
 #[test]
 fn test_hash_deterministic() {
     let f = parse_file(
@@ -100,3 +110,27 @@ fn test_empty_fn_hash() {
     let h = hash_file(&f);
     assert_ne!(h["main"], ContentHash::zero());
 }
+
+#[test]
+fn test_local_names_in_patterns_and_fields_do_not_affect_hash() {
+    let f1 = parse_file("program test\nstruct P {\n    x: Field,\n}\nfn f(p: P, o: Option<Field>) -> Field {\n    let q: P = p\n    match o {\n        Some(v) => { pub_write(v) }\n        None => { }\n    }\n    q.x\n}\nfn main() { }\n");
+    let f2 = parse_file("program test\nstruct P {\n    x: Field,\n}\nfn f(a: P, b: Option<Field>) -> Field {\n    let point: P = a\n    match b {\n        Some(value) => { pub_write(value) }\n        None => { }\n    }\n    point.x\n}\nfn main() { }\n");
+    assert_eq!(hash_file(&f1)["f"], hash_file(&f2)["f"]);
+
+    // Which local the field is read from still matters.
+    let f3 = parse_file("program test\nstruct P {\n    x: Field,\n}\nfn f(p: P, o: Option<Field>) -> Field {\n    let q: P = p\n    match o {\n        Some(v) => { pub_write(v) }\n        None => { }\n    }\n    p.x\n}\nfn main() { }\n");
+    assert_ne!(hash_file(&f1)["f"], hash_file(&f3)["f"]);
+}
+
+#[test]
+fn test_raw_hash_tracks_exact_text() {
+    let s1 = "program test\nfn add(a: Field, b: Field) -> Field { a + b }\nfn main() { }\n";
+    let s2 = "program test\nfn add(x: Field, y: Field) -> Field {\n    x + y\n}\nfn main() { }\n";
+    let (f1, f2) = (parse_file(s1), parse_file(s2));
+    assert_eq!(hash_file(&f1)["add"], hash_file(&f2)["add"]);
+
+    let (r1, r2) = (hash_file_raw(&f1, s1), hash_file_raw(&f2, s2));
+    assert_ne!(r1["add"], r2["add"]);
+    assert_eq!(r1["main"], r2["main"]);
+    assert_eq!(r1, hash_file_raw(&parse_file(s1), s1));
+}
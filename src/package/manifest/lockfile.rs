@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 use std::path::Path;
 
 use super::parse::parse_inline_table;
-use super::{LockedDep, Lockfile};
+use super::{LockedDep, LockedLibrary, Lockfile};
 
 // ─── Lockfile I/O ──────────────────────────────────────────────────
 
@@ -13,13 +13,17 @@ use super::{LockedDep, Lockfile};
 /// # trident.lock — DO NOT EDIT MANUALLY
 /// [lock]
 /// name = { hash = "abc...", source = "registry:https://..." }
+///
+/// [libraries]
+/// "std.crypto.hash" = { hash = "def...", source = "std" }
 /// ```
 pub fn load_lockfile(path: &Path) -> Result<Lockfile, String> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| format!("cannot read lockfile '{}': {}", path.display(), e))?;
 
     let mut locked: BTreeMap<String, LockedDep> = BTreeMap::new();
-    let mut in_lock_section = false;
+    let mut libraries: BTreeMap<String, LockedLibrary> = BTreeMap::new();
+    let mut section = String::new();
 
     for line in content.lines() {
         let trimmed = line.trim();
@@ -27,36 +31,48 @@ pub fn load_lockfile(path: &Path) -> Result<Lockfile, String> {
             continue;
         }
         if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            let section = trimmed[1..trimmed.len() - 1].trim();
-            in_lock_section = section == "lock";
-            continue;
-        }
-        if !in_lock_section {
+            section = trimmed[1..trimmed.len() - 1].trim().to_string();
             continue;
         }
 
         if let Some((key, value)) = trimmed.split_once('=') {
-            let name = key.trim().to_string();
+            let name = key.trim().trim_matches('"').to_string();
             let value = value.trim();
+            if !value.starts_with('{') {
+                continue;
+            }
+            let inner = value.trim_start_matches('{').trim_end_matches('}').trim();
+            let fields = parse_inline_table(inner);
+            let hash = fields.get("hash").cloned().unwrap_or_default();
+            let source = fields.get("source").cloned().unwrap_or_default();
 
-            if value.starts_with('{') {
-                let inner = value.trim_start_matches('{').trim_end_matches('}').trim();
-                let fields = parse_inline_table(inner);
-                let hash = fields.get("hash").cloned().unwrap_or_default();
-                let source = fields.get("source").cloned().unwrap_or_default();
-                locked.insert(
-                    name.clone(),
-                    LockedDep {
-                        name: name.clone(),
-                        hash,
-                        source,
-                    },
-                );
+            match section.as_str() {
+                "lock" => {
+                    locked.insert(
+                        name.clone(),
+                        LockedDep {
+                            name: name.clone(),
+                            hash,
+                            source,
+                        },
+                    );
+                }
+                "libraries" => {
+                    libraries.insert(
+                        name.clone(),
+                        LockedLibrary {
+                            module: name,
+                            hash,
+                            source,
+                        },
+                    );
+                }
+                _ => {}
             }
         }
     }
 
-    Ok(Lockfile { locked })
+    Ok(Lockfile { locked, libraries })
 }
 
 /// Save a lockfile to disk.
@@ -76,6 +92,75 @@ pub fn save_lockfile(path: &Path, lockfile: &Lockfile) -> Result<(), String> {
         ));
     }
 
+    if !lockfile.libraries.is_empty() {
+        out.push_str("\n[libraries]\n");
+        for (module, lib) in &lockfile.libraries {
+            out.push_str(&format!(
+                "\"{}\" = {{ hash = \"{}\", source = \"{}\" }}\n",
+                module, lib.hash, lib.source,
+            ));
+        }
+    }
+
     std::fs::write(path, &out)
         .map_err(|e| format!("cannot write lockfile '{}': {}", path.display(), e))
 }
+
+// ─── Library Pins ──────────────────────────────────────────────────
+
+/// A bundled library module whose content no longer matches its pin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LibraryMismatch {
+    pub module: String,
+    pub source: String,
+    /// Hash recorded in the lockfile.
+    pub locked: String,
+    /// Hash of the module the compiler resolved.
+    pub found: String,
+}
+
+/// Check the library modules a build `used` against the lockfile pins.
+///
+/// Modules without a pin are added. With `update`, the pins are replaced by
+/// exactly the modules in `used`, accepting any changes. Returns whether the
+/// lockfile changed and needs saving, or every mismatched module.
+pub fn pin_libraries(
+    lockfile: &mut Lockfile,
+    used: &[LockedLibrary],
+    update: bool,
+) -> Result<bool, Vec<LibraryMismatch>> {
+    if update {
+        let pinned: BTreeMap<String, LockedLibrary> = used
+            .iter()
+            .map(|lib| (lib.module.clone(), lib.clone()))
+            .collect();
+        let changed = pinned != lockfile.libraries;
+        lockfile.libraries = pinned;
+        return Ok(changed);
+    }
+
+    let mismatches: Vec<LibraryMismatch> = used
+        .iter()
+        .filter_map(|lib| {
+            let pin = lockfile.libraries.get(&lib.module)?;
+            (pin.hash != lib.hash).then(|| LibraryMismatch {
+                module: lib.module.clone(),
+                source: lib.source.clone(),
+                locked: pin.hash.clone(),
+                found: lib.hash.clone(),
+            })
+        })
+        .collect();
+    if !mismatches.is_empty() {
+        return Err(mismatches);
+    }
+
+    let mut changed = false;
+    for lib in used {
+        if !lockfile.libraries.contains_key(&lib.module) {
+            lockfile.libraries.insert(lib.module.clone(), lib.clone());
+            changed = true;
+        }
+    }
+    Ok(changed)
+}
@@ -2,13 +2,21 @@
 //!
 //! Parses `[dependencies]` from `trident.toml`, manages a lockfile
 //! (`trident.lock`), and caches dependency sources under `.trident/deps/`.
+//! The lockfile also pins the content hash of every bundled library module
+//! (`std.*`, `os.*`, `vm.*`) a build uses, so a compiler upgrade cannot
+//! silently change the program.
 //!
-//! Three dependency kinds:
+//! Four dependency kinds:
 //!   - **Hash** — pinned by a 64-hex-char BLAKE3 content hash.
 //!   - **Registry** — resolved via a `RegistryClient` by name.
+//!   - **Onchain** — pinned by the Poseidon2 digest a chain anchored,
+//!     looked up through an `AnchorLedger`.
 //!   - **Path** — local filesystem, re-read every build.
+//!
+//! `trident deps vendor` copies all locked sources into `vendor/`; projects
+//! with `vendor = true` in `[project]` resolve dependencies from there first.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 
 // ─── Data Types ────────────────────────────────────────────────────
@@ -20,6 +28,8 @@ pub enum Dependency {
     Hash { hash: String },
     /// Resolved via a registry by name.
     Registry { name: String, registry: String },
+    /// Pinned by the source digest anchored on `chain`.
+    Onchain { digest: String, chain: String },
     /// Local filesystem path.
     Path { path: PathBuf },
 }
@@ -29,28 +39,44 @@ pub enum Dependency {
 pub struct LockedDep {
     pub name: String,
     pub hash: String,
-    pub source: String, // "registry:<url>", "path:<relative>", "hash"
+    pub source: String, // "registry:<url>", "onchain:<chain>:<tx>", "path:<relative>", "hash"
+}
+
+/// A pinned bundled library module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockedLibrary {
+    /// Module name (`std.crypto.hash`).
+    pub module: String,
+    /// Content hash of the module AST (hex).
+    pub hash: String,
+    pub source: String, // "std", "os", "vm"
 }
 
 /// Package manifest: parsed `[dependencies]` from trident.toml.
 #[derive(Clone, Debug, Default)]
 pub struct Manifest {
     pub dependencies: BTreeMap<String, Dependency>,
+    /// Dependencies declared `optional = true`, enabled only through `[features]`.
+    pub optional: BTreeSet<String>,
 }
 
 /// Lock file contents.
 #[derive(Clone, Debug, Default)]
 pub struct Lockfile {
     pub locked: BTreeMap<String, LockedDep>,
+    /// Bundled library pins, keyed by module name.
+    pub libraries: BTreeMap<String, LockedLibrary>,
 }
 
 mod lockfile;
 mod parse;
 mod resolve;
+mod vendor;
 
-pub use lockfile::{load_lockfile, save_lockfile};
+pub use lockfile::{load_lockfile, pin_libraries, save_lockfile, LibraryMismatch};
 pub use parse::parse_dependencies;
 pub use resolve::{dep_source_path, dependency_search_paths, resolve_dependencies};
+pub use vendor::{vendor_dependencies, vendor_source_path, vendored_search_paths, VENDOR_DIR};
 
 #[cfg(test)]
 mod tests;
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 
 use crate::hash::ContentHash;
@@ -12,9 +12,14 @@ use super::{Dependency, Manifest};
 /// Handles three forms:
 ///   name = "64hexchars"                          -> Hash dep
 ///   name = { name = "x", registry = "url" }      -> Registry dep
+///   name = { onchain = "64hexchars", chain = "c" } -> Onchain dep
 ///   name = { path = "relative/path" }             -> Path dep
+///
+/// Inline tables may add `optional = true` to leave the dependency out of
+/// builds unless a feature enables it.
 pub fn parse_dependencies(toml_content: &str) -> Manifest {
     let mut deps: BTreeMap<String, Dependency> = BTreeMap::new();
+    let mut optional: BTreeSet<String> = BTreeSet::new();
     let mut in_deps_section = false;
 
     for line in toml_content.lines() {
@@ -42,6 +47,9 @@ pub fn parse_dependencies(toml_content: &str) -> Manifest {
                 // Inline table: parse key-value pairs inside braces.
                 let inner = value.trim_start_matches('{').trim_end_matches('}').trim();
                 let fields = parse_inline_table(inner);
+                if fields.get("optional").map(String::as_str) == Some("true") {
+                    optional.insert(key.to_string());
+                }
 
                 if let Some(path_val) = fields.get("path") {
                     deps.insert(
@@ -50,6 +58,16 @@ pub fn parse_dependencies(toml_content: &str) -> Manifest {
                             path: PathBuf::from(path_val),
                         },
                     );
+                } else if let Some(digest) = fields.get("onchain") {
+                    if is_hex_hash(digest) {
+                        deps.insert(
+                            key.to_string(),
+                            Dependency::Onchain {
+                                digest: digest.clone(),
+                                chain: fields.get("chain").cloned().unwrap_or_default(),
+                            },
+                        );
+                    }
                 } else if let Some(reg_name) = fields.get("name") {
                     let registry = fields.get("registry").cloned().unwrap_or_default();
                     deps.insert(
@@ -75,7 +93,10 @@ pub fn parse_dependencies(toml_content: &str) -> Manifest {
         }
     }
 
-    Manifest { dependencies: deps }
+    Manifest {
+        dependencies: deps,
+        optional,
+    }
 }
 
 /// Parse a TOML inline table body: `name = "x", registry = "url"`.
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-use crate::registry::{PullResult, RegistryClient};
+use crate::registry::{AnchorLedger, PullResult, RegistryClient};
 
 use super::{Dependency, LockedDep, Lockfile, Manifest};
 
@@ -54,11 +54,13 @@ pub(super) fn cache_dependency(
 /// populate the cache, and produce/update the lockfile.
 ///
 /// `default_registry` is the fallback registry URL when not specified per-dep.
+/// On-chain dependencies not yet cached are looked up through `ledger`.
 pub fn resolve_dependencies(
     project_root: &Path,
     manifest: &Manifest,
     existing_lock: &Option<Lockfile>,
     default_registry: &str,
+    ledger: Option<&dyn AnchorLedger>,
 ) -> Result<Lockfile, String> {
     let mut locked: BTreeMap<String, LockedDep> = BTreeMap::new();
 
@@ -84,13 +86,28 @@ pub fn resolve_dependencies(
                     &mut locked,
                 )?;
             }
+            Dependency::Onchain { digest, chain } => {
+                resolve_onchain_dep(
+                    project_root,
+                    dep_name,
+                    digest,
+                    chain,
+                    existing_lock,
+                    ledger,
+                    &mut locked,
+                )?;
+            }
             Dependency::Path { path } => {
                 resolve_path_dep(project_root, dep_name, path, &mut locked)?;
             }
         }
     }
 
-    Ok(Lockfile { locked })
+    let libraries = existing_lock
+        .as_ref()
+        .map(|lock| lock.libraries.clone())
+        .unwrap_or_default();
+    Ok(Lockfile { locked, libraries })
 }
 
 fn resolve_hash_dep(
@@ -174,6 +191,49 @@ fn resolve_registry_dep(
     Ok(())
 }
 
+pub(super) fn resolve_onchain_dep(
+    project_root: &Path,
+    dep_name: &str,
+    digest: &str,
+    chain: &str,
+    existing_lock: &Option<Lockfile>,
+    ledger: Option<&dyn AnchorLedger>,
+    locked: &mut BTreeMap<String, LockedDep>,
+) -> Result<(), String> {
+    let cached = dep_source_path(project_root, digest);
+    let previous = existing_lock
+        .as_ref()
+        .and_then(|lf| lf.locked.get(dep_name))
+        .filter(|ld| ld.hash == digest);
+    let source_desc = match previous {
+        // Already fetched and checked against the anchor.
+        Some(ld) if cached.exists() => ld.source.clone(),
+        _ => {
+            let ledger = ledger.ok_or_else(|| {
+                format!(
+                    "dep '{}': no ledger to look up digest {} on '{}'",
+                    dep_name, digest, chain
+                )
+            })?;
+            let (anchor, pull) = crate::registry::pull_anchored(ledger, chain, digest)
+                .map_err(|e| format!("cannot fetch dep '{}': {}", dep_name, e))?;
+            let source_desc = format!("onchain:{}:{}", chain, anchor.tx);
+            cache_dependency(project_root, digest, &pull.source, dep_name, &source_desc)?;
+            source_desc
+        }
+    };
+
+    locked.insert(
+        dep_name.to_string(),
+        LockedDep {
+            name: dep_name.to_string(),
+            hash: digest.to_string(),
+            source: source_desc,
+        },
+    );
+    Ok(())
+}
+
 pub(super) fn resolve_path_dep(
     project_root: &Path,
     dep_name: &str,
@@ -182,27 +242,13 @@ pub(super) fn resolve_path_dep(
 ) -> Result<(), String> {
     let abs_path = project_root.join(rel_path);
 
-    // Try both the path as given and with a .tri extension.
-    let source_file = if abs_path.is_file() {
-        abs_path.clone()
-    } else {
-        let with_ext = abs_path.with_extension("tri");
-        if with_ext.is_file() {
-            with_ext
-        } else {
-            // Try main.tri inside the directory.
-            let main_tri = abs_path.join("main.tri");
-            if main_tri.is_file() {
-                main_tri
-            } else {
-                return Err(format!(
-                    "path dep '{}': cannot find source at '{}' (tried .tri, main.tri)",
-                    dep_name,
-                    abs_path.display(),
-                ));
-            }
-        }
-    };
+    let source_file = find_path_dep_source(&abs_path).ok_or_else(|| {
+        format!(
+            "path dep '{}': cannot find source at '{}' (tried .tri, main.tri)",
+            dep_name,
+            abs_path.display(),
+        )
+    })?;
 
     let source = std::fs::read_to_string(&source_file).map_err(|e| {
         format!(
@@ -230,6 +276,16 @@ pub(super) fn resolve_path_dep(
     Ok(())
 }
 
+/// Find the source file of a path dependency: the path itself, the path
+/// with a `.tri` extension, or `main.tri` inside it.
+pub(super) fn find_path_dep_source(abs_path: &Path) -> Option<PathBuf> {
+    let with_ext = abs_path.with_extension("tri");
+    let main_tri = abs_path.join("main.tri");
+    [abs_path.to_path_buf(), with_ext, main_tri]
+        .into_iter()
+        .find(|p| p.is_file())
+}
+
 // ─── Query Helpers ─────────────────────────────────────────────────
 
 /// List all dependency source directories (for use in module resolution).
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use super::parse::{is_hex_hash, parse_inline_table};
-use super::resolve::{cache_dependency, resolve_path_dep};
+use super::resolve::{cache_dependency, resolve_onchain_dep, resolve_path_dep};
 use super::*;
 
 // ── parse_dependencies ─────────────────────────────────────
@@ -63,6 +63,24 @@ local_helper = { path = "../shared/helper" }
     }
 }
 
+#[test]
+fn test_parse_dependencies_onchain() {
+    let toml = r#"
+[dependencies]
+anchored = { onchain = "dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd", chain = "neptune" }
+bad = { onchain = "not-a-digest", chain = "neptune" }
+"#;
+    let manifest = parse_dependencies(toml);
+    assert_eq!(manifest.dependencies.len(), 1);
+    match &manifest.dependencies["anchored"] {
+        Dependency::Onchain { digest, chain } => {
+            assert_eq!(digest, &"d".repeat(64));
+            assert_eq!(chain, "neptune");
+        }
+        other => panic!("expected Onchain dep, got {:?}", other),
+    }
+}
+
 #[test]
 fn test_parse_dependencies_mixed() {
     let toml = r#"
@@ -143,7 +161,10 @@ fn test_lockfile_roundtrip() {
             source: "path:../shared/helper".to_string(),
         },
     );
-    let lockfile = Lockfile { locked };
+    let lockfile = Lockfile {
+        locked,
+        ..Default::default()
+    };
 
     save_lockfile(&lock_path, &lockfile).unwrap();
     let loaded = load_lockfile(&lock_path).unwrap();
@@ -289,7 +310,10 @@ fn test_dependency_search_paths() {
             source: "hash".to_string(),
         },
     );
-    let lockfile = Lockfile { locked };
+    let lockfile = Lockfile {
+        locked,
+        ..Default::default()
+    };
 
     let paths = dependency_search_paths(&root, &lockfile);
     assert_eq!(paths.len(), 2);
@@ -300,6 +324,59 @@ fn test_dependency_search_paths() {
     assert!(paths.contains(&expected_b));
 }
 
+// ── resolve_onchain_dep ────────────────────────────────────
+
+#[test]
+fn test_resolve_onchain_dep_reuses_a_locked_cache_and_needs_a_ledger_otherwise() {
+    let dir = tempfile::tempdir().unwrap();
+    let project_root = dir.path();
+    let digest = "d".repeat(64);
+    let mut locked = BTreeMap::new();
+
+    let err = resolve_onchain_dep(
+        project_root,
+        "anchored",
+        &digest,
+        "neptune",
+        &None,
+        None,
+        &mut locked,
+    )
+    .unwrap_err();
+    assert!(err.contains("no ledger"), "{}", err);
+
+    let source_desc = "onchain:neptune:0xfeed";
+    cache_dependency(
+        project_root,
+        &digest,
+        "fn f() { }\n",
+        "anchored",
+        source_desc,
+    )
+    .unwrap();
+    let mut lock = Lockfile::default();
+    lock.locked.insert(
+        "anchored".to_string(),
+        LockedDep {
+            name: "anchored".to_string(),
+            hash: digest.clone(),
+            source: source_desc.to_string(),
+        },
+    );
+    resolve_onchain_dep(
+        project_root,
+        "anchored",
+        &digest,
+        "neptune",
+        &Some(lock),
+        None,
+        &mut locked,
+    )
+    .unwrap();
+    assert_eq!(locked["anchored"].hash, digest);
+    assert_eq!(locked["anchored"].source, source_desc);
+}
+
 // ── cache_dependency ───────────────────────────────────────
 
 #[test]
@@ -382,7 +459,10 @@ fn test_lockfile_sorted_output() {
             source: "hash".to_string(),
         },
     );
-    let lockfile = Lockfile { locked };
+    let lockfile = Lockfile {
+        locked,
+        ..Default::default()
+    };
 
     save_lockfile(&lock_path, &lockfile).unwrap();
     let content = std::fs::read_to_string(&lock_path).unwrap();
@@ -395,3 +475,169 @@ fn test_lockfile_sorted_output() {
         "lockfile should be sorted alphabetically"
     );
 }
+
+// ── library pins ───────────────────────────────────────────
+
+fn library(module: &str, hash: &str) -> LockedLibrary {
+    LockedLibrary {
+        module: module.to_string(),
+        hash: hash.to_string(),
+        source: "std".to_string(),
+    }
+}
+
+#[test]
+fn test_lockfile_roundtrip_preserves_library_pins() {
+    let dir = tempfile::tempdir().unwrap();
+    let lock_path = dir.path().join("trident.lock");
+
+    let mut lockfile = Lockfile::default();
+    let lib = library("std.crypto.hash", "abc123");
+    lockfile.libraries.insert(lib.module.clone(), lib.clone());
+    save_lockfile(&lock_path, &lockfile).unwrap();
+
+    let content = std::fs::read_to_string(&lock_path).unwrap();
+    assert!(content.contains("[libraries]"));
+    assert!(content.contains("\"std.crypto.hash\" = { hash = \"abc123\", source = \"std\" }"));
+
+    let loaded = load_lockfile(&lock_path).unwrap();
+    assert!(loaded.locked.is_empty());
+    assert_eq!(loaded.libraries["std.crypto.hash"], lib);
+}
+
+#[test]
+fn test_pin_libraries_adds_missing_pins() {
+    let mut lockfile = Lockfile::default();
+    let used = [
+        library("std.io.mem", "aa"),
+        library("std.crypto.hash", "bb"),
+    ];
+    assert_eq!(pin_libraries(&mut lockfile, &used, false), Ok(true));
+    assert_eq!(lockfile.libraries.len(), 2);
+    assert_eq!(pin_libraries(&mut lockfile, &used, false), Ok(false));
+}
+
+#[test]
+fn test_pin_libraries_rejects_changed_module() {
+    let mut lockfile = Lockfile::default();
+    pin_libraries(&mut lockfile, &[library("std.io.mem", "aa")], false).unwrap();
+
+    let result = pin_libraries(&mut lockfile, &[library("std.io.mem", "ff")], false);
+    let mismatches = result.unwrap_err();
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].module, "std.io.mem");
+    assert_eq!(mismatches[0].locked, "aa");
+    assert_eq!(mismatches[0].found, "ff");
+    assert_eq!(lockfile.libraries["std.io.mem"].hash, "aa");
+}
+
+#[test]
+fn test_pin_libraries_update_accepts_changes_and_prunes() {
+    let mut lockfile = Lockfile::default();
+    let old = [
+        library("std.io.mem", "aa"),
+        library("std.crypto.hash", "bb"),
+    ];
+    pin_libraries(&mut lockfile, &old, false).unwrap();
+
+    let new = [library("std.io.mem", "ff")];
+    assert_eq!(pin_libraries(&mut lockfile, &new, true), Ok(true));
+    assert_eq!(lockfile.libraries.len(), 1);
+    assert_eq!(lockfile.libraries["std.io.mem"].hash, "ff");
+}
+
+// ── vendoring ──────────────────────────────────────────────
+
+/// A project with one cached registry dep and one path dep, both locked.
+fn vendorable_project(project_root: &Path) -> Lockfile {
+    let reg_hash = "dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd";
+    cache_dependency(
+        project_root,
+        reg_hash,
+        "module remote\n",
+        "remote",
+        "registry:http://example.com",
+    )
+    .unwrap();
+
+    std::fs::create_dir_all(project_root.join("libs")).unwrap();
+    std::fs::write(project_root.join("libs/helper.tri"), "module helper\n").unwrap();
+
+    let mut locked = BTreeMap::new();
+    resolve_path_dep(
+        project_root,
+        "helper",
+        Path::new("libs/helper.tri"),
+        &mut locked,
+    )
+    .unwrap();
+    locked.insert(
+        "remote".to_string(),
+        LockedDep {
+            name: "remote".to_string(),
+            hash: reg_hash.to_string(),
+            source: "registry:http://example.com".to_string(),
+        },
+    );
+    Lockfile {
+        locked,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_vendor_copies_registry_and_path_deps() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let lockfile = vendorable_project(root);
+
+    let vendored = vendor_dependencies(root, &lockfile).unwrap();
+    assert_eq!(vendored.len(), 2);
+    for dep in lockfile.locked.values() {
+        assert!(vendor_source_path(root, &dep.hash).exists());
+    }
+    let helper = &lockfile.locked["helper"];
+    let copied = std::fs::read_to_string(vendor_source_path(root, &helper.hash)).unwrap();
+    assert_eq!(copied, "module helper\n");
+}
+
+#[test]
+fn test_vendored_search_paths_prefer_vendor_over_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let lockfile = vendorable_project(root);
+    let remote = lockfile.locked["remote"].hash.clone();
+
+    let before = vendored_search_paths(root, &lockfile);
+    assert!(before.contains(&root.join(".trident").join("deps").join(&remote)));
+
+    vendor_dependencies(root, &lockfile).unwrap();
+    let after = vendored_search_paths(root, &lockfile);
+    assert!(after.contains(&root.join(VENDOR_DIR).join(&remote)));
+    assert!(after.iter().all(|p| p.starts_with(root.join(VENDOR_DIR))));
+}
+
+#[test]
+fn test_vendor_rejects_path_dep_changed_since_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let lockfile = vendorable_project(root);
+    std::fs::write(root.join("libs/helper.tri"), "module helper\nfn f() { }\n").unwrap();
+
+    let err = vendor_dependencies(root, &lockfile).unwrap_err();
+    assert!(err.contains("changed since it was locked"), "{}", err);
+    assert!(!root.join(VENDOR_DIR).exists());
+}
+
+#[test]
+fn test_vendor_removes_deps_no_longer_locked() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let mut lockfile = vendorable_project(root);
+    vendor_dependencies(root, &lockfile).unwrap();
+
+    let remote = lockfile.locked.remove("remote").unwrap();
+    vendor_dependencies(root, &lockfile).unwrap();
+    assert!(!root.join(VENDOR_DIR).join(&remote.hash).exists());
+    assert!(vendor_source_path(root, &lockfile.locked["helper"].hash).exists());
+}
@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use super::parse::is_hex_hash;
+use super::resolve::{dep_source_path, find_path_dep_source};
+use super::{LockedDep, Lockfile};
+
+// ─── Vendoring ─────────────────────────────────────────────────────
+
+/// Directory (relative to the project root) holding vendored dependencies.
+pub const VENDOR_DIR: &str = "vendor";
+
+/// Get the path of a dependency's vendored source.
+///
+/// Layout: `<project_root>/vendor/<hash>/main.tri`
+pub fn vendor_source_path(project_root: &Path, hash: &str) -> PathBuf {
+    project_root.join(VENDOR_DIR).join(hash).join("main.tri")
+}
+
+/// Copy every locked dependency into `<project_root>/vendor/`.
+///
+/// Registry and hash dependencies are copied from the `.trident/deps/`
+/// cache; path dependencies from their source path, after checking that
+/// their content still matches the locked hash. Vendored directories no
+/// longer in the lockfile are removed. Returns the vendored dependencies.
+pub fn vendor_dependencies(
+    project_root: &Path,
+    lockfile: &Lockfile,
+) -> Result<Vec<LockedDep>, String> {
+    // Read everything first so a missing source leaves vendor/ untouched.
+    let mut sources = Vec::new();
+    for dep in lockfile.locked.values() {
+        sources.push((dep, read_locked_source(project_root, dep)?));
+    }
+
+    let vendor_dir = project_root.join(VENDOR_DIR);
+    for (dep, source) in &sources {
+        let dep_dir = vendor_dir.join(&dep.hash);
+        std::fs::create_dir_all(&dep_dir)
+            .map_err(|e| format!("cannot create '{}': {}", dep_dir.display(), e))?;
+        std::fs::write(dep_dir.join("main.tri"), source)
+            .map_err(|e| format!("cannot vendor '{}': {}", dep.name, e))?;
+        let meta = format!("name={}\nsource={}\n", dep.name, dep.source);
+        std::fs::write(dep_dir.join("meta.txt"), meta)
+            .map_err(|e| format!("cannot write vendor metadata: {}", e))?;
+    }
+
+    if let Ok(entries) = std::fs::read_dir(&vendor_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stale = is_hex_hash(&name) && !lockfile.locked.values().any(|d| d.hash == name);
+            if stale && entry.path().is_dir() {
+                std::fs::remove_dir_all(entry.path())
+                    .map_err(|e| format!("cannot remove stale '{}': {}", name, e))?;
+            }
+        }
+    }
+
+    Ok(sources.into_iter().map(|(dep, _)| dep.clone()).collect())
+}
+
+/// Read the source a locked dependency resolves to.
+fn read_locked_source(project_root: &Path, dep: &LockedDep) -> Result<String, String> {
+    let Some(rel_path) = dep.source.strip_prefix("path:") else {
+        let cached = dep_source_path(project_root, &dep.hash);
+        return std::fs::read_to_string(&cached).map_err(|_| {
+            format!(
+                "dependency '{}' is not cached. Run `trident deps fetch` first.",
+                dep.name
+            )
+        });
+    };
+
+    let abs_path = project_root.join(rel_path);
+    let source_file = find_path_dep_source(&abs_path).ok_or_else(|| {
+        format!(
+            "path dep '{}': cannot find source at '{}'",
+            dep.name,
+            abs_path.display()
+        )
+    })?;
+    let source = std::fs::read_to_string(&source_file)
+        .map_err(|e| format!("path dep '{}': cannot read source: {}", dep.name, e))?;
+    let hash_raw = crate::poseidon2::hash_bytes(source.as_bytes());
+    let hash_hex: String = hash_raw.iter().map(|b| format!("{:02x}", b)).collect();
+    if hash_hex != dep.hash {
+        return Err(format!(
+            "path dep '{}' changed since it was locked. Run `trident deps fetch` first.",
+            dep.name
+        ));
+    }
+    Ok(source)
+}
+
+/// Like `dependency_search_paths`, but prefer `vendor/<hash>/` over the
+/// `.trident/deps/` cache for every dependency that has been vendored.
+pub fn vendored_search_paths(project_root: &Path, lockfile: &Lockfile) -> Vec<PathBuf> {
+    lockfile
+        .locked
+        .values()
+        .map(|dep| {
+            let vendored = project_root.join(VENDOR_DIR).join(&dep.hash);
+            if vendored.is_dir() {
+                vendored
+            } else {
+                project_root.join(".trident").join("deps").join(&dep.hash)
+            }
+        })
+        .collect()
+}
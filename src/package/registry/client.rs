@@ -3,27 +3,97 @@
 /// from malicious or misconfigured servers.
 const MAX_RESPONSE_SIZE: usize = 10 * 1024 * 1024;
 
+/// Default time allowed to open a connection, overridden by
+/// `$TRIDENT_REGISTRY_CONNECT_TIMEOUT` (seconds).
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Default time a read or write may stall, overridden by
+/// `$TRIDENT_REGISTRY_TIMEOUT` (seconds).
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// Default retries after a transient failure, overridden by
+/// `$TRIDENT_REGISTRY_RETRIES`.
+const DEFAULT_RETRIES: u32 = 3;
+/// Delay before the first retry; each later retry waits twice as long.
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(500);
+
 use std::io::{BufRead, BufReader, Write};
 use std::net::TcpStream;
+use std::time::Duration;
 
+use super::error::{is_transient_status, RegistryError};
 use super::json::{
-    extract_json_bool, extract_json_string, format_publish_json, parse_pull_response,
+    extract_json_bool, extract_json_string, format_publish_json, json_escape, parse_pull_response,
     parse_search_response,
 };
 use super::types::*;
 
 pub struct RegistryClient {
     base_url: String,
+    /// API token sent as `Authorization: Bearer` on writes.
+    token: Option<String>,
+    /// HTTP proxy requests go through, from `$HTTP_PROXY` by default.
+    proxy: Option<String>,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    /// Attempts after the first for transiently failed requests.
+    retries: u32,
+    backoff: Duration,
 }
 
 impl RegistryClient {
-    /// Create a new registry client.
+    /// Create a new registry client, authenticated with
+    /// `$TRIDENT_REGISTRY_TOKEN` when set.
+    ///
+    /// Timeouts and retries come from `$TRIDENT_REGISTRY_CONNECT_TIMEOUT`,
+    /// `$TRIDENT_REGISTRY_TIMEOUT` and `$TRIDENT_REGISTRY_RETRIES`, and the
+    /// proxy from `$HTTP_PROXY` and `$NO_PROXY`.
     pub fn new(url: &str) -> Self {
         Self {
             base_url: url.trim_end_matches('/').to_string(),
+            token: std::env::var("TRIDENT_REGISTRY_TOKEN")
+                .ok()
+                .filter(|t| !t.is_empty()),
+            proxy: proxy_for(url, |var| std::env::var(var).ok()),
+            connect_timeout: env_secs("TRIDENT_REGISTRY_CONNECT_TIMEOUT")
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            read_timeout: env_secs("TRIDENT_REGISTRY_TIMEOUT").unwrap_or(DEFAULT_READ_TIMEOUT),
+            retries: std::env::var("TRIDENT_REGISTRY_RETRIES")
+                .ok()
+                .and_then(|r| r.trim().parse().ok())
+                .unwrap_or(DEFAULT_RETRIES),
+            backoff: DEFAULT_BACKOFF,
         }
     }
 
+    /// Use `token` instead of `$TRIDENT_REGISTRY_TOKEN`.
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Send requests through `proxy` (`http://host:port`), or directly
+    /// with `None`, instead of following `$HTTP_PROXY`.
+    pub fn with_proxy(mut self, proxy: Option<String>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Give up on connecting after `connect`, and on a stalled read or
+    /// write after `read`. Zero durations are raised to one millisecond.
+    pub fn with_timeouts(mut self, connect: Duration, read: Duration) -> Self {
+        let floor = Duration::from_millis(1);
+        self.connect_timeout = connect.max(floor);
+        self.read_timeout = read.max(floor);
+        self
+    }
+
+    /// Retry a transiently failed request up to `retries` times, waiting
+    /// `backoff` before the first retry and doubling it after each.
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
     /// Get the default registry URL from environment or config.
     pub fn default_url() -> String {
         std::env::var("TRIDENT_REGISTRY_URL")
@@ -31,15 +101,12 @@ impl RegistryClient {
     }
 
     /// Publish a definition to the registry.
-    pub fn publish(&self, def: &PublishedDefinition) -> Result<PublishResult, String> {
+    pub fn publish(&self, def: &PublishedDefinition) -> Result<PublishResult, RegistryError> {
         let body = format_publish_json(def);
         let response = self.http_post("/api/v1/definitions", &body)?;
 
         if response.status >= 400 {
-            return Err(format!(
-                "publish failed ({}): {}",
-                response.status, response.body
-            ));
+            return Err(server_error(&response, "publish failed"));
         }
 
         Ok(PublishResult {
@@ -49,115 +116,154 @@ impl RegistryClient {
         })
     }
 
-    /// Pull a definition from the registry by hash.
-    pub fn pull(&self, hash: &str) -> Result<PullResult, String> {
+    /// Bind `name` (`owner/name`) to an already published hash.
+    ///
+    /// The registry claims the namespace for the token's identity on
+    /// first use and refuses binds from any other identity afterwards.
+    pub fn bind_name(&self, name: &str, hash: &str) -> Result<bool, RegistryError> {
+        self.require_token("bind a name")?;
+        let path = format!("/api/v1/names/{}", name);
+        let body = format!("{{\"hash\":{}}}", json_escape(hash));
+        let response = self.http_post(&path, &body)?;
+
+        if response.status >= 400 {
+            return Err(server_error(
+                &response,
+                &format!("binding '{}' failed", name),
+            ));
+        }
+        Ok(extract_json_bool(&response.body, "created"))
+    }
+
+    /// Yank `name@hash`: hide it from name resolution. The content stays
+    /// pullable by hash.
+    pub fn yank(&self, name: &str, hash: &str) -> Result<(), RegistryError> {
+        self.require_token("yank")?;
+        let path = format!("/api/v1/names/{}/yank", name);
+        let body = format!("{{\"hash\":{}}}", json_escape(hash));
+        let response = self.http_post(&path, &body)?;
+
+        if response.status >= 400 {
+            return Err(server_error(
+                &response,
+                &format!("yank of '{}@{}' failed", name, hash),
+            ));
+        }
+        Ok(())
+    }
+
+    fn require_token(&self, action: &str) -> Result<(), RegistryError> {
+        if self.token.is_none() {
+            return Err(RegistryError::Config(format!(
+                "cannot {} without a registry token (set $TRIDENT_REGISTRY_TOKEN)",
+                action
+            )));
+        }
+        Ok(())
+    }
+
+    /// Pull a definition from the registry by hash. A response carrying
+    /// any other hash is refused with `RegistryError::HashMismatch`.
+    pub fn pull(&self, hash: &str) -> Result<PullResult, RegistryError> {
         let path = format!("/api/v1/definitions/{}", hash);
         let response = self.http_get(&path)?;
 
         if response.status == 404 {
-            return Err(format!("definition {} not found in registry", hash));
+            return Err(RegistryError::Server {
+                status: 404,
+                message: format!("definition {} not found in registry", hash),
+            });
         }
         if response.status >= 400 {
-            return Err(format!(
-                "pull failed ({}): {}",
-                response.status, response.body
-            ));
+            return Err(server_error(&response, "pull failed"));
         }
 
-        Ok(parse_pull_response(&response.body))
+        let pull = parse_pull_response(&response.body);
+        if !pull.hash.eq_ignore_ascii_case(hash) {
+            return Err(RegistryError::HashMismatch {
+                expected: hash.to_string(),
+                found: pull.hash,
+            });
+        }
+        Ok(pull)
     }
 
     /// Pull a definition by name.
-    pub fn pull_by_name(&self, name: &str) -> Result<PullResult, String> {
+    pub fn pull_by_name(&self, name: &str) -> Result<PullResult, RegistryError> {
         let path = format!("/api/v1/names/{}", name);
         let response = self.http_get(&path)?;
 
         if response.status == 404 {
-            return Err(format!("name '{}' not found in registry", name));
+            return Err(RegistryError::Server {
+                status: 404,
+                message: format!("name '{}' not found in registry", name),
+            });
         }
         if response.status >= 400 {
-            return Err(format!(
-                "pull failed ({}): {}",
-                response.status, response.body
-            ));
+            return Err(server_error(&response, "pull failed"));
         }
 
         Ok(parse_pull_response(&response.body))
     }
 
     /// Search the registry.
-    pub fn search(&self, query: &str) -> Result<Vec<SearchResult>, String> {
+    pub fn search(&self, query: &str) -> Result<Vec<SearchResult>, RegistryError> {
         let path = format!("/api/v1/search?q={}", url_encode(query));
         let response = self.http_get(&path)?;
 
         if response.status >= 400 {
-            return Err(format!(
-                "search failed ({}): {}",
-                response.status, response.body
-            ));
+            return Err(server_error(&response, "search failed"));
         }
 
         Ok(parse_search_response(&response.body))
     }
 
     /// Search by type signature.
-    pub fn search_by_type(&self, type_sig: &str) -> Result<Vec<SearchResult>, String> {
+    pub fn search_by_type(&self, type_sig: &str) -> Result<Vec<SearchResult>, RegistryError> {
         let path = format!("/api/v1/search?type={}", url_encode(type_sig));
         let response = self.http_get(&path)?;
 
         if response.status >= 400 {
-            return Err(format!(
-                "search failed ({}): {}",
-                response.status, response.body
-            ));
+            return Err(server_error(&response, "search failed"));
         }
 
         Ok(parse_search_response(&response.body))
     }
 
     /// Search by tag.
-    pub fn search_by_tag(&self, tag: &str) -> Result<Vec<SearchResult>, String> {
+    pub fn search_by_tag(&self, tag: &str) -> Result<Vec<SearchResult>, RegistryError> {
         let path = format!("/api/v1/search?tag={}", url_encode(tag));
         let response = self.http_get(&path)?;
 
         if response.status >= 400 {
-            return Err(format!(
-                "search failed ({}): {}",
-                response.status, response.body
-            ));
+            return Err(server_error(&response, "search failed"));
         }
 
         Ok(parse_search_response(&response.body))
     }
 
     /// Check registry health.
-    pub fn health(&self) -> Result<bool, String> {
+    pub fn health(&self) -> Result<bool, RegistryError> {
         let response = self.http_get("/health")?;
         Ok(response.status == 200)
     }
 
     /// Get registry statistics.
-    pub fn stats(&self) -> Result<String, String> {
+    pub fn stats(&self) -> Result<String, RegistryError> {
         let response = self.http_get("/api/v1/stats")?;
         if response.status >= 400 {
-            return Err(format!(
-                "stats failed ({}): {}",
-                response.status, response.body
-            ));
+            return Err(server_error(&response, "stats failed"));
         }
         Ok(response.body)
     }
 
     /// Get transitive dependencies.
-    pub fn deps(&self, hash: &str) -> Result<Vec<(String, String)>, String> {
+    pub fn deps(&self, hash: &str) -> Result<Vec<(String, String)>, RegistryError> {
         let path = format!("/api/v1/deps/{}", hash);
         let response = self.http_get(&path)?;
 
         if response.status >= 400 {
-            return Err(format!(
-                "deps failed ({}): {}",
-                response.status, response.body
-            ));
+            return Err(server_error(&response, "deps failed"));
         }
 
         let mut result = Vec::new();
@@ -180,69 +286,154 @@ impl RegistryClient {
 
     // ─── HTTP Transport ───────────────────────────────────────
 
-    fn http_get(&self, path: &str) -> Result<ClientResponse, String> {
-        let (host, port, scheme_host) = parse_url(&self.base_url)?;
-        let addr = format!("{}:{}", host, port);
+    fn http_get(&self, path: &str) -> Result<ClientResponse, RegistryError> {
+        self.send("GET", path, None)
+    }
 
-        let sock_addr: std::net::SocketAddr = addr
-            .parse()
-            .or_else(|_| {
-                use std::net::ToSocketAddrs;
-                addr.to_socket_addrs()
-                    .map_err(|e| format!("resolve {}: {}", addr, e))?
-                    .next()
-                    .ok_or_else(|| format!("no addresses for {}", addr))
-            })
-            .map_err(|e| format!("cannot resolve {}: {}", addr, e))?;
-        let stream = TcpStream::connect_timeout(&sock_addr, std::time::Duration::from_secs(10))
-            .map_err(|e| format!("cannot connect to {}: {}", addr, e))?;
-        stream
-            .set_read_timeout(Some(std::time::Duration::from_secs(30)))
-            .map_err(|e| format!("set timeout: {}", e))?;
+    fn http_post(&self, path: &str, body: &str) -> Result<ClientResponse, RegistryError> {
+        self.send("POST", path, Some(body))
+    }
 
-        let request = format!(
-            "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nUser-Agent: trident/0.1\r\n\r\n",
-            path, scheme_host,
-        );
+    /// Send a request, retrying transient failures with exponential backoff.
+    ///
+    /// A failed connect is always retried: nothing reached the registry.
+    /// A connection that breaks midway and a transient status are retried
+    /// only for GETs, since a POST may already have taken effect.
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+    ) -> Result<ClientResponse, RegistryError> {
+        let (host, port, scheme_host) = parse_url(&self.base_url).map_err(RegistryError::Config)?;
+        let (addr, target) = match &self.proxy {
+            Some(proxy) => {
+                if proxy.contains('@') {
+                    return Err(RegistryError::Config(format!(
+                        "proxy credentials are not supported (proxy '{}')",
+                        proxy
+                    )));
+                }
+                let (proxy_host, proxy_port, _) = parse_url(proxy).map_err(|e| {
+                    RegistryError::Config(format!("invalid proxy '{}': {}", proxy, e))
+                })?;
+                (
+                    format!("{}:{}", proxy_host, proxy_port),
+                    format!("http://{}{}", scheme_host, path),
+                )
+            }
+            None => (format!("{}:{}", host, port), path.to_string()),
+        };
 
-        (&stream)
-            .write_all(request.as_bytes())
-            .map_err(|e| format!("write request: {}", e))?;
+        let mut request = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+            method, target, scheme_host,
+        );
+        if let Some(body) = body {
+            request.push_str(&format!(
+                "Content-Type: application/json\r\nContent-Length: {}\r\n",
+                body.len()
+            ));
+            if let Some(token) = &self.token {
+                request.push_str(&format!("Authorization: Bearer {}\r\n", token));
+            }
+        }
+        request.push_str("User-Agent: trident/0.1\r\n\r\n");
+        request.push_str(body.unwrap_or(""));
 
-        read_response(&stream)
+        let idempotent = body.is_none();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let last = attempts > self.retries;
+            let message = match self.send_once(&addr, &request) {
+                Ok(response) if idempotent && !last && is_transient_status(response.status) => {
+                    format!("HTTP {}", response.status)
+                }
+                Ok(response) => return Ok(response),
+                Err(Failure::Connect(message)) => message,
+                Err(Failure::Transfer(message)) if idempotent => message,
+                Err(Failure::Transfer(message)) => {
+                    return Err(self.network_error(attempts, message))
+                }
+                Err(Failure::Fatal(e)) => return Err(e),
+            };
+            if last {
+                return Err(self.network_error(attempts, message));
+            }
+            let doubling = 1u32 << (attempts - 1).min(16);
+            std::thread::sleep(self.backoff.saturating_mul(doubling));
+        }
     }
 
-    fn http_post(&self, path: &str, body: &str) -> Result<ClientResponse, String> {
-        let (host, port, scheme_host) = parse_url(&self.base_url)?;
-        let addr = format!("{}:{}", host, port);
-
+    fn send_once(&self, addr: &str, request: &str) -> Result<ClientResponse, Failure> {
         let sock_addr: std::net::SocketAddr = addr
             .parse()
             .or_else(|_| {
                 use std::net::ToSocketAddrs;
                 addr.to_socket_addrs()
-                    .map_err(|e| format!("resolve {}: {}", addr, e))?
+                    .map_err(|e| e.to_string())?
                     .next()
-                    .ok_or_else(|| format!("no addresses for {}", addr))
+                    .ok_or_else(|| "no addresses".to_string())
             })
-            .map_err(|e| format!("cannot resolve {}: {}", addr, e))?;
-        let stream = TcpStream::connect_timeout(&sock_addr, std::time::Duration::from_secs(10))
-            .map_err(|e| format!("cannot connect to {}: {}", addr, e))?;
+            .map_err(|e| Failure::Connect(format!("cannot resolve {}: {}", addr, e)))?;
+        let stream = TcpStream::connect_timeout(&sock_addr, self.connect_timeout)
+            .map_err(|e| Failure::Connect(format!("cannot connect to {}: {}", addr, e)))?;
         stream
-            .set_read_timeout(Some(std::time::Duration::from_secs(30)))
-            .map_err(|e| format!("set timeout: {}", e))?;
-
-        let request = format!(
-            "POST {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\nUser-Agent: trident/0.1\r\n\r\n{}",
-            path, scheme_host, body.len(), body,
-        );
+            .set_read_timeout(Some(self.read_timeout))
+            .and_then(|()| stream.set_write_timeout(Some(self.read_timeout)))
+            .map_err(|e| Failure::Connect(format!("set timeout: {}", e)))?;
 
         (&stream)
             .write_all(request.as_bytes())
-            .map_err(|e| format!("write request: {}", e))?;
+            .map_err(|e| transfer("write request", e))?;
 
         read_response(&stream)
     }
+
+    fn network_error(&self, attempts: u32, message: String) -> RegistryError {
+        let url = match &self.proxy {
+            Some(proxy) => format!("{} via proxy {}", self.base_url, proxy),
+            None => self.base_url.clone(),
+        };
+        RegistryError::Network {
+            url,
+            attempts,
+            message,
+        }
+    }
+}
+
+/// How a single attempt at a request failed.
+enum Failure {
+    /// Nothing was sent: the registry or proxy could not be reached.
+    Connect(String),
+    /// The connection broke or timed out after the request went out.
+    Transfer(String),
+    /// A response arrived but cannot be used; retrying will not help.
+    Fatal(RegistryError),
+}
+
+fn transfer(what: &str, e: std::io::Error) -> Failure {
+    match e.kind() {
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => {
+            Failure::Transfer(format!("{}: timed out", what))
+        }
+        _ => Failure::Transfer(format!("{}: {}", what, e)),
+    }
+}
+
+/// A `Server` error for a failed `action`, quoting the response body.
+fn server_error(response: &ClientResponse, action: &str) -> RegistryError {
+    let body = response.body.trim();
+    RegistryError::Server {
+        status: response.status,
+        message: if body.is_empty() {
+            action.to_string()
+        } else {
+            format!("{}: {}", action, body)
+        },
+    }
 }
 
 struct ClientResponse {
@@ -250,18 +441,26 @@ struct ClientResponse {
     body: String,
 }
 
-fn read_response(stream: &TcpStream) -> Result<ClientResponse, String> {
+fn read_response(stream: &TcpStream) -> Result<ClientResponse, Failure> {
     let mut reader = BufReader::new(stream);
 
     let mut status_line = String::new();
     reader
         .read_line(&mut status_line)
-        .map_err(|e| format!("read status: {}", e))?;
+        .map_err(|e| transfer("read status", e))?;
+    if status_line.is_empty() {
+        return Err(Failure::Transfer(
+            "connection closed before a response".to_string(),
+        ));
+    }
     let status = status_line
         .split_whitespace()
         .nth(1)
         .and_then(|s| s.parse::<u16>().ok())
-        .unwrap_or(500);
+        .ok_or_else(|| {
+            Failure::Transfer(format!("malformed status line '{}'", status_line.trim()))
+        })?;
+    let too_large = |message: String| Failure::Fatal(RegistryError::Server { status, message });
 
     let mut content_length: usize = 0;
     let mut chunked = false;
@@ -269,7 +468,7 @@ fn read_response(stream: &TcpStream) -> Result<ClientResponse, String> {
         let mut line = String::new();
         reader
             .read_line(&mut line)
-            .map_err(|e| format!("read header: {}", e))?;
+            .map_err(|e| transfer("read header", e))?;
         let line = line.trim().to_string();
         if line.is_empty() {
             break;
@@ -286,16 +485,15 @@ fn read_response(stream: &TcpStream) -> Result<ClientResponse, String> {
     }
 
     if content_length > MAX_RESPONSE_SIZE {
-        return Err(format!(
+        return Err(too_large(format!(
             "response too large: Content-Length {} exceeds limit of {} bytes",
             content_length, MAX_RESPONSE_SIZE,
-        ));
+        )));
     }
 
     let body = if content_length > 0 {
         let mut buf = vec![0u8; content_length];
-        std::io::Read::read_exact(&mut reader, &mut buf)
-            .map_err(|e| format!("read body: {}", e))?;
+        std::io::Read::read_exact(&mut reader, &mut buf).map_err(|e| transfer("read body", e))?;
         String::from_utf8(buf).unwrap_or_default()
     } else if chunked {
         let mut body = String::new();
@@ -303,20 +501,20 @@ fn read_response(stream: &TcpStream) -> Result<ClientResponse, String> {
             let mut chunk_line = String::new();
             reader
                 .read_line(&mut chunk_line)
-                .map_err(|e| format!("read chunk size: {}", e))?;
+                .map_err(|e| transfer("read chunk size", e))?;
             let chunk_size = usize::from_str_radix(chunk_line.trim(), 16).unwrap_or(0);
             if chunk_size == 0 {
                 break;
             }
             if body.len() + chunk_size > MAX_RESPONSE_SIZE {
-                return Err(format!(
+                return Err(too_large(format!(
                     "chunked response too large: exceeds limit of {} bytes",
                     MAX_RESPONSE_SIZE,
-                ));
+                )));
             }
             let mut chunk = vec![0u8; chunk_size];
             std::io::Read::read_exact(&mut reader, &mut chunk)
-                .map_err(|e| format!("read chunk: {}", e))?;
+                .map_err(|e| transfer("read chunk", e))?;
             body.push_str(&String::from_utf8(chunk).unwrap_or_default());
             let mut crlf = String::new();
             let _ = reader.read_line(&mut crlf);
@@ -326,16 +524,16 @@ fn read_response(stream: &TcpStream) -> Result<ClientResponse, String> {
         let mut body = Vec::new();
         let mut buf = [0u8; 8192];
         loop {
-            let n = std::io::Read::read(&mut reader, &mut buf)
-                .map_err(|e| format!("read body: {}", e))?;
+            let n =
+                std::io::Read::read(&mut reader, &mut buf).map_err(|e| transfer("read body", e))?;
             if n == 0 {
                 break;
             }
             if body.len() + n > MAX_RESPONSE_SIZE {
-                return Err(format!(
+                return Err(too_large(format!(
                     "response too large: exceeds limit of {} bytes",
                     MAX_RESPONSE_SIZE,
-                ));
+                )));
             }
             body.extend_from_slice(&buf[..n]);
         }
@@ -345,6 +543,41 @@ fn read_response(stream: &TcpStream) -> Result<ClientResponse, String> {
     Ok(ClientResponse { status, body })
 }
 
+/// The proxy for requests to `url`: `$http_proxy`/`$HTTP_PROXY` (or the
+/// `HTTPS` pair for `https://` URLs), unless `$no_proxy`/`$NO_PROXY`
+/// lists the host, a parent domain of it, or `*`.
+pub(super) fn proxy_for(url: &str, env: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let var = |name: &str| {
+        env(&name.to_lowercase())
+            .or_else(|| env(name))
+            .filter(|v| !v.trim().is_empty())
+    };
+    let proxy = if url.trim().starts_with("https://") {
+        var("HTTPS_PROXY")
+    } else {
+        var("HTTP_PROXY")
+    }?;
+
+    let host = parse_url(url).ok()?.0.to_lowercase();
+    let bypass = var("NO_PROXY").unwrap_or_default();
+    let bypassed = bypass.split(',').any(|entry| {
+        let entry = entry.trim().trim_start_matches('.').to_lowercase();
+        entry == "*"
+            || (!entry.is_empty() && (host == entry || host.ends_with(&format!(".{}", entry))))
+    });
+    (!bypassed).then(|| proxy.trim().to_string())
+}
+
+fn env_secs(var: &str) -> Option<Duration> {
+    std::env::var(var)
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs)
+}
+
 pub(super) fn parse_url(url: &str) -> Result<(String, u16, String), String> {
     let url = url.trim();
     let without_scheme = if let Some(rest) = url.strip_prefix("http://") {
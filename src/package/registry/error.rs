@@ -0,0 +1,78 @@
+use std::fmt;
+
+/// Why a registry request failed.
+///
+/// The variants separate failures worth retrying later (`Network`) from
+/// ones the registry answered (`Server`) and from content that must not be
+/// trusted (`HashMismatch`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    /// The request could not be made: bad URL, bad proxy, missing token.
+    Config(String),
+    /// The registry (or the proxy in front of it) could not be reached, or
+    /// the connection failed mid-request, on every attempt.
+    Network {
+        url: String,
+        attempts: u32,
+        message: String,
+    },
+    /// The registry answered with an error status, or with a response the
+    /// client refuses to read.
+    Server { status: u16, message: String },
+    /// The registry returned a definition under a different hash than the
+    /// one requested.
+    HashMismatch { expected: String, found: String },
+}
+
+impl RegistryError {
+    /// Whether retrying the same request later could succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RegistryError::Network { .. } => true,
+            RegistryError::Server { status, .. } => is_transient_status(*status),
+            RegistryError::Config(_) | RegistryError::HashMismatch { .. } => false,
+        }
+    }
+}
+
+/// Statuses a registry or proxy returns while overloaded or restarting.
+pub(super) fn is_transient_status(status: u16) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Config(message) => write!(f, "{}", message),
+            RegistryError::Network {
+                url,
+                attempts,
+                message,
+            } => {
+                let plural = if *attempts == 1 { "" } else { "s" };
+                write!(
+                    f,
+                    "network error: cannot reach {} ({} attempt{}): {}",
+                    url, attempts, plural, message
+                )
+            }
+            RegistryError::Server { status, message } => {
+                write!(f, "registry error (HTTP {}): {}", status, message)
+            }
+            RegistryError::HashMismatch { expected, found } => write!(
+                f,
+                "hash mismatch: requested {} but the registry returned {}",
+                expected,
+                if found.is_empty() { "no hash" } else { found }
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+impl From<RegistryError> for String {
+    fn from(e: RegistryError) -> String {
+        e.to_string()
+    }
+}
@@ -1,4 +1,5 @@
 use super::types::*;
+use crate::config::project::ProjectMetadata;
 
 /// Maximum length for a single JSON string value (1 MB).
 const MAX_STRING_LEN: usize = 1_000_000;
@@ -179,9 +180,20 @@ pub(super) fn format_publish_json(def: &PublishedDefinition) -> String {
     let requires: Vec<String> = def.requires.iter().map(|r| json_escape(r)).collect();
     let ensures: Vec<String> = def.ensures.iter().map(|e| json_escape(e)).collect();
     let tags: Vec<String> = def.tags.iter().map(|t| json_escape(t)).collect();
+    let authors: Vec<String> = def
+        .metadata
+        .authors
+        .iter()
+        .map(|a| json_escape(a))
+        .collect();
+    let optional = |v: &Option<String>| {
+        v.as_ref()
+            .map(|s| json_escape(s))
+            .unwrap_or_else(|| "null".to_string())
+    };
 
     format!(
-        "{{\"hash\":\"{}\",\"source\":{},\"module\":{},\"is_pub\":{},\"params\":[{}],\"return_ty\":{},\"dependencies\":[{}],\"requires\":[{}],\"ensures\":[{}],\"name\":{},\"tags\":[{}],\"verified\":{},\"verification_cert\":{}}}",
+        "{{\"hash\":\"{}\",\"source\":{},\"module\":{},\"is_pub\":{},\"params\":[{}],\"return_ty\":{},\"dependencies\":[{}],\"requires\":[{}],\"ensures\":[{}],\"name\":{},\"tags\":[{}],\"verified\":{},\"verification_cert\":{},\"signer_public_key\":{},\"signer_signature\":{},\"license\":{},\"authors\":[{}],\"description\":{},\"repository\":{}}}",
         def.hash,
         json_escape(&def.source),
         json_escape(&def.module),
@@ -195,10 +207,15 @@ pub(super) fn format_publish_json(def: &PublishedDefinition) -> String {
         tags.join(","),
         def.verified,
         def.verification_cert.as_ref().map(|c| json_escape(c)).unwrap_or_else(|| "null".to_string()),
+        def.signer_public_key.as_ref().map(|k| json_escape(k)).unwrap_or_else(|| "null".to_string()),
+        def.signer_signature.as_ref().map(|s| json_escape(s)).unwrap_or_else(|| "null".to_string()),
+        optional(&def.metadata.license),
+        authors.join(","),
+        optional(&def.metadata.description),
+        optional(&def.metadata.repository),
     )
 }
 
-#[cfg(test)]
 pub(super) fn parse_publish_body(body: &str) -> Result<PublishedDefinition, String> {
     let hash = extract_json_string(body, "hash");
     if hash.is_empty() {
@@ -247,6 +264,15 @@ pub(super) fn parse_publish_body(body: &str) -> Result<PublishedDefinition, Stri
         }
     };
 
+    let optional = |key: &str| {
+        let v = extract_json_string(body, key);
+        if v.is_empty() {
+            None
+        } else {
+            Some(v)
+        }
+    };
+
     Ok(PublishedDefinition {
         hash,
         source,
@@ -261,6 +287,14 @@ pub(super) fn parse_publish_body(body: &str) -> Result<PublishedDefinition, Stri
         tags,
         verified,
         verification_cert,
+        signer_public_key: optional("signer_public_key"),
+        signer_signature: optional("signer_signature"),
+        metadata: ProjectMetadata {
+            license: optional("license"),
+            authors: extract_json_array_strings(body, "authors"),
+            description: optional("description"),
+            repository: optional("repository"),
+        },
     })
 }
 
@@ -305,6 +339,8 @@ pub(super) fn parse_pull_response(body: &str) -> PullResult {
         dependencies: extract_json_array_strings(body, "dependencies"),
         requires: extract_json_array_strings(body, "requires"),
         ensures: extract_json_array_strings(body, "ensures"),
+        signer_public_key: extract_json_string(body, "signer_public_key"),
+        signer_signature: extract_json_string(body, "signer_signature"),
     }
 }
 
@@ -325,6 +361,8 @@ pub(super) fn parse_search_response(body: &str) -> Vec<SearchResult> {
             let module = extract_json_string(obj, "module");
             let signature = extract_json_string(obj, "signature");
             let verified = extract_json_bool(obj, "verified");
+            let verification_cert = Some(extract_json_string(obj, "verification_cert"))
+                .filter(|report| !report.is_empty());
             let tags = extract_json_array_strings(obj, "tags");
 
             if !hash.is_empty() {
@@ -334,6 +372,7 @@ pub(super) fn parse_search_response(body: &str) -> Vec<SearchResult> {
                     module,
                     signature,
                     verified,
+                    verification_cert,
                     tags,
                 });
             }
@@ -1,16 +1,34 @@
 //! Registry Client — HTTP client for interacting with a Trident registry.
 //!
 //! Provides a client for publishing and pulling content-addressed definitions
-//! to/from a remote registry over HTTP. Wire format is JSON.
+//! to/from a remote registry over HTTP. Wire format is JSON. A registry
+//! accepts publishes through `accept_publish`, which runs the static
+//! verifier itself instead of trusting the client's `verified` flag.
+//!
+//! Names are namespaced (`alice/merkle_utils`). Publishing is two-phase:
+//! content first, then name binds, which `NameTable` checks against the
+//! namespace owner's token identity.
+//!
+//! A definition can also be anchored on a chain by the Poseidon2 digest
+//! of its source (`onchain`); pulling by digest checks the source against
+//! the anchor, whichever registry serves it.
 
 mod client;
+mod error;
 mod json;
+mod names;
+mod onchain;
 mod store_integration;
 mod types;
+mod verify;
 
 pub use client::RegistryClient;
+pub use error::RegistryError;
+pub use names::{split_name, token_identity, NameBinding, NameTable};
+pub use onchain::{anchor_published, pull_anchored, source_digest, Anchor, AnchorLedger};
 pub use store_integration::{publish_codebase, pull_into_codebase};
 pub use types::{PublishResult, PublishedDefinition, PullResult, SearchResult};
+pub use verify::{accept_publish, report_verdict, verify_definition};
 
 #[cfg(test)]
 mod tests;
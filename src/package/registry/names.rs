@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+/// Split `alice/merkle_utils` into namespace and item name.
+///
+/// Both parts must be non-empty and made of ASCII alphanumerics, `_`,
+/// `-` or `.`; the namespace may not contain `.`.
+pub fn split_name(name: &str) -> Result<(&str, &str), String> {
+    let (namespace, item) = name
+        .split_once('/')
+        .ok_or_else(|| format!("name '{}' has no namespace (expected 'owner/name')", name))?;
+    let valid = |s: &str, extra: &[char]| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || extra.contains(&c))
+    };
+    if !valid(namespace, &[]) {
+        return Err(format!("invalid namespace in '{}'", name));
+    }
+    if !valid(item, &['.']) {
+        return Err(format!("invalid name in '{}'", name));
+    }
+    Ok((namespace, item))
+}
+
+/// Publisher identity derived from an API token: the Poseidon2 hash of
+/// the token bytes (hex). The registry keeps identities, never tokens.
+pub fn token_identity(token: &str) -> String {
+    crate::hash::ContentHash(crate::poseidon2::hash_bytes(token.as_bytes())).to_hex()
+}
+
+/// One hash bound to a name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NameBinding {
+    /// Content hash (hex).
+    pub hash: String,
+    /// Hidden from name resolution; the content stays pullable by hash.
+    pub yanked: bool,
+}
+
+/// Registry-side name table: namespace owners and name bindings.
+///
+/// A namespace belongs to the identity that first binds a name in it.
+/// Later binds and yanks in that namespace need the same identity.
+/// Bindings are append-only: yanking hides a binding, nothing is deleted.
+#[derive(Clone, Debug, Default)]
+pub struct NameTable {
+    owners: BTreeMap<String, String>,
+    bindings: BTreeMap<String, Vec<NameBinding>>,
+}
+
+impl NameTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Owner identity of a namespace, if claimed.
+    pub fn owner(&self, namespace: &str) -> Option<&str> {
+        self.owners.get(namespace).map(|s| s.as_str())
+    }
+
+    /// Bind `name` to `hash` on behalf of `identity`, claiming the
+    /// namespace if it is unowned. Returns whether a new binding was added.
+    pub fn bind(&mut self, name: &str, hash: &str, identity: &str) -> Result<bool, String> {
+        let (namespace, _) = split_name(name)?;
+        self.check_owner(namespace, identity)?;
+        self.owners
+            .entry(namespace.to_string())
+            .or_insert_with(|| identity.to_string());
+
+        let bindings = self.bindings.entry(name.to_string()).or_default();
+        if let Some(pos) = bindings.iter().position(|b| b.hash == hash) {
+            // Re-binding an existing hash makes it current again.
+            let mut binding = bindings.remove(pos);
+            binding.yanked = false;
+            bindings.push(binding);
+            return Ok(false);
+        }
+        bindings.push(NameBinding {
+            hash: hash.to_string(),
+            yanked: false,
+        });
+        Ok(true)
+    }
+
+    /// Hide `name@hash` from name resolution.
+    pub fn yank(&mut self, name: &str, hash: &str, identity: &str) -> Result<(), String> {
+        let (namespace, _) = split_name(name)?;
+        self.check_owner(namespace, identity)?;
+        let binding = self
+            .bindings
+            .get_mut(name)
+            .and_then(|bindings| bindings.iter_mut().find(|b| b.hash == hash))
+            .ok_or_else(|| format!("'{}' was never bound to {}", name, hash))?;
+        binding.yanked = true;
+        Ok(())
+    }
+
+    /// The most recent non-yanked hash bound to `name`.
+    pub fn resolve(&self, name: &str) -> Option<&str> {
+        self.bindings
+            .get(name)?
+            .iter()
+            .rev()
+            .find(|b| !b.yanked)
+            .map(|b| b.hash.as_str())
+    }
+
+    /// Every hash ever bound to `name`, oldest first, yanked included.
+    pub fn history(&self, name: &str) -> &[NameBinding] {
+        self.bindings.get(name).map(|b| b.as_slice()).unwrap_or(&[])
+    }
+
+    fn check_owner(&self, namespace: &str, identity: &str) -> Result<(), String> {
+        match self.owners.get(namespace) {
+            Some(owner) if owner != identity => Err(format!(
+                "namespace '{}' is owned by another publisher",
+                namespace
+            )),
+            _ => Ok(()),
+        }
+    }
+}
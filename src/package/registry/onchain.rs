@@ -0,0 +1,114 @@
+use super::client::RegistryClient;
+use super::error::RegistryError;
+use super::types::{PublishResult, PullResult};
+
+// ─── Anchors ──────────────────────────────────────────────────────
+
+/// A definition anchored on a chain: the transaction `tx` recorded the
+/// Poseidon2 `digest` of its source, together with where the source is
+/// served (`registry`) and under which registry content hash (`hash`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Anchor {
+    pub digest: String,
+    pub hash: String,
+    pub registry: String,
+    pub tx: String,
+}
+
+impl Anchor {
+    /// The `key=value` lines a ledger exchanges an anchor as.
+    pub fn to_lines(&self) -> String {
+        format!(
+            "digest={}\nhash={}\nregistry={}\ntx={}\n",
+            self.digest, self.hash, self.registry, self.tx
+        )
+    }
+
+    /// Read an anchor from `key=value` lines; `None` when a key other than
+    /// `tx` is missing. Unknown keys are ignored.
+    pub fn parse(text: &str) -> Option<Anchor> {
+        let field = |key: &str| {
+            text.lines()
+                .filter_map(|line| line.split_once('='))
+                .find(|(k, _)| k.trim() == key)
+                .map(|(_, v)| v.trim().to_string())
+        };
+        Some(Anchor {
+            digest: field("digest")?,
+            hash: field("hash")?,
+            registry: field("registry")?,
+            tx: field("tx").unwrap_or_default(),
+        })
+    }
+}
+
+/// Reads and writes anchors on a chain.
+///
+/// Warriors implement this against their chain's RPC; `trident` reaches
+/// them through the `anchor lookup` and `anchor write` commands.
+pub trait AnchorLedger {
+    /// The anchor `chain` holds for `digest`, if any.
+    fn lookup(&self, chain: &str, digest: &str) -> Result<Option<Anchor>, String>;
+    /// Anchor `anchor` on `chain`, returning the transaction id.
+    fn write(&self, chain: &str, anchor: &Anchor) -> Result<String, String>;
+}
+
+/// The digest a source is anchored under: its Poseidon2 hash, in hex.
+pub fn source_digest(source: &str) -> String {
+    crate::poseidon2::hash_bytes(source.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// ─── Resolution ───────────────────────────────────────────────────
+
+/// Pull the definition anchored on `chain` under `digest`.
+///
+/// The source comes from the registry the anchor names, and must hash to
+/// `digest`: a registry cannot serve anything but what was anchored.
+pub fn pull_anchored(
+    ledger: &dyn AnchorLedger,
+    chain: &str,
+    digest: &str,
+) -> Result<(Anchor, PullResult), String> {
+    let anchor = ledger
+        .lookup(chain, digest)?
+        .ok_or_else(|| format!("digest {} is not anchored on '{}'", digest, chain))?;
+    if !anchor.digest.eq_ignore_ascii_case(digest) {
+        return Err(format!(
+            "'{}' answered digest {} with an anchor for {}",
+            chain, digest, anchor.digest
+        ));
+    }
+    let pull = RegistryClient::new(&anchor.registry)
+        .pull(&anchor.hash)
+        .map_err(|e| format!("cannot fetch anchored {}: {}", digest, e))?;
+    let found = source_digest(&pull.source);
+    if !found.eq_ignore_ascii_case(digest) {
+        return Err(RegistryError::HashMismatch {
+            expected: digest.to_string(),
+            found,
+        }
+        .to_string());
+    }
+    Ok((anchor, pull))
+}
+
+/// Anchor a definition just published to `registry` on `chain`.
+pub fn anchor_published(
+    ledger: &dyn AnchorLedger,
+    chain: &str,
+    registry: &str,
+    published: &PublishResult,
+    source: &str,
+) -> Result<Anchor, String> {
+    let mut anchor = Anchor {
+        digest: source_digest(source),
+        hash: published.hash.clone(),
+        registry: registry.trim_end_matches('/').to_string(),
+        tx: String::new(),
+    };
+    anchor.tx = ledger.write(chain, &anchor)?;
+    Ok(anchor)
+}
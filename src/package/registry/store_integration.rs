@@ -1,16 +1,33 @@
+use crate::config::project::ProjectMetadata;
+use crate::deploy::signing::{self, SigningKey, TrustedKeys};
 use crate::hash::ContentHash;
 use crate::store::{Codebase, Definition};
 
 use super::client::RegistryClient;
 use super::types::*;
 
+/// Publish every named definition, signing each content hash with `key`.
+///
+/// Two phases: all content is uploaded first, then each name is bound as
+/// `namespace/name`. Without a namespace only content is published.
+/// Every definition carries the publishing project's `metadata`.
 pub fn publish_codebase(
     codebase: &Codebase,
     client: &RegistryClient,
     tags: &[String],
+    key: Option<&SigningKey>,
+    namespace: Option<&str>,
+    metadata: &ProjectMetadata,
 ) -> Result<Vec<PublishResult>, String> {
     let names = codebase.list_names();
+    let qualified = |name: &str| namespace.map(|ns| format!("{}/{}", ns, name));
+    for (name, _) in &names {
+        if let Some(full) = qualified(name) {
+            super::names::split_name(&full)?;
+        }
+    }
     let mut results = Vec::new();
+    let mut published = Vec::new();
 
     for (name, hash) in &names {
         let def = match codebase.lookup_hash(hash) {
@@ -28,28 +45,47 @@ pub fn publish_codebase(
             dependencies: def.dependencies.iter().map(|h| h.to_hex()).collect(),
             requires: def.requires.clone(),
             ensures: def.ensures.clone(),
-            name: Some(name.to_string()),
+            name: None,
             tags: tags.to_vec(),
             verified: false,
             verification_cert: None,
+            signer_public_key: key.map(|k| signing::encode_hex(k.public_key())),
+            signer_signature: key.map(|k| signing::encode_hex(&k.sign(hash.to_hex().as_bytes()))),
+            metadata: metadata.clone(),
         };
 
         match client.publish(&pub_def) {
-            Ok(result) => results.push(result),
+            Ok(result) => {
+                results.push(result);
+                published.push(*name);
+            }
             Err(e) => {
                 return Err(format!("failed to publish '{}': {}", name, e));
             }
         }
     }
 
+    for (result, name) in results.iter_mut().zip(published) {
+        if let Some(full) = qualified(name) {
+            client.bind_name(&full, &result.hash)?;
+            result.name_bound = true;
+        }
+    }
+
     Ok(results)
 }
 
 /// Pull a definition from a registry into the local store.
+///
+/// The publisher's signature over the content hash is checked first;
+/// unsigned, invalid, or untrusted definitions are refused unless
+/// `allow_unsigned`.
 pub fn pull_into_codebase(
     codebase: &mut Codebase,
     client: &RegistryClient,
     name_or_hash: &str,
+    trusted: &TrustedKeys,
+    allow_unsigned: bool,
 ) -> Result<PullResult, String> {
     let pull = if name_or_hash.len() == 64 && name_or_hash.chars().all(|c| c.is_ascii_hexdigit()) {
         client.pull(name_or_hash)?
@@ -60,6 +96,14 @@ pub fn pull_into_codebase(
     let hash = ContentHash::from_hex(&pull.hash)
         .ok_or_else(|| "invalid hash in pull response".to_string())?;
 
+    signing::verify_definition(
+        &pull.hash,
+        &pull.signer_public_key,
+        &pull.signer_signature,
+        trusted,
+    )
+    .require(&format!("'{}'", name_or_hash), allow_unsigned)?;
+
     if codebase.lookup_hash(&hash).is_some() {
         return Ok(pull);
     }
@@ -80,6 +124,7 @@ pub fn pull_into_codebase(
         requires: pull.requires.clone(),
         ensures: pull.ensures.clone(),
         first_seen: crate::package::unix_timestamp(),
+        raw_hash: None,
     };
 
     codebase.store_definition(hash, def);
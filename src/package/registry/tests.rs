@@ -1,6 +1,11 @@
 use super::client::*;
+use super::error::*;
 use super::json::*;
+use super::names::*;
+use super::onchain::*;
 use super::types::*;
+use super::verify::*;
+use crate::config::project::ProjectMetadata;
 use crate::hash::ContentHash;
 
 #[test]
@@ -96,6 +101,9 @@ fn test_publish_json_roundtrip() {
         tags: vec!["testing".to_string()],
         verified: false,
         verification_cert: None,
+        signer_public_key: None,
+        signer_signature: None,
+        metadata: ProjectMetadata::default(),
     };
 
     let json = format_publish_json(&pub_def);
@@ -128,6 +136,14 @@ fn test_publish_json_roundtrip_complex() {
         tags: vec!["math".to_string(), "core".to_string()],
         verified: true,
         verification_cert: Some("cert123".to_string()),
+        signer_public_key: Some("a".repeat(64)),
+        signer_signature: Some("b".repeat(128)),
+        metadata: ProjectMetadata {
+            license: Some("MIT OR Apache-2.0".to_string()),
+            authors: vec!["Ada <ada@example.org>".to_string()],
+            description: Some("Field \"math\"".to_string()),
+            repository: None,
+        },
     };
 
     let json = format_publish_json(&pub_def);
@@ -141,6 +157,9 @@ fn test_publish_json_roundtrip_complex() {
     assert_eq!(parsed.return_ty, pub_def.return_ty);
     assert_eq!(parsed.name, pub_def.name);
     assert_eq!(parsed.verified, pub_def.verified);
+    assert_eq!(parsed.signer_public_key, pub_def.signer_public_key);
+    assert_eq!(parsed.signer_signature, pub_def.signer_signature);
+    assert_eq!(parsed.metadata, pub_def.metadata);
 }
 
 #[test]
@@ -161,3 +180,343 @@ fn test_parse_publish_body_invalid_hash() {
     let body = r#"{"hash":"tooshort","source":"fn test() { }"}"#;
     assert!(parse_publish_body(body).is_err());
 }
+
+fn client_asserted(source: &str) -> String {
+    format_publish_json(&PublishedDefinition {
+        hash: "f".repeat(64),
+        source: source.to_string(),
+        module: "std.check".to_string(),
+        is_pub: true,
+        params: Vec::new(),
+        return_ty: None,
+        dependencies: Vec::new(),
+        requires: Vec::new(),
+        ensures: Vec::new(),
+        name: None,
+        tags: Vec::new(),
+        verified: true,
+        verification_cert: Some("trust me".to_string()),
+        signer_public_key: None,
+        signer_signature: None,
+        metadata: ProjectMetadata::default(),
+    })
+}
+
+#[test]
+fn test_accept_publish_overrides_client_verified_flag() {
+    let def = accept_publish(&client_asserted("fn bad() {\n    assert(1 == 2)\n}")).unwrap();
+    assert!(!def.verified);
+    let report = def.verification_cert.unwrap();
+    assert!(report_verdict(&report).unwrap().starts_with("UNSAFE"));
+
+    let def = accept_publish(&client_asserted(
+        "fn good(a: Field) {\n    assert(a == a)\n}",
+    ))
+    .unwrap();
+    assert!(def.verified);
+    assert!(report_verdict(&def.verification_cert.unwrap())
+        .unwrap()
+        .starts_with("SAFE"));
+}
+
+#[test]
+fn test_accept_publish_rejects_source_that_is_not_one_function() {
+    assert!(accept_publish(&client_asserted("fn (")).is_err());
+    assert!(accept_publish(&client_asserted("fn a() { }\nfn b() { }")).is_err());
+}
+
+#[test]
+fn test_search_response_carries_verification_report() {
+    let body = format!(
+        "{{\"results\":[{{\"hash\":\"{}\",\"name\":\"f\",\"verified\":false,\"verification_cert\":\"Verdict: UNSAFE\"}}]}}",
+        "a".repeat(64)
+    );
+    let results = parse_search_response(&body);
+    assert_eq!(
+        results[0]
+            .verification_cert
+            .as_deref()
+            .and_then(report_verdict),
+        Some("UNSAFE")
+    );
+}
+
+#[test]
+fn test_split_name_requires_namespace() {
+    assert_eq!(
+        split_name("alice/merkle_utils"),
+        Ok(("alice", "merkle_utils"))
+    );
+    assert_eq!(
+        split_name("alice/std.math.add"),
+        Ok(("alice", "std.math.add"))
+    );
+    assert!(split_name("merkle_utils").is_err());
+    assert!(split_name("/merkle_utils").is_err());
+    assert!(split_name("al.ice/x").is_err());
+    assert!(split_name("alice/a/b").is_err());
+}
+
+#[test]
+fn test_first_bind_claims_namespace_for_its_identity() {
+    let alice = token_identity("alice-token");
+    let mallory = token_identity("mallory-token");
+    let mut names = NameTable::new();
+
+    assert_eq!(names.bind("alice/utils", &"a".repeat(64), &alice), Ok(true));
+    assert_eq!(names.owner("alice"), Some(alice.as_str()));
+    assert!(names
+        .bind("alice/utils", &"b".repeat(64), &mallory)
+        .unwrap_err()
+        .contains("owned by another publisher"));
+    assert!(names
+        .bind("alice/other", &"b".repeat(64), &mallory)
+        .is_err());
+    assert!(names
+        .yank("alice/utils", &"a".repeat(64), &mallory)
+        .is_err());
+    assert_eq!(names.resolve("alice/utils"), Some("a".repeat(64).as_str()));
+}
+
+#[test]
+fn test_yank_hides_binding_but_keeps_history() {
+    let alice = token_identity("alice-token");
+    let mut names = NameTable::new();
+    let (v1, v2) = ("a".repeat(64), "b".repeat(64));
+    names.bind("alice/utils", &v1, &alice).unwrap();
+    names.bind("alice/utils", &v2, &alice).unwrap();
+
+    names.yank("alice/utils", &v2, &alice).unwrap();
+    assert_eq!(names.resolve("alice/utils"), Some(v1.as_str()));
+    names.yank("alice/utils", &v1, &alice).unwrap();
+    assert_eq!(names.resolve("alice/utils"), None);
+    assert_eq!(names.history("alice/utils").len(), 2);
+    assert!(names.history("alice/utils").iter().all(|b| b.yanked));
+
+    assert!(names.yank("alice/utils", &"c".repeat(64), &alice).is_err());
+    assert_eq!(names.bind("alice/utils", &v1, &alice), Ok(false));
+    assert_eq!(names.resolve("alice/utils"), Some(v1.as_str()));
+}
+
+/// Serve one scripted response per connection on a local port; the
+/// handle yields each request line received.
+fn scripted_registry(responses: Vec<String>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    use std::io::{BufRead, BufReader, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            requests.push(line.trim().to_string());
+            while line.trim() != "" {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+        requests
+    });
+    (url, handle)
+}
+
+fn http_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+fn quick_client(url: &str) -> RegistryClient {
+    RegistryClient::new(url)
+        .with_proxy(None)
+        .with_retries(2, std::time::Duration::from_millis(1))
+}
+
+#[test]
+fn test_client_retries_transient_status_then_succeeds() {
+    let hash = "a".repeat(64);
+    let body = format!("{{\"hash\":\"{}\",\"source\":\"fn f() {{ }}\"}}", hash);
+    let (url, server) = scripted_registry(vec![
+        http_response("503 Service Unavailable", ""),
+        http_response("200 OK", &body),
+    ]);
+    let pull = quick_client(&url).pull(&hash).unwrap();
+    assert_eq!(pull.hash, hash);
+    assert_eq!(server.join().unwrap().len(), 2);
+}
+
+#[test]
+fn test_client_errors_distinguish_server_and_hash_mismatch() {
+    let (hash, other) = ("a".repeat(64), "b".repeat(64));
+    let body = format!("{{\"hash\":\"{}\",\"source\":\"fn f() {{ }}\"}}", other);
+    let (url, server) = scripted_registry(vec![
+        http_response("404 Not Found", ""),
+        http_response("200 OK", &body),
+    ]);
+    let client = quick_client(&url);
+
+    let err = client.pull(&hash).unwrap_err();
+    assert!(matches!(err, RegistryError::Server { status: 404, .. }));
+    assert!(!err.is_transient());
+
+    let err = client.pull(&hash).unwrap_err();
+    assert_eq!(
+        err,
+        RegistryError::HashMismatch {
+            expected: hash,
+            found: other,
+        }
+    );
+    assert!(err.to_string().starts_with("hash mismatch"));
+    assert_eq!(server.join().unwrap().len(), 2);
+}
+
+#[test]
+fn test_unreachable_registry_is_network_error_after_retries() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let err = quick_client(&format!("http://127.0.0.1:{}", port))
+        .health()
+        .unwrap_err();
+    assert!(matches!(err, RegistryError::Network { attempts: 3, .. }));
+    assert!(err.is_transient());
+    assert!(err.to_string().starts_with("network error"));
+}
+
+#[test]
+fn test_proxied_request_uses_absolute_uri() {
+    let (proxy, server) = scripted_registry(vec![http_response("200 OK", "ok")]);
+    let client = RegistryClient::new("http://registry.example:8090").with_proxy(Some(proxy));
+    assert_eq!(client.health(), Ok(true));
+    assert_eq!(
+        server.join().unwrap(),
+        ["GET http://registry.example:8090/health HTTP/1.1"]
+    );
+}
+
+#[test]
+fn test_proxy_for_honours_no_proxy() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        }
+    };
+    let proxied = env(&[("HTTP_PROXY", "http://proxy:3128")]);
+    assert_eq!(
+        proxy_for("http://atlas.example.com", proxied),
+        Some("http://proxy:3128".to_string())
+    );
+
+    let bypassed = env(&[
+        ("http_proxy", "proxy:3128"),
+        ("NO_PROXY", "localhost, .example.com"),
+    ]);
+    assert_eq!(proxy_for("http://atlas.example.com:8090", &bypassed), None);
+    assert_eq!(
+        proxy_for("http://example.org", &bypassed),
+        Some("proxy:3128".to_string())
+    );
+    assert_eq!(proxy_for("http://localhost", &bypassed), None);
+
+    let everything = env(&[("HTTP_PROXY", "proxy:3128"), ("no_proxy", "*")]);
+    assert_eq!(proxy_for("http://example.org", everything), None);
+    assert_eq!(proxy_for("http://example.org", env(&[])), None);
+}
+
+/// An in-memory chain: anchors by digest, plus every anchor written.
+#[derive(Default)]
+struct MemoryLedger {
+    anchors: Vec<Anchor>,
+    written: std::cell::RefCell<Vec<(String, Anchor)>>,
+}
+
+impl AnchorLedger for MemoryLedger {
+    fn lookup(&self, _chain: &str, digest: &str) -> Result<Option<Anchor>, String> {
+        Ok(self.anchors.iter().find(|a| a.digest == digest).cloned())
+    }
+
+    fn write(&self, chain: &str, anchor: &Anchor) -> Result<String, String> {
+        let mut written = self.written.borrow_mut();
+        written.push((chain.to_string(), anchor.clone()));
+        Ok(format!("tx{}", written.len()))
+    }
+}
+
+#[test]
+fn test_anchor_lines_roundtrip() {
+    let anchor = Anchor {
+        digest: "d".repeat(64),
+        hash: "a".repeat(64),
+        registry: "http://registry.example:8090".to_string(),
+        tx: "0xfeed".to_string(),
+    };
+    assert_eq!(Anchor::parse(&anchor.to_lines()), Some(anchor));
+    assert_eq!(Anchor::parse("digest=dd\nhash=aa\n"), None);
+}
+
+#[test]
+fn test_pull_anchored_checks_source_against_digest() {
+    let (hash, source) = ("a".repeat(64), "fn f() { }");
+    let body = format!("{{\"hash\":\"{}\",\"source\":\"{}\"}}", hash, source);
+    let (url, server) = scripted_registry(vec![
+        http_response("200 OK", &body),
+        http_response("200 OK", &body),
+    ]);
+    let anchor = |digest: String| Anchor {
+        digest,
+        hash: hash.clone(),
+        registry: url.clone(),
+        tx: "0xfeed".to_string(),
+    };
+    let digest = source_digest(source);
+    let forged = "e".repeat(64);
+    let ledger = MemoryLedger {
+        anchors: vec![anchor(digest.clone()), anchor(forged.clone())],
+        ..MemoryLedger::default()
+    };
+
+    let (found, pull) = pull_anchored(&ledger, "neptune", &digest).unwrap();
+    assert_eq!(found.tx, "0xfeed");
+    assert_eq!(pull.source, source);
+
+    let err = pull_anchored(&ledger, "neptune", &forged).unwrap_err();
+    assert!(err.starts_with("hash mismatch"), "{}", err);
+    let err = pull_anchored(&ledger, "neptune", &"f".repeat(64)).unwrap_err();
+    assert!(err.contains("not anchored on 'neptune'"));
+    assert_eq!(server.join().unwrap().len(), 2);
+}
+
+#[test]
+fn test_anchor_published_writes_the_source_digest() {
+    let ledger = MemoryLedger::default();
+    let published = PublishResult {
+        hash: "a".repeat(64),
+        created: true,
+        name_bound: false,
+    };
+    let anchor = anchor_published(
+        &ledger,
+        "neptune",
+        "http://registry.example/",
+        &published,
+        "fn f() { }",
+    )
+    .unwrap();
+    assert_eq!(anchor.digest, source_digest("fn f() { }"));
+    assert_eq!(anchor.registry, "http://registry.example");
+    assert_eq!(anchor.tx, "tx1");
+    let written = ledger.written.borrow();
+    assert_eq!(written[0].0, "neptune");
+    assert_eq!(written[0].1.hash, published.hash);
+}
@@ -1,3 +1,5 @@
+use crate::config::project::ProjectMetadata;
+
 // ─── Published Definition (wire format) ───────────────────────────
 
 /// A definition as published to the registry (JSON wire format).
@@ -29,6 +31,13 @@ pub struct PublishedDefinition {
     pub verified: bool,
     /// Verification certificate (opaque string, if available).
     pub verification_cert: Option<String>,
+    /// Publisher's ed25519 public key (hex), if signed.
+    pub signer_public_key: Option<String>,
+    /// ed25519 signature over `hash` (hex), if signed.
+    pub signer_signature: Option<String>,
+    /// License, authors, description and repository of the publishing
+    /// project.
+    pub metadata: ProjectMetadata,
 }
 
 /// Search result entry.
@@ -39,6 +48,8 @@ pub struct SearchResult {
     pub module: String,
     pub signature: String,
     pub verified: bool,
+    /// Registry verification report, if the registry ran the verifier.
+    pub verification_cert: Option<String>,
     pub tags: Vec<String>,
 }
 
@@ -61,6 +72,10 @@ pub struct PullResult {
     pub dependencies: Vec<String>,
     pub requires: Vec<String>,
     pub ensures: Vec<String>,
+    /// Publisher's public key (hex); empty when unsigned.
+    pub signer_public_key: String,
+    /// Signature over `hash` (hex); empty when unsigned.
+    pub signer_signature: String,
 }
 
 // ─── Registry Client ──────────────────────────────────────────────
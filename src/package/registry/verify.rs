@@ -0,0 +1,65 @@
+use crate::ast::Item;
+
+use super::json::parse_publish_body;
+use super::types::PublishedDefinition;
+
+/// Accept a publish request on the registry side.
+///
+/// Parses the body, discards whatever `verified` and `verification_cert`
+/// the client sent, and re-derives both with [`verify_definition`].
+/// A registry stores the returned definition as-is.
+pub fn accept_publish(body: &str) -> Result<PublishedDefinition, String> {
+    let mut def = parse_publish_body(body)?;
+    verify_definition(&mut def)?;
+    Ok(def)
+}
+
+/// Re-parse a definition and run the static verifier over it.
+///
+/// Sets `verified` to the verifier's verdict and `verification_cert` to
+/// its full report. Fails only when the source is not a single function
+/// with a body, which a registry must reject outright.
+pub fn verify_definition(def: &mut PublishedDefinition) -> Result<(), String> {
+    let module = if def.module.is_empty() {
+        "registry"
+    } else {
+        &def.module
+    };
+    let source = format!("module {}\n\n{}\n", module, def.source);
+    let file = crate::parse_source_silent(&source, "registry.tri").map_err(|errors| {
+        let first = errors.first().map(|d| d.message.as_str()).unwrap_or("");
+        format!("definition {} does not parse: {}", def.hash, first)
+    })?;
+
+    let fn_count = file
+        .items
+        .iter()
+        .filter(|item| matches!(&item.node, Item::Fn(f) if f.body.is_some()))
+        .count();
+    if fn_count != 1 {
+        return Err(format!(
+            "definition {} must be exactly one function with a body, found {}",
+            def.hash, fn_count
+        ));
+    }
+
+    let (verified, report) = match crate::sym::analyze_all(&file).first() {
+        Some((_, system)) => {
+            let report = crate::solve::verify(system);
+            (report.is_safe(), report.format_report())
+        }
+        // Intrinsics and test functions have nothing to analyze.
+        None => (false, "Verdict: SKIPPED — nothing to analyze\n".to_string()),
+    };
+    def.verified = verified;
+    def.verification_cert = Some(report);
+    Ok(())
+}
+
+/// The `Verdict:` line of a verification report, for one-line display.
+pub fn report_verdict(report: &str) -> Option<&str> {
+    report
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix("Verdict: "))
+}
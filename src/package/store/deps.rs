@@ -137,6 +137,15 @@ fn walk_expr_for_calls(
                 walk_expr_for_calls(&elem.node, fn_hashes, self_name, deps, seen);
             }
         }
+        Expr::If {
+            cond,
+            then_block,
+            else_block,
+        } => {
+            walk_expr_for_calls(&cond.node, fn_hashes, self_name, deps, seen);
+            walk_block_for_calls(&then_block.node, fn_hashes, self_name, deps, seen);
+            walk_block_for_calls(&else_block.node, fn_hashes, self_name, deps, seen);
+        }
         Expr::Literal(_) | Expr::Var(_) => {}
     }
 }
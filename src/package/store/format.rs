@@ -68,7 +68,7 @@ fn format_block(block: &ast::Block, out: &mut String, indent: usize) {
     }
     if let Some(ref tail) = block.tail_expr {
         out.push_str(&pad);
-        out.push_str(&format_expr(&tail.node));
+        out.push_str(&format_tail(&tail.node));
         out.push('\n');
     }
 }
@@ -263,6 +263,14 @@ fn format_stmt(stmt: &Stmt, out: &mut String, pad: &str, indent: usize) {
                     ast::MatchPattern::Wildcard => {
                         out.push('_');
                     }
+                    ast::MatchPattern::Some(binding) => {
+                        out.push_str("Some(");
+                        out.push_str(&binding.node);
+                        out.push(')');
+                    }
+                    ast::MatchPattern::None => {
+                        out.push_str("None");
+                    }
                     ast::MatchPattern::Struct { name, fields } => {
                         out.push_str(&name.node);
                         out.push_str(" { ");
@@ -353,6 +361,39 @@ fn format_expr(expr: &Expr) -> String {
             let inner: Vec<String> = elems.iter().map(|e| format_expr(&e.node)).collect();
             format!("({})", inner.join(", "))
         }
+        Expr::If {
+            cond,
+            then_block,
+            else_block,
+        } => format!(
+            "if {} {} else {}",
+            format_expr(&cond.node),
+            format_branch(&then_block.node),
+            format_branch(&else_block.node)
+        ),
+    }
+}
+
+/// Format one branch of an `if` expression: inline when it is a bare
+/// value, one statement per line otherwise.
+fn format_branch(block: &ast::Block) -> String {
+    match (&block.stmts[..], &block.tail_expr) {
+        ([], Some(tail)) => format!("{{ {} }}", format_tail(&tail.node)),
+        _ => {
+            let mut out = String::from("{\n");
+            format_block(block, &mut out, 1);
+            out.push('}');
+            out
+        }
+    }
+}
+
+/// Format a block's tail value. A tail `if` is parenthesized so it does
+/// not read as an `if` statement.
+fn format_tail(expr: &Expr) -> String {
+    match expr {
+        Expr::If { .. } => format!("({})", format_expr(expr)),
+        _ => format_expr(expr),
     }
 }
 
@@ -367,12 +408,14 @@ fn format_expr_prec(expr: &Expr, parent_op: &ast::BinOp, _is_left: bool) -> Stri
 
 fn op_precedence(op: &ast::BinOp) -> u8 {
     match op {
-        ast::BinOp::Eq => 2,
-        ast::BinOp::Lt => 4,
-        ast::BinOp::Add => 6,
-        ast::BinOp::Mul | ast::BinOp::XFieldMul => 8,
-        ast::BinOp::BitAnd | ast::BinOp::BitXor => 10,
-        ast::BinOp::DivMod => 12,
+        ast::BinOp::Or => 2,
+        ast::BinOp::And => 4,
+        ast::BinOp::Eq => 6,
+        ast::BinOp::Lt => 8,
+        ast::BinOp::Add => 10,
+        ast::BinOp::Mul | ast::BinOp::XFieldMul => 12,
+        ast::BinOp::BitAnd | ast::BinOp::BitXor => 14,
+        ast::BinOp::DivMod => 16,
     }
 }
 
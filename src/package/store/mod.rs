@@ -58,6 +58,9 @@ pub struct Definition {
     pub ensures: Vec<String>,
     /// When this was first stored (Unix timestamp).
     pub first_seen: u64,
+    /// Hash of the exact source text last stored under this hash
+    /// (`hash::hash_file_raw`), when the source was available.
+    pub raw_hash: Option<ContentHash>,
 }
 
 /// A name binding entry in history.
@@ -131,6 +134,20 @@ impl Codebase {
 
     /// Add a parsed file to the codebase: hash all functions, store definitions.
     pub fn add_file(&mut self, file: &ast::File) -> AddResult {
+        self.add_definitions(file, BTreeMap::new())
+    }
+
+    /// Like `add_file`, also recording the raw-text hash of each
+    /// definition from the file's `source`.
+    pub fn add_source(&mut self, file: &ast::File, source: &str) -> AddResult {
+        self.add_definitions(file, hash::hash_file_raw(file, source))
+    }
+
+    fn add_definitions(
+        &mut self,
+        file: &ast::File,
+        raw_hashes: BTreeMap<String, ContentHash>,
+    ) -> AddResult {
         let fn_hashes = hash::hash_file(file);
         let module = file.name.node.clone();
         let now = unix_timestamp();
@@ -180,6 +197,7 @@ impl Codebase {
                         .get(&hash)
                         .map(|d| d.first_seen)
                         .unwrap_or(now),
+                    raw_hash: raw_hashes.get(&name).copied(),
                 };
 
                 self.definitions.insert(hash, def);
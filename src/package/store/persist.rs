@@ -190,6 +190,12 @@ pub(super) fn serialize_definition(def: &Definition) -> String {
     out.push_str(&def.first_seen.to_string());
     out.push('\n');
 
+    if let Some(raw) = &def.raw_hash {
+        out.push_str("raw_hash=");
+        out.push_str(&raw.to_hex());
+        out.push('\n');
+    }
+
     out
 }
 
@@ -260,6 +266,9 @@ pub(super) fn deserialize_definition(text: &str) -> Option<Definition> {
         .get("first_seen")
         .and_then(|s| s.parse().ok())
         .unwrap_or(0);
+    let raw_hash = map
+        .get("raw_hash")
+        .and_then(|s| ContentHash::from_hex(s.trim()));
 
     Some(Definition {
         source,
@@ -271,6 +280,7 @@ pub(super) fn deserialize_definition(text: &str) -> Option<Definition> {
         requires,
         ensures,
         first_seen,
+        raw_hash,
     })
 }
 
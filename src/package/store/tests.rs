@@ -299,6 +299,7 @@ fn test_definition_serialization_roundtrip() {
         requires: vec!["a > 0".to_string()],
         ensures: vec!["result == a + b".to_string()],
         first_seen: 1707580000,
+        raw_hash: Some(ContentHash([0x02; 32])),
     };
 
     let serialized = serialize_definition(&def);
@@ -314,6 +315,7 @@ fn test_definition_serialization_roundtrip() {
     assert_eq!(deserialized.requires, def.requires);
     assert_eq!(deserialized.ensures, def.ensures);
     assert_eq!(deserialized.first_seen, def.first_seen);
+    assert_eq!(deserialized.raw_hash, def.raw_hash);
 }
 
 #[test]
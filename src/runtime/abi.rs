@@ -0,0 +1,301 @@
+//! Program ABI: the externally visible interface of a compiled program.
+//!
+//! Lists the entry point and its `#[entry]` tags, public functions,
+//! events, public structs, and `ensure` error codes of the entry module
+//! with their source-level types, so clients can encode inputs and
+//! decode outputs without reading the `.tri` file.
+
+use std::collections::BTreeSet;
+
+use crate::ast::display::format_ast_type;
+use crate::ast::eval::PureFns;
+use crate::ast::navigate::calls_in_block;
+use crate::ast::{Expr, File, FileKind, Item};
+use crate::tir::lower::EventChannel;
+
+use super::artifact::json_string;
+
+/// A function parameter or struct/event field: name and type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiField {
+    pub name: String,
+    /// Source-level type (`Field`, `[U32; 4]`, `std.crypto.Point`).
+    pub ty: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiFunction {
+    pub name: String,
+    pub params: Vec<AbiField>,
+    /// Return type, `None` for unit.
+    pub returns: Option<String>,
+    /// Content hash of the function (hex).
+    pub hash: String,
+}
+
+/// An `#[entry]` operation and the tag that selects it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiEntry {
+    /// First public input read by the generated `main`.
+    pub tag: u64,
+    pub name: String,
+}
+
+/// An `ensure(cond, code)` check: the code it writes to public output
+/// before halting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiError {
+    pub code: u64,
+    /// The constant the code is written as, `None` for a literal.
+    pub name: Option<String>,
+    /// The function the check is in.
+    pub function: String,
+}
+
+/// Named field list: an event or a struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiRecord {
+    pub name: String,
+    pub fields: Vec<AbiField>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramAbi {
+    /// Program or module name.
+    pub name: String,
+    /// `program` or `module`.
+    pub kind: &'static str,
+    /// Entry point for programs, `None` for modules.
+    pub entry_point: Option<String>,
+    /// The entry point and every `pub fn`, in declaration order.
+    pub functions: Vec<AbiFunction>,
+    /// `#[entry]` operations `main` dispatches to, empty for a
+    /// hand-written `main`.
+    pub entries: Vec<AbiEntry>,
+    pub events: Vec<AbiRecord>,
+    /// Where the target writes `events`.
+    pub event_channel: EventChannel,
+    /// `pub struct` definitions.
+    pub structs: Vec<AbiRecord>,
+    /// `ensure` checks of every function, in source order.
+    pub errors: Vec<AbiError>,
+}
+
+impl ProgramAbi {
+    /// Extract the ABI from the entry module of a project.
+    pub fn from_file(file: &File) -> Self {
+        let is_program = file.kind == FileKind::Program;
+        let hashes = crate::hash::hash_file(file);
+        let field = |name: &str, ty: &crate::ast::Type| AbiField {
+            name: name.to_string(),
+            ty: format_ast_type(ty),
+        };
+        let mut abi = ProgramAbi {
+            name: file.name.node.clone(),
+            kind: if is_program { "program" } else { "module" },
+            entry_point: None,
+            functions: Vec::new(),
+            entries: Vec::new(),
+            events: Vec::new(),
+            event_channel: EventChannel::Shared,
+            structs: Vec::new(),
+            errors: Vec::new(),
+        };
+        let mut constants = PureFns::new();
+        constants.add_module(file, &BTreeSet::new());
+        for item in &file.items {
+            match &item.node {
+                Item::Fn(def) => {
+                    let is_entry = is_program && def.name.node == "main";
+                    if is_entry {
+                        abi.entry_point = Some(def.name.node.clone());
+                    }
+                    if is_program && def.is_entry {
+                        abi.entries.push(AbiEntry {
+                            tag: abi.entries.len() as u64,
+                            name: def.name.node.clone(),
+                        });
+                    }
+                    if def.is_test {
+                        continue;
+                    }
+                    let calls = def.body.iter().flat_map(|b| calls_in_block(&b.node));
+                    for call in calls {
+                        let Expr::Call { path, args, .. } = &call.node else {
+                            continue;
+                        };
+                        let [_, code] = args.as_slice() else {
+                            continue;
+                        };
+                        if path.node.as_dotted() != "ensure" {
+                            continue;
+                        }
+                        let Some(value) = constants.eval_const(&file.name.node, &code.node) else {
+                            continue;
+                        };
+                        abi.errors.push(AbiError {
+                            code: value,
+                            name: match &code.node {
+                                Expr::Var(name) => Some(name.clone()),
+                                _ => None,
+                            },
+                            function: def.name.node.clone(),
+                        });
+                    }
+                    if !(def.is_pub || is_entry) {
+                        continue;
+                    }
+                    abi.functions.push(AbiFunction {
+                        name: def.name.node.clone(),
+                        params: def
+                            .params
+                            .iter()
+                            .map(|p| field(&p.name.node, &p.ty.node))
+                            .collect(),
+                        returns: def.return_ty.as_ref().map(|t| format_ast_type(&t.node)),
+                        hash: hashes
+                            .get(&def.name.node)
+                            .map(|h| h.to_hex())
+                            .unwrap_or_default(),
+                    });
+                }
+                Item::Event(def) => abi.events.push(AbiRecord {
+                    name: def.name.node.clone(),
+                    fields: def
+                        .fields
+                        .iter()
+                        .map(|f| field(&f.name.node, &f.ty.node))
+                        .collect(),
+                }),
+                Item::Struct(def) if def.is_pub => abi.structs.push(AbiRecord {
+                    name: def.name.node.clone(),
+                    fields: def
+                        .fields
+                        .iter()
+                        .map(|f| field(&f.name.node, &f.ty.node))
+                        .collect(),
+                }),
+                _ => {}
+            }
+        }
+        abi
+    }
+
+    /// Serialize to JSON (`trident build --emit=abi`).
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\n");
+        out.push_str(&format!("  \"name\": {},\n", json_string(&self.name)));
+        out.push_str(&format!("  \"kind\": {},\n", json_string(self.kind)));
+        match &self.entry_point {
+            Some(ep) => out.push_str(&format!("  \"entry_point\": {},\n", json_string(ep))),
+            None => out.push_str("  \"entry_point\": null,\n"),
+        }
+
+        out.push_str("  \"functions\": [");
+        let functions: Vec<String> = self
+            .functions
+            .iter()
+            .map(|f| {
+                let returns = match &f.returns {
+                    Some(ty) => json_string(ty),
+                    None => "null".to_string(),
+                };
+                format!(
+                    "\n    {{ \"name\": {}, \"params\": {}, \"returns\": {}, \"hash\": {} }}",
+                    json_string(&f.name),
+                    fields_json(&f.params),
+                    returns,
+                    json_string(&f.hash)
+                )
+            })
+            .collect();
+        push_list(&mut out, &functions);
+        out.push_str(",\n");
+
+        out.push_str("  \"entries\": [");
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|e| {
+                format!(
+                    "\n    {{ \"tag\": {}, \"name\": {} }}",
+                    e.tag,
+                    json_string(&e.name)
+                )
+            })
+            .collect();
+        push_list(&mut out, &entries);
+        out.push_str(",\n");
+
+        out.push_str("  \"events\": [");
+        push_list(&mut out, &records_json(&self.events));
+        out.push_str(",\n");
+        out.push_str(&format!(
+            "  \"event_channel\": {},\n",
+            json_string(self.event_channel.name())
+        ));
+
+        out.push_str("  \"structs\": [");
+        push_list(&mut out, &records_json(&self.structs));
+        out.push_str(",\n");
+
+        out.push_str("  \"errors\": [");
+        let errors: Vec<String> = self
+            .errors
+            .iter()
+            .map(|e| {
+                let name = match &e.name {
+                    Some(name) => json_string(name),
+                    None => "null".to_string(),
+                };
+                format!(
+                    "\n    {{ \"code\": {}, \"name\": {}, \"function\": {} }}",
+                    e.code,
+                    name,
+                    json_string(&e.function)
+                )
+            })
+            .collect();
+        push_list(&mut out, &errors);
+        out.push_str("\n}\n");
+        out
+    }
+}
+
+fn fields_json(fields: &[AbiField]) -> String {
+    let parts: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            format!(
+                "{{ \"name\": {}, \"type\": {} }}",
+                json_string(&f.name),
+                json_string(&f.ty)
+            )
+        })
+        .collect();
+    format!("[{}]", parts.join(", "))
+}
+
+fn records_json(records: &[AbiRecord]) -> Vec<String> {
+    records
+        .iter()
+        .map(|r| {
+            format!(
+                "\n    {{ \"name\": {}, \"fields\": {} }}",
+                json_string(&r.name),
+                fields_json(&r.fields)
+            )
+        })
+        .collect()
+}
+
+/// Close a JSON array opened by the caller: `]` when empty, otherwise
+/// the comma-separated entries and `\n  ]`.
+fn push_list(out: &mut String, entries: &[String]) {
+    if entries.is_empty() {
+        out.push(']');
+    } else {
+        out.push_str(&entries.join(","));
+        out.push_str("\n  ]");
+    }
+}
@@ -153,7 +153,7 @@ impl ProgramBundle {
 // ─── JSON Helpers ──────────────────────────────────────────────────
 
 /// JSON-escape a string and wrap in quotes.
-fn json_string(s: &str) -> String {
+pub(crate) fn json_string(s: &str) -> String {
     let mut out = String::from('"');
     for ch in s.chars() {
         match ch {
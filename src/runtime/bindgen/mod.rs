@@ -0,0 +1,164 @@
+//! Host bindings: generate client code from a program's ABI.
+//!
+//! Each language gets typed structs for the ABI's public structs and
+//! events, codecs that pack them into field elements in declaration
+//! order, and the program's claim in the `std.proof` layout, so hosts
+//! never hand-pack field elements or duplicate layout constants.
+
+use std::collections::BTreeSet;
+
+use super::abi::ProgramAbi;
+
+mod rust;
+mod ts;
+
+pub use rust::rust_bindings;
+pub use ts::ts_bindings;
+
+/// An ABI type string (`[U32; 4]`, `(Field, Bool)`, `std.crypto.Point`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum AbiType {
+    Field,
+    XField,
+    Bool,
+    U32,
+    Digest,
+    Array(Box<AbiType>, usize),
+    Tuple(Vec<AbiType>),
+    Named(String),
+}
+
+impl AbiType {
+    pub(super) fn parse(text: &str) -> Result<Self, String> {
+        let mut rest = text;
+        let ty = Self::parse_inner(&mut rest)?;
+        if !rest.trim().is_empty() {
+            return Err(format!("unexpected '{}' in type '{}'", rest.trim(), text));
+        }
+        Ok(ty)
+    }
+
+    fn parse_inner(rest: &mut &str) -> Result<Self, String> {
+        *rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix('[') {
+            *rest = after;
+            let elem = Self::parse_inner(rest)?;
+            let after = rest
+                .trim_start()
+                .strip_prefix(';')
+                .ok_or("expected ';' in array type")?;
+            let (size, after) = after.split_once(']').ok_or("expected ']' in array type")?;
+            let size = size
+                .trim()
+                .parse()
+                .map_err(|_| format!("array size '{}' is not a number", size.trim()))?;
+            *rest = after;
+            return Ok(AbiType::Array(Box::new(elem), size));
+        }
+        if let Some(after) = rest.strip_prefix('(') {
+            *rest = after;
+            let mut elems = Vec::new();
+            loop {
+                *rest = rest.trim_start();
+                if let Some(after) = rest.strip_prefix(')') {
+                    *rest = after;
+                    return Ok(AbiType::Tuple(elems));
+                }
+                // Named tuple components (`a: Field`) drop their names.
+                let end = rest.find([':', ',', ')', '(', '[']).unwrap_or(rest.len());
+                if rest[end..].starts_with(':') {
+                    *rest = &rest[end + 1..];
+                }
+                elems.push(Self::parse_inner(rest)?);
+                *rest = rest.trim_start();
+                if let Some(after) = rest.strip_prefix(',') {
+                    *rest = after;
+                }
+            }
+        }
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(rest.len());
+        let (name, after) = rest.split_at(end);
+        *rest = after;
+        Ok(match name {
+            "Field" => AbiType::Field,
+            "XField" => AbiType::XField,
+            "Bool" => AbiType::Bool,
+            "U32" => AbiType::U32,
+            "Digest" => AbiType::Digest,
+            "" => return Err("expected a type".to_string()),
+            _ => AbiType::Named(name.to_string()),
+        })
+    }
+
+    fn tuple_arities(&self, out: &mut BTreeSet<usize>) {
+        match self {
+            AbiType::Array(elem, _) => elem.tuple_arities(out),
+            AbiType::Tuple(elems) => {
+                out.insert(elems.len());
+                for elem in elems {
+                    elem.tuple_arities(out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The generated name of an ABI struct referenced by `path`.
+fn struct_name<'a>(path: &'a str, structs: &BTreeSet<String>) -> Result<&'a str, String> {
+    let name = path.rsplit('.').next().unwrap_or(path);
+    if !structs.contains(name) {
+        return Err(format!("type '{}' is not a `pub struct` of the ABI", path));
+    }
+    Ok(name)
+}
+
+/// Items every generated module defines.
+const RESERVED: &[&str] = &[
+    "Fields", "XField", "Digest", "Claim", "Event", "Codec", "Reader",
+];
+
+/// Names of the ABI's structs and events, which share one namespace in
+/// the generated code.
+fn check_names(abi: &ProgramAbi) -> Result<BTreeSet<String>, String> {
+    let names: BTreeSet<String> = abi
+        .structs
+        .iter()
+        .chain(&abi.events)
+        .map(|r| r.name.clone())
+        .collect();
+    if names.len() != abi.structs.len() + abi.events.len() {
+        return Err("a struct and an event share a name".to_string());
+    }
+    if let Some(name) = RESERVED.iter().find(|n| names.contains(**n)) {
+        return Err(format!(
+            "'{}' clashes with a name the bindings define; rename it",
+            name
+        ));
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abi_type_strings_round_trip_to_rust_types() {
+        let structs: BTreeSet<String> = ["Point".to_string()].into();
+        let rust = |s: &str| AbiType::parse(s).unwrap().rust(&structs).unwrap();
+        assert_eq!(rust("Field"), "u64");
+        assert_eq!(rust("[U32; 4]"), "[u32; 4]");
+        assert_eq!(rust("(Field, [Bool; 2])"), "(u64, [bool; 2])");
+        assert_eq!(rust("(a: Digest, b: XField)"), "(Digest, XField)");
+        assert_eq!(rust("std.geo.Point"), "Point");
+        assert!(AbiType::parse("Secret")
+            .unwrap()
+            .rust(&structs)
+            .unwrap_err()
+            .contains("not a `pub struct`"));
+        assert!(AbiType::parse("[Field; N]").is_err());
+    }
+}
@@ -0,0 +1,488 @@
+//! Rust bindings: a dependency-free module with a `Fields` codec,
+//! the program's claim, its initial RAM, and a `verify_proof` wrapper
+//! for triton-vm.
+
+use std::collections::BTreeSet;
+
+use crate::deploy::claim::{ProofClaim, DIGEST_WIDTH};
+use crate::runtime::abi::{AbiField, AbiRecord, ProgramAbi};
+use crate::tir::ram::{RamLayout, RamRegionKind};
+
+use super::{check_names, struct_name, AbiType};
+
+impl AbiType {
+    pub(super) fn rust(&self, structs: &BTreeSet<String>) -> Result<String, String> {
+        Ok(match self {
+            AbiType::Field => "u64".to_string(),
+            AbiType::XField => "XField".to_string(),
+            AbiType::Bool => "bool".to_string(),
+            AbiType::U32 => "u32".to_string(),
+            AbiType::Digest => "Digest".to_string(),
+            AbiType::Array(elem, size) => format!("[{}; {}]", elem.rust(structs)?, size),
+            AbiType::Tuple(elems) => {
+                let parts: Result<Vec<_>, _> = elems.iter().map(|t| t.rust(structs)).collect();
+                match parts?.as_slice() {
+                    [] => "()".to_string(),
+                    [one] => format!("({},)", one),
+                    many => format!("({})", many.join(", ")),
+                }
+            }
+            AbiType::Named(path) => struct_name(path, structs)?.to_string(),
+        })
+    }
+}
+
+/// Field names that are Rust keywords need a raw identifier.
+fn rust_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else",
+        "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+        "move", "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "type",
+        "unsafe", "use", "where", "while", "yield",
+    ];
+    if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+const PRELUDE: &str = r#"/// A value with a fixed field-element encoding.
+pub trait Fields: Sized {
+    /// Number of field elements in the encoding.
+    const WIDTH: usize;
+    fn encode(&self, out: &mut Vec<u64>);
+    /// Decode from the front of `fields`, advancing it.
+    fn decode(fields: &mut &[u64]) -> Result<Self, String>;
+}
+
+pub type XField = [u64; 3];
+pub type Digest = [u64; DIGEST_WIDTH];
+
+/// Encode a value as a field stream.
+pub fn encode<T: Fields>(value: &T) -> Vec<u64> {
+    let mut out = Vec::with_capacity(T::WIDTH);
+    value.encode(&mut out);
+    out
+}
+
+/// Decode a value from the front of a field stream.
+pub fn decode<T: Fields>(mut fields: &[u64]) -> Result<T, String> {
+    T::decode(&mut fields)
+}
+
+fn take(fields: &mut &[u64]) -> Result<u64, String> {
+    let (&first, rest) = fields
+        .split_first()
+        .ok_or_else(|| "unexpected end of field stream".to_string())?;
+    *fields = rest;
+    Ok(first)
+}
+
+impl Fields for u64 {
+    const WIDTH: usize = 1;
+    fn encode(&self, out: &mut Vec<u64>) {
+        out.push(*self);
+    }
+    fn decode(fields: &mut &[u64]) -> Result<Self, String> {
+        take(fields)
+    }
+}
+
+impl Fields for u32 {
+    const WIDTH: usize = 1;
+    fn encode(&self, out: &mut Vec<u64>) {
+        out.push(u64::from(*self));
+    }
+    fn decode(fields: &mut &[u64]) -> Result<Self, String> {
+        let v = take(fields)?;
+        u32::try_from(v).map_err(|_| format!("U32 out of range: {}", v))
+    }
+}
+
+impl Fields for bool {
+    const WIDTH: usize = 1;
+    fn encode(&self, out: &mut Vec<u64>) {
+        out.push(u64::from(*self));
+    }
+    fn decode(fields: &mut &[u64]) -> Result<Self, String> {
+        match take(fields)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => Err(format!("Bool out of range: {}", v)),
+        }
+    }
+}
+
+impl<T: Fields, const N: usize> Fields for [T; N] {
+    const WIDTH: usize = T::WIDTH * N;
+    fn encode(&self, out: &mut Vec<u64>) {
+        for elem in self {
+            elem.encode(out);
+        }
+    }
+    fn decode(fields: &mut &[u64]) -> Result<Self, String> {
+        let elems = (0..N)
+            .map(|_| T::decode(fields))
+            .collect::<Result<Vec<T>, String>>()?;
+        elems
+            .try_into()
+            .map_err(|_| "array length mismatch".to_string())
+    }
+}
+"#;
+
+const INITIAL_RAM: &str = r#"/// Initial RAM for a prover as `(address, value)` pairs: every word of
+/// `RAM_IMAGE`, then `sec_ram` (the witness for `SEC_RAM`, one value list
+/// per slot in order). Feed the pairs to the VM's non-deterministic RAM.
+pub fn initial_ram(sec_ram: &[Vec<u64>]) -> Result<Vec<(u64, u64)>, String> {
+    if sec_ram.len() != SEC_RAM.len() {
+        let (want, got) = (SEC_RAM.len(), sec_ram.len());
+        return Err(format!("expected {} sec ram slots, got {}", want, got));
+    }
+    let mut ram = Vec::new();
+    for (start, values) in RAM_IMAGE {
+        ram.extend(values.iter().enumerate().map(|(i, &v)| (start + i as u64, v)));
+    }
+    for (&(addr, width), values) in SEC_RAM.iter().zip(sec_ram) {
+        if values.len() as u64 != width {
+            let got = values.len();
+            return Err(format!("sec ram slot {} needs {} values, got {}", addr, width, got));
+        }
+        ram.extend(values.iter().enumerate().map(|(i, &v)| (addr + i as u64, v)));
+    }
+    Ok(ram)
+}
+"#;
+
+const CLAIM: &str = r#"/// What a proof of this program asserts, in the `std.proof` layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Claim {
+    pub program_digest: Digest,
+    pub input: Vec<u64>,
+    pub output: Vec<u64>,
+}
+
+impl Claim {
+    /// Claim for this program with the given public input and output.
+    pub fn new(input: Vec<u64>, output: Vec<u64>) -> Self {
+        Claim {
+            program_digest: PROGRAM_DIGEST,
+            input,
+            output,
+        }
+    }
+
+    /// The fields `std.proof.read_claim` reads: digest, input count,
+    /// output count.
+    pub fn encode(&self) -> Vec<u64> {
+        let mut fields = self.program_digest.to_vec();
+        fields.push(self.input.len() as u64);
+        fields.push(self.output.len() as u64);
+        fields
+    }
+
+    /// Public input of a recursive verifier: the encoded claim, then the
+    /// program's public input, then its public output.
+    pub fn verifier_public_input(&self) -> Vec<u64> {
+        let mut fields = self.encode();
+        fields.extend_from_slice(&self.input);
+        fields.extend_from_slice(&self.output);
+        fields
+    }
+}
+
+/// Verify a Triton VM proof of `claim`. Enable the host crate's
+/// `triton-vm` feature (with a `triton-vm` dependency) to use it.
+#[cfg(feature = "triton-vm")]
+pub fn verify_proof(claim: &Claim, proof: &triton_vm::prelude::Proof) -> bool {
+    use triton_vm::prelude::{BFieldElement, Stark};
+    let elems = |v: &[u64]| v.iter().map(|&x| BFieldElement::new(x)).collect::<Vec<_>>();
+    let digest = triton_vm::prelude::Digest::new(claim.program_digest.map(BFieldElement::new));
+    let claim = triton_vm::prelude::Claim::new(digest)
+        .with_input(elems(&claim.input))
+        .with_output(elems(&claim.output));
+    triton_vm::verify(Stark::default(), &claim, proof)
+}
+"#;
+
+/// Generate a Rust module for `abi`. `claim` supplies the program
+/// digest (see `ProofClaim::for_program`); `ram` the RAM image and
+/// `sec ram` slots (see `ram_layout_project`).
+pub fn rust_bindings(
+    abi: &ProgramAbi,
+    claim: &ProofClaim,
+    ram: &RamLayout,
+) -> Result<String, String> {
+    let structs = check_names(abi)?;
+
+    let mut arities = BTreeSet::new();
+    for record in abi.structs.iter().chain(&abi.events) {
+        for field in &record.fields {
+            AbiType::parse(&field.ty)?.tuple_arities(&mut arities);
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "//! Rust bindings for {} `{}`.\n//!\n",
+        abi.kind, abi.name
+    ));
+    out.push_str("//! Generated by `trident bindgen --lang rust`. Do not edit.\n");
+    out.push_str("//! Values travel as Goldilocks field elements (`u64`), struct\n");
+    out.push_str("//! fields in declaration order.\n\n");
+    out.push_str("#![allow(dead_code)]\n\n");
+    out.push_str(&format!(
+        "pub const PROGRAM_NAME: &str = \"{}\";\n",
+        abi.name
+    ));
+    out.push_str(&format!(
+        "pub const DIGEST_WIDTH: usize = {};\n",
+        DIGEST_WIDTH
+    ));
+    let digest: Vec<String> = claim.program_digest.iter().map(u64::to_string).collect();
+    out.push_str("/// Poseidon2 digest of the compiled TASM, as in the package manifest.\n");
+    out.push_str(&format!(
+        "pub const PROGRAM_DIGEST: Digest = [{}];\n\n",
+        digest.join(", ")
+    ));
+    out.push_str(PRELUDE);
+    for arity in arities.into_iter().filter(|&n| n > 0) {
+        out.push('\n');
+        out.push_str(&tuple_impl(arity));
+    }
+
+    for record in &abi.structs {
+        out.push('\n');
+        out.push_str(&record_struct(record, "Public struct", &structs)?);
+    }
+    for (tag, record) in abi.events.iter().enumerate() {
+        out.push('\n');
+        out.push_str(&record_struct(record, "Event", &structs)?);
+        out.push_str(&format!(
+            "\nimpl {} {{\n    /// Tag `reveal` writes before the fields.\n    pub const TAG: u64 = {};\n}}\n",
+            record.name, tag
+        ));
+    }
+    if !abi.events.is_empty() {
+        out.push('\n');
+        out.push_str(&event_enum(&abi.events));
+    }
+
+    out.push('\n');
+    out.push_str(CLAIM);
+    out.push('\n');
+    out.push_str(&ram_consts(ram));
+    Ok(out)
+}
+
+/// `RAM_IMAGE`, `SEC_RAM`, and the `initial_ram` loader.
+fn ram_consts(ram: &RamLayout) -> String {
+    let mut out = String::from(
+        "/// Data segments from `ram.json` as `(first address, values)`:\n\
+         /// `generate!` tables, which `main` also writes on entry.\n",
+    );
+    out.push_str("pub const RAM_IMAGE: &[(u64, &[u64])] = &[\n");
+    for seg in ram.data() {
+        let values: Vec<String> = seg.values.iter().map(u64::to_string).collect();
+        out.push_str(&format!(
+            "    // {}\n    ({}, &[{}]),\n",
+            seg.name,
+            seg.start,
+            values.join(", ")
+        ));
+    }
+    out.push_str("];\n\n");
+    out.push_str(
+        "/// `sec ram` slots as `(address, width)`. Their contents are witness\n\
+         /// data: the prover supplies them alongside `RAM_IMAGE`.\n",
+    );
+    out.push_str("pub const SEC_RAM: &[(u64, u64)] = &[\n");
+    for region in ram.regions() {
+        if let (RamRegionKind::SecRam, Some(end)) = (region.kind, region.end) {
+            out.push_str(&format!(
+                "    ({}, {}), // {}\n",
+                region.start,
+                end - region.start,
+                region.label
+            ));
+        }
+    }
+    out.push_str("];\n\n");
+    out.push_str(INITIAL_RAM);
+    out
+}
+
+fn record_struct(
+    record: &AbiRecord,
+    what: &str,
+    structs: &BTreeSet<String>,
+) -> Result<String, String> {
+    let fields: Vec<(String, String)> = record
+        .fields
+        .iter()
+        .map(|AbiField { name, ty }| Ok((rust_ident(name), AbiType::parse(ty)?.rust(structs)?)))
+        .collect::<Result<_, String>>()?;
+
+    let mut out = format!(
+        "/// {} `{}`.\n#[derive(Clone, Debug, PartialEq, Eq)]\npub struct {} {{\n",
+        what, record.name, record.name
+    );
+    for (name, ty) in &fields {
+        out.push_str(&format!("    pub {}: {},\n", name, ty));
+    }
+    out.push_str("}\n\n");
+
+    let width: Vec<String> = fields
+        .iter()
+        .map(|(_, ty)| format!("<{} as Fields>::WIDTH", ty))
+        .collect();
+    out.push_str(&format!("impl Fields for {} {{\n", record.name));
+    out.push_str(&format!(
+        "    const WIDTH: usize = {};\n",
+        if width.is_empty() {
+            "0".to_string()
+        } else {
+            width.join(" + ")
+        }
+    ));
+    out.push_str("    fn encode(&self, out: &mut Vec<u64>) {\n");
+    if fields.is_empty() {
+        out.push_str("        let _ = out;\n");
+    }
+    for (name, _) in &fields {
+        out.push_str(&format!("        self.{}.encode(out);\n", name));
+    }
+    out.push_str("    }\n");
+    out.push_str("    fn decode(fields: &mut &[u64]) -> Result<Self, String> {\n");
+    if fields.is_empty() {
+        out.push_str("        let _ = fields;\n");
+    }
+    out.push_str(&format!("        Ok({} {{\n", record.name));
+    for (name, ty) in &fields {
+        out.push_str(&format!(
+            "            {}: <{} as Fields>::decode(fields)?,\n",
+            name, ty
+        ));
+    }
+    out.push_str("        })\n    }\n}\n");
+    Ok(out)
+}
+
+fn event_enum(events: &[AbiRecord]) -> String {
+    let mut out = String::from(
+        "/// Any event of the program, as `reveal` writes it to public output.\n\
+         #[derive(Clone, Debug, PartialEq, Eq)]\npub enum Event {\n",
+    );
+    for e in events {
+        out.push_str(&format!("    {}({}),\n", e.name, e.name));
+    }
+    out.push_str("}\n\nimpl Event {\n");
+    out.push_str("    /// Encode as the tag followed by the event's fields.\n");
+    out.push_str("    pub fn encode(&self, out: &mut Vec<u64>) {\n        match self {\n");
+    for e in events {
+        out.push_str(&format!(
+            "            Event::{}(e) => {{\n                out.push({}::TAG);\n                e.encode(out);\n            }}\n",
+            e.name, e.name
+        ));
+    }
+    out.push_str("        }\n    }\n\n");
+    out.push_str("    /// Decode one revealed event from the front of `fields`.\n");
+    out.push_str("    pub fn decode(fields: &mut &[u64]) -> Result<Self, String> {\n");
+    out.push_str("        match take(fields)? {\n");
+    for e in events {
+        out.push_str(&format!(
+            "            {}::TAG => Ok(Event::{}({}::decode(fields)?)),\n",
+            e.name, e.name, e.name
+        ));
+    }
+    out.push_str("            tag => Err(format!(\"unknown event tag {}\", tag)),\n");
+    out.push_str("        }\n    }\n}\n");
+    out
+}
+
+fn tuple_impl(arity: usize) -> String {
+    let params: Vec<String> = (0..arity).map(|i| format!("T{}", i)).collect();
+    let bounds: Vec<String> = params.iter().map(|p| format!("{}: Fields", p)).collect();
+    let tuple = if arity == 1 {
+        "(T0,)".to_string()
+    } else {
+        format!("({})", params.join(", "))
+    };
+    let width: Vec<String> = params.iter().map(|p| format!("{}::WIDTH", p)).collect();
+    let mut out = format!(
+        "impl<{}> Fields for {} {{\n    const WIDTH: usize = {};\n",
+        bounds.join(", "),
+        tuple,
+        width.join(" + ")
+    );
+    out.push_str("    fn encode(&self, out: &mut Vec<u64>) {\n");
+    for i in 0..arity {
+        out.push_str(&format!("        self.{}.encode(out);\n", i));
+    }
+    out.push_str("    }\n    fn decode(fields: &mut &[u64]) -> Result<Self, String> {\n");
+    let decodes: Vec<String> = params
+        .iter()
+        .map(|p| format!("{}::decode(fields)?", p))
+        .collect();
+    let value = if arity == 1 {
+        format!("({},)", decodes[0])
+    } else {
+        format!("({})", decodes.join(", "))
+    };
+    out.push_str(&format!("        Ok({})\n    }}\n}}\n", value));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_tags_follow_declaration_order() {
+        let record = |name: &str| AbiRecord {
+            name: name.to_string(),
+            fields: vec![AbiField {
+                name: "type".to_string(),
+                ty: "Field".to_string(),
+            }],
+        };
+        let abi = ProgramAbi {
+            name: "p".to_string(),
+            kind: "program",
+            entry_point: Some("main".to_string()),
+            functions: Vec::new(),
+            entries: Vec::new(),
+            events: vec![record("Opened"), record("Closed")],
+            event_channel: Default::default(),
+            structs: Vec::new(),
+            errors: Vec::new(),
+        };
+        let code = rust_bindings(
+            &abi,
+            &ProofClaim::for_program("halt\n"),
+            &RamLayout::default(),
+        )
+        .unwrap();
+        assert!(code.contains("impl Closed {\n    /// Tag `reveal` writes before the fields.\n    pub const TAG: u64 = 1;"));
+        assert!(code.contains("pub r#type: u64,"));
+        assert!(code.contains("Closed::TAG => Ok(Event::Closed(Closed::decode(fields)?)),"));
+    }
+
+    #[test]
+    fn test_ram_image_and_sec_ram_slots_are_emitted() {
+        let source = "program p\nsec ram: { 17: Digest }\nfn main() {}\n";
+        let file = crate::parse_source_silent(source, "p.tri").unwrap();
+        let tc = crate::target::TerrainConfig::triton();
+        let tables = [("p.SQ".to_string(), vec![0, 1, 4])].into_iter().collect();
+        let mut ram = RamLayout::new(&tc);
+        ram.reserve_data(&crate::tir::ram::data_segments(&tables, &tc));
+        assert!(ram.reserve_sec_ram(&file, &tc).is_empty());
+
+        let abi = ProgramAbi::from_file(&file);
+        let code = rust_bindings(&abi, &ProofClaim::for_program("halt\n"), &ram).unwrap();
+        assert!(code.contains("    // p.SQ\n    (536870909, &[0, 1, 4]),\n"));
+        assert!(code.contains("    (17, 5), // p: ram[17]: Digest\n"));
+        assert!(code.contains("pub fn initial_ram(sec_ram: &[Vec<u64>])"));
+    }
+}
@@ -0,0 +1,478 @@
+//! TypeScript bindings: interfaces plus `Codec` values for a web
+//! frontend. Field elements are `bigint`s; U32 is `number`.
+
+use std::collections::BTreeSet;
+
+use crate::deploy::claim::{ProofClaim, DIGEST_WIDTH};
+use crate::runtime::abi::{AbiRecord, ProgramAbi};
+use crate::tir::ram::{RamLayout, RamRegionKind};
+
+use super::{check_names, struct_name, AbiType};
+
+impl AbiType {
+    /// The TypeScript type and the expression for its codec.
+    fn ts(&self, structs: &BTreeSet<String>) -> Result<(String, String), String> {
+        Ok(match self {
+            AbiType::Field => ("bigint".to_string(), "Field".to_string()),
+            AbiType::XField => ("XField".to_string(), "XField".to_string()),
+            AbiType::Bool => ("boolean".to_string(), "Bool".to_string()),
+            AbiType::U32 => ("number".to_string(), "U32".to_string()),
+            AbiType::Digest => ("Digest".to_string(), "Digest".to_string()),
+            AbiType::Array(elem, size) => {
+                let (ty, codec) = elem.ts(structs)?;
+                (format!("{}[]", ty), format!("array({}, {})", codec, size))
+            }
+            AbiType::Tuple(elems) => {
+                let parts = elems
+                    .iter()
+                    .map(|t| t.ts(structs))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let (tys, codecs): (Vec<String>, Vec<String>) = parts.into_iter().unzip();
+                (
+                    format!("[{}]", tys.join(", ")),
+                    format!("tuple({})", codecs.join(", ")),
+                )
+            }
+            AbiType::Named(path) => {
+                let name = struct_name(path, structs)?;
+                (name.to_string(), name.to_string())
+            }
+        })
+    }
+}
+
+const PRELUDE: &str = r#"/** Encodes one value as a fixed number of field elements. */
+export interface Codec<T> {
+  readonly width: number;
+  encode(value: T, out: bigint[]): void;
+  decode(reader: Reader): T;
+}
+
+/** Reads field elements front to back. */
+export class Reader {
+  private pos = 0;
+  constructor(private readonly fields: readonly bigint[]) {}
+
+  next(): bigint {
+    if (this.pos >= this.fields.length) {
+      throw new Error("unexpected end of field stream");
+    }
+    return this.fields[this.pos++];
+  }
+
+  /** Field elements not read yet. */
+  get remaining(): number {
+    return this.fields.length - this.pos;
+  }
+}
+
+/** Goldilocks prime 2^64 - 2^32 + 1. */
+export const MODULUS = 0xffffffff00000001n;
+
+export const Field: Codec<bigint> = {
+  width: 1,
+  encode(value, out) {
+    if (value < 0n || value >= MODULUS) {
+      throw new Error(`Field out of range: ${value}`);
+    }
+    out.push(value);
+  },
+  decode: (reader) => reader.next(),
+};
+
+export const U32: Codec<number> = {
+  width: 1,
+  encode(value, out) {
+    if (!Number.isInteger(value) || value < 0 || value > 0xffffffff) {
+      throw new Error(`U32 out of range: ${value}`);
+    }
+    out.push(BigInt(value));
+  },
+  decode(reader) {
+    const v = reader.next();
+    if (v > 0xffffffffn) {
+      throw new Error(`U32 out of range: ${v}`);
+    }
+    return Number(v);
+  },
+};
+
+export const Bool: Codec<boolean> = {
+  width: 1,
+  encode: (value, out) => {
+    out.push(value ? 1n : 0n);
+  },
+  decode(reader) {
+    const v = reader.next();
+    if (v > 1n) {
+      throw new Error(`Bool out of range: ${v}`);
+    }
+    return v === 1n;
+  },
+};
+
+/** Fixed-length array; the length is checked on encode. */
+export function array<T>(elem: Codec<T>, length: number): Codec<T[]> {
+  return {
+    width: elem.width * length,
+    encode(value, out) {
+      if (value.length !== length) {
+        throw new Error(`expected ${length} elements, got ${value.length}`);
+      }
+      value.forEach((v) => elem.encode(v, out));
+    },
+    decode: (reader) => Array.from({ length }, () => elem.decode(reader)),
+  };
+}
+
+export function tuple<T extends unknown[]>(
+  ...elems: { [K in keyof T]: Codec<T[K]> }
+): Codec<T> {
+  const codecs = elems as unknown as Codec<unknown>[];
+  return {
+    width: codecs.reduce((sum, c) => sum + c.width, 0),
+    encode(value, out) {
+      codecs.forEach((c, i) => c.encode(value[i], out));
+    },
+    decode: (reader) => codecs.map((c) => c.decode(reader)) as T,
+  };
+}
+
+export type XField = bigint[];
+export const XField: Codec<XField> = array(Field, 3);
+export type Digest = bigint[];
+export const Digest: Codec<Digest> = array(Field, DIGEST_WIDTH);
+
+/** Encode a value as a field stream. */
+export function encode<T>(codec: Codec<T>, value: T): bigint[] {
+  const out: bigint[] = [];
+  codec.encode(value, out);
+  return out;
+}
+
+/** Decode a value from the front of a field stream. */
+export function decode<T>(codec: Codec<T>, fields: readonly bigint[]): T {
+  return codec.decode(new Reader(fields));
+}
+
+/** Hex form of field elements: each as 8 little-endian bytes. */
+export function fieldsToHex(fields: readonly bigint[]): string {
+  return fields
+    .map((f) => {
+      let hex = "";
+      for (let i = 0n; i < 8n; i++) {
+        hex += ((f >> (8n * i)) & 0xffn).toString(16).padStart(2, "0");
+      }
+      return hex;
+    })
+    .join("");
+}
+
+/** Inverse of `fieldsToHex`. */
+export function fieldsFromHex(hex: string): bigint[] {
+  const clean = hex.startsWith("0x") ? hex.slice(2) : hex;
+  if (clean.length % 16 !== 0 || !/^[0-9a-fA-F]*$/.test(clean)) {
+    throw new Error("expected a multiple of 16 hex characters");
+  }
+  const fields: bigint[] = [];
+  for (let at = 0; at < clean.length; at += 16) {
+    let f = 0n;
+    for (let i = 7; i >= 0; i--) {
+      f = (f << 8n) | BigInt(parseInt(clean.slice(at + 2 * i, at + 2 * i + 2), 16));
+    }
+    fields.push(f);
+  }
+  return fields;
+}
+
+export const digestToHex = (digest: Digest): string => fieldsToHex(digest);
+
+export function digestFromHex(hex: string): Digest {
+  const fields = fieldsFromHex(hex);
+  if (fields.length !== DIGEST_WIDTH) {
+    throw new Error(`digest needs ${DIGEST_WIDTH} elements, got ${fields.length}`);
+  }
+  return fields;
+}
+"#;
+
+const INITIAL_RAM: &str = r#"/**
+ * Initial RAM for a prover as `[address, value]` pairs: every word of
+ * `RAM_IMAGE`, then `secRam` (one value list per `SEC_RAM` slot, in
+ * order). Feed the pairs to the VM's non-deterministic RAM.
+ */
+export function initialRam(secRam: bigint[][]): [bigint, bigint][] {
+  if (secRam.length !== SEC_RAM.length) {
+    throw new Error(`expected ${SEC_RAM.length} sec ram slots, got ${secRam.length}`);
+  }
+  const ram: [bigint, bigint][] = [];
+  for (const seg of RAM_IMAGE) {
+    seg.values.forEach((v, i) => ram.push([seg.start + BigInt(i), v]));
+  }
+  SEC_RAM.forEach((slot, k) => {
+    if (secRam[k].length !== slot.width) {
+      throw new Error(`${slot.label} needs ${slot.width} values, got ${secRam[k].length}`);
+    }
+    secRam[k].forEach((v, i) => ram.push([slot.address + BigInt(i), v]));
+  });
+  return ram;
+}
+"#;
+
+const CLAIM: &str = r#"/** What a proof of this program asserts, in the `std.proof` layout. */
+export interface Claim {
+  programDigest: Digest;
+  input: bigint[];
+  output: bigint[];
+}
+
+/** Claim for this program with the given public input and output. */
+export function claim(input: bigint[], output: bigint[]): Claim {
+  return { programDigest: [...PROGRAM_DIGEST], input, output };
+}
+
+/** The fields `std.proof.read_claim` reads: digest, input count, output count. */
+export function encodeClaim(c: Claim): bigint[] {
+  return [...c.programDigest, BigInt(c.input.length), BigInt(c.output.length)];
+}
+
+/**
+ * Public input of a recursive verifier: the encoded claim, then the
+ * program's public input, then its public output.
+ */
+export function verifierPublicInput(c: Claim): bigint[] {
+  return [...encodeClaim(c), ...c.input, ...c.output];
+}
+"#;
+
+/// Generate a TypeScript module for `abi`. `claim` supplies the program
+/// digest (see `ProofClaim::for_program`); `ram` the RAM image and
+/// `sec ram` slots (see `ram_layout_project`).
+pub fn ts_bindings(
+    abi: &ProgramAbi,
+    claim: &ProofClaim,
+    ram: &RamLayout,
+) -> Result<String, String> {
+    let structs = check_names(abi)?;
+
+    let mut out = format!(
+        "// TypeScript bindings for {} `{}`.\n//\n",
+        abi.kind, abi.name
+    );
+    out.push_str("// Generated by `trident bindgen --lang ts`. Do not edit.\n");
+    out.push_str("// Values travel as Goldilocks field elements (`bigint`), struct\n");
+    out.push_str("// fields in declaration order.\n\n");
+    out.push_str(&format!("export const PROGRAM_NAME = {:?};\n", abi.name));
+    out.push_str(&format!("export const DIGEST_WIDTH = {};\n", DIGEST_WIDTH));
+    let digest: Vec<String> = claim
+        .program_digest
+        .iter()
+        .map(|f| format!("{}n", f))
+        .collect();
+    out.push_str("/** Poseidon2 digest of the compiled TASM, as in the package manifest. */\n");
+    out.push_str(&format!(
+        "export const PROGRAM_DIGEST: readonly bigint[] = [{}];\n\n",
+        digest.join(", ")
+    ));
+    out.push_str(PRELUDE);
+
+    for record in &abi.structs {
+        out.push('\n');
+        out.push_str(&record_codec(record, "Public struct", &structs)?);
+    }
+    for (tag, record) in abi.events.iter().enumerate() {
+        out.push('\n');
+        out.push_str(&record_codec(record, "Event", &structs)?);
+        out.push_str(&format!(
+            "/** Tag `reveal` writes before the fields of `{}`. */\nexport const {}_TAG = {}n;\n",
+            record.name,
+            screaming(&record.name),
+            tag
+        ));
+    }
+    if !abi.events.is_empty() {
+        out.push('\n');
+        out.push_str(&event_union(&abi.events));
+    }
+
+    out.push('\n');
+    out.push_str(CLAIM);
+    out.push('\n');
+    out.push_str(&ram_consts(ram));
+    Ok(out)
+}
+
+/// `RAM_IMAGE`, `SEC_RAM`, and the `initialRam` loader.
+fn ram_consts(ram: &RamLayout) -> String {
+    let mut out = String::from(
+        "/**\n * Data segments from `ram.json`: `generate!` tables, which `main`\n\
+         * also writes on entry.\n */\n",
+    );
+    out.push_str(
+        "export const RAM_IMAGE: readonly { name: string; start: bigint; values: readonly bigint[] }[] = [\n",
+    );
+    for seg in ram.data() {
+        let values: Vec<String> = seg.values.iter().map(|v| format!("{}n", v)).collect();
+        out.push_str(&format!(
+            "  {{ name: {:?}, start: {}n, values: [{}] }},\n",
+            seg.name,
+            seg.start,
+            values.join(", ")
+        ));
+    }
+    out.push_str("];\n\n");
+    out.push_str("/** `sec ram` slots. Their contents are witness data the prover supplies. */\n");
+    out.push_str(
+        "export const SEC_RAM: readonly { label: string; address: bigint; width: number }[] = [\n",
+    );
+    for region in ram.regions() {
+        if let (RamRegionKind::SecRam, Some(end)) = (region.kind, region.end) {
+            out.push_str(&format!(
+                "  {{ label: {:?}, address: {}n, width: {} }},\n",
+                region.label,
+                region.start,
+                end - region.start
+            ));
+        }
+    }
+    out.push_str("];\n\n");
+    out.push_str(INITIAL_RAM);
+    out
+}
+
+fn record_codec(
+    record: &AbiRecord,
+    what: &str,
+    structs: &BTreeSet<String>,
+) -> Result<String, String> {
+    let fields = record
+        .fields
+        .iter()
+        .map(|f| Ok((f.name.as_str(), AbiType::parse(&f.ty)?.ts(structs)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let name = &record.name;
+
+    let mut out = format!(
+        "/** {} `{}`. */\nexport interface {} {{\n",
+        what, name, name
+    );
+    for (field, (ty, _)) in &fields {
+        out.push_str(&format!("  {}: {};\n", field, ty));
+    }
+    out.push_str("}\n\n");
+
+    let width: Vec<String> = fields
+        .iter()
+        .map(|(_, (_, codec))| format!("{}.width", codec))
+        .collect();
+    out.push_str(&format!("export const {}: Codec<{}> = {{\n", name, name));
+    out.push_str(&format!(
+        "  get width() {{\n    return {};\n  }},\n",
+        if width.is_empty() {
+            "0".to_string()
+        } else {
+            width.join(" + ")
+        }
+    ));
+    out.push_str("  encode(value, out) {\n");
+    for (field, (_, codec)) in &fields {
+        out.push_str(&format!("    {}.encode(value.{}, out);\n", codec, field));
+    }
+    out.push_str("  },\n  decode(reader) {\n    return {\n");
+    for (field, (_, codec)) in &fields {
+        out.push_str(&format!("      {}: {}.decode(reader),\n", field, codec));
+    }
+    out.push_str("    };\n  },\n};\n");
+    Ok(out)
+}
+
+fn event_union(events: &[AbiRecord]) -> String {
+    let variants: Vec<String> = events
+        .iter()
+        .map(|e| format!("  | {{ kind: \"{}\"; value: {} }}", e.name, e.name))
+        .collect();
+    let mut out = format!(
+        "/** Any event of the program, as `reveal` writes it to public output. */\nexport type Event =\n{};\n\n",
+        variants.join("\n")
+    );
+    out.push_str("/** Encode an event as its tag followed by its fields. */\n");
+    out.push_str("export function encodeEvent(event: Event, out: bigint[]): void {\n");
+    out.push_str("  switch (event.kind) {\n");
+    for e in events {
+        out.push_str(&format!(
+            "    case \"{}\":\n      out.push({}_TAG);\n      {}.encode(event.value, out);\n      return;\n",
+            e.name,
+            screaming(&e.name),
+            e.name
+        ));
+    }
+    out.push_str("  }\n}\n\n");
+    out.push_str("/** Decode one revealed event from the reader. */\n");
+    out.push_str("export function decodeEvent(reader: Reader): Event {\n");
+    out.push_str("  const tag = reader.next();\n");
+    for e in events {
+        out.push_str(&format!(
+            "  if (tag === {}_TAG) {{\n    return {{ kind: \"{}\", value: {}.decode(reader) }};\n  }}\n",
+            screaming(&e.name),
+            e.name,
+            e.name
+        ));
+    }
+    out.push_str("  throw new Error(`unknown event tag ${tag}`);\n}\n");
+    out
+}
+
+/// `TokenMinted` -> `TOKEN_MINTED`.
+fn screaming(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::abi::AbiField;
+
+    #[test]
+    fn test_ts_events_get_tag_constants_and_a_tagged_union() {
+        let record = |name: &str, ty: &str| AbiRecord {
+            name: name.to_string(),
+            fields: vec![AbiField {
+                name: "at".to_string(),
+                ty: ty.to_string(),
+            }],
+        };
+        let abi = ProgramAbi {
+            name: "p".to_string(),
+            kind: "program",
+            entry_point: Some("main".to_string()),
+            functions: Vec::new(),
+            entries: Vec::new(),
+            events: vec![
+                record("TokenMinted", "Digest"),
+                record("Closed", "[U32; 2]"),
+            ],
+            event_channel: Default::default(),
+            structs: Vec::new(),
+            errors: Vec::new(),
+        };
+        let code = ts_bindings(
+            &abi,
+            &ProofClaim::for_program("halt\n"),
+            &RamLayout::default(),
+        )
+        .unwrap();
+        assert!(code.contains("export const TOKEN_MINTED_TAG = 0n;"));
+        assert!(code.contains("export const CLOSED_TAG = 1n;"));
+        assert!(code.contains("  at: number[];\n"));
+        assert!(code.contains("    array(U32, 2).encode(value.at, out);\n"));
+        assert!(code.contains("  | { kind: \"Closed\"; value: Closed }"));
+        assert!(code.contains("  if (tag === CLOSED_TAG) {"));
+    }
+}
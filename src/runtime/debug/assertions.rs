@@ -0,0 +1,149 @@
+//! Assertion table: the runtime checks of a program, by error id.
+//!
+//! Every `assert`, `assert_eq`, `assert_digest`, and `ensure` call (and
+//! `assume` where it is checked) gets an id, numbered from 1 in module
+//! then source order. Debug builds push the id below the operands of the
+//! check, so the stack of a failed run names the call that tripped.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::ast::navigate::calls_in_block;
+use crate::ast::{Expr, Item};
+use crate::pipeline::PreparedProject;
+use crate::runtime::artifact::json_string;
+use crate::CompileOptions;
+
+/// One runtime check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Assertion {
+    pub id: u64,
+    pub file: PathBuf,
+    /// 1-based line and column of the call.
+    pub line: usize,
+    pub column: usize,
+    /// The builtin the call lowers to.
+    pub check: String,
+    /// The enclosing function.
+    pub function: String,
+    /// Source text of the checked condition, when it fits on one line.
+    pub message: Option<String>,
+    /// Byte offset of the call's path, which the TIR builder looks up.
+    pub(crate) offset: u32,
+}
+
+/// All runtime checks of a program, in id order.
+#[derive(Clone, Debug, Default)]
+pub struct AssertionTable {
+    assertions: Vec<Assertion>,
+}
+
+impl AssertionTable {
+    /// Collect the checks of the functions active under `options`.
+    pub(crate) fn from_project(project: &PreparedProject, options: &CompileOptions) -> Self {
+        let intrinsics = project.intrinsic_map();
+        let mut assertions = Vec::new();
+        for pm in &project.modules {
+            let flags = options.flags_for(&pm.file.name.node);
+            for item in &pm.file.items {
+                let Item::Fn(func) = &item.node else {
+                    continue;
+                };
+                let active = func
+                    .cfg
+                    .as_ref()
+                    .is_none_or(|flag| flags.contains(&flag.node));
+                let Some(body) = func.body.as_ref().filter(|_| active && !func.is_test) else {
+                    continue;
+                };
+                for call in calls_in_block(&body.node) {
+                    let Expr::Call { path, args, .. } = &call.node else {
+                        continue;
+                    };
+                    let name = path.node.as_dotted();
+                    let base = name.rsplit('.').next().unwrap_or(&name);
+                    let check = intrinsics
+                        .get(&name)
+                        .or_else(|| intrinsics.get(base))
+                        .map_or(name.as_str(), String::as_str);
+                    let checked = match check {
+                        "assert" | "assert_eq" | "assert_digest" | "ensure" => true,
+                        "assume" => flags.contains("debug"),
+                        _ => false,
+                    };
+                    if !checked {
+                        continue;
+                    }
+                    let (line, column) =
+                        crate::diagnostic::line_column(&pm.source, call.span.start as usize);
+                    let message = args
+                        .first()
+                        .and_then(|arg| {
+                            pm.source
+                                .get(arg.span.start as usize..arg.span.end as usize)
+                        })
+                        .filter(|text| !text.contains('\n'))
+                        .map(str::to_string);
+                    assertions.push(Assertion {
+                        id: assertions.len() as u64 + 1,
+                        file: pm.file_path.clone(),
+                        line,
+                        column,
+                        check: check.to_string(),
+                        function: func.name.node.clone(),
+                        message,
+                        offset: path.span.start,
+                    });
+                }
+            }
+        }
+        AssertionTable { assertions }
+    }
+
+    pub fn assertions(&self) -> &[Assertion] {
+        &self.assertions
+    }
+
+    /// The check with error id `id`.
+    pub fn get(&self, id: u64) -> Option<&Assertion> {
+        id.checked_sub(1)
+            .and_then(|index| self.assertions.get(index as usize))
+    }
+
+    /// Error ids of the checks in `file`, by offset of the call's path.
+    pub(crate) fn ids_in(&self, file: &Path) -> BTreeMap<u32, u64> {
+        self.assertions
+            .iter()
+            .filter(|a| a.file == file)
+            .map(|a| (a.offset, a.id))
+            .collect()
+    }
+
+    /// Serialize to JSON (`trident build --emit=asserts`).
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .assertions
+            .iter()
+            .map(|a| {
+                let message = match &a.message {
+                    Some(message) => json_string(message),
+                    None => "null".to_string(),
+                };
+                format!(
+                    "    {{ \"id\": {}, \"file\": {}, \"line\": {}, \"column\": {}, \"check\": {}, \"function\": {}, \"message\": {} }}",
+                    a.id,
+                    json_string(&a.file.to_string_lossy()),
+                    a.line,
+                    a.column,
+                    json_string(&a.check),
+                    json_string(&a.function),
+                    message
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"assertions\": [\n{}\n  ]\n}}\n",
+            entries.join(",\n")
+        )
+    }
+}
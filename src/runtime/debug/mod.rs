@@ -0,0 +1,432 @@
+//! Proof-of-concept TASM debugger.
+//!
+//! Steps a linked TASM program one instruction at a time. Control flow
+//! (`call`, `return`, `recurse`, `recurse_or_return`, `skiz`, `halt`),
+//! I/O, non-determinism, and RAM follow Triton VM semantics; everything
+//! else runs through the block-level `StackState` from
+//! `cost::stack_verifier`. Hash, sponge, and Merkle instructions keep
+//! their stack effects but produce placeholder zeros — use a warrior
+//! (`trident run`) for cryptographically exact execution, or
+//! `Debugger::require_exact` to stop before a placeholder is computed.
+//!
+//! `divine` and `read_io` with too little input stop execution with
+//! `Stop::NeedsInput` instead of failing, so values can be injected and
+//! execution resumed.
+
+pub mod assertions;
+pub mod source_map;
+pub mod symbols;
+pub mod trace;
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::cost::stack_verifier::StackState;
+use crate::field::goldilocks::MODULUS;
+use crate::runtime::ProgramInput;
+
+pub use assertions::{Assertion, AssertionTable};
+pub use source_map::{FunctionSource, SourceMap};
+pub use symbols::{Symbol, SymbolTable};
+pub use trace::{diff_traces, Divergence, Snapshot, TraceDiff};
+
+/// Upper bound on instructions executed by one `resume`/`step_over`/`step_out`.
+pub const DEFAULT_CYCLE_LIMIT: u64 = 10_000_000;
+
+/// Instructions that keep their stack effect but compute placeholder
+/// values here (see `Debugger::require_exact`).
+pub const PLACEHOLDER_INSTRUCTIONS: &[&str] = &[
+    "hash",
+    "sponge_init",
+    "sponge_absorb",
+    "sponge_absorb_mem",
+    "sponge_squeeze",
+    "merkle_step",
+    "merkle_step_mem",
+    "xb_mul",
+    "x_invert",
+    "xx_dot_step",
+    "xb_dot_step",
+];
+
+/// One executable TASM instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    /// Line number in the TASM text (0-based).
+    pub line: usize,
+    /// Instruction text without indentation or trailing comment.
+    pub text: String,
+}
+
+/// An active call: who was called and where to return.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// Label jumped to by the `call`.
+    pub label: String,
+    /// Instruction index to continue at on `return`.
+    pub return_pc: usize,
+}
+
+/// Which input stream an instruction is waiting on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputKind {
+    /// `read_io` — public input.
+    Public,
+    /// `divine` — secret (non-deterministic) input.
+    Secret,
+}
+
+/// Why execution stopped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// The requested step finished; execution can continue.
+    Step,
+    /// Reached the entry of a label with a breakpoint.
+    Breakpoint(String),
+    /// `halt` executed.
+    Halted,
+    /// Execution failed (assertion, stack underflow, unknown label, ...).
+    Error(String),
+    /// The next instruction needs `count` more values on the `kind` stream.
+    NeedsInput { kind: InputKind, count: usize },
+    /// The cycle limit was reached before any other stop.
+    CycleLimit,
+}
+
+impl Stop {
+    /// Whether execution can make progress after this stop.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Stop::Halted | Stop::Error(_))
+    }
+}
+
+/// Interactive execution state for one TASM program.
+#[derive(Clone, Debug)]
+pub struct Debugger {
+    instructions: Vec<Instruction>,
+    /// Label → index of the first instruction after it.
+    labels: BTreeMap<String, usize>,
+    pc: usize,
+    frames: Vec<Frame>,
+    state: StackState,
+    ram: BTreeMap<u64, u64>,
+    public_input: VecDeque<u64>,
+    secret_input: VecDeque<u64>,
+    breakpoints: BTreeSet<String>,
+    cycles: u64,
+    cycle_limit: u64,
+    exact: bool,
+    finished: Option<Stop>,
+}
+
+impl Debugger {
+    /// Load a linked TASM program with its inputs.
+    pub fn new(tasm: &str, input: &ProgramInput) -> Self {
+        let mut instructions = Vec::new();
+        let mut labels = BTreeMap::new();
+        for (line, raw) in tasm.lines().enumerate() {
+            let text = raw.split("//").next().unwrap_or("").trim();
+            if text.is_empty() {
+                continue;
+            }
+            if let Some(label) = text.strip_suffix(':') {
+                labels.insert(label.to_string(), instructions.len());
+                continue;
+            }
+            instructions.push(Instruction {
+                line,
+                text: text.to_string(),
+            });
+        }
+        Self {
+            instructions,
+            labels,
+            pc: 0,
+            frames: Vec::new(),
+            state: StackState::new(Vec::new()),
+            ram: BTreeMap::new(),
+            public_input: input.public.iter().copied().collect(),
+            secret_input: input.secret.iter().copied().collect(),
+            breakpoints: BTreeSet::new(),
+            cycles: 0,
+            cycle_limit: DEFAULT_CYCLE_LIMIT,
+            exact: false,
+            finished: None,
+        }
+    }
+
+    // ── Inspection ─────────────────────────────────────────────────
+
+    /// The next instruction to execute, if any.
+    pub fn current(&self) -> Option<&Instruction> {
+        self.instructions.get(self.pc)
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Index of the next instruction in `instructions()`.
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Operand stack, bottom first.
+    pub fn stack(&self) -> &[u64] {
+        &self.state.stack
+    }
+
+    /// Call stack, outermost first.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// RAM cells written so far.
+    pub fn ram(&self) -> &BTreeMap<u64, u64> {
+        &self.ram
+    }
+
+    /// Values written with `write_io`, in output order.
+    pub fn output(&self) -> &[u64] {
+        &self.state.io_output
+    }
+
+    /// Instructions executed so far.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn has_label(&self, label: &str) -> bool {
+        self.labels.contains_key(label)
+    }
+
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.labels.keys().map(String::as_str)
+    }
+
+    // ── Control ────────────────────────────────────────────────────
+
+    pub fn set_cycle_limit(&mut self, limit: u64) {
+        self.cycle_limit = limit;
+    }
+
+    /// Stop with an error instead of executing any of the
+    /// `PLACEHOLDER_INSTRUCTIONS`, whose results would be wrong.
+    pub fn require_exact(&mut self) {
+        self.exact = true;
+    }
+
+    /// Break when execution enters `label`.
+    pub fn add_breakpoint(&mut self, label: &str) -> Result<(), String> {
+        if !self.labels.contains_key(label) {
+            return Err(format!("unknown label '{}'", label));
+        }
+        self.breakpoints.insert(label.to_string());
+        Ok(())
+    }
+
+    pub fn remove_breakpoint(&mut self, label: &str) -> bool {
+        self.breakpoints.remove(label)
+    }
+
+    pub fn breakpoints(&self) -> &BTreeSet<String> {
+        &self.breakpoints
+    }
+
+    /// Queue more public input for `read_io`.
+    pub fn provide_public(&mut self, values: &[u64]) {
+        self.public_input.extend(values);
+    }
+
+    /// Queue more secret input for `divine`.
+    pub fn provide_secret(&mut self, values: &[u64]) {
+        self.secret_input.extend(values);
+    }
+
+    /// Execute one instruction.
+    pub fn step(&mut self) -> Stop {
+        self.execute().unwrap_or(Stop::Step)
+    }
+
+    /// Execute one instruction, running any `call` to completion.
+    pub fn step_over(&mut self) -> Stop {
+        let depth = self.frames.len();
+        self.run_until(|dbg| dbg.frames.len() <= depth)
+    }
+
+    /// Run until the current function returns.
+    pub fn step_out(&mut self) -> Stop {
+        let Some(depth) = self.frames.len().checked_sub(1) else {
+            return self.resume();
+        };
+        self.run_until(|dbg| dbg.frames.len() <= depth)
+    }
+
+    /// Run until a breakpoint, halt, error, or missing input.
+    pub fn resume(&mut self) -> Stop {
+        self.run_until(|_| false)
+    }
+
+    fn run_until(&mut self, done: impl Fn(&Self) -> bool) -> Stop {
+        let start = self.cycles;
+        loop {
+            if let Some(stop) = self.execute() {
+                return stop;
+            }
+            if done(self) {
+                return Stop::Step;
+            }
+            if let Some(label) = self.breakpoint_here() {
+                return Stop::Breakpoint(label);
+            }
+            if self.cycles - start >= self.cycle_limit {
+                return Stop::CycleLimit;
+            }
+        }
+    }
+
+    /// The breakpoint label whose entry is the current instruction.
+    fn breakpoint_here(&self) -> Option<String> {
+        self.breakpoints
+            .iter()
+            .find(|label| self.labels.get(*label) == Some(&self.pc))
+            .cloned()
+    }
+
+    // ── Execution ──────────────────────────────────────────────────
+
+    /// Execute the instruction at `pc`. Returns a stop when execution
+    /// cannot continue past it.
+    fn execute(&mut self) -> Option<Stop> {
+        if let Some(stop) = &self.finished {
+            return Some(stop.clone());
+        }
+        let Some(instr) = self.instructions.get(self.pc).cloned() else {
+            return Some(self.finish(Stop::Error("ran past the end of the program".into())));
+        };
+        let parts: Vec<&str> = instr.text.split_whitespace().collect();
+        let op = parts[0];
+        let arg = parts.get(1).copied().unwrap_or("");
+        let n = arg.parse::<usize>().unwrap_or(1);
+
+        if self.exact && PLACEHOLDER_INSTRUCTIONS.contains(&op) {
+            return Some(self.finish(Stop::Error(format!(
+                "'{}' is not emulated: hash, sponge, Merkle and extension-field \
+                 instructions produce placeholder values; use `trident run`",
+                instr.text
+            ))));
+        }
+        let mut next = self.pc + 1;
+        match op {
+            "halt" => {
+                self.cycles += 1;
+                return Some(self.finish(Stop::Halted));
+            }
+            "call" => {
+                let Some(&target) = self.labels.get(arg) else {
+                    return Some(self.finish(Stop::Error(format!("unknown label '{}'", arg))));
+                };
+                self.frames.push(Frame {
+                    label: arg.to_string(),
+                    return_pc: next,
+                });
+                next = target;
+            }
+            "return" => match self.frames.pop() {
+                Some(frame) => next = frame.return_pc,
+                None => {
+                    return Some(self.finish(Stop::Error("return with empty call stack".into())))
+                }
+            },
+            "recurse" | "recurse_or_return" => {
+                let Some(frame) = self.frames.last().cloned() else {
+                    return Some(self.finish(Stop::Error(format!("{} outside a call", op))));
+                };
+                // Triton VM: return when st5 == st6 (absent elements read as 0).
+                let stack = &self.state.stack;
+                let st = |i: usize| stack.len().checked_sub(i + 1).map_or(0, |j| stack[j]);
+                let returns = op == "recurse_or_return" && st(5) == st(6);
+                if returns {
+                    self.frames.pop();
+                    next = frame.return_pc;
+                } else {
+                    next = self.labels[&frame.label];
+                }
+            }
+            "skiz" => {
+                let Some(top) = self.state.stack.pop() else {
+                    return Some(self.finish(Stop::Error("skiz on empty stack".into())));
+                };
+                if top == 0 {
+                    next += 1;
+                }
+            }
+            "read_io" | "divine" => {
+                let (queue, kind) = if op == "read_io" {
+                    (&mut self.public_input, InputKind::Public)
+                } else {
+                    (&mut self.secret_input, InputKind::Secret)
+                };
+                if queue.len() < n {
+                    let count = n - queue.len();
+                    return Some(Stop::NeedsInput { kind, count });
+                }
+                let values: Vec<u64> = queue.drain(..n).map(|v| v % MODULUS).collect();
+                self.state.stack.extend(values);
+            }
+            "read_mem" => {
+                let Some(mut pointer) = self.state.stack.pop() else {
+                    return Some(self.finish(Stop::Error("read_mem on empty stack".into())));
+                };
+                for _ in 0..n {
+                    let value = self.ram.get(&pointer).copied().unwrap_or(0);
+                    self.state.stack.push(value);
+                    pointer = field_sub(pointer, 1);
+                }
+                self.state.stack.push(pointer);
+            }
+            "write_mem" => {
+                if self.state.stack.len() < n + 1 {
+                    return Some(self.finish(Stop::Error("write_mem: stack underflow".into())));
+                }
+                let mut pointer = self.state.stack.pop().unwrap_or(0);
+                for _ in 0..n {
+                    let value = self.state.stack.pop().unwrap_or(0);
+                    self.ram.insert(pointer, value);
+                    pointer = field_add(pointer, 1);
+                }
+                self.state.stack.push(pointer);
+            }
+            _ => {
+                self.state.execute_line(&instr.text);
+                if self.state.error {
+                    let reason = if op.starts_with("assert") {
+                        format!("assertion failed: {}", instr.text)
+                    } else {
+                        format!("'{}' failed: stack underflow", instr.text)
+                    };
+                    return Some(self.finish(Stop::Error(reason)));
+                }
+            }
+        }
+
+        self.cycles += 1;
+        self.pc = next;
+        None
+    }
+
+    fn finish(&mut self, stop: Stop) -> Stop {
+        self.finished = Some(stop.clone());
+        stop
+    }
+}
+
+fn field_add(a: u64, b: u64) -> u64 {
+    ((a as u128 + b as u128) % MODULUS as u128) as u64
+}
+
+fn field_sub(a: u64, b: u64) -> u64 {
+    ((a as u128 + MODULUS as u128 - b as u128) % MODULUS as u128) as u64
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,124 @@
+//! Function-level source map from linked TASM labels to `.tri` source.
+//!
+//! The linker names every function `<module>__<fn>` (dots in the module
+//! name become underscores); monomorphized generics append `__<params>`.
+//! Branch and loop subroutines (`<module>__then__1`, ...) have no entry
+//! here — attribute them to the nearest enclosing call frame that does.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::ast::Item;
+use crate::pipeline::ParsedModule;
+use crate::runtime::artifact::json_string;
+use crate::tir::LabelScheme;
+
+/// Where a TASM function label comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionSource {
+    /// Linked label (`crypto_sponge__absorb`).
+    pub label: String,
+    /// Module name as declared (`crypto.sponge`).
+    pub module: String,
+    /// Function name (`absorb`).
+    pub name: String,
+    pub file: PathBuf,
+    /// First and last source line of the function (1-based, inclusive).
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Whether the function is size-generic (labels carry a `__<params>` suffix).
+    pub generic: bool,
+}
+
+/// Function-level mapping between TASM labels and source lines.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    functions: Vec<FunctionSource>,
+    sources: BTreeMap<PathBuf, String>,
+}
+
+impl SourceMap {
+    /// Build the map from the modules of a prepared project.
+    pub(crate) fn from_modules(modules: &[ParsedModule], scheme: LabelScheme) -> Self {
+        let mut map = SourceMap::default();
+        for pm in modules {
+            let module = pm.file.name.node.clone();
+            let prefix = scheme.module_prefix(&module);
+            for item in &pm.file.items {
+                let Item::Fn(def) = &item.node else {
+                    continue;
+                };
+                if def.body.is_none() {
+                    continue;
+                }
+                map.functions.push(FunctionSource {
+                    label: format!("{}{}", prefix, def.name.node),
+                    module: module.clone(),
+                    name: def.name.node.clone(),
+                    file: pm.file_path.clone(),
+                    start_line: line_of(&pm.source, item.span.start as usize),
+                    end_line: line_of(&pm.source, item.span.end.saturating_sub(1) as usize),
+                    generic: !def.type_params.is_empty(),
+                });
+            }
+            map.sources.insert(pm.file_path.clone(), pm.source.clone());
+        }
+        map
+    }
+
+    pub fn functions(&self) -> &[FunctionSource] {
+        &self.functions
+    }
+
+    /// Source text of a module file.
+    pub fn source(&self, file: &Path) -> Option<&str> {
+        self.sources.get(file).map(String::as_str)
+    }
+
+    /// The function a linked label enters, if it is a function label.
+    pub fn function_for_label(&self, label: &str) -> Option<&FunctionSource> {
+        self.functions.iter().find(|f| {
+            f.label == label
+                || (f.generic
+                    && label
+                        .strip_prefix(f.label.as_str())
+                        .is_some_and(|rest| rest.starts_with("__")))
+        })
+    }
+
+    /// The function containing `line` in a file whose path ends with
+    /// `file` (any file when `None`).
+    pub fn function_at(&self, file: Option<&str>, line: usize) -> Option<&FunctionSource> {
+        self.functions.iter().find(|f| {
+            (f.start_line..=f.end_line).contains(&line)
+                && file.is_none_or(|name| f.file.ends_with(name))
+        })
+    }
+
+    /// Serialize the function table to JSON (`trident build --emit=map`).
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .functions
+            .iter()
+            .map(|f| {
+                format!(
+                    "    {{ \"label\": {}, \"module\": {}, \"name\": {}, \"file\": {}, \"start_line\": {}, \"end_line\": {}, \"generic\": {} }}",
+                    json_string(&f.label),
+                    json_string(&f.module),
+                    json_string(&f.name),
+                    json_string(&f.file.to_string_lossy()),
+                    f.start_line,
+                    f.end_line,
+                    f.generic
+                )
+            })
+            .collect();
+        format!("{{\n  \"functions\": [\n{}\n  ]\n}}\n", entries.join(",\n"))
+    }
+}
+
+/// 1-based line number of a byte offset.
+pub(crate) fn line_of(source: &str, offset: usize) -> usize {
+    let offset = offset.min(source.len());
+    source[..offset].matches('\n').count() + 1
+}
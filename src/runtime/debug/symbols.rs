@@ -0,0 +1,102 @@
+//! Symbol table: every top-level item of every module in a project.
+//!
+//! Functions carry their linked TASM label (see `source_map`), so
+//! external tools can resolve labels without re-parsing the sources.
+
+use std::path::PathBuf;
+
+use crate::ast::Item;
+use crate::pipeline::ParsedModule;
+use crate::runtime::artifact::json_string;
+use crate::tir::LabelScheme;
+
+use super::source_map::line_of;
+
+/// One named top-level item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    /// Module name as declared (`crypto.sponge`).
+    pub module: String,
+    pub name: String,
+    /// `fn`, `struct`, `const`, or `event`.
+    pub kind: &'static str,
+    pub is_pub: bool,
+    /// Linked TASM label, for functions with a body.
+    pub label: Option<String>,
+    pub file: PathBuf,
+    /// 1-based line of the item's first token.
+    pub line: usize,
+}
+
+/// All symbols of a project, in module then declaration order.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    /// Build the table from the modules of a prepared project.
+    pub(crate) fn from_modules(modules: &[ParsedModule], scheme: LabelScheme) -> Self {
+        let mut symbols = Vec::new();
+        for pm in modules {
+            let module = pm.file.name.node.clone();
+            let prefix = scheme.module_prefix(&module);
+            for item in &pm.file.items {
+                let (name, kind, is_pub, label) = match &item.node {
+                    Item::Fn(def) => {
+                        if def.is_test {
+                            continue;
+                        }
+                        let label = def
+                            .body
+                            .as_ref()
+                            .map(|_| format!("{}{}", prefix, def.name.node));
+                        (&def.name.node, "fn", def.is_pub, label)
+                    }
+                    Item::Struct(def) => (&def.name.node, "struct", def.is_pub, None),
+                    Item::Const(def) => (&def.name.node, "const", def.is_pub, None),
+                    Item::Event(def) => (&def.name.node, "event", true, None),
+                };
+                symbols.push(Symbol {
+                    module: module.clone(),
+                    name: name.clone(),
+                    kind,
+                    is_pub,
+                    label,
+                    file: pm.file_path.clone(),
+                    line: line_of(&pm.source, item.span.start as usize),
+                });
+            }
+        }
+        SymbolTable { symbols }
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Serialize to JSON (`trident build --emit=sym`).
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .symbols
+            .iter()
+            .map(|s| {
+                let label = match &s.label {
+                    Some(label) => json_string(label),
+                    None => "null".to_string(),
+                };
+                format!(
+                    "    {{ \"module\": {}, \"name\": {}, \"kind\": {}, \"pub\": {}, \"label\": {}, \"file\": {}, \"line\": {} }}",
+                    json_string(&s.module),
+                    json_string(&s.name),
+                    json_string(s.kind),
+                    s.is_pub,
+                    label,
+                    json_string(&s.file.to_string_lossy()),
+                    s.line
+                )
+            })
+            .collect();
+        format!("{{\n  \"symbols\": [\n{}\n  ]\n}}\n", entries.join(",\n"))
+    }
+}
@@ -0,0 +1,243 @@
+use super::*;
+
+fn input(public: &[u64], secret: &[u64]) -> ProgramInput {
+    ProgramInput {
+        public: public.to_vec(),
+        secret: secret.to_vec(),
+        digests: Vec::new(),
+    }
+}
+
+const ADD_PROGRAM: &str = "    call app__main
+    halt
+app__add:
+    add
+    return
+
+app__main:
+    read_io 1
+    divine 1
+    call app__add
+    write_io 1
+    return
+";
+
+#[test]
+fn test_resume_runs_calls_to_halt() {
+    let mut dbg = Debugger::new(ADD_PROGRAM, &input(&[3], &[4]));
+    assert_eq!(dbg.resume(), Stop::Halted);
+    assert_eq!(dbg.output(), &[7]);
+    assert!(dbg.frames().is_empty());
+    assert_eq!(dbg.resume(), Stop::Halted, "halt is sticky");
+}
+
+#[test]
+fn test_breakpoint_stops_at_label_entry_with_call_stack() {
+    let mut dbg = Debugger::new(ADD_PROGRAM, &input(&[3], &[4]));
+    dbg.add_breakpoint("app__add").unwrap();
+    assert_eq!(dbg.resume(), Stop::Breakpoint("app__add".to_string()));
+    assert_eq!(dbg.current().unwrap().text, "add");
+    let labels: Vec<&str> = dbg.frames().iter().map(|f| f.label.as_str()).collect();
+    assert_eq!(labels, ["app__main", "app__add"]);
+    assert_eq!(dbg.stack(), &[3, 4]);
+    assert_eq!(dbg.resume(), Stop::Halted);
+}
+
+#[test]
+fn test_add_breakpoint_rejects_unknown_label() {
+    let mut dbg = Debugger::new(ADD_PROGRAM, &input(&[], &[]));
+    assert!(dbg.add_breakpoint("app__missing").is_err());
+}
+
+#[test]
+fn test_step_over_runs_call_and_step_out_returns_to_caller() {
+    let mut dbg = Debugger::new(ADD_PROGRAM, &input(&[3], &[4]));
+    assert_eq!(dbg.step(), Stop::Step); // call app__main
+    assert_eq!(dbg.step(), Stop::Step); // read_io 1
+    assert_eq!(dbg.step(), Stop::Step); // divine 1
+    assert_eq!(dbg.step_over(), Stop::Step); // call app__add, returns
+    assert_eq!(dbg.current().unwrap().text, "write_io 1");
+    assert_eq!(dbg.stack(), &[7]);
+
+    assert_eq!(dbg.step_out(), Stop::Step);
+    assert_eq!(dbg.current().unwrap().text, "halt");
+}
+
+#[test]
+fn test_missing_divine_input_pauses_until_injected() {
+    let mut dbg = Debugger::new(ADD_PROGRAM, &input(&[3], &[]));
+    assert_eq!(
+        dbg.resume(),
+        Stop::NeedsInput {
+            kind: InputKind::Secret,
+            count: 1
+        }
+    );
+    assert_eq!(dbg.current().unwrap().text, "divine 1");
+    dbg.provide_secret(&[5]);
+    assert_eq!(dbg.resume(), Stop::Halted);
+    assert_eq!(dbg.output(), &[8]);
+}
+
+#[test]
+fn test_skiz_skips_next_instruction_on_zero() {
+    let tasm = "    push 0\n    skiz\n    push 7\n    push 1\n    skiz\n    push 9\n    halt\n";
+    let mut dbg = Debugger::new(tasm, &input(&[], &[]));
+    assert_eq!(dbg.resume(), Stop::Halted);
+    assert_eq!(dbg.stack(), &[9]);
+}
+
+#[test]
+fn test_ram_write_then_read_roundtrips() {
+    // write_mem stores the top value at the pointer and increments it;
+    // read_mem reads downwards from the pointer.
+    let tasm = "    push 11\n    push 22\n    push 100\n    write_mem 2\n    pop 1\n    push 101\n    read_mem 2\n    pop 1\n    halt\n";
+    let mut dbg = Debugger::new(tasm, &input(&[], &[]));
+    assert_eq!(dbg.resume(), Stop::Halted);
+    assert_eq!(dbg.ram().get(&100), Some(&22));
+    assert_eq!(dbg.ram().get(&101), Some(&11));
+    assert_eq!(dbg.stack(), &[11, 22]);
+}
+
+#[test]
+fn test_recurse_loops_until_counter_returns() {
+    let tasm = "    push 3
+    call app__loop
+    halt
+app__loop:
+    dup 0
+    push 0
+    eq
+    skiz
+    return
+    push -1
+    add
+    recurse
+";
+    let mut dbg = Debugger::new(tasm, &input(&[], &[]));
+    assert_eq!(dbg.resume(), Stop::Halted);
+    assert_eq!(dbg.stack(), &[0]);
+}
+
+#[test]
+fn test_failed_assert_is_a_final_error() {
+    let mut dbg = Debugger::new("    push 0\n    assert\n    halt\n", &input(&[], &[]));
+    let stop = dbg.resume();
+    assert!(matches!(&stop, Stop::Error(msg) if msg.contains("assertion failed")));
+    assert!(stop.is_final());
+    assert_eq!(dbg.step(), stop);
+}
+
+#[test]
+fn test_require_exact_stops_before_placeholder_instructions() {
+    let tasm = "    push 0\n    push 0\n    push 0\n    push 0\n    push 0\n    push 0\n    push 0\n    push 0\n    push 0\n    push 0\n    hash\n    halt\n";
+    let mut loose = Debugger::new(tasm, &input(&[], &[]));
+    assert_eq!(loose.resume(), Stop::Halted);
+
+    let mut exact = Debugger::new(tasm, &input(&[], &[]));
+    exact.require_exact();
+    let stop = exact.resume();
+    assert!(matches!(&stop, Stop::Error(msg) if msg.starts_with("'hash' is not emulated")));
+    assert_eq!(exact.pc(), 10);
+}
+
+#[test]
+fn test_cycle_limit_interrupts_infinite_loop() {
+    let tasm = "    call app__spin\n    halt\napp__spin:\n    recurse\n";
+    let mut dbg = Debugger::new(tasm, &input(&[], &[]));
+    dbg.set_cycle_limit(50);
+    assert_eq!(dbg.resume(), Stop::CycleLimit);
+    assert_eq!(dbg.cycles(), 50);
+}
+
+#[test]
+fn test_source_map_links_labels_and_lines_to_functions() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program app\n\nfn first<N>(arr: [Field; N]) -> Field {\n    arr[0]\n}\n\nfn main() {\n    pub_write(first<2>([pub_read(), 1]))\n}\n",
+    )
+    .unwrap();
+    let map = crate::source_map_project(&entry, &crate::CompileOptions::default()).unwrap();
+
+    let main = map.function_for_label("app__main").unwrap();
+    assert_eq!((main.start_line, main.end_line), (7, 9));
+    assert_eq!(
+        map.function_for_label("app__first__N2").unwrap().name,
+        "first"
+    );
+    assert!(map.function_for_label("app__then__1").is_none());
+
+    assert_eq!(
+        map.function_at(Some("main.tri"), 4).unwrap().label,
+        "app__first"
+    );
+    assert_eq!(map.function_at(None, 8).unwrap().label, "app__main");
+    assert!(map.function_at(None, 2).is_none());
+    assert!(map.source(&main.file).unwrap().starts_with("program app"));
+}
+
+#[test]
+fn test_symbol_table_lists_items_with_function_labels() {
+    let dir = tempfile::tempdir().unwrap();
+    let entry = dir.path().join("main.tri");
+    std::fs::write(
+        &entry,
+        "program app\n\nconst LIMIT: Field = 3\n\nfn main() {\n    pub_write(LIMIT)\n}\n",
+    )
+    .unwrap();
+    let table = crate::symbol_table_project(&entry, &crate::CompileOptions::default()).unwrap();
+    let kinds: Vec<(&str, &str, usize)> = table
+        .symbols()
+        .iter()
+        .map(|s| (s.name.as_str(), s.kind, s.line))
+        .collect();
+    assert_eq!(kinds, [("LIMIT", "const", 3), ("main", "fn", 5)]);
+    assert_eq!(table.symbols()[1].label.as_deref(), Some("app__main"));
+    assert!(table.to_json().contains("\"label\": null"));
+}
+
+#[test]
+fn test_diff_traces_identical_programs_do_not_diverge() {
+    let mut old = Debugger::new(ADD_PROGRAM, &input(&[3], &[4]));
+    let mut new = Debugger::new(ADD_PROGRAM, &input(&[3], &[4]));
+    let diff = diff_traces(&mut old, &mut new, DEFAULT_CYCLE_LIMIT);
+    assert_eq!(diff.divergence, None);
+    assert_eq!((diff.old_stop, diff.new_stop), (Stop::Halted, Stop::Halted));
+}
+
+#[test]
+fn test_diff_traces_reports_first_diverging_instruction_and_stacks() {
+    let broken = ADD_PROGRAM.replace("    add\n", "    mul\n");
+    let mut old = Debugger::new(ADD_PROGRAM, &input(&[3], &[4]));
+    let mut new = Debugger::new(&broken, &input(&[3], &[4]));
+    let diff = diff_traces(&mut old, &mut new, DEFAULT_CYCLE_LIMIT);
+
+    let divergence = diff.divergence.unwrap();
+    assert_eq!(divergence.cycle, 4);
+    assert_eq!(divergence.old.instruction.unwrap().text, "add");
+    assert_eq!(divergence.new.instruction.unwrap().text, "mul");
+    assert_eq!(divergence.old.stack, [3, 4]);
+    assert_eq!(divergence.new.function.as_deref(), Some("app__add"));
+    assert_eq!(old.output(), &[7]);
+    assert_eq!(new.output(), &[12]);
+}
+
+#[test]
+fn test_diff_traces_ignores_label_layout_but_not_stack_effects() {
+    // Same instructions at different TASM lines: no divergence.
+    let shifted = ADD_PROGRAM.replace("app__add:\n", "\napp__add:\n");
+    let mut old = Debugger::new(ADD_PROGRAM, &input(&[3], &[4]));
+    let mut new = Debugger::new(&shifted, &input(&[3], &[4]));
+    assert_eq!(diff_traces(&mut old, &mut new, 100).divergence, None);
+
+    // Same instruction, different stack afterwards: diverges one cycle later.
+    let wrong = ADD_PROGRAM.replace("    divine 1\n", "    divine 1\n    push 1\n    add\n");
+    let mut old = Debugger::new(ADD_PROGRAM, &input(&[3], &[4]));
+    let mut new = Debugger::new(&wrong, &input(&[3], &[4]));
+    let divergence = diff_traces(&mut old, &mut new, 100).divergence.unwrap();
+    assert_eq!(divergence.cycle, 3);
+    assert_eq!(divergence.old.instruction.unwrap().text, "call app__add");
+    assert_eq!(divergence.new.instruction.unwrap().text, "push 1");
+}
@@ -0,0 +1,113 @@
+//! Lockstep comparison of two TASM programs run on the same input.
+//!
+//! Both programs execute one instruction at a time; the first cycle where
+//! the next instruction, the operand stack, or the output differs is
+//! reported. Meant for optimizer and backend regressions: compile the same
+//! program twice and find where the two executions part ways.
+
+use super::{Debugger, Instruction, Stop};
+
+/// State of one program just before a cycle executes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Next instruction (`None` past the end of the program).
+    pub instruction: Option<Instruction>,
+    /// Operand stack, bottom first.
+    pub stack: Vec<u64>,
+    /// Output written so far.
+    pub output: Vec<u64>,
+    /// Label of the innermost call frame.
+    pub function: Option<String>,
+}
+
+impl Snapshot {
+    fn of(dbg: &Debugger) -> Self {
+        Self {
+            instruction: dbg.current().cloned(),
+            stack: dbg.stack().to_vec(),
+            output: dbg.output().to_vec(),
+            function: dbg.frames().last().map(|f| f.label.clone()),
+        }
+    }
+
+    /// Whether two snapshots describe the same execution state. TASM
+    /// line numbers and call frames are ignored: they legitimately differ
+    /// between compilations.
+    fn matches(&self, other: &Snapshot) -> bool {
+        self.instruction.as_ref().map(|i| &i.text) == other.instruction.as_ref().map(|i| &i.text)
+            && self.stack == other.stack
+            && self.output == other.output
+    }
+}
+
+/// The first cycle at which the two executions differ.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Number of instructions both programs executed identically.
+    pub cycle: u64,
+    pub old: Snapshot,
+    pub new: Snapshot,
+}
+
+/// Result of comparing two executions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceDiff {
+    /// First point of difference, or `None` if the traces are identical.
+    pub divergence: Option<Divergence>,
+    /// How each program stopped when run to completion.
+    pub old_stop: Stop,
+    pub new_stop: Stop,
+}
+
+/// Run `old` and `new` in lockstep for at most `cycle_limit` instructions
+/// and report where they diverge. Both debuggers are left at the end of
+/// their runs, so their outputs can be inspected afterwards.
+pub fn diff_traces(old: &mut Debugger, new: &mut Debugger, cycle_limit: u64) -> TraceDiff {
+    old.set_cycle_limit(cycle_limit);
+    new.set_cycle_limit(cycle_limit);
+    let mut cycle = 0;
+    loop {
+        let (before_old, before_new) = (Snapshot::of(old), Snapshot::of(new));
+        if !before_old.matches(&before_new) {
+            return finish(old, new, cycle, before_old, before_new);
+        }
+        if cycle >= cycle_limit {
+            return TraceDiff {
+                divergence: None,
+                old_stop: Stop::CycleLimit,
+                new_stop: Stop::CycleLimit,
+            };
+        }
+        let (old_stop, new_stop) = (old.step(), new.step());
+        if old_stop != new_stop {
+            return finish(old, new, cycle, before_old, before_new);
+        }
+        if old_stop != Stop::Step {
+            return TraceDiff {
+                divergence: None,
+                old_stop,
+                new_stop,
+            };
+        }
+        cycle += 1;
+    }
+}
+
+/// Run both programs to completion after a divergence.
+fn finish(
+    old: &mut Debugger,
+    new: &mut Debugger,
+    cycle: u64,
+    old_snapshot: Snapshot,
+    new_snapshot: Snapshot,
+) -> TraceDiff {
+    TraceDiff {
+        divergence: Some(Divergence {
+            cycle,
+            old: old_snapshot,
+            new: new_snapshot,
+        }),
+        old_stop: old.resume(),
+        new_stop: new.resume(),
+    }
+}
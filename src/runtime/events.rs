@@ -0,0 +1,500 @@
+//! Event log decoding: turn a program's public output back into events.
+//!
+//! `reveal` writes the event's tag (its index among the entry module's
+//! events) followed by its fields; `seal` writes only the 5-element
+//! digest of the tag and fields. Decoding walks the output against the
+//! ABI: a digest matching a supplied preimage becomes a sealed event, a
+//! known tag with enough words after it becomes a revealed event, and
+//! everything else is kept as unrecognized words. Plain `pub_write`
+//! values that happen to equal a tag are indistinguishable from events,
+//! unless the target frames its events (`EventChannel::Framed`): then
+//! only words behind a frame header are read as events.
+
+use crate::field::goldilocks::MODULUS;
+use crate::tir::lower::{EventChannel, REVEAL_FRAME, SEAL_FRAME};
+
+use super::abi::{AbiRecord, ProgramAbi};
+use super::artifact::json_string;
+
+/// Elements `seal` writes to public output.
+pub const DIGEST_LEN: usize = 5;
+
+/// Elements `seal` hashes: the tag, zero padding, and the fields.
+const SEAL_RATE: usize = 10;
+
+/// One record of the decoded output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EventRecord {
+    /// A `reveal`: tag and fields in the clear.
+    Revealed {
+        /// Index of the tag in the output stream.
+        offset: usize,
+        event: String,
+        fields: Vec<(String, u64)>,
+    },
+    /// A `seal` whose digest matches a supplied preimage.
+    Sealed {
+        offset: usize,
+        event: String,
+        fields: Vec<(String, u64)>,
+        digest: [u64; DIGEST_LEN],
+    },
+    /// Words that are neither a known tag nor a supplied digest.
+    Unrecognized { offset: usize, words: Vec<u64> },
+}
+
+/// The fields of a sealed event, supplied so its digest can be found
+/// and checked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SealPreimage {
+    pub event: String,
+    /// Field values in declaration order.
+    pub fields: Vec<u64>,
+}
+
+impl SealPreimage {
+    /// Parse `Name=v1,v2,...` (`--seal`), checking the event and its
+    /// field count against the ABI.
+    pub fn parse(spec: &str, abi: &ProgramAbi) -> Result<Self, String> {
+        let (event, values) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("expected 'Event=v1,v2,...', got '{}'", spec))?;
+        let event = event.trim();
+        let record = find_event(abi, event)
+            .ok_or_else(|| format!("'{}' is not an event of '{}'", event, abi.name))?
+            .1;
+        let fields = parse_output_words(values)?;
+        if fields.len() != record.fields.len() {
+            return Err(format!(
+                "event '{}' has {} field(s), got {} value(s)",
+                event,
+                record.fields.len(),
+                fields.len()
+            ));
+        }
+        Ok(SealPreimage {
+            event: event.to_string(),
+            fields,
+        })
+    }
+
+    /// The elements `seal` hashes, top of stack first: the tag, zero
+    /// padding, then the fields in declaration order.
+    pub fn hash_input(&self, abi: &ProgramAbi) -> [u64; SEAL_RATE] {
+        let tag = find_event(abi, &self.event).map_or(0, |(tag, _)| tag);
+        let mut input = [0; SEAL_RATE];
+        input[0] = tag;
+        let start = SEAL_RATE - self.fields.len().min(SEAL_RATE - 1);
+        for (slot, value) in input[start..].iter_mut().zip(&self.fields) {
+            *slot = *value;
+        }
+        input
+    }
+}
+
+/// TASM that writes the digest of each preimage, in order, the way the
+/// Triton lowering of `seal` computes it. Run it on a warrior to get
+/// the digests `decode_events` matches against.
+pub fn seal_digest_tasm(preimages: &[SealPreimage], abi: &ProgramAbi) -> String {
+    let mut out = String::new();
+    for preimage in preimages {
+        out.push_str(&format!("    // seal {}\n", preimage.event));
+        for value in preimage.hash_input(abi).iter().rev() {
+            out.push_str(&format!("    push {}\n", value));
+        }
+        out.push_str("    hash\n    write_io 5\n");
+    }
+    out.push_str("    halt\n");
+    out
+}
+
+/// Decoded public output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EventLog {
+    pub records: Vec<EventRecord>,
+    /// Supplied preimages whose digest never appears in the output.
+    pub unmatched: Vec<SealPreimage>,
+}
+
+/// Decode `output` against the events of `abi`. Each entry of `seals`
+/// pairs a preimage with its digest; a digest is matched at most once.
+pub fn decode_events(
+    abi: &ProgramAbi,
+    output: &[u64],
+    seals: &[(SealPreimage, [u64; DIGEST_LEN])],
+) -> EventLog {
+    let mut log = EventLog::default();
+    let mut matched = vec![false; seals.len()];
+    let mut unknown: Vec<u64> = Vec::new();
+    let flush = |unknown: &mut Vec<u64>, records: &mut Vec<EventRecord>, offset: usize| {
+        if !unknown.is_empty() {
+            records.push(EventRecord::Unrecognized {
+                offset: offset - unknown.len(),
+                words: std::mem::take(unknown),
+            });
+        }
+    };
+    let framed = abi.event_channel != EventChannel::Shared;
+    let mut i = 0;
+    while i < output.len() {
+        // A framed event starts after its header; any other word is a
+        // program result.
+        let (start, frame) = if framed {
+            match frame_header(output[i]) {
+                Some((kind, len)) if i + len < output.len() => (i + 1, Some((kind, len))),
+                _ => {
+                    unknown.push(output[i]);
+                    i += 1;
+                    continue;
+                }
+            }
+        } else {
+            (i, None)
+        };
+        let sealed = output
+            .get(start..start + DIGEST_LEN)
+            .filter(|_| frame.is_none_or(|(kind, len)| kind == SEAL_FRAME && len == DIGEST_LEN))
+            .and_then(|words| (0..seals.len()).find(|&k| !matched[k] && seals[k].1 == words));
+        if let Some(k) = sealed {
+            flush(&mut unknown, &mut log.records, i);
+            matched[k] = true;
+            let (preimage, digest) = &seals[k];
+            let record = find_event(abi, &preimage.event).map(|(_, r)| r);
+            log.records.push(EventRecord::Sealed {
+                offset: start,
+                event: preimage.event.clone(),
+                fields: named_fields(record, &preimage.fields),
+                digest: *digest,
+            });
+            i = start + DIGEST_LEN;
+            continue;
+        }
+        let revealed = abi
+            .events
+            .get(output[start] as usize)
+            .filter(|r| start + r.fields.len() < output.len())
+            .filter(|r| {
+                frame.is_none_or(|(kind, len)| kind == REVEAL_FRAME && len == r.fields.len() + 1)
+            });
+        if let Some(record) = revealed {
+            flush(&mut unknown, &mut log.records, i);
+            let values = &output[start + 1..start + 1 + record.fields.len()];
+            log.records.push(EventRecord::Revealed {
+                offset: start,
+                event: record.name.clone(),
+                fields: named_fields(Some(record), values),
+            });
+            i = start + 1 + record.fields.len();
+            continue;
+        }
+        // A frame that matches no event is kept whole.
+        let end = frame.map_or(i + 1, |(_, len)| start + len);
+        unknown.extend_from_slice(&output[i..end]);
+        i = end;
+    }
+    flush(&mut unknown, &mut log.records, output.len());
+    log.unmatched = seals
+        .iter()
+        .zip(&matched)
+        .filter(|(_, m)| !**m)
+        .map(|((p, _), _)| p.clone())
+        .collect();
+    log
+}
+
+impl EventLog {
+    /// Human-readable listing for `trident decode-events`.
+    pub fn format_report(&self) -> String {
+        let mut out = String::new();
+        for record in &self.records {
+            match record {
+                EventRecord::Revealed {
+                    offset,
+                    event,
+                    fields,
+                } => out.push_str(&format!(
+                    "{:>6}  reveal  {}\n",
+                    offset,
+                    format_event(event, fields)
+                )),
+                EventRecord::Sealed {
+                    offset,
+                    event,
+                    fields,
+                    ..
+                } => out.push_str(&format!(
+                    "{:>6}  seal    {}  (digest verified)\n",
+                    offset,
+                    format_event(event, fields)
+                )),
+                EventRecord::Unrecognized { offset, words } => {
+                    out.push_str(&format!("{:>6}  ?       {:?}\n", offset, words))
+                }
+            }
+        }
+        if self.records.is_empty() {
+            out.push_str("no output\n");
+        }
+        for preimage in &self.unmatched {
+            out.push_str(&format!(
+                "error: no digest in the output matches sealed {} {:?}\n",
+                preimage.event, preimage.fields
+            ));
+        }
+        out
+    }
+
+    /// Machine-readable form (`--json`).
+    pub fn to_json(&self) -> String {
+        let fields_json = |fields: &[(String, u64)]| {
+            let parts: Vec<String> = fields
+                .iter()
+                .map(|(name, value)| format!("{}: {}", json_string(name), value))
+                .collect();
+            format!("{{ {} }}", parts.join(", "))
+        };
+        let words_json = |words: &[u64]| {
+            let parts: Vec<String> = words.iter().map(u64::to_string).collect();
+            format!("[{}]", parts.join(", "))
+        };
+        let records: Vec<String> = self
+            .records
+            .iter()
+            .map(|record| match record {
+                EventRecord::Revealed {
+                    offset,
+                    event,
+                    fields,
+                } => format!(
+                    "\n    {{ \"offset\": {}, \"kind\": \"reveal\", \"event\": {}, \"fields\": {} }}",
+                    offset,
+                    json_string(event),
+                    fields_json(fields)
+                ),
+                EventRecord::Sealed {
+                    offset,
+                    event,
+                    fields,
+                    digest,
+                } => format!(
+                    "\n    {{ \"offset\": {}, \"kind\": \"seal\", \"event\": {}, \"fields\": {}, \"digest\": {} }}",
+                    offset,
+                    json_string(event),
+                    fields_json(fields),
+                    words_json(digest)
+                ),
+                EventRecord::Unrecognized { offset, words } => format!(
+                    "\n    {{ \"offset\": {}, \"kind\": \"unrecognized\", \"words\": {} }}",
+                    offset,
+                    words_json(words)
+                ),
+            })
+            .collect();
+        let unmatched: Vec<String> = self
+            .unmatched
+            .iter()
+            .map(|p| {
+                format!(
+                    "\n    {{ \"event\": {}, \"fields\": {} }}",
+                    json_string(&p.event),
+                    words_json(&p.fields)
+                )
+            })
+            .collect();
+        let mut out = String::from("{\n  \"records\": [");
+        push_list(&mut out, &records);
+        out.push_str(",\n  \"unmatched_seals\": [");
+        push_list(&mut out, &unmatched);
+        out.push_str("\n}\n");
+        out
+    }
+}
+
+/// Parse a public-output stream: decimal or `0x` hex words separated
+/// by whitespace or commas.
+pub fn parse_output_words(text: &str) -> Result<Vec<u64>, String> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+        .map(parse_word)
+        .collect()
+}
+
+/// One field element, decimal or `0x` hex.
+pub(super) fn parse_word(word: &str) -> Result<u64, String> {
+    let value = match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => word.parse(),
+    }
+    .map_err(|_| format!("'{}' is not a field element", word))?;
+    if value >= MODULUS {
+        return Err(format!("'{}' is not below the field modulus", word));
+    }
+    Ok(value)
+}
+
+/// Kind (`REVEAL_FRAME` or `SEAL_FRAME`) and payload length of a frame
+/// header.
+fn frame_header(word: u64) -> Option<(u64, usize)> {
+    [REVEAL_FRAME, SEAL_FRAME].into_iter().find_map(|base| {
+        let len = word.checked_sub(base).filter(|&len| len < 1 << 16)?;
+        Some((base, len as usize))
+    })
+}
+
+/// An event's tag and definition.
+fn find_event<'a>(abi: &'a ProgramAbi, name: &str) -> Option<(u64, &'a AbiRecord)> {
+    abi.events
+        .iter()
+        .enumerate()
+        .find(|(_, r)| r.name == name)
+        .map(|(tag, r)| (tag as u64, r))
+}
+
+fn named_fields(record: Option<&AbiRecord>, values: &[u64]) -> Vec<(String, u64)> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let name = record
+                .and_then(|r| r.fields.get(i))
+                .map_or_else(|| i.to_string(), |f| f.name.clone());
+            (name, *value)
+        })
+        .collect()
+}
+
+fn format_event(event: &str, fields: &[(String, u64)]) -> String {
+    let parts: Vec<String> = fields
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect();
+    format!("{} {{ {} }}", event, parts.join(", "))
+}
+
+/// Close a JSON array opened by the caller.
+fn push_list(out: &mut String, entries: &[String]) {
+    if entries.is_empty() {
+        out.push(']');
+    } else {
+        out.push_str(&entries.join(","));
+        out.push_str("\n  ]");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn abi() -> ProgramAbi {
+        let source = "program p\n\
+            event Transfer { from: Field, to: Field, amount: Field }\n\
+            event Commit { value: Field }\n\
+            fn main() {}\n";
+        ProgramAbi::from_file(&crate::parse_source_silent(source, "p.tri").unwrap())
+    }
+
+    #[test]
+    fn test_reveals_seals_and_stray_words_decode_in_order() {
+        let abi = abi();
+        let commit = SealPreimage::parse("Commit=7", &abi).unwrap();
+        assert_eq!(commit.hash_input(&abi), [1, 0, 0, 0, 0, 0, 0, 0, 0, 7]);
+        let missing = SealPreimage::parse("Commit=0x8", &abi).unwrap();
+        let digest = [11, 12, 13, 14, 15];
+        let seals = [(commit, digest), (missing, [21, 22, 23, 24, 25])];
+
+        let output = parse_output_words("0, 3, 4, 50\n11 12 13 14 15\n99").unwrap();
+        let log = decode_events(&abi, &output, &seals);
+        assert_eq!(
+            log.records,
+            [
+                EventRecord::Revealed {
+                    offset: 0,
+                    event: "Transfer".to_string(),
+                    fields: vec![
+                        ("from".to_string(), 3),
+                        ("to".to_string(), 4),
+                        ("amount".to_string(), 50)
+                    ],
+                },
+                EventRecord::Sealed {
+                    offset: 4,
+                    event: "Commit".to_string(),
+                    fields: vec![("value".to_string(), 7)],
+                    digest,
+                },
+                EventRecord::Unrecognized {
+                    offset: 9,
+                    words: vec![99],
+                },
+            ]
+        );
+        assert_eq!(log.unmatched.len(), 1);
+        let report = log.format_report();
+        assert!(report.contains("reveal  Transfer { from: 3, to: 4, amount: 50 }"));
+        assert!(report.contains("no digest in the output matches sealed Commit [8]"));
+        assert!(log
+            .to_json()
+            .contains("\"kind\": \"seal\", \"event\": \"Commit\""));
+    }
+
+    #[test]
+    fn test_framed_output_only_reads_events_behind_a_header() {
+        let mut abi = abi();
+        abi.event_channel = EventChannel::Framed;
+        let commit = SealPreimage::parse("Commit=7", &abi).unwrap();
+        let digest = [11, 12, 13, 14, 15];
+        let mut output = vec![1, 2];
+        output.extend([REVEAL_FRAME + 2, 1, 9]);
+        output.extend([SEAL_FRAME + 5, 11, 12, 13, 14, 15]);
+        output.extend([REVEAL_FRAME + 4, 0, 3, 4, 50, 0]);
+        let log = decode_events(&abi, &output, &[(commit, digest)]);
+        assert_eq!(
+            log.records,
+            [
+                EventRecord::Unrecognized {
+                    offset: 0,
+                    words: vec![1, 2],
+                },
+                EventRecord::Revealed {
+                    offset: 3,
+                    event: "Commit".to_string(),
+                    fields: vec![("value".to_string(), 9)],
+                },
+                EventRecord::Sealed {
+                    offset: 6,
+                    event: "Commit".to_string(),
+                    fields: vec![("value".to_string(), 7)],
+                    digest,
+                },
+                EventRecord::Revealed {
+                    offset: 12,
+                    event: "Transfer".to_string(),
+                    fields: vec![
+                        ("from".to_string(), 3),
+                        ("to".to_string(), 4),
+                        ("amount".to_string(), 50)
+                    ],
+                },
+                EventRecord::Unrecognized {
+                    offset: 16,
+                    words: vec![0],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_seal_preimages_are_checked_against_the_abi() {
+        let abi = abi();
+        assert!(SealPreimage::parse("Commit=1,2", &abi)
+            .unwrap_err()
+            .contains("has 1 field(s)"));
+        assert!(SealPreimage::parse("Opened=1", &abi)
+            .unwrap_err()
+            .contains("not an event"));
+        assert!(parse_output_words("18446744069414584321").is_err());
+        let tasm = seal_digest_tasm(&[SealPreimage::parse("Commit=7", &abi).unwrap()], &abi);
+        assert!(tasm.starts_with("    // seal Commit\n    push 7\n"));
+        assert!(tasm.ends_with("    push 1\n    hash\n    write_io 5\n    halt\n"));
+    }
+}
@@ -0,0 +1,454 @@
+//! Input encoding: typed values to the field elements a program reads.
+//!
+//! The inverse of `events`. A values file lists what `pub_read` and
+//! `divine` should return, in order, each with its ABI type:
+//!
+//! ```text
+//! {
+//!   "entry": "pay",
+//!   "public": [{ "type": "U32", "value": 5 }],
+//!   "secret": [{ "type": "Point", "value": { "x": 1, "y": 2 } }]
+//! }
+//! ```
+//!
+//! Values are flattened the way the bindings encode them: structs in
+//! field declaration order, arrays and tuples element by element. An
+//! `entry` puts its `#[entry]` tag first on the public stream. The
+//! result can be saved as a witness file for `trident run` and
+//! `trident test`.
+
+use super::abi::ProgramAbi;
+use super::bindgen::AbiType;
+use super::events::parse_word;
+use super::ProgramInput;
+
+/// Encode a values file against `abi`.
+pub fn encode_input(abi: &ProgramAbi, json: &str) -> Result<ProgramInput, String> {
+    let root = Json::parse(json)?;
+    let Json::Object(keys) = &root else {
+        return Err("expected a JSON object with \"public\" and \"secret\"".to_string());
+    };
+    let mut input = ProgramInput::default();
+    for (key, value) in keys {
+        match key.as_str() {
+            "entry" => {
+                let Json::String(name) = value else {
+                    return Err("\"entry\" must be a string".to_string());
+                };
+                let entry = abi
+                    .entries
+                    .iter()
+                    .find(|e| e.name == *name)
+                    .ok_or_else(|| format!("'{}' is not an #[entry] of '{}'", name, abi.name))?;
+                input.public.insert(0, entry.tag);
+            }
+            "public" => encode_stream(abi, "public", value, &mut input.public)?,
+            "secret" => encode_stream(abi, "secret", value, &mut input.secret)?,
+            other => return Err(format!("unknown key \"{}\"", other)),
+        }
+    }
+    Ok(input)
+}
+
+fn encode_stream(
+    abi: &ProgramAbi,
+    stream: &str,
+    values: &Json,
+    out: &mut Vec<u64>,
+) -> Result<(), String> {
+    let Json::Array(values) = values else {
+        return Err(format!("\"{}\" must be an array", stream));
+    };
+    for (i, entry) in values.iter().enumerate() {
+        let path = format!("{}[{}]", stream, i);
+        let (Some(Json::String(ty)), Some(value)) = (entry.get("type"), entry.get("value")) else {
+            return Err(format!(
+                "{}: expected {{ \"type\": ..., \"value\": ... }}",
+                path
+            ));
+        };
+        let ty = AbiType::parse(ty).map_err(|e| format!("{}: {}", path, e))?;
+        encode_value(abi, &ty, value, &path, out)?;
+    }
+    Ok(())
+}
+
+fn encode_value(
+    abi: &ProgramAbi,
+    ty: &AbiType,
+    value: &Json,
+    path: &str,
+    out: &mut Vec<u64>,
+) -> Result<(), String> {
+    let elements = |len: usize| match value {
+        Json::Array(elems) if elems.len() == len => Ok(elems),
+        _ => Err(format!("{}: expected an array of {}", path, len)),
+    };
+    match ty {
+        AbiType::Field => out.push(field_element(value, path)?),
+        AbiType::U32 => {
+            let v = field_element(value, path)?;
+            if v > u64::from(u32::MAX) {
+                return Err(format!("{}: U32 out of range: {}", path, v));
+            }
+            out.push(v);
+        }
+        AbiType::Bool => match value {
+            Json::Bool(b) => out.push(u64::from(*b)),
+            _ => return Err(format!("{}: expected true or false", path)),
+        },
+        AbiType::XField | AbiType::Digest => {
+            let len = if *ty == AbiType::XField { 3 } else { 5 };
+            for (i, elem) in elements(len)?.iter().enumerate() {
+                out.push(field_element(elem, &format!("{}[{}]", path, i))?);
+            }
+        }
+        AbiType::Array(elem_ty, len) => {
+            for (i, elem) in elements(*len)?.iter().enumerate() {
+                encode_value(abi, elem_ty, elem, &format!("{}[{}]", path, i), out)?;
+            }
+        }
+        AbiType::Tuple(elem_tys) => {
+            let elems = elements(elem_tys.len())?;
+            for (i, (elem_ty, elem)) in elem_tys.iter().zip(elems).enumerate() {
+                encode_value(abi, elem_ty, elem, &format!("{}.{}", path, i), out)?;
+            }
+        }
+        AbiType::Named(name) => {
+            let base = name.rsplit('.').next().unwrap_or(name);
+            let record =
+                abi.structs.iter().find(|s| s.name == base).ok_or_else(|| {
+                    format!("{}: '{}' is not a `pub struct` of the ABI", path, name)
+                })?;
+            let Json::Object(given) = value else {
+                return Err(format!("{}: expected an object for '{}'", path, name));
+            };
+            if let Some((extra, _)) = given
+                .iter()
+                .find(|(k, _)| !record.fields.iter().any(|f| f.name == *k))
+            {
+                return Err(format!("{}: '{}' has no field '{}'", path, name, extra));
+            }
+            for field in &record.fields {
+                let field_path = format!("{}.{}", path, field.name);
+                let field_value = value
+                    .get(&field.name)
+                    .ok_or_else(|| format!("{}: missing", field_path))?;
+                let field_ty = AbiType::parse(&field.ty)?;
+                encode_value(abi, &field_ty, field_value, &field_path, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A field element from a JSON number or a decimal/`0x` hex string
+/// (for values past the 2^53 that JSON numbers carry exactly).
+fn field_element(value: &Json, path: &str) -> Result<u64, String> {
+    match value {
+        Json::Number(text) | Json::String(text) => {
+            parse_word(text).map_err(|e| format!("{}: {}", path, e))
+        }
+        _ => Err(format!("{}: expected a field element", path)),
+    }
+}
+
+impl ProgramInput {
+    /// Serialize as a witness file (`trident encode-input -o`).
+    pub fn to_witness_json(&self) -> String {
+        let words = |values: &[u64]| {
+            let parts: Vec<String> = values.iter().map(u64::to_string).collect();
+            format!("[{}]", parts.join(", "))
+        };
+        let digests: Vec<String> = self.digests.iter().map(|d| words(d)).collect();
+        format!(
+            "{{\n  \"public\": {},\n  \"secret\": {},\n  \"digests\": [{}]\n}}\n",
+            words(&self.public),
+            words(&self.secret),
+            digests.join(", ")
+        )
+    }
+
+    /// Read a witness file (`--witness`).
+    pub fn from_witness_json(json: &str) -> Result<Self, String> {
+        let root = Json::parse(json)?;
+        let words = |key: &str| -> Result<Vec<u64>, String> {
+            match root.get(key) {
+                None => Ok(Vec::new()),
+                Some(Json::Array(values)) => values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| field_element(v, &format!("{}[{}]", key, i)))
+                    .collect(),
+                Some(_) => Err(format!("\"{}\" must be an array", key)),
+            }
+        };
+        let mut input = ProgramInput {
+            public: words("public")?,
+            secret: words("secret")?,
+            digests: Vec::new(),
+        };
+        if let Some(Json::Array(digests)) = root.get("digests") {
+            for (i, digest) in digests.iter().enumerate() {
+                let path = format!("digests[{}]", i);
+                let Json::Array(elems) = digest else {
+                    return Err(format!("{}: expected an array of 5", path));
+                };
+                let words = elems
+                    .iter()
+                    .enumerate()
+                    .map(|(j, v)| field_element(v, &format!("{}[{}]", path, j)))
+                    .collect::<Result<Vec<u64>, String>>()?;
+                let digest = words
+                    .try_into()
+                    .map_err(|_| format!("{}: expected an array of 5", path))?;
+                input.digests.push(digest);
+            }
+        }
+        Ok(input)
+    }
+}
+
+/// Render an encoded stream as the comma-separated list `--input-values`
+/// and `--secret` take.
+pub fn format_words(words: &[u64]) -> String {
+    let parts: Vec<String> = words.iter().map(u64::to_string).collect();
+    parts.join(",")
+}
+
+/// Read a list of field elements, decimal or `0x` hex, separated by
+/// commas and/or whitespace: the inverse of `format_words`.
+pub fn parse_words(text: &str) -> Result<Vec<u64>, String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(parse_word)
+        .collect()
+}
+
+// ─── JSON ──────────────────────────────────────────────────────────
+
+/// A parsed JSON value. Numbers keep their text so 64-bit field
+/// elements survive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = JsonParser {
+            bytes: text.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_ws();
+        if parser.pos < parser.bytes.len() {
+            return Err(format!("trailing characters at byte {}", parser.pos));
+        }
+        Ok(value)
+    }
+
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn skip_ws(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, expected: &str) -> String {
+        format!("invalid JSON: expected {} at byte {}", expected, self.pos)
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_ws();
+        let found = self.bytes.get(self.pos) == Some(&byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_ws();
+        let rest = &self.bytes[self.pos..];
+        for (word, value) in [
+            ("null", Json::Null),
+            ("true", Json::Bool(true)),
+            ("false", Json::Bool(false)),
+        ] {
+            if rest.starts_with(word.as_bytes()) {
+                self.pos += word.len();
+                return Ok(value);
+            }
+        }
+        match rest.first() {
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut elems = Vec::new();
+                if self.eat(b']') {
+                    return Ok(Json::Array(elems));
+                }
+                loop {
+                    elems.push(self.value()?);
+                    if self.eat(b']') {
+                        return Ok(Json::Array(elems));
+                    }
+                    if !self.eat(b',') {
+                        return Err(self.error("',' or ']'"));
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut entries = Vec::new();
+                if self.eat(b'}') {
+                    return Ok(Json::Object(entries));
+                }
+                loop {
+                    self.skip_ws();
+                    let key = self.string()?;
+                    if !self.eat(b':') {
+                        return Err(self.error("':'"));
+                    }
+                    entries.push((key, self.value()?));
+                    if self.eat(b'}') {
+                        return Ok(Json::Object(entries));
+                    }
+                    if !self.eat(b',') {
+                        return Err(self.error("',' or '}'"));
+                    }
+                }
+            }
+            Some(c) if *c == b'-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || b"+-.".contains(c))
+                {
+                    self.pos += 1;
+                }
+                let text = String::from_utf8_lossy(&self.bytes[start..self.pos]);
+                Ok(Json::Number(text.into_owned()))
+            }
+            _ => Err(self.error("a value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.bytes.get(self.pos) != Some(&b'"') {
+            return Err(self.error("a string"));
+        }
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            match self.bytes.get(self.pos) {
+                None => return Err(self.error("closing '\"'")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return String::from_utf8(out).map_err(|_| self.error("UTF-8"));
+                }
+                Some(b'\\') => {
+                    let escaped = match self.bytes.get(self.pos + 1) {
+                        Some(b'n') => b'\n',
+                        Some(b't') => b'\t',
+                        Some(b'r') => b'\r',
+                        Some(&c @ (b'"' | b'\\' | b'/')) => c,
+                        _ => return Err(self.error("a supported escape")),
+                    };
+                    out.push(escaped);
+                    self.pos += 2;
+                }
+                Some(&c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn abi() -> ProgramAbi {
+        let source = "program p\n\
+            pub struct Point { x: Field, y: U32 }\n\
+            #[entry]\nfn open() {}\n\
+            #[entry]\nfn pay() {}\n";
+        ProgramAbi::from_file(&crate::parse_source_silent(source, "p.tri").unwrap())
+    }
+
+    #[test]
+    fn test_typed_values_flatten_in_declaration_order() {
+        let json = r#"{
+            "entry": "pay",
+            "public": [
+                { "type": "U32", "value": 5 },
+                { "type": "[Point; 2]", "value": [{ "y": 2, "x": 1 }, { "x": "0x10", "y": 3 }] }
+            ],
+            "secret": [
+                { "type": "(Bool, Digest)", "value": [true, [1, 2, 3, 4, 5]] },
+                { "type": "Field", "value": "18446744069414584320" }
+            ]
+        }"#;
+        let input = encode_input(&abi(), json).unwrap();
+        assert_eq!(input.public, [1, 5, 1, 2, 16, 3]);
+        assert_eq!(input.secret, [1, 1, 2, 3, 4, 5, 18446744069414584320]);
+        assert_eq!(format_words(&input.public), "1,5,1,2,16,3");
+        assert_eq!(parse_words("1,5, 1 2,0x10,,3"), Ok(input.public.clone()));
+        assert!(parse_words("1 x")
+            .unwrap_err()
+            .contains("'x' is not a field element"));
+
+        let witness = input.to_witness_json();
+        assert_eq!(
+            ProgramInput::from_witness_json(&witness).unwrap().secret,
+            input.secret
+        );
+    }
+
+    #[test]
+    fn test_values_that_do_not_fit_their_type_are_rejected() {
+        let abi = abi();
+        let encode = |value: &str| {
+            encode_input(&abi, &format!("{{ \"public\": [{}] }}", value)).unwrap_err()
+        };
+        assert!(encode(r#"{ "type": "U32", "value": 4294967296 }"#).contains("U32 out of range"));
+        assert!(
+            encode(r#"{ "type": "Point", "value": { "x": 1 } }"#).contains("public[0].y: missing")
+        );
+        assert!(
+            encode(r#"{ "type": "Point", "value": { "x": 1, "y": 2, "z": 3 } }"#)
+                .contains("has no field 'z'")
+        );
+        assert!(encode(r#"{ "type": "XField", "value": [1, 2] }"#).contains("array of 3"));
+        assert!(encode_input(&abi, r#"{ "entry": "close" }"#)
+            .unwrap_err()
+            .contains("not an #[entry]"));
+    }
+}
@@ -0,0 +1,27 @@
+//! Neptune transaction kernel layout.
+//!
+//! A transaction kernel is committed to as a Merkle tree (its MAST hash)
+//! whose leaves are the fields of Neptune's `TransactionKernel`, in
+//! declaration order. Warriors build and open the tree from this table;
+//! `os/neptune/kernel.tri` mirrors it as `TREE_HEIGHT` and one `LEAF_*`
+//! constant per field.
+
+/// Kernel fields in leaf order.
+pub const KERNEL_FIELDS: [&str; 8] = [
+    "inputs",
+    "outputs",
+    "announcements",
+    "fee",
+    "coinbase",
+    "timestamp",
+    "mutator_set_hash",
+    "merge_bit",
+];
+
+/// Height of the kernel Merkle tree.
+pub const KERNEL_TREE_HEIGHT: u32 = KERNEL_FIELDS.len().ilog2();
+
+/// Leaf index of kernel field `name`.
+pub fn kernel_leaf(name: &str) -> Option<usize> {
+    KERNEL_FIELDS.iter().position(|field| *field == name)
+}
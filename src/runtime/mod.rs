@@ -14,9 +14,16 @@
 //! No heavy dependencies here — only the interface contract and
 //! the serializable `ProgramBundle` artifact format.
 
+pub mod abi;
 pub mod artifact;
+pub mod bindgen;
+pub mod debug;
+pub mod events;
+pub mod input;
+pub mod kernel;
 
 use crate::field::proof::Claim;
+pub use abi::ProgramAbi;
 pub use artifact::ProgramBundle;
 
 // ─── Types ─────────────────────────────────────────────────────────
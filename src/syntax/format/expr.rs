@@ -1,6 +1,8 @@
 pub(crate) use crate::ast::display::format_ast_type as format_type;
 use crate::ast::*;
 
+use super::FormatCtx;
+
 /// Format an expression to a single-line string.
 pub(crate) fn format_expr(expr: &Expr) -> String {
     match expr {
@@ -53,6 +55,47 @@ pub(crate) fn format_expr(expr: &Expr) -> String {
             let inner: Vec<String> = elems.iter().map(|e| format_expr(&e.node)).collect();
             format!("({})", inner.join(", "))
         }
+        Expr::If { .. } => format_if_flat(expr).unwrap_or_else(|| {
+            let mut ctx = FormatCtx::new(&[]);
+            ctx.emit_if_expr(expr, "");
+            ctx.output
+        }),
+    }
+}
+
+/// Format an `if` expression on one line, or `None` if a branch holds
+/// statements and needs the multi-line layout.
+pub(super) fn format_if_flat(expr: &Expr) -> Option<String> {
+    let Expr::If {
+        cond,
+        then_block,
+        else_block,
+    } = expr
+    else {
+        return Some(format_expr(expr));
+    };
+    let then_str = match bare_tail(&then_block.node)? {
+        tail @ Expr::If { .. } => format!("({})", format_expr(tail)),
+        tail => format_expr(tail),
+    };
+    let else_tail = bare_tail(&else_block.node)?;
+    let else_str = match else_tail {
+        Expr::If { .. } => format_if_flat(else_tail)?,
+        other => format!("{{ {} }}", format_expr(other)),
+    };
+    Some(format!(
+        "if {} {{ {} }} else {}",
+        format_expr(&cond.node),
+        then_str,
+        else_str
+    ))
+}
+
+/// The tail of a block that holds nothing else.
+fn bare_tail(block: &Block) -> Option<&Expr> {
+    match (&block.stmts[..], &block.tail_expr) {
+        ([], Some(tail)) => Some(&tail.node),
+        _ => None,
     }
 }
 
@@ -1,3 +1,4 @@
+use crate::ast::display::format_where_clause;
 use crate::ast::*;
 use crate::span::Spanned;
 
@@ -35,13 +36,29 @@ impl FormatCtx {
         self.output.push_str(": ");
         self.output.push_str(&format_type(&c.ty.node));
         self.output.push_str(" = ");
-        self.output
-            .push_str(&super::expr::format_expr(&c.value.node));
+        match &c.value.node {
+            Expr::Call { path, args, .. } if c.generate => {
+                self.output.push_str("generate!(");
+                self.output.push_str(&path.node.as_dotted());
+                for arg in args {
+                    self.output.push_str(", ");
+                    self.output.push_str(&super::expr::format_expr(&arg.node));
+                }
+                self.output.push(')');
+            }
+            value => self.output.push_str(&super::expr::format_expr(value)),
+        }
         self.output.push('\n');
     }
 
     fn emit_struct(&mut self, s: &StructDef, indent: &str) {
         self.emit_cfg_attr(&s.cfg, indent);
+        if !s.derives.is_empty() {
+            let names: Vec<&str> = s.derives.iter().map(|d| d.node.as_str()).collect();
+            let attr = format!("#[derive({})]\n", names.join(", "));
+            self.output.push_str(indent);
+            self.output.push_str(&attr);
+        }
         self.output.push_str(indent);
         if s.is_pub {
             self.output.push_str("pub ");
@@ -89,7 +106,11 @@ impl FormatCtx {
 
         if f.is_test {
             self.output.push_str(indent);
-            self.output.push_str("#[test]\n");
+            let attr = match f.test_max_cycles {
+                Some(n) => format!("#[test(max_cycles = {})]\n", n),
+                None => "#[test]\n".to_string(),
+            };
+            self.output.push_str(&attr);
         }
 
         if f.is_pure {
@@ -97,6 +118,16 @@ impl FormatCtx {
             self.output.push_str("#[pure]\n");
         }
 
+        if f.is_entry {
+            self.output.push_str(indent);
+            self.output.push_str("#[entry]\n");
+        }
+
+        if f.no_spill {
+            self.output.push_str(indent);
+            self.output.push_str("#[no_spill]\n");
+        }
+
         for req in &f.requires {
             self.output.push_str(indent);
             self.output.push_str("#[requires(");
@@ -109,12 +140,27 @@ impl FormatCtx {
             self.output.push_str(&ens.node);
             self.output.push_str(")]\n");
         }
+        if !f.allows.is_empty() {
+            let lints: Vec<&str> = f.allows.iter().map(|l| l.node.as_str()).collect();
+            self.output.push_str(indent);
+            self.output.push_str("#[allow(");
+            self.output.push_str(&lints.join(", "));
+            self.output.push_str(")]\n");
+        }
 
         self.output.push_str(indent);
 
         if let Some(attr) = &f.intrinsic {
             self.output.push_str("#[");
-            self.output.push_str(&attr.node);
+            match f.intrinsic_attr() {
+                Some(Intrinsic {
+                    name,
+                    tier: Some(tier),
+                }) => self
+                    .output
+                    .push_str(&format!("intrinsic(tier = {}, name = \"{}\")", tier, name)),
+                _ => self.output.push_str(&attr.node),
+            }
             self.output.push_str("]\n");
             self.output.push_str(indent);
         }
@@ -170,6 +216,7 @@ impl FormatCtx {
                 self.output.push_str(" -> ");
                 self.output.push_str(&format_type(&ret.node));
             }
+            self.output.push_str(&format_where_clause(f));
         }
 
         match &f.body {
@@ -200,6 +247,7 @@ impl FormatCtx {
             sig.push_str(" -> ");
             sig.push_str(&format_type(&ret.node));
         }
+        sig.push_str(&format_where_clause(f));
         sig
     }
 }
@@ -122,6 +122,14 @@ impl FormatCtx {
             self.output.push('\n');
         }
 
+        for m in &file.mods {
+            self.output.push('\n');
+            self.emit_leading_comments(m.span.start, "");
+            self.output.push_str("mod ");
+            self.output.push_str(&m.node);
+            self.output.push('\n');
+        }
+
         for decl in &file.declarations {
             self.output.push('\n');
             match decl {
@@ -157,7 +165,15 @@ impl FormatCtx {
             }
         }
 
+        let has_entries = file
+            .items
+            .iter()
+            .any(|item| matches!(&item.node, Item::Fn(f) if f.is_entry));
         for item in &file.items {
+            // With #[entry] functions, `main` is the generated dispatcher.
+            if has_entries && matches!(&item.node, Item::Fn(f) if f.name.node == "main") {
+                continue;
+            }
             self.output.push('\n');
             self.emit_item(item, "");
         }
@@ -173,15 +189,31 @@ impl FormatCtx {
         if let Some(tail) = &block.tail_expr {
             self.emit_leading_comments(tail.span.start, &indent);
             self.output.push_str(&indent);
-            self.emit_expr_wrapped(&tail.node, &indent);
+            // A tail `if` needs parentheses, or it reparses as a statement.
+            if let Expr::If { .. } = tail.node {
+                self.output.push('(');
+                self.emit_expr_wrapped(&tail.node, &indent);
+                self.output.push(')');
+            } else {
+                self.emit_expr_wrapped(&tail.node, &indent);
+            }
             self.output.push('\n');
         }
     }
 
     /// Format an expression, wrapping long function calls.
     pub(super) fn emit_expr_wrapped(&mut self, expr: &Expr, indent: &str) {
-        let flat = format_expr(expr);
         let current_line_len = self.current_line_len();
+        if let Expr::If { .. } = expr {
+            match expr::format_if_flat(expr) {
+                Some(flat) if current_line_len + flat.len() <= MAX_WIDTH => {
+                    self.output.push_str(&flat);
+                }
+                _ => self.emit_if_expr(expr, indent),
+            }
+            return;
+        }
+        let flat = format_expr(expr);
         if current_line_len + flat.len() <= MAX_WIDTH {
             self.output.push_str(&flat);
         } else if let Expr::Call { path, args, .. } = expr {
@@ -153,6 +153,14 @@ impl FormatCtx {
                         MatchPattern::Wildcard => {
                             self.output.push('_');
                         }
+                        MatchPattern::Some(binding) => {
+                            self.output.push_str("Some(");
+                            self.output.push_str(&binding.node);
+                            self.output.push(')');
+                        }
+                        MatchPattern::None => {
+                            self.output.push_str("None");
+                        }
                         MatchPattern::Struct { name, fields } => {
                             self.output.push_str(&name.node);
                             self.output.push_str(" { ");
@@ -320,6 +328,40 @@ impl FormatCtx {
             }
         }
     }
+
+    /// Emit an `if` expression with one branch per line block. The caller
+    /// owns the text before `if` and the line break after the closing brace.
+    pub(super) fn emit_if_expr(&mut self, expr: &Expr, indent: &str) {
+        if let Expr::If {
+            cond,
+            then_block,
+            else_block,
+        } = expr
+        {
+            self.output.push_str("if ");
+            self.output.push_str(&format_expr(&cond.node));
+            self.output.push_str(" {\n");
+            self.emit_block(&then_block.node, indent);
+            self.output.push_str(indent);
+            if let Some(inner_if) = as_else_if_expr(&else_block.node) {
+                self.output.push_str("} else ");
+                self.emit_if_expr(inner_if, indent);
+            } else {
+                self.output.push_str("} else {\n");
+                self.emit_block(&else_block.node, indent);
+                self.output.push_str(indent);
+                self.output.push('}');
+            }
+        }
+    }
+}
+
+/// Check if a block is a single `if` expression (for `else if` chains).
+fn as_else_if_expr(block: &Block) -> Option<&Expr> {
+    match (&block.stmts[..], &block.tail_expr) {
+        ([], Some(tail)) if matches!(tail.node, Expr::If { .. }) => Some(&tail.node),
+        _ => None,
+    }
 }
 
 /// Check if a block is a single `if` statement (for `else if` chains).
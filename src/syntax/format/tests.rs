@@ -41,6 +41,18 @@ fn test_const_formatting() {
     assert_eq!(fmt(src), src);
 }
 
+#[test]
+fn test_generate_const_formatting() {
+    let src = "program test\n\nconst T: [Field; 4] = generate!(math.sq, 1, K)\n\nfn main() {\n    pub_write(T[0])\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
+#[test]
+fn test_test_max_cycles_formatting() {
+    let src = "program test\n\n#[test(max_cycles = 1000)]\nfn check() {\n    assert(1 == 1)\n}\n\nfn main() {\n    pub_write(pub_read())\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
 #[test]
 fn test_struct_formatting() {
     let src = "program test\n\nstruct Point {\n    x: Field,\n    y: Field,\n}\n\nfn main() {\n    pub_write(pub_read())\n}\n";
@@ -79,6 +91,12 @@ fn test_tuple_destructure() {
     assert_eq!(fmt(src), src);
 }
 
+#[test]
+fn test_named_tuple_type() {
+    let src = "program test\n\nfn divmod(a: U32, b: U32) -> (q: U32, r: U32) {\n    a /% b\n}\n\nfn main() {\n    let res: (q: U32, r: U32) = divmod(as_u32(pub_read()), 3)\n    pub_write(as_field(res.q))\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
 #[test]
 fn test_if_else() {
     let src = "program test\n\nfn main() {\n    let x: Field = pub_read()\n    if x == 0 {\n        pub_write(0)\n    } else {\n        pub_write(1)\n    }\n}\n";
@@ -117,6 +135,12 @@ fn test_binary_precedence() {
     assert_eq!(fmt(src), src);
 }
 
+#[test]
+fn test_logical_operator_parentheses_kept_only_where_needed() {
+    let src = "program test\n\nfn main() {\n    let a: Bool = pub_read() == 0\n    let b: Bool = pub_read() == 1\n    let x: Bool = (a || b) && a == b || b\n    assert(x)\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
 #[test]
 fn test_array_init() {
     let src =
@@ -215,6 +239,12 @@ fn test_use_declarations() {
     assert_eq!(fmt(src), src);
 }
 
+#[test]
+fn test_mod_declarations() {
+    let src = "program token\n\nuse std.hash\n\nmod transfers\n\nfn main() {\n    pub_write(pub_read())\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
 // --- Types ---
 
 #[test]
@@ -245,6 +275,18 @@ fn test_intrinsic_function() {
     assert!(out.contains("pub fn tip5"), "function name preserved");
 }
 
+#[test]
+fn test_tiered_intrinsic_is_canonical() {
+    let src = "module std.hash\n\n#[intrinsic(name=sponge_init,tier=2)]\npub fn init()\n";
+    let out = fmt(src);
+    assert!(
+        out.contains("#[intrinsic(tier = 2, name = \"sponge_init\")]"),
+        "tier and name printed in canonical order: {}",
+        out
+    );
+    assert_eq!(fmt(&out), out, "idempotent");
+}
+
 // --- Line wrapping ---
 
 #[test]
@@ -437,3 +479,45 @@ fn test_match_struct_pattern_with_literal_formatting() {
         "should format literal field pattern"
     );
 }
+
+#[test]
+fn test_if_expression_flat() {
+    let src = "program test\n\nfn main() {\n    let p: Bool = pub_read() == 1\n    let fee: Field = if p { 10 } else { 2 }\n    pub_write(fee)\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
+#[test]
+fn test_if_expression_with_statements_is_multiline() {
+    let src = "program test\n\nfn main() {\n    let p: Bool = pub_read() == 1\n    let fee: Field = if p {\n        let base: Field = pub_read()\n        base + 10\n    } else {\n        2\n    }\n    pub_write(fee)\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
+#[test]
+fn test_tail_if_expression_keeps_parentheses() {
+    let src = "program test\n\nfn pick(x: Field) -> Field {\n    (if x == 0 { 7 } else { 9 })\n}\n\nfn main() {\n    pub_write(pick(pub_read()))\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
+#[test]
+fn test_where_clause_round_trips() {
+    let src = "module m\n\npub fn first<N>(arr: [Field; N]) -> Field where N <= 32, N > 0 {\n    arr[0]\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
+#[test]
+fn test_entry_functions_round_trip_without_generated_main() {
+    let src = "program token\n\n#[entry]\npub fn pay() {\n    pub_write(pub_read())\n}\n\n#[entry]\npub fn burn() {\n    assert(pub_read() == 0)\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
+#[test]
+fn test_no_spill_round_trips() {
+    let src = "module m\n\n#[pure]\n#[no_spill]\npub fn mix(a: Field, b: Field) -> Field {\n    a * b + a\n}\n";
+    assert_eq!(fmt(src), src);
+}
+
+#[test]
+fn test_derives_round_trip_without_generated_functions() {
+    let src = "module m\n\n#[derive(Hash, FromWitness)]\npub struct Note {\n    amount: Field,\n    owner: Digest,\n}\n";
+    assert_eq!(fmt(src), src);
+}
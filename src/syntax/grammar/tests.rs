@@ -13,11 +13,11 @@ fn grammar_json_roundtrip() {
 #[test]
 fn rule_count() {
     let grammar = trident_grammar();
-    // 59 rules in the existing grammar.json
+    // 67 rules in the existing grammar.json
     assert_eq!(
         grammar.rules.len(),
-        59,
-        "expected 59 grammar rules, got {}",
+        67,
+        "expected 67 grammar rules, got {}",
         grammar.rules.len()
     );
 }
@@ -16,7 +16,7 @@ fn rules() -> Vec<(&'static str, Node)> {
             "source_file",
             seq(vec![
                 sym("_file_header"),
-                repeat(sym("use_declaration")),
+                repeat(choice(vec![sym("use_declaration"), sym("mod_declaration")])),
                 repeat(sym("io_declaration")),
                 repeat(sym("_item")),
             ]),
@@ -39,6 +39,10 @@ fn rules() -> Vec<(&'static str, Node)> {
             "use_declaration",
             seq(vec![str_("use"), sym("module_path")]),
         ),
+        (
+            "mod_declaration",
+            seq(vec![str_("mod"), field("name", sym("identifier"))]),
+        ),
         (
             "module_path",
             prec_left(
@@ -121,7 +125,22 @@ fn rules() -> Vec<(&'static str, Node)> {
                 str_(":"),
                 field("type", sym("_type")),
                 str_("="),
-                field("value", sym("_expression")),
+                field(
+                    "value",
+                    choice(vec![sym("generate_expression"), sym("_expression")]),
+                ),
+            ]),
+        ),
+        (
+            "generate_expression",
+            seq(vec![
+                str_("generate"),
+                str_("!"),
+                str_("("),
+                field("function", sym("module_path")),
+                repeat(seq(vec![str_(","), sym("_expression")])),
+                optional(str_(",")),
+                str_(")"),
             ]),
         ),
         (
@@ -206,6 +225,9 @@ fn rules() -> Vec<(&'static str, Node)> {
                 sym("primitive_type"),
                 sym("array_type"),
                 sym("tuple_type"),
+                sym("named_tuple_type"),
+                sym("option_type"),
+                sym("bytes_type"),
                 sym("named_type"),
             ]),
         ),
@@ -239,6 +261,41 @@ fn rules() -> Vec<(&'static str, Node)> {
                 str_(")"),
             ]),
         ),
+        (
+            "named_tuple_type",
+            seq(vec![
+                str_("("),
+                comma_sep1("tuple_component"),
+                optional(str_(",")),
+                str_(")"),
+            ]),
+        ),
+        (
+            "tuple_component",
+            seq(vec![
+                field("name", sym("identifier")),
+                str_(":"),
+                field("type", sym("_type")),
+            ]),
+        ),
+        (
+            "option_type",
+            seq(vec![
+                str_("Option"),
+                str_("<"),
+                field("payload", sym("_type")),
+                str_(">"),
+            ]),
+        ),
+        (
+            "bytes_type",
+            seq(vec![
+                str_("Bytes"),
+                str_("<"),
+                field("size", sym("integer_literal")),
+                str_(">"),
+            ]),
+        ),
         ("named_type", sym("module_path")),
         // ---- Block ----
         (
@@ -360,9 +417,22 @@ fn rules() -> Vec<(&'static str, Node)> {
             choice(vec![
                 sym("integer_literal"),
                 sym("boolean_literal"),
+                sym("option_pattern"),
                 str_("_"),
             ]),
         ),
+        (
+            "option_pattern",
+            choice(vec![
+                seq(vec![
+                    str_("Some"),
+                    str_("("),
+                    field("binding", choice(vec![sym("identifier"), str_("_")])),
+                    str_(")"),
+                ]),
+                str_("None"),
+            ]),
+        ),
         (
             "asm_block",
             prec(
@@ -446,6 +516,7 @@ fn rules() -> Vec<(&'static str, Node)> {
                 sym("array_init_expression"),
                 sym("tuple_expression"),
                 sym("parenthesized_expression"),
+                sym("if_expression"),
             ]),
         ),
         ("integer_literal", pattern("[0-9]+")),
@@ -519,6 +590,22 @@ fn rules() -> Vec<(&'static str, Node)> {
                         field("right", sym("_expression")),
                     ]),
                 ),
+                prec_left(
+                    1,
+                    seq(vec![
+                        field("left", sym("_expression")),
+                        str_("&&"),
+                        field("right", sym("_expression")),
+                    ]),
+                ),
+                prec_left(
+                    0,
+                    seq(vec![
+                        field("left", sym("_expression")),
+                        str_("||"),
+                        field("right", sym("_expression")),
+                    ]),
+                ),
             ]),
         ),
         (
@@ -587,6 +674,16 @@ fn rules() -> Vec<(&'static str, Node)> {
             "parenthesized_expression",
             seq(vec![str_("("), sym("_expression"), str_(")")]),
         ),
+        (
+            "if_expression",
+            seq(vec![
+                str_("if"),
+                field("condition", sym("_expression")),
+                field("then", sym("block")),
+                str_("else"),
+                field("else", choice(vec![sym("if_expression"), sym("block")])),
+            ]),
+        ),
         // ---- Terminals ----
         ("identifier", pattern("[a-zA-Z_][a-zA-Z0-9_]*")),
         ("line_comment", token(seq(vec![str_("//"), pattern(".*")]))),
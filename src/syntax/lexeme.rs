@@ -54,14 +54,19 @@ pub(crate) enum Lexeme {
     Lt,           // <
     Gt,           // >
     Amp,          // &
+    AmpAmp,       // &&
+    PipePipe,     // ||
     Caret,        // ^
     SlashPercent, // /%
     Hash,         // #
+    Bang,         // !
     Underscore,   // _
 
     // Literals
     Integer(u64),
     Ident(String),
+    /// `"text"`, accepted only in attribute arguments.
+    Str(String),
 
     // Inline assembly
     AsmBlock {
@@ -160,12 +165,16 @@ impl Lexeme {
             Lexeme::Lt => "'<'",
             Lexeme::Gt => "'>'",
             Lexeme::Amp => "'&'",
+            Lexeme::AmpAmp => "'&&'",
+            Lexeme::PipePipe => "'||'",
             Lexeme::Caret => "'^'",
             Lexeme::SlashPercent => "'/%'",
             Lexeme::Hash => "'#'",
+            Lexeme::Bang => "'!'",
             Lexeme::Underscore => "'_'",
             Lexeme::Integer(_) => "integer literal",
             Lexeme::Ident(_) => "identifier",
+            Lexeme::Str(_) => "string literal",
             Lexeme::AsmBlock { .. } => "asm block",
             Lexeme::Eof => "end of file",
         }
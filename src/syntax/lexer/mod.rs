@@ -97,6 +97,11 @@ impl<'src> Lexer<'src> {
                 return self.scan_number();
             }
 
+            // String literals (attribute arguments)
+            if ch == b'"' {
+                return self.scan_string(start);
+            }
+
             // Symbols
             if let Some(tok) = self.scan_symbol(start) {
                 return tok;
@@ -336,6 +341,34 @@ impl<'src> Lexer<'src> {
         }
     }
 
+    /// Scan a `"..."` literal. No escapes; it ends at the next quote and
+    /// must close on the same line.
+    fn scan_string(&mut self, start: usize) -> Spanned<Lexeme> {
+        self.pos += 1; // skip opening quote
+        let text_start = self.pos;
+        while self.pos < self.source.len()
+            && self.source[self.pos] != b'"'
+            && self.source[self.pos] != b'\n'
+        {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.source[text_start..self.pos])
+            .unwrap_or_default()
+            .to_string();
+        if self.peek() == Some(b'"') {
+            self.pos += 1;
+        } else {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "unterminated string literal".to_string(),
+                    Span::new(self.file_id, start as u32, self.pos as u32),
+                )
+                .with_help("close the string with `\"` on the same line".to_string()),
+            );
+        }
+        self.make_token(Lexeme::Str(text), start, self.pos)
+    }
+
     fn scan_symbol(&mut self, start: usize) -> Option<Spanned<Lexeme>> {
         let ch = self.source[self.pos];
         self.pos += 1;
@@ -353,9 +386,17 @@ impl<'src> Lexer<'src> {
             b'+' => Lexeme::Plus,
             b'<' => Lexeme::Lt,
             b'>' => Lexeme::Gt,
-            b'&' => Lexeme::Amp,
+            b'&' => {
+                if self.peek() == Some(b'&') {
+                    self.pos += 1;
+                    Lexeme::AmpAmp
+                } else {
+                    Lexeme::Amp
+                }
+            }
             b'^' => Lexeme::Caret,
             b'#' => Lexeme::Hash,
+            b'!' => Lexeme::Bang,
             b'.' => {
                 if self.peek() == Some(b'.') {
                     self.pos += 1;
@@ -398,6 +439,21 @@ impl<'src> Lexer<'src> {
                     Lexeme::Star
                 }
             }
+            b'|' => {
+                if self.peek() == Some(b'|') {
+                    self.pos += 1;
+                    Lexeme::PipePipe
+                } else {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            "unexpected '|'; Trident has no bitwise or operator".to_string(),
+                            Span::new(self.file_id, start as u32, self.pos as u32),
+                        )
+                        .with_help("use `||` for logical or".to_string()),
+                    );
+                    return None;
+                }
+            }
             b'/' => {
                 if self.peek() == Some(b'%') {
                     self.pos += 1;
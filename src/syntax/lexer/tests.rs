@@ -46,7 +46,7 @@ fn test_types() {
 
 #[test]
 fn test_symbols() {
-    let tokens = lex("( ) { } [ ] , : ; . .. -> = == + * *. < & ^ /% #");
+    let tokens = lex("( ) { } [ ] , : ; . .. -> = == + * *. < & && || ^ /% #");
     assert_eq!(
         tokens,
         vec![
@@ -69,6 +69,8 @@ fn test_symbols() {
             Lexeme::StarDot,
             Lexeme::Lt,
             Lexeme::Amp,
+            Lexeme::AmpAmp,
+            Lexeme::PipePipe,
             Lexeme::Caret,
             Lexeme::SlashPercent,
             Lexeme::Hash,
@@ -355,3 +357,19 @@ fn test_error_unterminated_asm_block() {
         "unterminated asm error should have help text"
     );
 }
+
+#[test]
+fn test_string_literal() {
+    let tokens = lex("name = \"sponge_init\"");
+    assert_eq!(tokens[2], Lexeme::Str("sponge_init".to_string()));
+}
+
+#[test]
+fn test_error_unterminated_string() {
+    let (_tokens, diags) = lex_with_errors("name = \"sponge_init\n");
+    assert!(
+        diags[0].message.contains("unterminated string literal"),
+        "should report unterminated string, got: {}",
+        diags[0].message
+    );
+}
@@ -10,10 +10,16 @@ impl Parser {
     }
 
     fn parse_expr_bp(&mut self, min_bp: u8) -> Spanned<Expr> {
+        // Every operator and postfix node counts as a nesting level, so a
+        // long `a + b + ...` chain is bounded like nested parentheses.
+        let mut levels = 0;
+        if !self.enter_expr_level(&mut levels) {
+            return Spanned::new(Expr::Literal(Literal::Integer(0)), self.current_span());
+        }
         let mut lhs = self.parse_primary();
 
         // Apply postfix operators: .field and [index]
-        lhs = self.parse_postfix(lhs);
+        lhs = self.parse_postfix(lhs, &mut levels);
 
         loop {
             let op = match self.peek() {
@@ -23,13 +29,15 @@ impl Parser {
                 Lexeme::Star => BinOp::Mul,
                 Lexeme::StarDot => BinOp::XFieldMul,
                 Lexeme::Amp => BinOp::BitAnd,
+                Lexeme::AmpAmp => BinOp::And,
+                Lexeme::PipePipe => BinOp::Or,
                 Lexeme::Caret => BinOp::BitXor,
                 Lexeme::SlashPercent => BinOp::DivMod,
                 _ => break,
             };
 
             let (l_bp, r_bp) = op.binding_power();
-            if l_bp < min_bp {
+            if l_bp < min_bp || !self.enter_expr_level(&mut levels) {
                 break;
             }
 
@@ -46,27 +54,43 @@ impl Parser {
             );
         }
 
+        for _ in 0..levels {
+            self.exit_nesting();
+        }
         lhs
     }
 
+    /// Enter one expression or type nesting level, counting it in
+    /// `levels`. Past the limit, report it and skip to the end of input.
+    pub(super) fn enter_expr_level(&mut self, levels: &mut u32) -> bool {
+        if !self.enter_nesting() {
+            self.exit_nesting();
+            while !self.at(&Lexeme::Eof) {
+                self.advance();
+            }
+            return false;
+        }
+        *levels += 1;
+        true
+    }
+
     /// Parse postfix operations: .field, [index], .method() chains
-    fn parse_postfix(&mut self, mut expr: Spanned<Expr>) -> Spanned<Expr> {
+    fn parse_postfix(&mut self, mut expr: Spanned<Expr>, levels: &mut u32) -> Spanned<Expr> {
         loop {
-            if self.at(&Lexeme::LBracket) {
-                self.advance();
-                let index = self.parse_expr();
-                self.expect(&Lexeme::RBracket);
-                let span = expr.span.merge(self.prev_span());
-                expr = Spanned::new(
-                    Expr::Index {
-                        expr: Box::new(expr),
-                        index: Box::new(index),
-                    },
-                    span,
-                );
-            } else {
+            if !self.at(&Lexeme::LBracket) || !self.enter_expr_level(levels) {
                 break;
             }
+            self.advance();
+            let index = self.parse_expr();
+            self.expect(&Lexeme::RBracket);
+            let span = expr.span.merge(self.prev_span());
+            expr = Spanned::new(
+                Expr::Index {
+                    expr: Box::new(expr),
+                    index: Box::new(index),
+                },
+                span,
+            );
         }
         expr
     }
@@ -109,6 +133,7 @@ impl Parser {
                     first
                 }
             }
+            Lexeme::If => self.parse_if_expr(),
             Lexeme::LBracket => {
                 self.advance();
                 let mut elements = Vec::new();
@@ -193,6 +218,51 @@ impl Parser {
         }
     }
 
+    /// Parse `if cond { a } else { b }` in expression position. The else
+    /// branch is required; `else if` nests another if-expression.
+    fn parse_if_expr(&mut self) -> Spanned<Expr> {
+        let start = self.current_span();
+        self.expect(&Lexeme::If);
+        let cond = self.parse_expr();
+        let then_block = self.parse_block();
+        let else_block = if self.eat(&Lexeme::Else) {
+            if self.at(&Lexeme::If) {
+                let inner_if = self.parse_if_expr();
+                let span = inner_if.span;
+                Spanned::new(
+                    Block {
+                        stmts: Vec::new(),
+                        tail_expr: Some(Box::new(inner_if)),
+                    },
+                    span,
+                )
+            } else {
+                self.parse_block()
+            }
+        } else {
+            self.error_with_help(
+                "`if` used as a value needs an `else` branch",
+                "add `else { ... }` producing a value of the same type",
+            );
+            Spanned::new(
+                Block {
+                    stmts: Vec::new(),
+                    tail_expr: None,
+                },
+                self.current_span(),
+            )
+        };
+        let span = start.merge(self.prev_span());
+        Spanned::new(
+            Expr::If {
+                cond: Box::new(cond),
+                then_block: Box::new(then_block),
+                else_block: Box::new(else_block),
+            },
+            span,
+        )
+    }
+
     fn parse_call_args(&mut self) -> Vec<Spanned<Expr>> {
         let mut args = Vec::new();
         while !self.at(&Lexeme::RParen) && !self.at(&Lexeme::Eof) {
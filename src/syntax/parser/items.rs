@@ -1,6 +1,7 @@
 use crate::ast::*;
+use crate::diagnostic::Diagnostic;
 use crate::lexeme::Lexeme;
-use crate::span::Spanned;
+use crate::span::{Span, Spanned};
 
 use super::Parser;
 
@@ -9,14 +10,26 @@ impl Parser {
         self.expect(&Lexeme::Program);
         let name = self.expect_ident();
 
-        let uses = self.parse_uses();
+        let mut uses = self.parse_uses();
+        let mut mods = Vec::new();
+        while self.at_mod() {
+            let start = self.current_span();
+            self.advance(); // consume mod
+            let part = self.expect_ident();
+            mods.push(Spanned::new(part.node, start.merge(part.span)));
+            uses.extend(self.parse_uses());
+        }
         let declarations = self.parse_declarations();
-        let items = self.parse_items();
+        let mut items = self.parse_items();
+        if let Some(main) = self.entry_dispatcher(&items) {
+            items.push(main);
+        }
 
         File {
             kind: FileKind::Program,
             name,
             uses,
+            mods,
             declarations,
             items,
         }
@@ -36,12 +49,37 @@ impl Parser {
         let name = Spanned::new(name_str, name.span);
 
         let uses = self.parse_uses();
+        if self.at_mod() {
+            self.error_with_help(
+                "`mod` is only allowed in a program",
+                "declare the part in the program file; modules import each other with `use`",
+            );
+            self.advance();
+            self.advance();
+        }
         let items = self.parse_items();
+        for item in &items {
+            if let Item::Fn(f) = &item.node {
+                if f.is_entry {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            format!("#[entry] function `{}` outside a program", f.name.node),
+                            f.name.span,
+                        )
+                        .with_help(
+                            "entry points belong to the program file; modules export `pub fn`"
+                                .to_string(),
+                        ),
+                    );
+                }
+            }
+        }
 
         File {
             kind: FileKind::Module,
             name,
             uses,
+            mods: Vec::new(),
             declarations: Vec::new(),
             items,
         }
@@ -97,6 +135,16 @@ impl Parser {
         decls
     }
 
+    /// Check for `mod <name>`. `mod` is contextual, so it stays a valid
+    /// identifier everywhere else.
+    fn at_mod(&self) -> bool {
+        matches!(self.peek(), Lexeme::Ident(word) if word == "mod")
+            && matches!(
+                self.tokens.get(self.pos + 1).map(|t| &t.node),
+                Some(Lexeme::Ident(_))
+            )
+    }
+
     /// Check if `pub` is followed by `input` or `output` (declaration, not item).
     fn is_declaration_ahead(&self) -> bool {
         if self.pos + 1 >= self.tokens.len() {
@@ -137,13 +185,19 @@ impl Parser {
             let start = self.current_span();
 
             // Parse attributes: #[cfg(flag)], #[intrinsic(name)], #[test],
-            // #[requires(pred)], #[ensures(pred)]
+            // #[entry], #[requires(pred)], #[ensures(pred)], #[allow(lint, ...)],
+            // #[derive(Trait, ...)]
             let mut cfg_attr: Option<Spanned<String>> = None;
             let mut intrinsic_attr: Option<Spanned<String>> = None;
             let mut is_test = false;
+            let mut test_max_cycles = None;
             let mut is_pure = false;
+            let mut entry_attr: Option<Span> = None;
+            let mut no_spill_attr: Option<Span> = None;
             let mut requires_attrs: Vec<Spanned<String>> = Vec::new();
             let mut ensures_attrs: Vec<Spanned<String>> = Vec::new();
+            let mut allow_attrs: Vec<Spanned<String>> = Vec::new();
+            let mut derive_attrs: Vec<Spanned<String>> = Vec::new();
             while self.at(&Lexeme::Hash) {
                 let attr = self.parse_attribute();
                 if attr.node.starts_with("cfg(") {
@@ -151,6 +205,16 @@ impl Parser {
                     let flag = attr.node[4..attr.node.len() - 1].to_string();
                     cfg_attr = Some(Spanned::new(flag, attr.span));
                 } else if attr.node.starts_with("intrinsic(") {
+                    if let Err(msg) = crate::ast::Intrinsic::parse(&attr.node) {
+                        self.diagnostics.push(
+                            Diagnostic::error(format!("invalid #[intrinsic]: {}", msg), attr.span)
+                                .with_help(
+                                    "write `#[intrinsic(name)]` or \
+                                     `#[intrinsic(tier = 2, name = \"name\")]`"
+                                        .to_string(),
+                                ),
+                        );
+                    }
                     intrinsic_attr = Some(attr);
                 } else if attr.node.starts_with("requires(") {
                     let pred = attr.node[9..attr.node.len() - 1].to_string();
@@ -158,18 +222,71 @@ impl Parser {
                 } else if attr.node.starts_with("ensures(") {
                     let pred = attr.node[8..attr.node.len() - 1].to_string();
                     ensures_attrs.push(Spanned::new(pred, attr.span));
+                } else if attr.node.starts_with("allow(") {
+                    for lint in attr.node[6..attr.node.len() - 1].split(',') {
+                        allow_attrs.push(Spanned::new(lint.trim().to_string(), attr.span));
+                    }
+                } else if attr.node.starts_with("derive(") {
+                    for name in attr.node[7..attr.node.len() - 1].split(',') {
+                        let name = name.trim();
+                        if !crate::ast::derive::DERIVES.contains(&name) {
+                            self.diagnostics.push(
+                                Diagnostic::error(format!("unknown derive `{}`", name), attr.span)
+                                    .with_help(format!(
+                                        "the built-in derives are {}",
+                                        crate::ast::derive::DERIVES.join(", ")
+                                    )),
+                            );
+                        }
+                        derive_attrs.push(Spanned::new(name.to_string(), attr.span));
+                    }
                 } else if attr.node == "test" {
                     is_test = true;
+                } else if let Some(args) = attr
+                    .node
+                    .strip_prefix("test(")
+                    .and_then(|rest| rest.strip_suffix(')'))
+                {
+                    is_test = true;
+                    test_max_cycles = self.parse_test_args(args, attr.span);
                 } else if attr.node == "pure" {
                     is_pure = true;
+                } else if attr.node == "entry" {
+                    entry_attr = Some(attr.span);
+                } else if attr.node == "no_spill" {
+                    no_spill_attr = Some(attr.span);
                 } else {
                     self.error_at_current(
-                        "unknown attribute; expected cfg, intrinsic, test, pure, requires, or ensures",
+                        "unknown attribute; expected cfg, intrinsic, test, pure, entry, no_spill, requires, ensures, allow, or derive",
                     );
                 }
             }
 
             let is_pub = self.eat(&Lexeme::Pub);
+            if let Some(span) = entry_attr {
+                if !self.at(&Lexeme::Fn) {
+                    self.diagnostics.push(Diagnostic::error(
+                        "#[entry] is only allowed on functions".to_string(),
+                        span,
+                    ));
+                }
+            }
+            if let Some(span) = no_spill_attr {
+                if !self.at(&Lexeme::Fn) {
+                    self.diagnostics.push(Diagnostic::error(
+                        "#[no_spill] is only allowed on functions".to_string(),
+                        span,
+                    ));
+                }
+            }
+            if let Some(attr) = derive_attrs.first() {
+                if !self.at(&Lexeme::Struct) {
+                    self.diagnostics.push(Diagnostic::error(
+                        "#[derive] is only allowed on structs".to_string(),
+                        attr.span,
+                    ));
+                }
+            }
 
             if self.at(&Lexeme::Const) {
                 self.reject_fn_only_attrs(
@@ -178,6 +295,7 @@ impl Parser {
                     is_pure,
                     &requires_attrs,
                     &ensures_attrs,
+                    &allow_attrs,
                 );
                 let item = self.parse_const(is_pub, cfg_attr);
                 let span = start.merge(self.prev_span());
@@ -189,8 +307,10 @@ impl Parser {
                     is_pure,
                     &requires_attrs,
                     &ensures_attrs,
+                    &allow_attrs,
                 );
-                let item = self.parse_struct(is_pub, cfg_attr);
+                let mut item = self.parse_struct(is_pub, cfg_attr);
+                item.derives = derive_attrs;
                 let span = start.merge(self.prev_span());
                 items.push(Spanned::new(Item::Struct(item), span));
             } else if self.at(&Lexeme::Event) {
@@ -200,12 +320,13 @@ impl Parser {
                     is_pure,
                     &requires_attrs,
                     &ensures_attrs,
+                    &allow_attrs,
                 );
                 let item = self.parse_event(cfg_attr);
                 let span = start.merge(self.prev_span());
                 items.push(Spanned::new(Item::Event(item), span));
             } else if self.at(&Lexeme::Fn) || self.at(&Lexeme::Hash) {
-                let item = self.parse_fn_with_attr(
+                let mut item = self.parse_fn_with_attr(
                     is_pub,
                     cfg_attr,
                     intrinsic_attr,
@@ -213,7 +334,11 @@ impl Parser {
                     is_pure,
                     requires_attrs,
                     ensures_attrs,
+                    allow_attrs,
                 );
+                item.test_max_cycles = test_max_cycles;
+                item.is_entry = entry_attr.is_some();
+                item.no_spill = no_spill_attr.is_some();
                 let span = start.merge(self.prev_span());
                 items.push(Spanned::new(Item::Fn(item), span));
             } else {
@@ -227,6 +352,160 @@ impl Parser {
         items
     }
 
+    /// Parse the arguments of `#[test(max_cycles = N)]`.
+    fn parse_test_args(&mut self, args: &str, span: Span) -> Option<u64> {
+        let value = args
+            .split_once('=')
+            .filter(|(key, _)| key.trim() == "max_cycles")
+            .and_then(|(_, value)| value.trim().parse::<u64>().ok());
+        if value.is_none() {
+            self.diagnostics.push(
+                Diagnostic::error(format!("invalid #[test({})]", args), span)
+                    .with_help("the only test option is `#[test(max_cycles = N)]`".to_string()),
+            );
+        }
+        value
+    }
+
+    /// Generate `main` for a program built from `#[entry]` functions.
+    ///
+    /// The generated `main` reads a tag from public input and calls the
+    /// entry with that index, counting in declaration order; any other
+    /// tag fails. Entries read their own inputs, as `main` would.
+    fn entry_dispatcher(&mut self, items: &[Spanned<Item>]) -> Option<Spanned<Item>> {
+        let entries: Vec<&FnDef> = items
+            .iter()
+            .filter_map(|item| match &item.node {
+                Item::Fn(f) if f.is_entry => Some(f),
+                _ => None,
+            })
+            .collect();
+        let span = entries.first()?.name.span;
+
+        for item in items {
+            if let Item::Fn(f) = &item.node {
+                if f.name.node == "main" && !f.is_test {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            "`main` cannot be defined alongside #[entry] functions".to_string(),
+                            f.name.span,
+                        )
+                        .with_help(
+                            "`main` is generated to dispatch on the entry tag; \
+                             move its body into an #[entry] function"
+                                .to_string(),
+                        ),
+                    );
+                }
+            }
+        }
+        for f in &entries {
+            if !f.params.is_empty()
+                || f.return_ty.is_some()
+                || !f.type_params.is_empty()
+                || f.is_test
+            {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "#[entry] function `{}` must take no parameters and return nothing",
+                            f.name.node
+                        ),
+                        f.name.span,
+                    )
+                    .with_help(
+                        "read inputs with `pub_read` in the body, as `main` does".to_string(),
+                    ),
+                );
+            }
+            if f.cfg.is_some() {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "#[entry] function `{}` cannot be conditionally compiled",
+                            f.name.node
+                        ),
+                        f.name.span,
+                    )
+                    .with_help(
+                        "entry tags follow declaration order, so every profile needs the same entries"
+                            .to_string(),
+                    ),
+                );
+            }
+        }
+
+        let call = |name: &str, args: Vec<Spanned<Expr>>, at: Span| {
+            Spanned::new(
+                Expr::Call {
+                    path: Spanned::new(ModulePath::single(name.to_string()), at),
+                    generic_args: Vec::new(),
+                    args,
+                },
+                at,
+            )
+        };
+        let block = |call: Spanned<Expr>| {
+            Spanned::new(
+                Block {
+                    stmts: vec![Spanned::new(Stmt::Expr(call), span)],
+                    tail_expr: None,
+                },
+                span,
+            )
+        };
+        let mut arms: Vec<MatchArm> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, f)| MatchArm {
+                pattern: Spanned::new(MatchPattern::Literal(Literal::Integer(i as u64)), span),
+                body: block(call(&f.name.node, Vec::new(), f.name.span)),
+            })
+            .collect();
+        let unknown = Spanned::new(Expr::Literal(Literal::Bool(false)), span);
+        arms.push(MatchArm {
+            pattern: Spanned::new(MatchPattern::Wildcard, span),
+            body: block(call("assert", vec![unknown], span)),
+        });
+
+        let read = Stmt::Let {
+            mutable: false,
+            pattern: Pattern::Name(Spanned::new("tag".to_string(), span)),
+            ty: Some(Spanned::new(Type::Field, span)),
+            init: call("pub_read", Vec::new(), span),
+        };
+        let dispatch = Stmt::Match {
+            expr: Spanned::new(Expr::Var("tag".to_string()), span),
+            arms,
+        };
+        let main = FnDef {
+            is_pub: false,
+            cfg: None,
+            intrinsic: None,
+            is_test: false,
+            test_max_cycles: None,
+            is_pure: false,
+            is_entry: false,
+            no_spill: false,
+            requires: Vec::new(),
+            ensures: Vec::new(),
+            allows: vec![Spanned::new("constant_condition".to_string(), span)],
+            name: Spanned::new("main".to_string(), span),
+            type_params: Vec::new(),
+            params: Vec::new(),
+            return_ty: None,
+            where_clause: Vec::new(),
+            body: Some(Spanned::new(
+                Block {
+                    stmts: vec![Spanned::new(read, span), Spanned::new(dispatch, span)],
+                    tail_expr: None,
+                },
+                span,
+            )),
+        };
+        Some(Spanned::new(Item::Fn(main), span))
+    }
+
     fn reject_fn_only_attrs(
         &mut self,
         intrinsic: &Option<Spanned<String>>,
@@ -234,6 +513,7 @@ impl Parser {
         is_pure: bool,
         requires: &[Spanned<String>],
         ensures: &[Spanned<String>],
+        allows: &[Spanned<String>],
     ) {
         if intrinsic.is_some() {
             self.error_at_current("#[intrinsic] is only allowed on functions");
@@ -247,6 +527,9 @@ impl Parser {
         if !requires.is_empty() || !ensures.is_empty() {
             self.error_at_current("#[requires] and #[ensures] are only allowed on functions");
         }
+        if !allows.is_empty() {
+            self.error_at_current("#[allow] is only allowed on functions");
+        }
     }
 
     fn parse_const(&mut self, is_pub: bool, cfg: Option<Spanned<String>>) -> ConstDef {
@@ -255,14 +538,51 @@ impl Parser {
         self.expect(&Lexeme::Colon);
         let ty = self.parse_type();
         self.expect(&Lexeme::Eq);
-        let value = self.parse_expr();
+        let generate = matches!(self.peek(), Lexeme::Ident(s) if s == "generate")
+            && matches!(
+                self.tokens.get(self.pos + 1).map(|t| &t.node),
+                Some(Lexeme::Bang)
+            );
+        let value = if generate {
+            self.parse_generate()
+        } else {
+            self.parse_expr()
+        };
         ConstDef {
             is_pub,
             cfg,
             name,
             ty,
             value,
+            generate,
+        }
+    }
+
+    /// `generate!(f, args...)`, returned as the call `f(args...)`.
+    fn parse_generate(&mut self) -> Spanned<Expr> {
+        let start = self.current_span();
+        self.advance(); // generate
+        self.expect(&Lexeme::Bang);
+        self.expect(&Lexeme::LParen);
+        let path_start = self.current_span();
+        let path = self.parse_module_path();
+        let path_span = path_start.merge(self.prev_span());
+        let mut args = Vec::new();
+        while self.eat(&Lexeme::Comma) {
+            if self.at(&Lexeme::RParen) {
+                break;
+            }
+            args.push(self.parse_expr());
         }
+        self.expect(&Lexeme::RParen);
+        Spanned::new(
+            Expr::Call {
+                path: Spanned::new(path, path_span),
+                generic_args: Vec::new(),
+                args,
+            },
+            start.merge(self.prev_span()),
+        )
     }
 
     fn parse_struct(&mut self, is_pub: bool, cfg: Option<Spanned<String>>) -> StructDef {
@@ -290,9 +610,11 @@ impl Parser {
             cfg,
             name,
             fields,
+            derives: Vec::new(),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn parse_fn_with_attr(
         &mut self,
         is_pub: bool,
@@ -302,6 +624,7 @@ impl Parser {
         is_pure: bool,
         requires: Vec<Spanned<String>>,
         ensures: Vec<Spanned<String>>,
+        allows: Vec<Spanned<String>>,
     ) -> FnDef {
         self.expect(&Lexeme::Fn);
         let name = self.expect_ident();
@@ -319,6 +642,8 @@ impl Parser {
             None
         };
 
+        let where_clause = self.parse_where_clause();
+
         let body = if self.at(&Lexeme::LBrace) {
             Some(self.parse_block())
         } else {
@@ -330,13 +655,18 @@ impl Parser {
             cfg,
             intrinsic,
             is_test,
+            test_max_cycles: None,
             is_pure,
+            is_entry: false,
+            no_spill: false,
             requires,
             ensures,
+            allows,
             name,
             type_params,
             params,
             return_ty,
+            where_clause,
             body,
         }
     }
@@ -357,6 +687,57 @@ impl Parser {
         params
     }
 
+    /// Parse `where N <= 32, M + N == 8` (if present). `where` is contextual:
+    /// after the signature only `{` or the next item can follow.
+    fn parse_where_clause(&mut self) -> Vec<Spanned<SizeBound>> {
+        if !matches!(self.peek(), Lexeme::Ident(w) if w == "where") {
+            return Vec::new();
+        }
+        self.advance();
+        let mut bounds = Vec::new();
+        loop {
+            let start = self.current_span();
+            let lhs = self.parse_array_size_expr();
+            let op = self.parse_size_cmp();
+            let rhs = self.parse_array_size_expr();
+            let span = start.merge(self.prev_span());
+            bounds.push(Spanned::new(SizeBound { lhs, op, rhs }, span));
+            if !self.eat(&Lexeme::Comma) || self.at(&Lexeme::LBrace) {
+                break;
+            }
+        }
+        bounds
+    }
+
+    /// Parse a size comparison. `<=` and `>=` lex as two adjacent tokens.
+    fn parse_size_cmp(&mut self) -> SizeCmp {
+        let op = match self.peek() {
+            Lexeme::EqEq => SizeCmp::Eq,
+            Lexeme::Lt => SizeCmp::Lt,
+            Lexeme::Gt => SizeCmp::Gt,
+            _ => {
+                self.error_with_help(
+                    &format!(
+                        "expected size comparison, found {}",
+                        self.peek().description()
+                    ),
+                    "bounds are written as `N <= 32`, `N > 0`, or `M + N == 8`",
+                );
+                return SizeCmp::Eq;
+            }
+        };
+        let op_end = self.advance().span.end;
+        if op == SizeCmp::Eq || !self.at(&Lexeme::Eq) || self.current_span().start != op_end {
+            return op;
+        }
+        self.advance();
+        if op == SizeCmp::Lt {
+            SizeCmp::Le
+        } else {
+            SizeCmp::Ge
+        }
+    }
+
     fn parse_attribute(&mut self) -> Spanned<String> {
         let start = self.current_span();
         self.expect(&Lexeme::Hash);
@@ -400,6 +781,7 @@ impl Parser {
         match self.peek() {
             Lexeme::Ident(s) => s.clone(),
             Lexeme::Integer(n) => n.to_string(),
+            Lexeme::Str(s) => format!("\"{}\"", s),
             Lexeme::Plus => "+".to_string(),
             Lexeme::Star => "*".to_string(),
             Lexeme::Eq => "=".to_string(),
@@ -407,6 +789,8 @@ impl Parser {
             Lexeme::Lt => "<".to_string(),
             Lexeme::Gt => ">".to_string(),
             Lexeme::Amp => "&".to_string(),
+            Lexeme::AmpAmp => "&&".to_string(),
+            Lexeme::PipePipe => "||".to_string(),
             Lexeme::Caret => "^".to_string(),
             Lexeme::Dot => ".".to_string(),
             Lexeme::Comma => ",".to_string(),
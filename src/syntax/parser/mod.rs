@@ -76,6 +76,16 @@ impl Parser {
         Ok(file)
     }
 
+    /// Parse a token run holding only top-level items (no file header).
+    /// Used by the LSP to re-parse a single edited item in place.
+    pub(crate) fn parse_items_only(mut self) -> Result<Vec<Spanned<Item>>, Vec<Diagnostic>> {
+        let items = self.parse_items();
+        if !self.diagnostics.is_empty() {
+            return Err(self.diagnostics);
+        }
+        Ok(items)
+    }
+
     fn enter_nesting(&mut self) -> bool {
         self.depth += 1;
         if self.depth > MAX_NESTING_DEPTH {
@@ -276,14 +276,21 @@ impl Parser {
             } else if self.at(&Lexeme::False) {
                 self.advance();
                 MatchPattern::Literal(Literal::Bool(false))
+            } else if matches!(self.peek(), Lexeme::Ident(name) if name == "None") {
+                self.advance();
+                MatchPattern::None
+            } else if matches!(self.peek(), Lexeme::Ident(name) if name == "Some")
+                && matches!(self.tokens[self.pos + 1].node, Lexeme::LParen)
+            {
+                self.parse_some_match_pattern()
             } else if matches!(self.peek(), Lexeme::Ident(_))
                 && matches!(self.tokens[self.pos + 1].node, Lexeme::LBrace)
             {
                 self.parse_struct_match_pattern()
             } else {
                 self.error_with_help(
-                    "expected match pattern (integer, true, false, StructName { ... }, or _)",
-                    "match arms use literal patterns like `0 =>`, `true =>`, struct patterns like `Point { x, y } =>`, or wildcard `_ =>`",
+                    "expected match pattern (integer, true, false, StructName { ... }, Some(x), None, or _)",
+                    "match arms use literal patterns like `0 =>`, `true =>`, struct patterns like `Point { x, y } =>`, option patterns like `Some(x) =>` and `None =>`, or wildcard `_ =>`",
                 );
                 self.advance();
                 MatchPattern::Wildcard
@@ -307,6 +314,21 @@ impl Parser {
         Spanned::new(Stmt::Match { expr, arms }, span)
     }
 
+    /// Parse an option pattern: `Some(x)` or `Some(_)`.
+    fn parse_some_match_pattern(&mut self) -> MatchPattern {
+        self.advance();
+        self.expect(&Lexeme::LParen);
+        let binding = if self.at(&Lexeme::Underscore) {
+            let span = self.current_span();
+            self.advance();
+            Spanned::new("_".to_string(), span)
+        } else {
+            self.expect_ident()
+        };
+        self.expect(&Lexeme::RParen);
+        MatchPattern::Some(binding)
+    }
+
     /// Parse a struct destructuring pattern: `Point { x, y: 0, z: _ }`.
     fn parse_struct_match_pattern(&mut self) -> MatchPattern {
         let name = self.expect_ident();
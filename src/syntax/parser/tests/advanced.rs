@@ -1,6 +1,6 @@
-use crate::syntax::parser::Parser;
 use crate::ast::*;
 use crate::lexer::Lexer;
+use crate::syntax::parser::Parser;
 
 use super::parse;
 
@@ -73,6 +73,29 @@ fn test_match_struct_pattern_with_wildcard_field() {
     }
 }
 
+#[test]
+fn test_option_type_and_patterns() {
+    let file = parse(
+        "program test\nfn main() {\n    let o: Option<Option<Field>> = None\n    match o {\n        Some(_) => { pub_write(1) }\n        None => { pub_write(0) }\n    }\n}",
+    );
+    if let Item::Fn(f) = &file.items[0].node {
+        let block = f.body.as_ref().unwrap();
+        if let Stmt::Let { ty, init, .. } = &block.node.stmts[0].node {
+            let inner = Type::Option(Box::new(Type::Field));
+            assert_eq!(ty.as_ref().unwrap().node, Type::Option(Box::new(inner)));
+            assert!(init.node.is_none());
+        } else {
+            panic!("expected let statement");
+        }
+        if let Stmt::Match { arms, .. } = &block.node.stmts[1].node {
+            assert!(matches!(&arms[0].pattern.node, MatchPattern::Some(b) if b.node == "_"));
+            assert!(matches!(arms[1].pattern.node, MatchPattern::None));
+        } else {
+            panic!("expected match statement");
+        }
+    }
+}
+
 // --- #[test] attribute parsing ---
 
 #[test]
@@ -127,6 +150,27 @@ fn test_no_arg_attribute_format() {
     }
 }
 
+#[test]
+fn test_test_attribute_max_cycles() {
+    let file = parse("program test\n#[test(max_cycles = 500)]\nfn capped() {}\nfn main() {}");
+    if let Item::Fn(f) = &file.items[0].node {
+        assert!(f.is_test);
+        assert_eq!(f.test_max_cycles, Some(500));
+    } else {
+        panic!("expected test function");
+    }
+}
+
+#[test]
+fn test_test_attribute_rejects_unknown_option() {
+    let diags = parse_err("program test\n#[test(timeout = 5)]\nfn t() {}\nfn main() {}");
+    assert!(
+        diags[0].message.contains("invalid #[test(timeout = 5)]"),
+        "got: {}",
+        diags[0].message
+    );
+}
+
 // --- Error path tests ---
 
 fn parse_err(source: &str) -> Vec<crate::diagnostic::Diagnostic> {
@@ -184,6 +228,21 @@ fn test_error_unexpected_token_in_expr() {
     );
 }
 
+#[test]
+fn test_error_intrinsic_tier_out_of_range() {
+    let diags = parse_err("module std.test\n#[intrinsic(tier = 5, name = \"x\")]\npub fn x()");
+    assert!(!diags.is_empty(), "should error on tier 5");
+    assert!(
+        diags[0].message.contains("tier must be 0 to 3"),
+        "should name the tier range, got: {}",
+        diags[0].message
+    );
+    assert!(
+        diags[0].help.is_some(),
+        "intrinsic error should have help text"
+    );
+}
+
 #[test]
 fn test_error_missing_fn_body() {
     let diags = parse_err("program test\nfn main() {\n    let x: Field = 1");
@@ -347,3 +406,22 @@ fn test_parse_generic_call_size_expr() {
         other => panic!("expected Call, got {:?}", other),
     }
 }
+
+#[test]
+fn test_parse_where_clause_bounds() {
+    let file = parse(
+        "program test\nfn f<M, N>(a: [Field; M]) -> Field where N <= 32, M + N == 8, N >= 1 { a[0] }",
+    );
+    let func = match &file.items[0].node {
+        Item::Fn(f) => f,
+        _ => panic!("expected fn"),
+    };
+    let bounds: Vec<String> = func
+        .where_clause
+        .iter()
+        .map(|b| b.node.to_string())
+        .collect();
+    assert_eq!(bounds, vec!["N <= 32", "M + N == 8", "N >= 1"]);
+    assert_eq!(func.where_clause[1].node.op, SizeCmp::Eq);
+    assert!(func.body.is_some());
+}
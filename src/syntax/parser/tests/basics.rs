@@ -1,4 +1,6 @@
 use crate::ast::*;
+use crate::lexer::Lexer;
+use crate::syntax::parser::Parser;
 
 use super::parse;
 
@@ -59,6 +61,24 @@ fn test_binary_expr() {
     }
 }
 
+#[test]
+fn test_logical_operators_bind_looser_than_comparison() {
+    // a == 0 || b == 0 && c  parses as  (a == 0) || ((b == 0) && c)
+    let file = parse("program test\n\nfn main() {\n    let x: Bool = a == 0 || b == 0 && c\n}");
+    if let Item::Fn(f) = &file.items[0].node {
+        let block = f.body.as_ref().unwrap();
+        let Stmt::Let { init, .. } = &block.node.stmts[0].node else {
+            panic!("expected let");
+        };
+        let Expr::BinOp { op, lhs, rhs } = &init.node else {
+            panic!("expected binary op");
+        };
+        assert_eq!(*op, BinOp::Or);
+        assert!(matches!(lhs.node, Expr::BinOp { op: BinOp::Eq, .. }));
+        assert!(matches!(rhs.node, Expr::BinOp { op: BinOp::And, .. }));
+    }
+}
+
 #[test]
 fn test_module() {
     let file = parse("module merkle\n\npub fn verify(root: Digest) {\n}");
@@ -69,6 +89,24 @@ fn test_module() {
     }
 }
 
+#[test]
+fn test_program_parts() {
+    let file = parse(
+        "program token\n\nuse std.io.io\nmod transfers\nmod supply\n\nfn main() {\n    let mod: Field = 1\n}",
+    );
+    let mods: Vec<&str> = file.mods.iter().map(|m| m.node.as_str()).collect();
+    assert_eq!(mods, vec!["transfers", "supply"]);
+    assert_eq!(file.uses.len(), 1);
+    assert_eq!(file.items.len(), 1);
+}
+
+#[test]
+fn test_mod_in_module_rejected() {
+    let (tokens, _, _) = Lexer::new("module merkle\nmod helpers\n", 0).tokenize();
+    let result = Parser::new(tokens).parse_file();
+    assert!(result.is_err(), "`mod` belongs in program files only");
+}
+
 #[test]
 fn test_program_declarations() {
     let file = parse("program test\n\npub input: [Field; 3]\npub output: Field\nsec input: [Field; 5]\n\nfn main() {\n}");
@@ -92,6 +130,23 @@ fn test_sec_ram_declaration() {
     }
 }
 
+#[test]
+fn test_named_tuple_return_type() {
+    let file =
+        parse("program test\nfn divmod(a: U32, b: U32) -> (q: U32, r: U32) {\n    a /% b\n}");
+    if let Item::Fn(f) = &file.items[0].node {
+        assert_eq!(
+            f.return_ty.as_ref().unwrap().node,
+            Type::NamedTuple(vec![
+                ("q".to_string(), Type::U32),
+                ("r".to_string(), Type::U32)
+            ])
+        );
+    } else {
+        panic!("expected function");
+    }
+}
+
 #[test]
 fn test_tuple_destructure_let() {
     let file = parse(
@@ -264,6 +319,19 @@ fn test_cfg_with_intrinsic() {
     }
 }
 
+#[test]
+fn test_tiered_intrinsic() {
+    let file =
+        parse("module std.test\n#[intrinsic(tier = 2, name = \"sponge_init\")]\npub fn init()");
+    if let Item::Fn(f) = &file.items[0].node {
+        let intrinsic = f.intrinsic_attr().unwrap();
+        assert_eq!(intrinsic.name, "sponge_init");
+        assert_eq!(intrinsic.tier, Some(2));
+    } else {
+        panic!("expected fn");
+    }
+}
+
 #[test]
 fn test_no_cfg() {
     let file = parse("program test\nfn main() {}");
@@ -334,3 +402,72 @@ fn test_match_wildcard_only() {
     }
 }
 
+// --- if expressions ---
+
+#[test]
+fn test_if_expression_in_let() {
+    let file = parse("program test\nfn main() {\n    let p: Bool = pub_read() == 1\n    let fee: Field = if p { 10 } else if pub_read() == 2 { 5 } else { 2 }\n}");
+    if let Item::Fn(f) = &file.items[0].node {
+        let block = f.body.as_ref().unwrap();
+        let Stmt::Let { init, .. } = &block.node.stmts[1].node else {
+            panic!("expected let");
+        };
+        let Expr::If { else_block, .. } = &init.node else {
+            panic!("expected if expression");
+        };
+        let nested = else_block.node.tail_expr.as_ref().unwrap();
+        assert!(matches!(nested.node, Expr::If { .. }));
+    }
+}
+
+#[test]
+fn test_if_at_statement_start_stays_a_statement() {
+    let file = parse("program test\nfn main() {\n    if pub_read() == 0 {\n        pub_write(0)\n    } else {\n        pub_write(1)\n    }\n}");
+    if let Item::Fn(f) = &file.items[0].node {
+        let block = f.body.as_ref().unwrap();
+        assert!(block.node.tail_expr.is_none());
+        assert!(matches!(block.node.stmts[0].node, Stmt::If { .. }));
+    }
+}
+
+#[test]
+fn test_if_expression_without_else_is_rejected() {
+    let source = "program test\nfn main() {\n    let x: Field = if pub_read() == 0 { 1 }\n}";
+    let (tokens, _, _) = Lexer::new(source, 0).tokenize();
+    let diags = Parser::new(tokens).parse_file().unwrap_err();
+    assert!(diags[0].message.contains("needs an `else` branch"));
+}
+
+#[test]
+fn test_entry_functions_generate_dispatching_main() {
+    let file = parse("program token\n#[entry]\npub fn pay() {\n}\n#[entry]\npub fn mint() {\n}");
+    assert_eq!(file.items.len(), 3);
+    let Item::Fn(main) = &file.items[2].node else {
+        panic!("expected generated main");
+    };
+    assert_eq!(main.name.node, "main");
+    let body = &main.body.as_ref().unwrap().node;
+    let Stmt::Match { arms, .. } = &body.stmts[1].node else {
+        panic!("expected tag match");
+    };
+    assert_eq!(arms.len(), 3, "one arm per entry plus the unknown-tag arm");
+    assert!(matches!(
+        arms[1].pattern.node,
+        MatchPattern::Literal(Literal::Integer(1))
+    ));
+    assert!(matches!(arms[2].pattern.node, MatchPattern::Wildcard));
+}
+
+#[test]
+fn test_entry_rejects_parameters_and_hand_written_main() {
+    let source = "program token\n#[entry]\npub fn pay(x: Field) {\n}\nfn main() {\n}";
+    let (tokens, _, _) = Lexer::new(source, 0).tokenize();
+    let diags = Parser::new(tokens).parse_file().unwrap_err();
+    let messages: Vec<&str> = diags.iter().map(|d| d.message.as_str()).collect();
+    assert!(messages
+        .iter()
+        .any(|m| m.contains("cannot be defined alongside")));
+    assert!(messages
+        .iter()
+        .any(|m| m.contains("must take no parameters")));
+}
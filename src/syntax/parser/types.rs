@@ -7,6 +7,10 @@ use super::Parser;
 impl Parser {
     pub(super) fn parse_type(&mut self) -> Spanned<Type> {
         let start = self.current_span();
+        let mut levels = 0;
+        if !self.enter_expr_level(&mut levels) {
+            return Spanned::new(Type::Field, start);
+        }
         let ty = match self.peek() {
             Lexeme::FieldTy => {
                 self.advance();
@@ -38,12 +42,47 @@ impl Parser {
             }
             Lexeme::LParen => {
                 self.advance();
-                let mut types = vec![self.parse_type().node];
-                while self.eat(&Lexeme::Comma) {
-                    types.push(self.parse_type().node);
-                }
+                let ty = if self.at_named_component() {
+                    let mut components = vec![self.parse_named_component()];
+                    while self.eat(&Lexeme::Comma) {
+                        components.push(self.parse_named_component());
+                    }
+                    Type::NamedTuple(components)
+                } else {
+                    let mut types = vec![self.parse_type().node];
+                    while self.eat(&Lexeme::Comma) {
+                        types.push(self.parse_type().node);
+                    }
+                    Type::Tuple(types)
+                };
                 self.expect(&Lexeme::RParen);
-                Type::Tuple(types)
+                ty
+            }
+            Lexeme::Ident(name)
+                if name == "Option"
+                    && matches!(
+                        self.tokens.get(self.pos + 1).map(|t| &t.node),
+                        Some(Lexeme::Lt)
+                    ) =>
+            {
+                self.advance();
+                self.advance();
+                let inner = self.parse_type();
+                self.expect(&Lexeme::Gt);
+                Type::Option(Box::new(inner.node))
+            }
+            Lexeme::Ident(name)
+                if name == "Bytes"
+                    && matches!(
+                        self.tokens.get(self.pos + 1).map(|t| &t.node),
+                        Some(Lexeme::Lt)
+                    ) =>
+            {
+                self.advance();
+                self.advance();
+                let size = self.parse_array_size_expr();
+                self.expect(&Lexeme::Gt);
+                Type::Bytes(size)
             }
             Lexeme::Ident(_) => {
                 let path = self.parse_module_path();
@@ -52,36 +91,66 @@ impl Parser {
             _ => {
                 self.error_with_help(
                     "expected type",
-                    "valid types are: Field, XField, Bool, U32, Digest, [T; N], (T, U), or a struct name",
+                    "valid types are: Field, XField, Bool, U32, Digest, [T; N], (T, U), (a: T, b: U), Option<T>, or a struct name",
                 );
                 Type::Field // fallback
             }
         };
+        self.exit_nesting();
         let span = start.merge(self.prev_span());
         Spanned::new(ty, span)
     }
 
+    /// Whether the next tokens start a named tuple component (`name:`).
+    fn at_named_component(&self) -> bool {
+        matches!(self.peek(), Lexeme::Ident(_))
+            && matches!(
+                self.tokens.get(self.pos + 1).map(|t| &t.node),
+                Some(Lexeme::Colon)
+            )
+    }
+
+    fn parse_named_component(&mut self) -> (String, Type) {
+        let name = self.expect_ident();
+        self.expect(&Lexeme::Colon);
+        (name.node, self.parse_type().node)
+    }
+
     // --- Array size expression parsing (compile-time arithmetic) ---
 
     /// Parse a compile-time size expression: `N`, `3`, `M + N`, `N * 2`, `M + N * 2`.
     /// Precedence: `*` binds tighter than `+`.
     pub(super) fn parse_array_size_expr(&mut self) -> ArraySize {
+        let mut levels = 0;
+        if !self.enter_expr_level(&mut levels) {
+            return ArraySize::Literal(0);
+        }
         let mut left = self.parse_array_size_mul();
-        while self.at(&Lexeme::Plus) {
+        while self.at(&Lexeme::Plus) && self.enter_expr_level(&mut levels) {
             self.advance();
             let right = self.parse_array_size_mul();
             left = ArraySize::Add(Box::new(left), Box::new(right));
         }
+        for _ in 0..levels {
+            self.exit_nesting();
+        }
         left
     }
 
     fn parse_array_size_mul(&mut self) -> ArraySize {
+        let mut levels = 0;
+        if !self.enter_expr_level(&mut levels) {
+            return ArraySize::Literal(0);
+        }
         let mut left = self.parse_array_size_atom();
-        while self.at(&Lexeme::Star) {
+        while self.at(&Lexeme::Star) && self.enter_expr_level(&mut levels) {
             self.advance();
             let right = self.parse_array_size_atom();
             left = ArraySize::Mul(Box::new(left), Box::new(right));
         }
+        for _ in 0..levels {
+            self.exit_nesting();
+        }
         left
     }
 
@@ -1,190 +1,101 @@
-//! Static analysis: recursion detection, call graph collection, used-module tracking.
+//! Static analysis: recursion detection and used-module tracking.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
+use crate::ast::navigate::calls_in_block;
 use crate::ast::*;
+use crate::diagnostic::Diagnostic;
+use crate::span::Span;
 
 use super::TypeChecker;
 
 impl TypeChecker {
-    /// Build a call graph from the file's functions and report any cycles.
+    /// Build a call graph from the file's active functions and report the
+    /// shortest call cycle through each function not already in a
+    /// reported cycle, at the call that closes it.
     pub(super) fn detect_recursion(&mut self, file: &File) {
-        // Build adjacency list: fn_name -> set of called fn_names
-        let mut call_graph: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        // fn_name -> calls in source order, with the span of each call
+        let mut call_graph: BTreeMap<String, Vec<(String, Span)>> = BTreeMap::new();
+        let mut fn_names = Vec::new();
 
         for item in &file.items {
+            if !self.is_item_cfg_active(&item.node) {
+                continue;
+            }
             if let Item::Fn(func) = &item.node {
                 if let Some(body) = &func.body {
-                    let mut callees = Vec::new();
-                    Self::collect_calls_block(&body.node, &mut callees);
+                    let callees = calls_in_block(&body.node)
+                        .into_iter()
+                        .filter_map(|call| match &call.node {
+                            // Full dotted path, so cross-module calls don't
+                            // collide with local functions of the same name.
+                            Expr::Call { path, .. } => Some((path.node.as_dotted(), call.span)),
+                            _ => None,
+                        })
+                        .collect();
                     call_graph.insert(func.name.node.clone(), callees);
+                    fn_names.push(func.name.node.clone());
                 }
             }
         }
 
-        // DFS cycle detection
-        let fn_names: Vec<String> = call_graph.keys().cloned().collect();
-        let mut visited = BTreeMap::new(); // 0=unvisited, 1=in-stack, 2=done
-
-        for name in &fn_names {
-            visited.insert(name.clone(), 0u8);
-        }
-
+        let mut reported: BTreeSet<String> = BTreeSet::new();
         for name in &fn_names {
-            if visited[name] == 0 {
-                let mut path = Vec::new();
-                if self.dfs_cycle(name, &call_graph, &mut visited, &mut path) {
-                    // Find the span for the function that starts the cycle
-                    let cycle_fn = &path[0];
-                    let span = file
-                        .items
-                        .iter()
-                        .find_map(|item| {
-                            if let Item::Fn(func) = &item.node {
-                                if func.name.node == *cycle_fn {
-                                    return Some(func.name.span);
-                                }
-                            }
-                            None
-                        })
-                        .unwrap_or(file.name.span);
-                    self.error_with_help(
-                        format!("recursive call cycle detected: {}", path.join(" -> ")),
-                        span,
-                        "stack-machine targets do not support recursion; use loops (`for`) or iterative algorithms instead".to_string(),
-                    );
-                }
-            }
-        }
-    }
-
-    /// Iterative DFS cycle detection. Returns true if a cycle is found,
-    /// with `path` containing the cycle.
-    fn dfs_cycle(
-        &self,
-        start: &str,
-        graph: &BTreeMap<String, Vec<String>>,
-        visited: &mut BTreeMap<String, u8>,
-        path: &mut Vec<String>,
-    ) -> bool {
-        // Stack frames: (node, callee_index)
-        let mut stack: Vec<(String, usize)> = Vec::new();
-        visited.insert(start.to_string(), 1);
-        path.push(start.to_string());
-        stack.push((start.to_string(), 0));
-
-        while let Some((node, idx)) = stack.last_mut() {
-            let callees = graph.get(node.as_str());
-            let len = callees.map_or(0, |c| c.len());
-
-            if *idx >= len {
-                // All callees explored — backtrack
-                visited.insert(node.clone(), 2);
-                path.pop();
-                stack.pop();
+            if reported.contains(name) {
                 continue;
             }
-
-            let callee = callees.expect("len > 0 implies Some")[*idx].clone();
-            *idx += 1;
-
-            let state = visited.get(&callee).copied().unwrap_or(2);
-            if state == 1 {
-                // Back-edge: cycle found
-                path.push(callee);
-                return true;
-            }
-            if state == 0 {
-                visited.insert(callee.clone(), 1);
-                path.push(callee.clone());
-                stack.push((callee, 0));
-            }
-        }
-
-        false
-    }
-
-    /// Collect all function call names from a block.
-    pub(super) fn collect_calls_block(block: &Block, calls: &mut Vec<String>) {
-        for stmt in &block.stmts {
-            Self::collect_calls_stmt(&stmt.node, calls);
-        }
-        if let Some(tail) = &block.tail_expr {
-            Self::collect_calls_expr(&tail.node, calls);
-        }
-    }
-
-    fn collect_calls_stmt(stmt: &Stmt, calls: &mut Vec<String>) {
-        match stmt {
-            Stmt::Let { init, .. } => Self::collect_calls_expr(&init.node, calls),
-            Stmt::Assign { value, .. } => Self::collect_calls_expr(&value.node, calls),
-            Stmt::If {
-                cond,
-                then_block,
-                else_block,
-            } => {
-                Self::collect_calls_expr(&cond.node, calls);
-                Self::collect_calls_block(&then_block.node, calls);
-                if let Some(eb) = else_block {
-                    Self::collect_calls_block(&eb.node, calls);
-                }
-            }
-            Stmt::For {
-                start, end, body, ..
-            } => {
-                Self::collect_calls_expr(&start.node, calls);
-                Self::collect_calls_expr(&end.node, calls);
-                Self::collect_calls_block(&body.node, calls);
-            }
-            Stmt::TupleAssign { value, .. } => Self::collect_calls_expr(&value.node, calls),
-            Stmt::Expr(expr) => Self::collect_calls_expr(&expr.node, calls),
-            Stmt::Return(Some(val)) => Self::collect_calls_expr(&val.node, calls),
-            Stmt::Return(None) => {}
-            Stmt::Reveal { fields, .. } | Stmt::Seal { fields, .. } => {
-                for (_, val) in fields {
-                    Self::collect_calls_expr(&val.node, calls);
-                }
-            }
-            Stmt::Asm { .. } => {}
-            Stmt::Match { expr, arms } => {
-                Self::collect_calls_expr(&expr.node, calls);
-                for arm in arms {
-                    Self::collect_calls_block(&arm.body.node, calls);
-                }
-            }
+            let Some((cycle, span)) = Self::shortest_cycle(name, &call_graph) else {
+                continue;
+            };
+            reported.extend(cycle.iter().cloned());
+            let message = if cycle.len() == 2 {
+                format!("recursive call cycle detected: '{}' calls itself", cycle[0])
+            } else {
+                format!("recursive call cycle detected: {}", cycle.join(" -> "))
+            };
+            self.diagnostics.push(
+                Diagnostic::error(message, span)
+                    .with_note(
+                        "the trace is sized before the program runs, so every call chain needs a depth known at compile time".to_string(),
+                    )
+                    .with_help(
+                        "rewrite the recursion as a loop, `for i in 0..n bounded MAX` with the deepest recursion you need as MAX, carrying in `let mut` variables what each call would pass on".to_string(),
+                    ),
+            );
         }
     }
 
-    fn collect_calls_expr(expr: &Expr, calls: &mut Vec<String>) {
-        match expr {
-            Expr::Call { path, args, .. } => {
-                // Use full dotted path for cross-module calls so they don't
-                // collide with local functions of the same name.
-                let dotted = path.node.as_dotted();
-                calls.push(dotted.clone());
-                for arg in args {
-                    Self::collect_calls_expr(&arg.node, calls);
-                }
-            }
-            Expr::BinOp { lhs, rhs, .. } => {
-                Self::collect_calls_expr(&lhs.node, calls);
-                Self::collect_calls_expr(&rhs.node, calls);
-            }
-            Expr::Tuple(elems) | Expr::ArrayInit(elems) => {
-                for e in elems {
-                    Self::collect_calls_expr(&e.node, calls);
-                }
-            }
-            Expr::FieldAccess { expr: inner, .. } | Expr::Index { expr: inner, .. } => {
-                Self::collect_calls_expr(&inner.node, calls);
-            }
-            Expr::StructInit { fields, .. } => {
-                for (_, val) in fields {
-                    Self::collect_calls_expr(&val.node, calls);
+    /// The shortest call cycle from `start` back to itself: its functions
+    /// (`start` first and last) and the span of the call that closes it.
+    fn shortest_cycle(
+        start: &str,
+        graph: &BTreeMap<String, Vec<(String, Span)>>,
+    ) -> Option<(Vec<String>, Span)> {
+        // Breadth-first from `start`, remembering each function's caller.
+        let mut parent: BTreeMap<&str, &str> = BTreeMap::new();
+        let mut queue: VecDeque<&str> = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for (callee, span) in graph.get(node).into_iter().flatten() {
+                if callee == start {
+                    let mut path = Vec::new();
+                    let mut at = node;
+                    while at != start {
+                        path.push(at.to_string());
+                        at = parent[at];
+                    }
+                    path.reverse();
+                    let mut cycle = vec![start.to_string()];
+                    cycle.extend(path);
+                    cycle.push(start.to_string());
+                    return Some((cycle, *span));
+                }
+                if graph.contains_key(callee.as_str()) && !parent.contains_key(callee.as_str()) {
+                    parent.insert(callee.as_str(), node);
+                    queue.push_back(callee);
                 }
             }
-            Expr::Literal(_) | Expr::Var(_) => {}
         }
+        None
     }
 
     /// Collect module prefixes used in calls and variable access within a block.
@@ -238,7 +149,7 @@ impl TypeChecker {
         }
     }
 
-    fn collect_used_modules_expr(expr: &Expr, used: &mut BTreeSet<String>) {
+    pub(super) fn collect_used_modules_expr(expr: &Expr, used: &mut BTreeSet<String>) {
         match expr {
             Expr::Call { path, args, .. } => {
                 let dotted = path.node.as_dotted();
@@ -280,6 +191,15 @@ impl TypeChecker {
                     Self::collect_used_modules_expr(&val.node, used);
                 }
             }
+            Expr::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                Self::collect_used_modules_expr(&cond.node, used);
+                Self::collect_used_modules_block(&then_block.node, used);
+                Self::collect_used_modules_block(&else_block.node, used);
+            }
             Expr::Literal(_) => {}
         }
     }
@@ -4,14 +4,18 @@ use crate::ast::*;
 use crate::span::{Span, Spanned};
 use crate::types::Ty;
 
-use super::TypeChecker;
+use super::{conditions, TypeChecker};
 
 impl TypeChecker {
     pub(super) fn check_fn(&mut self, func: &FnDef) {
+        if let Some((lint, span)) = conditions::unknown_lint(func) {
+            self.warning(format!("unknown lint '{}'", lint), span);
+        }
         if func.body.is_none() {
             return; // intrinsic, no body to check
         }
         if !func.type_params.is_empty() {
+            self.check_generic_body_calls(func);
             return; // generic — body checked per monomorphized instance
         }
 
@@ -41,6 +45,7 @@ impl TypeChecker {
         self.in_pure_fn = func.is_pure;
 
         self.push_scope();
+        self.nonzero_proven.clear();
 
         // Bind parameters
         for param in &func.params {
@@ -48,17 +53,43 @@ impl TypeChecker {
             self.define_var(&param.name.node, ty, false);
         }
 
+        self.return_ty = self
+            .functions
+            .get(&func.name.node)
+            .map_or(Ty::Unit, |sig| sig.return_ty.clone());
         let body = func.body.as_ref().expect("guarded by is_none check above");
-        self.check_block(&body.node);
+        let expected = (self.return_ty != Ty::Unit).then(|| self.return_ty.clone());
+        self.check_block_expecting(body, expected.as_ref());
 
         self.pop_scope();
         self.in_pure_fn = prev_pure;
+
+        self.lint_constant_conditions(func);
     }
 
-    pub(super) fn check_block(&mut self, block: &Block) -> Ty {
+    pub(super) fn check_block(&mut self, spanned: &Spanned<Block>) -> Ty {
+        self.check_block_expecting(spanned, None)
+    }
+
+    /// Check a block whose tail value the context expects to have type
+    /// `expected` (see `check_expr_expecting`).
+    pub(super) fn check_block_expecting(
+        &mut self,
+        spanned: &Spanned<Block>,
+        expected: Option<&Ty>,
+    ) -> Ty {
+        let tail_if = expected.and_then(|_| spanned.node.with_tail_if());
+        let block = tail_if.as_ref().unwrap_or(&spanned.node);
         self.push_scope();
+        // Nonzero facts learned inside the block do not outlive it.
+        let nonzero_before = self.nonzero_proven.clone();
+        let mut probing = self.probe_enters(spanned.span);
         let mut terminated = false;
         for stmt in &block.stmts {
+            if probing && stmt.span.end >= self.probe_offset() {
+                self.probe_capture(Some(&stmt.node));
+                probing = false;
+            }
             if terminated {
                 self.error_with_help(
                     "unreachable code after return statement".to_string(),
@@ -81,11 +112,19 @@ impl TypeChecker {
                 );
             }
         }
+        if probing {
+            self.probe_capture(None);
+        }
         let ty = if let Some(tail) = &block.tail_expr {
-            self.check_expr(&tail.node, tail.span)
+            match expected {
+                Some(expected) => self.check_expr_expecting(&tail.node, tail.span, expected),
+                None => self.check_expr(&tail.node, tail.span),
+            }
         } else {
             Ty::Unit
         };
+        self.nonzero_proven
+            .retain(|name| nonzero_before.contains(name));
         self.pop_scope();
         ty
     }
@@ -93,15 +132,15 @@ impl TypeChecker {
     pub(super) fn is_terminating_stmt(&self, stmt: &Stmt) -> bool {
         match stmt {
             Stmt::Return(_) => true,
-            // assert(false) is an unconditional halt
+            // assert(false) and ensure(false, code) are unconditional halts
             Stmt::Expr(expr) => {
                 if let Expr::Call { path, args, .. } = &expr.node {
                     let name = path.node.as_dotted();
-                    if (name == "assert" || name == "assert.is_true") && args.len() == 1 {
-                        if let Expr::Literal(Literal::Bool(false)) = &args[0].node {
-                            return true;
-                        }
-                    }
+                    let cond = match (name.as_str(), args.as_slice()) {
+                        ("assert" | "assert.is_true", [cond]) | ("ensure", [cond, _]) => cond,
+                        _ => return false,
+                    };
+                    return matches!(cond.node, Expr::Literal(Literal::Bool(false)));
                 }
                 false
             }
@@ -138,7 +177,7 @@ impl TypeChecker {
         // Check provided fields exist and have correct types
         for (name, val) in fields {
             if let Some((_def_name, def_ty)) = event_fields.iter().find(|(n, _)| *n == name.node) {
-                let val_ty = self.check_expr(&val.node, val.span);
+                let val_ty = self.check_expr_expecting(&val.node, val.span, def_ty);
                 if val_ty != *def_ty {
                     self.error(
                         format!(
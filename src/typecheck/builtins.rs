@@ -87,6 +87,20 @@ impl TypeChecker {
                 return_ty: Ty::Unit,
             },
         );
+        b.insert(
+            "assume".into(),
+            FnSig {
+                params: vec![("cond".into(), Ty::Bool)],
+                return_ty: Ty::Unit,
+            },
+        );
+        b.insert(
+            "ensure".into(),
+            FnSig {
+                params: vec![("cond".into(), Ty::Bool), ("code".into(), Ty::U32)],
+                return_ty: Ty::Unit,
+            },
+        );
         b.insert(
             "assert_eq".into(),
             FnSig {
@@ -127,6 +141,20 @@ impl TypeChecker {
                 return_ty: Ty::Field,
             },
         );
+        b.insert(
+            "checked_inv".into(),
+            FnSig {
+                params: vec![("a".into(), Ty::Field)],
+                return_ty: Ty::Field,
+            },
+        );
+        b.insert(
+            "checked_div".into(),
+            FnSig {
+                params: vec![("a".into(), Ty::Field), ("b".into(), Ty::Field)],
+                return_ty: Ty::Field,
+            },
+        );
         b.insert(
             "neg".into(),
             FnSig {
@@ -194,6 +222,13 @@ impl TypeChecker {
                 return_ty: Ty::Unit,
             },
         );
+        b.insert(
+            "sponge_absorb_field".into(),
+            FnSig {
+                params: vec![("x".into(), Ty::Field)],
+                return_ty: Ty::Unit,
+            },
+        );
         b.insert(
             "sponge_squeeze".into(),
             FnSig {
@@ -224,19 +259,49 @@ impl TypeChecker {
             },
         );
 
-        // Merkle — memory variant
+        // Merkle — memory variant: the sibling is read at ptr, which
+        // comes back advanced past it
         b.insert(
             "merkle_step_mem".into(),
+            FnSig {
+                params: {
+                    let mut p = vec![("ptr".into(), Ty::Field), ("idx".into(), Ty::U32)];
+                    for i in 0..dw {
+                        p.push((format!("d{}", i), Ty::Field));
+                    }
+                    p
+                },
+                return_ty: Ty::Tuple(vec![Ty::Field, Ty::U32, digest_ty.clone()]),
+            },
+        );
+
+        // Merkle — whole paths of `depth` steps
+        b.insert(
+            "merkle_walk".into(),
             FnSig {
                 params: {
                     let mut p = vec![("idx".into(), Ty::U32)];
                     for i in 0..dw {
                         p.push((format!("d{}", i), Ty::Field));
                     }
-                    p.push(("ptr".into(), Ty::Field));
+                    p.push(("depth".into(), Ty::Field));
+                    p
+                },
+                return_ty: Ty::Tuple(vec![Ty::U32, digest_ty.clone()]),
+            },
+        );
+        b.insert(
+            "merkle_walk_mem".into(),
+            FnSig {
+                params: {
+                    let mut p = vec![("ptr".into(), Ty::Field), ("idx".into(), Ty::U32)];
+                    for i in 0..dw {
+                        p.push((format!("d{}", i), Ty::Field));
+                    }
+                    p.push(("depth".into(), Ty::Field));
                     p
                 },
-                return_ty: Ty::Tuple(vec![Ty::U32, digest_ty.clone(), Ty::Field]),
+                return_ty: Ty::Tuple(vec![Ty::Field, Ty::U32, digest_ty.clone()]),
             },
         );
 
@@ -325,23 +390,290 @@ impl TypeChecker {
 /// Returns true if a builtin function name performs I/O side effects.
 /// Used by the `#[pure]` annotation checker.
 pub(super) fn is_io_builtin(name: &str) -> bool {
-    matches!(
-        name,
-        "pub_read"
-            | "pub_write"
-            | "sec_read"
-            | "divine"
-            | "sponge_init"
-            | "sponge_absorb"
-            | "sponge_squeeze"
-            | "sponge_absorb_mem"
-            | "ram_read"
-            | "ram_write"
-            | "ram_read_block"
-            | "ram_write_block"
-            | "merkle_step"
-            | "merkle_step_mem"
-    ) || name.starts_with("pub_read")
+    builtin_info(name).is_some_and(|b| b.io)
+        || name == "sec_read"
+        || name.starts_with("pub_read")
         || name.starts_with("pub_write")
         || name.starts_with("divine")
 }
+
+/// Documentation and Triton lowering of one builtin. This is the single
+/// metadata table for builtins: the type checker's signatures (target
+/// dependent, from `register_builtins`) plus what tooling shows about them.
+#[derive(Clone, Copy, Debug)]
+pub struct BuiltinInfo {
+    pub name: &'static str,
+    pub doc: &'static str,
+    /// TASM instructions a call lowers to, one per line (empty if the
+    /// call only relabels stack elements).
+    pub tasm: &'static str,
+    /// Performs I/O or touches non-deterministic state (not `#[pure]`).
+    pub io: bool,
+}
+
+const fn info(name: &'static str, doc: &'static str, tasm: &'static str, io: bool) -> BuiltinInfo {
+    BuiltinInfo {
+        name,
+        doc,
+        tasm,
+        io,
+    }
+}
+
+/// All builtins, in the order `register_builtins` declares them.
+pub const BUILTINS: &[BuiltinInfo] = &[
+    info(
+        "pub_read",
+        "Read one field element from public input.",
+        "read_io 1",
+        true,
+    ),
+    info(
+        "pub_read2",
+        "Read 2 field elements from public input.",
+        "read_io 2",
+        true,
+    ),
+    info(
+        "pub_read3",
+        "Read 3 field elements from public input.",
+        "read_io 3",
+        true,
+    ),
+    info(
+        "pub_read4",
+        "Read 4 field elements from public input.",
+        "read_io 4",
+        true,
+    ),
+    info(
+        "pub_read5",
+        "Read 5 field elements (Digest) from public input.",
+        "read_io 5",
+        true,
+    ),
+    info(
+        "pub_write",
+        "Write one field element to public output.",
+        "write_io 1",
+        true,
+    ),
+    info(
+        "pub_write2",
+        "Write 2 field elements to public output.",
+        "write_io 2",
+        true,
+    ),
+    info(
+        "pub_write3",
+        "Write 3 field elements to public output.",
+        "write_io 3",
+        true,
+    ),
+    info(
+        "pub_write4",
+        "Write 4 field elements to public output.",
+        "write_io 4",
+        true,
+    ),
+    info(
+        "pub_write5",
+        "Write 5 field elements to public output.",
+        "write_io 5",
+        true,
+    ),
+    info(
+        "divine",
+        "Read one non-deterministic field element (secret witness).",
+        "divine 1",
+        true,
+    ),
+    info(
+        "divine3",
+        "Read 3 non-deterministic field elements.",
+        "divine 3",
+        true,
+    ),
+    info(
+        "divine5",
+        "Read 5 non-deterministic field elements (Digest).",
+        "divine 5",
+        true,
+    ),
+    info(
+        "assert",
+        "Abort execution if condition is false.",
+        "assert",
+        false,
+    ),
+    info(
+        "assume",
+        "Let the verifier rely on a condition; only checked in debug builds.",
+        "assert (debug only)",
+        false,
+    ),
+    info(
+        "ensure",
+        "Abort execution if condition is false, writing the constant error code to public output first.",
+        "push 0\neq\nskiz\ncall <fail: push code, write_io 1, push 0, assert>",
+        false,
+    ),
+    info(
+        "assert_eq",
+        "Abort execution if a != b.",
+        "eq\nassert",
+        false,
+    ),
+    info(
+        "assert_digest",
+        "Abort execution if digests are not equal.",
+        "assert_vector\npop 5",
+        false,
+    ),
+    info("field_add", "Field addition.", "add", false),
+    info("field_mul", "Field multiplication.", "mul", false),
+    info("inv", "Field multiplicative inverse.", "invert", false),
+    info(
+        "checked_inv",
+        "Field multiplicative inverse; fails unless the argument is nonzero.",
+        "dup 0\npush 0\neq\npush 0\neq\nassert\ninvert",
+        false,
+    ),
+    info(
+        "checked_div",
+        "Field division a / b; fails unless b is nonzero.",
+        "dup 0\npush 0\neq\npush 0\neq\nassert\ninvert\nmul",
+        false,
+    ),
+    info("neg", "Field negation.", "push -1\nmul", false),
+    info("sub", "Field subtraction.", "push -1\nmul\nadd", false),
+    info(
+        "split",
+        "Split field element into (hi, lo) u32 limbs.",
+        "split",
+        false,
+    ),
+    info("log2", "Floor of log base 2.", "log_2_floor", false),
+    info("pow", "Integer exponentiation.", "pow", false),
+    info("popcount", "Count set bits.", "pop_count", false),
+    info("hash", "Tip5 hash of 10 field elements.", "hash", false),
+    info(
+        "sponge_init",
+        "Initialize the Tip5 sponge state.",
+        "sponge_init",
+        true,
+    ),
+    info(
+        "sponge_absorb",
+        "Absorb 10 field elements into the sponge.",
+        "sponge_absorb",
+        true,
+    ),
+    info(
+        "sponge_absorb_field",
+        "Absorb one field element; adjacent calls share full-rate absorptions.",
+        "sponge_absorb",
+        true,
+    ),
+    info(
+        "sponge_squeeze",
+        "Squeeze 10 field elements from the sponge.",
+        "sponge_squeeze",
+        true,
+    ),
+    info(
+        "sponge_absorb_mem",
+        "Absorb 10 field elements from RAM at ptr.",
+        "sponge_absorb_mem",
+        true,
+    ),
+    info(
+        "merkle_step",
+        "One step of Merkle tree authentication; the sibling is divined.",
+        "merkle_step",
+        true,
+    ),
+    info(
+        "merkle_step_mem",
+        "One Merkle step with the sibling read from RAM at ptr; returns ptr + 5.",
+        "push 0\nplace 6\nmerkle_step_mem\npick 6\npop 1",
+        true,
+    ),
+    info(
+        "merkle_walk",
+        "depth Merkle steps with divined siblings; a literal depth is unrolled.",
+        "call __merkle__N\npop 1",
+        true,
+    ),
+    info(
+        "merkle_walk_mem",
+        "depth Merkle steps with siblings read from RAM at ptr.",
+        "push 0\nplace 7\ncall __merkle__N\npop 1\npick 6\npop 1",
+        true,
+    ),
+    info(
+        "ram_read",
+        "Read one field element from RAM.",
+        "read_mem 1\npop 1",
+        true,
+    ),
+    info(
+        "ram_write",
+        "Write one field element to RAM.",
+        "write_mem 1\npop 1",
+        true,
+    ),
+    info(
+        "ram_read_block",
+        "Read 5 consecutive field elements from RAM.",
+        "read_mem 5\npop 1",
+        true,
+    ),
+    info(
+        "ram_write_block",
+        "Write 5 consecutive field elements to RAM.",
+        "write_mem 5\npop 1",
+        true,
+    ),
+    info(
+        "as_u32",
+        "Convert field to u32; fails unless the value is below 2^32.",
+        "split\nswap 1\npush 0\neq\nassert",
+        false,
+    ),
+    info("as_field", "Convert u32 to field element.", "", false),
+    info("xfield", "Construct extension field element.", "", false),
+    info(
+        "xinvert",
+        "Extension field multiplicative inverse.",
+        "x_invert",
+        false,
+    ),
+    info(
+        "xx_dot_step",
+        "Accumulate an XField dot product step from RAM.",
+        "xx_dot_step",
+        false,
+    ),
+    info(
+        "xb_dot_step",
+        "Accumulate a base-field dot product step from RAM.",
+        "xb_dot_step",
+        false,
+    ),
+];
+
+/// Metadata for a builtin by name.
+pub fn builtin_info(name: &str) -> Option<&'static BuiltinInfo> {
+    BUILTINS.iter().find(|b| b.name == name)
+}
+
+impl TypeChecker {
+    /// A builtin's parameters and return type as this checker's target
+    /// declares them.
+    pub(crate) fn builtin_params(&self, name: &str) -> Option<(Vec<(String, Ty)>, Ty)> {
+        builtin_info(name)?;
+        let sig = self.functions.get(name)?;
+        Some((sig.params.clone(), sig.return_ty.clone()))
+    }
+}
@@ -0,0 +1,350 @@
+//! Constant-condition lint: `assert` and `if` conditions that always hold
+//! or never hold, found by propagating constants and value ranges
+//! through a function body.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::*;
+use crate::diagnostic::Diagnostic;
+use crate::span::{Span, Spanned};
+
+use super::TypeChecker;
+
+/// Lint name accepted by `#[allow(...)]`.
+pub(super) const CONSTANT_CONDITION: &str = "constant_condition";
+
+/// Every lint `#[allow(...)]` knows.
+pub(super) const LINTS: &[&str] = &[CONSTANT_CONDITION];
+
+/// Goldilocks modulus; ranges never wrap past it.
+const MODULUS: u64 = 0xFFFF_FFFF_0000_0001;
+
+/// What is known about a value: an inclusive range of canonical field
+/// values, or a boolean.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Known {
+    Range(u64, u64),
+    Bool(bool),
+}
+
+/// Facts in scope: `None` marks a local whose value is unknown, so it
+/// also shadows a module constant of the same name.
+type Env = BTreeMap<String, Option<Known>>;
+
+impl TypeChecker {
+    /// Warn about conditions in `func` whose value is fixed at compile time.
+    pub(super) fn lint_constant_conditions(&mut self, func: &FnDef) {
+        if func.allows.iter().any(|l| l.node == CONSTANT_CONDITION) {
+            return;
+        }
+        let Some(body) = &func.body else {
+            return;
+        };
+        let mut mutated = BTreeSet::new();
+        collect_mutated(&body.node, &mut mutated);
+        let mut env: Env = func
+            .params
+            .iter()
+            .map(|p| (p.name.node.clone(), None))
+            .collect();
+        self.lint_block(&body.node, &mut env, &mutated);
+    }
+
+    fn lint_block(&mut self, block: &Block, env: &mut Env, mutated: &BTreeSet<String>) {
+        let outer = env.clone();
+        for stmt in &block.stmts {
+            self.lint_stmt(&stmt.node, env, mutated);
+        }
+        if let Some(tail) = &block.tail_expr {
+            self.lint_expr(tail, env, mutated);
+        }
+        *env = outer;
+    }
+
+    fn lint_stmt(&mut self, stmt: &Stmt, env: &mut Env, mutated: &BTreeSet<String>) {
+        match stmt {
+            Stmt::Let { pattern, init, .. } => {
+                self.lint_expr(init, env, mutated);
+                match pattern {
+                    Pattern::Name(name) => {
+                        let value = if mutated.contains(&name.node) {
+                            None
+                        } else {
+                            self.known(&init.node, env)
+                        };
+                        env.insert(name.node.clone(), value);
+                    }
+                    Pattern::Tuple(names) => {
+                        for name in names {
+                            env.insert(name.node.clone(), None);
+                        }
+                    }
+                }
+            }
+            Stmt::Assign { value, .. } | Stmt::TupleAssign { value, .. } => {
+                self.lint_expr(value, env, mutated);
+            }
+            Stmt::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                self.lint_condition(cond, "condition", env);
+                self.lint_expr(cond, env, mutated);
+                self.lint_block(&then_block.node, env, mutated);
+                if let Some(else_block) = else_block {
+                    self.lint_block(&else_block.node, env, mutated);
+                }
+            }
+            Stmt::For {
+                var,
+                start,
+                end,
+                body,
+                ..
+            } => {
+                let range = match (self.known(&start.node, env), self.known(&end.node, env)) {
+                    (Some(Known::Range(lo, _)), Some(Known::Range(_, hi))) if hi > lo => {
+                        Some(Known::Range(lo, hi - 1))
+                    }
+                    _ => None,
+                };
+                let outer = env.clone();
+                env.insert(var.node.clone(), range);
+                self.lint_block(&body.node, env, mutated);
+                *env = outer;
+            }
+            Stmt::Expr(expr) => {
+                if let Expr::Call { path, args, .. } = &expr.node {
+                    match (path.node.as_dotted().as_str(), args.as_slice()) {
+                        ("assert" | "assert.is_true", [cond]) | ("ensure", [cond, _]) => {
+                            self.lint_condition(cond, "assertion", env);
+                        }
+                        _ => {}
+                    }
+                }
+                self.lint_expr(expr, env, mutated);
+            }
+            Stmt::Return(Some(expr)) => self.lint_expr(expr, env, mutated),
+            Stmt::Reveal { fields, .. } | Stmt::Seal { fields, .. } => {
+                for (_, value) in fields {
+                    self.lint_expr(value, env, mutated);
+                }
+            }
+            Stmt::Match { expr, arms } => {
+                self.lint_expr(expr, env, mutated);
+                for arm in arms {
+                    let outer = env.clone();
+                    if let MatchPattern::Struct { fields, .. } = &arm.pattern.node {
+                        for field in fields {
+                            if let FieldPattern::Binding(name) = &field.pattern.node {
+                                env.insert(name.clone(), None);
+                            }
+                        }
+                    }
+                    if let MatchPattern::Some(binding) = &arm.pattern.node {
+                        env.insert(binding.node.clone(), None);
+                    }
+                    self.lint_block(&arm.body.node, env, mutated);
+                    *env = outer;
+                }
+            }
+            Stmt::Return(None) | Stmt::Asm { .. } => {}
+        }
+    }
+
+    /// Visit `if` expressions nested in `expr`.
+    fn lint_expr(&mut self, expr: &Spanned<Expr>, env: &mut Env, mutated: &BTreeSet<String>) {
+        match &expr.node {
+            Expr::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                self.lint_condition(cond, "condition", env);
+                self.lint_expr(cond, env, mutated);
+                self.lint_block(&then_block.node, env, mutated);
+                self.lint_block(&else_block.node, env, mutated);
+            }
+            Expr::BinOp { lhs, rhs, .. } => {
+                self.lint_expr(lhs, env, mutated);
+                self.lint_expr(rhs, env, mutated);
+            }
+            Expr::Call { args, .. } | Expr::ArrayInit(args) | Expr::Tuple(args) => {
+                for arg in args {
+                    self.lint_expr(arg, env, mutated);
+                }
+            }
+            Expr::FieldAccess { expr, .. } => self.lint_expr(expr, env, mutated),
+            Expr::Index { expr, index } => {
+                self.lint_expr(expr, env, mutated);
+                self.lint_expr(index, env, mutated);
+            }
+            Expr::StructInit { fields, .. } => {
+                for (_, value) in fields {
+                    self.lint_expr(value, env, mutated);
+                }
+            }
+            Expr::Literal(_) | Expr::Var(_) => {}
+        }
+    }
+
+    /// Warn when `cond` folds to a constant. A literal `true` / `false`
+    /// is written on purpose (`assert(false)` halts) and is not reported.
+    fn lint_condition(&mut self, cond: &Spanned<Expr>, what: &str, env: &Env) {
+        if matches!(cond.node, Expr::Literal(_)) {
+            return;
+        }
+        let Some(Known::Bool(value)) = self.known(&cond.node, env) else {
+            return;
+        };
+        let consequence = match (what, value) {
+            ("assertion", true) => "the check never fails",
+            ("assertion", false) => "the program always fails here",
+            (_, true) => "the else branch never runs",
+            (_, false) => "the then branch never runs",
+        };
+        self.diagnostics.push(
+            Diagnostic::warning(format!("{} is always {}", what, value), cond.span)
+                .with_note(format!(
+                    "{}; `{}` is on by default",
+                    consequence, CONSTANT_CONDITION
+                ))
+                .with_help(format!(
+                    "add `#[allow({})]` to the function if this is intentional",
+                    CONSTANT_CONDITION
+                )),
+        );
+    }
+
+    /// Fold `expr` to a constant or range where the facts in `env` allow.
+    fn known(&self, expr: &Expr, env: &Env) -> Option<Known> {
+        match expr {
+            Expr::Literal(Literal::Integer(v)) => Some(Known::Range(*v, *v)),
+            Expr::Literal(Literal::Bool(b)) => Some(Known::Bool(*b)),
+            Expr::Var(name) => match env.get(name) {
+                Some(value) => *value,
+                None => self.constants.get(name).map(|v| Known::Range(*v, *v)),
+            },
+            Expr::Call { path, args, .. } => {
+                let name = path.node.as_dotted();
+                let base = name.rsplit('.').next().unwrap_or(&name);
+                (base == "as_u32" && args.len() == 1).then_some(Known::Range(0, u32::MAX as u64))
+            }
+            Expr::BinOp { op, lhs, rhs } => {
+                let (l, r) = (self.known(&lhs.node, env), self.known(&rhs.node, env));
+                fold_binop(*op, l, r)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn fold_binop(op: BinOp, l: Option<Known>, r: Option<Known>) -> Option<Known> {
+    use Known::{Bool, Range};
+    match (op, l, r) {
+        (BinOp::Add, Some(Range(a, b)), Some(Range(c, d))) => {
+            let hi = b.checked_add(d).filter(|hi| *hi < MODULUS)?;
+            Some(Range(a + c, hi))
+        }
+        (BinOp::Mul, Some(Range(a, b)), Some(Range(c, d))) => {
+            let hi = b.checked_mul(d).filter(|hi| *hi < MODULUS)?;
+            Some(Range(a * c, hi))
+        }
+        (BinOp::Eq, Some(Range(a, b)), Some(Range(c, d))) => {
+            if a == b && c == d {
+                Some(Bool(a == c))
+            } else if b < c || d < a {
+                Some(Bool(false))
+            } else {
+                None
+            }
+        }
+        (BinOp::Eq, Some(Bool(a)), Some(Bool(b))) => Some(Bool(a == b)),
+        (BinOp::Lt, Some(Range(a, b)), Some(Range(c, d))) => {
+            if b < c {
+                Some(Bool(true))
+            } else if a >= d {
+                Some(Bool(false))
+            } else {
+                None
+            }
+        }
+        (BinOp::And, Some(Bool(false)), _) | (BinOp::And, _, Some(Bool(false))) => {
+            Some(Bool(false))
+        }
+        (BinOp::Or, Some(Bool(true)), _) | (BinOp::Or, _, Some(Bool(true))) => Some(Bool(true)),
+        (BinOp::And, Some(Bool(a)), Some(Bool(b))) => Some(Bool(a && b)),
+        (BinOp::Or, Some(Bool(a)), Some(Bool(b))) => Some(Bool(a || b)),
+        _ => None,
+    }
+}
+
+/// Names assigned anywhere in `block`; their `let` values are not facts.
+fn collect_mutated(block: &Block, out: &mut BTreeSet<String>) {
+    for stmt in &block.stmts {
+        match &stmt.node {
+            Stmt::Assign { place, .. } => {
+                let mut place = &place.node;
+                loop {
+                    match place {
+                        Place::Var(name) => {
+                            out.insert(name.clone());
+                            break;
+                        }
+                        Place::FieldAccess(inner, _) | Place::Index(inner, _) => {
+                            place = &inner.node;
+                        }
+                    }
+                }
+            }
+            Stmt::TupleAssign { names, .. } => {
+                out.extend(names.iter().map(|n| n.node.clone()));
+            }
+            Stmt::If {
+                then_block,
+                else_block,
+                ..
+            } => {
+                collect_mutated(&then_block.node, out);
+                if let Some(else_block) = else_block {
+                    collect_mutated(&else_block.node, out);
+                }
+            }
+            Stmt::For { body, .. } => collect_mutated(&body.node, out),
+            Stmt::Match { arms, .. } => {
+                for arm in arms {
+                    collect_mutated(&arm.body.node, out);
+                }
+            }
+            Stmt::Let { init: expr, .. } | Stmt::Expr(expr) => {
+                collect_mutated_expr(&expr.node, out)
+            }
+            _ => {}
+        }
+    }
+    if let Some(tail) = &block.tail_expr {
+        collect_mutated_expr(&tail.node, out);
+    }
+}
+
+/// Assignments inside the blocks of `if` expressions.
+fn collect_mutated_expr(expr: &Expr, out: &mut BTreeSet<String>) {
+    if let Expr::If {
+        then_block,
+        else_block,
+        ..
+    } = expr
+    {
+        collect_mutated(&then_block.node, out);
+        collect_mutated(&else_block.node, out);
+    }
+}
+
+/// Span of an unknown lint name in `#[allow(...)]`, if any.
+pub(super) fn unknown_lint(func: &FnDef) -> Option<(&str, Span)> {
+    func.allows
+        .iter()
+        .find(|l| !LINTS.contains(&l.node.as_str()))
+        .map(|l| (l.node.as_str(), l.span))
+}
@@ -3,7 +3,7 @@
 use std::collections::BTreeMap;
 
 use crate::ast::*;
-use crate::span::Span;
+use crate::span::{Span, Spanned};
 use crate::types::Ty;
 
 use super::builtins::is_io_builtin;
@@ -16,6 +16,14 @@ impl TypeChecker {
                 Literal::Integer(_) => Ty::Field,
                 Literal::Bool(_) => Ty::Bool,
             },
+            Expr::Var(_) if expr.is_none() => {
+                self.error_with_help(
+                    "`None` needs its Option type from the context".to_string(),
+                    span,
+                    "give it a declared type, as in `let x: Option<Field> = None`, or use it as an assignment, return value or struct field".to_string(),
+                );
+                Ty::Field
+            }
             Expr::Var(name) => {
                 // Direct variable lookup
                 if let Some(info) = self.lookup_var(name) {
@@ -23,7 +31,10 @@ impl TypeChecker {
                 }
                 // Known constant
                 if self.constants.contains_key(name) {
-                    return Ty::Field;
+                    return self.const_tys.get(name).cloned().unwrap_or(Ty::Field);
+                }
+                if let Some(ty) = self.tables.get(name) {
+                    return ty.clone();
                 }
                 // Dotted name: could be nested field access (var.field.subfield)
                 // or module constant. Try resolving from the first dot outward.
@@ -40,10 +51,26 @@ impl TypeChecker {
                 Ty::Field
             }
             Expr::BinOp { op, lhs, rhs } => {
-                let lhs_ty = self.check_expr(&lhs.node, lhs.span);
-                let rhs_ty = self.check_expr(&rhs.node, rhs.span);
+                let mut lhs_ty = self.check_expr(&lhs.node, lhs.span);
+                let mut rhs_ty = self.check_expr(&rhs.node, rhs.span);
+                // A literal operand takes the other operand's type where
+                // the operator accepts U32: `x < 10`, `x == 0`, `x & 255`.
+                if matches!(
+                    op,
+                    BinOp::Eq | BinOp::Lt | BinOp::BitAnd | BinOp::BitXor | BinOp::DivMod
+                ) {
+                    lhs_ty = self.retype_literal(lhs, &rhs_ty, &lhs_ty);
+                    rhs_ty = self.retype_literal(rhs, &lhs_ty, &rhs_ty);
+                }
                 self.check_binop(*op, &lhs_ty, &rhs_ty, span)
             }
+            Expr::Call { path, args, .. } if path.node.0 == ["Some"] => match expr.some_payload() {
+                Some(value) => Ty::Option(Box::new(self.check_expr(&value.node, value.span))),
+                None => {
+                    self.error(format!("Some takes one value, got {}", args.len()), span);
+                    Ty::Field
+                }
+            },
             Expr::Call {
                 path,
                 generic_args,
@@ -72,6 +99,7 @@ impl TypeChecker {
                 // Check if this is a generic function call.
                 if let Some(gdef) = self.generic_fns.get(&fn_name).cloned() {
                     // Resolve size arguments: explicit or inferred.
+                    let errors_before = self.diagnostics.len();
                     let size_args = if !generic_args.is_empty() {
                         // Explicit: sum<3>(...)
                         if generic_args.len() != gdef.type_params.len() {
@@ -84,6 +112,7 @@ impl TypeChecker {
                                 ),
                                 span,
                             );
+                            self.explain_cross_module_instance(&fn_name, &gdef, errors_before);
                             return Ty::Field;
                         }
                         let mut sizes = Vec::new();
@@ -103,12 +132,16 @@ impl TypeChecker {
                         // Infer from argument types.
                         self.infer_size_args(&gdef, &arg_tys, span)
                     };
+                    self.explain_cross_module_instance(&fn_name, &gdef, errors_before);
 
                     // Build substitution map.
                     let mut subs = BTreeMap::new();
                     for (param_name, size_val) in gdef.type_params.iter().zip(size_args.iter()) {
                         subs.insert(param_name.clone(), *size_val);
                     }
+                    if self.diagnostics.len() == errors_before {
+                        self.check_size_bounds(&fn_name, &gdef, &subs, span);
+                    }
 
                     // Monomorphize the signature.
                     let params: Vec<(String, Ty)> = gdef
@@ -137,6 +170,7 @@ impl TypeChecker {
                         for (i, ((_, expected), actual)) in
                             params.iter().zip(arg_tys.iter()).enumerate()
                         {
+                            let actual = &self.retype_literal(&args[i], expected, actual);
                             if expected != actual {
                                 self.error(
                                     format!(
@@ -156,6 +190,7 @@ impl TypeChecker {
                     let instance = MonoInstance {
                         name: fn_name.clone(),
                         size_args: size_args.clone(),
+                        instantiated_at: Some(span),
                     };
                     if !self.mono_instances.contains(&instance) {
                         self.mono_instances.push(instance.clone());
@@ -189,6 +224,7 @@ impl TypeChecker {
                         for (i, ((_, expected), actual)) in
                             sig.params.iter().zip(arg_tys.iter()).enumerate()
                         {
+                            let actual = &self.retype_literal(&args[i], expected, actual);
                             if expected != actual {
                                 self.error(
                                     format!(
@@ -205,6 +241,9 @@ impl TypeChecker {
                     }
                     // H0003: detect redundant as_u32 range checks
                     let base_name = fn_name.rsplit('.').next().unwrap_or(&fn_name);
+                    self.check_divisor(base_name, args, &arg_tys, span);
+                    self.check_error_code(base_name, args);
+                    self.check_intrinsic_tier(&fn_name, span);
                     if base_name == "as_u32" && args.len() == 1 {
                         if let Expr::Var(var_name) = &args[0].node {
                             if self.u32_proven.contains(var_name) {
@@ -242,6 +281,8 @@ impl TypeChecker {
                         );
                         Ty::Field
                     }
+                } else if let Ty::NamedTuple(_) = &inner_ty {
+                    self.named_component(&inner_ty, &field.node, span)
                 } else {
                     self.error(
                         format!("field access on non-struct type {}", inner_ty.display()),
@@ -255,6 +296,7 @@ impl TypeChecker {
                 let _idx_ty = self.check_expr(&index.node, index.span);
                 match &inner_ty {
                     Ty::Array(elem_ty, _) => *elem_ty.clone(),
+                    Ty::Bytes(_) => Ty::U32,
                     _ => {
                         self.error(
                             format!("index access on non-array type {}", inner_ty.display()),
@@ -275,7 +317,7 @@ impl TypeChecker {
                         if let Some((_name, val)) =
                             init_fields.iter().find(|(n, _)| n.node == *def_name)
                         {
-                            let val_ty = self.check_expr(&val.node, val.span);
+                            let val_ty = self.check_expr_expecting(&val.node, val.span, def_ty);
                             if val_ty != *def_ty {
                                 self.error(
                                     format!(
@@ -345,6 +387,11 @@ impl TypeChecker {
                     .collect();
                 Ty::Tuple(tys)
             }
+            Expr::If {
+                cond,
+                then_block,
+                else_block,
+            } => self.check_if_expr(cond, then_block, else_block, span, None),
         }
     }
 
@@ -376,6 +423,19 @@ impl TypeChecker {
                         ),
                         span,
                     );
+                } else if lhs.width() + 2 > self.target_config.stack_depth {
+                    // Each element pair is compared with a `dup` reaching
+                    // one below the right operand.
+                    self.error_with_help(
+                        format!(
+                            "operator '==' on {} compares {} elements, more than the {} a stack comparison can reach",
+                            lhs.display(),
+                            lhs.width(),
+                            self.target_config.stack_depth - 2
+                        ),
+                        span,
+                        "compare the fields or chunks separately".to_string(),
+                    );
                 }
                 Ty::Bool
             }
@@ -392,6 +452,20 @@ impl TypeChecker {
                 }
                 Ty::Bool
             }
+            BinOp::And | BinOp::Or => {
+                if lhs != &Ty::Bool || rhs != &Ty::Bool {
+                    self.error(
+                        format!(
+                            "operator '{}' requires Bool operands, got {} and {}",
+                            op.as_str(),
+                            lhs.display(),
+                            rhs.display()
+                        ),
+                        span,
+                    );
+                }
+                Ty::Bool
+            }
             BinOp::BitAnd | BinOp::BitXor => {
                 if lhs != &Ty::U32 || rhs != &Ty::U32 {
                     self.error(
@@ -444,8 +518,12 @@ impl TypeChecker {
         // For "st.s00.lo", tries "st" first, then "st.s00".
         for split in 1..parts.len() {
             let var_name = parts[..split].join(".");
-            if let Some(info) = self.lookup_var(&var_name) {
-                let mut ty = info.ty.clone();
+            // A composite constant's fields are read like a variable's.
+            let base = match self.lookup_var(&var_name) {
+                Some(info) => Some(info.ty.clone()),
+                None => self.tables.get(&var_name).cloned(),
+            };
+            if let Some(mut ty) = base {
                 for field in &parts[split..] {
                     if let Ty::Struct(ref sty) = ty {
                         if let Some((field_ty, _, _)) = sty.field_offset(field) {
@@ -457,6 +535,8 @@ impl TypeChecker {
                             );
                             return Some(Ty::Field);
                         }
+                    } else if let Ty::NamedTuple(_) = ty {
+                        ty = self.named_component(&ty, field, span);
                     } else {
                         self.error(
                             format!(
@@ -474,4 +554,312 @@ impl TypeChecker {
         }
         None
     }
+
+    /// Type of a named tuple component, reporting unknown names.
+    fn named_component(&mut self, tuple: &Ty, name: &str, span: Span) -> Ty {
+        if let Some(ty) = tuple.component(name) {
+            return ty;
+        }
+        self.error(
+            format!("tuple {} has no component '{}'", tuple.display(), name),
+            span,
+        );
+        Ty::Field
+    }
+
+    /// Check an expression whose type the context already knows.
+    ///
+    /// Integer literals default to `Field`; where `U32` is expected they
+    /// are `U32` instead, after checking that they fit in 32 bits. Tuple
+    /// and array literals pass the expectation on to their elements, and
+    /// `None` takes its payload type from it.
+    pub(super) fn check_expr_expecting(&mut self, expr: &Expr, span: Span, expected: &Ty) -> Ty {
+        match (expr, expected) {
+            (_, Ty::Option(_)) if expr.is_none() => expected.clone(),
+            (_, Ty::Option(payload)) if expr.some_payload().is_some() => {
+                let value = expr.some_payload().expect("guarded by is_some");
+                Ty::Option(Box::new(self.check_expr_expecting(
+                    &value.node,
+                    value.span,
+                    payload,
+                )))
+            }
+            (Expr::Literal(Literal::Integer(n)), Ty::U32) => {
+                if *n > u64::from(u32::MAX) {
+                    self.error_with_help(
+                        format!("integer literal {} does not fit in U32", n),
+                        span,
+                        "U32 values range from 0 to 4294967295".to_string(),
+                    );
+                }
+                Ty::U32
+            }
+            (Expr::ArrayInit(elements), Ty::Array(inner, _)) if !elements.is_empty() => {
+                let tys: Vec<Ty> = elements
+                    .iter()
+                    .map(|e| self.check_expr_expecting(&e.node, e.span, inner))
+                    .collect();
+                for (elem, ty) in elements.iter().zip(&tys).skip(1) {
+                    if *ty != tys[0] {
+                        self.error(
+                            format!(
+                                "array element type mismatch: expected {} got {}",
+                                tys[0].display(),
+                                ty.display()
+                            ),
+                            elem.span,
+                        );
+                    }
+                }
+                Ty::Array(Box::new(tys[0].clone()), elements.len() as u64)
+            }
+            (Expr::ArrayInit(elements), Ty::Bytes(_)) if !self.builds_bytes() => {
+                self.check_byte_literals(elements)
+            }
+            (_, Ty::Bytes(n)) if self.builds_bytes() => {
+                let words = Ty::Array(Box::new(Ty::U32), *n);
+                match self.check_expr_expecting(expr, span, &words) {
+                    ty if ty == words => expected.clone(),
+                    ty => ty,
+                }
+            }
+            (_, Ty::Array(elem, n)) if self.builds_bytes() && **elem == Ty::U32 => {
+                match self.check_expr(expr, span) {
+                    Ty::Bytes(m) if m == *n => expected.clone(),
+                    ty => ty,
+                }
+            }
+            (Expr::Tuple(elements), _) => match expected.tuple_elements() {
+                Some(expected_tys) if expected_tys.len() == elements.len() => Ty::Tuple(
+                    elements
+                        .iter()
+                        .zip(&expected_tys)
+                        .map(|(e, ty)| self.check_expr_expecting(&e.node, e.span, ty))
+                        .collect(),
+                ),
+                _ => self.check_expr(expr, span),
+            },
+            (
+                Expr::If {
+                    cond,
+                    then_block,
+                    else_block,
+                },
+                _,
+            ) => self.check_if_expr(cond, then_block, else_block, span, Some(expected)),
+            _ => self.check_expr(expr, span),
+        }
+    }
+
+    /// Whether values of `[U32; N]` and `Bytes<N>` convert into each other
+    /// here: only in `std.bytes`, whose functions range-check the bytes.
+    fn builds_bytes(&self) -> bool {
+        self.module_name == "std.bytes"
+    }
+
+    /// Check a `Bytes<N>` array literal outside `std.bytes`: every element
+    /// must be an integer literal below 256.
+    fn check_byte_literals(&mut self, elements: &[Spanned<Expr>]) -> Ty {
+        for elem in elements {
+            match &elem.node {
+                Expr::Literal(Literal::Integer(n)) if *n < 256 => {}
+                Expr::Literal(Literal::Integer(n)) => self.error_with_help(
+                    format!("byte literal {} is out of range", n),
+                    elem.span,
+                    "bytes range from 0 to 255".to_string(),
+                ),
+                _ => {
+                    self.check_expr(&elem.node, elem.span);
+                    self.error_with_help(
+                        "Bytes element is not a literal".to_string(),
+                        elem.span,
+                        "build Bytes from runtime values with std.bytes.from_u32s, which range-checks each one"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+        Ty::Bytes(elements.len() as u64)
+    }
+
+    /// Check an `if` expression: a Bool (or Field) condition and two
+    /// branches of the same type. An integer-literal branch takes the type
+    /// the context expects, or else the type of the other branch.
+    fn check_if_expr(
+        &mut self,
+        cond: &Spanned<Expr>,
+        then_block: &Spanned<Block>,
+        else_block: &Spanned<Block>,
+        span: Span,
+        expected: Option<&Ty>,
+    ) -> Ty {
+        let cond_ty = self.check_expr(&cond.node, cond.span);
+        if cond_ty != Ty::Bool && cond_ty != Ty::Field {
+            self.error(
+                format!(
+                    "if condition must be Bool or Field, got {}",
+                    cond_ty.display()
+                ),
+                cond.span,
+            );
+        }
+        let mut then_ty = self.check_block_expecting(then_block, expected);
+        let else_expected = expected.cloned().unwrap_or_else(|| then_ty.clone());
+        let else_ty = self.check_else_block(cond, else_block, Some(&else_expected));
+        if let Some(tail) = &then_block.node.tail_expr {
+            then_ty = self.retype_literal(tail, &else_ty, &then_ty);
+        }
+        if then_ty != else_ty {
+            self.error_with_help(
+                format!(
+                    "if branches have different types: {} and {}",
+                    then_ty.display(),
+                    else_ty.display()
+                ),
+                span,
+                "both branches of an `if` expression must produce the same type".to_string(),
+            );
+        }
+        then_ty
+    }
+
+    /// Warn on `inv(x)` unless `x` is proven nonzero, and record the
+    /// divisor of `checked_inv`/`checked_div` as proven: the call asserts it.
+    fn check_divisor(
+        &mut self,
+        base_name: &str,
+        args: &[Spanned<Expr>],
+        arg_tys: &[Ty],
+        span: Span,
+    ) {
+        match (base_name, args, arg_tys) {
+            ("inv", [arg], [Ty::Field]) if !self.is_proven_nonzero(&arg.node) => {
+                self.warning_with_help(
+                    "inv argument is not proven nonzero; inv(0) halts the VM".to_string(),
+                    span,
+                    "use `checked_inv(x)` or `checked_div(a, x)`, which assert a nonzero divisor"
+                        .to_string(),
+                );
+            }
+            ("checked_inv" | "checked_div", [.., divisor], _) => {
+                if let Expr::Var(name) = &divisor.node {
+                    self.nonzero_proven.insert(name.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `ensure` codes go into the ABI's error table, so they must be
+    /// known at compile time.
+    fn check_error_code(&mut self, base_name: &str, args: &[Spanned<Expr>]) {
+        if let ("ensure", [_, code]) = (base_name, args) {
+            if !self.is_constant_expr(&code.node) {
+                self.error_with_help(
+                    "ensure error code must be a constant".to_string(),
+                    code.span,
+                    "use an integer literal or a `const`, as in `ensure(ok, E_OVERFLOW)`"
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    /// A call to an intrinsic that needs a higher tier than the target
+    /// executes would lower to instructions the VM does not have.
+    fn check_intrinsic_tier(&mut self, fn_name: &str, span: Span) {
+        let Some(&tier) = self.intrinsic_tiers.get(fn_name) else {
+            return;
+        };
+        let target = &self.target_config.name;
+        let Some(max) = crate::resolve::target_tier(target) else {
+            return;
+        };
+        if tier > max {
+            self.error_with_help(
+                format!(
+                    "'{}' needs tier {} ({}), but target '{}' supports tiers 0-{}",
+                    fn_name,
+                    tier,
+                    tier_name(tier),
+                    target,
+                    max
+                ),
+                span,
+                format!(
+                    "compile for a target with tier {}, or put the call behind a #[cfg] flag",
+                    tier
+                ),
+            );
+        }
+    }
+
+    /// Whether `expr` is known to be nonzero: a nonzero literal or
+    /// constant, an inverse, or a variable in `nonzero_proven`.
+    pub(super) fn is_proven_nonzero(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Literal(Literal::Integer(n)) => *n != 0,
+            Expr::Var(name) => {
+                self.nonzero_proven.contains(name)
+                    || self.constants.get(name).is_some_and(|&v| v != 0)
+            }
+            Expr::Call { path, .. } => {
+                let name = path.node.as_dotted();
+                let base = name.rsplit('.').next().unwrap_or(&name);
+                matches!(base, "inv" | "checked_inv")
+            }
+            _ => false,
+        }
+    }
+
+    /// Check the else branch of `if x == 0 { .. } else { .. }` with `x`
+    /// proven nonzero.
+    pub(super) fn check_else_block(
+        &mut self,
+        cond: &Spanned<Expr>,
+        else_block: &Spanned<Block>,
+        expected: Option<&Ty>,
+    ) -> Ty {
+        let tested = match &cond.node {
+            Expr::BinOp {
+                op: BinOp::Eq,
+                lhs,
+                rhs,
+            } => match (&lhs.node, &rhs.node) {
+                (Expr::Var(name), Expr::Literal(Literal::Integer(0)))
+                | (Expr::Literal(Literal::Integer(0)), Expr::Var(name)) => Some(name.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let added = tested.filter(|name| self.nonzero_proven.insert(name.clone()));
+        let ty = self.check_block_expecting(else_block, expected);
+        if let Some(name) = added {
+            self.nonzero_proven.remove(&name);
+        }
+        ty
+    }
+
+    /// Retype an already-checked argument or operand against the type
+    /// its context expects. Only literals change: integers become `U32`
+    /// where `U32` is expected, and array literals become `Bytes<N>`
+    /// where bytes are expected (see `check_expr_expecting`).
+    fn retype_literal(&mut self, arg: &Spanned<Expr>, expected: &Ty, actual: &Ty) -> Ty {
+        let bytes = matches!(expected, Ty::Bytes(_)) && matches!(arg.node, Expr::ArrayInit(_));
+        if bytes || matches!(arg.node, Expr::Literal(Literal::Integer(_))) {
+            self.check_expr_expecting(&arg.node, arg.span, expected)
+        } else {
+            actual.clone()
+        }
+    }
+}
+
+/// Name of a TIR tier, as in `TIROp`.
+fn tier_name(tier: u8) -> &'static str {
+    match tier {
+        0 => "structure",
+        1 => "universal",
+        2 => "provable",
+        _ => "recursion",
+    }
 }
@@ -1,17 +1,23 @@
 mod analysis;
 mod block;
 mod builtins;
+mod conditions;
 mod expr;
+mod probe;
 mod resolve;
 mod stmt;
 #[cfg(test)]
 mod tests;
 pub mod types;
 
+pub use builtins::{builtin_info, BuiltinInfo, BUILTINS};
+pub(crate) use probe::ScopeAt;
+
 use std::collections::{BTreeMap, BTreeSet};
 
+use crate::ast::eval::{PureFns, DEFAULT_FUEL, MAX_TABLE_LEN};
 use crate::ast::*;
-use crate::diagnostic::Diagnostic;
+use crate::diagnostic::{Diagnostic, Suggestion};
 use crate::span::{Span, Spanned};
 use crate::types::{StructTy, Ty};
 
@@ -24,22 +30,33 @@ pub(super) struct FnSig {
 
 /// A generic (size-parameterized) function definition, stored unresolved.
 #[derive(Clone, Debug)]
-pub(super) struct GenericFnDef {
+pub(crate) struct GenericFnDef {
     /// Size parameter names, e.g. `["N"]`.
-    pub(super) type_params: Vec<String>,
+    pub(crate) type_params: Vec<String>,
     /// Parameter types as AST types (may contain `ArraySize::Param`).
-    pub(super) params: Vec<(String, Type)>,
+    pub(crate) params: Vec<(String, Type)>,
     /// Return type as AST type (may contain `ArraySize::Param`).
-    pub(super) return_ty: Option<Type>,
+    pub(crate) return_ty: Option<Type>,
+    /// `where` clause bounds, checked at each instantiation.
+    pub(crate) bounds: Vec<SizeBound>,
+    /// Module that defines the function, and emits its instances.
+    pub(crate) module: String,
 }
 
 /// A monomorphized instance of a generic function.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+///
+/// Two instances are equal when name and size arguments match; where
+/// they were requested does not take part in the comparison.
+#[derive(Clone, Debug)]
 pub struct MonoInstance {
     /// Original function name.
     pub name: String,
     /// Concrete size values for each type parameter.
     pub size_args: Vec<u64>,
+    /// The call that first requested this instance, in the module whose
+    /// type check recorded it. `None` for instances the emitter found
+    /// inside other instances.
+    pub instantiated_at: Option<Span>,
 }
 
 impl MonoInstance {
@@ -48,6 +65,27 @@ impl MonoInstance {
         let suffix: Vec<String> = self.size_args.iter().map(|n| format!("{}", n)).collect();
         format!("{}__N{}", self.name, suffix.join("_"))
     }
+
+    /// Source-level name: `sum` with N=3 -> `sum<3>`.
+    pub fn display_name(&self) -> String {
+        let args: Vec<String> = self.size_args.iter().map(|n| n.to_string()).collect();
+        format!("{}<{}>", self.name, args.join(", "))
+    }
+}
+
+impl PartialEq for MonoInstance {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.size_args == other.size_args
+    }
+}
+
+impl Eq for MonoInstance {}
+
+impl std::hash::Hash for MonoInstance {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.size_args.hash(state);
+    }
 }
 
 /// Variable info in scope.
@@ -67,12 +105,21 @@ pub struct ModuleExports {
     pub functions: Vec<FnExport>,
     pub constants: Vec<(String, Ty, u64)>, // (name, ty, value)
     pub structs: Vec<StructTy>,            // exported struct types
+    pub tables: Vec<(String, Ty)>,         // exported tables and composite constants
     pub warnings: Vec<Diagnostic>,         // non-fatal diagnostics
     /// Unique monomorphized instances of generic functions to emit.
     pub mono_instances: Vec<MonoInstance>,
     /// Per-call-site resolution: each generic call in AST order maps to a MonoInstance.
     /// The emitter consumes these in order to know which mangled name to call.
     pub call_resolutions: Vec<MonoInstance>,
+    /// Exported generic functions, monomorphized by importers at each call site.
+    /// Struct names in signatures are module-qualified.
+    pub(crate) generic_functions: Vec<(String, GenericFnDef)>,
+    /// `#[pure]` functions and constants visible to this module, for
+    /// compile-time evaluation in importers and the emitter.
+    pub pure_fns: PureFns,
+    /// Exported intrinsics that declare a tier: `(name, tier)`.
+    pub(crate) intrinsic_tiers: Vec<(String, u8)>,
 }
 
 pub(crate) struct TypeChecker {
@@ -82,6 +129,11 @@ pub(crate) struct TypeChecker {
     pub(super) scopes: Vec<BTreeMap<String, VarInfo>>,
     /// Known constants (name -> value).
     pub(super) constants: BTreeMap<String, u64>,
+    /// Declared types of `constants`; a `U32` constant is a `U32` operand.
+    pub(super) const_tys: BTreeMap<String, Ty>,
+    /// `generate!` tables and composite constants (name or module.name
+    /// -> type).
+    pub(super) tables: BTreeMap<String, Ty>,
     /// Known struct types (name or module.name -> StructTy).
     pub(super) structs: BTreeMap<String, StructTy>,
     /// Known event types (name -> field list).
@@ -90,6 +142,9 @@ pub(crate) struct TypeChecker {
     pub(super) diagnostics: Vec<Diagnostic>,
     /// Variables proven to be in U32 range (via as_u32, split, or U32 type).
     pub(super) u32_proven: BTreeSet<String>,
+    /// Variables proven nonzero in the current function (see
+    /// `is_proven_nonzero`). Cleared at each function entry.
+    pub(super) nonzero_proven: BTreeSet<String>,
     /// Generic (size-parameterized) function definitions.
     pub(super) generic_fns: BTreeMap<String, GenericFnDef>,
     /// Unique monomorphized instances collected during type checking.
@@ -102,6 +157,18 @@ pub(crate) struct TypeChecker {
     pub(super) target_config: crate::target::TerrainConfig,
     /// Whether we are currently inside a `#[pure]` function body.
     pub(super) in_pure_fn: bool,
+    /// Declared return type of the function being checked.
+    pub(super) return_ty: Ty,
+    /// `#[pure]` functions and constants of this and imported modules,
+    /// for folding constant initializers.
+    pub(super) pure_fns: PureFns,
+    /// Offset being probed by `scope_at`, and what was seen there.
+    pub(super) probe: Option<probe::ScopeProbe>,
+    /// Name of the module being checked.
+    pub(super) module_name: String,
+    /// Tier each `#[intrinsic(tier = N, ...)]` function needs, keyed like
+    /// `functions`.
+    pub(super) intrinsic_tiers: BTreeMap<String, u8>,
 }
 
 impl Default for TypeChecker {
@@ -120,16 +187,24 @@ impl TypeChecker {
             functions: BTreeMap::new(),
             scopes: Vec::new(),
             constants: BTreeMap::new(),
+            const_tys: BTreeMap::new(),
+            tables: BTreeMap::new(),
             structs: BTreeMap::new(),
             events: BTreeMap::new(),
             diagnostics: Vec::new(),
             u32_proven: BTreeSet::new(),
+            nonzero_proven: BTreeSet::new(),
             generic_fns: BTreeMap::new(),
             mono_instances: Vec::new(),
             call_resolutions: Vec::new(),
             cfg_flags: BTreeSet::from(["debug".to_string()]),
             target_config: config,
             in_pure_fn: false,
+            return_ty: Ty::Unit,
+            pure_fns: PureFns::new(),
+            probe: None,
+            module_name: String::new(),
+            intrinsic_tiers: BTreeMap::new(),
         };
         tc.register_builtins();
         tc
@@ -184,12 +259,22 @@ impl TypeChecker {
                 self.functions.insert(short, sig);
             }
         }
-        for (const_name, _ty, value) in &exports.constants {
+        for (const_name, ty, value) in &exports.constants {
             let qualified = format!("{}.{}", exports.module_name, const_name);
-            self.constants.insert(qualified, *value);
+            self.constants.insert(qualified.clone(), *value);
+            self.const_tys.insert(qualified, ty.clone());
             if has_short {
                 let short = format!("{}.{}", short_prefix, const_name);
-                self.constants.insert(short, *value);
+                self.constants.insert(short.clone(), *value);
+                self.const_tys.insert(short, ty.clone());
+            }
+        }
+        for (table_name, ty) in &exports.tables {
+            let qualified = format!("{}.{}", exports.module_name, table_name);
+            self.tables.insert(qualified, ty.clone());
+            if has_short {
+                let short = format!("{}.{}", short_prefix, table_name);
+                self.tables.insert(short, ty.clone());
             }
         }
         for sty in &exports.structs {
@@ -200,16 +285,39 @@ impl TypeChecker {
                 self.structs.insert(short, sty.clone());
             }
         }
+        self.pure_fns.merge(&exports.pure_fns);
+        for (fn_name, tier) in &exports.intrinsic_tiers {
+            let qualified = format!("{}.{}", exports.module_name, fn_name);
+            self.intrinsic_tiers.insert(qualified, *tier);
+            if has_short {
+                let short = format!("{}.{}", short_prefix, fn_name);
+                self.intrinsic_tiers.insert(short, *tier);
+            }
+        }
+        for (fn_name, gdef) in &exports.generic_functions {
+            let qualified = format!("{}.{}", exports.module_name, fn_name);
+            self.generic_fns.insert(qualified, gdef.clone());
+            if has_short {
+                let short = format!("{}.{}", short_prefix, fn_name);
+                self.generic_fns.insert(short, gdef.clone());
+            }
+        }
     }
 
-    pub(crate) fn check_file(mut self, file: &File) -> Result<ModuleExports, Vec<Diagnostic>> {
+    /// Register a file's items and type check every function body.
+    pub(super) fn check_items(&mut self, file: &File) {
+        self.module_name = file.name.node.clone();
         let is_std_module = file.name.node.starts_with("std.")
             || file.name.node.starts_with("vm.")
             || file.name.node.starts_with("os.")
             || file.name.node.starts_with("ext.")
             || file.name.node.contains(".ext.");
 
+        self.pure_fns.add_module(file, &self.cfg_flags);
+
         // First pass: register all structs, function signatures, and constants
+        let mut generated = Vec::new();
+        let mut composites = Vec::new();
         for item in &file.items {
             // Skip items excluded by conditional compilation
             if !self.is_item_cfg_active(&item.node) {
@@ -240,6 +348,10 @@ impl TypeChecker {
                             func.name.span,
                         );
                     }
+                    if let Some(tier) = func.intrinsic_attr().and_then(|i| i.tier) {
+                        self.intrinsic_tiers.insert(func.name.node.clone(), tier);
+                    }
+                    self.check_where_clause(func);
                     if func.type_params.is_empty() {
                         // Non-generic function: resolve immediately.
                         let params: Vec<(String, Ty)> = func
@@ -264,14 +376,50 @@ impl TypeChecker {
                                 .map(|p| (p.name.node.clone(), p.ty.node.clone()))
                                 .collect(),
                             return_ty: func.return_ty.as_ref().map(|t| t.node.clone()),
+                            bounds: func.where_clause.iter().map(|b| b.node.clone()).collect(),
+                            module: file.name.node.clone(),
                         };
                         self.generic_fns.insert(func.name.node.clone(), gdef);
                     }
                 }
+                // Checked once every function signature is known.
+                Item::Const(cdef) if cdef.generate => generated.push(cdef),
+                Item::Const(cdef)
+                    if !matches!(cdef.ty.node, Type::Field | Type::U32 | Type::Bool) =>
+                {
+                    composites.push(cdef)
+                }
                 Item::Const(cdef) => {
-                    if let Expr::Literal(Literal::Integer(v)) = &cdef.value.node {
-                        self.constants.insert(cdef.name.node.clone(), *v);
+                    let name = &cdef.name.node;
+                    let value = match &cdef.value.node {
+                        Expr::Literal(Literal::Integer(v)) => Some(*v),
+                        Expr::Literal(_) => continue,
+                        // Arithmetic and `#[pure]` calls over constants
+                        // are evaluated at compile time.
+                        _ => self.pure_fns.constant(&file.name.node, name),
+                    };
+                    let Some(v) = value else {
+                        self.error_with_help(
+                            format!("constant '{}' is not a compile-time value", name),
+                            cdef.value.span,
+                            "use a literal, another constant, or a `#[pure]` call over those"
+                                .to_string(),
+                        );
+                        continue;
+                    };
+                    if cdef.ty.node == Type::U32 && v > u64::from(u32::MAX) {
+                        let what = match &cdef.value.node {
+                            Expr::Literal(_) => "integer literal".to_string(),
+                            _ => format!("constant '{}' =", name),
+                        };
+                        self.error(
+                            format!("{} {} does not fit in U32", what, v),
+                            cdef.value.span,
+                        );
                     }
+                    self.constants.insert(name.clone(), v);
+                    let ty = self.resolve_type(&cdef.ty.node);
+                    self.const_tys.insert(name.clone(), ty);
                 }
                 Item::Event(edef) => {
                     if edef.fields.len() > 9 {
@@ -307,6 +455,13 @@ impl TypeChecker {
             }
         }
 
+        for cdef in generated {
+            self.check_generated_table(&file.name.node, cdef);
+        }
+        for cdef in composites {
+            self.check_composite_const(&file.name.node, cdef);
+        }
+
         // Recursion detection: build call graph and reject cycles
         self.detect_recursion(file);
 
@@ -319,6 +474,10 @@ impl TypeChecker {
                 self.check_fn(func);
             }
         }
+    }
+
+    pub(crate) fn check_file(mut self, file: &File) -> Result<ModuleExports, Vec<Diagnostic>> {
+        self.check_items(file);
 
         // Unused import detection: collect used module prefixes from all calls
         let mut used_prefixes: BTreeSet<String> = BTreeSet::new();
@@ -326,10 +485,16 @@ impl TypeChecker {
             if !self.is_item_cfg_active(&item.node) {
                 continue;
             }
-            if let Item::Fn(func) = &item.node {
-                if let Some(body) = &func.body {
-                    Self::collect_used_modules_block(&body.node, &mut used_prefixes);
+            match &item.node {
+                Item::Fn(func) => {
+                    if let Some(body) = &func.body {
+                        Self::collect_used_modules_block(&body.node, &mut used_prefixes);
+                    }
+                }
+                Item::Const(cdef) => {
+                    Self::collect_used_modules_expr(&cdef.value.node, &mut used_prefixes);
                 }
+                _ => {}
             }
         }
         for use_stmt in &file.uses {
@@ -341,21 +506,56 @@ impl TypeChecker {
                 .unwrap_or(&module_path)
                 .to_string();
             if !used_prefixes.contains(&short) && !used_prefixes.contains(&module_path) {
-                self.warning(format!("unused import '{}'", module_path), use_stmt.span);
+                self.diagnostics.push(
+                    Diagnostic::warning(format!("unused import '{}'", module_path), use_stmt.span)
+                        .with_suggestion(Suggestion {
+                            message: format!("remove `use {}`", module_path),
+                            span: use_stmt.span,
+                            replacement: String::new(),
+                        }),
+                );
             }
         }
 
         // Collect exports (pub items only)
         let module_name = file.name.node.clone();
         let mut exported_fns = Vec::new();
+        let mut exported_tiers = Vec::new();
         let mut exported_consts = Vec::new();
+        let mut exported_tables = Vec::new();
         let mut exported_structs = Vec::new();
+        let mut exported_generics = Vec::new();
+        let local_structs: BTreeSet<String> = file
+            .items
+            .iter()
+            .filter_map(|item| match &item.node {
+                Item::Struct(sdef) => Some(sdef.name.node.clone()),
+                _ => None,
+            })
+            .collect();
 
         for item in &file.items {
             if !self.is_item_cfg_active(&item.node) {
                 continue;
             }
             match &item.node {
+                Item::Fn(func) if func.is_pub && !func.type_params.is_empty() => {
+                    if let Some(gdef) = self.generic_fns.get(&func.name.node) {
+                        let qualify = |ty: &Type| qualify_type(ty, &module_name, &local_structs);
+                        let gdef = GenericFnDef {
+                            type_params: gdef.type_params.clone(),
+                            params: gdef
+                                .params
+                                .iter()
+                                .map(|(name, ty)| (name.clone(), qualify(ty)))
+                                .collect(),
+                            return_ty: gdef.return_ty.as_ref().map(qualify),
+                            bounds: gdef.bounds.clone(),
+                            module: module_name.clone(),
+                        };
+                        exported_generics.push((func.name.node.clone(), gdef));
+                    }
+                }
                 Item::Fn(func) if func.is_pub => {
                     let params: Vec<(String, Ty)> = func
                         .params
@@ -368,11 +568,16 @@ impl TypeChecker {
                         .map(|t| self.resolve_type(&t.node))
                         .unwrap_or(Ty::Unit);
                     exported_fns.push((func.name.node.clone(), params, return_ty));
+                    if let Some(&tier) = self.intrinsic_tiers.get(&func.name.node) {
+                        exported_tiers.push((func.name.node.clone(), tier));
+                    }
                 }
                 Item::Const(cdef) if cdef.is_pub => {
                     let ty = self.resolve_type(&cdef.ty.node);
-                    if let Expr::Literal(Literal::Integer(v)) = &cdef.value.node {
-                        exported_consts.push((cdef.name.node.clone(), ty, *v));
+                    if let Some(&v) = self.constants.get(&cdef.name.node) {
+                        exported_consts.push((cdef.name.node.clone(), ty, v));
+                    } else if self.tables.contains_key(&cdef.name.node) {
+                        exported_tables.push((cdef.name.node.clone(), ty));
                     }
                 }
                 Item::Struct(sdef) if sdef.is_pub => {
@@ -396,13 +601,123 @@ impl TypeChecker {
                 functions: exported_fns,
                 constants: exported_consts,
                 structs: exported_structs,
+                tables: exported_tables,
                 warnings: self.diagnostics,
                 mono_instances: self.mono_instances,
                 call_resolutions: self.call_resolutions,
+                generic_functions: exported_generics,
+                pure_fns: self.pure_fns,
+                intrinsic_tiers: exported_tiers,
             })
         }
     }
 
+    /// A `generate!` constant: an array of one-element values, filled by
+    /// running its `#[pure]` function at compile time.
+    fn check_generated_table(&mut self, module: &str, cdef: &ConstDef) {
+        let name = &cdef.name.node;
+        let ty = self.resolve_type(&cdef.ty.node);
+        let elem_ty = match &ty {
+            Ty::Array(elem, _) if matches!(**elem, Ty::Field | Ty::U32 | Ty::Bool) => {
+                (**elem).clone()
+            }
+            _ => {
+                self.error_with_help(
+                    format!(
+                        "generate! constant '{}' must be an array of Field, U32 or Bool, got {}",
+                        name,
+                        ty.display()
+                    ),
+                    cdef.ty.span,
+                    "declare it as `[Field; N]`".to_string(),
+                );
+                return;
+            }
+        };
+        let Expr::Call { path, .. } = &cdef.value.node else {
+            return;
+        };
+        let func = path.node.as_dotted();
+        let Some(sig) = self.functions.get(&func).cloned() else {
+            self.error(format!("undefined function '{}'", func), path.span);
+            return;
+        };
+        if sig.return_ty != elem_ty {
+            self.error(
+                format!(
+                    "generate! function '{}' returns {}, but the elements of '{}' are {}",
+                    func,
+                    sig.return_ty.display(),
+                    name,
+                    elem_ty.display()
+                ),
+                path.span,
+            );
+            return;
+        }
+        let Some(overflow) = self
+            .pure_fns
+            .table(module, name)
+            .map(|(_, values)| values.iter().copied().find(|&v| v > u64::from(u32::MAX)))
+        else {
+            self.error_with_help(
+                format!("constant '{}' could not be generated by '{}'", name, func),
+                cdef.value.span,
+                format!(
+                    "'{}' must be a `#[pure]` function of the index and the remaining \
+                     arguments; each call may take {} steps, and a table at most {} elements",
+                    func, DEFAULT_FUEL, MAX_TABLE_LEN
+                ),
+            );
+            return;
+        };
+        if elem_ty == Ty::U32 {
+            if let Some(v) = overflow {
+                self.error(
+                    format!("generated value {} in '{}' does not fit in U32", v, name),
+                    cdef.value.span,
+                );
+            }
+        }
+        self.tables.insert(name.clone(), ty);
+    }
+
+    /// A constant of array, struct, tuple or digest type. A digest is
+    /// written as an array of its elements: `[1, 2, 3, 4, 5]`.
+    fn check_composite_const(&mut self, module: &str, cdef: &ConstDef) {
+        let name = &cdef.name.node;
+        let ty = self.resolve_type(&cdef.ty.node);
+        let expected = match &ty {
+            Ty::Digest(width) => Ty::Array(Box::new(Ty::Field), u64::from(*width)),
+            _ => ty.clone(),
+        };
+        let value_ty = self.check_expr_expecting(&cdef.value.node, cdef.value.span, &expected);
+        if value_ty != expected && value_ty != ty {
+            self.error(
+                format!(
+                    "type mismatch: constant '{}' is declared {} but its value has type {}",
+                    name,
+                    ty.display(),
+                    value_ty.display()
+                ),
+                cdef.value.span,
+            );
+            return;
+        }
+        if self.pure_fns.table(module, name).is_none()
+            && self.pure_fns.composite(module, name).is_none()
+        {
+            self.error_with_help(
+                format!("constant '{}' is not a compile-time value", name),
+                cdef.value.span,
+                "build it from literals, other constants, or `#[pure]` calls over those"
+                    .to_string(),
+            );
+            return;
+        }
+        self.tables.insert(name.clone(), ty);
+    }
+
     // --- Scope management ---
 
     pub(super) fn push_scope(&mut self) {
@@ -442,4 +757,41 @@ impl TypeChecker {
     pub(super) fn warning(&mut self, msg: String, span: Span) {
         self.diagnostics.push(Diagnostic::warning(msg, span));
     }
+
+    pub(super) fn warning_with_help(&mut self, msg: String, span: Span, help: String) {
+        self.diagnostics
+            .push(Diagnostic::warning(msg, span).with_help(help));
+    }
+}
+
+/// Prefix local struct names with the module name so an exported
+/// signature resolves in the importing module's scope.
+fn qualify_type(ty: &Type, module_name: &str, local_structs: &BTreeSet<String>) -> Type {
+    match ty {
+        Type::Named(path) if path.0.len() == 1 && local_structs.contains(&path.0[0]) => {
+            let mut segments: Vec<String> = module_name.split('.').map(String::from).collect();
+            segments.push(path.0[0].clone());
+            Type::Named(ModulePath(segments))
+        }
+        Type::Array(inner, n) => Type::Array(
+            Box::new(qualify_type(inner, module_name, local_structs)),
+            n.clone(),
+        ),
+        Type::Tuple(elems) => Type::Tuple(
+            elems
+                .iter()
+                .map(|t| qualify_type(t, module_name, local_structs))
+                .collect(),
+        ),
+        Type::NamedTuple(components) => Type::NamedTuple(
+            components
+                .iter()
+                .map(|(name, t)| (name.clone(), qualify_type(t, module_name, local_structs)))
+                .collect(),
+        ),
+        Type::Option(inner) => {
+            Type::Option(Box::new(qualify_type(inner, module_name, local_structs)))
+        }
+        other => other.clone(),
+    }
 }
@@ -0,0 +1,154 @@
+//! Scope probing for editor tooling.
+//!
+//! `TypeChecker::scope_at` type checks a file while watching one byte
+//! offset, and reports the variables visible there, the type the code
+//! at that position is expected to have, and the struct, event, and
+//! function tables needed to interpret them.
+
+use std::collections::BTreeMap;
+
+use crate::ast::*;
+use crate::span::Span;
+use crate::types::{StructTy, Ty};
+
+use super::TypeChecker;
+
+/// Probe state carried through a checking pass.
+pub(crate) struct ScopeProbe {
+    offset: u32,
+    /// Scope depth of the capture; deeper (inner) blocks win.
+    depth: usize,
+    vars: BTreeMap<String, Ty>,
+    expected: Option<Ty>,
+}
+
+/// What the type checker knows at one source offset.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ScopeAt {
+    /// Variables in scope (inner bindings shadow outer ones).
+    pub vars: BTreeMap<String, Ty>,
+    /// Declared type of the `let` whose initializer holds the offset.
+    pub expected: Option<Ty>,
+    pub structs: BTreeMap<String, StructTy>,
+    pub events: BTreeMap<String, Vec<(String, Ty)>>,
+    /// Function name → (params, return type), including builtins and
+    /// imported `module.fn` names.
+    pub functions: BTreeMap<String, (Vec<(String, Ty)>, Ty)>,
+}
+
+impl TypeChecker {
+    /// Type check `file` and report the scope at byte `offset`. Type
+    /// errors elsewhere in the file do not stop the probe.
+    pub(crate) fn scope_at(mut self, file: &File, offset: u32) -> ScopeAt {
+        self.probe = Some(ScopeProbe {
+            offset,
+            depth: 0,
+            vars: BTreeMap::new(),
+            expected: None,
+        });
+        self.check_items(file);
+        let probe = self.probe.take().expect("probe set above");
+        ScopeAt {
+            vars: probe.vars,
+            expected: probe.expected,
+            structs: self.structs,
+            events: self.events,
+            functions: self
+                .functions
+                .into_iter()
+                .map(|(name, sig)| (name, (sig.params, sig.return_ty)))
+                .collect(),
+        }
+    }
+
+    /// Whether a block spanning `span` contains the probed offset.
+    pub(super) fn probe_enters(&self, span: Span) -> bool {
+        self.probe
+            .as_ref()
+            .is_some_and(|p| span.start <= p.offset && p.offset <= span.end)
+    }
+
+    pub(super) fn probe_offset(&self) -> u32 {
+        self.probe.as_ref().map_or(u32::MAX, |p| p.offset)
+    }
+
+    /// Record the current scope as the one at the probed offset, unless a
+    /// deeper block already did. `stmt` is the statement at or after the
+    /// offset, if any.
+    pub(super) fn probe_capture(&mut self, stmt: Option<&Stmt>) {
+        let depth = self.scopes.len();
+        let Some(offset) = self.probe.as_ref().map(|p| p.offset) else {
+            return;
+        };
+        if self.probe.as_ref().is_some_and(|p| p.depth > depth) {
+            return;
+        }
+        let mut vars = BTreeMap::new();
+        for scope in &self.scopes {
+            for (name, info) in scope {
+                vars.insert(name.clone(), info.ty.clone());
+            }
+        }
+        let expected = match stmt {
+            Some(Stmt::Let {
+                ty: Some(ty), init, ..
+            }) if init.span.start <= offset && offset <= init.span.end => {
+                Some(self.resolve_type(&ty.node))
+            }
+            _ => None,
+        };
+        self.probe = Some(ScopeProbe {
+            offset,
+            depth,
+            vars,
+            expected,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(source: &str, marker: &str) -> ScopeAt {
+        let file = crate::parse_source_silent(source, "test.tri").unwrap();
+        let offset = source.find(marker).unwrap() as u32;
+        TypeChecker::new().scope_at(&file, offset)
+    }
+
+    #[test]
+    fn test_scope_sees_params_and_earlier_lets_only() {
+        let source = "program test\nfn main(a: Field) {\n    let b: U32 = as_u32(a)\n    let c: Field = a\n    let d: Bool = true\n}\n";
+        let at = scope(source, "let c");
+        assert_eq!(at.vars.get("a"), Some(&Ty::Field));
+        assert_eq!(at.vars.get("b"), Some(&Ty::U32));
+        assert!(!at.vars.contains_key("d"));
+        assert_eq!(at.expected, None);
+    }
+
+    #[test]
+    fn test_scope_in_nested_block_includes_block_locals() {
+        let source = "program test\nfn main() {\n    if true {\n        let t: Field = 1\n        let u: Field = t\n    }\n    let v: Field = 2\n}\n";
+        let inner = scope(source, "let u");
+        assert!(inner.vars.contains_key("t"));
+        let outer = scope(source, "let v");
+        assert!(!outer.vars.contains_key("t"));
+    }
+
+    #[test]
+    fn test_expected_type_comes_from_let_annotation() {
+        let source = "program test\nstruct P {\n    x: U32,\n}\nfn main() {\n    let p: P = P { x: 1 }\n    let y: U32 = p.x\n}\n";
+        let at = scope(source, "p.x");
+        assert_eq!(at.expected, Some(Ty::U32));
+        assert!(matches!(at.vars.get("p"), Some(Ty::Struct(s)) if s.name == "P"));
+    }
+
+    #[test]
+    fn test_scope_at_end_of_block_sees_all_locals() {
+        let source = "program test\nfn main() {\n    let a: Field = 1\n    \n}\n";
+        let offset = source.find("    \n}").unwrap() as u32 + 4;
+        let file = crate::parse_source_silent(source, "test.tri").unwrap();
+        let at = TypeChecker::new().scope_at(&file, offset);
+        assert!(at.vars.contains_key("a"));
+    }
+}
@@ -3,6 +3,7 @@
 use std::collections::BTreeMap;
 
 use crate::ast::*;
+use crate::diagnostic::{Diagnostic, Severity};
 use crate::span::Span;
 use crate::types::Ty;
 
@@ -46,6 +47,143 @@ impl TypeChecker {
         result
     }
 
+    /// On size errors in a call to another module's generic function,
+    /// name the instance, the calling module, and the defining module:
+    /// the defining module emits the instance, so its sizes must be known
+    /// at the call.
+    pub(super) fn explain_cross_module_instance(
+        &mut self,
+        fn_name: &str,
+        gdef: &GenericFnDef,
+        errors_before: usize,
+    ) {
+        if gdef.module == self.module_name {
+            return;
+        }
+        let params = gdef.type_params.join(", ");
+        let placeholder = if gdef.type_params.len() == 1 {
+            "a number"
+        } else {
+            "numbers"
+        };
+        for diag in &mut self.diagnostics[errors_before..] {
+            if diag.severity != Severity::Error {
+                continue;
+            }
+            diag.notes.push(format!(
+                "module '{}' requires the instance '{}<{}>', which module '{}' defines and emits",
+                self.module_name, fn_name, params, gdef.module
+            ));
+            diag.help.get_or_insert_with(|| {
+                format!(
+                    "pass the sizes explicitly: `{}<{}>(...)` with {} replaced by {}",
+                    fn_name, params, params, placeholder
+                )
+            });
+        }
+    }
+
+    /// Reject calls to another module's generic functions inside a
+    /// generic body. The body is only built per instance, after the
+    /// defining module has emitted its instances, so the call would have
+    /// no instance to link to.
+    pub(super) fn check_generic_body_calls(&mut self, func: &FnDef) {
+        let Some(body) = &func.body else {
+            return;
+        };
+        for call in crate::ast::navigate::calls_in_block(&body.node) {
+            let Expr::Call { path, .. } = &call.node else {
+                continue;
+            };
+            let name = path.node.as_dotted();
+            let Some(gdef) = self.generic_fns.get(&name) else {
+                continue;
+            };
+            if gdef.module == self.module_name {
+                continue;
+            }
+            let diag = Diagnostic::error(
+                format!(
+                    "generic function '{}' calls '{}', a generic function of module '{}'",
+                    func.name.node, name, gdef.module
+                ),
+                call.span,
+            )
+            .with_note(format!(
+                "module '{}' emits the instances of '{}<{}>' before '{}' in module '{}' is instantiated, so the instance this call needs is never emitted",
+                gdef.module,
+                name,
+                gdef.type_params.join(", "),
+                func.name.node,
+                self.module_name
+            ))
+            .with_help(format!(
+                "call '{}' with explicit sizes (`{}<{}>(...)`, each a number) from a non-generic function, or move '{}' into module '{}'",
+                name,
+                name,
+                gdef.type_params.join(", "),
+                func.name.node,
+                gdef.module
+            ));
+            self.diagnostics.push(diag);
+        }
+    }
+
+    /// Check that a `where` clause only constrains the function's own size
+    /// parameters.
+    pub(super) fn check_where_clause(&mut self, func: &FnDef) {
+        for bound in &func.where_clause {
+            if func.type_params.is_empty() {
+                self.error(
+                    format!(
+                        "`where` clause on '{}', which has no size parameters",
+                        func.name.node
+                    ),
+                    bound.span,
+                );
+                return;
+            }
+            for name in bound.node.params() {
+                if !func.type_params.iter().any(|p| p.node == name) {
+                    self.error(
+                        format!("unknown size parameter '{}' in `where` clause", name),
+                        bound.span,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Report every `where` bound of a generic function that the
+    /// instantiation `subs` violates.
+    pub(super) fn check_size_bounds(
+        &mut self,
+        fn_name: &str,
+        gdef: &GenericFnDef,
+        subs: &BTreeMap<String, u64>,
+        span: Span,
+    ) {
+        for bound in &gdef.bounds {
+            if bound.holds(subs) {
+                continue;
+            }
+            let mut names = bound.params();
+            names.sort_unstable();
+            names.dedup();
+            let values: Vec<String> = names
+                .iter()
+                .map(|n| format!("{} = {}", n, subs.get(*n).copied().unwrap_or(0)))
+                .collect();
+            self.diagnostics.push(
+                Diagnostic::error(
+                    format!("call to '{}' violates `where {}`", fn_name, bound),
+                    span,
+                )
+                .with_note(format!("instantiated with {}", values.join(", "))),
+            );
+        }
+    }
+
     /// Recursively match an AST type pattern against a concrete Ty to extract
     /// size parameter bindings. E.g. `[Field; N]` vs `[Field; 5]` -> N=5.
     pub(super) fn unify_sizes(pattern: &Type, concrete: &Ty, subs: &mut BTreeMap<String, u64>) {
@@ -62,6 +200,18 @@ impl TypeChecker {
                     Self::unify_sizes(p, t, subs);
                 }
             }
+            (Type::NamedTuple(pats), concrete) => {
+                let tys = concrete.tuple_elements().unwrap_or_default();
+                for ((_, p), t) in pats.iter().zip(tys.iter()) {
+                    Self::unify_sizes(p, t, subs);
+                }
+            }
+            (Type::Option(inner_pat), Ty::Option(inner_ty)) => {
+                Self::unify_sizes(inner_pat, inner_ty, subs);
+            }
+            (Type::Bytes(ArraySize::Param(name)), Ty::Bytes(size)) => {
+                subs.insert(name.clone(), *size);
+            }
             _ => {}
         }
     }
@@ -89,6 +239,15 @@ impl TypeChecker {
                     .collect();
                 Ty::Tuple(resolved)
             }
+            Type::NamedTuple(components) => {
+                let resolved: Vec<(String, Ty)> = components
+                    .iter()
+                    .map(|(name, t)| (name.clone(), self.resolve_type_with_subs(t, subs)))
+                    .collect();
+                Ty::NamedTuple(resolved)
+            }
+            Type::Option(inner) => Ty::Option(Box::new(self.resolve_type_with_subs(inner, subs))),
+            Type::Bytes(n) => Ty::Bytes(n.eval(subs)),
             Type::Named(path) => {
                 let name = path.as_dotted();
                 if let Some(sty) = self.structs.get(&name) {
@@ -1,7 +1,7 @@
 //! Statement type checking: check_fn, check_block, check_stmt, check_event_stmt, check_place.
 
 use crate::ast::*;
-use crate::span::Span;
+use crate::span::{Span, Spanned};
 use crate::types::Ty;
 
 use super::TypeChecker;
@@ -15,9 +15,9 @@ impl TypeChecker {
                 ty,
                 init,
             } => {
-                let init_ty = self.check_expr(&init.node, init.span);
                 let resolved_ty = if let Some(declared_ty) = ty {
                     let expected = self.resolve_type(&declared_ty.node);
+                    let init_ty = self.check_expr_expecting(&init.node, init.span, &expected);
                     if expected != init_ty {
                         self.error(
                             format!(
@@ -30,12 +30,17 @@ impl TypeChecker {
                     }
                     expected
                 } else {
-                    init_ty
+                    self.check_expr(&init.node, init.span)
                 };
 
                 match pattern {
                     Pattern::Name(name) => {
                         self.define_var(&name.node, resolved_ty.clone(), *mutable);
+                        if self.is_proven_nonzero(&init.node) {
+                            self.nonzero_proven.insert(name.node.clone());
+                        } else {
+                            self.nonzero_proven.remove(&name.node);
+                        }
                         // Track U32-proven variables for H0003:
                         // When as_u32(x) or split(x) is called, the INPUT x
                         // has been range-checked. Mark x as proven so a
@@ -52,7 +57,7 @@ impl TypeChecker {
                     }
                     Pattern::Tuple(names) => {
                         // Destructure: type must be a tuple or Digest
-                        if let Ty::Tuple(elem_tys) = &resolved_ty {
+                        if let Some(elem_tys) = resolved_ty.tuple_elements() {
                             if names.len() != elem_tys.len() {
                                 self.error(
                                     format!(
@@ -64,6 +69,7 @@ impl TypeChecker {
                                 );
                             }
                             for (i, name) in names.iter().enumerate() {
+                                self.nonzero_proven.remove(&name.node);
                                 if name.node != "_" {
                                     let ty = elem_tys.get(i).cloned().unwrap_or(Ty::Field);
                                     self.define_var(&name.node, ty, *mutable);
@@ -83,6 +89,7 @@ impl TypeChecker {
                                 );
                             }
                             for name in names.iter() {
+                                self.nonzero_proven.remove(&name.node);
                                 if name.node != "_" {
                                     self.define_var(&name.node, Ty::Field, *mutable);
                                 }
@@ -108,7 +115,14 @@ impl TypeChecker {
                         "declare the variable with `let mut` to make it mutable".to_string(),
                     );
                 }
-                let val_ty = self.check_expr(&value.node, value.span);
+                if let Some(none_span) = none_in_literal(value, false) {
+                    self.error_with_help(
+                        "cannot assign a `None` inside a tuple or array literal".to_string(),
+                        none_span,
+                        "bind the literal with a typed `let` first and assign that".to_string(),
+                    );
+                }
+                let val_ty = self.check_expr_expecting(&value.node, value.span, &place_ty);
                 if place_ty != val_ty {
                     self.error(
                         format!(
@@ -122,6 +136,7 @@ impl TypeChecker {
                 // Invalidate U32-proven status on reassignment
                 if let Place::Var(name) = &place.node {
                     self.u32_proven.remove(name);
+                    self.nonzero_proven.remove(name);
                 }
             }
             Stmt::If {
@@ -139,9 +154,9 @@ impl TypeChecker {
                         cond.span,
                     );
                 }
-                self.check_block(&then_block.node);
+                self.check_block(then_block);
                 if let Some(else_blk) = else_block {
-                    self.check_block(&else_blk.node);
+                    self.check_else_block(cond, else_blk, None);
                 }
             }
             Stmt::For {
@@ -171,12 +186,12 @@ impl TypeChecker {
                 if var.node != "_" {
                     self.define_var(&var.node, Ty::U32, false);
                 }
-                self.check_block(&body.node);
+                self.check_block(body);
                 self.pop_scope();
             }
             Stmt::TupleAssign { names, value } => {
                 let val_ty = self.check_expr(&value.node, value.span);
-                let valid = if let Ty::Tuple(elem_tys) = &val_ty {
+                let valid = if let Some(elem_tys) = val_ty.tuple_elements() {
                     if names.len() != elem_tys.len() {
                         self.error(
                             format!(
@@ -232,7 +247,8 @@ impl TypeChecker {
             }
             Stmt::Return(value) => {
                 if let Some(val) = value {
-                    self.check_expr(&val.node, val.span);
+                    let expected = self.return_ty.clone();
+                    self.check_expr_expecting(&val.node, val.span, &expected);
                 }
             }
             Stmt::Reveal { event_name, fields } | Stmt::Seal { event_name, fields } => {
@@ -268,6 +284,8 @@ impl TypeChecker {
                 let mut has_wildcard = false;
                 let mut has_true = false;
                 let mut has_false = false;
+                let mut has_some = false;
+                let mut has_none = false;
                 let mut wildcard_seen = false;
 
                 for arm in arms {
@@ -280,8 +298,13 @@ impl TypeChecker {
                     }
 
                     match &arm.pattern.node {
-                        MatchPattern::Literal(Literal::Integer(_)) => {
-                            if scrutinee_ty != Ty::Field && scrutinee_ty != Ty::U32 {
+                        MatchPattern::Literal(Literal::Integer(n)) => {
+                            if scrutinee_ty == Ty::U32 && *n > u64::from(u32::MAX) {
+                                self.error(
+                                    format!("integer pattern {} does not fit in U32", n),
+                                    arm.pattern.span,
+                                );
+                            } else if scrutinee_ty != Ty::Field && scrutinee_ty != Ty::U32 {
                                 self.error(
                                     format!(
                                         "integer pattern requires Field or U32 scrutinee, got {}",
@@ -311,6 +334,22 @@ impl TypeChecker {
                             has_wildcard = true;
                             wildcard_seen = true;
                         }
+                        MatchPattern::Some(_) | MatchPattern::None => {
+                            if !matches!(scrutinee_ty, Ty::Option(_)) {
+                                self.error(
+                                    format!(
+                                        "option pattern requires an Option scrutinee, got {}",
+                                        scrutinee_ty.display()
+                                    ),
+                                    arm.pattern.span,
+                                );
+                            }
+                            if matches!(arm.pattern.node, MatchPattern::Some(_)) {
+                                has_some = true;
+                            } else {
+                                has_none = true;
+                            }
+                        }
                         MatchPattern::Struct { name, fields } => {
                             // Look up the struct type
                             if let Some(sty) = self.structs.get(&name.node).cloned() {
@@ -390,30 +429,76 @@ impl TypeChecker {
                                 }
                             }
                         }
-                        self.check_block(&arm.body.node);
+                        self.check_block(&arm.body);
+                        self.pop_scope();
+                    } else if let MatchPattern::Some(binding) = &arm.pattern.node {
+                        self.push_scope();
+                        if let Ty::Option(payload) = &scrutinee_ty {
+                            if binding.node != "_" {
+                                self.define_var(&binding.node, (**payload).clone(), false);
+                            }
+                        }
+                        self.check_block(&arm.body);
                         self.pop_scope();
                     } else {
-                        self.check_block(&arm.body.node);
+                        self.check_block(&arm.body);
                     }
                 }
 
                 // Exhaustiveness: require wildcard unless Bool with both true+false,
-                // or a struct pattern (structs have exactly one shape)
+                // an Option with both Some and None, or a struct pattern
+                // (structs have exactly one shape)
                 let has_struct_pattern = arms
                     .iter()
                     .any(|a| matches!(a.pattern.node, MatchPattern::Struct { .. }));
+                let is_option = matches!(scrutinee_ty, Ty::Option(_));
                 let exhaustive = has_wildcard
                     || (scrutinee_ty == Ty::Bool && has_true && has_false)
+                    || (is_option && has_some && has_none)
                     || has_struct_pattern;
                 if !exhaustive {
+                    let help = if is_option {
+                        "handle both `Some(x) => { ... }` and `None => { ... }`, or add a wildcard `_ => { ... }` arm"
+                    } else {
+                        "add a wildcard `_ => { ... }` arm to handle all remaining values"
+                    };
                     self.error_with_help(
                         "non-exhaustive match: not all possible values are covered".to_string(),
                         expr.span,
-                        "add a wildcard `_ => { ... }` arm to handle all remaining values"
-                            .to_string(),
+                        help.to_string(),
                     );
                 }
             }
         }
     }
 }
+
+/// The first `None` that sits in a tuple or array literal of `value`
+/// rather than directly in the value (possibly through `Some` or an `if`).
+/// Assignments size a `None` from the variable's width, which only
+/// covers the latter.
+fn none_in_literal(value: &Spanned<Expr>, in_literal: bool) -> Option<Span> {
+    if value.node.is_none() {
+        return in_literal.then_some(value.span);
+    }
+    match &value.node {
+        Expr::Tuple(elements) | Expr::ArrayInit(elements) => elements
+            .iter()
+            .find_map(|element| none_in_literal(element, true)),
+        Expr::If {
+            then_block,
+            else_block,
+            ..
+        } => [then_block, else_block].into_iter().find_map(|block| {
+            block
+                .node
+                .tail_expr
+                .as_deref()
+                .and_then(|tail| none_in_literal(tail, in_literal))
+        }),
+        _ => value
+            .node
+            .some_payload()
+            .and_then(|payload| none_in_literal(payload, in_literal)),
+    }
+}
@@ -76,6 +76,114 @@ fn test_match_struct_pattern_with_literal_field() {
     );
 }
 
+#[test]
+fn test_option_match_binds_the_payload() {
+    let result = check(
+        "program test\nfn find(x: Field) -> Option<U32> {\n    (if x == 0 { None } else { Some(as_u32(x)) })\n}\nfn main() {\n    let mut found: Option<U32> = None\n    found = find(pub_read())\n    match found {\n        Some(n) => { pub_write(as_field(n)) }\n        None => {}\n    }\n}",
+    );
+    assert!(
+        result.is_ok(),
+        "option construction and match should pass: {:?}",
+        result.err()
+    );
+}
+
+#[test]
+fn test_none_takes_its_type_from_a_tail_if() {
+    let result = check(
+        "program test\nfn nonzero(x: Field) -> Option<Field> {\n    if x == 0 { None } else if x == 1 { None } else { Some(x) }\n}\nfn main() {\n    match nonzero(pub_read()) {\n        Some(v) => { pub_write(v) }\n        None => {}\n    }\n}",
+    );
+    assert!(
+        result.is_ok(),
+        "a tail-position None should take the return type: {:?}",
+        result.err()
+    );
+    let errors = check_err(
+        "program test\nfn main() {\n    if pub_read() == 0 { None } else { Some(1) }\n}",
+    );
+    assert!(errors[0].message.contains("needs its Option type"));
+}
+
+#[test]
+fn test_option_match_must_cover_some_and_none() {
+    let errors = check_err(
+        "program test\nfn main() {\n    let o = Some(pub_read())\n    match o {\n        Some(v) => { pub_write(v) }\n    }\n}",
+    );
+    assert!(errors[0].message.contains("non-exhaustive match"));
+    assert!(errors[0].help.as_deref().unwrap_or("").contains("None =>"));
+}
+
+#[test]
+fn test_option_pattern_on_field_scrutinee_error() {
+    let errors = check_err(
+        "program test\nfn main() {\n    match pub_read() {\n        None => {}\n        _ => {}\n    }\n}",
+    );
+    assert!(errors[0].message.contains("requires an Option scrutinee"));
+}
+
+#[test]
+fn test_none_needs_a_declared_type() {
+    let errors = check_err("program test\nfn main() {\n    let o = None\n}");
+    assert!(errors[0].message.contains("needs its Option type"));
+    let errors = check_err(
+        "program test\nfn main() {\n    let mut p: (Option<Field>, Field) = (Some(1), 2)\n    p = (None, 3)\n}",
+    );
+    assert!(errors[0]
+        .message
+        .contains("inside a tuple or array literal"));
+}
+
+#[test]
+fn test_bytes_literals_are_range_checked() {
+    let result = check(
+        "program test\nfn main() {\n    let b: Bytes<3> = [0, 128, 255]\n    pub_write(as_field(b[1]))\n}",
+    );
+    assert!(
+        result.is_ok(),
+        "byte literals should pass: {:?}",
+        result.err()
+    );
+
+    let errors = check_err("program test\nfn main() {\n    let b: Bytes<2> = [1, 256]\n}");
+    assert!(errors[0]
+        .message
+        .contains("byte literal 256 is out of range"));
+}
+
+#[test]
+fn test_bytes_need_std_bytes_for_runtime_values() {
+    let errors = check_err(
+        "program test\nfn main() {\n    let x: U32 = as_u32(pub_read())\n    let b: Bytes<2> = [x, 1]\n}",
+    );
+    assert!(errors[0].message.contains("Bytes element is not a literal"));
+    assert!(errors[0]
+        .help
+        .as_deref()
+        .unwrap_or("")
+        .contains("std.bytes.from_u32s"));
+
+    let errors = check_err(
+        "program test\nfn main() {\n    let w: [U32; 2] = [1, 2]\n    let b: Bytes<2> = w\n}",
+    );
+    assert!(errors[0].message.contains("declared Bytes<2>"));
+}
+
+#[test]
+fn test_ensure_code_must_be_constant() {
+    let result = check(
+        "program test\nconst E_BAD: U32 = 3\nfn main() {\n    ensure(pub_read() == 1, E_BAD)\n    ensure(pub_read() == 2, 4)\n}",
+    );
+    assert!(
+        result.is_ok(),
+        "constant codes should pass: {:?}",
+        result.err()
+    );
+    let errors = check_err(
+        "program test\nfn main() {\n    let code = as_u32(pub_read())\n    ensure(pub_read() == 1, code)\n}",
+    );
+    assert!(errors[0].message.contains("error code must be a constant"));
+}
+
 // --- #[test] function validation ---
 
 #[test]
@@ -213,6 +321,35 @@ fn test_error_recursion_has_help() {
     );
 }
 
+#[test]
+fn test_recursion_reports_only_the_cycle() {
+    let diags = check_err(
+        "program test\nfn a() {\n    b()\n}\nfn b() {\n    pub_write(1)\n    a()\n}\nfn main() {\n    a()\n}",
+    );
+    let cycles: Vec<&str> = diags
+        .iter()
+        .filter(|d| d.message.contains("recursive call cycle"))
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(cycles, ["recursive call cycle detected: a -> b -> a"]);
+    assert!(diags[0]
+        .help
+        .as_deref()
+        .is_some_and(|h| h.contains("bounded")));
+
+    // A call in an index expression is still a call
+    let diags = check_err(
+        "program test\nfn f(i: U32) -> U32 {\n    let a: [U32; 2] = [i, i]\n    a[f(i)]\n}\nfn main() {\n}",
+    );
+    assert!(
+        diags
+            .iter()
+            .any(|d| d.message == "recursive call cycle detected: 'f' calls itself"),
+        "{:?}",
+        diags.iter().map(|d| &d.message).collect::<Vec<_>>()
+    );
+}
+
 #[test]
 fn test_error_non_exhaustive_match_has_help() {
     let diags = check_err(
@@ -417,3 +554,131 @@ fn test_pure_fn_rejects_sponge_init() {
         .iter()
         .any(|d| d.message.contains("#[pure]") && d.message.contains("sponge_init")));
 }
+
+#[test]
+fn test_const_folds_pure_call() {
+    let sq = "program test\n#[pure]\nfn sq(x: Field) -> Field {\n    x * x\n}\n";
+    let folded = format!(
+        "{}const K: Field = sq(12)\nfn main() {{\n    pub_write(K)\n}}",
+        sq
+    );
+    assert!(check(&folded).is_ok());
+    let diags = check_err(&format!("{}const N: U32 = sq(70000)\nfn main() {{}}", sq));
+    let msg = &diags[0].message;
+    assert!(
+        msg.contains("constant 'N' = 4900000000 does not fit in U32"),
+        "{}",
+        msg
+    );
+    let diags = check_err("program test\nconst K: Field = pub_read()\nfn main() {}");
+    let msg = &diags[0].message;
+    assert!(
+        msg.contains("constant 'K' is not a compile-time value"),
+        "{}",
+        msg
+    );
+}
+
+#[test]
+fn test_generated_table() {
+    let sq = "program test\n#[pure]\nfn sq(i: Field) -> Field {\n    i * i\n}\n";
+    let ok = format!(
+        "{}const T: [Field; 4] = generate!(sq)\nfn main() {{\n    pub_write(T[pub_read()])\n}}",
+        sq
+    );
+    assert!(check(&ok).is_ok(), "{:?}", check_err(&ok));
+
+    let diags = check_err(&format!(
+        "{}const T: Field = generate!(sq)\nfn main() {{}}",
+        sq
+    ));
+    assert!(
+        diags[0]
+            .message
+            .contains("must be an array of Field, U32 or Bool"),
+        "{}",
+        diags[0].message
+    );
+    let diags = check_err(&format!(
+        "{}const T: [U32; 4] = generate!(sq)\nfn main() {{}}",
+        sq
+    ));
+    assert!(
+        diags[0].message.contains("returns Field"),
+        "{}",
+        diags[0].message
+    );
+    let diags = check_err(
+        "program test\nfn sq(i: Field) -> Field {\n    i * i\n}\nconst T: [Field; 4] = generate!(sq)\nfn main() {}",
+    );
+    assert!(
+        diags[0]
+            .message
+            .contains("constant 'T' could not be generated by 'sq'"),
+        "{}",
+        diags[0].message
+    );
+}
+
+#[test]
+fn test_composite_constants() {
+    let ok = "program test\nstruct P {\n    x: Field,\n    y: U32,\n}\nconst ORIGIN: P = P { x: 1, y: 2 }\nconst ROOT: Digest = [1, 2, 3, 4, 5]\nconst DIMS: [U32; 2] = [3, 4]\nconst N: U32 = DIMS[1]\nfn main() {\n    let a: [Field; 2] = [ORIGIN.x, 0]\n    let n: U32 = N\n    let y: U32 = ORIGIN.y\n    let d: Digest = ROOT\n    pub_write(a[0])\n}";
+    assert!(check(ok).is_ok(), "{:?}", check_err(ok));
+
+    let diags = check_err("program test\nconst ROOT: Digest = [1, 2, 3]\nfn main() {}");
+    assert!(
+        diags[0]
+            .message
+            .contains("constant 'ROOT' is declared Digest but its value has type [Field; 3]"),
+        "{}",
+        diags[0].message
+    );
+    let diags = check_err(
+        "program test\nfn f(x: Field) -> Field {\n    x\n}\nconst T: [Field; 2] = [f(1), 2]\nfn main() {}",
+    );
+    assert!(
+        diags[0]
+            .message
+            .contains("constant 'T' is not a compile-time value"),
+        "{}",
+        diags[0].message
+    );
+}
+
+#[test]
+fn test_constant_condition_warnings() {
+    let exports = check(
+        "program test\nconst LIMIT: U32 = 4\nfn main() {\n    let x: U32 = 3\n    assert(x < LIMIT)\n    for i in 0..4 {\n        if i == 9 {\n            pub_write(0)\n        }\n    }\n}",
+    )
+    .unwrap();
+    let messages: Vec<&str> = exports
+        .warnings
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        vec!["assertion is always true", "condition is always false"]
+    );
+}
+
+#[test]
+fn test_constant_condition_ignores_literals_and_mutation() {
+    let exports = check(
+        "program test\nfn main() {\n    let mut x: U32 = 3\n    x = as_u32(pub_read())\n    assert(x < 4)\n    assert(false)\n}",
+    )
+    .unwrap();
+    assert!(exports.warnings.is_empty(), "{:?}", exports.warnings);
+}
+
+#[test]
+fn test_allow_constant_condition() {
+    let exports = check(
+        "program test\n#[allow(constant_condition)]\nfn main() {\n    let x: Field = 1\n    assert(x == 1)\n}",
+    )
+    .unwrap();
+    assert!(exports.warnings.is_empty(), "{:?}", exports.warnings);
+
+    let exports = check("program test\n#[allow(no_such_lint)]\nfn main() {\n}").unwrap();
+    assert_eq!(exports.warnings[0].message, "unknown lint 'no_such_lint'");
+}
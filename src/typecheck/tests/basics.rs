@@ -1,6 +1,6 @@
 //! TypeChecker unit tests.
 
-use super::{check, check_with_flags};
+use super::{check, check_err, check_with_flags};
 
 #[test]
 fn test_valid_field_arithmetic() {
@@ -62,6 +62,63 @@ fn test_tuple_destructure_arity_mismatch() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_named_tuple_component_access() {
+    let result = check("program test\nfn divmod(a: U32, b: U32) -> (q: U32, r: U32) {\n    a /% b\n}\nfn main() {\n    let res = divmod(as_u32(pub_read()), as_u32(pub_read()))\n    pub_write(as_field(res.q))\n    pub_write(as_field(res.r))\n}");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_named_tuple_is_structurally_typed() {
+    // Named and unnamed tuples with the same component types are interchangeable.
+    let result = check("program test\nfn pair() -> (a: Field, b: Field) {\n    (pub_read(), pub_read())\n}\nfn sum(p: (x: Field, y: Field)) -> Field {\n    p.x + p.y\n}\nfn main() {\n    let (a, b): (Field, Field) = pair()\n    let p: (Field, Field) = pair()\n    pub_write(sum(p) + sum((a, b)))\n}");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_named_tuple_unknown_component() {
+    let result = check("program test\nfn pair() -> (a: Field, b: Field) {\n    (pub_read(), pub_read())\n}\nfn main() {\n    let p = pair()\n    pub_write(p.c)\n}");
+    let errors = result.unwrap_err();
+    assert!(errors[0].message.contains("has no component 'c'"));
+}
+
+#[test]
+fn test_named_tuple_component_types_must_match() {
+    let result = check(
+        "program test\nfn main() {\n    let p: (a: Field, b: U32) = (pub_read(), pub_read())\n}",
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_if_expression_branches_must_match() {
+    let result = check("program test\nfn main() {\n    let p: Bool = pub_read() == 1\n    let x: Field = if p { pub_read() } else { as_u32(pub_read()) }\n}");
+    let errors = result.unwrap_err();
+    assert!(errors[0].message.contains("different types"));
+}
+
+#[test]
+fn test_if_expression_literal_branches_take_annotated_type() {
+    let result = check("program test\nfn main() {\n    let p: Bool = pub_read() == 1\n    let fee: U32 = if p { 10 } else { 2 }\n    pub_write(as_field(fee))\n}");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_if_expression_condition_must_be_bool_or_field() {
+    let result = check("program test\nfn main() {\n    let c: U32 = as_u32(pub_read())\n    let x: Field = if c { 1 } else { 2 }\n}");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_logical_operators_require_bool() {
+    let ok = check("program test\nfn main() {\n    let a: Field = pub_read()\n    let b: Bool = a == 0 || a == 1 && a == 2\n    assert(b)\n}");
+    assert!(ok.is_ok());
+    let err = check("program test\nfn main() {\n    let a: Field = pub_read()\n    let b: Bool = a && a == 1\n}");
+    assert!(err.unwrap_err()[0]
+        .message
+        .contains("requires Bool operands"));
+}
+
 #[test]
 fn test_reveal_valid() {
     let result = check("program test\nevent Transfer { from: Field, to: Field, amount: Field }\nfn main() {\n    reveal Transfer { from: pub_read(), to: pub_read(), amount: pub_read() }\n}");
@@ -228,6 +285,38 @@ fn test_h0003_no_false_positive() {
     assert!(!h0003, "should not warn on first as_u32 call");
 }
 
+#[test]
+fn test_inv_of_unproven_value_warns() {
+    let result =
+        check("program test\nfn main() {\n    let a: Field = pub_read()\n    pub_write(inv(a))\n}");
+    let exports = result.unwrap();
+    assert!(
+        exports
+            .warnings
+            .iter()
+            .any(|w| w.message.contains("not proven nonzero")),
+        "expected a zero-divisor warning, got: {:?}",
+        exports.warnings
+    );
+}
+
+#[test]
+fn test_inv_of_proven_nonzero_value_does_not_warn() {
+    // A literal, the else branch of `a == 0`, an inverse, and a divisor
+    // already asserted by checked_div are all nonzero.
+    let result = check(
+        "program test\nfn main() {\n    let a: Field = pub_read()\n    let b: Field = pub_read()\n    pub_write(inv(2))\n    if a == 0 {\n        pub_write(0)\n    } else {\n        pub_write(inv(a))\n    }\n    let q: Field = checked_div(a, b)\n    let r: Field = inv(q + 1)\n    pub_write(inv(b))\n}",
+    );
+    let exports = result.unwrap();
+    let warnings: Vec<_> = exports
+        .warnings
+        .iter()
+        .filter(|w| w.message.contains("not proven nonzero"))
+        .collect();
+    // Only inv(q + 1) is unproven.
+    assert_eq!(warnings.len(), 1, "got: {:?}", warnings);
+}
+
 #[test]
 fn test_asm_block_type_checks() {
     // asm blocks should pass type checking without errors
@@ -317,6 +406,47 @@ fn test_generic_fn_multiple_instantiations() {
     );
 }
 
+#[test]
+fn test_where_bound_accepts_satisfying_instantiation() {
+    let result = check(
+        "program test\nfn first<N>(arr: [Field; N]) -> Field where N <= 4, N > 0 {\n    arr[0]\n}\nfn main() {\n    let a: [Field; 3] = [1, 2, 3]\n    pub_write(first(a))\n}",
+    );
+    assert!(result.is_ok(), "N = 3 satisfies N <= 4: {:?}", result.err());
+}
+
+#[test]
+fn test_where_bound_violation_reports_bound_and_sizes() {
+    let diags = check_err(
+        "program test\nfn pair<M, N>(a: [Field; M], b: [Field; N]) -> Field where M + N <= 4 {\n    a[0]\n}\nfn main() {\n    let a: [Field; 3] = [1, 2, 3]\n    pub_write(pair(a, a))\n}",
+    );
+    assert_eq!(diags.len(), 1, "{:?}", diags);
+    assert_eq!(
+        diags[0].message,
+        "call to 'pair' violates `where M + N <= 4`"
+    );
+    assert_eq!(diags[0].notes, vec!["instantiated with M = 3, N = 3"]);
+}
+
+#[test]
+fn test_where_bound_checked_for_explicit_size_args() {
+    let diags = check_err(
+        "program test\nfn first<N>(arr: [Field; N]) -> Field where N < 2 {\n    arr[0]\n}\nfn main() {\n    let a: [Field; 3] = [1, 2, 3]\n    pub_write(first<3>(a))\n}",
+    );
+    assert!(diags
+        .iter()
+        .any(|d| d.message.contains("violates `where N < 2`")));
+}
+
+#[test]
+fn test_where_clause_rejects_unknown_size_parameter() {
+    let diags = check_err(
+        "program test\nfn first<N>(arr: [Field; N]) -> Field where M >= 1 {\n    arr[0]\n}\nfn main() {\n}",
+    );
+    assert!(diags
+        .iter()
+        .any(|d| d.message.contains("unknown size parameter 'M'")));
+}
+
 #[test]
 fn test_generic_fn_non_generic_with_size_args_fails() {
     // Calling a non-generic function with size args should error
@@ -426,3 +556,61 @@ fn test_match_bool_pattern_on_field_error() {
         "boolean pattern on Field scrutinee should fail"
     );
 }
+
+#[test]
+fn test_builtin_table_matches_registered_builtins() {
+    let tc = crate::typecheck::TypeChecker::new();
+    for name in tc.functions.keys() {
+        assert!(
+            crate::typecheck::builtin_info(name).is_some(),
+            "{} is registered but missing from BUILTINS",
+            name
+        );
+    }
+    for b in crate::typecheck::BUILTINS {
+        assert!(
+            tc.builtin_params(b.name).is_some(),
+            "{} is in BUILTINS but not registered for triton",
+            b.name
+        );
+    }
+}
+
+#[test]
+fn test_integer_literal_takes_expected_u32_type() {
+    let result = check("program test\nstruct Limits {\n    max: U32,\n}\nfn clamp(x: U32, max: U32) -> U32 {\n    x\n}\nfn main() {\n    let mut x: U32 = 0\n    x = 7\n    let y: U32 = as_u32(pub_read())\n    let l: Limits = Limits { max: 255 }\n    let pair: (U32, Field) = (1, 2)\n    let arr: [U32; 2] = [3, 4]\n    if y < 10 {\n        pub_write(as_field(clamp(y & 255, 100)))\n    }\n    assert(y == 3)\n}");
+    assert!(result.is_ok(), "{:?}", result.err());
+}
+
+#[test]
+fn test_integer_literal_stays_field_without_u32_context() {
+    let result = check(
+        "program test\nfn main() {\n    let x = 5\n    let y: Field = x + 1\n    pub_write(y)\n}",
+    );
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_u32_literal_out_of_range() {
+    let errors = check_err("program test\nfn main() {\n    let x: U32 = 4294967296\n}");
+    assert!(errors[0].message.contains("does not fit in U32"));
+    let errors = check_err("program test\nfn id(x: U32) -> U32 {\n    x\n}\nfn main() {\n    let y: U32 = id(99999999999)\n}");
+    assert!(errors[0].message.contains("does not fit in U32"));
+    let errors = check_err("program test\nconst BIG: U32 = 5000000000\nfn main() {\n}");
+    assert!(errors[0].message.contains("does not fit in U32"));
+    assert!(check("program test\nfn main() {\n    let x: U32 = 4294967295\n}").is_ok());
+}
+
+#[test]
+fn test_composite_equality_typechecks() {
+    let result = check("program test\nstruct Point {\n    x: Field,\n    y: Field,\n}\nfn main() {\n    let a: Digest = divine5()\n    let b: Digest = divine5()\n    assert(a == b)\n    let p: Point = Point { x: 1, y: 2 }\n    let q: Point = Point { x: 1, y: 2 }\n    let arr: [Field; 3] = [1, 2, 3]\n    let same: Bool = p == q && arr == [1, 2, 3]\n}");
+    assert!(result.is_ok(), "{:?}", result.err());
+}
+
+#[test]
+fn test_equality_wider_than_stack_reach_is_rejected() {
+    let diags = check_err("program test\nfn main() {\n    let a: [Field; 15] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]\n    assert(a == a)\n}");
+    assert!(diags.iter().any(|d| d
+        .message
+        .contains("operator '==' on [Field; 15] compares 15 elements")));
+}
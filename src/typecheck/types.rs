@@ -1,5 +1,5 @@
 /// Semantic types used by the type checker (distinct from AST syntactic types).
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Eq)]
 pub enum Ty {
     Field,
     /// Extension field element — width in base field elements (e.g. 3 for Triton's cubic extension).
@@ -10,10 +10,37 @@ pub enum Ty {
     Digest(u32),
     Array(Box<Ty>, u64),
     Tuple(Vec<Ty>),
+    /// Tuple with named components. Typed structurally: equal to any
+    /// tuple with the same component types, whatever the names.
+    NamedTuple(Vec<(String, Ty)>),
+    /// Optional value: a Bool flag followed by the payload.
+    Option(Box<Ty>),
+    /// `Bytes<N>`: N elements, each a `U32` below 256.
+    Bytes(u64),
     Struct(StructTy),
     Unit,
 }
 
+impl PartialEq for Ty {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Ty::Field, Ty::Field)
+            | (Ty::Bool, Ty::Bool)
+            | (Ty::U32, Ty::U32)
+            | (Ty::Unit, Ty::Unit) => true,
+            (Ty::XField(a), Ty::XField(b)) | (Ty::Digest(a), Ty::Digest(b)) => a == b,
+            (Ty::Array(a, n), Ty::Array(b, m)) => n == m && a == b,
+            (Ty::Option(a), Ty::Option(b)) => a == b,
+            (Ty::Bytes(n), Ty::Bytes(m)) => n == m,
+            (Ty::Struct(a), Ty::Struct(b)) => a == b,
+            _ => match (self.tuple_parts(), other.tuple_parts()) {
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            },
+        }
+    }
+}
+
 /// A resolved struct type with field layout.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StructTy {
@@ -56,6 +83,9 @@ impl Ty {
                 inner.width().saturating_mul(len)
             }
             Ty::Tuple(elems) => elems.iter().map(|t| t.width()).sum(),
+            Ty::NamedTuple(components) => components.iter().map(|(_, t)| t.width()).sum(),
+            Ty::Option(inner) => 1 + inner.width(),
+            Ty::Bytes(n) => u32::try_from(*n).unwrap_or(u32::MAX),
             Ty::Struct(s) => s.width(),
             Ty::Unit => 0,
         }
@@ -73,8 +103,42 @@ impl Ty {
                 let parts: Vec<_> = elems.iter().map(|t| t.display()).collect();
                 format!("({})", parts.join(", "))
             }
+            Ty::NamedTuple(components) => {
+                let parts: Vec<_> = components
+                    .iter()
+                    .map(|(name, t)| format!("{}: {}", name, t.display()))
+                    .collect();
+                format!("({})", parts.join(", "))
+            }
+            Ty::Option(inner) => format!("Option<{}>", inner.display()),
+            Ty::Bytes(n) => format!("Bytes<{}>", n),
             Ty::Struct(s) => s.name.clone(),
             Ty::Unit => "()".to_string(),
         }
     }
+
+    /// Component types of a tuple, named or not.
+    pub fn tuple_elements(&self) -> Option<Vec<Ty>> {
+        self.tuple_parts()
+            .map(|parts| parts.into_iter().cloned().collect())
+    }
+
+    fn tuple_parts(&self) -> Option<Vec<&Ty>> {
+        match self {
+            Ty::Tuple(elems) => Some(elems.iter().collect()),
+            Ty::NamedTuple(components) => Some(components.iter().map(|(_, t)| t).collect()),
+            _ => None,
+        }
+    }
+
+    /// Type of a named tuple component.
+    pub fn component(&self, name: &str) -> Option<Ty> {
+        let Ty::NamedTuple(components) = self else {
+            return None;
+        };
+        components
+            .iter()
+            .find(|(component, _)| component == name)
+            .map(|(_, ty)| ty.clone())
+    }
 }
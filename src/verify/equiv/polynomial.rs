@@ -74,7 +74,9 @@ pub(crate) fn normalize_polynomial(val: &SymValue) -> Option<Vec<PolyTerm>> {
         | SymValue::Divine(_)
         | SymValue::PubInput(_)
         | SymValue::Ite(_, _, _)
-        | SymValue::FieldAccess(_, _) => None,
+        | SymValue::FieldAccess(_, _)
+        | SymValue::Select(_, _)
+        | SymValue::Array(_) => None,
     }
 }
 
@@ -4,3 +4,4 @@ pub mod smt;
 pub mod solve;
 pub mod sym;
 pub mod synthesize;
+pub mod witness;
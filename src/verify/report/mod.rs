@@ -7,7 +7,7 @@
 use crate::solve::{format_constraint, Counterexample, Verdict, VerificationReport};
 #[cfg(test)]
 use crate::sym::SymValue;
-use crate::sym::{Constraint, ConstraintSystem};
+use crate::sym::{Constraint, ConstraintSystem, LoopLimit};
 
 // ─── Data Structures ───────────────────────────────────────────────
 
@@ -21,6 +21,8 @@ pub struct JsonReport {
     pub constraints: Vec<JsonConstraint>,
     pub counterexamples: Vec<JsonCounterexample>,
     pub redundant_assertions: Vec<usize>,
+    pub loop_limits: Vec<LoopLimit>,
+    pub assumptions: Vec<String>,
     pub suggestions: Vec<JsonSuggestion>,
 }
 
@@ -191,6 +193,8 @@ pub fn generate_json_report(
         constraints,
         counterexamples,
         redundant_assertions: report.redundant_assertions.clone(),
+        loop_limits: report.loop_limits.clone(),
+        assumptions: report.assumptions.clone(),
         suggestions,
     };
 
@@ -279,6 +283,27 @@ fn serialize_report(r: &JsonReport) -> String {
     }
     out.push_str("],\n");
 
+    // loop_limits
+    out.push_str("  \"loop_limits\": [\n");
+    for (i, limit) in r.loop_limits.iter().enumerate() {
+        out.push_str(&serialize_loop_limit(limit));
+        if i + 1 < r.loop_limits.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str("  ],\n");
+
+    // assumptions
+    out.push_str("  \"assumptions\": [");
+    for (i, assumption) in r.assumptions.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(&format!("\"{}\"", json_escape(assumption)));
+    }
+    out.push_str("],\n");
+
     // suggestions
     out.push_str("  \"suggestions\": [\n");
     for (i, s) in r.suggestions.iter().enumerate() {
@@ -334,6 +359,22 @@ fn serialize_counterexample(ce: &JsonCounterexample) -> String {
     out
 }
 
+fn serialize_loop_limit(limit: &LoopLimit) -> String {
+    let mut out = String::new();
+    out.push_str("    {\n");
+    out.push_str(&json_str(6, "function", &limit.function));
+    out.push_str(",\n");
+    out.push_str(&json_str(6, "var", &limit.var));
+    out.push_str(",\n");
+    out.push_str(&format!("      \"unrolled\": {},\n", limit.unrolled));
+    match limit.bound {
+        Some(bound) => out.push_str(&format!("      \"bound\": {}\n", bound)),
+        None => out.push_str("      \"bound\": null\n"),
+    }
+    out.push_str("    }");
+    out
+}
+
 fn serialize_suggestion(s: &JsonSuggestion) -> String {
     let mut out = String::new();
     out.push_str("    {\n");
@@ -5,7 +5,7 @@
 //! unconstrained divine inputs).
 
 use crate::solve::{format_constraint, VerificationReport};
-use crate::sym::{Constraint, ConstraintSystem, SymValue};
+use crate::sym::{Constraint, ConstraintSystem, SymArray, SymValue};
 
 use super::JsonSuggestion;
 
@@ -150,5 +150,26 @@ fn collect_divine_refs_value(v: &SymValue, out: &mut Vec<String>) {
         SymValue::FieldAccess(inner, _) => {
             collect_divine_refs_value(inner, out);
         }
+        SymValue::Select(array, index) => {
+            collect_divine_refs_array(array, out);
+            collect_divine_refs_value(index, out);
+        }
+        SymValue::Array(array) => collect_divine_refs_array(array, out),
+    }
+}
+
+fn collect_divine_refs_array(a: &SymArray, out: &mut Vec<String>) {
+    match a {
+        SymArray::Base(_) => {}
+        SymArray::Store(inner, index, value) => {
+            collect_divine_refs_array(inner, out);
+            collect_divine_refs_value(index, out);
+            collect_divine_refs_value(value, out);
+        }
+        SymArray::Ite(c, t, e) => {
+            collect_divine_refs_value(c, out);
+            collect_divine_refs_array(t, out);
+            collect_divine_refs_array(e, out);
+        }
     }
 }
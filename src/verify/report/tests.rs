@@ -172,3 +172,28 @@ fn test_static_violation_in_json() {
     assert!(json.contains("\"verdict\": \"unsafe\""));
     assert!(json.contains("\"static_violations\": 1"));
 }
+
+#[test]
+fn test_json_loop_limits() {
+    let (system, report) = verify_source(
+        "program test\nfn main() {\n    let n: Field = pub_read()\n    for i in 0..n bounded 100 {\n        pub_write(i)\n    }\n}\n",
+    );
+    let json = generate_json_report("test.tri", &system, &report);
+    assert!(json.contains(
+        "  \"loop_limits\": [\n    {\n      \"function\": \"main\",\n      \"var\": \"i\",\n      \"unrolled\": 64,\n      \"bound\": 100\n    }\n  ],\n"
+    ));
+}
+
+#[test]
+fn test_json_assumptions() {
+    let (system, report) = verify_source(
+        "program test\nfn main() {\n    let x: Field = pub_read()\n    assume(x == 5)\n    assert_eq(x + 1, 6)\n}\n",
+    );
+    assert_eq!(report.verdict, Verdict::Safe);
+    let json = generate_json_report("test.tri", &system, &report);
+    assert!(
+        json.contains("  \"assumptions\": [\"(pub_in_0 == 5)\"],\n"),
+        "{}",
+        json
+    );
+}
@@ -7,6 +7,8 @@
 //! - Field elements as 128-bit bitvectors (to handle multiplication overflow)
 //! - All operations mod p (p = 2^64 - 2^32 + 1)
 //! - Equality checks, range constraints, conditional assertions
+//! - RAM and array values as SMT arrays from field to field
+//!   (`select`/`store`, logic `QF_ABV`)
 //!
 //! The encoder produces two kinds of queries:
 //! 1. **Safety check**: Is there an assignment that violates any constraint?
@@ -14,7 +16,7 @@
 //! 2. **Witness existence**: For divine inputs, does a valid witness exist?
 //!    (check-sat on all constraints)
 
-use crate::sym::{Constraint, ConstraintSystem, SymValue, GOLDILOCKS_P};
+use crate::sym::{Constraint, ConstraintSystem, SymArray, SymValue, GOLDILOCKS_P};
 use std::collections::BTreeSet;
 
 /// Generate SMT-LIB2 encoding of a constraint system.
@@ -84,7 +86,11 @@ impl SmtEncoder {
         self.emit(&format!("; Variables: {}", system.num_variables));
         self.emit(&format!("; Constraints: {}", system.constraints.len()));
         self.emit("");
-        self.emit("(set-logic QF_BV)");
+        if system.arrays.is_empty() {
+            self.emit("(set-logic QF_BV)");
+        } else {
+            self.emit("(set-logic QF_ABV)");
+        }
         self.emit("");
 
         // Define the Goldilocks prime as a constant
@@ -113,6 +119,16 @@ impl SmtEncoder {
         }
         self.emit("");
 
+        // Assumptions hold in both modes: they narrow the inputs considered
+        if !system.assumptions.is_empty() {
+            self.emit("; Assumptions (from assume)");
+            for assumption in &system.assumptions {
+                let smt = self.encode_constraint(assumption);
+                self.emit(&format!("(assert {})", smt));
+            }
+            self.emit("");
+        }
+
         // Encode constraints
         match self.mode {
             QueryMode::SafetyCheck => {
@@ -206,6 +222,12 @@ impl SmtEncoder {
         for name in &var_names {
             self.emit(&format!("(declare-fun {} () (_ BitVec 128))", name));
         }
+        for name in &system.arrays {
+            self.emit(&format!(
+                "(declare-fun {} () (Array (_ BitVec 128) (_ BitVec 128)))",
+                array_smt_name(name)
+            ));
+        }
     }
 
     fn encode_constraint(&mut self, constraint: &Constraint) -> String {
@@ -343,10 +365,40 @@ impl SmtEncoder {
                 }
                 name
             }
+            SymValue::Select(array, index) => {
+                let sa = self.encode_array(array);
+                let si = self.encode_value(index);
+                // Initial contents are unconstrained; reduce into the field.
+                format!("(field_mod (select {} {}))", sa, si)
+            }
+            SymValue::Array(array) => self.encode_array(array),
+        }
+    }
+
+    fn encode_array(&mut self, array: &SymArray) -> String {
+        match array {
+            SymArray::Base(name) => array_smt_name(name),
+            SymArray::Store(inner, index, value) => {
+                let sa = self.encode_array(inner);
+                let si = self.encode_value(index);
+                let sv = self.encode_value(value);
+                format!("(store {} {} {})", sa, si, sv)
+            }
+            SymArray::Ite(cond, a, b) => {
+                let sc = self.encode_value(cond);
+                let sa = self.encode_array(a);
+                let sb = self.encode_array(b);
+                format!("(ite (not (= {} (_ bv0 128))) {} {})", sc, sa, sb)
+            }
         }
     }
 }
 
+/// SMT name of a base array, kept apart from scalar variables.
+fn array_smt_name(name: &str) -> String {
+    format!("__arr_{}", sanitize_smt_name(name))
+}
+
 /// Sanitize a variable name for SMT-LIB2 (replace dots with underscores, etc.).
 fn sanitize_smt_name(name: &str) -> String {
     let sanitized: String = name
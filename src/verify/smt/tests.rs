@@ -73,3 +73,25 @@ fn test_empty_constraints() {
     );
     assert!(smt.contains("No constraints"));
 }
+
+#[test]
+fn test_assumptions_asserted_before_the_query() {
+    let smt = parse_and_encode(
+        "program test\nfn main() {\n    let x: Field = pub_read()\n    assume(x == 5)\n    assert_eq(x, 5)\n}\n",
+        QueryMode::SafetyCheck,
+    );
+    let assumptions = smt.find("; Assumptions (from assume)").unwrap();
+    let query = smt.find("; Safety check").unwrap();
+    assert!(assumptions < query);
+}
+
+#[test]
+fn test_ram_encoded_with_array_theory() {
+    let smt = parse_and_encode(
+        "program test\nfn main() {\n    let a: Field = pub_read()\n    ram_write(a, 3)\n    assert_eq(ram_read(pub_read()), 3)\n}\n",
+        QueryMode::SafetyCheck,
+    );
+    assert!(smt.contains("(set-logic QF_ABV)"));
+    assert!(smt.contains("(declare-fun __arr_ram () (Array (_ BitVec 128) (_ BitVec 128)))"));
+    assert!(smt.contains("(select (store __arr_ram pub_in_0 (_ bv3 128)) pub_in_1)"));
+}
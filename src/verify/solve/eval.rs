@@ -157,9 +157,53 @@ impl<'a> Evaluator<'a> {
                 // Field access on a symbolic value — cannot evaluate concretely
                 None
             }
+            SymValue::Select(array, index) => {
+                let index = self.eval(index)?;
+                self.eval_element(array, index)
+            }
+            // Whole arrays have no single field value
+            SymValue::Array(_) => None,
+        }
+    }
+
+    /// Element `index` of a symbolic array. Base arrays take their
+    /// elements from a per-array seed in the assignments.
+    fn eval_element(&self, array: &SymArray, index: u64) -> Option<u64> {
+        match array {
+            SymArray::Base(name) => {
+                let seed = self.assignments.get(name).copied()?;
+                let h = seed
+                    .wrapping_mul(0x517CC1B727220A95)
+                    .wrapping_add(index)
+                    .wrapping_mul(0x6C62272E07BB0142);
+                Some(h % GOLDILOCKS_P)
+            }
+            SymArray::Store(inner, at, value) => {
+                if self.eval(at)? == index {
+                    self.eval(value)
+                } else {
+                    self.eval_element(inner, index)
+                }
+            }
+            SymArray::Ite(cond, a, b) => {
+                if self.eval(cond)? != 0 {
+                    self.eval_element(a, index)
+                } else {
+                    self.eval_element(b, index)
+                }
+            }
         }
     }
 
+    /// Whether the assignments break an `assume(...)`. Such assignments
+    /// describe runs the program promises never happen, so they are
+    /// skipped rather than reported.
+    pub(crate) fn excluded_by(&self, assumptions: &[Constraint]) -> bool {
+        assumptions
+            .iter()
+            .any(|a| self.check_constraint(a) == Some(false))
+    }
+
     /// Check if a constraint is satisfied under current assignments.
     /// Returns: Some(true) if satisfied, Some(false) if violated, None if unevaluable.
     pub(crate) fn check_constraint(&self, c: &Constraint) -> Option<bool> {
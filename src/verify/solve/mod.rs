@@ -22,7 +22,7 @@
 use std::collections::BTreeMap;
 
 use crate::field::goldilocks::MODULUS as GOLDILOCKS_P;
-use crate::sym::{Constraint, ConstraintSystem, SymValue};
+use crate::sym::{Constraint, ConstraintSystem, LoopLimit, SymArray, SymValue};
 
 mod eval;
 mod solver;
@@ -134,6 +134,11 @@ pub struct VerificationReport {
     pub redundant_assertions: Vec<usize>,
     /// Hash-dependent constraints (witness-required, not testable by random sampling).
     pub witness_required: usize,
+    /// Loops not unrolled to their bound; the verdict covers only the
+    /// iterations explored.
+    pub loop_limits: Vec<LoopLimit>,
+    /// `assume(...)` facts the checks relied on, formatted.
+    pub assumptions: Vec<String>,
     /// Overall verdict.
     pub verdict: Verdict,
 }
@@ -203,6 +208,30 @@ impl VerificationReport {
         }
         report.push('\n');
 
+        // Unroll limits
+        if !self.loop_limits.is_empty() {
+            report.push_str(&format!(
+                "Bound reached: {} loop(s) not fully unrolled\n",
+                self.loop_limits.len()
+            ));
+            for limit in &self.loop_limits {
+                report.push_str(&format!("  - {}\n", limit));
+            }
+            report.push_str("  Later iterations were not checked.\n\n");
+        }
+
+        // Assumptions
+        if !self.assumptions.is_empty() {
+            report.push_str(&format!(
+                "Assumed: {} fact(s) from assume()\n",
+                self.assumptions.len()
+            ));
+            for assumption in &self.assumptions {
+                report.push_str(&format!("  - {}\n", assumption));
+            }
+            report.push_str("  Inputs breaking these were not checked.\n\n");
+        }
+
         // Verdict
         let verdict_str = match &self.verdict {
             Verdict::Safe => "SAFE — no violations found",
@@ -212,7 +241,14 @@ impl VerificationReport {
             }
             Verdict::BmcViolation => "UNSAFE — bounded model checking found violations",
         };
-        report.push_str(&format!("Verdict: {}\n", verdict_str));
+        if self.verdict == Verdict::Safe && !self.loop_limits.is_empty() {
+            report.push_str(&format!(
+                "Verdict: {} (up to the bounds above)\n",
+                verdict_str
+            ));
+        } else {
+            report.push_str(&format!("Verdict: {}\n", verdict_str));
+        }
 
         report
     }
@@ -270,6 +306,8 @@ pub fn verify(system: &ConstraintSystem) -> VerificationReport {
         bmc_result,
         redundant_assertions: redundant,
         witness_required,
+        loop_limits: system.loop_limits.clone(),
+        assumptions: system.assumptions.iter().map(format_assumption).collect(),
         verdict,
     }
 }
@@ -304,6 +342,12 @@ fn collect_variables(system: &ConstraintSystem) -> Vec<String> {
             names.push(key);
         }
     }
+    // Base arrays get one seed each (see `Evaluator::eval_element`)
+    for name in &system.arrays {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
     names
 }
 
@@ -438,6 +482,14 @@ pub fn format_constraint(c: &Constraint) -> String {
     }
 }
 
+/// Format an assumption as the condition given to `assume`.
+pub fn format_assumption(c: &Constraint) -> String {
+    match c {
+        Constraint::AssertTrue(v) => format_sym_value(v),
+        other => format_constraint(other),
+    }
+}
+
 /// Format a symbolic value for display (abbreviated).
 pub fn format_sym_value(v: &SymValue) -> String {
     match v {
@@ -462,5 +514,28 @@ pub fn format_sym_value(v: &SymValue) -> String {
         SymValue::FieldAccess(inner, field) => {
             format!("{}.{}", format_sym_value(inner), field)
         }
+        SymValue::Select(array, index) => {
+            format!("{}[{}]", format_sym_array(array), format_sym_value(index))
+        }
+        SymValue::Array(array) => format_sym_array(array),
+    }
+}
+
+/// Format a symbolic array: `ram`, `ram{5 := x}`, or an ITE.
+fn format_sym_array(a: &SymArray) -> String {
+    match a {
+        SymArray::Base(name) => name.clone(),
+        SymArray::Store(inner, index, value) => format!(
+            "{}{{{} := {}}}",
+            format_sym_array(inner),
+            format_sym_value(index),
+            format_sym_value(value)
+        ),
+        SymArray::Ite(c, t, e) => format!(
+            "(if {} then {} else {})",
+            format_sym_value(c),
+            format_sym_array(t),
+            format_sym_array(e)
+        ),
     }
 }
@@ -39,6 +39,7 @@ pub fn solve(system: &ConstraintSystem, config: &SolverConfig) -> SolverResult {
 
     // Collect all variable names we need to assign
     let var_names = collect_variables(system);
+    let mut evaluated_rounds = 0;
 
     for _round in 0..config.rounds {
         // Generate random assignments for all variables
@@ -53,6 +54,10 @@ pub fn solve(system: &ConstraintSystem, config: &SolverConfig) -> SolverResult {
         }
 
         let evaluator = Evaluator::new(&assignments);
+        if evaluator.excluded_by(&system.assumptions) {
+            continue;
+        }
+        evaluated_rounds += 1;
 
         for (i, constraint) in system.constraints.iter().enumerate() {
             // Hash-dependent constraints require specific witnesses —
@@ -82,8 +87,9 @@ pub fn solve(system: &ConstraintSystem, config: &SolverConfig) -> SolverResult {
         }
     }
 
-    // Determine always-satisfied constraints
-    let always_satisfied = if config.detect_redundant {
+    // Determine always-satisfied constraints (nothing is, if no round
+    // satisfied the assumptions)
+    let always_satisfied = if config.detect_redundant && evaluated_rounds > 0 {
         (0..num_constraints)
             .filter(|&i| {
                 !ever_failed[i] && !ever_unevaluable[i] && !system.constraints[i].is_trivial()
@@ -101,7 +107,7 @@ pub fn solve(system: &ConstraintSystem, config: &SolverConfig) -> SolverResult {
 
     SolverResult {
         constraints_checked: num_constraints,
-        rounds: config.rounds,
+        rounds: evaluated_rounds,
         counterexamples,
         always_satisfied,
         unevaluable,
@@ -151,8 +157,14 @@ pub fn bounded_check(system: &ConstraintSystem, config: &BmcConfig) -> SolverRes
         // No variables: just evaluate once with empty assignment
         let assignments = BTreeMap::new();
         let evaluator = Evaluator::new(&assignments);
-        total_rounds = 1;
-        for (i, constraint) in system.constraints.iter().enumerate() {
+        // With no inputs, a broken assumption makes the whole body dead.
+        let checked = if evaluator.excluded_by(&system.assumptions) {
+            &[][..]
+        } else {
+            total_rounds = 1;
+            &system.constraints[..]
+        };
+        for (i, constraint) in checked.iter().enumerate() {
             if constraint.is_hash_dependent() {
                 ever_unevaluable[i] = true;
                 continue;
@@ -178,8 +190,11 @@ pub fn bounded_check(system: &ConstraintSystem, config: &BmcConfig) -> SolverRes
         let combos = generate_combinations(&var_names, &interesting_values, 10_000);
 
         for assignments in &combos {
-            total_rounds += 1;
             let evaluator = Evaluator::new(assignments);
+            if evaluator.excluded_by(&system.assumptions) {
+                continue;
+            }
+            total_rounds += 1;
             for (i, constraint) in system.constraints.iter().enumerate() {
                 if constraint.is_hash_dependent() {
                     ever_unevaluable[i] = true;
@@ -209,13 +224,16 @@ pub fn bounded_check(system: &ConstraintSystem, config: &BmcConfig) -> SolverRes
         let sample_count = config.values_per_var * 100;
 
         for _ in 0..sample_count {
-            total_rounds += 1;
             let mut assignments = BTreeMap::new();
             for name in &var_names {
                 assignments.insert(name.clone(), rng.next_field());
             }
 
             let evaluator = Evaluator::new(&assignments);
+            if evaluator.excluded_by(&system.assumptions) {
+                continue;
+            }
+            total_rounds += 1;
             for (i, constraint) in system.constraints.iter().enumerate() {
                 if constraint.is_hash_dependent() {
                     ever_unevaluable[i] = true;
@@ -242,6 +260,7 @@ pub fn bounded_check(system: &ConstraintSystem, config: &BmcConfig) -> SolverRes
     }
 
     let always_satisfied: Vec<usize> = (0..num_constraints)
+        .filter(|_| total_rounds > 0)
         .filter(|&i| !ever_failed[i] && !ever_unevaluable[i] && !system.constraints[i].is_trivial())
         .collect();
 
@@ -3,6 +3,16 @@ use super::*;
 /// Maximum iterations for constant-range for-loop unrolling in symbolic execution.
 const MAX_CONST_LOOP_UNROLL: u64 = 10_000;
 
+/// Default unroll limit for loops with a runtime end (see `with_unroll_limit`).
+pub const DEFAULT_UNROLL_LIMIT: u64 = 64;
+
+/// Environment key holding the current RAM as a `SymValue::Array`, so
+/// branch merging turns diverging writes into array ITEs.
+pub(crate) const RAM_KEY: &str = "__ram";
+
+/// Base array name of the RAM the program starts with.
+pub(crate) const RAM_BASE: &str = "ram";
+
 // ─── Symbolic Executor ─────────────────────────────────────────────
 
 /// Symbolic executor that walks the AST and builds a constraint system.
@@ -25,13 +35,18 @@ pub struct SymExecutor {
     pub(crate) call_depth: u32,
     /// Maximum call depth before giving up.
     pub(crate) max_call_depth: u32,
+    /// Most iterations unrolled for a loop with a runtime end.
+    pub(crate) unroll_limit: u64,
+    /// Function whose body is executing (for `LoopLimit` reports).
+    pub(crate) current_fn: String,
 }
 
 impl SymExecutor {
     pub fn new() -> Self {
+        let ram = SymValue::Array(Box::new(SymArray::Base(RAM_BASE.to_string())));
         Self {
             system: ConstraintSystem::new(),
-            env: BTreeMap::new(),
+            env: BTreeMap::from([(RAM_KEY.to_string(), ram)]),
             versions: BTreeMap::new(),
             divine_counter: 0,
             pub_input_counter: 0,
@@ -39,14 +54,25 @@ impl SymExecutor {
             functions: BTreeMap::new(),
             call_depth: 0,
             max_call_depth: 64,
+            unroll_limit: DEFAULT_UNROLL_LIMIT,
+            current_fn: String::new(),
         }
     }
 
+    /// Unroll loops with a runtime end at most `limit` times, even when
+    /// their `bounded` value is larger. Cut-short loops are reported in
+    /// `ConstraintSystem::loop_limits`.
+    pub fn with_unroll_limit(mut self, limit: u64) -> Self {
+        self.unroll_limit = limit;
+        self
+    }
+
     /// Execute a file and produce its constraint system (main function only).
     pub fn execute_file(mut self, file: &File) -> ConstraintSystem {
         self.register_functions(file);
 
         if let Some(main_fn) = self.functions.get("main").cloned() {
+            self.current_fn = "main".to_string();
             if let Some(ref body) = main_fn.body {
                 self.execute_block(&body.node);
             }
@@ -60,6 +86,7 @@ impl SymExecutor {
         self.register_functions(file);
 
         if let Some(func) = self.functions.get(fn_name).cloned() {
+            self.current_fn = fn_name.to_string();
             // Create symbolic inputs for each parameter
             for param in &func.params {
                 let var = self.fresh_var(&param.name.node);
@@ -115,20 +142,79 @@ impl SymExecutor {
         SymValue::Var(var)
     }
 
+    /// Interpret `value` as an array. Variables become base arrays of
+    /// their own (array inputs); other scalars get a fresh base.
+    pub(crate) fn as_array(&mut self, value: &SymValue) -> SymArray {
+        match value {
+            SymValue::Array(array) => (**array).clone(),
+            SymValue::Ite(cond, a, b) => SymArray::Ite(
+                cond.clone(),
+                Box::new(self.as_array(a)),
+                Box::new(self.as_array(b)),
+            ),
+            SymValue::Var(var) => self.base_array(&var.to_string()),
+            _ => {
+                let var = self.fresh_var("__array");
+                self.base_array(&var.to_string())
+            }
+        }
+    }
+
+    /// A base array, recorded in the system so encoders declare it.
+    pub(crate) fn base_array(&mut self, name: &str) -> SymArray {
+        self.system.arrays.insert(name.to_string());
+        SymArray::Base(name.to_string())
+    }
+
+    /// The current RAM.
+    pub(crate) fn ram(&mut self) -> SymArray {
+        self.system.arrays.insert(RAM_BASE.to_string());
+        match self.env.get(RAM_KEY).cloned() {
+            Some(ram) => self.as_array(&ram),
+            None => SymArray::Base(RAM_BASE.to_string()),
+        }
+    }
+
+    pub(crate) fn set_ram(&mut self, ram: SymArray) {
+        self.env
+            .insert(RAM_KEY.to_string(), SymValue::Array(Box::new(ram)));
+    }
+
+    /// Record a loop unrolled fewer than `bound` times (`None`: unknown).
+    fn record_loop_limit(&mut self, var: &str, unrolled: u64, bound: Option<u64>) {
+        if bound.is_none_or(|bound| unrolled < bound) {
+            self.system.loop_limits.push(LoopLimit {
+                function: self.current_fn.clone(),
+                var: var.to_string(),
+                unrolled,
+                bound,
+            });
+        }
+    }
+
     /// Add a constraint, wrapping with current path condition.
     pub(crate) fn add_constraint(&mut self, c: Constraint) {
+        let c = self.under_path(c);
+        self.system.constraints.push(c);
+    }
+
+    /// Record an `assume(...)`: a fact the verifier may rely on, holding
+    /// only on the current path.
+    pub(crate) fn add_assumption(&mut self, c: Constraint) {
+        let c = self.under_path(c);
+        self.system.assumptions.push(c);
+    }
+
+    fn under_path(&self, c: Constraint) -> Constraint {
         if self.path_condition.is_empty() {
-            self.system.constraints.push(c);
-        } else {
-            // Combine path conditions: cond1 AND cond2 AND ... => constraint
-            let mut combined = self.path_condition[0].clone();
-            for pc in &self.path_condition[1..] {
-                combined = SymValue::Mul(Box::new(combined), Box::new(pc.clone()));
-            }
-            self.system
-                .constraints
-                .push(Constraint::Conditional(combined, Box::new(c)));
+            return c;
+        }
+        // Combine path conditions: cond1 AND cond2 AND ... => constraint
+        let mut combined = self.path_condition[0].clone();
+        for pc in &self.path_condition[1..] {
+            combined = SymValue::Mul(Box::new(combined), Box::new(pc.clone()));
         }
+        Constraint::Conditional(combined, Box::new(c))
     }
 
     /// Execute a block of statements.
@@ -142,6 +228,18 @@ impl SymExecutor {
         }
     }
 
+    /// Execute a block and return the value of its tail expression
+    /// (0 for a block without one).
+    pub(crate) fn eval_block_value(&mut self, block: &Block) -> SymValue {
+        for stmt in &block.stmts {
+            self.execute_stmt(&stmt.node);
+        }
+        match &block.tail_expr {
+            Some(tail) => self.eval_expr(&tail.node),
+            None => SymValue::Const(0),
+        }
+    }
+
     /// Execute a single statement.
     pub(crate) fn execute_stmt(&mut self, stmt: &Stmt) {
         match stmt {
@@ -173,9 +271,24 @@ impl SymExecutor {
             }
             Stmt::Assign { place, value } => {
                 let val = self.eval_expr(&value.node);
-                if let Place::Var(name) = &place.node {
-                    let _var = self.fresh_var(name);
-                    self.env.insert(name.clone(), val);
+                match &place.node {
+                    Place::Var(name) => {
+                        let _var = self.fresh_var(name);
+                        self.env.insert(name.clone(), val);
+                    }
+                    Place::Index(inner, index) => {
+                        if let Place::Var(name) = &inner.node {
+                            let idx = self.eval_expr(&index.node);
+                            let current = match self.env.get(name).cloned() {
+                                Some(current) => current,
+                                None => SymValue::Var(self.fresh_var(name)),
+                            };
+                            let array = self.as_array(&current).store(idx, val);
+                            self.env
+                                .insert(name.clone(), SymValue::Array(Box::new(array)));
+                        }
+                    }
+                    Place::FieldAccess(..) => {}
                 }
             }
             Stmt::If {
@@ -204,62 +317,48 @@ impl SymExecutor {
                     self.path_condition.pop();
                 }
                 let else_env = self.env.clone();
-
-                // Merge environments: for each variable modified in either branch,
-                // create an ITE symbolic value
-                let mut merged = saved_env;
-                for (name, then_val) in &then_env {
-                    let else_val = else_env.get(name).unwrap_or(then_val);
-                    if then_val != else_val {
-                        let ite = SymValue::Ite(
-                            Box::new(cond_val.clone()),
-                            Box::new(then_val.clone()),
-                            Box::new(else_val.clone()),
-                        );
-                        merged.insert(name.clone(), ite);
-                    } else {
-                        merged.insert(name.clone(), then_val.clone());
-                    }
-                }
-                // Also merge vars that only exist in else_env
-                for (name, else_val) in &else_env {
-                    if !then_env.contains_key(name) {
-                        merged.insert(name.clone(), else_val.clone());
-                    }
-                }
-                self.env = merged;
+                self.env = merge_branch_envs(&cond_val, saved_env, &then_env, &else_env);
             }
             Stmt::For {
                 var,
                 start,
                 end,
+                bound,
                 body,
-                ..
             } => {
                 let start_val = self.eval_expr(&start.node);
                 let end_val = self.eval_expr(&end.node);
 
                 // If both are constants, unroll exactly
                 if let (Some(s), Some(e)) = (start_val.as_const(), end_val.as_const()) {
-                    for i in s..e.min(s + MAX_CONST_LOOP_UNROLL) {
+                    let count = e.saturating_sub(s);
+                    let unrolled = count.min(MAX_CONST_LOOP_UNROLL);
+                    for i in s..s + unrolled {
                         self.env.insert(var.node.clone(), SymValue::Const(i));
                         self.execute_block(&body.node);
                     }
+                    self.record_loop_limit(&var.node, unrolled, Some(count));
                 } else {
-                    // Dynamic bound: unroll up to the declared bound
-                    // Each iteration gets a path condition: i < end
-                    let bound = 64u64; // default max unroll
-                    if let Some(s) = start_val.as_const() {
-                        for i in s..(s + bound) {
-                            let iter_val = SymValue::Const(i);
-                            let in_range =
-                                SymValue::Lt(Box::new(iter_val.clone()), Box::new(end_val.clone()));
-                            self.env.insert(var.node.clone(), iter_val);
-                            self.path_condition.push(in_range);
-                            self.execute_block(&body.node);
-                            self.path_condition.pop();
-                        }
+                    // Runtime end: iteration k runs only while start + k < end,
+                    // so its state is joined with the state before it.
+                    let unrolled = bound.unwrap_or(u64::MAX).min(self.unroll_limit);
+                    for k in 0..unrolled {
+                        let iter_val = SymValue::Add(
+                            Box::new(start_val.clone()),
+                            Box::new(SymValue::Const(k)),
+                        )
+                        .simplify();
+                        let in_range =
+                            SymValue::Lt(Box::new(iter_val.clone()), Box::new(end_val.clone()));
+                        let before = self.env.clone();
+                        self.env.insert(var.node.clone(), iter_val);
+                        self.path_condition.push(in_range.clone());
+                        self.execute_block(&body.node);
+                        self.path_condition.pop();
+                        let after = std::mem::take(&mut self.env);
+                        self.env = merge_branch_envs(&in_range, before.clone(), &after, &before);
                     }
+                    self.record_loop_limit(&var.node, unrolled, *bound);
                 }
             }
             Stmt::Expr(expr) => {
@@ -295,6 +394,17 @@ impl SymExecutor {
                             // Struct patterns are unconditional (type-checked)
                             SymValue::Const(1)
                         }
+                        MatchPattern::Some(_) | MatchPattern::None => {
+                            let flag = SymValue::FieldAccess(
+                                Box::new(match_val.clone()),
+                                "is_some".to_string(),
+                            );
+                            let present = matches!(arm.pattern.node, MatchPattern::Some(_));
+                            SymValue::Eq(
+                                Box::new(flag),
+                                Box::new(SymValue::Const(u64::from(present))),
+                            )
+                        }
                     };
                     self.path_condition.push(cond.clone());
                     // For struct patterns, bind fields before executing body
@@ -310,6 +420,11 @@ impl SymExecutor {
                             }
                         }
                     }
+                    if let MatchPattern::Some(binding) = &arm.pattern.node {
+                        let payload =
+                            SymValue::FieldAccess(Box::new(match_val.clone()), "value".to_string());
+                        self.env.insert(binding.node.clone(), payload);
+                    }
                     self.execute_block(&arm.body.node);
                     self.path_condition.pop();
                     merged_envs.push((cond, self.env.clone()));
@@ -345,3 +460,34 @@ impl SymExecutor {
         }
     }
 }
+
+/// Merge the environments of two branches: every variable that differs
+/// between them becomes an ITE on the branch condition.
+pub(crate) fn merge_branch_envs(
+    cond: &SymValue,
+    saved: BTreeMap<String, SymValue>,
+    then_env: &BTreeMap<String, SymValue>,
+    else_env: &BTreeMap<String, SymValue>,
+) -> BTreeMap<String, SymValue> {
+    let mut merged = saved;
+    for (name, then_val) in then_env {
+        let else_val = else_env.get(name).unwrap_or(then_val);
+        if then_val != else_val {
+            let ite = SymValue::Ite(
+                Box::new(cond.clone()),
+                Box::new(then_val.clone()),
+                Box::new(else_val.clone()),
+            );
+            merged.insert(name.clone(), ite);
+        } else {
+            merged.insert(name.clone(), then_val.clone());
+        }
+    }
+    // Also merge vars that only exist in else_env
+    for (name, else_val) in else_env {
+        if !then_env.contains_key(name) {
+            merged.insert(name.clone(), else_val.clone());
+        }
+    }
+    merged
+}
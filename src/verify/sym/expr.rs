@@ -12,6 +12,11 @@ impl SymExecutor {
                     SymValue::Var(var)
                 })
             }
+            Expr::BinOp { op, lhs, rhs }
+                if matches!(op, BinOp::And | BinOp::Or) && !rhs.node.is_pure() =>
+            {
+                self.eval_expr(&Expr::short_circuit(*op, lhs, rhs))
+            }
             Expr::BinOp { op, lhs, rhs } => {
                 let l = self.eval_expr(&lhs.node);
                 let r = self.eval_expr(&rhs.node);
@@ -20,6 +25,10 @@ impl SymExecutor {
                     BinOp::Mul => SymValue::Mul(Box::new(l), Box::new(r)).simplify(),
                     BinOp::Eq => SymValue::Eq(Box::new(l), Box::new(r)).simplify(),
                     BinOp::Lt => SymValue::Lt(Box::new(l), Box::new(r)),
+                    BinOp::And => SymValue::Mul(Box::new(l), Box::new(r)).simplify(),
+                    BinOp::Or => {
+                        SymValue::Ite(Box::new(l), Box::new(SymValue::Const(1)), Box::new(r))
+                    }
                     _ => {
                         // BitAnd, BitXor, DivMod, XFieldMul — leave as opaque
                         SymValue::Var(self.fresh_var("__binop"))
@@ -43,10 +52,10 @@ impl SymExecutor {
                 let var = self.fresh_var("__field");
                 SymValue::Var(var)
             }
-            Expr::Index { expr, .. } => {
-                let _ = self.eval_expr(&expr.node);
-                let var = self.fresh_var("__index");
-                SymValue::Var(var)
+            Expr::Index { expr, index } => {
+                let base = self.eval_expr(&expr.node);
+                let idx = self.eval_expr(&index.node);
+                self.as_array(&base).select(idx)
             }
             Expr::StructInit { fields, .. } => {
                 for (_, val) in fields {
@@ -56,11 +65,40 @@ impl SymExecutor {
                 SymValue::Var(var)
             }
             Expr::ArrayInit(elems) => {
-                for e in elems {
-                    let _ = self.eval_expr(&e.node);
-                }
                 let var = self.fresh_var("__array");
-                SymValue::Var(var)
+                let mut array = self.base_array(&var.to_string());
+                for (i, e) in elems.iter().enumerate() {
+                    let val = self.eval_expr(&e.node);
+                    array = array.store(SymValue::Const(i as u64), val);
+                }
+                SymValue::Array(Box::new(array))
+            }
+            Expr::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                let cond_val = self.eval_expr(&cond.node);
+                let saved_env = self.env.clone();
+
+                self.path_condition.push(cond_val.clone());
+                let then_val = self.eval_block_value(&then_block.node);
+                self.path_condition.pop();
+                let then_env = std::mem::replace(&mut self.env, saved_env.clone());
+
+                let neg_cond =
+                    SymValue::Sub(Box::new(SymValue::Const(1)), Box::new(cond_val.clone()));
+                self.path_condition.push(neg_cond);
+                let else_val = self.eval_block_value(&else_block.node);
+                self.path_condition.pop();
+                let else_env = self.env.clone();
+
+                self.env = merge_branch_envs(&cond_val, saved_env, &then_env, &else_env);
+                if then_val == else_val {
+                    then_val
+                } else {
+                    SymValue::Ite(Box::new(cond_val), Box::new(then_val), Box::new(else_val))
+                }
             }
         }
     }
@@ -110,13 +148,20 @@ impl SymExecutor {
                 let inputs: Vec<SymValue> = args.iter().map(|a| self.eval_expr(&a.node)).collect();
                 return SymValue::Hash(inputs, 0);
             }
-            "assert" => {
+            "assert" | "ensure" => {
                 if let Some(arg) = args.first() {
                     let val = self.eval_expr(&arg.node);
                     self.add_constraint(Constraint::AssertTrue(val));
                 }
                 return SymValue::Const(0);
             }
+            "assume" => {
+                if let Some(arg) = args.first() {
+                    let val = self.eval_expr(&arg.node);
+                    self.add_assumption(Constraint::AssertTrue(val));
+                }
+                return SymValue::Const(0);
+            }
             "assert_eq" | "eq" => {
                 if args.len() >= 2 {
                     let a = self.eval_expr(&args[0].node);
@@ -164,6 +209,22 @@ impl SymExecutor {
                 }
                 return SymValue::Const(0);
             }
+            "checked_inv" | "checked_div" => {
+                // The divisor is the last argument; both assert it nonzero.
+                let vals: Vec<SymValue> = args.iter().map(|a| self.eval_expr(&a.node)).collect();
+                let Some(divisor) = vals.last().cloned() else {
+                    return SymValue::Const(0);
+                };
+                let is_zero = SymValue::Eq(Box::new(divisor.clone()), Box::new(SymValue::Const(0)));
+                self.add_constraint(Constraint::Equal(is_zero.simplify(), SymValue::Const(0)));
+                let inverse = SymValue::Inv(Box::new(divisor));
+                return match &vals[..] {
+                    [dividend, _] => {
+                        SymValue::Mul(Box::new(dividend.clone()), Box::new(inverse)).simplify()
+                    }
+                    _ => inverse,
+                };
+            }
             "inv" => {
                 if let Some(arg) = args.first() {
                     let val = self.eval_expr(&arg.node);
@@ -171,6 +232,46 @@ impl SymExecutor {
                 }
                 return SymValue::Const(0);
             }
+            "ram_read" => {
+                if let Some(arg) = args.first() {
+                    let addr = self.eval_expr(&arg.node);
+                    return self.ram().select(addr);
+                }
+                return SymValue::Const(0);
+            }
+            "ram_write" => {
+                if let [addr, value] = args {
+                    let addr = self.eval_expr(&addr.node);
+                    let value = self.eval_expr(&value.node);
+                    let ram = self.ram().store(addr, value);
+                    self.set_ram(ram);
+                }
+                return SymValue::Const(0);
+            }
+            "ram_read_block" => {
+                // Digests are opaque; the words stay readable one by one.
+                if let Some(arg) = args.first() {
+                    let _ = self.eval_expr(&arg.node);
+                }
+                let var = self.fresh_var("__digest");
+                return SymValue::Var(var);
+            }
+            "ram_write_block" => {
+                if let [addr, value] = args {
+                    let addr = self.eval_expr(&addr.node);
+                    let value = self.eval_expr(&value.node);
+                    let mut ram = self.ram();
+                    for k in 0..5u64 {
+                        let at =
+                            SymValue::Add(Box::new(addr.clone()), Box::new(SymValue::Const(k)))
+                                .simplify();
+                        let word = SymValue::FieldAccess(Box::new(value.clone()), k.to_string());
+                        ram = ram.store(at, word);
+                    }
+                    self.set_ram(ram);
+                }
+                return SymValue::Const(0);
+            }
             _ => {}
         }
 
@@ -187,6 +288,7 @@ impl SymExecutor {
                 if let Some(ref body) = func.body {
                     self.call_depth += 1;
                     let saved_env = self.env.clone();
+                    let caller = std::mem::replace(&mut self.current_fn, func.name.node.clone());
 
                     // Bind parameters
                     for (param, arg) in func.params.iter().zip(args.iter()) {
@@ -197,8 +299,13 @@ impl SymExecutor {
                     // Execute function body
                     self.execute_block(&body.node);
 
-                    // Restore environment (except new constraints are kept)
+                    // Restore environment (new constraints and RAM writes are kept)
+                    let ram = self.env.remove(RAM_KEY);
                     self.env = saved_env;
+                    if let Some(ram) = ram {
+                        self.env.insert(RAM_KEY.to_string(), ram);
+                    }
+                    self.current_fn = caller;
                     self.call_depth -= 1;
                 }
             }
@@ -11,14 +11,17 @@
 //! 1. Assigns a symbolic variable to each `let` binding
 //! 2. Tracks constraints from `assert`, `assert_eq`, `assert_digest`
 //! 3. Encodes `if/else` as path conditions
-//! 4. Unrolls bounded `for` loops up to their bound
+//! 4. Unrolls bounded `for` loops up to their bound (or the executor's
+//!    unroll limit), joining each iteration's state with an ITE on the
+//!    loop condition; loops cut short are recorded as `LoopLimit`s
 //! 5. Inlines function calls (no recursion → always terminates)
-//! 6. Produces a `ConstraintSystem` that can be checked by:
+//! 6. Models RAM and array values as `SymArray` select/store terms
+//! 7. Produces a `ConstraintSystem` that can be checked by:
 //!    - The algebraic solver (polynomial identity testing)
 //!    - A bounded model checker (enumerate concrete values)
 //!    - An SMT solver (Z3/CVC5 via SMT-LIB encoding)
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::ast::*;
 use crate::span::Spanned;
@@ -66,6 +69,10 @@ pub enum SymValue {
     PubInput(u32),
     /// If-then-else: if cond then a else b.
     Ite(Box<SymValue>, Box<SymValue>, Box<SymValue>),
+    /// Array read: array[index].
+    Select(Box<SymArray>, Box<SymValue>),
+    /// A whole array value (an array variable, or RAM in the environment).
+    Array(Box<SymArray>),
 }
 
 impl SymValue {
@@ -103,6 +110,8 @@ impl SymValue {
                 c.contains_opaque() || t.contains_opaque() || e.contains_opaque()
             }
             SymValue::FieldAccess(inner, _) => inner.contains_opaque(),
+            SymValue::Select(array, index) => array.contains_opaque() || index.contains_opaque(),
+            SymValue::Array(array) => array.contains_opaque(),
             SymValue::Const(_) | SymValue::Divine(_) | SymValue::PubInput(_) => false,
         }
     }
@@ -187,8 +196,65 @@ impl SymValue {
     }
 }
 
-/// A named symbolic variable.
+/// A symbolic array in the theory of arrays: RAM, or the value of an
+/// array variable. Reads are `SymValue::Select` terms.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SymArray {
+    /// Unconstrained contents: prover-initialized RAM or an array input.
+    Base(String),
+    /// The array with element `index` replaced by `value`.
+    Store(Box<SymArray>, Box<SymValue>, Box<SymValue>),
+    /// If-then-else over arrays, from merging branches.
+    Ite(Box<SymValue>, Box<SymArray>, Box<SymArray>),
+}
+
+impl SymArray {
+    /// Read element `index`. Stores at the same index resolve to the
+    /// stored value, and stores at a different constant index are
+    /// skipped; anything else stays a `Select` term.
+    pub fn select(&self, index: SymValue) -> SymValue {
+        match self {
+            SymArray::Store(inner, at, value) => {
+                if **at == index {
+                    return (**value).clone();
+                }
+                if at.is_const() && index.is_const() {
+                    return inner.select(index);
+                }
+                SymValue::Select(Box::new(self.clone()), Box::new(index))
+            }
+            SymArray::Ite(cond, a, b) => {
+                let (a, b) = (a.select(index.clone()), b.select(index));
+                if a == b {
+                    a
+                } else {
+                    SymValue::Ite(cond.clone(), Box::new(a), Box::new(b))
+                }
+            }
+            SymArray::Base(_) => SymValue::Select(Box::new(self.clone()), Box::new(index)),
+        }
+    }
+
+    /// The array with element `index` set to `value`.
+    pub fn store(self, index: SymValue, value: SymValue) -> SymArray {
+        SymArray::Store(Box::new(self), Box::new(index), Box::new(value))
+    }
+
+    pub fn contains_opaque(&self) -> bool {
+        match self {
+            SymArray::Base(_) => false,
+            SymArray::Store(inner, index, value) => {
+                inner.contains_opaque() || index.contains_opaque() || value.contains_opaque()
+            }
+            SymArray::Ite(cond, a, b) => {
+                cond.contains_opaque() || a.contains_opaque() || b.contains_opaque()
+            }
+        }
+    }
+}
+
+/// A named symbolic variable.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SymVar {
     pub name: String,
     /// SSA version number (for mutable variables).
@@ -274,6 +340,38 @@ impl Constraint {
     }
 }
 
+// ─── Loop Limits ───────────────────────────────────────────────────
+
+/// A loop the executor stopped unrolling before its bound. Constraints
+/// from later iterations are missing, so a "safe" verdict only covers
+/// the iterations explored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoopLimit {
+    /// Function containing the loop.
+    pub function: String,
+    /// Loop variable.
+    pub var: String,
+    /// Iterations explored.
+    pub unrolled: u64,
+    /// Iterations the loop may run: its `bounded` value or range
+    /// length. `None` when the executor could not tell.
+    pub bound: Option<u64>,
+}
+
+impl std::fmt::Display for LoopLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`for {}` in {}: unrolled {} of ",
+            self.var, self.function, self.unrolled
+        )?;
+        match self.bound {
+            Some(bound) => write!(f, "{} iterations", bound),
+            None => write!(f, "an unknown number of iterations"),
+        }
+    }
+}
+
 // ─── Constraint System ─────────────────────────────────────────────
 
 /// The complete constraint system for a program or function.
@@ -281,6 +379,8 @@ impl Constraint {
 pub struct ConstraintSystem {
     /// All constraints that must hold.
     pub constraints: Vec<Constraint>,
+    /// Facts from `assume(...)`: not checked, only relied on.
+    pub assumptions: Vec<Constraint>,
     /// Symbolic variables introduced (name → latest version).
     pub variables: BTreeMap<String, u32>,
     /// Public inputs read (in order).
@@ -289,6 +389,10 @@ pub struct ConstraintSystem {
     pub pub_outputs: Vec<SymValue>,
     /// Divine inputs consumed (in order).
     pub divine_inputs: Vec<SymVar>,
+    /// Base arrays the constraints may read (`ram` and array inputs).
+    pub arrays: BTreeSet<String>,
+    /// Loops not unrolled to their bound.
+    pub loop_limits: Vec<LoopLimit>,
     /// Number of unique symbolic variables.
     pub num_variables: u32,
 }
@@ -297,14 +401,30 @@ impl ConstraintSystem {
     pub fn new() -> Self {
         Self {
             constraints: Vec::new(),
+            assumptions: Vec::new(),
             variables: BTreeMap::new(),
             pub_inputs: Vec::new(),
             pub_outputs: Vec::new(),
             divine_inputs: Vec::new(),
+            arrays: BTreeSet::new(),
+            loop_limits: Vec::new(),
             num_variables: 0,
         }
     }
 
+    /// Append another function's system, e.g. to verify a whole module.
+    pub fn extend(&mut self, other: ConstraintSystem) {
+        self.constraints.extend(other.constraints);
+        self.assumptions.extend(other.assumptions);
+        self.num_variables += other.num_variables;
+        self.variables.extend(other.variables);
+        self.pub_inputs.extend(other.pub_inputs);
+        self.pub_outputs.extend(other.pub_outputs);
+        self.divine_inputs.extend(other.divine_inputs);
+        self.arrays.extend(other.arrays);
+        self.loop_limits.extend(other.loop_limits);
+    }
+
     /// Count of non-trivial constraints.
     pub fn active_constraints(&self) -> usize {
         self.constraints.iter().filter(|c| !c.is_trivial()).count()
@@ -65,7 +65,7 @@ fn check_expr_for_preconditions(
 ) {
     if let Expr::Call { path, args, .. } = expr {
         let call_name = path.node.0.last().map(|s| s.as_str()).unwrap_or("");
-        if call_name == "assert" {
+        if call_name == "assert" || call_name == "ensure" {
             if let Some(arg) = args.first() {
                 let cond_str = expr_to_string(&arg.node);
                 if param_names.iter().any(|p| cond_str.contains(p)) {
@@ -6,13 +6,17 @@
 //! 2. Counterexample-guided inductive synthesis (CEGIS): propose candidate
 //!    invariants, verify with solver, refine using counterexamples.
 //! 3. Specification inference: suggest postconditions from code analysis.
+//!
+//! `generate_tests` turns the synthesized specs into `#[test]` functions.
 
 mod infer;
+mod suite;
 mod templates;
 #[cfg(test)]
 mod tests;
 
 pub(crate) use infer::{cegis_refine, infer_postconditions_from_body, infer_preconditions};
+pub use suite::generate_tests;
 pub(crate) use templates::match_templates;
 
 #[cfg(test)]
@@ -261,5 +265,21 @@ pub(crate) fn expr_to_string(expr: &Expr) -> String {
                 .collect();
             format!("{} {{ {} }}", name, field_strs.join(", "))
         }
+        Expr::If {
+            cond,
+            then_block,
+            else_block,
+        } => {
+            let branch = |block: &Block| match &block.tail_expr {
+                Some(tail) if block.stmts.is_empty() => expr_to_string(&tail.node),
+                _ => "...".to_string(),
+            };
+            format!(
+                "if {} {{ {} }} else {{ {} }}",
+                expr_to_string(&cond.node),
+                branch(&then_block.node),
+                branch(&else_block.node)
+            )
+        }
     }
 }
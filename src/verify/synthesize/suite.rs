@@ -0,0 +1,167 @@
+//! Test generation from synthesized specs.
+//!
+//! Turns the pre- and postconditions found by `synthesize_specs` into
+//! `#[test]` functions that call the function on sample inputs and assert
+//! the postconditions on its result. The output is meant to be appended to
+//! the source file and reviewed: the inputs are arbitrary and a synthesized
+//! spec may be wrong.
+
+use super::*;
+use crate::ast::display::format_ast_type;
+use crate::scaffold::extract_variables;
+
+/// Prefix of generated test names, so reruns can skip covered functions.
+const TEST_PREFIX: &str = "synth_test_";
+
+/// Section rule around the generated tests, as in the standard library.
+const RULE: &str =
+    "// ---------------------------------------------------------------------------\n";
+
+/// Sample values for parameters, in declaration order. They decrease so
+/// that `a - b` on U32 parameters does not underflow.
+const SAMPLES: [u64; 8] = [23, 19, 17, 13, 11, 7, 5, 3];
+
+/// Generate `#[test]` functions for the specs of `file`.
+///
+/// A function gets a test when it has a body, scalar parameters and return
+/// type, no size parameters, and at least one postcondition over its
+/// parameters and `result` that parses as a Trident expression. Functions
+/// that already have a `synth_test_<name>` test are skipped. Returns an
+/// empty string when no test was generated.
+pub fn generate_tests(file: &File, specs: &[SynthesizedSpec]) -> String {
+    let existing: Vec<&str> = file
+        .items
+        .iter()
+        .filter_map(|item| match &item.node {
+            Item::Fn(func) => Some(func.name.node.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut tests = Vec::new();
+    for item in &file.items {
+        let Item::Fn(func) = &item.node else {
+            continue;
+        };
+        let test_name = format!("{}{}", TEST_PREFIX, func.name.node);
+        if !is_testable(func) || existing.contains(&test_name.as_str()) {
+            continue;
+        }
+        let mut own: Vec<&SynthesizedSpec> = Vec::new();
+        for spec in specs {
+            if spec.function == func.name.node
+                && !own.iter().any(|s| s.expression == spec.expression)
+                && usable(func, &spec.expression)
+            {
+                own.push(spec);
+            }
+        }
+        let pre: Vec<&SynthesizedSpec> = own
+            .iter()
+            .copied()
+            .filter(|s| s.kind == SpecKind::Precondition && !s.expression.contains("result"))
+            .collect();
+        let post: Vec<&SynthesizedSpec> = own
+            .iter()
+            .copied()
+            .filter(|s| s.kind == SpecKind::Postcondition)
+            .collect();
+        if post.is_empty() {
+            continue;
+        }
+        tests.push(test_function(func, &test_name, &pre, &post));
+    }
+
+    if tests.is_empty() {
+        return String::new();
+    }
+    let mut out = String::new();
+    out.push_str(RULE);
+    out.push_str("// Synthesized tests (trident synth-tests). The inputs are samples and the\n");
+    out.push_str("// assertions are inferred: review both before relying on these tests.\n");
+    out.push_str(RULE);
+    out.push_str(&tests.join("\n"));
+    out
+}
+
+/// Whether a test can call `func` with literal arguments and bind its result.
+fn is_testable(func: &FnDef) -> bool {
+    func.body.is_some()
+        && !func.is_test
+        && !func.is_entry
+        && func.intrinsic.is_none()
+        && func.type_params.is_empty()
+        && func.name.node != "main"
+        && func.params.iter().all(|p| is_scalar(&p.ty.node))
+        && func.return_ty.as_ref().is_some_and(|t| is_scalar(&t.node))
+}
+
+fn is_scalar(ty: &Type) -> bool {
+    matches!(ty, Type::Field | Type::U32 | Type::Bool)
+}
+
+/// Whether `expression` only names parameters (and `result`) and parses.
+fn usable(func: &FnDef, expression: &str) -> bool {
+    let names: Vec<&str> = func.params.iter().map(|p| p.name.node.as_str()).collect();
+    extract_variables(expression)
+        .iter()
+        .all(|v| names.contains(&v.as_str()))
+        && parses(expression)
+}
+
+/// Whether `expression` parses as the argument of an `assert`.
+fn parses(expression: &str) -> bool {
+    let probe = format!(
+        "program probe\n\nfn main() {{\n    assert({})\n}}\n",
+        expression
+    );
+    crate::parse_source_silent(&probe, "synth-tests").is_ok()
+}
+
+fn test_function(
+    func: &FnDef,
+    test_name: &str,
+    pre: &[&SynthesizedSpec],
+    post: &[&SynthesizedSpec],
+) -> String {
+    let fn_name = &func.name.node;
+    let mut out = format!("\n#[test]\nfn {}() {{\n", test_name);
+    for (i, param) in func.params.iter().enumerate() {
+        out.push_str(&format!(
+            "    let {}: {} = {}\n",
+            param.name.node,
+            format_ast_type(&param.ty.node),
+            sample(&param.ty.node, i)
+        ));
+    }
+    for spec in pre {
+        out.push_str(&format!(
+            "    // requires ({}%): {}\n    assert({})\n",
+            spec.confidence, spec.explanation, spec.expression
+        ));
+    }
+    let args: Vec<&str> = func.params.iter().map(|p| p.name.node.as_str()).collect();
+    let ret = &func.return_ty.as_ref().expect("testable").node;
+    out.push_str(&format!(
+        "    let result: {} = {}({})\n",
+        format_ast_type(ret),
+        fn_name,
+        args.join(", ")
+    ));
+    for spec in post {
+        out.push_str(&format!(
+            "    // ensures ({}%): {}\n    assert({})\n",
+            spec.confidence, spec.explanation, spec.expression
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Sample literal for the `index`-th parameter.
+fn sample(ty: &Type, index: usize) -> String {
+    match ty {
+        Type::Bool => index.is_multiple_of(2).to_string(),
+        _ => SAMPLES[index % SAMPLES.len()].to_string(),
+    }
+}
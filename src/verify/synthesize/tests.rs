@@ -328,3 +328,44 @@ fn test_expr_to_string_var() {
     let expr = Expr::Var("x".to_string());
     assert_eq!(expr_to_string(&expr), "x");
 }
+
+// -- Test generation --
+
+#[test]
+fn test_generate_tests_asserts_postconditions() {
+    let source = r#"program test
+fn add(a: Field, b: Field) -> Field {
+a + b
+}
+fn sum_loop(x: Field) -> Field {
+let mut acc: Field = 0
+for i in 0..10 {
+    acc = acc + x
+}
+acc
+}
+fn main() {}
+"#;
+    let file = parse_program(source);
+    let specs = synthesize_specs(&file);
+    let tests = generate_tests(&file, &specs);
+    assert!(
+        tests.contains("#[test]\nfn synth_test_add() {"),
+        "{}",
+        tests
+    );
+    assert!(tests.contains("let result: Field = add(a, b)"), "{}", tests);
+    assert!(tests.contains("assert(result == a + b)"), "{}", tests);
+    assert!(
+        !tests.contains("synth_test_sum_loop"),
+        "loop specs are not expressions over parameters: {}",
+        tests
+    );
+
+    // The extended file type-checks and a rerun adds nothing.
+    let extended = format!("{}\n{}", source, tests);
+    crate::check_silent(&extended, "test.tri").expect("generated tests type-check");
+    let file = parse_program(&extended);
+    let specs = synthesize_specs(&file);
+    assert_eq!(generate_tests(&file, &specs), "");
+}
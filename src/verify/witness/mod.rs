@@ -0,0 +1,468 @@
+//! Witness generator synthesis.
+//!
+//! Many `divine()` inputs are fixed by the public inputs: an inverse
+//! checked with `assert_eq(x * inv, 1)`, or a hi/lo decomposition checked
+//! with `assert_eq(hi * 2^32 + lo, x)` and two `as_u32` range checks.
+//! For those, the prover should not have to hand-code the witness logic.
+//! This module reads the constraint system of `main`, solves each
+//! equality for the divine input it mentions, and returns a
+//! `WitnessRecipe`: the divine inputs as expressions over the public
+//! inputs, in an order where every step only uses earlier results.
+//!
+//! A recipe renders as a standalone Rust function (`to_rust`) or as a
+//! JSON expression tree (`to_json`) for provers in other languages.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::solve::Evaluator;
+use crate::sym::{Constraint, ConstraintSystem, SymValue, SymVar, GOLDILOCKS_P};
+
+#[cfg(test)]
+mod tests;
+
+/// The weight of the high limb in a hi/lo `U32` decomposition.
+const U32_RADIX: u64 = 1 << 32;
+
+// ─── Data Structures ───────────────────────────────────────────────
+
+/// How to compute every divine input of a program from its public inputs.
+#[derive(Clone, Debug)]
+pub struct WitnessRecipe {
+    /// Program name, used to name the generated function.
+    pub program: String,
+    /// Number of public inputs the program reads.
+    pub pub_inputs: usize,
+    /// One step per divine input, in dependency order.
+    pub steps: Vec<WitnessStep>,
+}
+
+/// One divine input and how to compute it.
+#[derive(Clone, Debug)]
+pub struct WitnessStep {
+    /// Position among the program's divine inputs (read order).
+    pub index: usize,
+    pub value: WitnessValue,
+}
+
+/// How a step computes its divine input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessValue {
+    /// The value of a field expression.
+    Expr(SymValue),
+    /// `value >> 32` of the canonical representative.
+    High32(SymValue),
+    /// `value & 0xffff_ffff` of the canonical representative.
+    Low32(SymValue),
+}
+
+// ─── Synthesis ─────────────────────────────────────────────────────
+
+/// Derive a recipe for every divine input of `system`. On failure,
+/// returns the divine inputs the constraints do not pin down.
+///
+/// Only unconditional equalities are used: a constraint under a path
+/// condition does not determine a value on every run.
+pub fn synthesize_witness(
+    program: &str,
+    system: &ConstraintSystem,
+) -> Result<WitnessRecipe, Vec<String>> {
+    let equations: Vec<(&SymValue, &SymValue)> = system
+        .constraints
+        .iter()
+        .filter_map(|c| match c {
+            Constraint::Equal(a, b) => Some((a, b)),
+            Constraint::AssertTrue(SymValue::Eq(a, b)) => Some((a.as_ref(), b.as_ref())),
+            _ => None,
+        })
+        .collect();
+    let ranged: BTreeSet<&SymVar> = system
+        .constraints
+        .iter()
+        .filter_map(|c| match c {
+            Constraint::RangeU32(SymValue::Var(v)) => Some(v),
+            _ => None,
+        })
+        .collect();
+
+    let mut known: BTreeSet<&SymVar> = system.pub_inputs.iter().collect();
+    let mut steps = Vec::new();
+    let mut progress = true;
+    while progress && steps.len() < system.divine_inputs.len() {
+        progress = false;
+        for (index, var) in system.divine_inputs.iter().enumerate() {
+            if known.contains(var) {
+                continue;
+            }
+            if let Some(value) = solve_for(var, &equations, &known) {
+                steps.push(WitnessStep { index, value });
+                known.insert(var);
+                progress = true;
+                continue;
+            }
+            if let Some((hi, lo, value)) = solve_split(var, &equations, &known, &ranged) {
+                let index_of = |v: &SymVar| system.divine_inputs.iter().position(|d| d == v);
+                let (Some(hi_index), Some(lo_index)) = (index_of(hi), index_of(lo)) else {
+                    continue;
+                };
+                steps.push(WitnessStep {
+                    index: hi_index,
+                    value: WitnessValue::High32(value.clone()),
+                });
+                steps.push(WitnessStep {
+                    index: lo_index,
+                    value: WitnessValue::Low32(value),
+                });
+                known.insert(hi);
+                known.insert(lo);
+                progress = true;
+            }
+        }
+    }
+
+    let undetermined: Vec<String> = system
+        .divine_inputs
+        .iter()
+        .filter(|v| !known.contains(v))
+        .map(|v| v.to_string())
+        .collect();
+    if !undetermined.is_empty() {
+        return Err(undetermined);
+    }
+    Ok(WitnessRecipe {
+        program: program.to_string(),
+        pub_inputs: system.pub_inputs.len(),
+        steps,
+    })
+}
+
+/// Solve one of `equations` for `target`, given the `known` variables.
+fn solve_for(
+    target: &SymVar,
+    equations: &[(&SymValue, &SymValue)],
+    known: &BTreeSet<&SymVar>,
+) -> Option<WitnessValue> {
+    equations.iter().find_map(|&(a, b)| {
+        let solved = match (mentions(a, target), mentions(b, target)) {
+            (true, false) => isolate(target, a, b.clone()),
+            (false, true) => isolate(target, b, a.clone()),
+            _ => None,
+        }?
+        .simplify();
+        computable(&solved, known).then_some(WitnessValue::Expr(solved))
+    })
+}
+
+/// Rewrite `side == rhs` into `target == ...`. `target` must occur
+/// exactly once in `side`, under field operations only.
+fn isolate(target: &SymVar, side: &SymValue, rhs: SymValue) -> Option<SymValue> {
+    let boxed = |v: &SymValue| Box::new(v.clone());
+    match side {
+        SymValue::Var(v) if v == target => Some(rhs),
+        SymValue::Add(a, b) => match (mentions(a, target), mentions(b, target)) {
+            (true, false) => isolate(target, a, SymValue::Sub(Box::new(rhs), boxed(b))),
+            (false, true) => isolate(target, b, SymValue::Sub(Box::new(rhs), boxed(a))),
+            _ => None,
+        },
+        SymValue::Sub(a, b) => match (mentions(a, target), mentions(b, target)) {
+            (true, false) => isolate(target, a, SymValue::Add(Box::new(rhs), boxed(b))),
+            (false, true) => isolate(target, b, SymValue::Sub(boxed(a), Box::new(rhs))),
+            _ => None,
+        },
+        // Unique only where the other factor is nonzero; elsewhere the
+        // generator reports that no witness exists.
+        SymValue::Mul(a, b) => match (mentions(a, target), mentions(b, target)) {
+            (true, false) => isolate(
+                target,
+                a,
+                SymValue::Mul(Box::new(rhs), Box::new(SymValue::Inv(boxed(b)))),
+            ),
+            (false, true) => isolate(
+                target,
+                b,
+                SymValue::Mul(Box::new(rhs), Box::new(SymValue::Inv(boxed(a)))),
+            ),
+            _ => None,
+        },
+        SymValue::Neg(a) => isolate(target, a, SymValue::Neg(Box::new(rhs))),
+        _ => None,
+    }
+}
+
+/// Match `hi * 2^32 + lo == value` where `hi` or `lo` is `target`, both
+/// are range-checked divine inputs, and `value` is known. Returns
+/// `(hi, lo, value)`.
+fn solve_split<'a>(
+    target: &SymVar,
+    equations: &[(&'a SymValue, &'a SymValue)],
+    known: &BTreeSet<&SymVar>,
+    ranged: &BTreeSet<&SymVar>,
+) -> Option<(&'a SymVar, &'a SymVar, SymValue)> {
+    equations.iter().find_map(|&(a, b)| {
+        [(a, b), (b, a)].into_iter().find_map(|(limbs, value)| {
+            let (hi, lo) = split_limbs(limbs)?;
+            let fits = (hi == target || lo == target)
+                && hi != lo
+                && ranged.contains(hi)
+                && ranged.contains(lo)
+                && !known.contains(hi)
+                && !known.contains(lo)
+                && computable(value, known);
+            fits.then(|| (hi, lo, value.clone()))
+        })
+    })
+}
+
+/// `hi * 2^32 + lo`, with either operand order.
+fn split_limbs(v: &SymValue) -> Option<(&SymVar, &SymVar)> {
+    let SymValue::Add(a, b) = v else {
+        return None;
+    };
+    match (a.as_ref(), b.as_ref()) {
+        (hi, SymValue::Var(lo)) | (SymValue::Var(lo), hi) => Some((high_limb(hi)?, lo)),
+        _ => None,
+    }
+}
+
+/// `hi * 2^32`, with either operand order.
+fn high_limb(v: &SymValue) -> Option<&SymVar> {
+    let SymValue::Mul(x, y) = v else {
+        return None;
+    };
+    match (x.as_ref(), y.as_ref()) {
+        (SymValue::Var(h), SymValue::Const(U32_RADIX))
+        | (SymValue::Const(U32_RADIX), SymValue::Var(h)) => Some(h),
+        _ => None,
+    }
+}
+
+/// Whether `target` occurs in `v`.
+fn mentions(v: &SymValue, target: &SymVar) -> bool {
+    match v {
+        SymValue::Var(var) => var == target,
+        SymValue::Add(a, b)
+        | SymValue::Mul(a, b)
+        | SymValue::Sub(a, b)
+        | SymValue::Eq(a, b)
+        | SymValue::Lt(a, b) => mentions(a, target) || mentions(b, target),
+        SymValue::Neg(a) | SymValue::Inv(a) | SymValue::FieldAccess(a, _) => mentions(a, target),
+        SymValue::Ite(c, t, e) => mentions(c, target) || mentions(t, target) || mentions(e, target),
+        SymValue::Hash(inputs, _) => inputs.iter().any(|i| mentions(i, target)),
+        SymValue::Select(_, index) => mentions(index, target),
+        SymValue::Const(_) | SymValue::Divine(_) | SymValue::PubInput(_) | SymValue::Array(_) => {
+            false
+        }
+    }
+}
+
+/// Whether `v` is field arithmetic over `known` variables only.
+fn computable(v: &SymValue, known: &BTreeSet<&SymVar>) -> bool {
+    match v {
+        SymValue::Const(_) => true,
+        SymValue::Var(var) => known.contains(var),
+        SymValue::Add(a, b)
+        | SymValue::Mul(a, b)
+        | SymValue::Sub(a, b)
+        | SymValue::Eq(a, b)
+        | SymValue::Lt(a, b) => computable(a, known) && computable(b, known),
+        SymValue::Neg(a) | SymValue::Inv(a) => computable(a, known),
+        SymValue::Ite(c, t, e) => {
+            computable(c, known) && computable(t, known) && computable(e, known)
+        }
+        _ => false,
+    }
+}
+
+// ─── Generation ────────────────────────────────────────────────────
+
+impl WitnessRecipe {
+    /// Run the recipe: the divine inputs, in read order, for the given
+    /// public inputs. Fails when an inverse of zero is needed, i.e. no
+    /// witness satisfies the program for these inputs.
+    pub fn generate(&self, pub_inputs: &[u64]) -> Result<Vec<u64>, String> {
+        if pub_inputs.len() < self.pub_inputs {
+            return Err(format!(
+                "expected {} public input(s), got {}",
+                self.pub_inputs,
+                pub_inputs.len()
+            ));
+        }
+        let mut assignments: BTreeMap<String, u64> = pub_inputs
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("pub_in_{}", i), v % GOLDILOCKS_P))
+            .collect();
+        let mut divine = vec![0; self.steps.len()];
+        for step in &self.steps {
+            let evaluator = Evaluator::new(&assignments);
+            let (WitnessValue::Expr(v) | WitnessValue::High32(v) | WitnessValue::Low32(v)) =
+                &step.value;
+            let value = evaluator
+                .eval(v)
+                .ok_or_else(|| format!("divine_{}: no witness (inverse of zero)", step.index))?;
+            let value = match step.value {
+                WitnessValue::Expr(_) => value,
+                WitnessValue::High32(_) => value / U32_RADIX,
+                WitnessValue::Low32(_) => value % U32_RADIX,
+            };
+            assignments.insert(format!("divine_{}", step.index), value);
+            divine[step.index] = value;
+        }
+        Ok(divine)
+    }
+}
+
+// ─── Rendering ─────────────────────────────────────────────────────
+
+impl WitnessRecipe {
+    /// A self-contained Rust function `<program>_witness(pub_in: &[u64])
+    /// -> Option<Vec<u64>>` with the Goldilocks helpers it needs.
+    pub fn to_rust(&self) -> String {
+        let mut helpers = BTreeSet::new();
+        let mut body = String::new();
+        for i in 0..self.pub_inputs {
+            body.push_str(&format!(
+                "    let pub_in_{} = *pub_in.get({})? % P;\n",
+                i, i
+            ));
+        }
+        for step in &self.steps {
+            let expr = match &step.value {
+                WitnessValue::Expr(v) => rust_expr(v, &mut helpers),
+                WitnessValue::High32(v) => format!("{} >> 32", rust_expr(v, &mut helpers)),
+                WitnessValue::Low32(v) => {
+                    format!("{} & 0xffff_ffff", rust_expr(v, &mut helpers))
+                }
+            };
+            body.push_str(&format!("    let divine_{} = {};\n", step.index, expr));
+        }
+        let outputs: Vec<String> = (0..self.steps.len())
+            .map(|i| format!("divine_{}", i))
+            .collect();
+        body.push_str(&format!("    Some(vec![{}])\n", outputs.join(", ")));
+
+        // `inv` is built on `mul`, and `neg` on `sub`.
+        if helpers.contains("inv") {
+            helpers.insert("mul");
+        }
+        if helpers.contains("neg") {
+            helpers.insert("sub");
+        }
+
+        let mut out = String::new();
+        out.push_str(&format!(
+            "// Witness generator for `{}`, synthesized by `trident witness synth`.\n",
+            self.program
+        ));
+        out.push_str("// Returns the divine inputs in the order the program reads them,\n");
+        out.push_str("// or `None` if no witness exists for these public inputs.\n\n");
+        out.push_str("const P: u64 = 0xffff_ffff_0000_0001;\n\n");
+        for helper in &helpers {
+            out.push_str(rust_helper(helper));
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "pub fn {}_witness(pub_in: &[u64]) -> Option<Vec<u64>> {{\n",
+            self.program
+        ));
+        out.push_str(&body);
+        out.push_str("}\n");
+        out
+    }
+
+    /// The recipe as JSON: each divine input as an expression tree of
+    /// `{"const": n}`, `{"pub_in": i}`, `{"divine": i}` and
+    /// `{"op": name, "args": [...]}` nodes, in evaluation order.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\n");
+        out.push_str(&format!("  \"program\": \"{}\",\n", self.program));
+        out.push_str(&format!("  \"pub_inputs\": {},\n", self.pub_inputs));
+        out.push_str("  \"divine\": [\n");
+        for (i, step) in self.steps.iter().enumerate() {
+            let value = match &step.value {
+                WitnessValue::Expr(v) => json_expr(v),
+                WitnessValue::High32(v) => json_op("hi32", &[v]),
+                WitnessValue::Low32(v) => json_op("lo32", &[v]),
+            };
+            out.push_str(&format!(
+                "    {{\"index\": {}, \"value\": {}}}",
+                step.index, value
+            ));
+            if i + 1 < self.steps.len() {
+                out.push(',');
+            }
+            out.push('\n');
+        }
+        out.push_str("  ]\n}\n");
+        out
+    }
+}
+
+fn rust_expr(v: &SymValue, helpers: &mut BTreeSet<&'static str>) -> String {
+    match v {
+        SymValue::Const(c) => format!("{}", c % GOLDILOCKS_P),
+        SymValue::Var(var) => var.name.clone(),
+        SymValue::Add(a, b) => rust_call("add", &[a, b], helpers),
+        SymValue::Sub(a, b) => rust_call("sub", &[a, b], helpers),
+        SymValue::Mul(a, b) => rust_call("mul", &[a, b], helpers),
+        SymValue::Eq(a, b) => rust_call("eq", &[a, b], helpers),
+        SymValue::Lt(a, b) => rust_call("lt", &[a, b], helpers),
+        SymValue::Neg(a) => rust_call("neg", &[a], helpers),
+        SymValue::Inv(a) => format!("{}?", rust_call("inv", &[a], helpers)),
+        SymValue::Ite(c, t, e) => format!(
+            "if {} != 0 {{ {} }} else {{ {} }}",
+            rust_expr(c, helpers),
+            rust_expr(t, helpers),
+            rust_expr(e, helpers)
+        ),
+        // `computable` admits nothing else into a recipe.
+        _ => unreachable!("not a field expression: {:?}", v),
+    }
+}
+
+fn rust_call(
+    name: &'static str,
+    args: &[&SymValue],
+    helpers: &mut BTreeSet<&'static str>,
+) -> String {
+    helpers.insert(name);
+    let args: Vec<String> = args.iter().map(|a| rust_expr(a, helpers)).collect();
+    format!("{}({})", name, args.join(", "))
+}
+
+fn rust_helper(name: &str) -> &'static str {
+    match name {
+        "add" => "fn add(a: u64, b: u64) -> u64 {\n    ((a as u128 + b as u128) % P as u128) as u64\n}\n",
+        "sub" => "fn sub(a: u64, b: u64) -> u64 {\n    ((a as u128 + P as u128 - b as u128) % P as u128) as u64\n}\n",
+        "mul" => "fn mul(a: u64, b: u64) -> u64 {\n    ((a as u128 * b as u128) % P as u128) as u64\n}\n",
+        "neg" => "fn neg(a: u64) -> u64 {\n    sub(0, a)\n}\n",
+        "eq" => "fn eq(a: u64, b: u64) -> u64 {\n    u64::from(a == b)\n}\n",
+        "lt" => "fn lt(a: u64, b: u64) -> u64 {\n    u64::from(a < b)\n}\n",
+        "inv" => "fn inv(a: u64) -> Option<u64> {\n    if a == 0 {\n        return None;\n    }\n    let (mut base, mut exp, mut acc) = (a, P - 2, 1);\n    while exp > 0 {\n        if exp & 1 == 1 {\n            acc = mul(acc, base);\n        }\n        base = mul(base, base);\n        exp >>= 1;\n    }\n    Some(acc)\n}\n",
+        _ => unreachable!("unknown helper {}", name),
+    }
+}
+
+fn json_expr(v: &SymValue) -> String {
+    match v {
+        SymValue::Const(c) => format!("{{\"const\": {}}}", c % GOLDILOCKS_P),
+        SymValue::Var(var) => match var.name.rsplit_once('_') {
+            Some(("pub_in", i)) => format!("{{\"pub_in\": {}}}", i),
+            Some(("divine", i)) => format!("{{\"divine\": {}}}", i),
+            _ => unreachable!("not an input: {}", var),
+        },
+        SymValue::Add(a, b) => json_op("add", &[a, b]),
+        SymValue::Sub(a, b) => json_op("sub", &[a, b]),
+        SymValue::Mul(a, b) => json_op("mul", &[a, b]),
+        SymValue::Eq(a, b) => json_op("eq", &[a, b]),
+        SymValue::Lt(a, b) => json_op("lt", &[a, b]),
+        SymValue::Neg(a) => json_op("neg", &[a]),
+        SymValue::Inv(a) => json_op("inv", &[a]),
+        SymValue::Ite(c, t, e) => json_op("ite", &[c, t, e]),
+        _ => unreachable!("not a field expression: {:?}", v),
+    }
+}
+
+fn json_op(name: &str, args: &[&SymValue]) -> String {
+    let args: Vec<String> = args.iter().map(|a| json_expr(a)).collect();
+    format!("{{\"op\": \"{}\", \"args\": [{}]}}", name, args.join(", "))
+}
@@ -0,0 +1,69 @@
+use super::*;
+use crate::sym;
+
+fn recipe_for(source: &str) -> Result<WitnessRecipe, Vec<String>> {
+    let file = crate::parse_source_silent(source, "test.tri").unwrap();
+    let system = sym::analyze(&file);
+    synthesize_witness(&file.name.node, &system)
+}
+
+#[test]
+fn test_inverse_is_synthesized() {
+    let recipe = recipe_for(
+        "program test\nfn main() {\n    let x: Field = pub_read()\n    let inv: Field = divine()\n    assert_eq(x * inv, 1)\n}\n",
+    )
+    .unwrap();
+    let witness = recipe.generate(&[5]).unwrap();
+    assert_eq!(crate::solve::field_mul(witness[0], 5), 1);
+    assert!(recipe.generate(&[0]).is_err());
+}
+
+#[test]
+fn test_u32_decomposition_is_synthesized() {
+    let recipe = recipe_for(
+        "program test\nfn main() {\n    let x: Field = pub_read()\n    let hi: U32 = as_u32(divine())\n    let lo: U32 = as_u32(divine())\n    assert_eq(as_field(hi) * 4294967296 + as_field(lo), x)\n}\n",
+    )
+    .unwrap();
+    assert_eq!(recipe.generate(&[(7 << 32) + 9]).unwrap(), vec![7, 9]);
+}
+
+#[test]
+fn test_steps_follow_dependencies() {
+    // `b` is read first but depends on `a`.
+    let recipe = recipe_for(
+        "program test\nfn main() {\n    let x: Field = pub_read()\n    let b: Field = divine()\n    let a: Field = divine()\n    assert_eq(b, a + 1)\n    assert_eq(a, x * 2)\n}\n",
+    )
+    .unwrap();
+    let order: Vec<usize> = recipe.steps.iter().map(|s| s.index).collect();
+    assert_eq!(order, vec![1, 0]);
+    assert_eq!(recipe.generate(&[10]).unwrap(), vec![21, 20]);
+}
+
+#[test]
+fn test_undetermined_divine_is_reported() {
+    let result = recipe_for(
+        "program test\nfn main() {\n    let x: Field = pub_read()\n    let h: Field = divine()\n    pub_write(h + x)\n}\n",
+    );
+    assert_eq!(result.unwrap_err(), vec!["divine_0".to_string()]);
+}
+
+#[test]
+fn test_rust_and_json_rendering() {
+    let recipe = recipe_for(
+        "program test\nfn main() {\n    let x: Field = pub_read()\n    let inv: Field = divine()\n    assert_eq(x * inv, 1)\n}\n",
+    )
+    .unwrap();
+    let rust = recipe.to_rust();
+    assert!(rust.contains("pub fn test_witness(pub_in: &[u64]) -> Option<Vec<u64>> {"));
+    assert!(rust.contains("    let pub_in_0 = *pub_in.get(0)? % P;\n"));
+    assert!(rust.contains("fn inv(a: u64) -> Option<u64>"));
+    assert!(rust.contains("fn mul(a: u64, b: u64)"));
+    assert!(!rust.contains("fn add("));
+    let json = recipe.to_json();
+    assert!(json.contains("\"pub_inputs\": 1"));
+    assert!(
+        json.contains("{\"op\": \"inv\", \"args\": [{\"pub_in\": 0}]}"),
+        "{}",
+        json
+    );
+}
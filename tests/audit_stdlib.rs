@@ -111,6 +111,32 @@ fn main() {
     );
 }
 
+#[test]
+fn test_std_crypto_merkle_verify_mem_reads_siblings_from_ram() {
+    let tasm = compile_test_program(
+        "_test_merkle_mem.tri",
+        r#"program test_merkle_mem
+use std.crypto.merkle
+
+fn main() {
+    let leaf: Digest = divine5()
+    let root: Digest = divine5()
+    let (idx, _hi) = split(pub_read())
+    merkle.verify_mem(leaf, root, idx, pub_read(), pub_read())
+    pub_write(0)
+}
+"#,
+    );
+    assert!(
+        tasm.contains("__verify_mem:"),
+        "missing verify_mem function"
+    );
+    assert!(
+        tasm.contains("merkle_step_mem"),
+        "verify_mem should emit merkle_step_mem"
+    );
+}
+
 // ── std.crypto.bigint ──
 
 #[test]
@@ -177,6 +203,33 @@ fn main() {
     assert!(tasm.contains("__init:"), "missing init function");
 }
 
+// ── std.bytes ──
+
+#[test]
+fn test_std_bytes_round_trips_words_and_lanes() {
+    let tasm = compile_test_program(
+        "_test_bytes.tri",
+        r#"program test_bytes
+use std.bytes
+use std.crypto.keccak256
+use std.crypto.sha256
+
+fn main() {
+    let word: U32 = sha256.word_from_bytes([97, 98, 99, 128])
+    let raw: [U32; 2] = [as_u32(pub_read()), as_u32(pub_read())]
+    let checked: Bytes<2> = bytes.from_u32s(raw)
+    let packed: Field = bytes.pack_bytes(checked)
+    let lane: keccak256.Lane = keccak256.lane_from_bytes(bytes.unpack_bytes(packed))
+    let out: Bytes<4> = sha256.word_bytes(word)
+    pub_write(as_field(out[0]) + as_field(lane.lo))
+}
+"#,
+    );
+    assert!(tasm.contains("__from_u32s"), "missing from_u32s instance");
+    assert!(tasm.contains("__pack_bytes"), "missing pack_bytes instance");
+    assert!(tasm.contains("__word_be:"), "missing word_be function");
+}
+
 // ── std.crypto.ecdsa ──
 
 #[test]
@@ -247,3 +300,127 @@ fn main() {
     );
     assert!(tasm.contains("__on_curve:"), "missing on_curve function");
 }
+
+// ── std.crypto.transcript ──
+
+#[test]
+fn test_std_crypto_transcript_compiles() {
+    let tasm = compile_test_program(
+        "_test_transcript.tri",
+        r#"program test_transcript
+use std.crypto.transcript
+
+fn main() {
+    let commitment: Digest = divine5()
+    transcript.start_with_domain(1)
+    transcript.absorb_digest(commitment)
+    transcript.absorb_field(pub_read())
+    let alpha: Field = transcript.challenge()
+    let idx: U32 = transcript.challenge_index(as_u32(8))
+    pub_write(alpha)
+    pub_write(as_field(idx))
+}
+"#,
+    );
+    assert!(tasm.contains("__challenge:"), "missing challenge function");
+    assert!(
+        tasm.contains("sponge_init"),
+        "transcript should emit sponge_init"
+    );
+    assert!(
+        tasm.contains("sponge_absorb"),
+        "transcript should emit sponge_absorb"
+    );
+    assert!(
+        tasm.contains("sponge_squeeze"),
+        "transcript should emit sponge_squeeze"
+    );
+}
+
+#[test]
+fn test_std_proof_compiles() {
+    let tasm = compile_test_program(
+        "_test_proof.tri",
+        r#"program test_proof
+use std.proof
+
+fn main() {
+    let claim: proof.Claim = proof.read_claim()
+    proof.write_claim(claim)
+    let s: proof.ProofStream = proof.open_stream(divine())
+    let root: Digest = proof.peek_digest(s)
+    let rest: proof.ProofStream = proof.advance(s, 5)
+    pub_write(proof.peek_field(rest))
+    proof.finish(proof.advance(rest, 1))
+}
+"#,
+    );
+    assert!(
+        tasm.contains("__read_claim:"),
+        "missing read_claim function"
+    );
+    assert!(
+        tasm.contains("__open_stream:"),
+        "missing open_stream function"
+    );
+}
+
+#[test]
+fn test_std_crypto_transcript_tags_each_message_type() {
+    let tasm = compile_test_program(
+        "_test_transcript_tags.tri",
+        r#"program test_transcript_tags
+use std.crypto.transcript
+
+fn main() {
+    let d: Digest = divine5()
+    transcript.start_with_domain(7)
+    transcript.absorb_field(pub_read())
+    transcript.absorb_xfield(1, 2, 3)
+    transcript.absorb_digest(d)
+    transcript.absorb_digest_pair(d, d)
+    pub_write(transcript.challenge())
+}
+"#,
+    );
+    let body = |name: &str| -> Vec<&str> {
+        let label = format!("std-crypto-transcript__{}:", name);
+        tasm.lines()
+            .skip_while(|l| l.trim() != label)
+            .skip(1)
+            .take_while(|l| l.trim() != "return")
+            .map(str::trim)
+            .collect()
+    };
+    // Each typed absorb pushes its own tag and no other type's.
+    let tags = [
+        ("start_with_domain", 1),
+        ("absorb_field", 2),
+        ("absorb_xfield", 3),
+        ("absorb_digest", 4),
+        ("absorb_digest_pair", 5),
+    ];
+    for (name, tag) in tags {
+        let pushes: Vec<&str> = body(name)
+            .into_iter()
+            .filter(|l| l.starts_with("push "))
+            .collect();
+        for (other, other_tag) in tags.iter().skip(1) {
+            let pushed = pushes.contains(&format!("push {}", other_tag).as_str());
+            assert_eq!(pushed, *other_tag == tag, "{} vs {}", name, other);
+        }
+        assert!(
+            pushes.contains(&format!("push {}", tag).as_str()),
+            "{}",
+            name
+        );
+    }
+    let pair_blocks = body("absorb_digest_pair")
+        .into_iter()
+        .filter(|l| *l == "sponge_absorb")
+        .count();
+    assert_eq!(
+        pair_blocks, 2,
+        "a digest pair and its padding take two blocks"
+    );
+}
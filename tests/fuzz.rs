@@ -0,0 +1,243 @@
+//! Mutation fuzzing for the lexer, parser and type checker.
+//!
+//! Seeds are every `.tri` file under `std/`, `os/` and `vm/` plus the
+//! regression corpus in `tests/fuzz/corpus/`. Every mutated input must come
+//! back from `check_silent` as `Ok` or as diagnostics, never as a panic.
+//! The default run is short enough for `cargo test`; longer sessions:
+//!
+//!     TRIDENT_FUZZ_ITERS=100000 TRIDENT_FUZZ_SEED=7 cargo test --test fuzz
+//!
+//! A stack overflow aborts the process instead of panicking, so the input
+//! under test is written to `target/fuzz/current.tri` before each case.
+//! Panicking inputs are saved as `target/fuzz/crash-<seed>-<case>.tri`.
+//! Minimize a crash, fix it, and add it to the corpus.
+
+use std::path::{Path, PathBuf};
+
+const DEFAULT_ITERS: u64 = 1000;
+const DEFAULT_SEED: u64 = 0x0074_7269_6465_6e74;
+
+/// Same stack as `test_error_max_nesting_depth`: the parser may recurse
+/// 256 levels before the nesting guard stops it.
+const STACK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Fragments that mutations splice in: keywords, types, operators and
+/// delimiters, so mutants reach the parser instead of failing in the lexer.
+const TOKENS: &str = "program module use fn pub sec let mut const struct if else for in \
+    bounded return true false event reveal seal match Field XField Bool U32 Digest _ \
+    ( ) { } [ ] , : ; . .. -> = => == + * *. < > & && || ^ /% #[test] as_u32 inv \
+    0 1 4294967296 x";
+
+/// Openers and closers for the nesting mutation.
+const WRAPS: &[(&str, &str)] = &[
+    ("(", ")"),
+    ("[", "]"),
+    ("{ ", " }"),
+    ("if true { ", " }"),
+    ("f(", ")"),
+    ("1 + ", ""),
+    ("a[", "]"),
+];
+
+/// xorshift64*: deterministic, so a failing seed reproduces exactly.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn collect_tri(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_tri(&path, out);
+        } else if path.extension().is_some_and(|e| e == "tri") {
+            out.push(path);
+        }
+    }
+}
+
+fn corpus() -> Vec<(PathBuf, String)> {
+    let mut paths = Vec::new();
+    collect_tri(&root().join("tests/fuzz/corpus"), &mut paths);
+    paths.sort();
+    read_all(paths)
+}
+
+fn seeds() -> Vec<(PathBuf, String)> {
+    let mut paths = Vec::new();
+    for dir in ["std", "os", "vm"] {
+        collect_tri(&root().join(dir), &mut paths);
+    }
+    paths.sort();
+    let mut seeds = read_all(paths);
+    seeds.extend(corpus());
+    seeds
+}
+
+fn read_all(paths: Vec<PathBuf>) -> Vec<(PathBuf, String)> {
+    paths
+        .into_iter()
+        .map(|p| {
+            let src = std::fs::read_to_string(&p).expect("read seed");
+            (p, src)
+        })
+        .collect()
+}
+
+/// Char-boundary position at or before `i`.
+fn floor_boundary(s: &str, mut i: usize) -> usize {
+    i = i.min(s.len());
+    while !s.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+fn pick_range(rng: &mut Rng, s: &str) -> (usize, usize) {
+    let a = floor_boundary(s, rng.below(s.len() + 1));
+    let len = 1 + rng.below(64);
+    let b = floor_boundary(s, a + len);
+    (a, b)
+}
+
+fn mutate(rng: &mut Rng, src: &str, seeds: &[(PathBuf, String)]) -> String {
+    let mut s = src.to_string();
+    for _ in 0..1 + rng.below(4) {
+        let at = floor_boundary(&s, rng.below(s.len() + 1));
+        match rng.below(7) {
+            // Replace one character with an arbitrary ASCII one.
+            0 => {
+                if let Some(end) = (at + 1..=s.len()).find(|&i| s.is_char_boundary(i)) {
+                    let c = (b' ' + rng.below(95) as u8) as char;
+                    s.replace_range(at..end, &c.to_string());
+                }
+            }
+            // Delete a span.
+            1 => {
+                let (a, b) = pick_range(rng, &s);
+                s.replace_range(a..b, "");
+            }
+            // Duplicate a span in place.
+            2 => {
+                let (a, b) = pick_range(rng, &s);
+                let dup = s[a..b].to_string();
+                s.insert_str(b, &dup);
+            }
+            // Insert a token.
+            3 => {
+                let tokens: Vec<&str> = TOKENS.split_whitespace().collect();
+                let tok = tokens[rng.below(tokens.len())];
+                s.insert_str(at, &format!(" {} ", tok));
+            }
+            // Splice in a span from another seed.
+            4 => {
+                let other = &seeds[rng.below(seeds.len())].1;
+                let (a, b) = pick_range(rng, other);
+                s.insert_str(at, &other[a..b]);
+            }
+            // Wrap a point in deep nesting, past the parser's limit at times.
+            5 => {
+                let (open, close) = WRAPS[rng.below(WRAPS.len())];
+                let depth = 1 + rng.below(400);
+                let wrapped = format!("{}x{}", open.repeat(depth), close.repeat(depth));
+                s.insert_str(at, &wrapped);
+            }
+            // Truncate.
+            _ => s.truncate(at),
+        }
+    }
+    s
+}
+
+fn fuzz_dir() -> PathBuf {
+    let dir = root().join("target/fuzz");
+    std::fs::create_dir_all(&dir).expect("create target/fuzz");
+    dir
+}
+
+/// Run the front end on `source` in a fresh thread; `Err` means it panicked.
+fn check_survives(source: String) -> Result<(), String> {
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let _ = trident::check_silent(&source, "fuzz.tri");
+        })
+        .expect("spawn fuzz thread")
+        .join()
+        .map_err(|e| {
+            e.downcast_ref::<String>()
+                .cloned()
+                .or_else(|| e.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default()
+        })
+}
+
+#[test]
+fn test_fuzz_corpus_never_panics() {
+    let corpus = corpus();
+    assert!(!corpus.is_empty(), "tests/fuzz/corpus is empty");
+    for (path, src) in corpus {
+        if let Err(msg) = check_survives(src) {
+            panic!("{} panicked the front end: {}", path.display(), msg);
+        }
+    }
+}
+
+#[test]
+fn test_fuzz_mutated_sources_never_panic() {
+    let seeds = seeds();
+    assert!(!seeds.is_empty(), "no .tri seeds found");
+    let iters = env_u64("TRIDENT_FUZZ_ITERS", DEFAULT_ITERS);
+    let seed = env_u64("TRIDENT_FUZZ_SEED", DEFAULT_SEED);
+    let mut rng = Rng(seed.max(1));
+    let dir = fuzz_dir();
+    let current = dir.join("current.tri");
+
+    let mut crashes = Vec::new();
+    for case in 0..iters {
+        let (path, src) = &seeds[rng.below(seeds.len())];
+        let input = mutate(&mut rng, src, &seeds);
+        std::fs::write(&current, &input).expect("write current input");
+        if let Err(msg) = check_survives(input.clone()) {
+            let out = dir.join(format!("crash-{}-{}.tri", seed, case));
+            std::fs::write(&out, &input).expect("write crash input");
+            crashes.push(format!(
+                "{} (from {}): {}",
+                out.display(),
+                path.display(),
+                msg
+            ));
+        }
+    }
+    assert!(
+        crashes.is_empty(),
+        "{} of {} mutants panicked:\n{}",
+        crashes.len(),
+        iters,
+        crashes.join("\n")
+    );
+}
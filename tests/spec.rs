@@ -0,0 +1,49 @@
+//! Language conformance suite.
+//!
+//! Each `tests/spec/<area>/<name>.tri` states its expected outcome in
+//! `// spec:` header comments (compiles, fails with an error, produces an
+//! output, or traps); see `trident::run_spec`. The suite pins the
+//! language definition, so a typechecker or backend change that alters
+//! one of these outcomes is a language change and must say so here.
+//! The same suite runs with:
+//!
+//!     trident test tests/spec --spec
+
+use std::path::Path;
+
+#[test]
+fn test_language_spec_conformance() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/spec");
+    let report = trident::run_spec_suite(
+        &dir,
+        &trident::CompileOptions::default(),
+        &trident::TestSelection::default(),
+    )
+    .expect("run tests/spec");
+    assert!(!report.results.is_empty(), "no tests/spec cases");
+
+    let failures: Vec<String> = report
+        .results
+        .iter()
+        .filter(|r| !r.passed)
+        .map(|r| format!("{}: {}", r.name, r.error.as_deref().unwrap_or("")))
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "{} of {} spec cases do not conform:\n\n{}",
+        failures.len(),
+        report.results.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_spec_header_rejects_two_outcomes() {
+    let err = trident::parse_spec_header("// spec: compile-ok\n// spec: output 1\nprogram p\n")
+        .unwrap_err();
+    assert!(err.contains("one outcome"));
+    let header = trident::parse_spec_header("// spec: input 1 2\n// spec: trap\nprogram p\n")
+        .expect("valid header");
+    assert_eq!(header.expect, trident::SpecExpectation::Trap(None));
+    assert_eq!(header.input.public, vec![1, 2]);
+}
@@ -0,0 +1,150 @@
+//! Cost model validation against real Triton VM traces.
+//!
+//! Each `tests/cost_model/<name>.tri` is compiled, run on Triton VM, and
+//! the measured table heights are compared with `analyze_costs`. A
+//! `// input: 1 2 3` first line gives the program's public input. The
+//! corpus uses exact loop bounds and branch-free code, so the static
+//! estimate should land close to the real trace; drift beyond the
+//! tolerances below means the cost model no longer matches the ISA.
+//!
+//! Part of the `tests/triton-vm` crate:
+//!
+//!     cargo test --manifest-path tests/triton-vm/Cargo.toml --test cost_model
+
+use std::path::{Path, PathBuf};
+
+use triton_vm::air::table::TableId;
+use triton_vm::prelude::{BFieldElement, NonDeterminism, Program, PublicInput, VM};
+
+/// A prediction may miss the measured height by this fraction of it...
+const RELATIVE_TOLERANCE: f64 = 0.25;
+/// ...plus this many rows, for code outside the functions the analyzer
+/// walks: the `call __main` / `halt` preamble and sponge padding.
+const ABSOLUTE_TOLERANCE: u64 = 32;
+
+/// How a predicted table height is held against the measured one.
+#[derive(Clone, Copy, PartialEq)]
+enum Check {
+    /// Within the tolerances above.
+    Close,
+    /// Never below the measurement. The analyzer charges every u32
+    /// instruction its worst case; the VM counts the operands' bits and
+    /// shares rows between repeated operations.
+    Bound,
+    /// Not comparable. Triton VM's jump-stack table has one row per
+    /// cycle; the analyzer's column counts call and return traffic.
+    Skip,
+}
+
+/// The analyzer's tables, in `ProgramCost::table_names` order, and the
+/// Triton VM table whose height each estimates.
+const TABLES: [(&str, TableId, Check); 6] = [
+    ("processor", TableId::Processor, Check::Close),
+    ("hash", TableId::Hash, Check::Close),
+    ("u32", TableId::U32, Check::Bound),
+    ("op_stack", TableId::OpStack, Check::Close),
+    ("ram", TableId::Ram, Check::Close),
+    ("jump_stack", TableId::JumpStack, Check::Skip),
+];
+
+fn corpus() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cost_model");
+    let mut cases: Vec<PathBuf> = std::fs::read_dir(dir)
+        .expect("read tests/cost_model")
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "tri"))
+        .collect();
+    cases.sort();
+    cases
+}
+
+fn public_input(source: &str) -> Vec<BFieldElement> {
+    source
+        .lines()
+        .next()
+        .and_then(|line| line.trim().strip_prefix("// input:"))
+        .unwrap_or("")
+        .split_whitespace()
+        .map(|v| BFieldElement::new(v.parse().expect("numeric input")))
+        .collect()
+}
+
+fn within_tolerance(predicted: u64, measured: u64) -> bool {
+    let margin = (measured as f64 * RELATIVE_TOLERANCE) as u64 + ABSOLUTE_TOLERANCE;
+    predicted.abs_diff(measured) <= margin
+}
+
+#[test]
+fn cost_model_matches_triton_vm_traces() {
+    let mut report = String::new();
+    let mut failures = Vec::new();
+
+    for path in corpus() {
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        let filename = path.to_string_lossy().to_string();
+        let source = std::fs::read_to_string(&path).unwrap();
+
+        let tasm = trident::compile(&source, &filename)
+            .unwrap_or_else(|_| panic!("{} should compile", name));
+        let cost = trident::analyze_costs(&source, &filename)
+            .unwrap_or_else(|_| panic!("{} should analyze", name));
+        let program = Program::from_code(&tasm)
+            .unwrap_or_else(|e| panic!("{}: Triton VM rejects the TASM: {}", name, e));
+        let (aet, _) = VM::trace_execution(
+            program,
+            PublicInput::new(public_input(&source)),
+            NonDeterminism::default(),
+        )
+        .unwrap_or_else(|e| panic!("{}: execution failed: {}", name, e));
+
+        for (i, &(table, id, check)) in TABLES.iter().enumerate() {
+            if check == Check::Skip {
+                continue;
+            }
+            let predicted = cost.total.get(i);
+            let mut measured = aet.height_of_table(id) as u64;
+            if id == TableId::Hash {
+                // Program attestation is a cycle-count heuristic in the
+                // analyzer; compare only the rows execution adds.
+                measured -= aet.program_hash_trace.nrows() as u64;
+            }
+            let ok = match check {
+                Check::Close => within_tolerance(predicted, measured),
+                Check::Bound => predicted >= measured,
+                Check::Skip => unreachable!(),
+            };
+            report.push_str(&format!(
+                "{:<14} {:<10} predicted {:>6}  measured {:>6}{}\n",
+                name,
+                table,
+                predicted,
+                measured,
+                if ok { "" } else { "  OUT OF TOLERANCE" }
+            ));
+            if !ok {
+                failures.push(format!("{}.{}", name, table));
+            }
+        }
+
+        let measured_padded = aet.padded_height() as u64;
+        report.push_str(&format!(
+            "{:<14} {:<10} predicted {:>6}  measured {:>6}\n",
+            name, "padded", cost.padded_height, measured_padded
+        ));
+        // The lookup and cascade tables, which the analyzer does not
+        // model, set the floor: the cascade grows with every distinct limb
+        // a Tip5 permutation looks up. The prediction may only fall short.
+        if cost.padded_height > measured_padded {
+            failures.push(format!("{}.padded_height", name));
+        }
+    }
+
+    eprint!("{}", report);
+    assert!(
+        failures.is_empty(),
+        "cost model drifted from Triton VM for: {}\n{}",
+        failures.join(", "),
+        report
+    );
+}
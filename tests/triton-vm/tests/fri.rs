@@ -0,0 +1,263 @@
+//! End-to-end check of `os.neptune.fri` on Triton VM.
+//!
+//! An honest prover, written here in Rust, commits to a low-degree
+//! codeword and its foldings with Tip5 Merkle trees and answers the
+//! verifier's queries. The Trident verifier must accept its proof and
+//! reject proofs that fold to the wrong final polynomial or open a
+//! value the root does not commit to.
+//!
+//! Fiat-Shamir challenges come from Trident programs that run the
+//! verifier's own transcript steps on the VM, so the prover and the
+//! verifier agree on word order by construction.
+//!
+//! Part of the `tests/triton-vm` crate:
+//!
+//!     cargo test --manifest-path tests/triton-vm/Cargo.toml --test fri
+
+use std::path::PathBuf;
+
+use triton_vm::prelude::twenty_first::math::traits::PrimitiveRootOfUnity;
+use triton_vm::prelude::twenty_first::prelude::MerkleTree;
+use triton_vm::prelude::{
+    BFieldElement, Digest, NonDeterminism, Program, PublicInput, Tip5, XFieldElement, VM,
+};
+
+/// log2 of the first codeword's length.
+const LOG_SIZE: u32 = 5;
+/// Folding rounds, final polynomial length and queries: `verify<R, D, Q>`.
+const ROUNDS: usize = 2;
+const FINAL_LEN: usize = 2;
+const QUERIES: usize = 2;
+
+/// RAM layout the verifier and the challenge programs share.
+const LAYOUT: &str = "fri.Layout { roots: 1000, alphas: 2000, final_poly: 3000 }";
+
+fn bfe(v: u64) -> BFieldElement {
+    BFieldElement::new(v)
+}
+
+fn xfe_words(x: XFieldElement) -> [BFieldElement; 3] {
+    x.coefficients
+}
+
+/// Compile a single-file program that imports the std and os libraries.
+/// Those are found from the working directory, the crate root.
+fn compile(name: &str, source: &str) -> Program {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("fri_{}", name));
+    std::fs::create_dir_all(&dir).unwrap();
+    let entry = dir.join("main.tri");
+    std::fs::write(&entry, source).unwrap();
+    let tasm =
+        trident::compile_project(&entry).unwrap_or_else(|_| panic!("{} should compile", name));
+    Program::from_code(&tasm)
+        .unwrap_or_else(|e| panic!("{}: Triton VM rejects the TASM: {}", name, e))
+}
+
+/// Secret tokens that `io.divine5()` turns into `d`: the first token
+/// lands deepest, the digest's first word on top.
+fn digest_tokens(d: Digest) -> Vec<BFieldElement> {
+    d.values().iter().rev().copied().collect()
+}
+
+/// Leaf of a codeword value, as `fri.leaf_digest` computes it: `hash`
+/// takes its last argument from the top of the stack.
+fn leaf_digest(v: XFieldElement) -> Digest {
+    let [c0, c1, c2] = xfe_words(v);
+    let zero = bfe(0);
+    let input = [zero, zero, zero, zero, zero, zero, bfe(1), c2, c1, c0];
+    Digest::new(Tip5::hash_10(&input))
+}
+
+fn eval(coefficients: &[XFieldElement], x: BFieldElement) -> XFieldElement {
+    coefficients
+        .iter()
+        .rev()
+        .fold(XFieldElement::from(0u64), |acc, &c| acc * x + c)
+}
+
+/// The folded polynomial f'(y) = E(y) + alpha * O(y), for f(x) = E(x^2) + x * O(x^2).
+fn fold(coefficients: &[XFieldElement], alpha: XFieldElement) -> Vec<XFieldElement> {
+    coefficients
+        .chunks(2)
+        .map(|pair| pair[0] + alpha * pair[1])
+        .collect()
+}
+
+/// One committed round: the codeword over `offset * <generator>` and its tree.
+struct Round {
+    codeword: Vec<XFieldElement>,
+    tree: MerkleTree,
+}
+
+impl Round {
+    fn commit(coefficients: &[XFieldElement], log_size: u32) -> Self {
+        let n = 1usize << log_size;
+        let (offset, generator) = domain(LOG_SIZE - log_size);
+        let mut x = offset;
+        let mut codeword = Vec::with_capacity(n);
+        for _ in 0..n {
+            codeword.push(eval(coefficients, x));
+            x *= generator;
+        }
+        let leaves: Vec<Digest> = codeword.iter().map(|&v| leaf_digest(v)).collect();
+        let tree = MerkleTree::sequential_new(&leaves).unwrap();
+        Round { codeword, tree }
+    }
+
+    /// Siblings from leaf `idx` up to the root, in `merkle_step` order.
+    fn path(&self, idx: usize) -> Vec<Digest> {
+        let mut node = self.codeword.len() + idx;
+        let mut siblings = Vec::new();
+        while node > 1 {
+            siblings.push(self.tree.node(node ^ 1).unwrap());
+            node /= 2;
+        }
+        siblings
+    }
+}
+
+/// Offset and generator of the domain after `folds` foldings.
+fn domain(folds: u32) -> (BFieldElement, BFieldElement) {
+    let mut offset = BFieldElement::generator();
+    let mut generator = BFieldElement::primitive_root_of_unity(1 << LOG_SIZE).unwrap();
+    for _ in 0..folds {
+        offset = offset * offset;
+        generator = generator * generator;
+    }
+    (offset, generator)
+}
+
+/// Run the verifier's commit phase over `roots` on the VM and return
+/// the folding challenges. With `final_poly`, also absorb it and
+/// return the query indices instead.
+fn challenges(
+    roots: &[Digest],
+    final_poly: Option<&[XFieldElement]>,
+) -> (Vec<XFieldElement>, Vec<usize>) {
+    let k = roots.len();
+    let mut body = format!(
+        "    fri.commit_phase<{k}>(layout)\n    for r in 0..{k} {{\n        let a: fri.XFe = fri.load_xfe(layout.alphas + as_field(r) * 3)\n        pub_write(a.c0)\n        pub_write(a.c1)\n        pub_write(a.c2)\n    }}\n"
+    );
+    let mut secret: Vec<BFieldElement> = roots.iter().flat_map(|&r| digest_tokens(r)).collect();
+    if let Some(coefficients) = final_poly {
+        body.push_str(&format!(
+            "    fri.read_final<{}>(layout)\n    for q in 0..{} {{\n        pub_write(as_field(transcript.challenge_index(as_u32({}))))\n    }}\n",
+            coefficients.len(),
+            QUERIES,
+            LOG_SIZE
+        ));
+        secret.extend(coefficients.iter().flat_map(|&c| xfe_words(c)));
+    }
+    let source = format!(
+        "program challenges\nuse os.neptune.fri\nuse std.crypto.transcript\nfn main() {{\n    transcript.start()\n    let layout: fri.Layout = {LAYOUT}\n{body}}}\n"
+    );
+    let name = format!(
+        "challenges_{}{}",
+        k,
+        if final_poly.is_some() { "_q" } else { "" }
+    );
+    let out = VM::run(
+        compile(&name, &source),
+        PublicInput::default(),
+        NonDeterminism::new(secret),
+    )
+    .unwrap_or_else(|e| panic!("{}: execution failed: {}", name, e));
+    let alphas = out[..3 * k]
+        .chunks(3)
+        .map(|c| XFieldElement::new([c[0], c[1], c[2]]))
+        .collect();
+    let queries = out[3 * k..].iter().map(|q| q.value() as usize).collect();
+    (alphas, queries)
+}
+
+/// A proof as the verifier reads it: public input and non-determinism.
+struct Proof {
+    public: Vec<BFieldElement>,
+    secret: NonDeterminism,
+}
+
+/// How the prover deviates from the protocol.
+#[derive(Clone, Copy, PartialEq)]
+enum Prover {
+    Honest,
+    /// Sends a final polynomial the last codeword does not fold to, and
+    /// answers the queries drawn from that transcript.
+    WrongFinalPoly,
+    /// Opens a first-round value the root does not commit to.
+    WrongOpening,
+}
+
+fn prove(prover: Prover) -> Proof {
+    let mut poly: Vec<XFieldElement> = (0..FINAL_LEN << ROUNDS)
+        .map(|i| XFieldElement::new([bfe(3 * i as u64 + 1), bfe(i as u64 * i as u64), bfe(7)]))
+        .collect();
+    let mut rounds = Vec::new();
+    let mut roots = Vec::new();
+    for r in 0..ROUNDS {
+        let round = Round::commit(&poly, LOG_SIZE - r as u32);
+        roots.push(round.tree.root());
+        let (alphas, _) = challenges(&roots, None);
+        poly = fold(&poly, alphas[r]);
+        rounds.push(round);
+    }
+    if prover == Prover::WrongFinalPoly {
+        poly[0] += XFieldElement::from(1u64);
+    }
+    let (_, queries) = challenges(&roots, Some(&poly));
+
+    let mut tokens: Vec<BFieldElement> = roots.iter().flat_map(|&r| digest_tokens(r)).collect();
+    tokens.extend(poly.iter().flat_map(|&c| xfe_words(c)));
+    let mut digests = Vec::new();
+    for &query in &queries {
+        let mut idx = query;
+        for (r, round) in rounds.iter().enumerate() {
+            let half = round.codeword.len() / 2;
+            let i = idx % half;
+            let mut fx = round.codeword[i];
+            if prover == Prover::WrongOpening && r == 0 {
+                fx += XFieldElement::from(1u64);
+            }
+            tokens.extend(xfe_words(fx));
+            tokens.extend(xfe_words(round.codeword[i + half]));
+            digests.extend(round.path(i));
+            digests.extend(round.path(i + half));
+            idx = i;
+        }
+    }
+
+    let (offset, generator) = domain(0);
+    Proof {
+        public: vec![offset, generator],
+        secret: NonDeterminism::new(tokens).with_digests(digests),
+    }
+}
+
+fn verifier() -> Program {
+    let source = format!(
+        "program fri_verifier\nuse os.neptune.fri\nuse std.crypto.transcript\nfn main() {{\n    transcript.start()\n    let dom: fri.Domain = fri.Domain {{ offset: pub_read(), generator: pub_read(), log_size: as_u32({LOG_SIZE}) }}\n    let layout: fri.Layout = {LAYOUT}\n    fri.verify<{ROUNDS}, {FINAL_LEN}, {QUERIES}>(dom, layout)\n}}\n"
+    );
+    compile("verifier", &source)
+}
+
+fn verify(proof: Proof) -> Result<(), String> {
+    VM::run(verifier(), PublicInput::new(proof.public), proof.secret)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn fri_verifier_accepts_an_honest_proof() {
+    if let Err(e) = verify(prove(Prover::Honest)) {
+        panic!("honest proof rejected: {}", e);
+    }
+}
+
+#[test]
+fn fri_verifier_rejects_a_wrong_final_polynomial() {
+    assert!(verify(prove(Prover::WrongFinalPoly)).is_err());
+}
+
+#[test]
+fn fri_verifier_rejects_an_opening_the_root_does_not_commit_to() {
+    assert!(verify(prove(Prover::WrongOpening)).is_err());
+}
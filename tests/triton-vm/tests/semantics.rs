@@ -0,0 +1,113 @@
+//! Instruction semantics on Triton VM.
+//!
+//! The debugger emulates TASM with the same operand conventions the
+//! lowering assumes, so a lowering that disagrees with the real ISA
+//! passes every emulated test. These programs run on Triton VM itself
+//! and check their output.
+//!
+//! Part of the `tests/triton-vm` crate:
+//!
+//!     cargo test --manifest-path tests/triton-vm/Cargo.toml --test semantics
+
+use std::path::PathBuf;
+
+use triton_vm::prelude::twenty_first::prelude::Sponge;
+use triton_vm::prelude::{BFieldElement, NonDeterminism, Program, PublicInput, Tip5, VM};
+
+/// Compile `source` as a single-file program and run it on Triton VM
+/// with `public` input; returns the public output.
+fn run(name: &str, source: &str, public: &[u64]) -> Vec<u64> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("semantics_{}", name));
+    std::fs::create_dir_all(&dir).unwrap();
+    let entry = dir.join("main.tri");
+    std::fs::write(&entry, source).unwrap();
+    let tasm =
+        trident::compile_project(&entry).unwrap_or_else(|_| panic!("{} should compile", name));
+    let program = Program::from_code(&tasm)
+        .unwrap_or_else(|e| panic!("{}: Triton VM rejects the TASM: {}", name, e));
+    let input = PublicInput::new(public.iter().copied().map(BFieldElement::new).collect());
+    VM::run(program, input, NonDeterminism::default())
+        .unwrap_or_else(|e| panic!("{}: execution failed: {}", name, e))
+        .iter()
+        .map(|v| v.value())
+        .collect()
+}
+
+#[test]
+fn u32_operators_take_their_operands_in_source_order() {
+    let source = "program u32_order\nfn main() {\n    let a: U32 = as_u32(pub_read())\n    let b: U32 = as_u32(pub_read())\n    pub_write(if a < b { 1 } else { 0 })\n    pub_write(if b < a { 1 } else { 0 })\n    let (q, r) = b /% a\n    pub_write(as_field(q))\n    pub_write(as_field(r))\n    pub_write(as_field(pow(a, as_u32(2))))\n}\n";
+    assert_eq!(run("u32_order", source, &[3, 11]), [1, 0, 3, 2, 9]);
+}
+
+#[test]
+fn ram_writes_land_at_their_address_and_blocks_read_back_in_order() {
+    let source = "program ram_order\nsec ram: { 100: Field, 200: Digest }\nfn main() {\n    ram_write(100, pub_read())\n    ram_write_block(200, pub_read5())\n    let d: Digest = ram_read_block(200)\n    let (a, b, c, e, f) = d\n    pub_write(ram_read(100))\n    pub_write(ram_read(200))\n    pub_write(a * 10000 + b * 1000 + c * 100 + e * 10 + f)\n}\n";
+    assert_eq!(run("ram_order", source, &[7, 1, 2, 3, 4, 5]), [7, 5, 12345]);
+}
+
+/// `d[0]` and `d[9]` of `sponge_squeeze()` after absorbing each of
+/// `messages` as packed `sponge_absorb_field` runs frame them: the
+/// fields, a 1, then zeros up to the rate. `sponge_absorb` takes its
+/// first rate word from the top of the stack, where the last pushed
+/// word of a block sits; `sponge_squeeze` leaves its first word on top,
+/// which is the array's last element.
+fn packed_digest(messages: &[&[u64]]) -> Vec<u64> {
+    let mut sponge = Tip5::init();
+    for message in messages {
+        let mut words: Vec<BFieldElement> =
+            message.iter().copied().map(BFieldElement::new).collect();
+        words.push(BFieldElement::new(1));
+        words.resize(
+            words.len().div_ceil(Tip5::RATE) * Tip5::RATE,
+            BFieldElement::new(0),
+        );
+        for block in words.chunks(Tip5::RATE) {
+            let mut block: [BFieldElement; Tip5::RATE] = block.try_into().unwrap();
+            block.reverse();
+            sponge.absorb(block);
+        }
+    }
+    let squeezed = sponge.squeeze();
+    vec![squeezed[Tip5::RATE - 1].value(), squeezed[0].value()]
+}
+
+#[test]
+fn packed_field_absorbs_hash_each_run_as_a_framed_message() {
+    let program = |name: &str, body: &str| {
+        format!(
+            "program {name}\nfn main() {{\n    sponge_init()\n{body}    let d: [Field; 10] = sponge_squeeze()\n    pub_write(d[0])\n    pub_write(d[9])\n}}\n"
+        )
+    };
+    let absorb = "    sponge_absorb_field(pub_read())\n";
+    let one = run("absorb_one", &program("absorb_one", absorb), &[7]);
+    assert_eq!(one, packed_digest(&[&[7]]));
+
+    // A trailing zero is a different message, not padding.
+    let two = run(
+        "absorb_two",
+        &program("absorb_two", &absorb.repeat(2)),
+        &[7, 0],
+    );
+    assert_eq!(two, packed_digest(&[&[7, 0]]));
+    assert_ne!(one, two);
+
+    // Twelve fields span two blocks; ten need a block for the 1 alone.
+    let inputs: Vec<u64> = (1..=12).collect();
+    let twelve = run(
+        "absorb_twelve",
+        &program("absorb_twelve", &absorb.repeat(12)),
+        &inputs,
+    );
+    assert_eq!(twelve, packed_digest(&[&inputs]));
+    let ten = run(
+        "absorb_ten",
+        &program("absorb_ten", &absorb.repeat(10)),
+        &inputs[..10],
+    );
+    assert_eq!(ten, packed_digest(&[&inputs[..10]]));
+
+    // Another statement between two calls makes two messages.
+    let split = format!("{absorb}    pub_write(0)\n{absorb}");
+    let split = run("absorb_split", &program("absorb_split", &split), &[7, 8]);
+    assert_eq!(split[1..], packed_digest(&[&[7], &[8]]));
+}
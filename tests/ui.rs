@@ -0,0 +1,92 @@
+//! Golden-file tests for rendered diagnostics.
+//!
+//! Each `tests/ui/<name>.tri` is type-checked and its diagnostics, errors
+//! and warnings alike, are rendered without colors and compared with
+//! `tests/ui/<name>.stderr`. A missing `.stderr` means "no diagnostics".
+//! After an intended message change, regenerate the golden files with:
+//!
+//!     BLESS=1 cargo test --test ui
+//!
+//! and review the `.stderr` diff like any other change.
+
+use std::path::{Path, PathBuf};
+
+fn ui_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/ui")
+}
+
+fn ui_cases() -> Vec<PathBuf> {
+    let mut cases: Vec<PathBuf> = std::fs::read_dir(ui_dir())
+        .expect("read tests/ui")
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "tri"))
+        .collect();
+    cases.sort();
+    cases
+}
+
+/// Render every diagnostic for `path` the way the golden file stores it.
+fn render(path: &Path) -> String {
+    let source = std::fs::read_to_string(path).expect("read ui case");
+    let name = format!("tests/ui/{}", path.file_name().unwrap().to_string_lossy());
+    let diagnostics = trident::collect_diagnostics(&source, &name);
+    trident::diagnostic::render_diagnostics_to_string(&diagnostics, &name, &source)
+}
+
+/// Line-by-line diff, enough to show which lines of a golden file moved.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut out = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => {}
+            (e, a) => {
+                if let Some(e) = e {
+                    out.push_str(&format!("{:>4} - {}\n", i + 1, e));
+                }
+                if let Some(a) = a {
+                    out.push_str(&format!("{:>4} + {}\n", i + 1, a));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn test_ui_diagnostics_match_golden_files() {
+    let bless = std::env::var_os("BLESS").is_some_and(|v| v != "0");
+    let cases = ui_cases();
+    assert!(!cases.is_empty(), "no tests/ui/*.tri cases");
+
+    let mut failures = Vec::new();
+    for case in &cases {
+        let actual = render(case);
+        let golden = case.with_extension("stderr");
+        if bless {
+            if actual.is_empty() {
+                let _ = std::fs::remove_file(&golden);
+            } else {
+                std::fs::write(&golden, &actual).expect("write golden file");
+            }
+            continue;
+        }
+        let expected = std::fs::read_to_string(&golden).unwrap_or_default();
+        if expected != actual {
+            failures.push(format!(
+                "{}:\n{}",
+                golden.display(),
+                diff(&expected, &actual)
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} ui cases changed (rerun with BLESS=1 to accept):\n\n{}",
+        failures.len(),
+        cases.len(),
+        failures.join("\n")
+    );
+}